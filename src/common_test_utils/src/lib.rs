@@ -35,6 +35,12 @@ pub const SLIPWAY_CONTEXT_COMPONENT_NAME: &str = "slipwayhq.context.0.0.1";
 pub const SLIPWAY_CONTEXT_COMPONENT_TAR_NAME: &str = "slipwayhq.context.0.0.1.tar";
 pub const SLIPWAY_CONTEXT_JS_COMPONENT_TAR_NAME: &str = "slipwayhq.context_js.0.0.1.tar";
 
+pub const SLIPWAY_STREAMING_COMPONENT_TAR_NAME: &str = "slipwayhq.streaming.0.0.1.tar";
+
+pub const SLIPWAY_TYPED_COMPONENT_TAR_NAME: &str = "slipwayhq.typed.0.0.1.tar";
+
+pub const SLIPWAY_BATCH_CALLER_COMPONENT_TAR_NAME: &str = "slipwayhq.batch_caller.0.0.1.tar";
+
 pub const SLIPWAY_INCREMENT_JSON_SCHEMA_COMPONENT_NAME: &str =
     "slipwayhq.increment_json_schema.0.0.1";
 pub const SLIPWAY_INCREMENT_JSON_SCHEMA_COMPONENT_FOLDER_NAME: &str =
@@ -47,10 +53,16 @@ pub const SLIPWAY_INCREMENT_TEN_COMPONENT_TAR_NAME: &str = "slipwayhq.increment_
 
 pub const SLIPWAY_FRAGMENT_COMPONENT_NAME: &str = "slipwayhq.fragment.0.0.1";
 pub const SLIPWAY_FRAGMENT_COMPONENT_TAR_NAME: &str = "slipwayhq.fragment.0.0.1.tar";
+pub const SLIPWAY_FRAGMENT_NAMED_INPUTS_COMPONENT_TAR_NAME: &str =
+    "slipwayhq.fragment_named_inputs.0.0.1.tar";
+pub const SLIPWAY_FRAGMENT_CUSTOM_HANDLES_COMPONENT_TAR_NAME: &str =
+    "slipwayhq.fragment_custom_handles.0.0.1.tar";
 
 pub const SLIPWAY_RIGGING_JS_COMPONENT_NAME: &str = "slipwayhq.rigging_js.0.0.1";
 pub const SLIPWAY_RIGGING_JS_COMPONENT_TAR_NAME: &str = "slipwayhq.rigging_js.0.0.1.tar";
 
+pub const SLIPWAY_SPIN_JS_COMPONENT_TAR_NAME: &str = "slipwayhq.spin_js.0.0.1.tar";
+
 pub fn get_slipway_test_components_path() -> PathBuf {
     find_ancestor_path(PathBuf::from(SLIPWAY_TEST_COMPONENTS_PATH))
 }
@@ -0,0 +1,260 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use slipway_engine::{BasicComponentCache, BasicComponentsLoader, Rig, SlipwayReference};
+use tar::{Archive, Builder};
+use tempfile::TempDir;
+use tracing::info;
+use walkdir::WalkDir;
+
+use crate::{registry_auth::RegistryAuth, utils::append_bytes};
+
+pub(super) const BUNDLE_EXTENSION: &str = "bundle";
+const BUNDLE_MANIFEST_FILE_NAME: &str = "bundle.json";
+const BUNDLE_COMPONENTS_FOLDER_NAME: &str = "components";
+
+/// The manifest at the root of a bundle, describing the Rig and where
+/// each of its Components can be found within the bundle.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BundleManifest {
+    rig: Rig,
+    components: Vec<BundledComponent>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BundledComponent {
+    reference: SlipwayReference,
+    path: PathBuf,
+}
+
+/// A bundle which has been extracted to a temporary folder.
+/// The folder is deleted when this is dropped.
+pub(super) struct ExtractedBundle {
+    pub rig: Rig,
+    pub components_loader: BasicComponentsLoader,
+    _directory: TempDir,
+}
+
+/// Writes the Rig and all the Components it uses, including nested Components,
+/// to a single file which can be run without access to any registry.
+pub(super) async fn bundle_rig(
+    rig_path: &Path,
+    output_path: Option<PathBuf>,
+    registry_urls: Vec<String>,
+    registry_auth: Vec<RegistryAuth>,
+    allow_non_http_git_urls: bool,
+    proxy: Option<String>,
+) -> anyhow::Result<()> {
+    let file_contents = tokio::fs::read_to_string(rig_path)
+        .await
+        .with_context(|| format!("Failed to read rig from {}", rig_path.display()))?;
+    let rig = crate::utils::parse_rig_file(rig_path, &file_contents)?;
+
+    let components_loader = crate::utils::create_components_loader(
+        registry_urls,
+        registry_auth,
+        allow_non_http_git_urls,
+        proxy,
+        rig_path.parent(),
+    )?;
+    let component_cache = BasicComponentCache::primed(&rig, &components_loader).await?;
+
+    let mut components: Vec<_> = component_cache
+        .into_inner()
+        .into_iter()
+        .filter(|(reference, _)| !matches!(reference, SlipwayReference::Special(_)))
+        .collect();
+
+    // Sort so that bundles are deterministic.
+    components.sort_by_key(|(reference, _)| reference.to_string());
+
+    let output_path = output_path.unwrap_or_else(|| rig_path.with_extension(BUNDLE_EXTENSION));
+
+    let output_file = File::create(&output_path)
+        .with_context(|| format!("Failed to create bundle file {}", output_path.display()))?;
+    let mut tar_builder = Builder::new(output_file);
+
+    let mut bundled_components = Vec::new();
+    for (index, (reference, component)) in components.into_iter().enumerate() {
+        let component_path = component.files.get_component_path();
+        let bundled_path = Path::new(BUNDLE_COMPONENTS_FOLDER_NAME).join(format!("{index}.tar"));
+
+        if component_path.is_dir() {
+            let component_tar = create_tar_from_directory(component_path)?;
+            append_bytes(&mut tar_builder, &bundled_path, &component_tar)?;
+        } else {
+            tar_builder
+                .append_path_with_name(component_path, &bundled_path)
+                .with_context(|| {
+                    format!(
+                        "Failed to add component {} from {}",
+                        reference,
+                        component_path.display()
+                    )
+                })?;
+        }
+
+        bundled_components.push(BundledComponent {
+            reference,
+            path: bundled_path,
+        });
+    }
+
+    let manifest = BundleManifest {
+        rig,
+        components: bundled_components,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    append_bytes(
+        &mut tar_builder,
+        Path::new(BUNDLE_MANIFEST_FILE_NAME),
+        &manifest_json,
+    )?;
+
+    tar_builder.finish()?;
+
+    info!("Written rig bundle to: {}", output_path.display());
+
+    Ok(())
+}
+
+/// Extracts the bundle to a temporary folder, and returns the Rig along with a
+/// Components loader which loads every Component from the bundle.
+pub(super) fn extract_bundle(bundle_path: &Path) -> anyhow::Result<ExtractedBundle> {
+    let bundle_file = File::open(bundle_path)
+        .with_context(|| format!("Failed to open bundle {}", bundle_path.display()))?;
+
+    let directory = tempfile::tempdir()?;
+    Archive::new(bundle_file)
+        .unpack(directory.path())
+        .with_context(|| format!("Failed to extract bundle {}", bundle_path.display()))?;
+
+    let manifest_path = directory.path().join(BUNDLE_MANIFEST_FILE_NAME);
+    let manifest_file = File::open(&manifest_path)
+        .with_context(|| format!("Bundle {} has no manifest", bundle_path.display()))?;
+    let manifest: BundleManifest = serde_json::from_reader(manifest_file).with_context(|| {
+        format!(
+            "Failed to parse manifest of bundle {}",
+            bundle_path.display()
+        )
+    })?;
+
+    let mut builder = BasicComponentsLoader::builder().without_default_registry();
+    for component in manifest.components {
+        builder =
+            builder.component_path(component.reference, &directory.path().join(component.path));
+    }
+
+    Ok(ExtractedBundle {
+        rig: manifest.rig,
+        components_loader: builder.build(),
+        _directory: directory,
+    })
+}
+
+pub(super) fn is_bundle(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == BUNDLE_EXTENSION)
+}
+
+fn create_tar_from_directory(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let mut tar_builder = Builder::new(Vec::new());
+
+    for entry in WalkDir::new(path) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            let rel_path = entry.path().strip_prefix(path)?;
+            if let Some(definition) =
+                crate::utils::try_convert_yaml_component_definition(rel_path, entry.path())?
+            {
+                append_bytes(
+                    &mut tar_builder,
+                    Path::new(crate::SLIPWAY_COMPONENT_FILE_NAME),
+                    &definition,
+                )?;
+                continue;
+            }
+            tar_builder.append_path_with_name(entry.path(), rel_path)?;
+        }
+    }
+
+    Ok(tar_builder.into_inner()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use common_macros::slipway_test_async;
+    use slipway_engine::ComponentsLoader;
+
+    use super::*;
+
+    #[slipway_test_async]
+    async fn it_should_bundle_and_extract_rig() {
+        let directory = tempfile::tempdir().unwrap();
+
+        let component_directory = directory.path().join("my_component");
+        std::fs::create_dir(&component_directory).unwrap();
+        std::fs::write(
+            component_directory.join(crate::SLIPWAY_COMPONENT_FILE_NAME),
+            serde_json::to_string(&serde_json::json!({
+                "publisher": "test",
+                "name": "my_component",
+                "version": "1.0.0",
+                "input": {},
+                "output": {}
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+
+        let rig_path = directory.path().join("rig.json");
+        std::fs::write(
+            &rig_path,
+            serde_json::to_string(&serde_json::json!({
+                "rigging": {
+                    "a": {
+                        "component": "file:my_component",
+                        "input": {}
+                    },
+                    "b": {
+                        "component": "passthrough",
+                        "input": {}
+                    }
+                }
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+
+        bundle_rig(&rig_path, None, vec![], vec![], false, None)
+            .await
+            .unwrap();
+
+        // Remove the component so that it can only be loaded from the bundle.
+        std::fs::remove_dir_all(&component_directory).unwrap();
+
+        let bundle_path = directory.path().join("rig.bundle");
+        assert!(is_bundle(&bundle_path));
+
+        let bundle = extract_bundle(&bundle_path).unwrap();
+        assert_eq!(bundle.rig.rigging.components.len(), 2);
+
+        let reference = SlipwayReference::from_str("file:my_component").unwrap();
+        let result = bundle
+            .components_loader
+            .load_components(std::slice::from_ref(&reference))
+            .await;
+
+        let loaded = result.into_iter().next().unwrap().unwrap();
+        assert_eq!(loaded.reference, reference);
+        assert!(loaded.definition.contains("my_component"));
+    }
+}
@@ -24,4 +24,17 @@ pub enum CanvasError {
         handle: ComponentHandle,
         error: String, // Using String so we don't expose the ViuError
     },
+
+    #[error("Component {handle} SVG invalid: {message}")]
+    InvalidSvg {
+        handle: ComponentHandle,
+        message: String,
+    },
+
+    #[error("Component {handle} SVG output could not be saved to {path}\n{error}")]
+    SvgSaveFailed {
+        handle: ComponentHandle,
+        path: PathBuf,
+        error: std::io::Error,
+    },
 }
@@ -1,4 +1,5 @@
 mod errors;
+mod svg;
 
 use std::path::Path;
 
@@ -7,6 +8,7 @@ use image::{DynamicImage, ImageBuffer, RgbaImage};
 use slipway_engine::ComponentHandle;
 
 pub use errors::CanvasError;
+pub(super) use svg::{get_svg, save_svg_if_exists};
 
 pub(super) fn get_canvas_image<'rig>(
     handle: &'rig ComponentHandle,
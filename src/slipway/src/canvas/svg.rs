@@ -0,0 +1,67 @@
+use std::path::Path;
+
+use slipway_engine::ComponentHandle;
+
+use super::CanvasError;
+
+/// Returns the SVG markup in the output's `svg` field.
+pub(crate) fn get_svg<'output>(
+    handle: &ComponentHandle,
+    output: &'output serde_json::Value,
+) -> Result<&'output str, CanvasError> {
+    let Some(svg) = output.get("svg") else {
+        return Err(CanvasError::InvalidSvg {
+            handle: handle.clone(),
+            message: "Output has no svg field".to_string(),
+        });
+    };
+
+    svg.as_str().ok_or_else(|| CanvasError::InvalidSvg {
+        handle: handle.clone(),
+        message: "SVG field is not a string".to_string(),
+    })
+}
+
+/// Saves the output's SVG markup to the save path, or otherwise returns it so it can be
+/// written to the terminal. Returns `None` if the output contains no SVG.
+pub(crate) fn save_svg_if_exists<'output>(
+    handle: &ComponentHandle,
+    output: &'output serde_json::Value,
+    save_path: Option<&Path>,
+) -> Result<Option<&'output str>, CanvasError> {
+    let Ok(svg) = get_svg(handle, output) else {
+        return Ok(None);
+    };
+
+    if let Some(save_path) = save_path {
+        std::fs::write(save_path, svg).map_err(|error| CanvasError::SvgSaveFailed {
+            handle: handle.clone(),
+            path: save_path.to_path_buf(),
+            error,
+        })?;
+    }
+
+    Ok(Some(svg))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn it_should_get_svg_markup() {
+        let handle = ComponentHandle::from_str("chart").unwrap();
+        let output = json!({ "svg": "<svg xmlns=\"http://www.w3.org/2000/svg\"/>" });
+
+        assert_eq!(
+            get_svg(&handle, &output).unwrap(),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\"/>"
+        );
+        assert!(get_svg(&handle, &json!({ "svg": 5 })).is_err());
+        assert!(get_svg(&handle, &json!({ "canvas": {} })).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use std::{
+    io::Write,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Local};
+use slipway_engine::{
+    CachedComponent, list_cached_components, remove_cached_component, remove_unused_cached_content,
+};
+use slipway_host::render_state::{ByteUnits, format_bytes};
+
+/// Lists the components in the components cache, and removes any which have not
+/// been used within the `prune_older_than` duration, if specified.
+pub(crate) fn list_component_cache<W: Write>(
+    w: &mut W,
+    cache_path: &Path,
+    prune_older_than: Option<Duration>,
+) -> anyhow::Result<()> {
+    let components = list_cached_components(cache_path)?;
+
+    writeln!(w, "Component cache: {}", cache_path.display())?;
+
+    for component in components.iter() {
+        write_component(w, component)?;
+    }
+
+    let total_bytes = components.iter().map(|c| c.size_bytes).sum::<u64>();
+    writeln!(
+        w,
+        "Total: {} component(s), {}",
+        components.len(),
+        format_size(total_bytes)
+    )?;
+
+    if let Some(older_than) = prune_older_than {
+        let now = SystemTime::now();
+        let stale_components = components
+            .iter()
+            .filter(|c| is_stale(c, now, older_than))
+            .collect::<Vec<_>>();
+
+        for component in stale_components.iter() {
+            remove_cached_component(component)?;
+        }
+
+        writeln!(
+            w,
+            "Pruned: {} component(s), {}",
+            stale_components.len(),
+            format_size(stale_components.iter().map(|c| c.size_bytes).sum())
+        )?;
+
+        // Files shared between components are only removed once no component uses them.
+        let removed_content_bytes = remove_unused_cached_content(cache_path)?;
+        writeln!(
+            w,
+            "Removed unused shared files: {}",
+            format_size(removed_content_bytes)
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_component<W: Write>(w: &mut W, component: &CachedComponent) -> std::io::Result<()> {
+    let name = match component.reference.as_deref() {
+        Some(reference) => reference.to_string(),
+        None => component
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+    };
+
+    let last_used: DateTime<Local> = component.last_used.into();
+
+    writeln!(
+        w,
+        "  {name}  {}  last used {}",
+        format_size(component.size_bytes),
+        last_used.format("%Y-%m-%d %H:%M")
+    )
+}
+
+fn is_stale(component: &CachedComponent, now: SystemTime, older_than: Duration) -> bool {
+    now.duration_since(component.last_used)
+        .is_ok_and(|age| age > older_than)
+}
+
+fn format_size(bytes: u64) -> String {
+    format_bytes(bytes as usize, ByteUnits::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn cached_component(age: Duration) -> CachedComponent {
+        CachedComponent {
+            path: PathBuf::from("/cache/a.tar"),
+            reference: Some("p.n.1.0.0".to_string()),
+            size_bytes: 2048,
+            last_used: SystemTime::now() - age,
+        }
+    }
+
+    #[test]
+    fn it_should_only_prune_components_older_than_duration() {
+        let now = SystemTime::now();
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        assert!(is_stale(&cached_component(day * 31), now, day * 30));
+        assert!(!is_stale(&cached_component(day * 29), now, day * 30));
+    }
+
+    #[test]
+    fn it_should_write_component_summary() {
+        let mut output = Vec::new();
+        write_component(&mut output, &cached_component(Duration::ZERO)).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("  p.n.1.0.0  2 kb  last used "));
+    }
+
+    #[test]
+    fn it_should_list_and_prune_empty_cache() {
+        let cache = tempfile::tempdir().unwrap();
+
+        let mut output = Vec::new();
+        list_component_cache(&mut output, cache.path(), Some(Duration::ZERO)).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Total: 0 component(s), 0 bytes\n"));
+        assert!(output.contains("Pruned: 0 component(s), 0 bytes\n"));
+        assert!(output.contains("Removed unused shared files: 0 bytes\n"));
+    }
+}
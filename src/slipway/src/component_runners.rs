@@ -1,4 +1,5 @@
 use slipway_engine::ComponentRunner;
+use slipway_wasmtime_runner::{WasmComponentRunner, WasmComponentRunnerOptions};
 
 /// Returns the list of component runners in the order they will be tried.
 /// The order is important because if multiple runners can run a component
@@ -7,10 +8,28 @@ use slipway_engine::ComponentRunner;
 /// Changing the order of the runners will potentially break components which
 /// rely on the order of execution, so should not be done lightly.
 pub fn get_component_runners() -> Vec<Box<dyn ComponentRunner>> {
+    get_component_runners_with_wasm_options(WasmComponentRunnerOptions::default())
+}
+
+/// Returns the list of component runners, with the WASM runner using the given options.
+pub fn get_component_runners_with_wasm_options(
+    wasm_options: WasmComponentRunnerOptions,
+) -> Vec<Box<dyn ComponentRunner>> {
+    get_component_runners_with_wasm_runner(
+        WasmComponentRunner::try_new_with_options(wasm_options)
+            .expect("Should be able to create Wasmtime engine with no target"),
+    )
+}
+
+/// Returns the list of component runners, using the given WASM runner, so that
+/// components it has already compiled are reused.
+pub fn get_component_runners_with_wasm_runner(
+    wasm_runner: WasmComponentRunner,
+) -> Vec<Box<dyn ComponentRunner>> {
     vec![
         Box::new(slipway_engine::SpecialComponentRunner {}),
         Box::new(slipway_js_boa_runner::BoaComponentRunner {}),
-        Box::new(slipway_wasmtime_runner::WasmComponentRunner::new()),
+        Box::new(wasm_runner),
         Box::new(slipway_fragment_runner::FragmentComponentRunner {}),
     ]
 }
@@ -0,0 +1,173 @@
+use std::fmt::Debug;
+
+use slipway_engine::ComponentHandle;
+use tracing::{
+    Level, Metadata, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id},
+};
+use tracing_subscriber::{
+    Layer,
+    layer::Context,
+    registry::{LookupSpan, SpanRef},
+};
+
+/// The name of the span the engine creates around each Component run.
+const COMPONENT_SPAN_NAME: &str = "component";
+
+/// Marks a Component span, and therefore everything logged within it,
+/// as being traced.
+struct TracedComponent;
+
+/// Marks the spans of the traced Components as they are created, so that
+/// [`is_enabled`] can find them when filtering events logged within them.
+pub(super) struct TracedComponentsLayer {
+    handles: Vec<ComponentHandle>,
+}
+
+impl TracedComponentsLayer {
+    pub fn new(handles: Vec<ComponentHandle>) -> Self {
+        TracedComponentsLayer { handles }
+    }
+
+    fn is_traced(&self, span_handle: &str) -> bool {
+        // The span's value is the handle, optionally followed by a `:` and the
+        // Component's reference.
+        let span_handle = span_handle
+            .split_once(':')
+            .map_or(span_handle, |(handle, _)| handle);
+
+        self.handles.iter().any(|h| h.0 == span_handle)
+    }
+}
+
+impl<S> Layer<S> for TracedComponentsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != COMPONENT_SPAN_NAME {
+            return;
+        }
+
+        let mut visitor = SpanHandleVisitor::default();
+        attrs.record(&mut visitor);
+
+        if let Some(span_handle) = visitor.0
+            && self.is_traced(&span_handle)
+            && let Some(span) = ctx.span(id)
+        {
+            span.extensions_mut().insert(TracedComponent);
+        }
+    }
+}
+
+/// Records the first field of a Component span, which holds the Component's handle.
+#[derive(Default)]
+struct SpanHandleVisitor(Option<String>);
+
+impl Visit for SpanHandleVisitor {
+    fn record_debug(&mut self, _field: &Field, value: &dyn Debug) {
+        if self.0.is_none() {
+            self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+/// Returns true if the span or event should be logged, either because it is within
+/// the global log level, or because it was logged within a traced Component.
+pub(super) fn is_enabled<S>(metadata: &Metadata<'_>, ctx: &Context<'_, S>, log_level: Level) -> bool
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    if *metadata.level() <= log_level {
+        return true;
+    }
+
+    // Component spans must always be enabled so that events within traced Components
+    // can find them, even when the global log level is below the span's level.
+    if metadata.is_span() && metadata.name() == COMPONENT_SPAN_NAME {
+        return true;
+    }
+
+    ctx.lookup_current()
+        .is_some_and(|span| is_within_traced_component(&span))
+}
+
+fn is_within_traced_component<S>(span: &SpanRef<'_, S>) -> bool
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    span.scope()
+        .any(|s| s.extensions().get::<TracedComponent>().is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        str::FromStr,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::{debug, info_span, trace};
+    use tracing_subscriber::{filter::DynFilterFn, fmt::MakeWriter, layer::SubscriberExt};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for SharedWriter {
+        type Writer = SharedWriter;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn it_should_only_trace_the_requested_components() {
+        let writer = SharedWriter::default();
+
+        let subscriber = tracing_subscriber::registry()
+            .with(TracedComponentsLayer::new(vec![
+                ComponentHandle::from_str("traced").unwrap(),
+            ]))
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(writer.clone())
+                    .with_filter(DynFilterFn::new(|metadata, ctx| {
+                        is_enabled(metadata, ctx, Level::WARN)
+                    })),
+            );
+
+        tracing::subscriber::with_default(subscriber, || {
+            info_span!("component", ""=%"traced:acme.weather").in_scope(|| {
+                trace!("traced component message");
+                info_span!("nested").in_scope(|| debug!("nested traced message"));
+            });
+            info_span!("component", ""=%"other:acme.weather").in_scope(|| {
+                trace!("other component message");
+            });
+            debug!("global message");
+        });
+
+        let output = String::from_utf8(writer.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("traced component message"));
+        assert!(output.contains("nested traced message"));
+        assert!(!output.contains("other component message"));
+        assert!(!output.contains("global message"));
+    }
+}
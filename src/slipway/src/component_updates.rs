@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use futures::future::join_all;
+use semver::Version;
+use slipway_engine::{
+    BasicComponentCache, BasicComponentsLoader, ComponentHttpClient, ProcessedUrl,
+    SlipwayReference, process_url_str, resolve_registry_lookup_url,
+};
+use tracing::debug;
+
+/// Kept short so that an unresponsive registry delays the run as little as possible.
+const REGISTRY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const VERSION_PLACEHOLDER: &str = "{version}";
+
+/// Checks the registries for newer versions of each registry component in the cache,
+/// and prints a warning for each component which is behind.
+///
+/// Registries are queried using the components loader's HTTP client, so the
+/// configured proxy, headers and registry credentials apply.
+///
+/// This is advisory only, so registries which can't be reached are ignored and
+/// the warnings don't fail the run when warnings are denied.
+pub(crate) async fn warn_of_component_updates(
+    component_cache: &BasicComponentCache,
+    components_loader: &BasicComponentsLoader,
+) {
+    let client = components_loader.http_client();
+    let registry_lookup_urls = components_loader.registry_lookup_urls();
+    let futures = component_cache
+        .iter()
+        .filter_map(|(reference, _)| match reference {
+            SlipwayReference::Registry {
+                publisher,
+                name,
+                version,
+            } => Some(async move {
+                let newer_version =
+                    find_newer_version(client, registry_lookup_urls, publisher, name, version)
+                        .await;
+                (reference, newer_version)
+            }),
+            _ => None,
+        });
+
+    for (reference, newer_version) in join_all(futures).await {
+        if let Some(newer_version) = newer_version {
+            eprintln!(
+                "Warning: Component \"{reference}\" is out of date. Version {newer_version} is available."
+            );
+        }
+    }
+}
+
+/// Returns the newest version of the component found in any of the registries,
+/// if it is newer than the given version.
+///
+/// Registries only resolve individual versions, and have no endpoint for listing
+/// the versions of a component, so only the next major, minor and patch versions
+/// are probed. A newer version is therefore missed if the next version in its
+/// series was never published, for example `1.2.0` to `1.4.0` is found by probing
+/// `1.3.0` only if `1.3.0` exists.
+async fn find_newer_version(
+    client: &ComponentHttpClient,
+    registry_lookup_urls: &[String],
+    publisher: &str,
+    name: &str,
+    version: &Version,
+) -> Option<Version> {
+    // Registries which don't include the version in their URLs, such as components
+    // directories, only ever contain a single version of each component.
+    let versioned_registry_lookup_urls: Vec<&String> = registry_lookup_urls
+        .iter()
+        .filter(|url| url.contains(VERSION_PLACEHOLDER))
+        .collect();
+
+    for candidate in newer_candidate_versions(version) {
+        for registry_lookup_url in versioned_registry_lookup_urls.iter() {
+            let url = resolve_registry_lookup_url(registry_lookup_url, publisher, name, &candidate);
+            if exists_in_registry(client, &url).await {
+                return Some(candidate);
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns the next major, minor and patch versions, newest first, followed by
+/// the release version if the given version is a pre-release.
+fn newer_candidate_versions(version: &Version) -> Vec<Version> {
+    let mut candidates = vec![
+        Version::new(version.major + 1, 0, 0),
+        Version::new(version.major, version.minor + 1, 0),
+        Version::new(version.major, version.minor, version.patch + 1),
+    ];
+
+    if !version.pre.is_empty() {
+        candidates.push(Version::new(version.major, version.minor, version.patch));
+    }
+
+    candidates
+}
+
+async fn exists_in_registry(client: &ComponentHttpClient, url: &str) -> bool {
+    match process_url_str(url) {
+        Ok(ProcessedUrl::Http(url)) => {
+            let request = match client.request(reqwest::Method::HEAD, &url) {
+                Ok(request) => request,
+                Err(e) => {
+                    debug!("Failed to create HTTP client to check for component updates: {e}");
+                    return false;
+                }
+            };
+
+            match request.timeout(REGISTRY_TIMEOUT).send().await {
+                Ok(response) => response.status().is_success(),
+                Err(e) => {
+                    debug!("Failed to check for component at \"{url}\": {e}");
+                    false
+                }
+            }
+        }
+        Ok(ProcessedUrl::AbsolutePath(path)) | Ok(ProcessedUrl::RelativePath(path)) => {
+            path.exists()
+        }
+        Ok(ProcessedUrl::Other(_)) | Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+
+    use super::*;
+
+    #[test]
+    fn it_should_return_newer_candidate_versions() {
+        assert_eq!(
+            newer_candidate_versions(&Version::parse("1.2.3").unwrap()),
+            vec![
+                Version::parse("2.0.0").unwrap(),
+                Version::parse("1.3.0").unwrap(),
+                Version::parse("1.2.4").unwrap(),
+            ]
+        );
+
+        assert_eq!(
+            newer_candidate_versions(&Version::parse("1.2.3-beta").unwrap()).last(),
+            Some(&Version::parse("1.2.3").unwrap())
+        );
+    }
+
+    #[slipway_test_async]
+    async fn it_should_find_newer_version_in_file_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("acme.weather")).unwrap();
+        std::fs::write(dir.path().join("acme.weather.1.0.0.tar"), "").unwrap();
+        std::fs::write(dir.path().join("acme.weather.1.1.0.tar"), "").unwrap();
+
+        let registry_lookup_urls = vec![
+            // Registries without a version must not be reported as having newer versions.
+            format!("file://{}/{{publisher}}.{{name}}", dir.path().display()),
+            format!(
+                "file://{}/{{publisher}}.{{name}}.{{version}}.tar",
+                dir.path().display()
+            ),
+        ];
+
+        let components_loader = BasicComponentsLoader::builder()
+            .without_default_registry()
+            .components_cache_path(dir.path())
+            .build();
+        let client = components_loader.http_client();
+
+        assert_eq!(
+            find_newer_version(
+                client,
+                &registry_lookup_urls,
+                "acme",
+                "weather",
+                &Version::parse("1.0.0").unwrap()
+            )
+            .await,
+            Some(Version::parse("1.1.0").unwrap())
+        );
+
+        assert_eq!(
+            find_newer_version(
+                client,
+                &registry_lookup_urls,
+                "acme",
+                "weather",
+                &Version::parse("1.1.0").unwrap()
+            )
+            .await,
+            None
+        );
+    }
+}
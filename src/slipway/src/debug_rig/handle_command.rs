@@ -1,7 +1,10 @@
 use std::{io::Write, sync::Arc};
 
 use slipway_engine::{CallChain, ComponentHandle, ComponentRunner, Immutable, RigExecutionState};
-use slipway_host::{render_state::to_view_model::to_shortcuts, render_state::write_state};
+use slipway_host::{
+    render_state::RenderStateOptions, render_state::to_view_model::to_shortcuts,
+    render_state::write_state,
+};
 
 use crate::json_editor::JsonEditor;
 
@@ -14,10 +17,11 @@ pub(super) async fn handle_command<'rig, 'cache, W: Write>(
     json_editor: &impl JsonEditor,
     component_runners: &[Box<dyn ComponentRunner>],
     call_chain: Arc<CallChain<'rig>>,
+    render_options: RenderStateOptions,
 ) -> anyhow::Result<HandleCommandResult<'rig, 'cache>> {
     let result = match debug_cli.command {
         DebuggerCommand::Print {} => {
-            write_state::<_, anyhow::Error>(w, state)?;
+            write_state::<_, anyhow::Error>(w, state, render_options)?;
             HandleCommandResult::Continue(None)
         }
         DebuggerCommand::Run { handle } => {
@@ -79,6 +83,11 @@ pub(super) async fn handle_command<'rig, 'cache, W: Write>(
             writeln!(w)?;
             HandleCommandResult::Continue(None)
         }
+        DebuggerCommand::Explain { handle, format } => {
+            let handle = get_handle(&handle, state)?;
+            crate::explain::write_input_explanation(w, state, handle, format)?;
+            HandleCommandResult::Continue(None)
+        }
         DebuggerCommand::Exit => HandleCommandResult::Exit,
     };
 
@@ -101,11 +110,7 @@ fn get_handle<'rig>(
         return Ok(handle);
     }
 
-    if let Some(&handle) = state
-        .valid_execution_order
-        .iter()
-        .find(|&h| h.0 == handle_str)
-    {
+    if let Some(&handle) = state.execution_order().iter().find(|&h| h.0 == handle_str) {
         return Ok(handle);
     }
 
@@ -563,6 +568,7 @@ mod tests {
             json_editor,
             &component_runners,
             CallChain::full_trust_arc(),
+            Default::default(),
         )
         .await
         .unwrap()
@@ -590,6 +596,7 @@ mod tests {
             &NoJsonEditor {},
             &[],
             CallChain::full_trust_arc(),
+            Default::default(),
         )
         .await
         .unwrap()
@@ -47,13 +47,15 @@ mod tests {
     use serde_json::json;
     use slipway_engine::{
         BasicComponentCache, BasicComponentsLoader, BasicComponentsLoaderBuilder, ComponentRigging,
-        Rig, RigSession, Rigging, RunComponentError, RunError, SlipwayReference, utils::ch,
+        Rig, RigSession, Rigging, RunComponentError, RunError, SPECIAL_COMPONENT_RUNNER_IDENTIFIER,
+        SlipwayReference, SpecialComponentReference, utils::ch,
     };
 
     use common_test_utils::{
         SLIPWAY_INCREMENT_COMPONENT_FOLDER_NAME, get_slipway_test_components_path,
         get_slipway_test_components_registry_url,
     };
+    use slipway_fragment_runner::FRAGMENT_COMPONENT_RUNNER_IDENTIFIER;
     use slipway_wasmtime_runner::WASMTIME_COMPONENT_RUNNER_IDENTIFIER;
 
     use crate::component_runners::get_component_runners;
@@ -189,4 +191,109 @@ mod tests {
             Ok(_) => panic!("Expected WasmExecutionFailed/RunCallFailed, got result"),
         }
     }
+
+    fn create_passthrough_rig(
+        component_handle: &ComponentHandle,
+        input: serde_json::Value,
+        runner: &str,
+    ) -> Rig {
+        let mut rigging = ComponentRigging::for_test_with_reference(
+            SlipwayReference::Special(SpecialComponentReference::Passthrough),
+            Some(input),
+        );
+        rigging.runner = Some(runner.to_string());
+
+        Rig::for_test(Rigging {
+            components: [(component_handle.clone(), rigging)].into_iter().collect(),
+        })
+    }
+
+    async fn run_passthrough_with_runner(
+        handle: &ComponentHandle,
+        runner: &str,
+    ) -> Result<serde_json::Value, SlipwayDebugError> {
+        let rig = create_passthrough_rig(handle, json!({ "value": 42 }), runner);
+
+        // Special components don't need to be loaded, so we don't need the test components.
+        let components_loader = BasicComponentsLoaderBuilder::new()
+            .without_default_registry()
+            .build();
+
+        let component_cache = BasicComponentCache::primed(&rig, &components_loader)
+            .await
+            .unwrap();
+        let rig_session = RigSession::new_for_test(rig, &component_cache);
+        let state = rig_session.initialize().unwrap();
+        let component_runners = get_component_runners();
+
+        let state = handle_run_command(
+            handle,
+            &state,
+            &component_runners,
+            CallChain::full_trust_arc(),
+        )
+        .await?;
+
+        let component_state = state
+            .component_states
+            .get(handle)
+            .expect("Component should exist");
+
+        Ok(component_state
+            .execution_output
+            .as_ref()
+            .expect("Component should have execution output")
+            .value
+            .clone())
+    }
+
+    #[slipway_test_async]
+    async fn it_should_run_component_with_requested_runner() {
+        let handle = ch("test_component");
+
+        let output = run_passthrough_with_runner(&handle, SPECIAL_COMPONENT_RUNNER_IDENTIFIER)
+            .await
+            .unwrap();
+
+        assert_eq!(output, json!({ "value": 42 }));
+    }
+
+    #[slipway_test_async]
+    async fn it_should_error_if_requested_runner_cannot_run_component() {
+        let handle = ch("test_component");
+
+        let result =
+            run_passthrough_with_runner(&handle, FRAGMENT_COMPONENT_RUNNER_IDENTIFIER).await;
+
+        match result {
+            Err(SlipwayDebugError::RunError(RunError::RequestedComponentRunnerCannotRun {
+                component_handle,
+                component_runner,
+            })) => {
+                assert_eq!(component_handle, handle);
+                assert_eq!(component_runner, FRAGMENT_COMPONENT_RUNNER_IDENTIFIER);
+            }
+            Err(x) => panic!("Expected RequestedComponentRunnerCannotRun, got {}", x),
+            Ok(_) => panic!("Expected RequestedComponentRunnerCannotRun, got result"),
+        }
+    }
+
+    #[slipway_test_async]
+    async fn it_should_error_if_requested_runner_does_not_exist() {
+        let handle = ch("test_component");
+
+        let result = run_passthrough_with_runner(&handle, "unknown").await;
+
+        match result {
+            Err(SlipwayDebugError::RunError(RunError::RequestedComponentRunnerNotFound {
+                component_handle,
+                component_runner,
+            })) => {
+                assert_eq!(component_handle, handle);
+                assert_eq!(component_runner, "unknown");
+            }
+            Err(x) => panic!("Expected RequestedComponentRunnerNotFound, got {}", x),
+            Ok(_) => panic!("Expected RequestedComponentRunnerNotFound, got result"),
+        }
+    }
 }
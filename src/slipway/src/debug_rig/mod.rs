@@ -2,20 +2,21 @@ use crate::json_editor::{JsonEditor, JsonEditorImpl};
 use anyhow::Context;
 use handle_command::{HandleCommandResult, handle_command};
 use serde_json::json;
-use slipway_host::render_state::write_state;
-use std::io::{self, Write};
-use std::path::PathBuf;
+use slipway_host::render_state::{RenderStateOptions, write_state};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use termion::{color, style};
 
 use slipway_engine::{
     BasicComponentCache, BasicComponentsLoader, CallChain, ComponentHandle, ComponentRigging,
-    Environment, Permissions, Rig, RigSession, RigSessionOptions, Rigging, SlipwayReference,
-    parse_rig,
+    Permissions, Rig, RigSession, Rigging, SlipwayReference,
 };
 
 use crate::component_runners::get_component_runners;
+use crate::explain::ExplainFormat;
+use crate::run_options::RunOptions;
 
 mod errors;
 mod handle_clear_input_command;
@@ -104,6 +105,17 @@ enum DebuggerCommand {
         save: Option<std::path::PathBuf>,
     },
 
+    /// Explain where each JSON Path expression in the input of a component resolved from.
+    Explain {
+        /// The component to explain.
+        #[arg(required = true)]
+        handle: String,
+
+        /// The format of the explanation.
+        #[arg(short, long, value_enum, default_value_t)]
+        format: ExplainFormat,
+    },
+
     /// Exit the debugger.
     Exit,
 }
@@ -123,8 +135,8 @@ pub(crate) async fn debug_rig_from_component_file<W: Write>(
     input: Option<String>,
     input_path: Option<std::path::PathBuf>,
     component_permissions: Permissions<'_>,
-    registry_urls: Vec<String>,
-    fonts_path: Option<PathBuf>,
+    options: RunOptions,
+    render_options: RenderStateOptions,
 ) -> anyhow::Result<()> {
     writeln!(w, "Debugging {}", component_reference)?;
     let json_editor = JsonEditorImpl::new();
@@ -137,17 +149,23 @@ pub(crate) async fn debug_rig_from_component_file<W: Write>(
     // component, but there is no advantage to being more restrictive here.
     let rig_permissions = Permissions::allow_all();
 
+    let components_loader = options.create_components_loader(None)?;
+
     debug_rig(
         w,
         rig,
         json_editor,
         rig_permissions,
-        registry_urls,
-        fonts_path,
+        components_loader,
+        options,
+        render_options,
     )
     .await
 }
 
+/// The handle of the component in rigs created to run a single component.
+pub(super) const COMPONENT_RIG_HANDLE: &str = "wrapped";
+
 pub(super) fn get_component_rig(
     component_reference: SlipwayReference,
     component_permissions: &Permissions<'_>,
@@ -158,7 +176,7 @@ pub(super) fn get_component_rig(
         constants: None,
         rigging: Rigging {
             components: [(
-                ComponentHandle::from_str("wrapped").unwrap(),
+                ComponentHandle::from_str(COMPONENT_RIG_HANDLE).unwrap(),
                 ComponentRigging {
                     component: component_reference,
                     input: Some(initial_input),
@@ -166,12 +184,17 @@ pub(super) fn get_component_rig(
                     deny: Some(component_permissions.deny.to_vec()),
                     permissions_chain: None,
                     callouts: None,
+                    runner: None,
+                    memoize: None,
+                    skip: None,
                 },
             )]
             .into_iter()
             .collect(),
         },
         context: None,
+        outputs: None,
+        templates: None,
     }
 }
 
@@ -191,9 +214,10 @@ pub(super) fn get_component_input<W: Write>(
                 input
             }
             Some(input_path) => {
-                serde_json::from_str(&std::fs::read_to_string(input_path.clone()).with_context(
-                    || format!("Failed to read input from {}", input_path.display()),
-                )?)?
+                let input = read_input_file(&input_path, io::stdin().lock())?;
+                serde_json::from_str(&input).with_context(|| {
+                    format!("Failed to parse JSON input from {}", input_path.display())
+                })?
             }
         },
         Some(input) => serde_json::from_str(&input)
@@ -202,28 +226,51 @@ pub(super) fn get_component_input<W: Write>(
     Ok(initial_input)
 }
 
+/// The input file path which indicates the input should be read from stdin.
+pub(crate) const STDIN_INPUT_PATH: &str = "-";
+
+/// Reads the input file, or reads `stdin` to the end if the path is `-`.
+fn read_input_file(input_path: &Path, mut stdin: impl Read) -> anyhow::Result<String> {
+    if input_path.as_os_str() == STDIN_INPUT_PATH {
+        let mut input = String::new();
+        stdin
+            .read_to_string(&mut input)
+            .context("Failed to read input from stdin")?;
+        Ok(input)
+    } else {
+        std::fs::read_to_string(input_path)
+            .with_context(|| format!("Failed to read input from {}", input_path.display()))
+    }
+}
+
 pub(crate) async fn debug_rig_from_rig_file<W: Write>(
     w: &mut W,
     input: std::path::PathBuf,
     engine_permissions: Permissions<'_>,
-    registry_urls: Vec<String>,
-    fonts_path: Option<PathBuf>,
+    overlays: Vec<PathBuf>,
+    constant_overrides: Vec<(String, serde_json::Value)>,
+    options: RunOptions,
+    render_options: RenderStateOptions,
 ) -> anyhow::Result<()> {
     writeln!(w, "Debugging {}", input.display())?;
 
-    let file_contents = std::fs::read_to_string(input.clone())
-        .with_context(|| format!("Failed to read component from {}", input.display()))?;
-    let rig = parse_rig(&file_contents)?;
+    let (file_contents, rig_directory) = crate::utils::read_rig_file(&input, io::stdin().lock())?;
+    let mut rig = crate::utils::parse_rig_file(&input, &file_contents)?;
+    crate::utils::apply_rig_overlays(&mut rig, &overlays)?;
+    crate::utils::apply_constant_overrides(&mut rig, constant_overrides);
 
     let json_editor = JsonEditorImpl::new();
 
+    let components_loader = options.create_components_loader(rig_directory.as_deref())?;
+
     debug_rig(
         w,
         rig,
         json_editor,
         engine_permissions,
-        registry_urls,
-        fonts_path,
+        components_loader,
+        options,
+        render_options,
     )
     .await
 }
@@ -243,29 +290,27 @@ async fn debug_rig<W: Write>(
     rig: Rig,
     json_editor: impl JsonEditor,
     engine_permissions: Permissions<'_>,
-    registry_urls: Vec<String>,
-    fonts_path: Option<PathBuf>,
+    components_loader: BasicComponentsLoader,
+    options: RunOptions,
+    render_options: RenderStateOptions,
 ) -> anyhow::Result<()> {
-    let components_loader = BasicComponentsLoader::builder()
-        .registry_lookup_urls(registry_urls)
-        .build();
-
-    let timezone = crate::utils::get_system_timezone();
-    let locale = crate::utils::get_system_locale();
     let component_cache = BasicComponentCache::primed(&rig, &components_loader).await?;
-    let session_options = RigSessionOptions::new_for_run(
-        &rig,
-        false,
-        fonts_path.as_deref(),
-        Environment { timezone, locale },
-    )
-    .await;
+    crate::utils::handle_component_warnings(&component_cache, options.deny_warnings)?;
+    let session_options = options
+        .session_options_builder(&components_loader)
+        .build(&rig)
+        .await;
+    crate::utils::handle_missing_fonts(&component_cache, &session_options, options.deny_warnings)
+        .await?;
+    options
+        .check_for_updates(&component_cache, &components_loader)
+        .await;
     let session = RigSession::new_with_options(rig, &component_cache, session_options);
     let mut state = session.initialize()?;
 
     let component_runners = get_component_runners();
 
-    write_state::<_, anyhow::Error>(w, &state)?;
+    write_state::<_, anyhow::Error>(w, &state, render_options)?;
 
     let help_color = color::Fg(color::Yellow);
     writeln!(
@@ -290,7 +335,12 @@ async fn debug_rig<W: Write>(
         w.flush().unwrap();
 
         let mut input = String::new();
-        if io::stdin().read_line(&mut input).is_ok() {
+        if let Ok(bytes_read) = io::stdin().read_line(&mut input) {
+            // Stdin has been closed, for example because the rig was piped in.
+            if bytes_read == 0 {
+                break;
+            }
+
             let mut args = input.split_whitespace().collect::<Vec<&str>>();
             if args.is_empty() {
                 continue;
@@ -307,12 +357,13 @@ async fn debug_rig<W: Write>(
                         &json_editor,
                         &component_runners,
                         Arc::clone(&permissions_chain),
+                        render_options,
                     )
                     .await
                     {
                         Ok(HandleCommandResult::Continue(Some(s))) => {
                             state = s;
-                            write_state::<_, anyhow::Error>(w, &state)?;
+                            write_state::<_, anyhow::Error>(w, &state, render_options)?;
                         }
                         Ok(HandleCommandResult::Continue(None)) => {}
                         Ok(HandleCommandResult::Exit) => break,
@@ -324,7 +375,7 @@ async fn debug_rig<W: Write>(
                                 e,
                                 color::Fg(color::Reset)
                             )?;
-                            write_state::<_, anyhow::Error>(w, &state)?;
+                            write_state::<_, anyhow::Error>(w, &state, render_options)?;
                         }
                     }
                 }
@@ -339,3 +390,24 @@ async fn debug_rig<W: Write>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn it_should_read_input_from_stdin_when_path_is_dash() {
+        let stdin = Cursor::new(r#"{"value":1}"#);
+        let input = read_input_file(Path::new(STDIN_INPUT_PATH), stdin).unwrap();
+        assert_eq!(input, r#"{"value":1}"#);
+    }
+
+    #[test]
+    fn it_should_not_read_stdin_when_path_is_a_file() {
+        let stdin = Cursor::new(r#"{"value":1}"#);
+        let result = read_input_file(Path::new("./does_not_exist.json"), stdin);
+        assert!(result.is_err());
+    }
+}
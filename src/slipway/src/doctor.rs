@@ -0,0 +1,361 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use slipway_engine::{
+    DEFAULT_REGISTRY_LOOKUP_URL, FontContext, ProcessedUrl, process_url_str,
+    try_get_default_slipway_components_cache_dir,
+};
+use termion::color;
+
+use crate::{summary::Summary, utils::ColorMode};
+
+const REGISTRY_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_FONT_FAMILIES: [&str; 2] = ["Roboto", "Roboto Mono"];
+
+/// The result of a single diagnostic check.
+struct Check {
+    name: String,
+    outcome: CheckOutcome,
+}
+
+enum CheckOutcome {
+    Pass { detail: String },
+    Fail { detail: String, hint: String },
+}
+
+impl Check {
+    fn pass(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            outcome: CheckOutcome::Pass {
+                detail: detail.into(),
+            },
+        }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            outcome: CheckOutcome::Fail {
+                detail: detail.into(),
+                hint: hint.into(),
+            },
+        }
+    }
+
+    fn is_failure(&self) -> bool {
+        matches!(self.outcome, CheckOutcome::Fail { .. })
+    }
+}
+
+/// Checks the environment Slipway is running in, and writes a checklist of the results
+/// along with hints for fixing any failures.
+pub(super) async fn doctor<W: Write>(
+    w: &mut W,
+    registry_urls: Vec<String>,
+    color: ColorMode,
+) -> anyhow::Result<()> {
+    let mut checks = vec![check_components_cache_directory(
+        try_get_default_slipway_components_cache_dir().as_deref(),
+    )];
+
+    let current_directory = std::env::current_dir()?;
+    for registry_url in registry_urls
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once(DEFAULT_REGISTRY_LOOKUP_URL))
+    {
+        checks.push(check_registry(registry_url, &current_directory).await);
+    }
+
+    checks.push(check_fonts());
+    checks.push(check_wasm_engine());
+    checks.push(check_component_runners());
+
+    let failures = checks.iter().filter(|c| c.is_failure()).count();
+    create_summary(&checks, failures, color.use_color()).write(w)?;
+
+    if failures > 0 {
+        anyhow::bail!("{failures} check(s) failed.");
+    }
+
+    Ok(())
+}
+
+fn create_summary(checks: &[Check], failures: usize, use_color: bool) -> Summary {
+    let label = |label: &str, fg: &dyn color::Color| {
+        if use_color {
+            format!("{}[{label}]{}", color::Fg(fg), color::Fg(color::Reset))
+        } else {
+            format!("[{label}]")
+        }
+    };
+
+    let mut summary = Summary::new("Doctor summary");
+    summary
+        .field("Passed", checks.len() - failures)
+        .field("Failed", failures);
+
+    for check in checks {
+        match &check.outcome {
+            CheckOutcome::Pass { detail } => {
+                summary.line(format!(
+                    "{} {}: {}",
+                    label("PASS", &color::Green),
+                    check.name,
+                    detail
+                ));
+            }
+            CheckOutcome::Fail { detail, hint } => {
+                summary
+                    .line(format!(
+                        "{} {}: {}",
+                        label("FAIL", &color::Red),
+                        check.name,
+                        detail
+                    ))
+                    .line(format!("       Hint: {hint}"));
+            }
+        }
+    }
+
+    summary
+}
+
+fn check_components_cache_directory(path: Option<&Path>) -> Check {
+    const NAME: &str = "Components cache directory";
+
+    let Some(path) = path else {
+        return Check::fail(
+            NAME,
+            "Unable to determine the home directory",
+            "Ensure the HOME environment variable is set to your home directory.",
+        );
+    };
+
+    // The cache directory is created when the first component is downloaded,
+    // so if it doesn't exist yet we check its nearest existing ancestor instead.
+    let Some(existing) = path.ancestors().find(|p| p.exists()) else {
+        return Check::fail(
+            NAME,
+            format!("No part of {} exists", path.display()),
+            "Ensure your home directory exists and is accessible.",
+        );
+    };
+
+    if !existing.is_dir() {
+        return Check::fail(
+            NAME,
+            format!("{} is not a directory", existing.display()),
+            format!("Remove {} so the cache can be created.", existing.display()),
+        );
+    }
+
+    match tempfile::tempfile_in(existing) {
+        Ok(_) if existing == path => Check::pass(NAME, format!("{} is writable", path.display())),
+        Ok(_) => Check::pass(
+            NAME,
+            format!("{} does not exist yet, but can be created", path.display()),
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            format!("{} is not writable: {e}", existing.display()),
+            format!(
+                "Check the permissions of {}, or run `slipway clear-component-cache`.",
+                existing.display()
+            ),
+        ),
+    }
+}
+
+async fn check_registry(registry_url: &str, current_directory: &Path) -> Check {
+    let name = format!("Registry {registry_url}");
+
+    match process_url_str(registry_url) {
+        Ok(ProcessedUrl::Http(url)) => {
+            let origin = url.origin().ascii_serialization();
+            let client = match reqwest::Client::builder().timeout(REGISTRY_TIMEOUT).build() {
+                Ok(client) => client,
+                Err(e) => {
+                    return Check::fail(
+                        name,
+                        format!("Failed to create HTTP client: {e}"),
+                        "Check your proxy environment variables.",
+                    );
+                }
+            };
+
+            // Any response at all means the registry host is reachable.
+            match client.head(&origin).send().await {
+                Ok(response) => Check::pass(
+                    name,
+                    format!("{origin} responded with {}", response.status()),
+                ),
+                Err(e) => Check::fail(
+                    name,
+                    format!("{origin} is unreachable: {e}"),
+                    "Check your network connection and proxy settings, or use --registry to specify a reachable registry.",
+                ),
+            }
+        }
+        Ok(ProcessedUrl::RelativePath(path)) => {
+            check_file_registry(name, &current_directory.join(path))
+        }
+        Ok(ProcessedUrl::AbsolutePath(path)) => check_file_registry(name, &path),
+        Ok(ProcessedUrl::Other(url)) => Check::fail(
+            name,
+            format!("Unsupported URL scheme \"{}\"", url.scheme()),
+            "Registry URLs must use the http, https or file scheme.",
+        ),
+        Err(e) => Check::fail(
+            name,
+            e,
+            "Ensure the registry URL is a valid URL template, for example https://registry.example.com/{publisher}/{name}/{version}",
+        ),
+    }
+}
+
+fn check_file_registry(name: String, path_template: &Path) -> Check {
+    let directory = get_file_registry_directory(path_template);
+
+    if directory.is_dir() {
+        Check::pass(name, format!("{} exists", directory.display()))
+    } else {
+        Check::fail(
+            name,
+            format!("{} does not exist", directory.display()),
+            "Check the registry path is correct. Relative paths are resolved against the current directory.",
+        )
+    }
+}
+
+/// Returns the directory of a file registry path template, which is the
+/// directory above the first templated path segment.
+fn get_file_registry_directory(path_template: &Path) -> PathBuf {
+    let path_template = path_template.to_string_lossy();
+    let static_prefix = match path_template.find('{') {
+        Some(index) => &path_template[..index],
+        None => &path_template,
+    };
+
+    let static_prefix = Path::new(static_prefix);
+    if static_prefix
+        .to_string_lossy()
+        .ends_with(std::path::MAIN_SEPARATOR)
+    {
+        static_prefix.to_path_buf()
+    } else {
+        static_prefix
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default()
+    }
+}
+
+fn check_fonts() -> Check {
+    const NAME: &str = "Bundled fonts";
+
+    let mut font_context = FontContext::new();
+    let missing: Vec<&str> = DEFAULT_FONT_FAMILIES
+        .into_iter()
+        .filter(|family| font_context.collection.family_by_name(family).is_none())
+        .collect();
+
+    if missing.is_empty() {
+        Check::pass(NAME, format!("Loaded {}", DEFAULT_FONT_FAMILIES.join(", ")))
+    } else {
+        Check::fail(
+            NAME,
+            format!("Failed to load {}", missing.join(", ")),
+            "This Slipway build may be corrupt. Try reinstalling Slipway.",
+        )
+    }
+}
+
+fn check_wasm_engine() -> Check {
+    const NAME: &str = "WASM engine";
+
+    match slipway_wasmtime_runner::WasmComponentRunner::try_new() {
+        Ok(_) => Check::pass(NAME, "Wasmtime initialized"),
+        Err(e) => Check::fail(
+            NAME,
+            format!("Wasmtime failed to initialize: {e}"),
+            "Wasmtime may not support this platform. Check the Slipway installation instructions for supported platforms.",
+        ),
+    }
+}
+
+fn check_component_runners() -> Check {
+    let runners = crate::component_runners::get_component_runners()
+        .iter()
+        .map(|r| r.identifier())
+        .collect::<Vec<_>>();
+
+    Check::pass("Component runners", runners.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_check_components_cache_directory() {
+        let directory = tempfile::tempdir().unwrap();
+
+        assert!(!check_components_cache_directory(Some(directory.path())).is_failure());
+        assert!(
+            !check_components_cache_directory(Some(&directory.path().join("a/b"))).is_failure()
+        );
+
+        let file_path = directory.path().join("file");
+        std::fs::write(&file_path, "").unwrap();
+        assert!(check_components_cache_directory(Some(&file_path.join("a"))).is_failure());
+
+        assert!(check_components_cache_directory(None).is_failure());
+    }
+
+    #[test]
+    fn it_should_only_color_summary_when_color_is_used() {
+        let checks = vec![
+            Check::pass("a", "passed"),
+            Check::fail("b", "failed", "fix it"),
+        ];
+
+        let mut output = Vec::new();
+        create_summary(&checks, 1, false)
+            .write(&mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("[PASS] a: passed"));
+        assert!(output.contains("[FAIL] b: failed"));
+        assert!(!output.contains('\x1b'));
+
+        let mut output = Vec::new();
+        create_summary(&checks, 1, true).write(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(&format!("{}[PASS]", color::Fg(color::Green))));
+        assert!(output.contains(&format!("{}[FAIL]", color::Fg(color::Red))));
+    }
+
+    #[test]
+    fn it_should_get_file_registry_directory() {
+        assert_eq!(
+            get_file_registry_directory(Path::new(
+                "/home/user/components/{publisher}.{name}.{version}.tar"
+            )),
+            PathBuf::from("/home/user/components")
+        );
+        assert_eq!(
+            get_file_registry_directory(Path::new("/home/user/slipway_{name}/components")),
+            PathBuf::from("/home/user")
+        );
+    }
+
+    #[test]
+    fn it_should_load_bundled_fonts() {
+        assert!(!check_fonts().is_failure());
+    }
+}
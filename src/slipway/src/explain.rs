@@ -0,0 +1,151 @@
+use std::io::Write;
+
+use slipway_engine::{ComponentHandle, InputProvenance, RigExecutionState};
+
+/// The maximum width of a value in the table output, after which it is truncated.
+const MAX_TABLE_VALUE_WIDTH: usize = 60;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum ExplainFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+/// Which component's input to explain, and how to write the explanation.
+#[derive(Clone, Debug)]
+pub(crate) struct Explain {
+    pub handle: ComponentHandle,
+    pub format: ExplainFormat,
+}
+
+/// Writes where each JSON Path expression in the component's input resolved from.
+pub(crate) fn write_input_explanation<W: Write + ?Sized>(
+    w: &mut W,
+    state: &RigExecutionState,
+    handle: &ComponentHandle,
+    format: ExplainFormat,
+) -> anyhow::Result<()> {
+    let provenance = state.explain_component_input(handle)?;
+
+    match format {
+        ExplainFormat::Json => {
+            writeln!(w, "{}", serde_json::to_string_pretty(&provenance)?)?;
+        }
+        ExplainFormat::Table => {
+            writeln!(w, "Input of \"{handle}\":")?;
+            write_table(w, &provenance)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_table<W: Write + ?Sized>(
+    w: &mut W,
+    provenance: &[InputProvenance],
+) -> std::io::Result<()> {
+    if provenance.is_empty() {
+        writeln!(w, "No values were resolved from JSON Path expressions.")?;
+        return Ok(());
+    }
+
+    let header = ["Input path", "Expression", "Source", "Value"].map(String::from);
+    let rows: Vec<[String; 4]> = provenance
+        .iter()
+        .map(|p| {
+            [
+                p.input_path.clone(),
+                p.expression.clone(),
+                p.source_component
+                    .as_ref()
+                    .map_or_else(|| "-".to_string(), |h| h.to_string()),
+                format_value(p.value.as_ref()),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.clone().map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(w, "{}", line.trim_end())?;
+    }
+
+    Ok(())
+}
+
+fn format_value(value: Option<&serde_json::Value>) -> String {
+    let Some(value) = value else {
+        return "(none)".to_string();
+    };
+
+    let value = value.to_string();
+    if value.chars().count() <= MAX_TABLE_VALUE_WIDTH {
+        return value;
+    }
+
+    let truncated: String = value.chars().take(MAX_TABLE_VALUE_WIDTH - 3).collect();
+    format!("{truncated}...")
+}
+
+#[cfg(test)]
+mod tests {
+    use slipway_engine::utils::ch;
+
+    use super::*;
+
+    #[test]
+    fn it_should_write_provenance_as_table() {
+        let provenance = vec![
+            InputProvenance {
+                input_path: "input.a".to_string(),
+                expression: "$$.other.a".to_string(),
+                resolved_path: "$.rigging.other.output.a".to_string(),
+                source_component: Some(ch("other")),
+                source_hash: None,
+                value: Some(serde_json::json!(1)),
+            },
+            InputProvenance {
+                input_path: "input.description".to_string(),
+                expression: "$?description".to_string(),
+                resolved_path: "$.description".to_string(),
+                source_component: None,
+                source_hash: None,
+                value: None,
+            },
+        ];
+
+        let mut output = Vec::new();
+        write_table(&mut output, &provenance).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                "Input path         Expression     Source  Value\n",
+                "input.a            $$.other.a     other   1\n",
+                "input.description  $?description  -       (none)\n",
+            )
+        );
+    }
+
+    #[test]
+    fn it_should_truncate_long_values() {
+        let value = serde_json::json!("x".repeat(100));
+
+        let formatted = format_value(Some(&value));
+
+        assert_eq!(formatted.chars().count(), MAX_TABLE_VALUE_WIDTH);
+        assert!(formatted.ends_with("..."));
+    }
+}
@@ -1,9 +1,13 @@
 use std::{str::FromStr, sync::Arc};
 
 use anyhow::Context;
-use slipway_engine::{ComponentHandle, ComponentOutput, Immutable, RigExecutionState};
+use slipway_engine::{
+    ComponentHandle, ComponentOutput, Immutable, OutputContentType, RigExecutionState, Rigging,
+};
 
-pub(super) fn get_rig_output<'rig>(
+const OUTPUT_COMPONENT_NAMES: [&str; 2] = ["render", "output"];
+
+fn get_rig_output<'rig>(
     state: &Immutable<RigExecutionState<'rig, '_>>,
 ) -> Result<RigOutput<'rig>, anyhow::Error> {
     if state.component_states.len() == 1 {
@@ -21,11 +25,10 @@ pub(super) fn get_rig_output<'rig>(
         return Ok(RigOutput {
             handle,
             output: Arc::clone(output),
+            content_type: state.output_content_type(handle),
         });
     }
 
-    const OUTPUT_COMPONENT_NAMES: [&str; 2] = ["render", "output"];
-
     for name in OUTPUT_COMPONENT_NAMES.iter() {
         let handle =
             &ComponentHandle::from_str(name).context("Failed to parse output component name.")?;
@@ -35,6 +38,7 @@ pub(super) fn get_rig_output<'rig>(
                 return Ok(RigOutput {
                     handle: component_state.handle,
                     output: Arc::clone(output),
+                    content_type: state.output_content_type(handle),
                 });
             }
         }
@@ -46,7 +50,255 @@ pub(super) fn get_rig_output<'rig>(
     ))
 }
 
+/// Returns the handle of the component whose output is the rig's output,
+/// or `None` if the rig has no designated output component.
+pub(super) fn get_rig_output_handle(rigging: &Rigging) -> Option<&ComponentHandle> {
+    if rigging.components.len() == 1 {
+        return rigging.components.keys().next();
+    }
+
+    OUTPUT_COMPONENT_NAMES.iter().find_map(|name| {
+        rigging
+            .components
+            .get_key_value(&ComponentHandle::from_str(name).ok()?)
+            .map(|(handle, _)| handle)
+    })
+}
+
+pub(super) fn get_component_output<'rig>(
+    state: &Immutable<RigExecutionState<'rig, '_>>,
+    handle: &ComponentHandle,
+) -> Result<RigOutput<'rig>, anyhow::Error> {
+    let Some(component_state) = state.component_states.get(handle) else {
+        return Err(anyhow::anyhow!(
+            "Component \"{}\" does not exist in the rig.",
+            handle
+        ));
+    };
+
+    let Some(output) = component_state.execution_output.as_ref() else {
+        return Err(anyhow::anyhow!(
+            "Component \"{}\" did not produce an output.",
+            handle
+        ));
+    };
+
+    Ok(RigOutput {
+        handle: component_state.handle,
+        output: Arc::clone(output),
+        content_type: state.output_content_type(handle),
+    })
+}
+
+/// Returns the rig's named outputs, if the rig specifies them, or otherwise
+/// the output of the rig's output component.
+pub(super) fn get_rig_outputs<'rig>(
+    state: &Immutable<RigExecutionState<'rig, '_>>,
+) -> Result<RigOutputs<'rig>, anyhow::Error> {
+    match get_named_rig_outputs(state)? {
+        Some(outputs) => Ok(RigOutputs::Named(outputs)),
+        None => get_rig_output(state).map(RigOutputs::Single),
+    }
+}
+
+/// Returns the output of each of the rig's named outputs, ordered by name,
+/// or `None` if the rig does not specify named outputs.
+pub(super) fn get_named_rig_outputs<'rig>(
+    state: &Immutable<RigExecutionState<'rig, '_>>,
+) -> Result<Option<Vec<NamedRigOutput<'rig>>>, anyhow::Error> {
+    let session = state.session;
+    let Some(outputs) = session.rig().outputs.as_ref() else {
+        return Ok(None);
+    };
+
+    outputs
+        .iter()
+        .map(|(name, handle)| {
+            let output = get_component_output(state, handle)
+                .map_err(|e| anyhow::anyhow!("Failed to get rig output \"{name}\": {e}"))?;
+
+            Ok(NamedRigOutput {
+                name: name.0.as_str(),
+                output,
+            })
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()
+        .map(Some)
+}
+
 pub(super) struct RigOutput<'rig> {
     pub handle: &'rig ComponentHandle,
     pub output: Arc<ComponentOutput>,
+
+    /// The content type the component declares for its output, if any.
+    pub content_type: Option<OutputContentType>,
+}
+
+pub(super) enum RigOutputs<'rig> {
+    Single(RigOutput<'rig>),
+
+    /// The rig's named outputs, ordered by name.
+    Named(Vec<NamedRigOutput<'rig>>),
+}
+
+pub(super) struct NamedRigOutput<'rig> {
+    pub name: &'rig str,
+    pub output: RigOutput<'rig>,
+}
+
+impl RigOutputs<'_> {
+    /// Returns the output value, where named outputs are combined into
+    /// an object keyed by output name.
+    pub fn to_value(&self) -> serde_json::Value {
+        match self {
+            RigOutputs::Single(rig_output) => rig_output.output.value.clone(),
+            RigOutputs::Named(outputs) => serde_json::Value::Object(
+                outputs
+                    .iter()
+                    .map(|o| (o.name.to_string(), o.output.output.value.clone()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+    use serde_json::json;
+    use slipway_engine::{
+        BasicComponentCache, ComponentRigging, Instruction, Rig, RigOutputName, RigSession,
+        Rigging, RunMetadata, utils::ch,
+    };
+
+    use super::*;
+
+    #[slipway_test_async]
+    async fn it_should_get_specific_component_output() {
+        let rig = Rig::for_test(Rigging {
+            components: [
+                ComponentRigging::for_test("a", None),
+                ComponentRigging::for_test("b", Some(json!({"a": "$$.a"}))),
+            ]
+            .into_iter()
+            .collect(),
+        });
+
+        let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+        let rig_session = RigSession::new_for_test(rig, &component_cache);
+        let state = rig_session
+            .initialize()
+            .unwrap()
+            .step(Instruction::SetOutput {
+                handle: ch("a"),
+                value: json!({"value": 1}),
+                metadata: RunMetadata::default(),
+            })
+            .unwrap();
+
+        let output = get_component_output(&state, &ch("a")).unwrap();
+        assert_eq!(output.handle, &ch("a"));
+        assert_eq!(output.output.value, json!({"value": 1}));
+
+        let Err(error) = get_component_output(&state, &ch("b")) else {
+            panic!("Expected an error for a component without output");
+        };
+        assert_eq!(
+            error.to_string(),
+            "Component \"b\" did not produce an output."
+        );
+
+        let Err(error) = get_component_output(&state, &ch("c")) else {
+            panic!("Expected an error for a missing component");
+        };
+        assert_eq!(
+            error.to_string(),
+            "Component \"c\" does not exist in the rig."
+        );
+    }
+
+    #[slipway_test_async]
+    async fn it_should_get_named_rig_outputs() {
+        let mut rig = Rig::for_test(Rigging {
+            components: [
+                ComponentRigging::for_test("a", None),
+                ComponentRigging::for_test("b", None),
+            ]
+            .into_iter()
+            .collect(),
+        });
+        rig.outputs = Some(
+            [
+                (RigOutputName::from_str("chart").unwrap(), ch("a")),
+                (RigOutputName::from_str("data").unwrap(), ch("b")),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+        let rig_session = RigSession::new_for_test(rig, &component_cache);
+        let state = rig_session
+            .initialize()
+            .unwrap()
+            .step(Instruction::SetOutput {
+                handle: ch("a"),
+                value: json!({"value": 1}),
+                metadata: RunMetadata::default(),
+            })
+            .unwrap();
+
+        let Err(error) = get_rig_outputs(&state) else {
+            panic!("Expected an error for a named output without output");
+        };
+        assert_eq!(
+            error.to_string(),
+            "Failed to get rig output \"data\": Component \"b\" did not produce an output."
+        );
+
+        let state = state
+            .step(Instruction::SetOutput {
+                handle: ch("b"),
+                value: json!({"value": 2}),
+                metadata: RunMetadata::default(),
+            })
+            .unwrap();
+
+        let outputs = get_rig_outputs(&state).unwrap();
+        let RigOutputs::Named(named_outputs) = &outputs else {
+            panic!("Expected named rig outputs");
+        };
+        assert_eq!(
+            named_outputs.iter().map(|o| o.name).collect::<Vec<_>>(),
+            vec!["chart", "data"]
+        );
+        assert_eq!(
+            outputs.to_value(),
+            json!({
+                "chart": {"value": 1},
+                "data": {"value": 2},
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_get_rig_output_handle() {
+        let rigging = |handles: &[&str]| Rigging {
+            components: handles
+                .iter()
+                .map(|h| ComponentRigging::for_test(h, None))
+                .collect(),
+        };
+
+        assert_eq!(get_rig_output_handle(&rigging(&["a"])), Some(&ch("a")));
+        assert_eq!(
+            get_rig_output_handle(&rigging(&["a", "output"])),
+            Some(&ch("output"))
+        );
+        assert_eq!(
+            get_rig_output_handle(&rigging(&["output", "render"])),
+            Some(&ch("render"))
+        );
+        assert_eq!(get_rig_output_handle(&rigging(&["a", "b"])), None);
+    }
 }
@@ -1,22 +1,33 @@
 #![allow(dead_code)]
 
+mod bundle;
 mod canvas;
+mod component_cache;
 mod component_runners;
+mod component_tracing;
+mod component_updates;
 mod debug_rig;
+mod doctor;
+mod explain;
 mod get_rig_output;
 mod host_error;
 mod json_editor;
 mod package;
 mod permissions;
 mod primitives;
+mod registry_auth;
+mod run_options;
 mod run_rig;
 mod serve;
+mod summary;
+mod test_component;
 mod utils;
+mod wit;
 
 #[cfg(test)]
 mod test_utils;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::{
     Args, Parser, Subcommand,
@@ -25,17 +36,30 @@ use clap::{
         styling::{AnsiColor, Effects},
     },
 };
-use permissions::CommonPermissionsArgs;
+use explain::{Explain, ExplainFormat};
+use permissions::{CommonPermissionsArgs, PermissionsOwned};
 use primitives::{DeviceName, PlaylistName, RigName};
+use registry_auth::RegistryAuth;
+use run_options::RunOptions;
 use semver::Version;
-use slipway_engine::{Name, Publisher, SlipwayReference, clear_components_cache};
-use slipway_host::hash_string;
+use slipway_engine::{
+    ComponentHandle, HashAlgorithm, Name, Publisher, SlipwayReference, clear_components_cache,
+    get_default_slipway_components_cache_dir,
+};
+use slipway_host::{
+    hash_string,
+    render_state::{ByteUnits, RenderStateOptions},
+};
 use time::{OffsetDateTime, format_description};
 use tracing::{Level, info};
-use tracing_subscriber::{FmtSubscriber, fmt::time::FormatTime};
+use tracing_subscriber::{
+    FmtSubscriber, Layer, filter::DynFilterFn, fmt::time::FormatTime, layer::SubscriberExt,
+};
+use utils::{ColorMode, DefinitionFormat};
 
-const WASM_INTERFACE_TYPE_STR: &str = include_str!("../../wit/latest/slipway.wit");
 const SLIPWAY_COMPONENT_FILE_NAME: &str = "slipway_component.json";
+const SLIPWAY_COMPONENT_YAML_FILE_NAMES: [&str; 2] =
+    ["slipway_component.yaml", "slipway_component.yml"];
 const AOT_ARTIFACT_FOLDER_NAME: &str = "aot";
 const DEFAULT_TIMEZONE: &str = "Etc/UTC";
 const DEFAULT_LOCALE: &str = "en-US";
@@ -63,14 +87,21 @@ pub(crate) enum Commands {
     /// Run a Slipway Rig.
     #[command(arg_required_else_help = true)]
     Run {
-        /// The path to the Rig file.
+        /// The path to the Rig file, or a `.bundle` file created with `slipway bundle`.
+        /// Relative local Component references in the Rig are resolved
+        /// relative to the directory containing the Rig file.
+        /// Use `-` to read the Rig from stdin, in which case relative references
+        /// are resolved relative to the current working directory.
+        /// Rig files with a `.yaml` or `.yml` extension are parsed as YAML,
+        /// and Rig files with a `.jsonc` extension may contain
+        /// comments and trailing commas.
         rig: PathBuf,
 
         #[command(flatten)]
         common: Box<CommonRunArgs>,
 
         /// The optional path to save the Rig output to.
-        /// The path can be a `.png` file, a `.json` file, or a folder.
+        /// The path can be a `.png` file, a `.svg` file, a `.json` file, or a folder.
         #[arg(short, long)]
         output: Option<std::path::PathBuf>,
 
@@ -78,15 +109,84 @@ pub(crate) enum Commands {
         #[arg(short, long)]
         output_debug_rig: Option<std::path::PathBuf>,
 
+        /// The optional file path to write a JSON profile of the run to,
+        /// containing the per-phase timings of each Component, the total
+        /// wall time of the Rig and Component cache statistics.
+        #[arg(long)]
+        profile: Option<std::path::PathBuf>,
+
+        /// The optional file path to write a JSON summary of the run to once the
+        /// Rig has finished, containing the number of Components run and skipped,
+        /// the total duration, the status of each Component, where each value in
+        /// each Component's input came from, and any warnings.
+        #[arg(long)]
+        summary: Option<std::path::PathBuf>,
+
         /// The optional folder path where additional fonts are located.
         #[arg(short, long)]
         fonts: Option<std::path::PathBuf>,
+
+        /// The optional handle of a Component whose output should be written
+        /// instead of the Rig's output.
+        #[arg(long)]
+        component: Option<ComponentHandle>,
+
+        /// The optional handle of a Component to run the Rig until.
+        /// Only the Components required to produce its output are run,
+        /// and its output is written instead of the Rig's output.
+        #[arg(long, conflicts_with = "component")]
+        until: Option<ComponentHandle>,
+
+        /// Seed the output of a Component before the Rig runs, in the format `handle=value`,
+        /// so that the Component is not run. The value must be JSON.
+        /// The seeded outputs are shown as overrides in the Rig graph.
+        /// This can be specified multiple times.
+        #[arg(long, value_parser = crate::utils::parse_seed_output)]
+        seed_output: Vec<(ComponentHandle, serde_json::Value)>,
+
+        /// Continue running the Components which don't depend on a failed Component,
+        /// rather than stopping at the first failure.
+        /// Every failure is reported once the Rig has finished running.
+        #[arg(long)]
+        continue_on_error: bool,
+
+        /// The optional handle of a Component whose input should be explained,
+        /// showing where each JSON Path expression in the input resolved from.
+        #[arg(long)]
+        explain: Option<ComponentHandle>,
+
+        /// The format of the input explanation.
+        #[arg(long, value_enum, default_value_t, requires = "explain")]
+        explain_format: ExplainFormat,
+
+        /// An overlay file to deep merge into the Rig before it runs, for example to
+        /// change Component inputs or constants for a particular environment.
+        /// Objects are merged recursively, `null` removes a property, and other values
+        /// replace the existing value. Overlays use the same file formats as Rigs.
+        /// This can be specified multiple times, and later overlays take precedence.
+        /// Constants set with `--set-constant` are applied after any overlays.
+        #[arg(long)]
+        overlay: Vec<PathBuf>,
+
+        /// Override a Rig constant, in the format `key=value`.
+        /// The value is parsed as JSON if possible, otherwise it is used as a string.
+        /// Nested constants can be set using `.` separated keys, such as `location.city=London`.
+        /// This can be specified multiple times.
+        #[arg(long, value_parser = crate::utils::parse_constant_override)]
+        set_constant: Vec<(String, serde_json::Value)>,
     },
 
     /// Debug a Slipway Rig.
     #[command(arg_required_else_help = true)]
     Debug {
         /// The path to the Rig file.
+        /// Relative local Component references in the Rig are resolved
+        /// relative to the directory containing the Rig file.
+        /// Use `-` to read the Rig from stdin, in which case relative references
+        /// are resolved relative to the current working directory.
+        /// Rig files with a `.yaml` or `.yml` extension are parsed as YAML,
+        /// and Rig files with a `.jsonc` extension may contain
+        /// comments and trailing commas.
         rig: PathBuf,
 
         #[command(flatten)]
@@ -95,6 +195,36 @@ pub(crate) enum Commands {
         /// The optional folder path where additional fonts are located.
         #[arg(short, long)]
         fonts: Option<std::path::PathBuf>,
+
+        /// An overlay file to deep merge into the Rig before it runs, for example to
+        /// change Component inputs or constants for a particular environment.
+        /// Objects are merged recursively, `null` removes a property, and other values
+        /// replace the existing value. Overlays use the same file formats as Rigs.
+        /// This can be specified multiple times, and later overlays take precedence.
+        /// Constants set with `--set-constant` are applied after any overlays.
+        #[arg(long)]
+        overlay: Vec<PathBuf>,
+
+        /// Override a Rig constant, in the format `key=value`.
+        /// The value is parsed as JSON if possible, otherwise it is used as a string.
+        /// Nested constants can be set using `.` separated keys, such as `location.city=London`.
+        /// This can be specified multiple times.
+        #[arg(long, value_parser = crate::utils::parse_constant_override)]
+        set_constant: Vec<(String, serde_json::Value)>,
+
+        /// The number of characters of each hash to display in the component graph.
+        #[arg(long, default_value_t = slipway_host::render_state::DEFAULT_HASH_RENDER_CHAR_COUNT)]
+        hash_chars: usize,
+
+        /// Whether to use color in the component graph. By default color is used
+        /// unless the `NO_COLOR` environment variable is set or stdout is not a terminal.
+        #[arg(long, value_enum, default_value_t)]
+        color: ColorMode,
+
+        /// The units used for input and output sizes in the component graph:
+        /// `short` (multiples of 1024 labelled kb, mb), `binary` (KiB, MiB) or `decimal` (kB, MB).
+        #[arg(long, default_value = "short")]
+        byte_units: ByteUnits,
     },
 
     /// Run a Slipway component.
@@ -108,6 +238,8 @@ pub(crate) enum Commands {
         input: Option<String>,
 
         /// The optional path to the file containing the Component's input.
+        /// Use `-` to read the input from stdin. If neither `--input` nor `--input-file`
+        /// are specified and stdin is not a terminal, the input is read from stdin.
         #[arg(short('f'), long)]
         input_file: Option<PathBuf>,
 
@@ -115,7 +247,7 @@ pub(crate) enum Commands {
         common: Box<CommonRunArgs>,
 
         /// The optional path to save the Rig output to.
-        /// The path can be a `.png` file, a `.json` file, or a folder.
+        /// The path can be a `.png` file, a `.svg` file, a `.json` file, or a folder.
         #[arg(short, long)]
         output: Option<std::path::PathBuf>,
 
@@ -144,6 +276,20 @@ pub(crate) enum Commands {
         /// The optional folder path where additional fonts are located.
         #[arg(short, long)]
         fonts: Option<std::path::PathBuf>,
+
+        /// The number of characters of each hash to display in the component graph.
+        #[arg(long, default_value_t = slipway_host::render_state::DEFAULT_HASH_RENDER_CHAR_COUNT)]
+        hash_chars: usize,
+
+        /// Whether to use color in the component graph. By default color is used
+        /// unless the `NO_COLOR` environment variable is set or stdout is not a terminal.
+        #[arg(long, value_enum, default_value_t)]
+        color: ColorMode,
+
+        /// The units used for input and output sizes in the component graph:
+        /// `short` (multiples of 1024 labelled kb, mb), `binary` (KiB, MiB) or `decimal` (kB, MB).
+        #[arg(long, default_value = "short")]
+        byte_units: ByteUnits,
     },
 
     /// Create default configuration for a Component.
@@ -156,6 +302,10 @@ pub(crate) enum Commands {
         /// The Component name (lowercase alphanumeric plus underscores).
         #[arg(short, long)]
         name: Name,
+
+        /// The format of the Component definition file.
+        #[arg(long, value_enum, default_value_t)]
+        format: DefinitionFormat,
     },
 
     /// Create default configuration for a Rig.
@@ -164,6 +314,10 @@ pub(crate) enum Commands {
         /// The Rig name (lowercase alphanumeric plus underscores).
         #[arg(short, long)]
         name: RigName,
+
+        /// The format of the Rig file.
+        #[arg(long, value_enum, default_value_t)]
+        format: DefinitionFormat,
     },
 
     /// Serve HTTP requests. Use `slipway serve --help` for more commands.
@@ -180,25 +334,145 @@ pub(crate) enum Commands {
         #[arg(long, verbatim_doc_comment)]
         aot: bool,
 
+        /// Expose a developer preview page at /preview which lists
+        /// the devices, playlists and rigs along with their rendered output.
+        /// The page requires an API key, for example as an authorization
+        /// query string parameter.
+        #[arg(long, verbatim_doc_comment)]
+        preview: bool,
+
+        /// Open the preview page in a browser. Implies --preview.
+        #[arg(long)]
+        open: bool,
+
         #[command(subcommand)]
         subcommand: Option<ServeCommands>,
     },
 
+    /// Bundle a Slipway Rig and all of its Components into a single file,
+    /// which can be run with `slipway run` without access to any registry.
+    #[command(arg_required_else_help = true)]
+    Bundle {
+        /// The path to the Rig file.
+        /// Rig files with a `.yaml` or `.yml` extension are parsed as YAML,
+        /// and Rig files with a `.jsonc` extension may contain
+        /// comments and trailing commas.
+        rig: PathBuf,
+
+        /// The optional path to write the bundle to.
+        /// Defaults to the Rig path with a `.bundle` extension.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// The registry URL to interpolate and use in preference to the default registry.
+        /// Can be specified multiple times.
+        #[arg(short, long)]
+        registry: Vec<String>,
+
+        /// Credentials for downloading components from registries whose URLs are beneath
+        /// the given URL, in the format `url=bearer:TOKEN_ENV` or
+        /// `url=basic:USERNAME_ENV:PASSWORD_ENV`. Can be specified multiple times.
+        #[arg(long, value_parser = crate::registry_auth::parse_registry_auth)]
+        registry_auth: Vec<RegistryAuth>,
+
+        /// Allow git components to be fetched from `ssh://` and `file://` URLs.
+        #[arg(long)]
+        allow_non_http_git_urls: bool,
+
+        /// An explicit proxy URL for downloading Components. When not set the
+        /// `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables are used.
+        #[arg(long)]
+        proxy: Option<String>,
+
+        /// The log level (error, warn, info, debug, trace).
+        #[arg(short, long)]
+        log_level: Option<String>,
+    },
+
     /// Package up a Slipway Component into a .tar file.
     #[command(arg_required_else_help = true)]
     Package {
         /// The path to the directory containing the Component files.
         folder_path: PathBuf,
 
+        /// Fail if validating the Component manifest produces any warnings.
+        #[arg(long)]
+        strict: bool,
+
         /// The log level (error, warn, info, debug, trace).
         #[arg(short, long)]
         log_level: Option<String>,
     },
 
+    /// Test a Slipway Component by running the fixtures in its `tests` folder.
+    /// Each fixture is a JSON file containing an `input` and an `expected_output`,
+    /// and passes if running the Component with the input produces the expected output.
+    #[command(arg_required_else_help = true)]
+    Test {
+        /// The path to the directory containing the Component files.
+        component: PathBuf,
+
+        /// The registry URL to interpolate and use in preference to the default registry.
+        /// Can be specified multiple times.
+        #[arg(short, long)]
+        registry: Vec<String>,
+
+        /// Credentials for downloading components from registries whose URLs are beneath
+        /// the given URL, in the format `url=bearer:TOKEN_ENV` or
+        /// `url=basic:USERNAME_ENV:PASSWORD_ENV`. Can be specified multiple times.
+        #[arg(long, value_parser = crate::registry_auth::parse_registry_auth)]
+        registry_auth: Vec<RegistryAuth>,
+
+        /// Allow git components to be fetched from `ssh://` and `file://` URLs.
+        #[arg(long)]
+        allow_non_http_git_urls: bool,
+
+        /// An explicit proxy URL for downloading Components and for the HTTP requests
+        /// Components make. When not set the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`
+        /// environment variables are used.
+        #[arg(long)]
+        proxy: Option<String>,
+
+        /// The log level (error, warn, info, debug, trace).
+        #[arg(short, long)]
+        log_level: Option<String>,
+
+        #[command(flatten)]
+        permissions: CommonPermissionsArgs,
+    },
+
+    /// Check the environment for common problems, such as an unwritable
+    /// Component cache or an unreachable registry.
+    Doctor {
+        /// An additional registry URL to check, alongside the default registry.
+        /// Can be specified multiple times.
+        #[arg(short, long)]
+        registry: Vec<String>,
+
+        /// Whether to use color in the checklist. By default color is used
+        /// unless the `NO_COLOR` environment variable is set or stdout is not a terminal.
+        #[arg(long, value_enum, default_value_t)]
+        color: ColorMode,
+    },
+
     /// Clear the local Component cache of downloaded Components (by default located in ~/.slipway).
     #[command()]
     ClearComponentCache,
 
+    /// List the Components in the local Component cache, with their sizes and when they
+    /// were last used, optionally pruning Components which haven't been used recently.
+    #[command()]
+    ListComponentCache {
+        /// Delete cached Components which haven't been used within the `--older-than` duration.
+        #[arg(long, requires = "older_than")]
+        prune: bool,
+
+        /// How long since a Component was last used before it is pruned,
+        /// for example `30d`, `12h`, `90m` or `45s`.
+        #[arg(long, requires = "prune", value_parser = crate::utils::parse_duration)]
+        older_than: Option<std::time::Duration>,
+    },
+
     /// Generates a long, random key, suitable for use as an API key or for the SLIPWAY_SECRET
     /// environment variable.
     #[command(arg_required_else_help = false)]
@@ -214,7 +488,19 @@ pub(crate) enum Commands {
 
     /// Output the WIT (WASM Interface Type) definition, for building Slipway Components.
     #[command()]
-    Wit,
+    Wit {
+        /// The version of the WIT to output, as listed by `--list`.
+        /// Defaults to the latest version.
+        wit_version: Option<String>,
+
+        /// Output the version of the latest WIT, rather than the WIT itself.
+        #[arg(long, conflicts_with_all = ["wit_version", "list"])]
+        version: bool,
+
+        /// List the versions of the WIT bundled with Slipway, newest first.
+        #[arg(long, conflicts_with = "wit_version")]
+        list: bool,
+    },
 
     /// Output the current Slipway version.
     #[command()]
@@ -231,7 +517,19 @@ enum ServeCommands {
     InitConfig,
 
     /// Download all required Components to the local Components folder.
-    Consolidate,
+    Consolidate {
+        /// After consolidating, check that every Component could be run,
+        /// by compiling WASM Components and parsing Javascript Components,
+        /// and report which Components passed or failed.
+        #[arg(long)]
+        verify: bool,
+
+        /// Stop at the first Component which fails to load. Use `--fail-fast=false`
+        /// to continue past failures and report every failed Component at the end,
+        /// still exiting with an error if any failed.
+        #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+        fail_fast: bool,
+    },
 
     /// Try to ahead-of-time compile any WASM Components.
     AotCompile {
@@ -239,6 +537,16 @@ enum ServeCommands {
         /// This is a target triple, such as `x86_64-unknown-linux-gnu`.
         #[arg(short, long)]
         target: Option<String>,
+
+        /// Optional folder to also write the AOT compiled artifacts to, named as
+        /// `{hash}.{target}.wasm_aot` so the folder can be published as an AOT registry.
+        /// Each artifact is signed with the `aot_registry_key` from the serve configuration.
+        #[arg(short, long)]
+        publish: Option<PathBuf>,
+
+        /// Recompile components even if an up to date AOT compiled artifact exists.
+        #[arg(long)]
+        force: bool,
     },
 
     /// Add a device to use when serving HTTP requests.
@@ -310,6 +618,13 @@ struct CommonRunArgs {
     #[arg(short, long)]
     log_level: Option<String>,
 
+    /// Log everything at the trace level within the Component with this handle,
+    /// including its input, output, duration and host calls, while other
+    /// Components log at the global log level.
+    /// This can be specified multiple times to trace several Components.
+    #[arg(long)]
+    trace_component: Vec<ComponentHandle>,
+
     /// The registry URL to interpolate and use in preference to the default registry.
     /// This can be specified multiple times to search multiple registries in order.
     /// For example:
@@ -318,10 +633,147 @@ struct CommonRunArgs {
     #[arg(short, long, verbatim_doc_comment)]
     registry: Vec<String>,
 
+    /// A local directory of component folders named `{publisher}.{name}`, which is
+    /// searched before any registry. Components not found in the directory are
+    /// loaded from the registries as normal.
+    /// This can be specified multiple times to search multiple directories in order.
+    #[arg(long)]
+    components_dir: Vec<PathBuf>,
+
+    /// Credentials for downloading components from registries whose URLs are beneath
+    /// the given URL, read from the named environment variables.
+    /// This can be specified multiple times. For example:
+    ///   https://registry.example.com/=bearer:REGISTRY_TOKEN
+    ///   https://private.example.com/=basic:REGISTRY_USER:REGISTRY_PASSWORD
+    #[arg(long, verbatim_doc_comment, value_parser = crate::registry_auth::parse_registry_auth)]
+    registry_auth: Vec<RegistryAuth>,
+
+    /// Allow git components to be fetched from `ssh://` and `file://` URLs.
+    /// By default only `https://` and `http://` git URLs are allowed.
+    #[arg(long)]
+    allow_non_http_git_urls: bool,
+
+    /// An explicit proxy URL for downloading Components and for the HTTP requests
+    /// Components make. When not set the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`
+    /// environment variables are used.
+    #[arg(long)]
+    proxy: Option<String>,
+
+    /// Treat component warnings as errors.
+    #[arg(long)]
+    deny_warnings: bool,
+
+    /// Record HTTP requests and responses to this cassette file, or replay them
+    /// from it if the file already exists.
+    #[arg(long, env = "SLIPWAY_HTTP_CASSETTE")]
+    http_cassette: Option<PathBuf>,
+
+    /// A header to redact when recording to the HTTP cassette.
+    /// The Authorization header is always redacted.
+    /// This can be specified multiple times.
+    #[arg(long)]
+    http_cassette_redact_header: Vec<String>,
+
+    /// Leniently coerce component outputs to the types in their output schemas,
+    /// for example turning "42" into 42 where a number is expected.
+    /// Values which cannot be safely coerced still fail validation.
+    #[arg(long)]
+    coerce_outputs: bool,
+
+    /// Log outgoing HTTP requests and their responses, with secrets redacted
+    /// and bodies truncated. Requires a log level of `trace`.
+    #[arg(long)]
+    log_http: bool,
+
+    /// When a Component is denied access to a resource, explain which permission
+    /// caused the denial at each level of the call chain, including the `allow`
+    /// and `deny` permissions of each level.
+    #[arg(long)]
+    explain_permissions: bool,
+
+    /// Log each permission checked host call a Component makes, such as HTTP fetches,
+    /// environment variable reads, file loads and callouts, along with whether it
+    /// was allowed. Entries are written at the info level to the `slipway_audit` target.
+    #[arg(long)]
+    audit_log: bool,
+
+    /// The maximum size of each Component's output, in bytes of serialized JSON.
+    /// Components returning larger outputs fail, protecting the host from
+    /// Components which return excessively large outputs.
+    #[arg(long)]
+    max_output_bytes: Option<usize>,
+
+    /// The maximum time each Javascript Component can run for, for example `30s`
+    /// or `5m`. The limit is checked each time the Component calls the host,
+    /// and when it finishes.
+    #[arg(long, value_parser = crate::utils::parse_duration)]
+    js_time_limit: Option<std::time::Duration>,
+
+    /// The maximum number of iterations of any single loop in a Javascript
+    /// Component, which stops loops that never call the host.
+    #[arg(long)]
+    js_step_limit: Option<u64>,
+
+    /// The maximum number of Components which can execute concurrently across the rig,
+    /// including callouts and fragments. Components beyond the limit wait for others
+    /// to finish, rather than failing.
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    max_concurrent_components: Option<usize>,
+
+    /// The algorithm used to hash Component inputs and outputs, either `sha256`
+    /// or `blake3`. BLAKE3 is faster for Components with large inputs or outputs.
+    #[arg(long, default_value = "sha256")]
+    hash_algorithm: HashAlgorithm,
+
+    /// Check the registries for newer versions of the rig's registry Components,
+    /// and warn about any which are out of date. This is off by default so that
+    /// rigs can be run offline, and never causes the run to fail.
+    /// Only the next major, minor and patch versions of each Component are checked.
+    #[arg(long)]
+    check_updates: bool,
+
     #[command(flatten)]
     permissions: CommonPermissionsArgs,
 }
 
+impl CommonRunArgs {
+    /// Configures tracing, and returns the run options and permissions given by the arguments.
+    fn into_run_options(
+        self,
+        fonts_path: Option<PathBuf>,
+    ) -> anyhow::Result<(RunOptions, PermissionsOwned)> {
+        configure_component_tracing(self.log_level, self.trace_component);
+
+        let options = RunOptions {
+            registry_urls: crate::utils::registry_urls_with_components_dirs(
+                self.components_dir,
+                self.registry,
+            )?,
+            registry_auth: self.registry_auth,
+            allow_non_http_git_urls: self.allow_non_http_git_urls,
+            proxy: self.proxy,
+            deny_warnings: self.deny_warnings,
+            http_cassette: crate::utils::load_http_cassette(
+                self.http_cassette,
+                self.http_cassette_redact_header,
+            )?,
+            coerce_outputs: self.coerce_outputs,
+            log_http: self.log_http,
+            explain_permissions: self.explain_permissions,
+            audit_log: self.audit_log,
+            max_output_bytes: self.max_output_bytes,
+            js_time_limit: self.js_time_limit,
+            js_step_limit: self.js_step_limit,
+            max_concurrent_components: self.max_concurrent_components,
+            hash_algorithm: self.hash_algorithm,
+            check_updates: self.check_updates,
+            fonts_path,
+        };
+
+        Ok((options, self.permissions.into_permissions()?))
+    }
+}
+
 enum RuntimeType {
     TokioSingleThread,
     Actix,
@@ -367,34 +819,65 @@ async fn main_single_threaded(args: Cli) -> anyhow::Result<()> {
             common,
             output,
             output_debug_rig,
+            profile,
+            summary,
             fonts,
+            component,
+            until,
+            seed_output,
+            continue_on_error,
+            explain,
+            explain_format,
+            overlay,
+            set_constant,
         } => {
-            let log_level = common.log_level;
-            let registry_url = common.registry;
-            configure_tracing(log_level);
-            let permissions = common.permissions.into_permissions()?;
+            let (options, permissions) = common.into_run_options(fonts)?;
             run_rig::run_rig(
                 Box::new(std::io::stdout()),
                 rig,
                 (&permissions).into(),
-                registry_url,
-                output,
-                output_debug_rig,
-                fonts,
+                overlay,
+                set_constant,
+                options,
+                run_rig::RunRigOutputOptions {
+                    save_path: output,
+                    debug_rig_path: output_debug_rig,
+                    profile_path: profile,
+                    output_component: component,
+                    until,
+                    seed_outputs: seed_output,
+                    continue_on_error,
+                    explain: explain.map(|handle| Explain {
+                        handle,
+                        format: explain_format,
+                    }),
+                    summary_path: summary,
+                },
             )
             .await?;
         }
-        Commands::Debug { rig, common, fonts } => {
-            let log_level = common.log_level;
-            let registry_url = common.registry;
-            configure_tracing(log_level);
-            let permissions = common.permissions.into_permissions()?;
+        Commands::Debug {
+            rig,
+            common,
+            fonts,
+            overlay,
+            set_constant,
+            hash_chars,
+            color,
+            byte_units,
+        } => {
+            let (options, permissions) = common.into_run_options(fonts)?;
+            let render_options = RenderStateOptions::with_hash_chars(hash_chars)
+                .color(color.use_color())
+                .byte_units(byte_units);
             debug_rig::debug_rig_from_rig_file(
                 &mut std::io::stdout(),
                 rig,
                 (&permissions).into(),
-                registry_url,
-                fonts,
+                overlay,
+                set_constant,
+                options,
+                render_options,
             )
             .await?;
         }
@@ -406,19 +889,15 @@ async fn main_single_threaded(args: Cli) -> anyhow::Result<()> {
             output,
             fonts,
         } => {
-            let log_level = common.log_level;
-            let registry_url = common.registry;
-            configure_tracing(log_level);
-            let permissions = common.permissions.into_permissions()?;
+            let (options, permissions) = common.into_run_options(fonts)?;
             run_rig::run_rig_from_component_file(
                 Box::new(std::io::stdout()),
                 component,
                 input,
                 input_file,
                 (&permissions).into(),
-                registry_url,
+                options,
                 output,
-                fonts,
             )
             .await?;
         }
@@ -428,23 +907,30 @@ async fn main_single_threaded(args: Cli) -> anyhow::Result<()> {
             input_file,
             common,
             fonts,
+            hash_chars,
+            color,
+            byte_units,
         } => {
-            let log_level = common.log_level;
-            let registry_url = common.registry;
-            configure_tracing(log_level);
-            let permissions = common.permissions.into_permissions()?;
+            let (options, permissions) = common.into_run_options(fonts)?;
+            let render_options = RenderStateOptions::with_hash_chars(hash_chars)
+                .color(color.use_color())
+                .byte_units(byte_units);
             debug_rig::debug_rig_from_component_file(
                 &mut std::io::stdout(),
                 component,
                 input,
                 input_file,
                 (&permissions).into(),
-                registry_url,
-                fonts,
+                options,
+                render_options,
             )
             .await?;
         }
-        Commands::InitComponent { publisher, name } => {
+        Commands::InitComponent {
+            publisher,
+            name,
+            format,
+        } => {
             let component = slipway_engine::Component {
                 publisher,
                 name,
@@ -454,15 +940,23 @@ async fn main_single_threaded(args: Cli) -> anyhow::Result<()> {
                 output: serde_json::Value::Object(Default::default()),
                 constants: None,
                 rigging: None,
+                fragment_inputs: None,
+                fragment_input: None,
+                fragment_output: None,
+                permissions: None,
+                batchable: None,
+                output_content_type: None,
+                fonts: None,
                 callouts: None,
             };
 
-            serde_json::to_writer_pretty(
-                std::fs::File::create(SLIPWAY_COMPONENT_FILE_NAME)?,
-                &component,
-            )?;
+            let file_name = match format {
+                DefinitionFormat::Json => SLIPWAY_COMPONENT_FILE_NAME,
+                DefinitionFormat::Yaml => SLIPWAY_COMPONENT_YAML_FILE_NAMES[0],
+            };
+            format.write_file(Path::new(file_name), &component)?;
         }
-        Commands::InitRig { name } => {
+        Commands::InitRig { name, format } => {
             let rig = slipway_engine::Rig {
                 description: None,
                 constants: None,
@@ -470,21 +964,79 @@ async fn main_single_threaded(args: Cli) -> anyhow::Result<()> {
                     components: Default::default(),
                 },
                 context: None,
+                outputs: None,
+                templates: None,
             };
 
-            serde_json::to_writer_pretty(std::fs::File::create(name.to_string() + ".json")?, &rig)?;
+            let file_name = format!("{}.{}", name, format.extension());
+            format.write_file(Path::new(&file_name), &rig)?;
+        }
+        Commands::Bundle {
+            rig,
+            output,
+            registry,
+            registry_auth,
+            allow_non_http_git_urls,
+            proxy,
+            log_level,
+        } => {
+            configure_tracing(log_level);
+            bundle::bundle_rig(
+                &rig,
+                output,
+                registry,
+                registry_auth,
+                allow_non_http_git_urls,
+                proxy,
+            )
+            .await?;
         }
         Commands::Package {
             folder_path,
+            strict,
             log_level,
         } => {
             configure_tracing(log_level);
-            package::package_component(&folder_path)?;
+            package::package_component(&folder_path, strict).await?;
+        }
+        Commands::Test {
+            component,
+            registry,
+            registry_auth,
+            allow_non_http_git_urls,
+            proxy,
+            log_level,
+            permissions,
+        } => {
+            configure_tracing(log_level);
+            let permissions = permissions.into_permissions()?;
+            test_component::test_component(
+                &mut std::io::stdout(),
+                component,
+                (&permissions).into(),
+                registry,
+                registry_auth,
+                allow_non_http_git_urls,
+                proxy,
+            )
+            .await?;
+        }
+        Commands::Doctor { registry, color } => {
+            configure_tracing(Some("warn".to_string()));
+            doctor::doctor(&mut std::io::stdout(), registry, color).await?;
         }
         Commands::ClearComponentCache => {
             configure_tracing(Default::default());
             clear_components_cache();
         }
+        Commands::ListComponentCache { older_than, .. } => {
+            configure_tracing(Default::default());
+            component_cache::list_component_cache(
+                &mut std::io::stdout(),
+                &get_default_slipway_components_cache_dir(),
+                older_than,
+            )?;
+        }
         Commands::GenerateKey => {
             configure_tracing(Default::default());
             let key = serve::create_api_key();
@@ -507,17 +1059,27 @@ async fn main_single_threaded(args: Cli) -> anyhow::Result<()> {
             }
             println!("{}", hash_string(&value));
         }
-        Commands::Wit => {
-            println!("{}", WASM_INTERFACE_TYPE_STR);
+        Commands::Wit {
+            wit_version,
+            version,
+            list,
+        } => {
+            if version {
+                println!("{}", wit::current_wit_version()?);
+            } else if list {
+                for wit_version in wit::list_wit_versions()? {
+                    println!("{}", wit_version);
+                }
+            } else {
+                println!("{}", wit::get_wit(wit_version.as_deref())?);
+            }
         }
         Commands::Version => {
             let version = env!("CARGO_PKG_VERSION");
             println!("{}", version);
         }
         Commands::Serve {
-            path,
-            aot: _,
-            subcommand,
+            path, subcommand, ..
         } => match subcommand {
             Some(ServeCommands::Init) => {
                 configure_tracing(Default::default());
@@ -527,15 +1089,30 @@ async fn main_single_threaded(args: Cli) -> anyhow::Result<()> {
                 configure_tracing(Default::default());
                 serve::commands::init_serve_config(&path).await?;
             }
-            Some(ServeCommands::Consolidate) => {
+            Some(ServeCommands::Consolidate { verify, fail_fast }) => {
                 configure_tracing(Some("debug".to_string()));
-                serve::commands::consolidate(path).await?;
+                let cache = serve::commands::consolidate(path.clone(), fail_fast).await?;
+                if verify {
+                    serve::commands::verify(&path, cache).await?;
+                }
             }
-            Some(ServeCommands::AotCompile { target }) => {
+            Some(ServeCommands::AotCompile {
+                target,
+                publish,
+                force,
+            }) => {
                 configure_tracing(Some("debug".to_string()));
                 let aot_path = path.join(AOT_ARTIFACT_FOLDER_NAME);
-                let cache = serve::commands::consolidate(path.clone()).await?;
-                serve::commands::aot_compile(aot_path, target.as_deref(), cache).await?;
+                let cache = serve::commands::consolidate(path.clone(), true).await?;
+                serve::commands::aot_compile(
+                    &path,
+                    aot_path,
+                    target.as_deref(),
+                    force,
+                    publish,
+                    cache,
+                )
+                .await?;
             }
             Some(ServeCommands::AddDevice { name, playlist }) => {
                 configure_tracing(Default::default());
@@ -585,6 +1162,8 @@ async fn main_actix_web(args: Cli) -> anyhow::Result<()> {
         Commands::Serve {
             path,
             aot,
+            preview,
+            open,
             subcommand: None,
         } => {
             let aot_path = if aot {
@@ -592,7 +1171,7 @@ async fn main_actix_web(args: Cli) -> anyhow::Result<()> {
             } else {
                 None
             };
-            serve::serve(path, aot_path).await?;
+            serve::serve(path, aot_path, preview, open).await?;
         }
         _ => {
             panic!("Command is not supported in actix-web mode.");
@@ -621,6 +1200,12 @@ impl FormatTime for CustomTimer {
 }
 
 fn configure_tracing(log_level: Option<String>) {
+    configure_component_tracing(log_level, vec![]);
+}
+
+/// Configures tracing at the given log level, except within the traced Components
+/// where everything is logged at the trace level.
+fn configure_component_tracing(log_level: Option<String>, traced_components: Vec<ComponentHandle>) {
     let log_level = match log_level.map(|level| level.to_lowercase()).as_deref() {
         Some("error") => Level::ERROR,
         Some("warn") => Level::WARN,
@@ -631,11 +1216,29 @@ fn configure_tracing(log_level: Option<String>) {
         _ => Level::INFO,
     };
 
-    let subscriber = FmtSubscriber::builder()
-        .with_target(false)
-        .with_timer(CustomTimer)
-        .with_max_level(log_level)
-        .finish();
+    if traced_components.is_empty() {
+        let subscriber = FmtSubscriber::builder()
+            .with_target(false)
+            .with_timer(CustomTimer)
+            .with_max_level(log_level)
+            .finish();
+
+        tracing::subscriber::set_global_default(subscriber)
+            .expect("setting default subscriber failed");
+        return;
+    }
+
+    let traced_components_layer = component_tracing::TracedComponentsLayer::new(traced_components);
+    let subscriber = tracing_subscriber::registry()
+        .with(traced_components_layer)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_target(false)
+                .with_timer(CustomTimer)
+                .with_filter(DynFilterFn::new(move |metadata, ctx| {
+                    component_tracing::is_enabled(metadata, ctx, log_level)
+                })),
+        );
 
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 }
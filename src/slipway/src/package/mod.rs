@@ -0,0 +1,148 @@
+mod slipway_ignore;
+
+use std::{fs::File, io::BufReader, path::Path};
+
+use slipway_engine::{
+    BasicComponentsLoader, ComponentsLoader, SlipwayReference, validate_component,
+};
+use tar::Builder;
+use tracing::{error, info, warn};
+use walkdir::WalkDir;
+
+use crate::{SLIPWAY_COMPONENT_FILE_NAME, SLIPWAY_COMPONENT_YAML_FILE_NAMES};
+
+use slipway_ignore::{SLIPWAY_IGNORE_FILE_NAME, SlipwayIgnore};
+
+pub async fn package_component(path: &Path, strict: bool) -> anyhow::Result<()> {
+    if !path.exists() {
+        error!("Path does not exist: {:?}", path);
+        return Ok(());
+    }
+
+    if !path.is_dir() {
+        error!("Path is not a directory: {:?}", path);
+        return Ok(());
+    }
+
+    let file_path = path.join(SLIPWAY_COMPONENT_FILE_NAME);
+    let yaml_file_path = SLIPWAY_COMPONENT_YAML_FILE_NAMES
+        .iter()
+        .map(|file_name| path.join(file_name))
+        .find(|yaml_file_path| yaml_file_path.exists());
+
+    if !file_path.exists() && yaml_file_path.is_none() {
+        error!("Component file does not exist: {:?}", file_path);
+        return Ok(());
+    }
+
+    validate_manifest(path, strict).await?;
+
+    let json: serde_json::Value = match yaml_file_path {
+        Some(yaml_file_path) if !file_path.exists() => {
+            slipway_engine::yaml_to_json(&std::fs::read_to_string(yaml_file_path)?)?
+        }
+        _ => {
+            let file = File::open(file_path)?;
+            let reader = BufReader::new(file);
+            serde_json::from_reader(reader)?
+        }
+    };
+
+    let publisher = json["publisher"].as_str().unwrap_or_default();
+    let name = json["name"].as_str().unwrap_or_default();
+    let version = json["version"].as_str().unwrap_or_default();
+
+    let tar_name = format!("{}.{}.{}.tar", publisher, name, version);
+
+    let tar_path = match path.parent() {
+        Some(parent) => parent.join(tar_name),
+        None => {
+            warn!(
+                "Failed to get component parent folder for, so writing .tar file to component folder."
+            );
+            path.join(tar_name)
+        }
+    };
+
+    let ignore = SlipwayIgnore::load(path)?;
+
+    let tar_file = File::create(&tar_path)?;
+    let mut tar_builder = Builder::new(tar_file);
+    let mut packaged_files = Vec::new();
+
+    let walker = WalkDir::new(path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| match entry.path().strip_prefix(path) {
+            Ok(rel_path) if rel_path.as_os_str().is_empty() => true,
+            Ok(rel_path) => !ignore.is_ignored(rel_path, entry.file_type().is_dir()),
+            Err(_) => true,
+        });
+
+    for entry in walker {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            let rel_path = entry.path().strip_prefix(path)?;
+            if rel_path == Path::new(SLIPWAY_IGNORE_FILE_NAME) {
+                continue;
+            }
+            if let Some(definition) =
+                crate::utils::try_convert_yaml_component_definition(rel_path, entry.path())?
+            {
+                crate::utils::append_bytes(
+                    &mut tar_builder,
+                    Path::new(SLIPWAY_COMPONENT_FILE_NAME),
+                    &definition,
+                )?;
+                packaged_files.push(SLIPWAY_COMPONENT_FILE_NAME.to_string());
+                continue;
+            }
+            tar_builder.append_path_with_name(entry.path(), rel_path)?;
+            packaged_files.push(rel_path.display().to_string());
+        }
+    }
+
+    tar_builder.finish()?;
+
+    info!("Packaged {} files:", packaged_files.len());
+    for file in packaged_files.iter() {
+        info!("  {file}");
+    }
+
+    info!("Written component tar file to: {}", tar_path.display());
+
+    Ok(())
+}
+
+/// Validates the component manifest in the same way as loading the component would,
+/// so that broken components are caught before they are published.
+async fn validate_manifest(path: &Path, strict: bool) -> anyhow::Result<()> {
+    let reference = SlipwayReference::Local {
+        path: std::path::absolute(path)?,
+    };
+
+    let components_loader = BasicComponentsLoader::builder()
+        .without_default_registry()
+        .build();
+
+    let loaded_component = components_loader
+        .load_components(std::slice::from_ref(&reference))
+        .await
+        .pop()
+        .expect("loader should return one result per reference")?;
+
+    let warnings = validate_component(&loaded_component).await?;
+
+    for warning in warnings.iter() {
+        warn!("{warning}");
+    }
+
+    if strict && !warnings.is_empty() {
+        anyhow::bail!(
+            "Component manifest has {} warning(s), and --strict was specified.",
+            warnings.len()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use anyhow::Context;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+pub(super) const SLIPWAY_IGNORE_FILE_NAME: &str = ".slipwayignore";
+
+const DEFAULT_IGNORE_PATTERNS: [&str; 3] = ["target/", "node_modules/", ".git/"];
+
+/// A set of gitignore patterns determining which files are excluded
+/// when packaging a component.
+///
+/// The default patterns always apply, and the patterns in the `.slipwayignore`
+/// file are added after them, so the defaults can be overridden by negating
+/// them, for example with `!target/`. As with git, the last matching pattern wins.
+pub(super) struct SlipwayIgnore {
+    gitignore: Gitignore,
+}
+
+impl SlipwayIgnore {
+    /// Loads the `.slipwayignore` file from the component folder, if it exists,
+    /// adding its patterns to the default ignore list.
+    pub fn load(component_path: &Path) -> anyhow::Result<Self> {
+        let ignore_path = component_path.join(SLIPWAY_IGNORE_FILE_NAME);
+
+        if !ignore_path.exists() {
+            return Self::parse("");
+        }
+
+        let content = std::fs::read_to_string(&ignore_path)
+            .with_context(|| format!("Failed to read {}", ignore_path.display()))?;
+
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        // Paths are matched relative to the component folder, so the root is empty.
+        let mut builder = GitignoreBuilder::new("");
+
+        for line in DEFAULT_IGNORE_PATTERNS.into_iter().chain(content.lines()) {
+            builder
+                .add_line(None, line)
+                .with_context(|| format!("Invalid {SLIPWAY_IGNORE_FILE_NAME} pattern: {line}"))?;
+        }
+
+        let gitignore = builder
+            .build()
+            .with_context(|| format!("Failed to build {SLIPWAY_IGNORE_FILE_NAME} patterns"))?;
+
+        Ok(SlipwayIgnore { gitignore })
+    }
+
+    /// Returns whether the given path, relative to the component folder, is ignored.
+    pub fn is_ignored(&self, relative_path: &Path, is_dir: bool) -> bool {
+        self.gitignore.matched(relative_path, is_dir).is_ignore()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_ignore_default_directories() {
+        let ignore = SlipwayIgnore::parse("").unwrap();
+
+        assert!(ignore.is_ignored(Path::new("target"), true));
+        assert!(ignore.is_ignored(Path::new("src/node_modules"), true));
+        assert!(ignore.is_ignored(Path::new(".git"), true));
+        assert!(!ignore.is_ignored(Path::new("target"), false));
+        assert!(!ignore.is_ignored(Path::new("slipway_component.json"), false));
+    }
+
+    #[test]
+    fn it_should_keep_default_directories_unless_negated() {
+        let ignore = SlipwayIgnore::parse("*.log\n!node_modules/").unwrap();
+
+        assert!(ignore.is_ignored(Path::new("debug.log"), false));
+        assert!(ignore.is_ignored(Path::new("target"), true));
+        assert!(ignore.is_ignored(Path::new(".git"), true));
+        assert!(!ignore.is_ignored(Path::new("node_modules"), true));
+    }
+
+    #[test]
+    fn it_should_support_gitignore_syntax() {
+        let ignore = SlipwayIgnore::parse(
+            r#"
+            # Comment
+            *.log
+            !keep.log
+            /build
+            docs/**/*.md
+            src/[a-c].rs
+            "#
+            .lines()
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .as_str(),
+        )
+        .unwrap();
+
+        assert!(ignore.is_ignored(Path::new("debug.log"), false));
+        assert!(ignore.is_ignored(Path::new("logs/debug.log"), false));
+        assert!(!ignore.is_ignored(Path::new("keep.log"), false));
+
+        assert!(ignore.is_ignored(Path::new("build"), true));
+        assert!(!ignore.is_ignored(Path::new("src/build"), true));
+
+        assert!(ignore.is_ignored(Path::new("docs/readme.md"), false));
+        assert!(ignore.is_ignored(Path::new("docs/a/b/readme.md"), false));
+        assert!(!ignore.is_ignored(Path::new("readme.md"), false));
+
+        assert!(ignore.is_ignored(Path::new("src/b.rs"), false));
+        assert!(!ignore.is_ignored(Path::new("src/d.rs"), false));
+    }
+}
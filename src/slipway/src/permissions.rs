@@ -37,7 +37,7 @@ impl<'a> From<&'a PermissionsOwned> for slipway_engine::Permissions<'a> {
 macro_rules! create_url_permissions {
     ($permission_name:ident, $name:ident, $doc_name:expr) => {
         paste! {
-            #[derive(Debug, Args)]
+            #[derive(Debug, Default, Args)]
             pub(super) struct $permission_name {
                 #[doc = "Allow any " $doc_name "s at the rig level."]
                 #[arg(long)]
@@ -70,7 +70,7 @@ macro_rules! create_url_permissions {
 macro_rules! create_path_permissions {
     ($permission_name:ident, $name:ident, $doc_name:expr) => {
         paste! {
-            #[derive(Debug, Args)]
+            #[derive(Debug, Default, Args)]
             pub(super) struct $permission_name {
                 #[doc = "Allow any " $doc_name "s at the rig level."]
                 #[arg(long)]
@@ -103,7 +103,7 @@ macro_rules! create_path_permissions {
 macro_rules! create_string_permissions {
     ($permission_name:ident, $name:ident, $doc_name:expr) => {
         paste! {
-            #[derive(Debug, Args)]
+            #[derive(Debug, Default, Args)]
             pub(super) struct $permission_name {
                 #[doc = "Allow any " $doc_name "s at the rig level."]
                 #[arg(long)]
@@ -144,7 +144,7 @@ macro_rules! create_string_permissions {
 macro_rules! create_simple_string_permissions {
     ($permission_name:ident, $name:ident, $doc_name:expr) => {
         paste! {
-            #[derive(Debug, Args)]
+            #[derive(Debug, Default, Args)]
             pub(super) struct $permission_name {
                 #[doc = "Allow any " $doc_name "s at the rig level."]
                 #[arg(long)]
@@ -175,13 +175,14 @@ create_url_permissions!(
     http_components,
     "HTTP component"
 );
+create_url_permissions!(GitComponentPermissionArgs, git_components, "git component");
 create_simple_string_permissions!(
     LocalComponentPermissionArgs,
     local_components,
     "local component"
 );
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Args)]
 pub(super) struct RegistryComponentPermissionArgs {
     /// Allow any registry components at the rig level.
     #[arg(long)]
@@ -209,7 +210,7 @@ pub(super) struct RegistryComponentPermissionArgs {
     deny_registry_components_matching: Vec<String>,
 }
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Args)]
 pub(super) struct CommonPermissionsArgs {
     /// Allow all permissions at the rig level.
     #[arg(long)]
@@ -234,6 +235,9 @@ pub(super) struct CommonPermissionsArgs {
     #[command(flatten)]
     http_components: HttpComponentPermissionArgs,
 
+    #[command(flatten)]
+    git_components: GitComponentPermissionArgs,
+
     #[command(flatten)]
     local_components: LocalComponentPermissionArgs,
 
@@ -323,6 +327,19 @@ impl CommonPermissionsArgs {
             self.http_components.deny_http_components_prefix,
         );
 
+        // Git Components
+        add_url_permissions(
+            &mut allow,
+            &mut deny,
+            Permission::GitComponents,
+            self.git_components.allow_git_components,
+            self.git_components.allow_git_components_exact,
+            self.git_components.allow_git_components_prefix,
+            self.git_components.deny_git_components,
+            self.git_components.deny_git_components_exact,
+            self.git_components.deny_git_components_prefix,
+        );
+
         // Local Components
         add_local_component_permissions(
             &mut allow,
@@ -631,6 +648,7 @@ mod tests {
                 deny_http_components_exact: vec![],
                 deny_http_components_prefix: vec![],
             },
+            git_components: GitComponentPermissionArgs::default(),
             local_components: LocalComponentPermissionArgs {
                 allow_local_components: true,
                 allow_local_components_exact: vec![],
@@ -716,6 +734,7 @@ mod tests {
                 deny_http_components_exact: vec![],
                 deny_http_components_prefix: vec![],
             },
+            git_components: GitComponentPermissionArgs::default(),
             local_components: LocalComponentPermissionArgs {
                 allow_local_components: false,
                 allow_local_components_exact: vec![],
@@ -801,6 +820,7 @@ mod tests {
                 deny_http_components_exact: vec![],
                 deny_http_components_prefix: vec![],
             },
+            git_components: GitComponentPermissionArgs::default(),
             local_components: LocalComponentPermissionArgs {
                 allow_local_components: false,
                 allow_local_components_exact: vec![],
@@ -893,6 +913,7 @@ mod tests {
                 deny_http_components_exact: vec![],
                 deny_http_components_prefix: vec![],
             },
+            git_components: GitComponentPermissionArgs::default(),
             local_components: LocalComponentPermissionArgs {
                 allow_local_components: false,
                 allow_local_components_exact: vec![],
@@ -985,6 +1006,7 @@ mod tests {
                 deny_http_components_exact: vec![],
                 deny_http_components_prefix: vec![],
             },
+            git_components: GitComponentPermissionArgs::default(),
             local_components: LocalComponentPermissionArgs {
                 allow_local_components: false,
                 allow_local_components_exact: vec![],
@@ -1082,6 +1104,7 @@ mod tests {
                 deny_http_components_exact: vec![],
                 deny_http_components_prefix: vec![],
             },
+            git_components: GitComponentPermissionArgs::default(),
             local_components: LocalComponentPermissionArgs {
                 allow_local_components: false,
                 allow_local_components_exact: vec![],
@@ -1179,6 +1202,7 @@ mod tests {
                 deny_http_components_exact: vec![Url::parse("https://example3.com").unwrap()],
                 deny_http_components_prefix: vec![Url::parse("https://example4.com").unwrap()],
             },
+            git_components: GitComponentPermissionArgs::default(),
             local_components: LocalComponentPermissionArgs {
                 allow_local_components: false,
                 allow_local_components_exact: vec![],
@@ -1222,6 +1246,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_git_component_permissions() {
+        let url = Url::parse("https://github.com/slipwayhq/example.git").unwrap();
+        let prefix = Url::parse("https://github.com/slipwayhq/").unwrap();
+
+        let cases = [
+            (
+                GitComponentPermissionArgs {
+                    allow_git_components: true,
+                    ..Default::default()
+                },
+                vec![Permission::GitComponents(UrlPermission::Any {})],
+                vec![],
+            ),
+            (
+                GitComponentPermissionArgs {
+                    allow_git_components_exact: vec![url.clone()],
+                    deny_git_components_prefix: vec![prefix.clone()],
+                    ..Default::default()
+                },
+                vec![Permission::GitComponents(UrlPermission::Exact {
+                    exact: url.clone(),
+                })],
+                vec![Permission::GitComponents(UrlPermission::Prefix {
+                    prefix: prefix.clone(),
+                })],
+            ),
+            (
+                GitComponentPermissionArgs {
+                    allow_git_components_prefix: vec![prefix.clone()],
+                    deny_git_components: true,
+                    deny_git_components_exact: vec![url.clone()],
+                    ..Default::default()
+                },
+                vec![Permission::GitComponents(UrlPermission::Prefix {
+                    prefix: prefix.clone(),
+                })],
+                vec![
+                    Permission::GitComponents(UrlPermission::Any {}),
+                    Permission::GitComponents(UrlPermission::Exact { exact: url.clone() }),
+                ],
+            ),
+        ];
+
+        for (git_components, expected_allow, expected_deny) in cases {
+            let args = CommonPermissionsArgs {
+                git_components,
+                ..Default::default()
+            };
+
+            let permissions = args.into_permissions().unwrap();
+
+            assert_eq!(permissions.allow, expected_allow);
+            assert_eq!(permissions.deny, expected_deny);
+        }
+    }
+
     #[test]
     fn test_local_component_permissions() {
         let args = CommonPermissionsArgs {
@@ -1271,6 +1352,7 @@ mod tests {
                 deny_http_components_exact: vec![],
                 deny_http_components_prefix: vec![],
             },
+            git_components: GitComponentPermissionArgs::default(),
             local_components: LocalComponentPermissionArgs {
                 allow_local_components: true,
                 allow_local_components_exact: vec!["foo.wasm".to_string()],
@@ -1357,6 +1439,7 @@ mod tests {
                 deny_http_components_exact: vec![],
                 deny_http_components_prefix: vec![],
             },
+            git_components: GitComponentPermissionArgs::default(),
             local_components: LocalComponentPermissionArgs {
                 allow_local_components: false,
                 allow_local_components_exact: vec![],
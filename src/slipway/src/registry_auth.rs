@@ -0,0 +1,202 @@
+use anyhow::Context;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use slipway_engine::RegistryUrlPrefix;
+
+/// Credentials sent when downloading components from registries whose URLs are
+/// beneath the given URL prefix, matched by origin and whole path segments.
+/// The credentials are read from environment variables so that secrets don't
+/// appear in command lines or shell history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RegistryAuth {
+    pub url_prefix: String,
+    pub credentials: RegistryCredentials,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RegistryCredentials {
+    /// A bearer token read from the named environment variable.
+    Bearer { token_env: String },
+
+    /// A username and password read from the named environment variables.
+    Basic {
+        username_env: String,
+        password_env: String,
+    },
+}
+
+impl RegistryAuth {
+    /// Returns the value of the `Authorization` header, reading the credentials
+    /// from their environment variables.
+    pub fn authorization_header(&self) -> anyhow::Result<String> {
+        self.authorization_header_from(|name| std::env::var(name).ok())
+    }
+
+    /// Returns the value of the `Authorization` header, reading the credentials
+    /// using the given environment variable lookup.
+    fn authorization_header_from(
+        &self,
+        env_var: impl Fn(&str) -> Option<String>,
+    ) -> anyhow::Result<String> {
+        let read_env_var = |name: &str| {
+            env_var(name).with_context(|| {
+                format!(
+                    "Environment variable \"{name}\" for the credentials of registry {} is not set",
+                    self.url_prefix
+                )
+            })
+        };
+
+        match &self.credentials {
+            RegistryCredentials::Bearer { token_env } => {
+                Ok(format!("Bearer {}", read_env_var(token_env)?))
+            }
+            RegistryCredentials::Basic {
+                username_env,
+                password_env,
+            } => {
+                let username = read_env_var(username_env)?;
+                let password = read_env_var(password_env)?;
+                Ok(format!(
+                    "Basic {}",
+                    BASE64_STANDARD.encode(format!("{username}:{password}"))
+                ))
+            }
+        }
+    }
+}
+
+/// Parses registry credentials in the format `url_prefix=bearer:TOKEN_ENV`
+/// or `url_prefix=basic:USERNAME_ENV:PASSWORD_ENV`.
+pub(crate) fn parse_registry_auth(s: &str) -> Result<RegistryAuth, String> {
+    let invalid = || {
+        format!(
+            "Expected registry credentials in the format url=bearer:TOKEN_ENV or url=basic:USERNAME_ENV:PASSWORD_ENV, but got: {s}"
+        )
+    };
+
+    // URLs can contain `=`, but the credentials can't.
+    let (url_prefix, credentials) = s.rsplit_once('=').ok_or_else(invalid)?;
+
+    if url_prefix.is_empty() {
+        return Err(invalid());
+    }
+
+    if RegistryUrlPrefix::parse(url_prefix).is_none() {
+        return Err(format!(
+            "Registry credentials must be for an absolute URL with a host, but got: {url_prefix}"
+        ));
+    }
+
+    let credentials = match credentials.split(':').collect::<Vec<_>>().as_slice() {
+        ["bearer", token_env] if !token_env.is_empty() => RegistryCredentials::Bearer {
+            token_env: token_env.to_string(),
+        },
+        ["basic", username_env, password_env]
+            if !username_env.is_empty() && !password_env.is_empty() =>
+        {
+            RegistryCredentials::Basic {
+                username_env: username_env.to_string(),
+                password_env: password_env.to_string(),
+            }
+        }
+        _ => return Err(invalid()),
+    };
+
+    Ok(RegistryAuth {
+        url_prefix: url_prefix.to_string(),
+        credentials,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn it_should_parse_bearer_credentials() {
+        assert_eq!(
+            parse_registry_auth("https://example.com/r?a=b=bearer:TOKEN").unwrap(),
+            RegistryAuth {
+                url_prefix: "https://example.com/r?a=b".to_string(),
+                credentials: RegistryCredentials::Bearer {
+                    token_env: "TOKEN".to_string()
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn it_should_parse_basic_credentials() {
+        assert_eq!(
+            parse_registry_auth("https://example.com/=basic:USER:PASS").unwrap(),
+            RegistryAuth {
+                url_prefix: "https://example.com/".to_string(),
+                credentials: RegistryCredentials::Basic {
+                    username_env: "USER".to_string(),
+                    password_env: "PASS".to_string(),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn it_should_reject_invalid_credentials() {
+        for s in [
+            "https://example.com/",
+            "=bearer:TOKEN",
+            "https://example.com/=bearer:",
+            "https://example.com/=basic:USER",
+            "https://example.com/=token:TOKEN",
+            "example.com=bearer:TOKEN",
+            "file:registry=bearer:TOKEN",
+        ] {
+            assert!(parse_registry_auth(s).is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn it_should_read_credentials_from_environment() {
+        let env = HashMap::from([
+            ("REGISTRY_USER", "user"),
+            ("REGISTRY_PASSWORD", "pass"),
+            ("REGISTRY_TOKEN", "token"),
+        ]);
+        let env_var = |name: &str| env.get(name).map(|value| value.to_string());
+
+        let basic = RegistryAuth {
+            url_prefix: "https://example.com/".to_string(),
+            credentials: RegistryCredentials::Basic {
+                username_env: "REGISTRY_USER".to_string(),
+                password_env: "REGISTRY_PASSWORD".to_string(),
+            },
+        };
+
+        assert_eq!(
+            basic.authorization_header_from(env_var).unwrap(),
+            "Basic dXNlcjpwYXNz"
+        );
+
+        let bearer = RegistryAuth {
+            url_prefix: "https://example.com/".to_string(),
+            credentials: RegistryCredentials::Bearer {
+                token_env: "REGISTRY_TOKEN".to_string(),
+            },
+        };
+
+        assert_eq!(
+            bearer.authorization_header_from(env_var).unwrap(),
+            "Bearer token"
+        );
+
+        let missing = RegistryAuth {
+            url_prefix: "https://example.com/".to_string(),
+            credentials: RegistryCredentials::Bearer {
+                token_env: "REGISTRY_MISSING_TOKEN".to_string(),
+            },
+        };
+
+        assert!(missing.authorization_header_from(env_var).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use slipway_engine::{
+    BasicComponentCache, BasicComponentsLoader, Environment, HashAlgorithm, HttpCassette,
+    RigSessionOptions, RigSessionOptionsBuilder,
+};
+
+use crate::registry_auth::RegistryAuth;
+
+/// The options shared by the commands which run or debug rigs, built once from
+/// the command line arguments.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RunOptions {
+    pub registry_urls: Vec<String>,
+    pub registry_auth: Vec<RegistryAuth>,
+    pub allow_non_http_git_urls: bool,
+    pub proxy: Option<String>,
+    pub deny_warnings: bool,
+    pub http_cassette: Option<Arc<HttpCassette>>,
+    pub coerce_outputs: bool,
+    pub log_http: bool,
+    pub explain_permissions: bool,
+    pub audit_log: bool,
+    pub max_output_bytes: Option<usize>,
+    pub js_time_limit: Option<Duration>,
+    pub js_step_limit: Option<u64>,
+    pub max_concurrent_components: Option<usize>,
+    pub hash_algorithm: HashAlgorithm,
+    pub check_updates: bool,
+    pub fonts_path: Option<PathBuf>,
+}
+
+impl RunOptions {
+    /// Creates a components loader for the configured registries, resolving
+    /// local components relative to the rig directory if there is one.
+    pub fn create_components_loader(
+        &self,
+        rig_directory: Option<&Path>,
+    ) -> anyhow::Result<BasicComponentsLoader> {
+        crate::utils::create_components_loader(
+            self.registry_urls.clone(),
+            self.registry_auth.clone(),
+            self.allow_non_http_git_urls,
+            self.proxy.clone(),
+            rig_directory,
+        )
+    }
+
+    /// Returns a session options builder for the system environment, with the
+    /// configured options applied. AOT components are downloaded using the
+    /// components loader's HTTP client.
+    pub fn session_options_builder(
+        &self,
+        components_loader: &BasicComponentsLoader,
+    ) -> RigSessionOptionsBuilder {
+        let timezone = crate::utils::get_system_timezone();
+        let locale = crate::utils::get_system_locale();
+
+        RigSessionOptions::builder(Environment { timezone, locale })
+            .fonts_path(self.fonts_path.clone())
+            .proxy(self.proxy.clone())
+            .components_http_client(Some(Arc::clone(components_loader.http_client())))
+            .http_cassette(self.http_cassette.clone())
+            .coerce_outputs(self.coerce_outputs)
+            .log_http(self.log_http)
+            .explain_permissions(self.explain_permissions)
+            .audit_log(self.audit_log)
+            .max_output_bytes(self.max_output_bytes)
+            .js_time_limit(self.js_time_limit)
+            .js_step_limit(self.js_step_limit)
+            .max_concurrent_components(self.max_concurrent_components)
+            .hash_algorithm(self.hash_algorithm)
+    }
+
+    /// Warns about out of date registry components, if enabled.
+    pub async fn check_for_updates(
+        &self,
+        component_cache: &BasicComponentCache,
+        components_loader: &BasicComponentsLoader,
+    ) {
+        if self.check_updates {
+            crate::component_updates::warn_of_component_updates(component_cache, components_loader)
+                .await;
+        }
+    }
+}
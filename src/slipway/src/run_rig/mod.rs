@@ -1,40 +1,68 @@
 use crate::json_editor::JsonEditorImpl;
 use std::{
-    io::Write,
+    io::{IsTerminal, Write},
     path::{Path, PathBuf},
     sync::Arc,
+    time::Instant,
 };
 
 use anyhow::Context;
 use slipway_engine::{
-    BasicComponentCache, BasicComponentsLoader, CallChain, Environment, Immutable, Permissions,
-    Rig, RigExecutionState, RigSession, RigSessionOptions, SlipwayReference, parse_rig,
+    BasicComponentCache, BasicComponentsLoader, CallChain, ComponentHandle, Immutable,
+    OutputContentType, Permissions, Rig, RigExecutionState, RigSession, SlipwayReference,
 };
 use slipway_host::{
     render_state::{
         WriteComponentOutputs,
         to_view_model::{ComponentViewModel, RigExecutionStateViewModel},
     },
-    run::RunEventHandler,
+    run::{RunEventHandler, RunRigOptions},
     tracing_writer::TraceOrWriter,
 };
 
 use crate::{
-    canvas::render_canvas_if_exists, component_runners::get_component_runners,
+    canvas::{render_canvas_if_exists, save_svg_if_exists},
+    component_runners::get_component_runners,
+    explain::{Explain, write_input_explanation},
+    get_rig_output::{RigOutput, RigOutputs},
     host_error::HostError,
+    run_options::RunOptions,
 };
 
-#[allow(clippy::too_many_arguments)] // For now at least.
+mod profile;
+mod summary;
+
+/// Options for running a rig which only apply to the `run` command.
+#[derive(Default)]
+pub(super) struct RunRigOutputOptions {
+    pub save_path: Option<PathBuf>,
+    pub debug_rig_path: Option<PathBuf>,
+    pub profile_path: Option<PathBuf>,
+    pub output_component: Option<ComponentHandle>,
+    pub until: Option<ComponentHandle>,
+    pub seed_outputs: Vec<(ComponentHandle, serde_json::Value)>,
+    pub continue_on_error: bool,
+    pub explain: Option<Explain>,
+    pub summary_path: Option<PathBuf>,
+}
+
 pub(super) async fn run_rig_from_component_file(
     mut w: Box<dyn Write>,
     component_reference: SlipwayReference,
     input: Option<String>,
     input_path: Option<std::path::PathBuf>,
     component_permissions: Permissions<'_>,
-    registry_urls: Vec<String>,
+    options: RunOptions,
     save_path: Option<PathBuf>,
-    fonts_path: Option<PathBuf>,
 ) -> anyhow::Result<()> {
+    // If no input was specified and something is being piped in, use that as the input.
+    let input_path = match (&input, input_path) {
+        (None, None) if !std::io::stdin().is_terminal() => {
+            Some(PathBuf::from(super::debug_rig::STDIN_INPUT_PATH))
+        }
+        (_, input_path) => input_path,
+    };
+
     let json_editor = JsonEditorImpl::new();
     let initial_input =
         super::debug_rig::get_component_input(&mut w, input, input_path, &json_editor)?;
@@ -50,14 +78,18 @@ pub(super) async fn run_rig_from_component_file(
     // component, but there is no advantage to being more restrictive here.
     let rig_permissions = Permissions::allow_all();
 
+    let components_loader = options.create_components_loader(None)?;
+
     run_rig_inner(
         w,
         rig,
         rig_permissions,
-        registry_urls,
-        save_path,
-        None,
-        fonts_path,
+        &components_loader,
+        options,
+        RunRigOutputOptions {
+            save_path,
+            ..Default::default()
+        },
     )
     .await
 }
@@ -66,24 +98,41 @@ pub(super) async fn run_rig(
     mut w: Box<dyn Write>,
     input: std::path::PathBuf,
     engine_permissions: Permissions<'_>,
-    registry_urls: Vec<String>,
-    save_path: Option<PathBuf>,
-    debug_rig_path: Option<PathBuf>,
-    fonts_path: Option<PathBuf>,
+    overlays: Vec<PathBuf>,
+    constant_overrides: Vec<(String, serde_json::Value)>,
+    options: RunOptions,
+    output_options: RunRigOutputOptions,
 ) -> anyhow::Result<()> {
     writeln!(&mut w, "Launching {}", input.display())?;
-    let file_contents = tokio::fs::read_to_string(input.clone())
-        .await
-        .with_context(|| format!("Failed to read component from {}", input.display()))?;
-    let rig = parse_rig(&file_contents)?;
+
+    if crate::bundle::is_bundle(&input) {
+        let mut bundle = crate::bundle::extract_bundle(&input)?;
+        crate::utils::apply_rig_overlays(&mut bundle.rig, &overlays)?;
+        crate::utils::apply_constant_overrides(&mut bundle.rig, constant_overrides);
+        return run_rig_inner(
+            w,
+            bundle.rig,
+            engine_permissions,
+            &bundle.components_loader,
+            options,
+            output_options,
+        )
+        .await;
+    }
+
+    let (file_contents, rig_directory) =
+        crate::utils::read_rig_file(&input, std::io::stdin().lock())?;
+    let mut rig = crate::utils::parse_rig_file(&input, &file_contents)?;
+    crate::utils::apply_rig_overlays(&mut rig, &overlays)?;
+    crate::utils::apply_constant_overrides(&mut rig, constant_overrides);
+    let components_loader = options.create_components_loader(rig_directory.as_deref())?;
     run_rig_inner(
         w,
         rig,
         engine_permissions,
-        registry_urls,
-        save_path,
-        debug_rig_path,
-        fonts_path,
+        &components_loader,
+        options,
+        output_options,
     )
     .await
 }
@@ -92,42 +141,90 @@ pub(super) async fn run_rig_inner(
     w: Box<dyn Write>,
     rig: Rig,
     engine_permissions: Permissions<'_>,
-    registry_urls: Vec<String>,
-    save_path: Option<PathBuf>,
-    debug_rig_path: Option<PathBuf>,
-    fonts_path: Option<PathBuf>,
+    components_loader: &BasicComponentsLoader,
+    options: RunOptions,
+    output_options: RunRigOutputOptions,
 ) -> anyhow::Result<()> {
-    let components_loader = BasicComponentsLoader::builder()
-        .registry_lookup_urls(registry_urls)
-        .build();
-
-    let timezone = crate::utils::get_system_timezone();
-    let locale = crate::utils::get_system_locale();
-    let component_cache = BasicComponentCache::primed(&rig, &components_loader).await?;
-    let session_options = RigSessionOptions::new_for_run(
-        &rig,
-        debug_rig_path.is_some(),
-        fonts_path.as_deref(),
-        Environment { timezone, locale },
-    )
-    .await;
+    let RunRigOutputOptions {
+        save_path,
+        debug_rig_path,
+        profile_path,
+        output_component,
+        until,
+        seed_outputs,
+        continue_on_error,
+        explain,
+        summary_path,
+    } = output_options;
+    let deny_warnings = options.deny_warnings;
+
+    if let Some(explain) = &explain
+        && !rig.rigging.components.contains_key(&explain.handle)
+    {
+        anyhow::bail!("Component \"{}\" does not exist in the rig", explain.handle);
+    }
+
+    for handle in until
+        .iter()
+        .chain(seed_outputs.iter().map(|(handle, _)| handle))
+    {
+        if !rig.rigging.components.contains_key(handle) {
+            anyhow::bail!("Component \"{}\" does not exist in the rig", handle);
+        }
+    }
+
+    let start = Instant::now();
+    let component_cache = BasicComponentCache::primed(&rig, components_loader).await?;
+    let component_cache_profile =
+        profile::ComponentCacheProfile::new(component_cache.len(), start.elapsed());
+    crate::utils::handle_component_warnings(&component_cache, deny_warnings)?;
+    let session_options = options
+        .session_options_builder(components_loader)
+        .run_record(debug_rig_path.is_some())
+        .record_input_provenance(summary_path.is_some())
+        .build(&rig)
+        .await;
+    crate::utils::handle_missing_fonts(&component_cache, &session_options, deny_warnings).await?;
+    options
+        .check_for_updates(&component_cache, components_loader)
+        .await;
+
+    // Components which don't contribute to the output are only reported when the
+    // whole rig is run, as otherwise they are expected.
+    let unused_components_output = match (&output_component, &until) {
+        // Rigs with named outputs have no single output component to check against.
+        (None, None) if rig.outputs.is_none() => {
+            crate::get_rig_output::get_rig_output_handle(&rig.rigging).cloned()
+        }
+        _ => None,
+    };
+
     let session = RigSession::new_with_options(rig, &component_cache, session_options);
 
-    let mut event_handler = CliRunEventHandler::new(
-        save_path,
-        WriteComponentOutputsType::LeafComponents,
-        TraceOrWriter::Writer(w),
-    );
+    let write_outputs_type = match output_component.or_else(|| until.clone()) {
+        Some(handle) => WriteComponentOutputsType::SingleComponent(handle),
+        None => WriteComponentOutputsType::LeafComponents,
+    };
+
+    let mut event_handler =
+        CliRunEventHandler::new(save_path, write_outputs_type, TraceOrWriter::Writer(w))
+            .explain(explain)
+            .warn_unused_components(unused_components_output, deny_warnings);
     let component_runners = get_component_runners();
     let component_runners_slice = component_runners.as_slice();
 
     let call_chain = Arc::new(CallChain::new(engine_permissions));
 
-    let maybe_run_rig_result = slipway_host::run::run_rig(
+    let maybe_run_rig_result = slipway_host::run::run_rig_with_options(
         &session,
         &mut event_handler,
         component_runners_slice,
         call_chain,
+        RunRigOptions {
+            until: until.as_ref(),
+            output_overrides: seed_outputs,
+            continue_on_error,
+        },
     )
     .await;
 
@@ -140,7 +237,43 @@ pub(super) async fn run_rig_inner(
             .context("Failed to write debug rig")?;
     }
 
-    maybe_run_rig_result?;
+    let final_state = maybe_run_rig_result?;
+
+    if let Some(profile_path) = profile_path {
+        profile::RigProfile::new(&final_state, start.elapsed(), component_cache_profile)
+            .write(&profile_path)?;
+    }
+
+    if let Some(summary_path) = summary_path {
+        let warnings = component_cache
+            .warnings()
+            .iter()
+            .map(|w| w.to_string())
+            .chain(event_handler.rig_warnings.iter().cloned())
+            .collect();
+
+        summary::RunSummary::new(
+            &final_state,
+            &event_handler.summary_events,
+            start.elapsed(),
+            warnings,
+        )
+        .write(&summary_path)?;
+    }
+
+    let failed_components = final_state.failed_components();
+    if !failed_components.is_empty() {
+        let failures = failed_components
+            .iter()
+            .map(|(handle, failure)| format!("Component \"{handle}\" failed:\n{}", failure.message))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        anyhow::bail!(
+            "{} component(s) failed to run.\n\n{failures}",
+            failed_components.len()
+        );
+    }
 
     Ok(())
 }
@@ -148,6 +281,10 @@ pub(super) async fn run_rig_inner(
 pub(super) struct CliRunEventHandler {
     save_path: Option<PathBuf>,
     write_outputs_type: WriteComponentOutputsType,
+    explain: Option<Explain>,
+    unused_components: Option<UnusedComponentsCheck>,
+    rig_warnings: Vec<String>,
+    summary_events: summary::RunSummaryEvents,
     inner: slipway_host::run::tracing_run_event_handler::TracingRunEventHandler,
 }
 
@@ -160,6 +297,10 @@ impl CliRunEventHandler {
         Self {
             save_path,
             write_outputs_type,
+            explain: None,
+            unused_components: None,
+            rig_warnings: Vec::new(),
+            summary_events: summary::RunSummaryEvents::default(),
             inner: slipway_host::run::tracing_run_event_handler::TracingRunEventHandler::new_for(
                 level,
             ),
@@ -167,11 +308,89 @@ impl CliRunEventHandler {
     }
 }
 
+impl CliRunEventHandler {
+    /// Explains the input of the given component once it has been resolved.
+    pub fn explain(mut self, explain: Option<Explain>) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// Warns about components whose output is not used by the given output component,
+    /// once the rig has been initialized. If `deny_warnings` is set the run fails instead.
+    pub fn warn_unused_components(
+        mut self,
+        output_handle: Option<ComponentHandle>,
+        deny_warnings: bool,
+    ) -> Self {
+        self.unused_components = output_handle.map(|output_handle| UnusedComponentsCheck {
+            output_handle,
+            deny_warnings,
+        });
+        self
+    }
+
+    fn check_unused_components(
+        &mut self,
+        state: &RigExecutionState<'_, '_>,
+    ) -> Result<(), HostError> {
+        // Only check the initial state.
+        let Some(check) = self.unused_components.take() else {
+            return Ok(());
+        };
+
+        let unused_components = state
+            .unused_components(&check.output_handle)
+            .unwrap_or_default();
+
+        for handle in unused_components.iter() {
+            let warning = format!(
+                "Component \"{handle}\" output is not used by the output component \"{}\".",
+                check.output_handle
+            );
+            eprintln!("Warning: {warning}");
+            self.rig_warnings.push(warning);
+        }
+
+        if check.deny_warnings && !unused_components.is_empty() {
+            return Err(HostError::Other(format!(
+                "{} rig warning(s) were found and warnings are denied.",
+                unused_components.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn write_explanation_if_ready(
+        &mut self,
+        state: &RigExecutionState<'_, '_>,
+    ) -> Result<(), HostError> {
+        let Some(explain) = &self.explain else {
+            return Ok(());
+        };
+
+        let is_ready = state
+            .component_states
+            .get(&explain.handle)
+            .is_some_and(|c| c.execution_input.is_some());
+
+        if !is_ready {
+            return Ok(());
+        }
+
+        // Only explain the input once.
+        let explain = self.explain.take().expect("explain should exist");
+        write_input_explanation(self.inner.writer(), state, &explain.handle, explain.format)
+            .map_err(|e| HostError::Other(format!("{e:#}")))
+    }
+}
+
 impl<'rig, 'cache> RunEventHandler<'rig, 'cache, HostError> for CliRunEventHandler {
     fn handle_component_run_start<'state>(
         &mut self,
         event: slipway_host::run::ComponentRunStartEvent<'rig>,
     ) -> Result<(), HostError> {
+        self.summary_events.record_run_start(event.component_handle);
         self.inner
             .handle_component_run_start(event)
             .map_err(HostError::from)
@@ -181,6 +400,7 @@ impl<'rig, 'cache> RunEventHandler<'rig, 'cache, HostError> for CliRunEventHandl
         &mut self,
         event: slipway_host::run::ComponentRunEndEvent<'rig>,
     ) -> Result<(), HostError> {
+        self.summary_events.record_run_end(event.component_handle);
         self.inner
             .handle_component_run_end(event)
             .map_err(HostError::from)
@@ -193,14 +413,18 @@ impl<'rig, 'cache> RunEventHandler<'rig, 'cache, HostError> for CliRunEventHandl
         let is_complete = event.is_complete;
 
         let state = event.state;
+        self.check_unused_components(state)?;
+        self.write_explanation_if_ready(state)?;
+
         let view_model = self
             .inner
             .handle_state_changed(event)
             .map_err(HostError::from)?;
 
-        if is_complete {
+        // The outputs can't be written if any component failed to run.
+        if is_complete && state.failed_components().is_empty() {
             let write_component_outputs = SlipwayWriteMultipleComponentOutputs {
-                write_outputs_type: self.write_outputs_type,
+                write_outputs_type: self.write_outputs_type.clone(),
             };
 
             write_component_outputs.write_component_outputs(
@@ -215,6 +439,11 @@ impl<'rig, 'cache> RunEventHandler<'rig, 'cache, HostError> for CliRunEventHandl
     }
 }
 
+struct UnusedComponentsCheck {
+    output_handle: ComponentHandle,
+    deny_warnings: bool,
+}
+
 struct SlipwayWriteMultipleComponentOutputs {
     write_outputs_type: WriteComponentOutputsType,
 }
@@ -230,19 +459,50 @@ impl<W: Write> WriteComponentOutputs<W, HostError> for SlipwayWriteMultipleCompo
         let extension = save_path.and_then(|p| p.extension().and_then(|ext| ext.to_str()));
         match extension {
             Some(extension) => {
-                let rig_output = crate::get_rig_output::get_rig_output(state)
-                    .map_err(|e| HostError::Other(format!("{e}")))?;
+                let rig_outputs = match &self.write_outputs_type {
+                    WriteComponentOutputsType::SingleComponent(handle) => {
+                        crate::get_rig_output::get_component_output(state, handle)
+                            .map(RigOutputs::Single)
+                    }
+                    _ => crate::get_rig_output::get_rig_outputs(state),
+                }
+                .map_err(|e| HostError::Other(format!("{e}")))?;
 
                 let save_path_unwrapped = save_path.unwrap();
-                writeln!(
-                    w,
-                    "Writing \"{}\" output to: {}",
-                    rig_output.handle,
-                    save_path_unwrapped.to_string_lossy()
-                )?;
-
-                match extension {
-                    "json" => {
+                match &rig_outputs {
+                    RigOutputs::Single(rig_output) => writeln!(
+                        w,
+                        "Writing \"{}\" output to: {}",
+                        rig_output.handle,
+                        save_path_unwrapped.to_string_lossy()
+                    )?,
+                    RigOutputs::Named(_) => writeln!(
+                        w,
+                        "Writing rig outputs to: {}",
+                        save_path_unwrapped.to_string_lossy()
+                    )?,
+                }
+
+                let content_type = match extension {
+                    "json" => OutputContentType::Json,
+                    "png" => OutputContentType::Png,
+                    "svg" => OutputContentType::Svg,
+                    _ => match &rig_outputs {
+                        RigOutputs::Single(RigOutput {
+                            content_type: Some(content_type),
+                            ..
+                        }) => *content_type,
+                        _ => {
+                            return Err(HostError::Other(format!(
+                                "File extension should be \"json\", \"png\" or \"svg\" unless the output component declares an output content type. Extension was \"{}\".",
+                                extension
+                            )));
+                        }
+                    },
+                };
+
+                match content_type {
+                    OutputContentType::Json => {
                         let output_file =
                             std::fs::File::create(save_path_unwrapped).map_err(|error| {
                                 HostError::Other(format!(
@@ -251,7 +511,7 @@ impl<W: Write> WriteComponentOutputs<W, HostError> for SlipwayWriteMultipleCompo
                                 ))
                             })?;
 
-                        serde_json::to_writer_pretty(output_file, &rig_output.output.value)
+                        serde_json::to_writer_pretty(output_file, &rig_outputs.to_value())
                             .map_err(|error| {
                                 HostError::Other(format!(
                                     "Failed to write output file for rig: {}",
@@ -259,7 +519,14 @@ impl<W: Write> WriteComponentOutputs<W, HostError> for SlipwayWriteMultipleCompo
                                 ))
                             })?;
                     }
-                    "png" => {
+                    OutputContentType::Png => {
+                        let RigOutputs::Single(rig_output) = &rig_outputs else {
+                            return Err(HostError::Other(
+                                "The rig has named outputs, which must be saved to a folder."
+                                    .to_string(),
+                            ));
+                        };
+
                         if !render_canvas_if_exists(
                             rig_output.handle,
                             &rig_output.output.value,
@@ -271,31 +538,75 @@ impl<W: Write> WriteComponentOutputs<W, HostError> for SlipwayWriteMultipleCompo
                             )));
                         }
                     }
-                    _ => {
-                        return Err(HostError::Other(format!(
-                            "File extension should be \"json\" or \"png\". Extension was \"{}\".",
-                            extension
-                        )));
+                    OutputContentType::Svg => {
+                        let RigOutputs::Single(rig_output) = &rig_outputs else {
+                            return Err(HostError::Other(
+                                "The rig has named outputs, which must be saved to a folder."
+                                    .to_string(),
+                            ));
+                        };
+
+                        if save_svg_if_exists(
+                            rig_output.handle,
+                            &rig_output.output.value,
+                            save_path,
+                        )?
+                        .is_none()
+                        {
+                            return Err(HostError::Other(format!(
+                                "No SVG found for rig output: {}",
+                                rig_output.handle
+                            )));
+                        }
                     }
                 }
             }
-            None => match self.write_outputs_type {
+            None => match &self.write_outputs_type {
                 WriteComponentOutputsType::None => {}
+                WriteComponentOutputsType::SingleComponent(handle) => {
+                    let component_output =
+                        crate::get_rig_output::get_component_output(state, handle)
+                            .map_err(|e| HostError::Other(format!("{e}")))?;
+                    writeln!(w, "{:#}", component_output.output.value)?;
+                }
                 WriteComponentOutputsType::LeafComponents => {
+                    // Rigs with named outputs only write those outputs.
+                    let named_outputs = crate::get_rig_output::get_named_rig_outputs(state)
+                        .map_err(|e| HostError::Other(format!("{e}")))?;
+
+                    if let Some(named_outputs) = named_outputs {
+                        for named_output in named_outputs.iter() {
+                            write_output(
+                                w,
+                                save_path,
+                                named_output.name,
+                                &format!(
+                                    "Rig output \"{}\" (component \"{}\"):",
+                                    named_output.name, named_output.output.handle
+                                ),
+                                named_output.output.handle,
+                                &named_output.output.output.value,
+                                named_output.output.content_type,
+                            )?;
+                        }
+
+                        return Ok(());
+                    }
+
                     for group in view_model.groups.iter() {
                         for component in group.components.iter() {
                             if !component.output_row_indexes.is_empty() {
                                 continue;
                             }
 
-                            write_component_output(w, save_path, component)?;
+                            write_component_output(w, save_path, state, component)?;
                         }
                     }
                 }
                 WriteComponentOutputsType::AllComponents => {
                     for group in view_model.groups.iter() {
                         for component in group.components.iter() {
-                            write_component_output(w, save_path, component)?;
+                            write_component_output(w, save_path, state, component)?;
                         }
                     }
                 }
@@ -306,68 +617,145 @@ impl<W: Write> WriteComponentOutputs<W, HostError> for SlipwayWriteMultipleCompo
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub(super) enum WriteComponentOutputsType {
     None,
     LeafComponents,
     AllComponents,
+    SingleComponent(ComponentHandle),
 }
 
 fn write_component_output<W: Write>(
     w: &mut W,
     save_path: Option<&Path>,
+    state: &RigExecutionState<'_, '_>,
     component: &ComponentViewModel,
 ) -> Result<(), HostError> {
-    if let Some(save_path) = save_path.as_ref() {
+    if let Some(output) = component.state.output() {
+        write_output(
+            w,
+            save_path,
+            &component.handle.0,
+            &format!("Component \"{}\" output:", component.handle),
+            component.handle,
+            output,
+            state.output_content_type(component.handle),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes the output to `<name>.png` if it contains a canvas, to `<name>.svg` if it
+/// contains SVG markup, and otherwise to `<name>.json`, in the save path folder.
+/// If there is no save path the output is written to the writer under the given title.
+///
+/// If the component declares an output content type then the output is written
+/// as that type, rather than as an image only if it happens to contain one.
+fn write_output<W: Write>(
+    w: &mut W,
+    save_path: Option<&Path>,
+    name: &str,
+    title: &str,
+    handle: &ComponentHandle,
+    output: &serde_json::Value,
+    content_type: Option<OutputContentType>,
+) -> Result<(), HostError> {
+    if let Some(save_path) = save_path {
         std::fs::create_dir_all(save_path).map_err(|error| {
             HostError::Other(format!(
                 "Failed to create directory to save outputs: {}",
                 error
             ))
         })?;
+
+        writeln!(
+            w,
+            "Writing \"{}\" output to folder: {}",
+            name,
+            save_path.to_string_lossy()
+        )?;
+    } else {
+        writeln!(w, "{title}")?;
     }
 
-    if let Some(output) = component.state.output() {
-        if let Some(save_path) = save_path {
-            writeln!(
-                w,
-                "Writing \"{}\" output to folder: {}",
-                component.handle,
-                save_path.to_string_lossy()
-            )?;
-        } else {
-            writeln!(w, "Component \"{}\" output:", component.handle)?;
+    let image_path = |content_type: OutputContentType| {
+        save_path.map(|p| p.join(format!("{name}.{}", content_type.file_extension())))
+    };
+
+    let rendered = match content_type {
+        Some(OutputContentType::Json) => false,
+        Some(OutputContentType::Png) => {
+            if !render_canvas_if_exists(
+                handle,
+                output,
+                image_path(OutputContentType::Png).as_deref(),
+            )? {
+                return Err(HostError::Other(format!(
+                    "Component {} declares a PNG output but no canvas was found in its output.",
+                    handle
+                )));
+            }
+            true
         }
-
-        if !render_canvas_if_exists(
-            component.handle,
-            output,
-            save_path
-                .map(|p| p.join(format!("{}.png", component.handle.0)))
-                .as_deref(),
-        )? {
-            if let Some(save_path) = save_path {
-                let output_path = save_path.join(format!("{}.json", component.handle.0));
-                let output_file = std::fs::File::create(output_path).map_err(|error| {
-                    HostError::Other(format!(
-                        "Failed to create output file for component {}: {}",
-                        component.handle, error
-                    ))
-                })?;
-
-                serde_json::to_writer_pretty(output_file, output).map_err(|error| {
-                    HostError::Other(format!(
-                        "Failed to write output file for component {}: {}",
-                        component.handle, error
-                    ))
-                })?;
-            } else {
-                writeln!(w, "{:#}", output)?;
+        Some(OutputContentType::Svg) => {
+            if !write_svg_if_exists(w, handle, output, image_path(OutputContentType::Svg))? {
+                return Err(HostError::Other(format!(
+                    "Component {} declares an SVG output but no SVG was found in its output.",
+                    handle
+                )));
             }
+            true
         }
+        None => {
+            render_canvas_if_exists(
+                handle,
+                output,
+                image_path(OutputContentType::Png).as_deref(),
+            )? || write_svg_if_exists(w, handle, output, image_path(OutputContentType::Svg))?
+        }
+    };
 
-        writeln!(w)?;
+    if !rendered {
+        if let Some(save_path) = save_path {
+            let output_path = save_path.join(format!("{name}.json"));
+            let output_file = std::fs::File::create(output_path).map_err(|error| {
+                HostError::Other(format!(
+                    "Failed to create output file for component {}: {}",
+                    handle, error
+                ))
+            })?;
+
+            serde_json::to_writer_pretty(output_file, output).map_err(|error| {
+                HostError::Other(format!(
+                    "Failed to write output file for component {}: {}",
+                    handle, error
+                ))
+            })?;
+        } else {
+            writeln!(w, "{:#}", output)?;
+        }
     }
 
+    writeln!(w)?;
+
     Ok(())
 }
+
+/// Saves the output's SVG markup to the path, or writes it to the writer if there
+/// is no path. Returns false if the output contains no SVG.
+fn write_svg_if_exists<W: Write>(
+    w: &mut W,
+    handle: &ComponentHandle,
+    output: &serde_json::Value,
+    path: Option<PathBuf>,
+) -> Result<bool, HostError> {
+    match save_svg_if_exists(handle, output, path.as_deref())? {
+        Some(svg) if path.is_none() => {
+            writeln!(w, "{svg}")?;
+            Ok(true)
+        }
+        Some(_) => Ok(true),
+        None => Ok(false),
+    }
+}
@@ -0,0 +1,134 @@
+use std::{path::Path, time::Duration};
+
+use anyhow::Context;
+use serde::Serialize;
+use slipway_engine::{ComponentHandle, NestedRunMetadata, RigExecutionState, RunMetadata};
+
+/// Per-phase timings of a rig run, written by `slipway run --profile`.
+///
+/// Durations are in microseconds. Components are nested in the same way as
+/// the rig execution, so the output can be converted directly into a flame graph.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub(super) struct RigProfile {
+    pub total_duration_us: u64,
+    pub component_cache: ComponentCacheProfile,
+    pub components: Vec<ComponentProfile>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub(super) struct ComponentCacheProfile {
+    pub component_count: usize,
+    pub prime_duration_us: u64,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub(super) struct ComponentProfile {
+    pub handle: ComponentHandle,
+    pub total_duration_us: u64,
+    pub prepare_input_duration_us: u64,
+    pub prepare_component_duration_us: u64,
+    pub call_duration_us: u64,
+    pub process_output_duration_us: u64,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nested: Vec<ComponentProfile>,
+}
+
+impl RigProfile {
+    /// Creates the profile from the components which have run, in execution order.
+    pub fn new(
+        state: &RigExecutionState<'_, '_>,
+        total_duration: Duration,
+        component_cache: ComponentCacheProfile,
+    ) -> Self {
+        let components = state
+            .execution_order()
+            .iter()
+            .filter_map(|&handle| {
+                let output = state.component_states[handle].execution_output.as_ref()?;
+                Some(ComponentProfile::new(handle, &output.run_metadata))
+            })
+            .collect();
+
+        RigProfile {
+            total_duration_us: to_micros(total_duration),
+            component_cache,
+            components,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize profile")?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write profile to {}", path.display()))
+    }
+}
+
+impl ComponentCacheProfile {
+    pub fn new(component_count: usize, prime_duration: Duration) -> Self {
+        ComponentCacheProfile {
+            component_count,
+            prime_duration_us: to_micros(prime_duration),
+        }
+    }
+}
+
+impl ComponentProfile {
+    fn new(handle: &ComponentHandle, metadata: &RunMetadata) -> Self {
+        ComponentProfile {
+            handle: handle.clone(),
+            total_duration_us: to_micros(metadata.overall_duration()),
+            prepare_input_duration_us: to_micros(metadata.prepare_input_duration),
+            prepare_component_duration_us: to_micros(metadata.prepare_component_duration),
+            call_duration_us: to_micros(metadata.call_duration),
+            process_output_duration_us: to_micros(metadata.process_output_duration),
+            nested: metadata
+                .nested
+                .iter()
+                .flatten()
+                .map(|NestedRunMetadata { handle, metadata }| {
+                    ComponentProfile::new(handle, metadata)
+                })
+                .collect(),
+        }
+    }
+}
+
+fn to_micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn it_should_include_nested_component_timings() {
+        let metadata = RunMetadata {
+            prepare_input_duration: Duration::from_micros(1),
+            prepare_component_duration: Duration::from_micros(2),
+            call_duration: Duration::from_micros(3),
+            process_output_duration: Duration::from_micros(4),
+            nested: Some(vec![NestedRunMetadata {
+                handle: ComponentHandle::from_str("inner").unwrap(),
+                metadata: RunMetadata {
+                    call_duration: Duration::from_micros(5),
+                    ..Default::default()
+                },
+            }]),
+            memoized_from: None,
+        };
+
+        let profile =
+            ComponentProfile::new(&ComponentHandle::from_str("outer").unwrap(), &metadata);
+
+        assert_eq!(profile.total_duration_us, 10);
+        assert_eq!(profile.call_duration_us, 3);
+        assert_eq!(profile.nested.len(), 1);
+        assert_eq!(profile.nested[0].handle.to_string(), "inner");
+        assert_eq!(profile.nested[0].total_duration_us, 5);
+        assert!(profile.nested[0].nested.is_empty());
+    }
+}
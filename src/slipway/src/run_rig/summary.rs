@@ -0,0 +1,174 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use serde::Serialize;
+use slipway_engine::{ComponentHandle, ComponentState, InputProvenance, RigExecutionState};
+
+/// A machine readable summary of a rig run, written to the file given by `slipway run --summary`.
+///
+/// Durations are in microseconds.
+#[derive(Debug, Serialize, PartialEq)]
+pub(super) struct RunSummary {
+    pub components_run: usize,
+    pub components_skipped: usize,
+    pub total_duration_us: u64,
+    pub components: Vec<ComponentSummary>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub(super) struct ComponentSummary {
+    pub handle: ComponentHandle,
+    pub status: ComponentStatus,
+
+    /// The time taken to run the component, if it was run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_us: Option<u64>,
+
+    /// Where each value in the component's evaluated input came from.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub input_provenance: Vec<InputProvenance>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum ComponentStatus {
+    Succeeded,
+    Skipped,
+    Failed,
+
+    /// The output was seeded before the rig ran, so the component was not run.
+    Overridden,
+
+    /// The component was not run, because a component it depends on failed
+    /// or it was not required.
+    NotRun,
+}
+
+/// The component run events collected while the rig runs.
+#[derive(Debug, Default)]
+pub(super) struct RunSummaryEvents {
+    run_starts: HashMap<ComponentHandle, Instant>,
+    run_durations: HashMap<ComponentHandle, Duration>,
+}
+
+impl RunSummaryEvents {
+    pub fn record_run_start(&mut self, handle: &ComponentHandle) {
+        self.run_starts.insert(handle.clone(), Instant::now());
+    }
+
+    pub fn record_run_end(&mut self, handle: &ComponentHandle) {
+        if let Some(start) = self.run_starts.remove(handle) {
+            self.run_durations.insert(handle.clone(), start.elapsed());
+        }
+    }
+}
+
+impl RunSummary {
+    /// Creates the summary from the final state of the rig, in execution order.
+    pub fn new(
+        state: &RigExecutionState<'_, '_>,
+        events: &RunSummaryEvents,
+        total_duration: Duration,
+        warnings: Vec<String>,
+    ) -> Self {
+        let components = state
+            .execution_order()
+            .iter()
+            .map(|&handle| ComponentSummary {
+                handle: handle.clone(),
+                status: ComponentStatus::new(&state.component_states[handle]),
+                duration_us: events.run_durations.get(handle).copied().map(to_micros),
+                input_provenance: state.component_states[handle]
+                    .input_provenance
+                    .as_deref()
+                    .cloned()
+                    .unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+
+        let components_run = components
+            .iter()
+            .filter(|c| c.duration_us.is_some() && c.status != ComponentStatus::Skipped)
+            .count();
+
+        let components_skipped = components
+            .iter()
+            .filter(|c| c.status == ComponentStatus::Skipped)
+            .count();
+
+        RunSummary {
+            components_run,
+            components_skipped,
+            total_duration_us: to_micros(total_duration),
+            components,
+            warnings,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize run summary")?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write run summary to {}", path.display()))
+    }
+}
+
+impl ComponentStatus {
+    fn new(component_state: &ComponentState<'_>) -> Self {
+        if component_state.execution_failure.is_some() {
+            ComponentStatus::Failed
+        } else if component_state.output_override.is_some() {
+            ComponentStatus::Overridden
+        } else if component_state.execution_output.is_none() {
+            ComponentStatus::NotRun
+        } else if component_state.execution_skipped {
+            ComponentStatus::Skipped
+        } else {
+            ComponentStatus::Succeeded
+        }
+    }
+}
+
+fn to_micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn it_should_record_run_durations_for_completed_runs() {
+        let a = ComponentHandle::from_str("a").unwrap();
+        let b = ComponentHandle::from_str("b").unwrap();
+
+        let mut events = RunSummaryEvents::default();
+        events.record_run_start(&a);
+        events.record_run_end(&a);
+        events.record_run_start(&b);
+
+        assert!(events.run_durations.contains_key(&a));
+        assert!(!events.run_durations.contains_key(&b));
+    }
+
+    #[test]
+    fn it_should_serialize_statuses_as_snake_case() {
+        let summary = ComponentSummary {
+            handle: ComponentHandle::from_str("a").unwrap(),
+            status: ComponentStatus::NotRun,
+            duration_us: None,
+            input_provenance: Vec::new(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&summary).unwrap(),
+            serde_json::json!({ "handle": "a", "status": "not_run" })
+        );
+    }
+}
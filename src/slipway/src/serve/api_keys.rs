@@ -0,0 +1,163 @@
+use std::path::Path;
+
+use anyhow::Context;
+use tracing::{info, warn};
+
+use super::{RegisteredApiKey, SlipwayServeConfig, truncate_hashed_api_key};
+
+/// An environment variable containing a JSON array of hashed API keys,
+/// in the same format as the `api_keys` in the config file.
+const SLIPWAY_API_KEYS_KEY: &str = "SLIPWAY_API_KEYS";
+
+/// An environment variable containing the path of a JSON file of hashed API keys,
+/// such as a mounted Kubernetes secret.
+const SLIPWAY_API_KEYS_FILE_KEY: &str = "SLIPWAY_API_KEYS_FILE";
+
+/// Adds the API keys supplied through the environment to the config, so that
+/// keys can be managed as secrets rather than being written to the config file.
+pub(super) async fn add_environment_api_keys(
+    config: &mut SlipwayServeConfig,
+) -> anyhow::Result<()> {
+    let api_keys = load_environment_api_keys(
+        std::env::var(SLIPWAY_API_KEYS_KEY).ok(),
+        std::env::var(SLIPWAY_API_KEYS_FILE_KEY).ok(),
+    )
+    .await?;
+
+    if !api_keys.is_empty() {
+        info!("Loaded {} API key(s) from the environment.", api_keys.len());
+    }
+
+    merge_api_keys(&mut config.api_keys, api_keys);
+
+    Ok(())
+}
+
+async fn load_environment_api_keys(
+    api_keys_json: Option<String>,
+    api_keys_file: Option<String>,
+) -> anyhow::Result<Vec<RegisteredApiKey>> {
+    let mut api_keys = Vec::new();
+
+    if let Some(api_keys_json) = api_keys_json {
+        api_keys.extend(
+            parse_api_keys(api_keys_json.as_bytes())
+                .with_context(|| format!("Failed to parse {SLIPWAY_API_KEYS_KEY}"))?,
+        );
+    }
+
+    if let Some(api_keys_file) = api_keys_file {
+        let path = Path::new(&api_keys_file);
+        let bytes = tokio::fs::read(path).await.with_context(|| {
+            format!(
+                "Failed to read API keys file from {SLIPWAY_API_KEYS_FILE_KEY}: {}",
+                path.display()
+            )
+        })?;
+        api_keys.extend(
+            parse_api_keys(&bytes)
+                .with_context(|| format!("Failed to parse API keys file: {}", path.display()))?,
+        );
+    }
+
+    Ok(api_keys)
+}
+
+fn parse_api_keys(bytes: &[u8]) -> serde_json::Result<Vec<RegisteredApiKey>> {
+    serde_json::from_slice(bytes)
+}
+
+/// Adds the additional API keys to the existing keys. An additional key with the
+/// same hash as an existing key replaces it.
+fn merge_api_keys(api_keys: &mut Vec<RegisteredApiKey>, additional: Vec<RegisteredApiKey>) {
+    for api_key in additional {
+        if let Some(existing) = api_keys
+            .iter_mut()
+            .find(|existing| existing.hashed_key == api_key.hashed_key)
+        {
+            warn!(
+                "API key with hashed value starting \"{}\" is defined more than once. Using the last definition.",
+                truncate_hashed_api_key(&api_key.hashed_key)
+            );
+            *existing = api_key;
+        } else {
+            api_keys.push(api_key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::primitives::DeviceName;
+
+    use super::*;
+
+    fn api_key(hashed_key: &str, device: Option<&str>) -> RegisteredApiKey {
+        RegisteredApiKey {
+            hashed_key: hashed_key.to_string(),
+            device: device.map(|d| DeviceName::from_str(d).unwrap()),
+            description: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_load_api_keys_from_json_and_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("api_keys.json");
+        std::fs::write(
+            &file_path,
+            r#"[{ "hashed_key": "ffffff1", "device": "kitchen" }]"#,
+        )
+        .unwrap();
+
+        let api_keys = load_environment_api_keys(
+            Some(r#"[{ "hashed_key": "eeeeee1", "description": "From env" }]"#.to_string()),
+            Some(file_path.to_string_lossy().to_string()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(api_keys.len(), 2);
+        assert_eq!(api_keys[0].hashed_key, "eeeeee1");
+        assert_eq!(api_keys[0].description.as_deref(), Some("From env"));
+        assert_eq!(api_keys[1].hashed_key, "ffffff1");
+        assert_eq!(
+            api_keys[1].device,
+            Some(DeviceName::from_str("kitchen").unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_to_load_invalid_api_keys() {
+        let result = load_environment_api_keys(Some("{}".to_string()), None).await;
+        assert!(result.is_err());
+
+        let result =
+            load_environment_api_keys(None, Some("/does/not/exist/api_keys.json".to_string()))
+                .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_should_merge_api_keys_replacing_duplicates() {
+        let mut api_keys = vec![api_key("aaaaaa1", None), api_key("bbbbbb1", None)];
+
+        merge_api_keys(
+            &mut api_keys,
+            vec![
+                api_key("bbbbbb1", Some("kitchen")),
+                api_key("cccccc1", None),
+            ],
+        );
+
+        assert_eq!(
+            api_keys
+                .iter()
+                .map(|k| (k.hashed_key.as_str(), k.device.is_some()))
+                .collect::<Vec<_>>(),
+            vec![("aaaaaa1", false), ("bbbbbb1", true), ("cccccc1", false)]
+        );
+    }
+}
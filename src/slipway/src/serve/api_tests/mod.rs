@@ -1,8 +1,9 @@
 use std::{collections::HashMap, path::PathBuf, str::FromStr};
 
 use actix_web::{
+    App,
     body::MessageBody,
-    dev::ServiceResponse,
+    dev::{ServiceFactory, ServiceRequest, ServiceResponse},
     http::{StatusCode, header::HeaderName},
     test,
 };
@@ -12,20 +13,37 @@ use slipway_host::hash_string;
 use crate::{
     primitives::{DeviceName, PlaylistName, RigName},
     serve::{
-        REFRESH_RATE_HEADER, RegisteredApiKey, RepositoryConfig, SlipwayServeConfig,
-        SlipwayServeEnvironment, create_app,
+        REFRESH_RATE_HEADER, RegisteredApiKey, RepositoryConfig, SharedServeState,
+        SlipwayServeConfig, SlipwayServeEnvironment, create_app,
         repository::{RigResultFormat, RigResultImageFormat, RigResultPartialSpec},
     },
 };
 
 use super::{
-    Device, Playlist, ShowApiKeys,
+    Device, Playlist, ShowApiKeys, WarmupMode,
     repository::{PlaylistItem, Refresh},
 };
 
 mod trmnl_display;
 mod trmnl_setup;
 
+/// Creates an app with its own shared state, as each server does.
+fn create_test_app(
+    config: SlipwayServeConfig,
+    secret: Option<String>,
+) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    let shared = SharedServeState::new(&config);
+    create_app(PathBuf::from("."), None, config, secret, shared)
+}
+
 fn dn(s: &str) -> DeviceName {
     DeviceName::from_str(s).unwrap()
 }
@@ -79,6 +97,10 @@ fn playlist(name: &str, rig_name: &str) -> (PlaylistName, Playlist) {
 }
 
 fn rig(name: &str) -> (RigName, slipway_engine::Rig) {
+    rig_with_output(name, serde_json::json!({ "foo": "bar" }))
+}
+
+fn rig_with_output(name: &str, output: serde_json::Value) -> (RigName, slipway_engine::Rig) {
     (
         rn(name),
         slipway_engine::Rig::for_test(Rigging {
@@ -88,9 +110,7 @@ fn rig(name: &str) -> (RigName, slipway_engine::Rig) {
                     slipway_engine::SlipwayReference::Special(
                         SpecialComponentReference::Passthrough,
                     ),
-                    Some(serde_json::json!({
-                        "foo": "bar"
-                    })),
+                    Some(output),
                 ),
             )]
             .into_iter()
@@ -143,11 +163,25 @@ async fn when_devices_playlists_and_rigs_do_not_exist_should_return_not_found()
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_auth_for_key(""),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: HashMap::new(),
             playlists: HashMap::new(),
@@ -155,7 +189,7 @@ async fn when_devices_playlists_and_rigs_do_not_exist_should_return_not_found()
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     {
         let request = test::TestRequest::get().uri("/devices/foo").to_request();
@@ -190,11 +224,25 @@ async fn when_devices_playlists_and_rigs_exist_it_should_execute_rigs() {
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_auth_for_key(""),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -202,7 +250,7 @@ async fn when_devices_playlists_and_rigs_exist_it_should_execute_rigs() {
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     async fn assert_response(response: ServiceResponse<impl MessageBody>, has_refresh_rate: bool) {
         let status = response.status();
@@ -251,11 +299,25 @@ async fn when_auth_not_supplied_it_should_return_unauthorized() {
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_auth_for_key("auth123"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -263,7 +325,7 @@ async fn when_auth_not_supplied_it_should_return_unauthorized() {
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     async fn assert_response(
         response: Result<ServiceResponse<impl MessageBody>, actix_web::Error>,
@@ -304,11 +366,25 @@ async fn when_auth_not_supplied_it_should_allow_favicon_requests() {
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_auth_for_key("auth123"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -316,7 +392,7 @@ async fn when_auth_not_supplied_it_should_allow_favicon_requests() {
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     let request = test::TestRequest::get().uri("/favicon.ico").to_request();
     let response = test::try_call_service(&app, request).await.unwrap();
@@ -329,11 +405,25 @@ async fn when_auth_incorrect_it_should_return_unauthorized() {
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_device_auth_for_key("auth456", "d_1"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -341,7 +431,7 @@ async fn when_auth_incorrect_it_should_return_unauthorized() {
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     async fn assert_response(
         response: Result<ServiceResponse<impl MessageBody>, actix_web::Error>,
@@ -385,11 +475,25 @@ async fn when_auth_for_incorrect_device_it_should_return_unauthorized() {
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_device_auth_for_key("auth1234", "d_2"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -397,7 +501,7 @@ async fn when_auth_for_incorrect_device_it_should_return_unauthorized() {
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     async fn assert_response(
         response: Result<ServiceResponse<impl MessageBody>, actix_web::Error>,
@@ -445,11 +549,25 @@ async fn when_auth_supplied_it_should_execute_rigs() {
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_auth_for_key("auth123"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -457,7 +575,7 @@ async fn when_auth_supplied_it_should_execute_rigs() {
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     async fn assert_response(response: ServiceResponse<impl MessageBody>, has_refresh_rate: bool) {
         let status = response.status();
@@ -504,16 +622,244 @@ async fn when_auth_supplied_it_should_execute_rigs() {
     }
 }
 
+#[test_log::test(actix_web::test)]
+async fn when_etag_matches_rig_output_it_should_return_not_modified() {
+    let config = SlipwayServeConfig {
+        log_level: Some("debug".to_string()),
+        registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
+        environment: SlipwayServeEnvironment::for_test(),
+        rig_permissions: HashMap::new(),
+        api_keys: create_auth_for_key("auth123"),
+        show_api_keys: ShowApiKeys::Never,
+        port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
+        repository: RepositoryConfig::Memory {
+            devices: HashMap::new(),
+            playlists: HashMap::new(),
+            rigs: vec![rig("r_1")].into_iter().collect(),
+        },
+    };
+
+    let app = test::init_service(create_test_app(config, None)).await;
+
+    let etag = {
+        let request = test::TestRequest::get()
+            .uri("/rigs/r_1?format=json")
+            .append_header(("Authorization", "auth123"))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        response
+            .headers()
+            .get("etag")
+            .expect("Response should have an ETag")
+            .to_str()
+            .unwrap()
+            .to_string()
+    };
+
+    {
+        let request = test::TestRequest::get()
+            .uri("/rigs/r_1?format=json")
+            .append_header(("Authorization", "auth123"))
+            .append_header(("If-None-Match", format!("\"{etag}\"")))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(get_body(response).await.is_empty());
+    }
+
+    {
+        // The ETag depends on the requested format.
+        let request = test::TestRequest::get()
+            .uri("/rigs/r_1?format=json&rotate=90")
+            .append_header(("Authorization", "auth123"))
+            .append_header(("If-None-Match", etag))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
+
+#[test_log::test(actix_web::test)]
+async fn when_json_accepted_and_format_not_specified_it_should_return_json() {
+    let config = SlipwayServeConfig {
+        log_level: Some("debug".to_string()),
+        registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
+        environment: SlipwayServeEnvironment::for_test(),
+        rig_permissions: HashMap::new(),
+        api_keys: create_auth_for_key("auth123"),
+        show_api_keys: ShowApiKeys::Never,
+        port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
+        repository: RepositoryConfig::Memory {
+            devices: HashMap::new(),
+            playlists: HashMap::new(),
+            rigs: vec![rig("r_1")].into_iter().collect(),
+        },
+    };
+
+    let app = test::init_service(create_test_app(config, None)).await;
+
+    {
+        let request = test::TestRequest::get()
+            .uri("/rigs/r_1")
+            .append_header(("Authorization", "auth123"))
+            .append_header(("Accept", "application/json"))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            get_body_json(response).await,
+            serde_json::json!({"foo": "bar"})
+        );
+    }
+
+    {
+        // The rig's output is not an image, so the default format fails.
+        let request = test::TestRequest::get()
+            .uri("/rigs/r_1")
+            .append_header(("Authorization", "auth123"))
+            .append_header(("Accept", "*/*"))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[test_log::test(actix_web::test)]
+async fn when_svg_requested_it_should_return_svg() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"/>"#;
+
+    let config = SlipwayServeConfig {
+        log_level: Some("debug".to_string()),
+        registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
+        environment: SlipwayServeEnvironment::for_test(),
+        rig_permissions: HashMap::new(),
+        api_keys: create_auth_for_key("auth123"),
+        show_api_keys: ShowApiKeys::Never,
+        port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
+        repository: RepositoryConfig::Memory {
+            devices: HashMap::new(),
+            playlists: HashMap::new(),
+            rigs: vec![
+                rig_with_output("r_1", serde_json::json!({ "svg": svg })),
+                rig("r_2"),
+            ]
+            .into_iter()
+            .collect(),
+        },
+    };
+
+    let app = test::init_service(create_test_app(config, None)).await;
+
+    {
+        let request = test::TestRequest::get()
+            .uri("/rigs/r_1?format=svg")
+            .append_header(("Authorization", "auth123"))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "image/svg+xml"
+        );
+        assert_eq!(
+            response.headers().get("content-security-policy").unwrap(),
+            "default-src 'none'; style-src 'unsafe-inline'"
+        );
+        assert_eq!(
+            response.headers().get("x-content-type-options").unwrap(),
+            "nosniff"
+        );
+        assert_eq!(get_body(response).await, svg);
+    }
+
+    {
+        let request = test::TestRequest::get()
+            .uri("/rigs/r_1")
+            .append_header(("Authorization", "auth123"))
+            .append_header(("Accept", "image/svg+xml"))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(get_body(response).await, svg);
+    }
+
+    {
+        let request = test::TestRequest::get()
+            .uri("/rigs/r_2?format=svg")
+            .append_header(("Authorization", "auth123"))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
 #[test_log::test(actix_web::test)]
 async fn when_device_auth_supplied_it_should_execute_rigs() {
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_device_auth_for_key("auth456", "d_1"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -521,7 +867,7 @@ async fn when_device_auth_supplied_it_should_execute_rigs() {
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     async fn assert_response(response: ServiceResponse<impl MessageBody>, has_refresh_rate: bool) {
         let status = response.status();
@@ -555,11 +901,25 @@ async fn when_device_result_spec_it_should_pass_through_to_url() {
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_auth_for_key("auth123"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device_with_spec(
                 "d_1",
@@ -577,7 +937,7 @@ async fn when_device_result_spec_it_should_pass_through_to_url() {
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     let request = test::TestRequest::get()
         .uri("/devices/d_1")
@@ -597,11 +957,25 @@ async fn when_device_result_spec_query_string_should_override() {
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_auth_for_key("auth123"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device_with_spec(
                 "d_1",
@@ -619,7 +993,7 @@ async fn when_device_result_spec_query_string_should_override() {
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     let request = test::TestRequest::get()
         .uri("/devices/d_1?format=html_js&image_format=bmp_1bit&rotate=180")
@@ -633,3 +1007,198 @@ async fn when_device_result_spec_query_string_should_override() {
 
     assert!(body.contains("/devices/d_1?format=image&image_format=bmp_1bit&rotate=180"));
 }
+
+#[test_log::test(actix_web::test)]
+async fn when_preview_enabled_it_should_list_devices_playlists_and_rigs() {
+    let config = SlipwayServeConfig {
+        log_level: Some("debug".to_string()),
+        registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
+        environment: SlipwayServeEnvironment::for_test(),
+        rig_permissions: HashMap::new(),
+        api_keys: create_auth_for_key("auth123"),
+        show_api_keys: ShowApiKeys::Never,
+        port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: true,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
+        repository: RepositoryConfig::Memory {
+            devices: vec![device("d_1", "p_1")].into_iter().collect(),
+            playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
+            rigs: vec![rig("r_1")].into_iter().collect(),
+        },
+    };
+
+    let app = test::init_service(create_test_app(config, None)).await;
+
+    let request = test::TestRequest::get()
+        .uri("/preview?authorization=auth123")
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    let status = response.status();
+    let body = get_body(response).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains(r#"src="/devices/d_1?format=image&amp;authorization=auth123""#));
+    assert!(body.contains(r#"src="/playlists/p_1?format=image&amp;authorization=auth123""#));
+    assert!(body.contains(r#"src="/rigs/r_1?format=image&amp;authorization=auth123""#));
+}
+
+#[test_log::test(actix_web::test)]
+async fn when_preview_not_enabled_it_should_return_not_found() {
+    let config = SlipwayServeConfig {
+        log_level: Some("debug".to_string()),
+        registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
+        environment: SlipwayServeEnvironment::for_test(),
+        rig_permissions: HashMap::new(),
+        api_keys: create_auth_for_key("auth123"),
+        show_api_keys: ShowApiKeys::Never,
+        port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
+        repository: RepositoryConfig::Memory {
+            devices: HashMap::new(),
+            playlists: HashMap::new(),
+            rigs: HashMap::new(),
+        },
+    };
+
+    let app = test::init_service(create_test_app(config, None)).await;
+
+    let request = test::TestRequest::get()
+        .uri("/preview?authorization=auth123")
+        .to_request();
+    let response = test::call_service(&app, request).await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[test_log::test(actix_web::test)]
+async fn when_preview_enabled_it_should_require_authorization() {
+    let config = SlipwayServeConfig {
+        api_keys: create_auth_for_key("auth123"),
+        preview: true,
+        ..Default::default()
+    };
+
+    let app = test::init_service(create_test_app(config, None)).await;
+
+    for uri in ["/preview", "/preview?authorization=auth456"] {
+        let request = test::TestRequest::get().uri(uri).to_request();
+        let response = test::try_call_service(&app, request).await;
+        let status = match response {
+            Ok(response) => response.status(),
+            Err(e) => e.error_response().status(),
+        };
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[test_log::test(actix_web::test)]
+async fn it_should_create_render_queue_for_each_server() {
+    let limited = SharedServeState::new(&SlipwayServeConfig {
+        max_concurrent_renders: Some(1),
+        max_queued_renders: Some(0),
+        ..Default::default()
+    });
+    let unlimited = SharedServeState::new(&SlipwayServeConfig::default());
+
+    let _permit = limited.render_queue.acquire().await.unwrap();
+
+    // Workers of the same server share its queue.
+    assert!(limited.clone().render_queue.acquire().await.is_err());
+
+    // Other servers use their own configuration.
+    assert!(unlimited.render_queue.acquire().await.is_ok());
+}
+
+#[test_log::test(actix_web::test)]
+async fn it_should_reload_for_api_keys_not_associated_with_a_device() {
+    let mut api_keys = create_auth_for_key("auth123");
+    api_keys.extend(create_device_auth_for_key("auth456", "d_1"));
+
+    let config = SlipwayServeConfig {
+        log_level: Some("debug".to_string()),
+        registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
+        environment: SlipwayServeEnvironment::for_test(),
+        rig_permissions: HashMap::new(),
+        api_keys,
+        show_api_keys: ShowApiKeys::Never,
+        port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
+        repository: RepositoryConfig::Memory {
+            devices: vec![device("d_1", "p_1")].into_iter().collect(),
+            playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
+            rigs: vec![rig("r_1")].into_iter().collect(),
+        },
+    };
+
+    let app = test::init_service(create_test_app(config, None)).await;
+
+    {
+        let request = test::TestRequest::post()
+            .uri("/reload")
+            .append_header(("Authorization", "auth123"))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    {
+        let request = test::TestRequest::post()
+            .uri("/reload")
+            .append_header(("Authorization", "auth456"))
+            .to_request();
+        let response = test::try_call_service(&app, request).await;
+        let status = match response {
+            Ok(response) => response.status(),
+            Err(e) => e.error_response().status(),
+        };
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    {
+        let request = test::TestRequest::post().uri("/reload").to_request();
+        let response = test::try_call_service(&app, request).await;
+        let status = match response {
+            Ok(response) => response.status(),
+            Err(e) => e.error_response().status(),
+        };
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::collections::HashMap;
 
 use actix_web::body::MessageBody;
 use actix_web::dev::ServiceResponse;
@@ -9,11 +9,11 @@ use crate::serve::api_tests::{
 };
 use crate::serve::repository::{RigResultImageFormat, RigResultPartialSpec};
 use crate::serve::{
-    ACCESS_TOKEN_HEADER, AUTHORIZATION_HEADER, ShowApiKeys, SlipwayServeEnvironment,
+    ACCESS_TOKEN_HEADER, AUTHORIZATION_HEADER, ShowApiKeys, SlipwayServeEnvironment, WarmupMode,
 };
-use crate::serve::{RepositoryConfig, SlipwayServeConfig, create_app};
+use crate::serve::{RepositoryConfig, SlipwayServeConfig};
 
-use super::{device, get_body_json, playlist, rig};
+use super::{create_test_app, device, get_body_json, playlist, rig};
 
 const MAC: &str = "aa:bb:cc:00:00:01";
 const MAC2: &str = "aa:bb:cc:00:00:02";
@@ -29,11 +29,25 @@ async fn when_no_device_associated_with_api_key_it_should_return_forbidden() {
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_auth_for_key(API_KEY),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -41,7 +55,7 @@ async fn when_no_device_associated_with_api_key_it_should_return_forbidden() {
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, secret())).await;
+    let app = test::init_service(create_test_app(config, secret())).await;
 
     let request = test::TestRequest::get()
         .uri("/trmnl/api/display")
@@ -60,11 +74,25 @@ async fn when_api_key_incorrect_it_should_return_unauthorized() {
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_device_auth_for_key(API_KEY, "d_1"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -72,7 +100,7 @@ async fn when_api_key_incorrect_it_should_return_unauthorized() {
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, secret())).await;
+    let app = test::init_service(create_test_app(config, secret())).await;
 
     let request = test::TestRequest::get()
         .uri("/trmnl/api/display")
@@ -91,11 +119,25 @@ async fn when_valid_request_and_secret_it_should_return_rig_result_with_sas() {
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_device_auth_for_key(API_KEY, "d_1"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -103,7 +145,7 @@ async fn when_valid_request_and_secret_it_should_return_rig_result_with_sas() {
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, secret())).await;
+    let app = test::init_service(create_test_app(config, secret())).await;
 
     let request = test::TestRequest::get()
         .uri("/trmnl/api/display")
@@ -132,11 +174,25 @@ async fn when_valid_request_and_no_secret_it_should_return_rig_result_with_api_k
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_device_auth_for_key(API_KEY, "d_1"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -144,7 +200,7 @@ async fn when_valid_request_and_no_secret_it_should_return_rig_result_with_api_k
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     async fn assert_response(response: ServiceResponse<impl MessageBody>) {
         let status = response.status();
@@ -197,11 +253,25 @@ async fn when_valid_request_and_image_format_overridden_it_should_return_specifi
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_device_auth_for_key(API_KEY, "d_1"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device_with_spec(
                 "d_1",
@@ -218,7 +288,7 @@ async fn when_valid_request_and_image_format_overridden_it_should_return_specifi
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     async fn assert_response(response: ServiceResponse<impl MessageBody>) {
         let status = response.status();
@@ -1,13 +1,13 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::collections::HashMap;
 
 use actix_web::{http::StatusCode, test};
 use slipway_host::hash_string;
 
 use crate::serve::api_tests::create_device_auth_for_key;
-use crate::serve::{ID_HEADER, ShowApiKeys, SlipwayServeEnvironment};
-use crate::serve::{RepositoryConfig, SlipwayServeConfig, create_app};
+use crate::serve::{ID_HEADER, ShowApiKeys, SlipwayServeEnvironment, WarmupMode};
+use crate::serve::{RepositoryConfig, SlipwayServeConfig};
 
-use super::{device, get_body_json, playlist, rig};
+use super::{create_test_app, device, get_body_json, playlist, rig};
 
 const MAC: &str = "aa:bb:cc:00:00:01";
 const HASHED_API_KEY: &str = "bar";
@@ -18,11 +18,25 @@ async fn when_device_already_configured_for_trmnl_it_should_return_new_credentia
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: create_device_auth_for_key(API_KEY, "d_1"),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -30,7 +44,7 @@ async fn when_device_already_configured_for_trmnl_it_should_return_new_credentia
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     let request = test::TestRequest::get()
         .uri("/trmnl/api/setup")
@@ -48,11 +62,25 @@ async fn when_device_not_configured_for_trmnl_it_should_return_new_credentials()
     let config = SlipwayServeConfig {
         log_level: Some("debug".to_string()),
         registry_urls: vec![],
+        registry_headers: vec![],
+        user_agent: None,
         environment: SlipwayServeEnvironment::for_test(),
         rig_permissions: HashMap::new(),
         api_keys: Vec::new(),
         show_api_keys: ShowApiKeys::Never,
         port: None,
+        proxy: None,
+        aot_registry_url: None,
+        aot_registry_key: None,
+        preview: false,
+        wasm_opt_level: None,
+        shutdown_timeout_seconds: None,
+        warmup: WarmupMode::Lazy,
+        max_concurrent_renders: None,
+        max_queued_renders: None,
+        js_time_limit_seconds: None,
+        js_step_limit: None,
+        max_concurrent_components: None,
         repository: RepositoryConfig::Memory {
             devices: vec![device("d_1", "p_1")].into_iter().collect(),
             playlists: vec![playlist("p_1", "r_1")].into_iter().collect(),
@@ -60,7 +88,7 @@ async fn when_device_not_configured_for_trmnl_it_should_return_new_credentials()
         },
     };
 
-    let app = test::init_service(create_app(PathBuf::from("."), None, config, None)).await;
+    let app = test::init_service(create_test_app(config, None)).await;
 
     let request = test::TestRequest::get()
         .uri("/trmnl/api/setup")
@@ -21,6 +21,8 @@ pub async fn add_rig(
             components: Default::default(),
         },
         context: None,
+        outputs: None,
+        templates: None,
     };
 
     repository.set_rig(&name, &rig).await?;
@@ -1,34 +1,252 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use slipway_engine::BasicComponentCache;
+use anyhow::Context;
+use slipway_engine::{
+    BasicComponentCache, ComponentFiles, SlipwayReference, TryAotCompileComponentResult,
+};
+use slipway_host::{SLIPWAY_COMPONENT_WASM_FILE_NAME, hash_bytes};
+use slipway_wasmtime_runner::get_aot_registry_files;
 use tracing::info;
 
-use crate::component_runners::get_component_runners;
+use crate::{
+    component_runners::get_component_runners_with_wasm_options, serve::load_serve_config,
+    summary::Summary,
+};
+
+/// The outcome of AOT compiling all the components in the cache.
+#[derive(Default)]
+struct AotCompileSummary {
+    compiled: usize,
+    up_to_date: usize,
+    skipped: usize,
+    bytes_written: u64,
+    targets: BTreeSet<String>,
+    failed: Vec<AotCompileFailure>,
+}
+
+struct AotCompileFailure {
+    component: SlipwayReference,
+    runner: String,
+    error: String,
+}
 
 pub async fn aot_compile(
+    serve_path: &Path,
     aot_path: PathBuf,
     target: Option<&str>,
+    force: bool,
+    publish_path: Option<PathBuf>,
     component_cache: BasicComponentCache,
 ) -> anyhow::Result<()> {
-    let component_runners = get_component_runners();
+    let config = load_serve_config(serve_path).await?;
+    let aot_registry_key = match &publish_path {
+        Some(_) => Some(config.aot_registry_key()?.context(
+            "Publishing AOT artifacts requires an \"aot_registry_key\" in the serve configuration, \
+            so that the artifacts can be verified when they are fetched from the AOT registry.",
+        )?),
+        None => None,
+    };
+    let component_runners = get_component_runners_with_wasm_options(config.wasm_runner_options());
 
-    let components = component_cache.into_inner();
+    let mut components: Vec<_> = component_cache.into_inner().into_iter().collect();
+    components.sort_by_key(|(reference, _)| reference.to_string());
 
     tokio::fs::create_dir_all(&aot_path).await?;
+    if let Some(publish_path) = &publish_path {
+        tokio::fs::create_dir_all(publish_path).await?;
+    }
+
+    let mut summary = AotCompileSummary::default();
 
     for (name, component) in components {
+        let mut attempted = false;
         for runner in component_runners.iter() {
             match runner
-                .aot_compile(&name, &aot_path, target, Arc::clone(&component.files))
-                .await?
+                .aot_compile(
+                    &name,
+                    &aot_path,
+                    target,
+                    force,
+                    Arc::clone(&component.files),
+                )
+                .await
             {
-                slipway_engine::TryAotCompileComponentResult::Compiled => {
+                Ok(TryAotCompileComponentResult::Compiled {
+                    target,
+                    path,
+                    bytes_written,
+                }) => {
                     info!("AOT compiled \"{name}\" with \"{}\".", runner.identifier());
+                    attempted = true;
+
+                    if let (Some(publish_path), Some(aot_registry_key)) =
+                        (&publish_path, &aot_registry_key)
+                        && let Err(e) = publish_artifact(
+                            &component.files,
+                            &path,
+                            &target,
+                            publish_path,
+                            aot_registry_key,
+                        )
+                        .await
+                    {
+                        summary.failed.push(AotCompileFailure {
+                            component: name.clone(),
+                            runner: runner.identifier(),
+                            error: format!("Failed to publish AOT artifact: {e:#}"),
+                        });
+                        continue;
+                    }
+
+                    summary.compiled += 1;
+                    summary.bytes_written += bytes_written;
+                    summary.targets.insert(target);
+                }
+                Ok(TryAotCompileComponentResult::UpToDate { target, path }) => {
+                    info!("\"{name}\" is up to date for \"{}\".", runner.identifier());
+                    attempted = true;
+
+                    if let (Some(publish_path), Some(aot_registry_key)) =
+                        (&publish_path, &aot_registry_key)
+                        && let Err(e) = publish_artifact(
+                            &component.files,
+                            &path,
+                            &target,
+                            publish_path,
+                            aot_registry_key,
+                        )
+                        .await
+                    {
+                        summary.failed.push(AotCompileFailure {
+                            component: name.clone(),
+                            runner: runner.identifier(),
+                            error: format!("Failed to publish AOT artifact: {e:#}"),
+                        });
+                        continue;
+                    }
+
+                    summary.up_to_date += 1;
+                    summary.targets.insert(target);
+                }
+                Ok(TryAotCompileComponentResult::CannotCompile) => {}
+                Err(e) => {
+                    attempted = true;
+                    summary.failed.push(AotCompileFailure {
+                        component: name.clone(),
+                        runner: runner.identifier(),
+                        error: e.to_string(),
+                    });
                 }
-                slipway_engine::TryAotCompileComponentResult::CannotCompile => {}
             }
         }
+
+        if !attempted {
+            summary.skipped += 1;
+        }
+    }
+
+    create_summary(&summary, target).write(&mut std::io::stdout())?;
+
+    if !summary.failed.is_empty() {
+        anyhow::bail!(
+            "{} component(s) failed to AOT compile.",
+            summary.failed.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Writes the AOT compiled artifact to the publish folder, along with its compatibility
+/// hash and signature, named so that it can be found in an AOT registry by the
+/// WASM hash and target.
+async fn publish_artifact(
+    files: &ComponentFiles,
+    artifact_path: &Path,
+    target: &str,
+    publish_path: &Path,
+    aot_registry_key: &str,
+) -> anyhow::Result<()> {
+    let wasm_bytes = files
+        .try_get_bin(SLIPWAY_COMPONENT_WASM_FILE_NAME)
+        .await?
+        .context("Compiled component has no WASM file")?;
+
+    let registry_files = get_aot_registry_files(
+        aot_registry_key,
+        artifact_path,
+        &hash_bytes(&wasm_bytes),
+        target,
+    )
+    .await?;
+
+    for (file_name, contents) in registry_files {
+        let path = publish_path.join(&file_name);
+        tokio::fs::write(&path, contents)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        info!("Published AOT registry file: {file_name}");
     }
 
     Ok(())
 }
+
+fn create_summary(compile_summary: &AotCompileSummary, requested_target: Option<&str>) -> Summary {
+    let target = if compile_summary.targets.is_empty() {
+        requested_target.unwrap_or("host").to_string()
+    } else {
+        compile_summary
+            .targets
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut summary = Summary::new("AOT compile summary");
+    summary
+        .field("Target", target)
+        .field("Compiled", compile_summary.compiled)
+        .field("Up to date", compile_summary.up_to_date)
+        .field(
+            "Skipped (no AOT compilation required)",
+            compile_summary.skipped,
+        )
+        .field("Failed", compile_summary.failed.len())
+        .field("Total bytes written", compile_summary.bytes_written);
+
+    for failure in &compile_summary.failed {
+        summary.line(format!(
+            "Failed to compile \"{}\" with \"{}\": {}",
+            failure.component, failure.runner, failure.error
+        ));
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_write_requested_target_if_nothing_compiled() {
+        let summary = AotCompileSummary::default();
+
+        let mut output = Vec::new();
+        create_summary(&summary, Some("aarch64-apple-darwin"))
+            .write(&mut output)
+            .unwrap();
+
+        assert!(
+            String::from_utf8(output)
+                .unwrap()
+                .contains("  Target: aarch64-apple-darwin\n")
+        );
+    }
+}
@@ -1,11 +1,18 @@
 use std::{collections::HashMap, path::PathBuf};
 
-use slipway_engine::{BasicComponentCache, BasicComponentsLoader};
-use tracing::info;
+use slipway_engine::{BasicComponentCache, SharedComponentsCache};
+use tracing::{error, info};
 
 use crate::serve::{create_repository, load_serve_config};
 
-pub async fn consolidate(serve_path: PathBuf) -> anyhow::Result<BasicComponentCache> {
+/// Loads the components of every rig, caching any remote components locally.
+///
+/// If `fail_fast` is false then rigs and components which fail to load don't stop
+/// the remaining rigs being consolidated, and every failure is reported at the end.
+pub async fn consolidate(
+    serve_path: PathBuf,
+    fail_fast: bool,
+) -> anyhow::Result<BasicComponentCache> {
     let config = load_serve_config(&serve_path).await?;
     let repository = create_repository(&serve_path, &config.repository);
 
@@ -13,20 +20,50 @@ pub async fn consolidate(serve_path: PathBuf) -> anyhow::Result<BasicComponentCa
 
     let rigs = repository.list_rigs().await?;
 
-    let components_loader = BasicComponentsLoader::builder()
-        .local_base_directory(&serve_path)
-        .registry_lookup_urls(config.registry_urls.clone())
-        .build();
+    let components_loader = config.components_loader_builder(&serve_path)?.build();
+
+    // Components used by several rigs are only loaded once.
+    let shared_components_cache = SharedComponentsCache::new();
+    let components_loader = shared_components_cache.loader(&components_loader);
 
     let mut all_components = HashMap::new();
-    for rig in rigs {
-        info!("Consolidating rig: {rig}");
-        let rig = repository.get_rig(&rig).await?;
-        let cache = BasicComponentCache::primed(&rig, &components_loader).await?;
+    let mut failures = Vec::new();
+    for rig_name in rigs {
+        info!("Consolidating rig: {rig_name}");
+
+        let rig = match repository.get_rig(&rig_name).await {
+            Ok(rig) => rig,
+            Err(e) if fail_fast => return Err(e.into()),
+            Err(e) => {
+                failures.push(format!("Rig \"{rig_name}\": {e}"));
+                continue;
+            }
+        };
+
+        let cache = if fail_fast {
+            BasicComponentCache::primed(&rig, &components_loader).await?
+        } else {
+            let (cache, errors) =
+                BasicComponentCache::primed_collecting_errors(&rig, &components_loader).await;
+            failures.extend(errors.iter().map(|e| format!("Rig \"{rig_name}\": {e}")));
+            cache
+        };
+
         let components = cache.into_inner();
         all_components.extend(components);
     }
 
+    if !failures.is_empty() {
+        for failure in failures.iter() {
+            error!("{failure}");
+        }
+
+        anyhow::bail!(
+            "{} failure(s) occurred while consolidating.",
+            failures.len()
+        );
+    }
+
     info!("Done.");
 
     let all_components_cache = BasicComponentCache::for_primed(all_components);
@@ -5,6 +5,7 @@ mod add_rig;
 mod aot_compile;
 mod consolidate;
 mod init;
+mod verify;
 
 const COMPONENTS_PATH: &str = "components";
 
@@ -16,3 +17,4 @@ pub use aot_compile::aot_compile;
 pub use consolidate::consolidate;
 pub use init::init;
 pub use init::init_serve_config;
+pub use verify::verify;
@@ -0,0 +1,106 @@
+use std::{path::Path, sync::Arc};
+
+use slipway_engine::{BasicComponentCache, SlipwayReference, TryVerifyComponentResult};
+use tracing::info;
+
+use crate::{
+    component_runners::get_component_runners_with_wasm_options, serve::load_serve_config,
+    summary::Summary,
+};
+
+/// The outcome of verifying a single component.
+enum VerifyOutcome {
+    Passed { runner: String },
+    Skipped,
+    Failed { runner: String, error: String },
+}
+
+struct ComponentVerification {
+    component: SlipwayReference,
+    outcome: VerifyOutcome,
+}
+
+/// Checks that every component in the cache could be run, without running it.
+pub async fn verify(serve_path: &Path, component_cache: BasicComponentCache) -> anyhow::Result<()> {
+    let config = load_serve_config(serve_path).await?;
+    let component_runners = get_component_runners_with_wasm_options(config.wasm_runner_options());
+
+    let mut components: Vec<_> = component_cache.into_inner().into_iter().collect();
+    components.sort_by_key(|(reference, _)| reference.to_string());
+
+    let mut verifications = Vec::with_capacity(components.len());
+
+    for (name, component) in components {
+        let mut outcome = VerifyOutcome::Skipped;
+        for runner in component_runners.iter() {
+            match runner.verify(&name, Arc::clone(&component.files)).await {
+                Ok(TryVerifyComponentResult::Verified) => {
+                    info!("Verified \"{name}\" with \"{}\".", runner.identifier());
+                    outcome = VerifyOutcome::Passed {
+                        runner: runner.identifier(),
+                    };
+                    break;
+                }
+                Ok(TryVerifyComponentResult::CannotVerify) => {}
+                Err(e) => {
+                    outcome = VerifyOutcome::Failed {
+                        runner: runner.identifier(),
+                        error: e.to_string(),
+                    };
+                    break;
+                }
+            }
+        }
+
+        verifications.push(ComponentVerification {
+            component: name,
+            outcome,
+        });
+    }
+
+    create_summary(&verifications).write(&mut std::io::stdout())?;
+
+    let failed = verifications
+        .iter()
+        .filter(|v| matches!(v.outcome, VerifyOutcome::Failed { .. }))
+        .count();
+
+    if failed > 0 {
+        anyhow::bail!("{failed} component(s) failed verification.");
+    }
+
+    Ok(())
+}
+
+fn create_summary(verifications: &[ComponentVerification]) -> Summary {
+    let count =
+        |f: fn(&VerifyOutcome) -> bool| verifications.iter().filter(|v| f(&v.outcome)).count();
+
+    let mut summary = Summary::new("Verify summary");
+    summary
+        .field(
+            "Passed",
+            count(|o| matches!(o, VerifyOutcome::Passed { .. })),
+        )
+        .field(
+            "Skipped (no verification available)",
+            count(|o| matches!(o, VerifyOutcome::Skipped)),
+        )
+        .field(
+            "Failed",
+            count(|o| matches!(o, VerifyOutcome::Failed { .. })),
+        );
+
+    for verification in verifications {
+        let component = &verification.component;
+        summary.line(match &verification.outcome {
+            VerifyOutcome::Passed { runner } => format!("Passed \"{component}\" with \"{runner}\""),
+            VerifyOutcome::Skipped => format!("Skipped \"{component}\""),
+            VerifyOutcome::Failed { runner, error } => {
+                format!("Failed \"{component}\" with \"{runner}\": {error}")
+            }
+        });
+    }
+
+    summary
+}
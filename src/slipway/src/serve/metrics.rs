@@ -0,0 +1,61 @@
+use std::fmt::Write;
+
+use actix_web::{HttpResponse, Responder, get, web};
+
+use super::{ServeState, render_queue::RenderQueue};
+
+/// Returns server metrics in the Prometheus text exposition format.
+#[get("/metrics")]
+pub(super) async fn get_metrics(data: web::Data<ServeState>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(format_metrics(&data.shared.render_queue))
+}
+
+fn format_metrics(render_queue: &RenderQueue) -> String {
+    let mut metrics = String::new();
+
+    for (name, help, value) in [
+        (
+            "slipway_render_queue_depth",
+            "The number of rig renders waiting for another render to finish.",
+            render_queue.queue_depth(),
+        ),
+        (
+            "slipway_renders_in_progress",
+            "The number of rig renders currently running.",
+            render_queue.renders_in_progress(),
+        ),
+    ] {
+        writeln!(metrics, "# HELP {name} {help}").expect("Writing to a string should not fail");
+        writeln!(metrics, "# TYPE {name} gauge").expect("Writing to a string should not fail");
+        writeln!(metrics, "{name} {value}").expect("Writing to a string should not fail");
+    }
+
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+
+    use super::*;
+
+    #[slipway_test_async]
+    async fn it_should_format_render_queue_metrics() {
+        let render_queue = RenderQueue::new(Some(1), None);
+        let _permit = render_queue.acquire().await.unwrap();
+
+        assert_eq!(
+            format_metrics(&render_queue),
+            concat!(
+                "# HELP slipway_render_queue_depth The number of rig renders waiting for another render to finish.\n",
+                "# TYPE slipway_render_queue_depth gauge\n",
+                "slipway_render_queue_depth 0\n",
+                "# HELP slipway_renders_in_progress The number of rig renders currently running.\n",
+                "# TYPE slipway_renders_in_progress gauge\n",
+                "slipway_renders_in_progress 1\n",
+            )
+        );
+    }
+}
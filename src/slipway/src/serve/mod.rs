@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 
 use actix_cors::Cors;
 use actix_web::body::MessageBody;
@@ -12,13 +13,18 @@ use chrono_tz::Tz;
 use repository::{Device, Playlist, ServeRepository};
 use serde::{Deserialize, Serialize};
 
-use slipway_engine::TEST_TIMEZONE;
+use slipway_engine::{
+    BasicComponentsLoader, BasicComponentsLoaderBuilder, SharedComponentsCache, TEST_TIMEZONE,
+};
+use slipway_wasmtime_runner::{WasmComponentRunner, WasmComponentRunnerOptions, WasmOptLevel};
 use tracing::{debug, info, warn};
 
 use crate::permissions::PermissionsOwned;
 use crate::primitives::{DeviceName, PlaylistName, RigName};
+use crate::serve::render_queue::RenderQueue;
 use crate::serve::responses::ServeError;
 
+mod api_keys;
 #[cfg(test)]
 mod api_tests;
 mod auth;
@@ -26,11 +32,16 @@ mod bmp;
 pub(super) mod commands;
 mod devices;
 mod favicon;
+mod metrics;
 mod playlists;
+mod preview;
+mod reload;
+mod render_queue;
 mod repository;
 mod responses;
 mod rigs;
 pub(super) mod trmnl;
+mod warmup;
 
 const SLIPWAY_SECRET_KEY: &str = "SLIPWAY_SECRET";
 
@@ -48,6 +59,27 @@ const SERVE_CONFIG_FILE_NAME: &str = "slipway_serve.json";
 
 const GENERATED_API_KEY_LENGTH: usize = 52;
 
+/// Matches the actix-web default, so in-flight rig renders normally have time to finish.
+const DEFAULT_SHUTDOWN_TIMEOUT_SECONDS: u64 = 30;
+
+/// How long clients are asked to wait before retrying when the render queue is full.
+const RENDER_QUEUE_RETRY_AFTER_SECONDS: u64 = 5;
+
+/// The WASM runner shared by every rig run by the server, so that each WASM
+/// component is only compiled once.
+static SHARED_WASM_RUNNER: OnceLock<WasmComponentRunner> = OnceLock::new();
+
+/// Returns the shared WASM runner, creating it with the configured options
+/// if necessary. The configuration can't change while the server is running.
+fn shared_wasm_runner(config: &SlipwayServeConfig) -> WasmComponentRunner {
+    SHARED_WASM_RUNNER
+        .get_or_init(|| {
+            WasmComponentRunner::try_new_with_options(config.wasm_runner_options())
+                .expect("Should be able to create Wasmtime engine with no target")
+        })
+        .clone()
+}
+
 fn truncate_hashed_api_key(hashed_api_key: &str) -> &str {
     &hashed_api_key[..6]
 }
@@ -56,6 +88,30 @@ pub(super) fn create_api_key() -> String {
     nanoid::nanoid!(GENERATED_API_KEY_LENGTH)
 }
 
+/// State shared by every worker of a server, created once per server so that
+/// it reflects that server's configuration.
+#[derive(Debug, Clone)]
+struct SharedServeState {
+    /// Shared so that the configured render limits apply across all requests.
+    pub render_queue: Arc<RenderQueue>,
+
+    /// Components loaded for one rig are reused by every later rig run by the server,
+    /// until the cache is cleared by a reload.
+    pub components_cache: SharedComponentsCache,
+}
+
+impl SharedServeState {
+    pub fn new(config: &SlipwayServeConfig) -> Self {
+        Self {
+            render_queue: Arc::new(RenderQueue::new(
+                config.max_concurrent_renders,
+                config.max_queued_renders,
+            )),
+            components_cache: SharedComponentsCache::new(),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct ServeState {
     pub base_path: PathBuf,
@@ -63,6 +119,7 @@ struct ServeState {
     pub config: SlipwayServeConfig,
     pub secret: Option<String>,
     pub repository: Box<dyn ServeRepository>,
+    pub shared: SharedServeState,
 }
 
 impl ServeState {
@@ -72,6 +129,7 @@ impl ServeState {
         config: SlipwayServeConfig,
         secret: Option<String>,
         repository: Box<dyn ServeRepository>,
+        shared: SharedServeState,
     ) -> Self {
         Self {
             base_path,
@@ -79,6 +137,7 @@ impl ServeState {
             config,
             secret,
             repository,
+            shared,
         }
     }
 }
@@ -92,6 +151,14 @@ struct SlipwayServeConfig {
     #[serde(default)]
     registry_urls: Vec<String>,
 
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    registry_headers: Vec<RegistryHeaderConfig>,
+
+    /// The User-Agent sent when downloading components.
+    /// Defaults to `slipway/<version>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    user_agent: Option<String>,
+
     #[serde(flatten)]
     environment: SlipwayServeEnvironment,
 
@@ -107,10 +174,140 @@ struct SlipwayServeConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     port: Option<u16>,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    proxy: Option<String>,
+
+    /// An optional URL template for fetching AOT compiled WASM components,
+    /// such as `https://registry.example.com/aot/{hash}.{target}.wasm_aot`.
+    /// The URL must use `https`, and `aot_registry_key` must also be set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aot_registry_url: Option<String>,
+
+    /// The key used to sign AOT compiled WASM components when publishing them,
+    /// and to verify them when fetching them from the `aot_registry_url`.
+    /// The value can reference environment variables as `${NAME}` so that the key
+    /// doesn't need to be stored in the configuration file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aot_registry_key: Option<String>,
+
+    /// Whether to expose the developer preview page.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    preview: bool,
+
+    /// The Cranelift optimization level used when compiling WASM components,
+    /// either `none`, `speed` or `speed_and_size`. Defaults to `speed`.
+    /// Lower levels compile faster, which can reduce latency when components
+    /// are not AOT compiled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wasm_opt_level: Option<WasmOptLevel>,
+
+    /// How long to wait for in-flight requests to finish when the server receives
+    /// SIGTERM, before closing their connections. New connections are not accepted
+    /// while waiting. Defaults to 30 seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shutdown_timeout_seconds: Option<u64>,
+
+    /// When WASM components are compiled. With `lazy` each component is compiled
+    /// the first time a rig using it is run, and with `eager` the components of
+    /// every rig are compiled at startup, before requests are accepted.
+    /// Either way, compiled components are reused by later requests.
+    #[serde(default, skip_serializing_if = "WarmupMode::is_default")]
+    warmup: WarmupMode,
+
+    /// The maximum number of rigs rendered at once. Further requests wait for
+    /// a render to finish before starting. Defaults to no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_concurrent_renders: Option<usize>,
+
+    /// The maximum number of requests which can wait for a render when
+    /// `max_concurrent_renders` is reached. Requests arriving when the queue is
+    /// full receive a `503` response with a `Retry-After` header. Defaults to no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_queued_renders: Option<usize>,
+
+    /// The maximum number of seconds each Javascript component can run for.
+    /// The limit is checked each time the component calls the host, and when
+    /// it finishes. Defaults to no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    js_time_limit_seconds: Option<u64>,
+
+    /// The maximum number of iterations of any single loop in a Javascript
+    /// component, which stops loops that never call the host. Defaults to no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    js_step_limit: Option<u64>,
+
+    /// The maximum number of components which can execute concurrently within each
+    /// rendered rig, including callouts and fragments. Components beyond the limit
+    /// wait for others to finish. Defaults to no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_concurrent_components: Option<usize>,
+
     #[serde(default, skip_serializing_if = "RepositoryConfig::is_default")]
     repository: RepositoryConfig,
 }
 
+/// A header sent when downloading components, for example to authenticate with
+/// a private registry. The value can reference environment variables as `${NAME}`
+/// so that tokens don't need to be stored in the configuration file.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+struct RegistryHeaderConfig {
+    /// The registry URL the header applies to. If omitted it applies to all downloads.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    registry_url: Option<String>,
+    name: String,
+    value: String,
+}
+
+impl SlipwayServeConfig {
+    /// Returns the AOT registry key, with any environment variables resolved.
+    fn aot_registry_key(&self) -> anyhow::Result<Option<String>> {
+        self.aot_registry_key
+            .as_deref()
+            .map(crate::utils::interpolate_env_vars)
+            .transpose()
+            .context("Failed to resolve the AOT registry key")
+    }
+
+    /// Returns the options used to compile WASM components.
+    fn wasm_runner_options(&self) -> WasmComponentRunnerOptions {
+        WasmComponentRunnerOptions {
+            opt_level: self.wasm_opt_level.unwrap_or_default(),
+        }
+    }
+
+    /// Creates a components loader builder using the configured registries and headers.
+    fn components_loader_builder(
+        &self,
+        base_path: &Path,
+    ) -> anyhow::Result<BasicComponentsLoaderBuilder> {
+        let mut builder = BasicComponentsLoader::builder()
+            .local_base_directory(base_path)
+            .registry_lookup_urls(self.registry_urls.clone())
+            .proxy(self.proxy.clone());
+
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+
+        for header in &self.registry_headers {
+            let value = crate::utils::interpolate_env_vars(&header.value).with_context(|| {
+                format!(
+                    "Failed to resolve value of registry header \"{}\"",
+                    header.name
+                )
+            })?;
+
+            builder = match &header.registry_url {
+                Some(registry_url) => builder.registry_header(registry_url, &header.name, &value),
+                None => builder.header(&header.name, &value),
+            };
+        }
+
+        Ok(builder)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(deny_unknown_fields)]
 struct SlipwayServeEnvironment {
@@ -175,6 +372,20 @@ impl ShowApiKeys {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum WarmupMode {
+    #[default]
+    Lazy,
+    Eager,
+}
+
+impl WarmupMode {
+    pub fn is_default(&self) -> bool {
+        matches!(self, WarmupMode::Lazy)
+    }
+}
+
 #[derive(Clone)]
 struct RequestState {
     pub supplied_api_key: Option<SuppliedApiKey>,
@@ -192,9 +403,18 @@ struct SuppliedResolvedApiKey {
     pub resolved: RegisteredApiKey,
 }
 
-pub async fn serve(path: PathBuf, aot_path: Option<PathBuf>) -> anyhow::Result<()> {
-    let config = load_serve_config(&path).await?;
-    serve_with_config(path, aot_path, config).await?;
+pub async fn serve(
+    path: PathBuf,
+    aot_path: Option<PathBuf>,
+    preview: bool,
+    open: bool,
+) -> anyhow::Result<()> {
+    let mut config = load_serve_config(&path).await?;
+    api_keys::add_environment_api_keys(&mut config).await?;
+    if preview || open {
+        config.preview = true;
+    }
+    serve_with_config(path, aot_path, config, open).await?;
     Ok(())
 }
 
@@ -257,6 +477,7 @@ async fn serve_with_config(
     root: PathBuf,
     aot_path: Option<PathBuf>,
     config: SlipwayServeConfig,
+    open: bool,
 ) -> anyhow::Result<()> {
     super::configure_tracing(config.log_level.clone());
 
@@ -264,18 +485,52 @@ async fn serve_with_config(
 
     let secret = std::env::var(SLIPWAY_SECRET_KEY).ok();
     let port = config.port.unwrap_or(8080);
+    let preview = config.preview;
+    let shutdown_timeout_seconds = config
+        .shutdown_timeout_seconds
+        .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECONDS);
+
+    let shared = SharedServeState::new(&config);
+
+    if config.warmup == WarmupMode::Eager {
+        warmup::warm_up_rigs(
+            &root,
+            aot_path.as_deref(),
+            &config,
+            &shared.components_cache,
+        )
+        .await;
+    }
 
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         create_app(
             root.clone(),
             aot_path.clone(),
             config.clone(),
             secret.clone(),
+            shared.clone(),
         )
     })
+    // On SIGTERM the server stops accepting connections and waits for in-flight
+    // requests to finish, so that rolling deployments don't drop requests.
+    .shutdown_timeout(shutdown_timeout_seconds)
     .bind(("0.0.0.0", port))?
-    .run()
-    .await?;
+    .run();
+
+    if preview {
+        let preview_url = format!("http://localhost:{port}{}", preview::PREVIEW_PATH);
+        info!(
+            "Preview available at {preview_url} (append ?{AUTHORIZATION_HEADER}=<API key> to authorize)"
+        );
+
+        if open && let Err(e) = open::that_detached(&preview_url) {
+            warn!("Failed to open browser at {preview_url}: {e}");
+        }
+    }
+
+    server.await?;
+
+    info!("Slipway Serve stopped.");
 
     Ok(())
 }
@@ -285,6 +540,7 @@ fn create_app(
     aot_path: Option<PathBuf>,
     config: SlipwayServeConfig,
     secret: Option<String>,
+    shared: SharedServeState,
 ) -> App<
     impl ServiceFactory<
         ServiceRequest,
@@ -298,7 +554,7 @@ fn create_app(
 
     App::new()
         .app_data(web::Data::new(ServeState::new(
-            root, aot_path, config, secret, repository,
+            root, aot_path, config, secret, repository, shared,
         )))
         .wrap(
             Cors::default()
@@ -321,8 +577,11 @@ fn create_app(
             web::scope("")
                 .wrap(from_fn(auth::auth_middleware))
                 .service(rigs::get_rig::get_rig)
+                .service(metrics::get_metrics)
+                .service(reload::reload)
                 .service(playlists::get_playlist::get_playlist)
-                .service(devices::get_device::get_device),
+                .service(devices::get_device::get_device)
+                .service(preview::get_preview),
         )
 }
 
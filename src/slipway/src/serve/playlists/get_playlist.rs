@@ -72,10 +72,17 @@ pub async fn get_playlist_response(
     let rig_name = playlist_item.rig;
     let refresh_rate_seconds = playlist_item.refresh_rate_seconds;
 
-    let rig_response =
-        super::super::rigs::get_rig::get_rig_response(&rig_name, device, result_spec, state, req)
-            .instrument(debug_span!("rig", ""=%rig_name))
-            .await?;
+    // Playlists are displayed by devices, so the requested or device's format is always used.
+    let rig_response = super::super::rigs::get_rig::get_rig_response(
+        &rig_name,
+        device,
+        result_spec,
+        false,
+        state,
+        req,
+    )
+    .instrument(debug_span!("rig", ""=%rig_name))
+    .await?;
 
     Ok(PlaylistResponse {
         refresh_rate_seconds,
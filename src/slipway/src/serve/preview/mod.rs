@@ -0,0 +1,120 @@
+use std::fmt::Display;
+
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, get, web};
+use serde::Deserialize;
+
+use super::{ServeState, responses::ServeError};
+
+pub(super) const PREVIEW_PATH: &str = "/preview";
+
+/// How often the preview page reloads to refresh the rendered output.
+const PREVIEW_REFRESH_SECONDS: u32 = 60;
+
+#[derive(Deserialize)]
+struct PreviewQuery {
+    /// An API key to forward to the rig output endpoints.
+    #[serde(default)]
+    authorization: Option<String>,
+}
+
+/// A developer preview page listing the devices, playlists and rigs,
+/// with their rendered output loaded from the usual endpoints.
+///
+/// The page requires the same authorization as the endpoints it loads from,
+/// as it reveals the names of everything the server hosts.
+#[get("/preview")]
+pub(super) async fn get_preview(
+    query: web::Query<PreviewQuery>,
+    data: web::Data<ServeState>,
+) -> Result<HttpResponse, ServeError> {
+    let state = data.into_inner();
+
+    if !state.config.preview {
+        return Err(ServeError::UserFacing(
+            StatusCode::NOT_FOUND,
+            "Preview is not enabled.".to_string(),
+        ));
+    }
+
+    let query = query.into_inner();
+    let repository = &state.repository;
+
+    let mut devices = repository.list_devices().await?;
+    devices.sort_by_key(|n| n.to_string());
+    let mut playlists = repository.list_playlists().await?;
+    playlists.sort_by_key(|n| n.to_string());
+    let mut rigs = repository.list_rigs().await?;
+    rigs.sort_by_key(|n| n.to_string());
+
+    let auth_query = query
+        .authorization
+        .map(|key| {
+            format!(
+                "&authorization={}",
+                url::form_urlencoded::byte_serialize(key.as_bytes()).collect::<String>()
+            )
+        })
+        .unwrap_or_default();
+
+    let sections = [
+        write_section("Devices", "devices", &devices, &auth_query),
+        write_section("Playlists", "playlists", &playlists, &auth_query),
+        write_section("Rigs", "rigs", &rigs, &auth_query),
+    ]
+    .join("\n");
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta http-equiv="refresh" content="{PREVIEW_REFRESH_SECONDS}">
+<title>Slipway Preview</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+.items {{ display: flex; flex-wrap: wrap; gap: 1.5em; }}
+figure {{ margin: 0; }}
+img {{ max-width: 480px; border: 1px solid #ccc; }}
+</style>
+</head>
+<body>
+<h1>Slipway Preview</h1>
+{sections}
+</body>
+</html>
+"#
+    );
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(html))
+}
+
+fn write_section(title: &str, path: &str, names: &[impl Display], auth_query: &str) -> String {
+    if names.is_empty() {
+        return format!("<h2>{title}</h2>\n<p>None.</p>");
+    }
+
+    let items = names
+        .iter()
+        .map(|name| {
+            let src = escape_html(&format!("/{path}/{name}?format=image{auth_query}"));
+            let name = escape_html(&name.to_string());
+            format!(
+                r#"<figure><figcaption>{name}</figcaption><img src="{src}" alt="{name}"></figure>"#,
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!("<h2>{title}</h2>\n<div class=\"items\">\n{items}\n</div>")
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
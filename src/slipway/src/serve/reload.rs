@@ -0,0 +1,29 @@
+use actix_web::{HttpRequest, HttpResponse, http::StatusCode, post, web};
+use tracing::info;
+
+use super::{ServeState, get_resolved_api_key_from_state, responses::ServeError};
+
+/// Clears the components cached by the server, so that components which have
+/// changed since they were loaded are loaded again by the next rig which uses them.
+///
+/// API keys associated with a device can't reload the server, as they are
+/// stored on the device.
+#[post("/reload")]
+pub(super) async fn reload(
+    data: web::Data<ServeState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ServeError> {
+    let supplied_api_key = get_resolved_api_key_from_state(&req)?;
+    if supplied_api_key.resolved.device.is_some() {
+        return Err(ServeError::UserFacing(
+            StatusCode::FORBIDDEN,
+            "API keys associated with a device can't reload the server.".to_string(),
+        ));
+    }
+
+    let count = data.shared.components_cache.len();
+    data.shared.components_cache.clear();
+    info!("Reloaded server, clearing {count} cached component(s).");
+
+    Ok(HttpResponse::NoContent().finish())
+}
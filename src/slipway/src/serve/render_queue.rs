@@ -0,0 +1,163 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Bounds the number of rigs the server renders concurrently, so that spikes in
+/// requests wait for a render to finish rather than exhausting CPU and memory.
+///
+/// Requests which can't start rendering immediately wait in a queue, and once
+/// the queue is full further requests are rejected rather than queued.
+#[derive(Debug)]
+pub(super) struct RenderQueue {
+    permits: Option<Arc<Semaphore>>,
+    max_queue_depth: Option<usize>,
+    queue_depth: AtomicUsize,
+    renders_in_progress: AtomicUsize,
+}
+
+/// Returned when a render can't be queued because the queue is full.
+#[derive(Debug)]
+pub(super) struct RenderQueueFull;
+
+impl RenderQueue {
+    pub fn new(max_concurrent_renders: Option<usize>, max_queue_depth: Option<usize>) -> Self {
+        RenderQueue {
+            permits: max_concurrent_renders.map(|max| Arc::new(Semaphore::new(max.max(1)))),
+            max_queue_depth,
+            queue_depth: AtomicUsize::new(0),
+            renders_in_progress: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of renders waiting for another render to finish.
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth.load(Ordering::Relaxed)
+    }
+
+    /// The number of renders currently running.
+    pub fn renders_in_progress(&self) -> usize {
+        self.renders_in_progress.load(Ordering::Relaxed)
+    }
+
+    /// Waits until a render can start, queueing if the concurrency limit has been
+    /// reached, and holds the render slot until the returned permit is dropped.
+    pub async fn acquire(&self) -> Result<RenderPermit<'_>, RenderQueueFull> {
+        let permit = match &self.permits {
+            None => None,
+            Some(permits) => Some(match Arc::clone(permits).try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    // The slot is released on drop, so that requests which are
+                    // cancelled while waiting don't permanently occupy the queue.
+                    let _queue_slot = self.reserve_queue_slot()?;
+
+                    Arc::clone(permits)
+                        .acquire_owned()
+                        .await
+                        .expect("render queue semaphore should never be closed")
+                }
+            }),
+        };
+
+        self.renders_in_progress.fetch_add(1, Ordering::Relaxed);
+
+        Ok(RenderPermit {
+            queue: self,
+            _permit: permit,
+        })
+    }
+
+    fn reserve_queue_slot(&self) -> Result<QueueSlot<'_>, RenderQueueFull> {
+        self.queue_depth
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |depth| {
+                match self.max_queue_depth {
+                    Some(max_queue_depth) if depth >= max_queue_depth => None,
+                    _ => Some(depth + 1),
+                }
+            })
+            .map_err(|_| RenderQueueFull)?;
+
+        Ok(QueueSlot { queue: self })
+    }
+}
+
+/// A place in the queue, which is released when dropped.
+struct QueueSlot<'queue> {
+    queue: &'queue RenderQueue,
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.queue.queue_depth.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Permission to render a rig, which is released when dropped.
+pub(super) struct RenderPermit<'queue> {
+    queue: &'queue RenderQueue,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for RenderPermit<'_> {
+    fn drop(&mut self) {
+        self.queue
+            .renders_in_progress
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+    use futures::FutureExt;
+
+    use super::*;
+
+    #[slipway_test_async]
+    async fn it_should_queue_renders_when_limit_is_reached() {
+        let queue = RenderQueue::new(Some(1), None);
+
+        let first = queue.acquire().await.unwrap();
+        assert_eq!(queue.renders_in_progress(), 1);
+
+        let mut second = Box::pin(queue.acquire());
+        assert!((&mut second).now_or_never().is_none());
+        assert_eq!(queue.queue_depth(), 1);
+
+        drop(first);
+
+        let _second = second.await.unwrap();
+        assert_eq!(queue.queue_depth(), 0);
+        assert_eq!(queue.renders_in_progress(), 1);
+    }
+
+    #[slipway_test_async]
+    async fn it_should_reject_renders_when_queue_is_full() {
+        let queue = RenderQueue::new(Some(1), Some(1));
+
+        let _first = queue.acquire().await.unwrap();
+
+        let mut second = Box::pin(queue.acquire());
+        assert!((&mut second).now_or_never().is_none());
+
+        assert!(matches!(queue.acquire().await, Err(RenderQueueFull)));
+
+        // Cancelling a queued render frees its place in the queue.
+        drop(second);
+        assert_eq!(queue.queue_depth(), 0);
+    }
+
+    #[slipway_test_async]
+    async fn it_should_not_limit_renders_when_no_limit_is_set() {
+        let queue = RenderQueue::new(None, Some(0));
+
+        let _first = queue.acquire().await.unwrap();
+        let _second = queue.acquire().await.unwrap();
+
+        assert_eq!(queue.renders_in_progress(), 2);
+        assert_eq!(queue.queue_depth(), 0);
+    }
+}
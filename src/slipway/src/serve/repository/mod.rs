@@ -125,6 +125,9 @@ pub(super) enum RigResultFormat {
     /// Return the JSON output of the rig.
     Json,
 
+    /// Return the SVG markup in the rig output's `svg` field.
+    Svg,
+
     /// Return the image encoded as a data URL.
     /// We expose this as `html` to the user because while internally this is a data URL,
     /// the user sees it as an HTML page containing a data URL.
@@ -1,12 +1,16 @@
 use actix_web::body::{BoxBody, EitherBody};
 use actix_web::http::StatusCode;
-use actix_web::http::header::{ContentType, HeaderName, HeaderValue};
-use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use actix_web::http::header::{
+    Accept, CONTENT_SECURITY_POLICY, ContentType, ETAG, Header, HeaderName, HeaderValue,
+    IF_NONE_MATCH, RETRY_AFTER, X_CONTENT_TYPE_OPTIONS,
+};
+use actix_web::{HttpRequest, HttpResponse, HttpResponseBuilder, Responder};
 use serde::{Deserialize, Deserializer};
 
 use base64::prelude::*;
 use image::{DynamicImage, ImageFormat, RgbaImage};
-use slipway_host::hash_bytes;
+use slipway_engine::Hash;
+use slipway_host::hash_string;
 use std::io::Cursor;
 use std::str::FromStr;
 use thiserror::Error;
@@ -25,15 +29,37 @@ pub(super) enum ServeError {
 
     #[error("{0}: {1}")]
     UserFacingJson(StatusCode, serde_json::Value),
+
+    /// The server is too busy to handle the request, and the client should
+    /// retry after the given number of seconds.
+    #[error("{}: {message}", StatusCode::SERVICE_UNAVAILABLE)]
+    Unavailable {
+        message: String,
+        retry_after_seconds: u64,
+    },
 }
 
 impl actix_web::error::ResponseError for ServeError {
     fn error_response(&self) -> HttpResponse {
         debug!("Error response: {:?}", self);
 
-        HttpResponse::build(self.status_code())
-            .insert_header(ContentType::html())
-            .body(self.to_string())
+        let mut response = HttpResponse::build(self.status_code());
+
+        if let ServeError::UserFacingJson(_, body) = self {
+            return response.json(body);
+        }
+
+        response.insert_header(ContentType::html());
+
+        if let ServeError::Unavailable {
+            retry_after_seconds,
+            ..
+        } = self
+        {
+            response.insert_header((RETRY_AFTER, retry_after_seconds.to_string()));
+        }
+
+        response.body(self.to_string())
     }
 
     fn status_code(&self) -> StatusCode {
@@ -41,14 +67,20 @@ impl actix_web::error::ResponseError for ServeError {
             ServeError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ServeError::UserFacing(status_code, _) => status_code,
             ServeError::UserFacingJson(status_code, _) => status_code,
+            ServeError::Unavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
         }
     }
 }
 
 pub(super) enum RigResponse {
     Image(ImageResponse),
-    Json(web::Json<serde_json::Value>),
+    Json(JsonResponse),
+    Svg(SvgResponse),
     Url(UrlResponse),
+
+    /// The rig output is unchanged since the client's previous request,
+    /// as identified by the ETag the client supplied.
+    NotModified(NotModifiedResponse),
 }
 
 impl Responder for RigResponse {
@@ -57,8 +89,12 @@ impl Responder for RigResponse {
     fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
         match self {
             RigResponse::Image(image) => image.respond_to(req).map_into_right_body(),
-            RigResponse::Json(json) => json.respond_to(req),
+            RigResponse::Json(json) => json.respond_to(req).map_into_right_body(),
+            RigResponse::Svg(svg) => svg.respond_to(req).map_into_right_body(),
             RigResponse::Url(url) => url.respond_to(req).map_into_right_body(),
+            RigResponse::NotModified(not_modified) => {
+                not_modified.respond_to(req).map_into_right_body()
+            }
         }
     }
 }
@@ -76,10 +112,14 @@ impl Responder for PlaylistResponse {
             RigResponse::Image(image) => image
                 .respond_with_refresh(req, self.refresh_rate_seconds)
                 .map_into_right_body(),
-            RigResponse::Json(json) => json.respond_to(req),
+            RigResponse::Json(json) => json.respond_to(req).map_into_right_body(),
+            RigResponse::Svg(svg) => svg.respond_to(req).map_into_right_body(),
             RigResponse::Url(url) => url
                 .respond_with_refresh(req, self.refresh_rate_seconds)
                 .map_into_right_body(),
+            RigResponse::NotModified(not_modified) => {
+                not_modified.respond_to(req).map_into_right_body()
+            }
         };
 
         response.headers_mut().append(
@@ -127,10 +167,107 @@ impl Responder for UrlResponse {
     }
 }
 
+/// Returns an ETag identifying a response body, derived from the hash of the rig output
+/// and the options used to create the body from the output.
+pub(super) fn get_rig_etag(
+    output_hash: &Hash,
+    format: &RigResultFormat,
+    image_format: &RigResultImageFormat,
+    rotate: u16,
+) -> String {
+    hash_string(&format!(
+        "{output_hash}:{format:?}:{image_format:?}:{rotate}"
+    ))
+}
+
+/// Returns true if the request's `If-None-Match` header contains the given ETag,
+/// in which case the client already has the response body.
+pub(super) fn is_etag_matched(req: &HttpRequest, etag: &str) -> bool {
+    let Some(if_none_match) = req
+        .headers()
+        .get(IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return false;
+    };
+
+    debug!("Client supplied ETag: {}", if_none_match);
+
+    if_none_match.split(',').any(|candidate| {
+        let candidate = candidate.trim();
+        let candidate = candidate.strip_prefix("W/").unwrap_or(candidate);
+        candidate == "*" || candidate.trim_matches('"') == etag
+    })
+}
+
+fn insert_etag_header(response: &mut HttpResponseBuilder, etag: &str) {
+    response.insert_header((
+        ETAG,
+        HeaderValue::from_str(etag).expect("ETag value should be lowercase hex"),
+    ));
+}
+
+pub(super) struct NotModifiedResponse {
+    pub etag: String,
+}
+
+impl Responder for NotModifiedResponse {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        info!("Returning 304 Not Modified.");
+        let mut response = HttpResponse::NotModified();
+        insert_etag_header(&mut response, &self.etag);
+        response.finish()
+    }
+}
+
+pub(super) struct JsonResponse {
+    pub value: serde_json::Value,
+    pub etag: String,
+}
+
+impl Responder for JsonResponse {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let mut response = HttpResponse::Ok();
+        insert_etag_header(&mut response, &self.etag);
+        response.json(self.value)
+    }
+}
+
+/// The content security policy for SVG responses. SVGs are rendered from component
+/// output, so scripts and external resources are blocked in case they are opened
+/// directly in a browser, while the inline styles SVGs commonly use are allowed.
+const SVG_CONTENT_SECURITY_POLICY: &str = "default-src 'none'; style-src 'unsafe-inline'";
+
+pub(super) struct SvgResponse {
+    pub svg: String,
+    pub etag: String,
+}
+
+impl Responder for SvgResponse {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        info!("Responding with SVG of size {} bytes.", self.svg.len());
+
+        let mut response = HttpResponse::Ok();
+        insert_etag_header(&mut response, &self.etag);
+        response
+            .insert_header((CONTENT_SECURITY_POLICY, SVG_CONTENT_SECURITY_POLICY))
+            .insert_header((X_CONTENT_TYPE_OPTIONS, "nosniff"))
+            .content_type("image/svg+xml")
+            .body(self.svg)
+    }
+}
+
 pub(super) struct ImageResponse {
     pub image: RgbaImage,
     pub format: RigResultImageFormat,
     pub wrap_in_html: bool,
+    pub etag: String,
 }
 
 impl ImageResponse {
@@ -167,37 +304,15 @@ impl ImageResponse {
                 BASE64_STANDARD.encode(&image_bytes)
             );
 
-            return HttpResponse::Ok()
-                .content_type(ContentType::html())
-                .body(html);
-        }
-
-        let maybe_etag = _req
-            .headers()
-            .get("if-none-match")
-            .and_then(|v| v.to_str().ok());
-
-        if let Some(etag) = maybe_etag {
-            debug!("Device supplied ETag: {}", etag);
-            let new_etag = hash_bytes(&image_bytes);
-            debug!("Calculated ETag     : {}", new_etag);
-
-            if etag == new_etag {
-                info!("Returning 304 Not Modified.");
-                return HttpResponse::NotModified().finish();
-            }
+            let mut response = HttpResponse::Ok();
+            insert_etag_header(&mut response, &self.etag);
+            return response.content_type(ContentType::html()).body(html);
         }
 
-        let etag = hash_bytes(&image_bytes);
-
         let body = image_bytes;
 
         let mut response = HttpResponse::Ok();
-
-        response.insert_header((
-            HeaderName::from_static("etag"),
-            HeaderValue::from_str(&etag).expect("ETag value should lowercase hex"),
-        ));
+        insert_etag_header(&mut response, &self.etag);
 
         match self.format {
             RigResultImageFormat::Jpeg => {
@@ -250,6 +365,16 @@ impl FormatQuery {
         Default::default()
     }
 
+    /// If the query doesn't specify a format, uses the format preferred by the
+    /// request's `Accept` header, if it prefers JSON or an image.
+    pub fn with_accepted_format(mut self, req: &HttpRequest) -> Self {
+        if self.format.is_none() {
+            self.format = accepted_format(req);
+        }
+
+        self
+    }
+
     pub fn into_spec(self) -> RigResultSpec {
         RigResultSpec {
             format: self.format.unwrap_or_default(),
@@ -267,6 +392,32 @@ impl FormatQuery {
     }
 }
 
+/// Returns the format preferred by the request's `Accept` header, or `None` if
+/// the header prefers neither JSON, SVG nor an image, for example when it accepts anything.
+fn accepted_format(req: &HttpRequest) -> Option<RigResultFormat> {
+    let accept = Accept::parse(req).ok()?;
+
+    for mime in accept.ranked() {
+        if mime.essence_str() == "application/json" {
+            return Some(RigResultFormat::Json);
+        }
+
+        if mime.essence_str() == "image/svg+xml" {
+            return Some(RigResultFormat::Svg);
+        }
+
+        if mime.type_().as_str() == "image" {
+            return Some(RigResultFormat::Image);
+        }
+
+        if mime.essence_str() == "*/*" {
+            return None;
+        }
+    }
+
+    None
+}
+
 pub fn deserialize_number_from_string<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
 where
     D: Deserializer<'de>,
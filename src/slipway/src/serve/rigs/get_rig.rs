@@ -6,17 +6,22 @@ use actix_web::{HttpMessage, HttpRequest, get, web};
 use anyhow::Context;
 use image::imageops::{rotate90, rotate180, rotate270};
 use serde::Deserialize;
+use slipway_engine::OutputContentType;
 use tracing::{Instrument, info_span};
 
 use crate::primitives::{DeviceName, RigName};
 use crate::serve::auth::compute_signature_parts;
+use crate::serve::render_queue::RenderQueueFull;
 use crate::serve::repository::{RigResultFormat, RigResultSpec};
 use crate::serve::{
-    API_GET_DEVICE_PATH, Device, RequestState, TRMNL_DISPLAY_PATH, truncate_hashed_api_key,
-    try_get_api_key_from_state,
+    API_GET_DEVICE_PATH, Device, RENDER_QUEUE_RETRY_AFTER_SECONDS, RequestState,
+    TRMNL_DISPLAY_PATH, truncate_hashed_api_key, try_get_api_key_from_state,
 };
 
-use crate::serve::responses::{FormatQuery, ImageResponse, RigResponse, ServeError, UrlResponse};
+use crate::serve::responses::{
+    FormatQuery, ImageResponse, JsonResponse, NotModifiedResponse, RigResponse, ServeError,
+    SvgResponse, UrlResponse, get_rig_etag, is_etag_matched,
+};
 
 use super::super::ServeState;
 
@@ -45,7 +50,9 @@ pub async fn get_rig(
 
     let state = data.into_inner();
     let rig_name = path.rig_name;
-    let result_spec = query.output.into_spec();
+    let output = query.output.with_accepted_format(&req);
+    let format_is_default = output.format.is_none();
+    let result_spec = output.into_spec();
 
     let device = match query.device {
         Some(device_name) => {
@@ -55,9 +62,16 @@ pub async fn get_rig(
         None => None,
     };
 
-    get_rig_response(&rig_name, device, result_spec, state, req)
-        .instrument(info_span!("rig", ""=%rig_name))
-        .await
+    get_rig_response(
+        &rig_name,
+        device,
+        result_spec,
+        format_is_default,
+        state,
+        req,
+    )
+    .instrument(info_span!("rig", ""=%rig_name))
+    .await
 }
 
 pub struct RequestingDevice {
@@ -110,10 +124,13 @@ pub fn assert_api_key_is_valid_for_rig(
     Ok(())
 }
 
+/// If `format_is_default` is true then the format was not chosen by the request,
+/// and the content type declared by the rig's output component takes precedence.
 pub async fn get_rig_response(
     rig_name: &RigName,
     device: Option<RequestingDevice>,
     result_spec: RigResultSpec,
+    format_is_default: bool,
     state: Arc<ServeState>,
     req: HttpRequest,
 ) -> Result<RigResponse, ServeError> {
@@ -126,16 +143,69 @@ pub async fn get_rig_response(
     let rotate = result_spec.rotate;
 
     match format {
-        RigResultFormat::Image | RigResultFormat::DataUrl | RigResultFormat::Json => {
+        RigResultFormat::Image
+        | RigResultFormat::DataUrl
+        | RigResultFormat::Json
+        | RigResultFormat::Svg => {
+            // Held until the response body has been created from the rig output.
+            let _render_permit =
+                state
+                    .shared
+                    .render_queue
+                    .acquire()
+                    .await
+                    .map_err(|RenderQueueFull| ServeError::Unavailable {
+                        message: "The server is busy rendering other rigs. Please try again later."
+                            .to_string(),
+                        retry_after_seconds: RENDER_QUEUE_RETRY_AFTER_SECONDS,
+                    })?;
+
             let result =
                 super::run_rig::run_rig(state, rig, rig_name, device.and_then(|d| d.context))
                     .await
-                    .map_err(ServeError::Internal)?;
+                    .map_err(super::rig_error::rig_error_response)?;
+
+            let format = match result.content_type {
+                Some(OutputContentType::Json) if format_is_default => RigResultFormat::Json,
+                Some(OutputContentType::Svg) if format_is_default => RigResultFormat::Svg,
+                _ => format,
+            };
+
+            // The ETag is derived from the rig output, so an unchanged output
+            // can be detected without rendering the response body.
+            let etag = get_rig_etag(&result.output_hash, &format, &image_format, rotate);
+            if is_etag_matched(&req, &etag) {
+                return Ok(RigResponse::NotModified(NotModifiedResponse { etag }));
+            }
 
             if matches!(format, RigResultFormat::Json) {
-                Ok(RigResponse::Json(web::Json(result.output)))
+                Ok(RigResponse::Json(JsonResponse {
+                    value: result.output,
+                    etag,
+                }))
             } else {
-                let maybe_image = crate::canvas::get_canvas_image(&result.handle, &result.output);
+                let Some(handle) = result.handle else {
+                    return Err(ServeError::UserFacing(
+                        StatusCode::BAD_REQUEST,
+                        "Rigs with named outputs can only be returned as JSON.".to_string(),
+                    ));
+                };
+
+                if matches!(format, RigResultFormat::Svg) {
+                    let svg = crate::canvas::get_svg(&handle, &result.output).map_err(|_| {
+                        ServeError::UserFacing(
+                            StatusCode::BAD_REQUEST,
+                            "Could not find an SVG in the rig output.".to_string(),
+                        )
+                    })?;
+
+                    return Ok(RigResponse::Svg(SvgResponse {
+                        svg: svg.to_string(),
+                        etag,
+                    }));
+                }
+
+                let maybe_image = crate::canvas::get_canvas_image(&handle, &result.output);
 
                 if let Ok(image) = maybe_image {
                     let image = match rotate {
@@ -154,6 +224,7 @@ pub async fn get_rig_response(
                         image,
                         format: image_format,
                         wrap_in_html: matches!(format, RigResultFormat::DataUrl),
+                        etag,
                     }))
                 } else {
                     Err(ServeError::UserFacing(
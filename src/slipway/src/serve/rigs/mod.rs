@@ -1,2 +1,3 @@
 pub(super) mod get_rig;
+mod rig_error;
 pub(super) mod run_rig;
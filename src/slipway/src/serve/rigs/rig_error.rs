@@ -0,0 +1,308 @@
+use actix_web::http::StatusCode;
+use serde::Serialize;
+use slipway_engine::{
+    ComponentHandle, RunComponentError, RunError,
+    errors::{ComponentLoadError, ComponentLoadErrorInner, RigError},
+};
+
+use crate::{host_error::HostError, serve::responses::ServeError};
+
+/// The body of the JSON response returned when running a rig fails, so that
+/// clients can handle errors programmatically.
+#[derive(Debug, Serialize)]
+struct RigErrorBody {
+    code: RigErrorCode,
+    message: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    component_handle: Option<ComponentHandle>,
+
+    /// The chain of errors which caused the error, outermost first.
+    inner: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum RigErrorCode {
+    PermissionDenied,
+    ComponentNotFound,
+    Timeout,
+    ComponentFailed,
+    InternalError,
+}
+
+impl RigErrorCode {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RigErrorCode::PermissionDenied => StatusCode::FORBIDDEN,
+            RigErrorCode::ComponentNotFound => StatusCode::NOT_FOUND,
+            RigErrorCode::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            RigErrorCode::ComponentFailed | RigErrorCode::InternalError => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+/// Converts an error from running a rig into a JSON error response, with a status
+/// code describing the kind of failure.
+pub(in crate::serve) fn rig_error_response(error: anyhow::Error) -> ServeError {
+    let body = rig_error_body(&error);
+    let status_code = body.code.status_code();
+
+    ServeError::UserFacingJson(
+        status_code,
+        serde_json::to_value(body).expect("Rig error body should serialize"),
+    )
+}
+
+fn rig_error_body(error: &anyhow::Error) -> RigErrorBody {
+    if let Some(run_error) = error.downcast_ref::<RunError<HostError>>()
+        && let Some(body) = run_error_body(run_error)
+    {
+        return body;
+    }
+
+    if let Some(load_error) = error.downcast_ref::<ComponentLoadError>()
+        && let Some(body) = component_load_error_body(load_error, None)
+    {
+        return body;
+    }
+
+    if let Some(RigError::ComponentNotFound { handle }) = error.downcast_ref::<RigError>() {
+        return RigErrorBody {
+            code: RigErrorCode::ComponentNotFound,
+            message: error.to_string(),
+            component_handle: Some(handle.clone()),
+            inner: vec![],
+        };
+    }
+
+    RigErrorBody {
+        code: RigErrorCode::InternalError,
+        message: error.to_string(),
+        component_handle: None,
+        inner: error.chain().skip(1).map(|e| e.to_string()).collect(),
+    }
+}
+
+fn run_error_body(error: &RunError<HostError>) -> Option<RigErrorBody> {
+    match error {
+        RunError::ComponentLoadFailed(load_error)
+        | RunError::Rig(RigError::ComponentLoadFailed(load_error)) => {
+            component_load_error_body(load_error, None)
+        }
+        RunError::Rig(RigError::ComponentNotFound { handle }) => Some(RigErrorBody {
+            code: RigErrorCode::ComponentNotFound,
+            message: error.to_string(),
+            component_handle: Some(handle.clone()),
+            inner: vec![],
+        }),
+        RunError::RunComponentFailed {
+            component_handle,
+            error: run_component_error,
+            ..
+        } => Some(run_component_error_body(
+            run_component_error,
+            component_handle,
+        )),
+        _ => None,
+    }
+}
+
+fn run_component_error_body(
+    error: &RunComponentError,
+    component_handle: &ComponentHandle,
+) -> RigErrorBody {
+    if let RunComponentError::ComponentLoadFailed(load_error) = error
+        && let Some(body) = component_load_error_body(load_error, Some(component_handle))
+    {
+        return body;
+    }
+
+    let (code, message, inner) = match error {
+        RunComponentError::Timeout { message } => (RigErrorCode::Timeout, message.clone(), vec![]),
+        RunComponentError::RunCallReturnedError { message, inner } => (
+            RigErrorCode::ComponentFailed,
+            message.clone(),
+            inner.clone(),
+        ),
+        RunComponentError::PermissionDenied { message, inner } => (
+            RigErrorCode::PermissionDenied,
+            message.clone(),
+            inner.clone(),
+        ),
+        _ => (RigErrorCode::ComponentFailed, error.to_string(), vec![]),
+    };
+
+    RigErrorBody {
+        code,
+        message,
+        component_handle: Some(component_handle.clone()),
+        inner,
+    }
+}
+
+/// Returns the body for load errors which have a more specific code than
+/// an internal error.
+fn component_load_error_body(
+    error: &ComponentLoadError,
+    component_handle: Option<&ComponentHandle>,
+) -> Option<RigErrorBody> {
+    let (code, inner) = match &error.error {
+        ComponentLoadErrorInner::PermissionDenied { inner, .. } => {
+            (RigErrorCode::PermissionDenied, inner.clone())
+        }
+        ComponentLoadErrorInner::NotFound => (RigErrorCode::ComponentNotFound, vec![]),
+        _ => return None,
+    };
+
+    Some(RigErrorBody {
+        code,
+        message: error.to_string(),
+        component_handle: component_handle.cloned(),
+        inner,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use serde_json::json;
+    use slipway_engine::SlipwayReference;
+
+    use super::*;
+
+    fn component_handle() -> ComponentHandle {
+        ComponentHandle::from_str("render").unwrap()
+    }
+
+    fn response_parts(error: anyhow::Error) -> (StatusCode, serde_json::Value) {
+        match rig_error_response(error) {
+            ServeError::UserFacingJson(status_code, body) => (status_code, body),
+            other => panic!("Expected a JSON error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn it_should_return_forbidden_for_permission_errors() {
+        let error = RunError::<HostError>::ComponentLoadFailed(ComponentLoadError::new(
+            &SlipwayReference::from_str("p.n.1.0.0").unwrap(),
+            ComponentLoadErrorInner::PermissionDenied {
+                message: "Denied".to_string(),
+                inner: vec!["No permission matched".to_string()],
+            },
+        ));
+
+        let (status_code, body) = response_parts(error.into());
+
+        assert_eq!(status_code, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "permission_denied");
+        assert_eq!(body["inner"], json!(["No permission matched"]));
+    }
+
+    #[test]
+    fn it_should_return_forbidden_for_runtime_permission_errors() {
+        let error = RunError::<HostError>::RunComponentFailed {
+            component_handle: component_handle(),
+            component_runner: "wasmtime".to_string(),
+            error: RunComponentError::PermissionDenied {
+                message: "Component \"render\" does not have permission to fetch url".to_string(),
+                inner: vec!["No permission matched".to_string()],
+            },
+        };
+
+        let (status_code, body) = response_parts(error.into());
+
+        assert_eq!(status_code, StatusCode::FORBIDDEN);
+        assert_eq!(
+            body,
+            json!({
+                "code": "permission_denied",
+                "message": "Component \"render\" does not have permission to fetch url",
+                "component_handle": "render",
+                "inner": ["No permission matched"],
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_return_gateway_timeout_for_timeouts() {
+        let error = RunError::<HostError>::RunComponentFailed {
+            component_handle: component_handle(),
+            component_runner: "javascript".to_string(),
+            error: RunComponentError::Timeout {
+                message: "Loop limit exceeded".to_string(),
+            },
+        };
+
+        let (status_code, body) = response_parts(error.into());
+
+        assert_eq!(status_code, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(
+            body,
+            json!({
+                "code": "timeout",
+                "message": "Loop limit exceeded",
+                "component_handle": "render",
+                "inner": [],
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_include_component_error_chain() {
+        let error = RunError::<HostError>::RunComponentFailed {
+            component_handle: component_handle(),
+            component_runner: "wasmtime".to_string(),
+            error: RunComponentError::RunCallReturnedError {
+                message: "Failed to fetch data".to_string(),
+                inner: vec!["Connection refused".to_string()],
+            },
+        };
+
+        let (status_code, body) = response_parts(error.into());
+
+        assert_eq!(status_code, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            body,
+            json!({
+                "code": "component_failed",
+                "message": "Failed to fetch data",
+                "component_handle": "render",
+                "inner": ["Connection refused"],
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_return_not_found_for_missing_components() {
+        let error = ComponentLoadError::new(
+            &SlipwayReference::from_str("p.n.1.0.0").unwrap(),
+            ComponentLoadErrorInner::NotFound,
+        );
+
+        let (status_code, body) = response_parts(error.into());
+
+        assert_eq!(status_code, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "component_not_found");
+    }
+
+    #[test]
+    fn it_should_return_internal_error_for_other_errors() {
+        let error = anyhow::anyhow!("Disk full").context("Failed to write output");
+
+        let (status_code, body) = response_parts(error);
+
+        assert_eq!(status_code, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            body,
+            json!({
+                "code": "internal_error",
+                "message": "Failed to write output",
+                "inner": ["Disk full"],
+            })
+        );
+    }
+}
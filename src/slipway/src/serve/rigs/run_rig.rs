@@ -1,20 +1,22 @@
 use std::sync::Arc;
 
 use slipway_engine::{
-    BasicComponentCache, BasicComponentsLoader, CallChain, ComponentHandle, Environment,
-    Permission, Rig, RigSession, RigSessionOptions,
+    BasicComponentCache, CallChain, ComponentHandle, Environment, Hash, JsonMetadata,
+    OutputContentType, Permission, Rig, RigSession, RigSessionOptions,
 };
 use slipway_host::tracing_writer::TraceOrWriter;
+use tracing::warn;
 
 use crate::{
-    component_runners::get_component_runners,
+    component_runners::get_component_runners_with_wasm_runner,
+    get_rig_output::RigOutputs,
     permissions::PERMISSIONS_EMPTY,
     primitives::RigName,
     run_rig::{CliRunEventHandler, WriteComponentOutputsType},
     serve::repository::file_system::FONTS_FOLDER_NAME,
 };
 
-use super::super::ServeState;
+use super::super::{ServeState, shared_wasm_runner};
 
 pub async fn run_rig(
     state: Arc<ServeState>,
@@ -22,9 +24,9 @@ pub async fn run_rig(
     rig_name: &RigName,
     device_context: Option<serde_json::Value>,
 ) -> anyhow::Result<RunRigResult> {
-    let components_loader = BasicComponentsLoader::builder()
-        .local_base_directory(&state.base_path)
-        .registry_lookup_urls(state.config.registry_urls.clone())
+    let components_loader = state
+        .config
+        .components_loader_builder(&state.base_path)?
         .build();
 
     let timezone = state
@@ -42,16 +44,37 @@ pub async fn run_rig(
         .clone()
         .unwrap_or_else(crate::utils::get_system_timezone);
 
-    let component_cache = BasicComponentCache::primed(&rig, &components_loader).await?;
-    let session_options = RigSessionOptions::new_for_serve(
+    let component_cache = BasicComponentCache::primed(
         &rig,
-        state.base_path.clone(),
-        state.aot_path.clone(),
-        state.base_path.join(FONTS_FOLDER_NAME),
-        Environment { timezone, locale },
-        device_context,
+        &state.shared.components_cache.loader(&components_loader),
     )
-    .await;
+    .await?;
+    let session_options = RigSessionOptions::builder(Environment { timezone, locale })
+        .base_path(state.base_path.clone())
+        .aot_path(state.aot_path.clone())
+        .aot_registry_url(state.config.aot_registry_url.clone())
+        .aot_registry_key(state.config.aot_registry_key()?)
+        .fonts_path(Some(state.base_path.join(FONTS_FOLDER_NAME)))
+        .device_context(device_context)
+        .proxy(state.config.proxy.clone())
+        .components_http_client(Some(Arc::clone(components_loader.http_client())))
+        .js_time_limit(
+            state
+                .config
+                .js_time_limit_seconds
+                .map(std::time::Duration::from_secs),
+        )
+        .js_step_limit(state.config.js_step_limit)
+        .max_concurrent_components(state.config.max_concurrent_components.map(|max| max.max(1)))
+        .build(&rig)
+        .await;
+
+    for warning in
+        slipway_host::fonts::find_missing_fonts(component_cache.iter(), &session_options).await
+    {
+        warn!("Rig \"{rig_name}\": {warning}");
+    }
+
     let session = RigSession::new_with_options(rig, &component_cache, session_options);
 
     let mut event_handler = CliRunEventHandler::new(
@@ -60,7 +83,8 @@ pub async fn run_rig(
         TraceOrWriter::Trace(tracing::Level::INFO),
     );
 
-    let component_runners = get_component_runners();
+    let component_runners =
+        get_component_runners_with_wasm_runner(shared_wasm_runner(&state.config));
     let component_runners_slice = component_runners.as_slice();
 
     let rig_permissions = state
@@ -79,11 +103,25 @@ pub async fn run_rig(
     )
     .await?;
 
-    let rig_output = crate::get_rig_output::get_rig_output(&result)?;
-
-    Ok(RunRigResult {
-        handle: rig_output.handle.clone(),
-        output: rig_output.output.value.clone(),
+    let rig_outputs = crate::get_rig_output::get_rig_outputs(&result)?;
+
+    Ok(match &rig_outputs {
+        RigOutputs::Single(rig_output) => RunRigResult {
+            handle: Some(rig_output.handle.clone()),
+            output: rig_output.output.value.clone(),
+            output_hash: rig_output.output.json_metadata.hash.clone(),
+            content_type: rig_output.content_type,
+        },
+        RigOutputs::Named(_) => {
+            let output = rig_outputs.to_value();
+            RunRigResult {
+                handle: None,
+                output_hash: JsonMetadata::from_value(&output, session.options().hash_algorithm)
+                    .hash,
+                output,
+                content_type: None,
+            }
+        }
     })
 }
 
@@ -97,6 +135,13 @@ struct RigConfig {
 }
 
 pub struct RunRigResult {
-    pub handle: ComponentHandle,
+    /// The output component, or `None` if the output combines the rig's named outputs.
+    pub handle: Option<ComponentHandle>,
     pub output: serde_json::Value,
+
+    /// The hash of the output, which changes whenever the output changes.
+    pub output_hash: Hash,
+
+    /// The content type the output component declares for its output, if any.
+    pub content_type: Option<OutputContentType>,
 }
@@ -0,0 +1,91 @@
+use std::{path::Path, sync::Arc, time::Instant};
+
+use slipway_engine::{BasicComponentCache, SharedComponentsCache};
+use tracing::{info, warn};
+
+use crate::primitives::RigName;
+
+use super::{
+    SlipwayServeConfig, create_repository, repository::ServeRepository, shared_wasm_runner,
+};
+
+/// Loads the components of every rig in the repository and compiles their WASM
+/// components, so that the first request for each rig doesn't pay the cost.
+///
+/// Failures are logged rather than returned, as a rig which can't be warmed up
+/// shouldn't stop the server from serving the others.
+pub(super) async fn warm_up_rigs(
+    root: &Path,
+    aot_path: Option<&Path>,
+    config: &SlipwayServeConfig,
+    components_cache: &SharedComponentsCache,
+) {
+    let start = Instant::now();
+    let repository = create_repository(root, &config.repository);
+
+    let rig_names = match repository.list_rigs().await {
+        Ok(rig_names) => rig_names,
+        Err(e) => {
+            warn!("Failed to list rigs to warm up: {e}");
+            return;
+        }
+    };
+
+    for rig_name in rig_names.iter() {
+        if let Err(e) = warm_up_rig(
+            root,
+            aot_path,
+            config,
+            components_cache,
+            repository.as_ref(),
+            rig_name,
+        )
+        .await
+        {
+            warn!("Failed to warm up rig \"{rig_name}\": {e:#}");
+        }
+    }
+
+    info!(
+        "Warmed up {} rig(s) in {:.2?}.",
+        rig_names.len(),
+        start.elapsed()
+    );
+}
+
+/// Warms up the WASM components used by the rig. Components shared with
+/// previously warmed up rigs are not compiled again.
+async fn warm_up_rig(
+    root: &Path,
+    aot_path: Option<&Path>,
+    config: &SlipwayServeConfig,
+    components_cache: &SharedComponentsCache,
+    repository: &dyn ServeRepository,
+    rig_name: &RigName,
+) -> anyhow::Result<()> {
+    let start = Instant::now();
+
+    let rig = repository.get_rig(rig_name).await?;
+    let components_loader = config.components_loader_builder(root)?.build();
+    let component_cache =
+        BasicComponentCache::primed(&rig, &components_cache.loader(&components_loader)).await?;
+
+    let wasm_runner = shared_wasm_runner(config);
+
+    let mut count = 0;
+    for (reference, component) in component_cache.into_inner() {
+        if wasm_runner
+            .warm_up(&reference, Arc::clone(&component.files), aot_path)
+            .await?
+        {
+            count += 1;
+        }
+    }
+
+    info!(
+        "Warmed up {count} WASM component(s) for rig \"{rig_name}\" in {:.2?}.",
+        start.elapsed()
+    );
+
+    Ok(())
+}
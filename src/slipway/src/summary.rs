@@ -0,0 +1,75 @@
+use std::{fmt::Display, io::Write};
+
+/// The summary written at the end of a command which processes many items,
+/// such as `slipway doctor` or `slipway serve aot-compile`.
+///
+/// It is written as a title, followed by a labelled value for each total,
+/// followed by a line for each item worth reporting individually.
+pub(crate) struct Summary {
+    title: String,
+    fields: Vec<(String, String)>,
+    lines: Vec<String>,
+}
+
+impl Summary {
+    pub fn new(title: impl Into<String>) -> Self {
+        Summary {
+            title: title.into(),
+            fields: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn field(&mut self, label: impl Into<String>, value: impl Display) -> &mut Self {
+        self.fields.push((label.into(), value.to_string()));
+        self
+    }
+
+    pub fn line(&mut self, line: impl Into<String>) -> &mut Self {
+        self.lines.push(line.into());
+        self
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        writeln!(w, "{}:", self.title)?;
+
+        for (label, value) in &self.fields {
+            writeln!(w, "  {label}: {value}")?;
+        }
+
+        for line in &self.lines {
+            writeln!(w, "  {line}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_write_summary() {
+        let mut summary = Summary::new("Test summary");
+        summary
+            .field("Passed", 2)
+            .field("Target", "x86_64")
+            .line("Failed \"file:a\": broken")
+            .line("  Hint: fix it");
+
+        let mut output = Vec::new();
+        summary.write(&mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                "Test summary:\n",
+                "  Passed: 2\n",
+                "  Target: x86_64\n",
+                "  Failed \"file:a\": broken\n",
+                "    Hint: fix it\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use serde::Deserialize;
+use slipway_engine::{
+    BasicComponentCache, BasicComponentsLoader, CallChain, ComponentHandle, Environment,
+    Permissions, RigSession, RigSessionOptions, SlipwayReference,
+};
+use slipway_host::run::no_event_handler;
+use termion::color;
+
+use crate::{component_runners::get_component_runners, registry_auth::RegistryAuth};
+
+/// The folder within a Component's folder which contains its test fixtures.
+const TESTS_FOLDER_NAME: &str = "tests";
+
+/// A test case for a Component, which passes if running the Component
+/// with the input produces the expected output.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TestFixture {
+    input: serde_json::Value,
+    expected_output: serde_json::Value,
+}
+
+/// The result of running a single test fixture.
+struct TestCase {
+    name: String,
+    outcome: TestOutcome,
+}
+
+enum TestOutcome {
+    Pass,
+    Fail { detail: String },
+}
+
+/// Runs each `tests/*.json` fixture in the Component's folder through the Component,
+/// and writes whether the output of each matched the fixture's expected output.
+pub(super) async fn test_component<W: Write>(
+    w: &mut W,
+    component_path: PathBuf,
+    component_permissions: Permissions<'_>,
+    registry_urls: Vec<String>,
+    registry_auth: Vec<RegistryAuth>,
+    allow_non_http_git_urls: bool,
+    proxy: Option<String>,
+) -> anyhow::Result<()> {
+    let fixture_paths = find_fixture_paths(&component_path.join(TESTS_FOLDER_NAME))?;
+
+    let components_loader = crate::utils::create_components_loader(
+        registry_urls,
+        registry_auth,
+        allow_non_http_git_urls,
+        proxy.clone(),
+        None,
+    )?;
+
+    let component_reference = SlipwayReference::Local {
+        path: component_path,
+    };
+
+    let mut test_cases = Vec::with_capacity(fixture_paths.len());
+    for fixture_path in fixture_paths {
+        let name = fixture_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| fixture_path.display().to_string());
+
+        let outcome = match run_fixture(
+            &fixture_path,
+            &component_reference,
+            &component_permissions,
+            &components_loader,
+            proxy.as_deref(),
+        )
+        .await
+        {
+            Ok(outcome) => outcome,
+            Err(e) => TestOutcome::Fail {
+                detail: format!("{e:#}"),
+            },
+        };
+
+        test_cases.push(TestCase { name, outcome });
+    }
+
+    write_results(w, &test_cases)?;
+
+    let failures = test_cases
+        .iter()
+        .filter(|c| matches!(c.outcome, TestOutcome::Fail { .. }))
+        .count();
+
+    if failures > 0 {
+        anyhow::bail!("{failures} of {} test(s) failed.", test_cases.len());
+    }
+
+    Ok(())
+}
+
+/// Returns the paths of the JSON files in the folder, ordered by name.
+fn find_fixture_paths(tests_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(tests_path).with_context(|| {
+        format!(
+            "Failed to read test fixtures folder: {}",
+            tests_path.display()
+        )
+    })?;
+
+    let mut fixture_paths = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|e| e == "json") {
+            fixture_paths.push(path);
+        }
+    }
+
+    if fixture_paths.is_empty() {
+        anyhow::bail!("No test fixtures found in {}", tests_path.display());
+    }
+
+    fixture_paths.sort();
+
+    Ok(fixture_paths)
+}
+
+async fn run_fixture(
+    fixture_path: &Path,
+    component_reference: &SlipwayReference,
+    component_permissions: &Permissions<'_>,
+    components_loader: &BasicComponentsLoader,
+    proxy: Option<&str>,
+) -> anyhow::Result<TestOutcome> {
+    let fixture_bytes = std::fs::read(fixture_path)
+        .with_context(|| format!("Failed to read test fixture: {}", fixture_path.display()))?;
+    let fixture: TestFixture = serde_json::from_slice(&fixture_bytes).with_context(|| {
+        format!(
+            "Failed to parse test fixture, which should contain an `input` and `expected_output`: {}",
+            fixture_path.display()
+        )
+    })?;
+
+    let rig = crate::debug_rig::get_component_rig(
+        component_reference.clone(),
+        component_permissions,
+        fixture.input,
+    );
+
+    let component_cache = BasicComponentCache::primed(&rig, components_loader).await?;
+
+    // Fixtures use a fixed environment so that their outputs don't depend on
+    // the timezone or locale of the machine running the tests.
+    let session_options = RigSessionOptions::builder(Environment::for_test())
+        .proxy(proxy.map(str::to_owned))
+        .components_http_client(Some(Arc::clone(components_loader.http_client())))
+        .build(&rig)
+        .await;
+    let session = RigSession::new_with_options(rig, &component_cache, session_options);
+
+    // We created the rig, so we can trust it to only pass on the
+    // component_permissions to the component.
+    let call_chain = Arc::new(CallChain::new(Permissions::allow_all()));
+    let component_runners = get_component_runners();
+
+    let state = slipway_host::run::run_rig(
+        &session,
+        &mut no_event_handler(),
+        &component_runners,
+        call_chain,
+    )
+    .await?;
+
+    let handle = ComponentHandle::from_str(crate::debug_rig::COMPONENT_RIG_HANDLE)?;
+    let output = crate::get_rig_output::get_component_output(&state, &handle)?;
+
+    Ok(compare_output(
+        &fixture.expected_output,
+        &output.output.value,
+    ))
+}
+
+fn compare_output(expected: &serde_json::Value, actual: &serde_json::Value) -> TestOutcome {
+    if expected == actual {
+        return TestOutcome::Pass;
+    }
+
+    let pretty =
+        |value: &serde_json::Value| serde_json::to_string_pretty(value).unwrap_or_default();
+
+    TestOutcome::Fail {
+        detail: format!(
+            "Output did not match the expected output.\nExpected:\n{}\nActual:\n{}",
+            pretty(expected),
+            pretty(actual)
+        ),
+    }
+}
+
+fn write_results<W: Write>(w: &mut W, test_cases: &[TestCase]) -> std::io::Result<()> {
+    for test_case in test_cases {
+        match &test_case.outcome {
+            TestOutcome::Pass => {
+                writeln!(
+                    w,
+                    "{}[PASS]{} {}",
+                    color::Fg(color::Green),
+                    color::Fg(color::Reset),
+                    test_case.name
+                )?;
+            }
+            TestOutcome::Fail { detail } => {
+                writeln!(
+                    w,
+                    "{}[FAIL]{} {}",
+                    color::Fg(color::Red),
+                    color::Fg(color::Reset),
+                    test_case.name
+                )?;
+                for line in detail.lines() {
+                    writeln!(w, "       {line}")?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn strip_colors(s: &str) -> String {
+        s.replace(&color::Fg(color::Green).to_string(), "")
+            .replace(&color::Fg(color::Red).to_string(), "")
+            .replace(&color::Fg(color::Reset).to_string(), "")
+    }
+
+    #[test]
+    fn it_should_find_json_fixtures_in_name_order() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::write(directory.path().join("b.json"), "{}").unwrap();
+        std::fs::write(directory.path().join("a.json"), "{}").unwrap();
+        std::fs::write(directory.path().join("notes.txt"), "").unwrap();
+
+        let fixture_paths = find_fixture_paths(directory.path()).unwrap();
+
+        assert_eq!(
+            fixture_paths,
+            vec![
+                directory.path().join("a.json"),
+                directory.path().join("b.json")
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_fail_if_no_fixtures_exist() {
+        let directory = tempfile::tempdir().unwrap();
+        assert!(find_fixture_paths(directory.path()).is_err());
+        assert!(find_fixture_paths(&directory.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn it_should_write_results() {
+        let test_cases = vec![
+            TestCase {
+                name: "first".to_string(),
+                outcome: compare_output(&json!({ "a": 1 }), &json!({ "a": 1 })),
+            },
+            TestCase {
+                name: "second".to_string(),
+                outcome: compare_output(&json!(1), &json!(2)),
+            },
+        ];
+
+        let mut output = Vec::new();
+        write_results(&mut output, &test_cases).unwrap();
+
+        assert_eq!(
+            strip_colors(&String::from_utf8(output).unwrap()),
+            concat!(
+                "[PASS] first\n",
+                "[FAIL] second\n",
+                "       Output did not match the expected output.\n",
+                "       Expected:\n",
+                "       1\n",
+                "       Actual:\n",
+                "       2\n",
+            )
+        );
+    }
+}
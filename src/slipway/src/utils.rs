@@ -1,5 +1,22 @@
+use std::{
+    io::{IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use slipway_engine::{
+    BasicComponentCache, BasicComponentsLoader, ComponentHandle, ComponentLoadWarning,
+    HttpCassette, ProcessedUrl, Rig, RigError, RigSessionOptions, is_jsonc_path, is_yaml_path,
+    parse_rig, parse_rig_jsonc, parse_rig_yaml, process_url_str, strip_jsonc,
+};
 use tracing::info;
 
+use crate::registry_auth::RegistryAuth;
+
+const AUTHORIZATION_HEADER: &str = "Authorization";
+
 pub(crate) fn get_system_timezone() -> String {
     iana_time_zone::get_timezone().unwrap_or_else(|_| {
         info!(
@@ -19,3 +36,577 @@ pub(crate) fn get_system_locale() -> String {
         crate::DEFAULT_LOCALE.to_string()
     })
 }
+
+/// Creates a components loader for running a rig.
+///
+/// Relative local component references in the rig are resolved against the
+/// directory containing the rig file, so rigs are portable regardless of the
+/// current working directory. Relative registry URLs are specified on the command
+/// line, so they continue to resolve against the current working directory.
+pub(crate) fn create_components_loader(
+    registry_urls: Vec<String>,
+    registry_auth: Vec<RegistryAuth>,
+    allow_non_http_git_urls: bool,
+    proxy: Option<String>,
+    rig_directory: Option<&Path>,
+) -> anyhow::Result<BasicComponentsLoader> {
+    let current_directory =
+        std::env::current_dir().context("Failed to get the current directory")?;
+
+    let registry_urls = registry_urls
+        .into_iter()
+        .map(|url| resolve_registry_url(url, &current_directory))
+        .collect();
+
+    let mut builder = BasicComponentsLoader::builder().registry_lookup_urls(registry_urls);
+
+    for auth in registry_auth {
+        builder = builder.registry_header(
+            &auth.url_prefix,
+            AUTHORIZATION_HEADER,
+            &auth.authorization_header()?,
+        );
+    }
+
+    if let Some(rig_directory) = rig_directory {
+        builder = builder.local_base_directory(rig_directory);
+    }
+
+    Ok(builder
+        .allow_non_http_git_urls(allow_non_http_git_urls)
+        .proxy(proxy)
+        .build())
+}
+
+/// Reads the rig file, or reads the rig from `stdin` if the path is `-`.
+///
+/// Also returns the directory which relative local component references in the rig
+/// should be resolved against. For a rig read from `stdin` this is the current
+/// working directory.
+pub(crate) fn read_rig_file(
+    path: &Path,
+    mut stdin: impl Read,
+) -> anyhow::Result<(String, Option<PathBuf>)> {
+    if path.as_os_str() == crate::debug_rig::STDIN_INPUT_PATH {
+        let mut rig = String::new();
+        stdin
+            .read_to_string(&mut rig)
+            .context("Failed to read rig from stdin")?;
+        let current_directory =
+            std::env::current_dir().context("Failed to get the current directory")?;
+        Ok((rig, Some(current_directory)))
+    } else {
+        let rig = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read rig from {}", path.display()))?;
+        Ok((rig, path.parent().map(Path::to_path_buf)))
+    }
+}
+
+/// Parses the rig as YAML if the path has a `.yaml` or `.yml` extension,
+/// allows comments and trailing commas if it has a `.jsonc` extension,
+/// and otherwise parses it as strict JSON.
+pub(crate) fn parse_rig_file(path: &Path, file_contents: &str) -> Result<Rig, RigError> {
+    if is_yaml_path(path) {
+        parse_rig_yaml(file_contents)
+    } else if is_jsonc_path(path) {
+        parse_rig_jsonc(file_contents)
+    } else {
+        parse_rig(file_contents)
+    }
+}
+
+/// If the file is a YAML component definition at the root of a component directory,
+/// returns the equivalent JSON definition. Packaged components always contain
+/// a JSON definition, so YAML definitions are converted as they are packaged.
+pub(crate) fn try_convert_yaml_component_definition(
+    relative_path: &Path,
+    full_path: &Path,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    if !crate::SLIPWAY_COMPONENT_YAML_FILE_NAMES
+        .iter()
+        .any(|file_name| relative_path == Path::new(file_name))
+    {
+        return Ok(None);
+    }
+
+    // A JSON definition takes precedence, in which case the YAML file is packaged as is.
+    if full_path
+        .with_file_name(crate::SLIPWAY_COMPONENT_FILE_NAME)
+        .exists()
+    {
+        return Ok(None);
+    }
+
+    let yaml = std::fs::read_to_string(full_path)
+        .with_context(|| format!("Failed to read component from {}", full_path.display()))?;
+    let json = slipway_engine::yaml_to_json(&yaml)?;
+    Ok(Some(serde_json::to_vec_pretty(&json)?))
+}
+
+/// Appends the data to the tar archive as a file at the given path.
+pub(crate) fn append_bytes<W: Write>(
+    tar_builder: &mut tar::Builder<W>,
+    path: &Path,
+    data: &[u8],
+) -> anyhow::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar_builder.append_data(&mut header, path, data)?;
+    Ok(())
+}
+
+/// Writes any warnings found while loading components to stderr.
+/// If `deny_warnings` is set, any warnings cause an error to be returned.
+pub(crate) fn handle_component_warnings(
+    component_cache: &BasicComponentCache,
+    deny_warnings: bool,
+) -> anyhow::Result<()> {
+    handle_warnings(component_cache.warnings(), deny_warnings)
+}
+
+/// Reports any components which require fonts that can't be found, before the rig is run.
+pub(crate) async fn handle_missing_fonts(
+    component_cache: &BasicComponentCache,
+    session_options: &RigSessionOptions,
+    deny_warnings: bool,
+) -> anyhow::Result<()> {
+    let warnings =
+        slipway_host::fonts::find_missing_fonts(component_cache.iter(), session_options).await;
+    handle_warnings(&warnings, deny_warnings)
+}
+
+fn handle_warnings(warnings: &[ComponentLoadWarning], deny_warnings: bool) -> anyhow::Result<()> {
+    for warning in warnings {
+        eprintln!("Warning: {warning}");
+    }
+
+    if deny_warnings && !warnings.is_empty() {
+        anyhow::bail!(
+            "{} component warning(s) were found and warnings are denied.",
+            warnings.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Loads the HTTP cassette used to record or replay HTTP requests, if a path was specified.
+pub(crate) fn load_http_cassette(
+    path: Option<PathBuf>,
+    redact_headers: Vec<String>,
+) -> anyhow::Result<Option<Arc<HttpCassette>>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+
+    let redact_headers = std::iter::once(AUTHORIZATION_HEADER.to_string())
+        .chain(redact_headers)
+        .collect();
+
+    let cassette = HttpCassette::load(&path, redact_headers)
+        .with_context(|| format!("Failed to load HTTP cassette from {}", path.display()))?;
+
+    Ok(Some(Arc::new(cassette)))
+}
+
+/// Parses a `key=value` rig constant override, where the value is parsed as JSON
+/// if possible, otherwise it is used as a string.
+pub(crate) fn parse_constant_override(s: &str) -> Result<(String, serde_json::Value), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected a constant in the format key=value, but got: {s}"))?;
+
+    if key.is_empty() {
+        return Err(format!("Constant key must not be empty: {s}"));
+    }
+
+    let value = serde_json::from_str(value)
+        .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+
+    Ok((key.to_string(), value))
+}
+
+/// Parses a `handle=value` component output seed, where the value must be JSON.
+pub(crate) fn parse_seed_output(s: &str) -> Result<(ComponentHandle, serde_json::Value), String> {
+    let (handle, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected an output in the format handle=value, but got: {s}"))?;
+
+    let handle = ComponentHandle::from_str(handle)
+        .map_err(|e| format!("Invalid component handle \"{handle}\": {e}"))?;
+
+    let value = serde_json::from_str(value)
+        .map_err(|e| format!("Output for component \"{handle}\" is not valid JSON: {e}"))?;
+
+    Ok((handle, value))
+}
+
+/// Parses a duration such as `30d`, `12h`, `90m` or `45s`.
+pub(crate) fn parse_duration(s: &str) -> Result<std::time::Duration, String> {
+    let invalid = || format!("Expected a duration such as 30d, 12h, 90m or 45s, but got: {s}");
+
+    let (unit_index, _) = s.char_indices().last().ok_or_else(invalid)?;
+    let (value, unit) = s.split_at(unit_index);
+    let value: u64 = value.parse().map_err(|_| invalid())?;
+
+    let unit_seconds = match unit {
+        "d" => 24 * 60 * 60,
+        "h" => 60 * 60,
+        "m" => 60,
+        "s" => 1,
+        _ => return Err(invalid()),
+    };
+
+    let seconds = value
+        .checked_mul(unit_seconds)
+        .ok_or_else(|| format!("Duration is too large: {s}"))?;
+
+    Ok(std::time::Duration::from_secs(seconds))
+}
+
+/// Applies the constant overrides to the rig, in order.
+pub(crate) fn apply_constant_overrides(
+    rig: &mut Rig,
+    constant_overrides: Vec<(String, serde_json::Value)>,
+) {
+    for (key, value) in constant_overrides {
+        rig.set_constant(&key, value);
+    }
+}
+
+/// Deep merges each overlay file into the rig, in the order given, so later
+/// overlays take precedence over earlier ones.
+pub(crate) fn apply_rig_overlays(rig: &mut Rig, overlay_paths: &[PathBuf]) -> anyhow::Result<()> {
+    for path in overlay_paths {
+        let overlay = read_rig_overlay(path)?;
+        rig.apply_overlay(overlay)
+            .with_context(|| format!("Failed to apply rig overlay {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Reads a rig overlay, which is parsed using the same rules as rig files.
+fn read_rig_overlay(path: &Path) -> anyhow::Result<serde_json::Value> {
+    let file_contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read rig overlay from {}", path.display()))?;
+
+    let overlay = if is_yaml_path(path) {
+        serde_norway::from_str(&file_contents).map_err(anyhow::Error::from)
+    } else if is_jsonc_path(path) {
+        serde_json::from_str(&strip_jsonc(&file_contents)).map_err(anyhow::Error::from)
+    } else {
+        serde_json::from_str(&file_contents).map_err(anyhow::Error::from)
+    };
+
+    overlay.with_context(|| format!("Failed to parse rig overlay {}", path.display()))
+}
+
+/// Whether to use colors and styles when writing to the terminal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum ColorMode {
+    /// Use color unless `NO_COLOR` is set or stdout is not a terminal.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Returns whether color should be used when writing to stdout.
+    pub fn use_color(self) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                !is_no_color_set(std::env::var_os("NO_COLOR")) && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// The file format used when creating Rig and Component definitions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum DefinitionFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+impl DefinitionFormat {
+    /// Writes the definition to the file in this format.
+    pub fn write_file(self, path: &Path, value: &impl serde::Serialize) -> anyhow::Result<()> {
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        match self {
+            DefinitionFormat::Json => serde_json::to_writer_pretty(file, value)?,
+            DefinitionFormat::Yaml => serde_norway::to_writer(file, value)?,
+        }
+        Ok(())
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            DefinitionFormat::Json => "json",
+            DefinitionFormat::Yaml => "yaml",
+        }
+    }
+}
+
+/// Following https://no-color.org, color is disabled when `NO_COLOR` is set to any non-empty value.
+fn is_no_color_set(value: Option<std::ffi::OsString>) -> bool {
+    value.is_some_and(|v| !v.is_empty())
+}
+
+/// Replaces `${NAME}` references in the value with the named environment variable,
+/// so that secrets such as tokens can be kept out of configuration files.
+pub(crate) fn interpolate_env_vars(value: &str) -> anyhow::Result<String> {
+    let mut result = String::with_capacity(value.len());
+    let mut remaining = value;
+
+    while let Some(start) = remaining.find("${") {
+        result.push_str(&remaining[..start]);
+        let after_start = &remaining[start + 2..];
+        let end = after_start
+            .find('}')
+            .with_context(|| format!("Unterminated environment variable reference in: {value}"))?;
+
+        let name = &after_start[..end];
+        let env_value = std::env::var(name)
+            .with_context(|| format!("Environment variable \"{name}\" is not set"))?;
+        result.push_str(&env_value);
+
+        remaining = &after_start[end + 1..];
+    }
+
+    result.push_str(remaining);
+    Ok(result)
+}
+
+/// Returns the registry URLs with a registry for each components directory placed
+/// before them, so that components in the directories shadow registry components.
+/// Each directory contains a `{publisher}.{name}` folder per component.
+pub(crate) fn registry_urls_with_components_dirs(
+    components_dirs: Vec<PathBuf>,
+    registry_urls: Vec<String>,
+) -> anyhow::Result<Vec<String>> {
+    let mut urls = components_dirs
+        .iter()
+        .map(|dir| components_dir_registry_url(dir))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    urls.extend(registry_urls);
+    Ok(urls)
+}
+
+/// Relative directories are resolved against the current working directory, as with
+/// relative registry URLs. The directory is percent-encoded, while the template
+/// segments are appended as-is so that they can be interpolated.
+fn components_dir_registry_url(dir: &Path) -> anyhow::Result<String> {
+    let absolute_dir = std::path::absolute(dir)
+        .with_context(|| format!("Failed to resolve components directory {}", dir.display()))?;
+
+    let url = url::Url::from_directory_path(&absolute_dir).map_err(|_| {
+        anyhow::anyhow!(
+            "Failed to convert components directory {} to a URL",
+            absolute_dir.display()
+        )
+    })?;
+
+    Ok(format!("{url}{{publisher}}.{{name}}"))
+}
+
+/// Converts a relative `file:` registry URL to an absolute one using the given directory.
+fn resolve_registry_url(url: String, current_directory: &Path) -> String {
+    match process_url_str(&url) {
+        Ok(ProcessedUrl::RelativePath(path)) => {
+            format!("file://{}", current_directory.join(path).display())
+        }
+        _ => url,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+    use slipway_engine::resolve_registry_lookup_url;
+
+    use super::*;
+
+    #[test]
+    fn it_should_resolve_relative_registry_urls_against_directory() {
+        assert_eq!(
+            resolve_registry_url(
+                "file:components/{publisher}.{name}.{version}.tar".to_string(),
+                Path::new("/home/user")
+            ),
+            "file:///home/user/components/{publisher}.{name}.{version}.tar"
+        );
+    }
+
+    #[test]
+    fn it_should_place_components_dirs_before_registries() {
+        let current_directory = std::env::current_dir().unwrap();
+        let relative_url = url::Url::from_directory_path(current_directory.join("components"))
+            .unwrap()
+            .to_string();
+
+        assert_eq!(
+            registry_urls_with_components_dirs(
+                vec![PathBuf::from("components"), PathBuf::from("/home/user/dev")],
+                vec!["https://registry.example.com/{publisher}/{name}".to_string()]
+            )
+            .unwrap(),
+            vec![
+                format!("{relative_url}{{publisher}}.{{name}}"),
+                "file:///home/user/dev/{publisher}.{name}".to_string(),
+                "https://registry.example.com/{publisher}/{name}".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_percent_encode_components_dirs() {
+        let url = components_dir_registry_url(Path::new("/home/user/my components#1")).unwrap();
+        assert_eq!(
+            url,
+            "file:///home/user/my%20components%231/{publisher}.{name}"
+        );
+
+        let resolved = resolve_registry_lookup_url(&url, "acme", "widget", &Version::new(1, 0, 0));
+        assert_eq!(
+            process_url_str(&resolved).unwrap(),
+            ProcessedUrl::AbsolutePath(PathBuf::from("/home/user/my components#1/acme.widget"))
+        );
+    }
+
+    #[test]
+    fn it_should_parse_constant_overrides() {
+        assert_eq!(
+            parse_constant_override("count=5").unwrap(),
+            ("count".to_string(), serde_json::json!(5))
+        );
+        assert_eq!(
+            parse_constant_override("location.city=London").unwrap(),
+            ("location.city".to_string(), serde_json::json!("London"))
+        );
+        assert_eq!(
+            parse_constant_override(r#"flags={"a":true}"#).unwrap(),
+            ("flags".to_string(), serde_json::json!({ "a": true }))
+        );
+        assert_eq!(
+            parse_constant_override("equation=a=b").unwrap(),
+            ("equation".to_string(), serde_json::json!("a=b"))
+        );
+        assert!(parse_constant_override("missing_value").is_err());
+        assert!(parse_constant_override("=value").is_err());
+    }
+
+    #[test]
+    fn it_should_parse_seed_outputs() {
+        assert_eq!(
+            parse_seed_output(r#"weather={"temperature":20}"#).unwrap(),
+            (
+                ComponentHandle::from_str("weather").unwrap(),
+                serde_json::json!({ "temperature": 20 })
+            )
+        );
+        assert_eq!(
+            parse_seed_output("count=5").unwrap(),
+            (
+                ComponentHandle::from_str("count").unwrap(),
+                serde_json::json!(5)
+            )
+        );
+        assert!(parse_seed_output("weather=not json").is_err());
+        assert!(parse_seed_output("missing_value").is_err());
+        assert!(parse_seed_output("=5").is_err());
+    }
+
+    #[test]
+    fn it_should_read_rig_from_stdin_when_path_is_dash() {
+        let stdin = std::io::Cursor::new(r#"{"rigging":{}}"#);
+        let (rig, rig_directory) = read_rig_file(Path::new("-"), stdin).unwrap();
+        assert_eq!(rig, r#"{"rigging":{}}"#);
+        assert_eq!(rig_directory, Some(std::env::current_dir().unwrap()));
+    }
+
+    #[test]
+    fn it_should_apply_rig_overlays_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.json");
+        let second = dir.path().join("second.yaml");
+        std::fs::write(
+            &first,
+            r#"{ "constants": { "env": "staging", "debug": true } }"#,
+        )
+        .unwrap();
+        std::fs::write(&second, "constants:\n  env: prod\n").unwrap();
+
+        let mut rig = parse_rig(r#"{ "constants": { "env": "dev" }, "rigging": {} }"#).unwrap();
+        apply_rig_overlays(&mut rig, &[first, second]).unwrap();
+
+        assert_eq!(
+            rig.constants,
+            Some(serde_json::json!({ "env": "prod", "debug": true }))
+        );
+    }
+
+    #[test]
+    fn it_should_detect_no_color() {
+        assert!(!is_no_color_set(None));
+        assert!(!is_no_color_set(Some("".into())));
+        assert!(is_no_color_set(Some("1".into())));
+        assert!(ColorMode::Always.use_color());
+        assert!(!ColorMode::Never.use_color());
+    }
+
+    #[test]
+    fn it_should_interpolate_env_vars() {
+        // SAFETY: The variable name is unique to this test.
+        unsafe { std::env::set_var("SLIPWAY_TEST_INTERPOLATE_TOKEN", "abc") };
+
+        assert_eq!(
+            interpolate_env_vars("Bearer ${SLIPWAY_TEST_INTERPOLATE_TOKEN}!").unwrap(),
+            "Bearer abc!"
+        );
+        assert_eq!(interpolate_env_vars("plain").unwrap(), "plain");
+        assert!(interpolate_env_vars("${SLIPWAY_TEST_INTERPOLATE_MISSING}").is_err());
+        assert!(interpolate_env_vars("${UNTERMINATED").is_err());
+    }
+
+    #[test]
+    fn it_should_not_modify_absolute_registry_urls() {
+        for url in [
+            "file:///components/{publisher}.{name}.{version}.tar",
+            "https://example.com/{publisher}.{name}.{version}.tar",
+        ] {
+            assert_eq!(
+                resolve_registry_url(url.to_string(), Path::new("/home/user")),
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_parse_durations() {
+        use std::time::Duration;
+
+        assert_eq!(
+            parse_duration("30d"),
+            Ok(Duration::from_secs(30 * 24 * 60 * 60))
+        );
+        assert_eq!(parse_duration("12h"), Ok(Duration::from_secs(12 * 60 * 60)));
+        assert_eq!(parse_duration("90m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("30w").is_err());
+        assert!(parse_duration("30é").is_err());
+        assert!(parse_duration("-1d").is_err());
+        assert!(parse_duration(&format!("{}d", u64::MAX)).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::Context;
+
+/// The version identifier which always refers to the newest bundled WIT.
+const LATEST_WIT_VERSION: &str = "latest";
+
+/// The WIT (WASM Interface Type) definitions bundled with Slipway, newest first.
+/// When the interface changes, the previous definition should be copied to
+/// `wit/<version>/slipway.wit` and added here, so that Components pinned to
+/// an older revision of the interface can still retrieve it.
+const BUNDLED_WIT: &[&str] = &[
+    include_str!("../../wit/latest/slipway.wit"),
+    include_str!("../../wit/0.1.0/slipway.wit"),
+];
+
+/// Returns the version of the newest bundled WIT, as declared by its package.
+pub(super) fn current_wit_version() -> anyhow::Result<&'static str> {
+    wit_version(BUNDLED_WIT[0])
+}
+
+/// Returns the versions of the bundled WIT definitions, newest first.
+pub(super) fn list_wit_versions() -> anyhow::Result<Vec<&'static str>> {
+    BUNDLED_WIT.iter().map(|wit| wit_version(wit)).collect()
+}
+
+/// Returns the bundled WIT with the given version, or the newest WIT if no version
+/// is specified.
+pub(super) fn get_wit(version: Option<&str>) -> anyhow::Result<&'static str> {
+    let Some(version) = version.filter(|v| *v != LATEST_WIT_VERSION) else {
+        return Ok(BUNDLED_WIT[0]);
+    };
+
+    for &wit in BUNDLED_WIT {
+        if wit_version(wit)? == version {
+            return Ok(wit);
+        }
+    }
+
+    anyhow::bail!(
+        "WIT version \"{version}\" is not bundled with this version of Slipway. Available versions: {}",
+        list_wit_versions()?.join(", ")
+    );
+}
+
+/// Parses the version from the package declaration of the WIT,
+/// for example `package slipway:component@0.1.0;`.
+fn wit_version(wit: &'static str) -> anyhow::Result<&'static str> {
+    wit.lines()
+        .find_map(|line| line.trim().strip_prefix("package "))
+        .and_then(|package| package.trim_end_matches(';').split_once('@'))
+        .map(|(_, version)| version.trim())
+        .context("Bundled WIT should declare a versioned package")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_parse_wit_version() {
+        assert_eq!(
+            wit_version("// Comment\npackage slipway:component@1.2.3;\n\ninterface types {}")
+                .unwrap(),
+            "1.2.3"
+        );
+        assert!(wit_version("package slipway:component;").is_err());
+    }
+
+    #[test]
+    fn it_should_get_wit_by_version() {
+        let current_version = current_wit_version().unwrap();
+
+        assert_eq!(list_wit_versions().unwrap()[0], current_version);
+        assert_eq!(get_wit(None).unwrap(), BUNDLED_WIT[0]);
+        assert_eq!(get_wit(Some("latest")).unwrap(), BUNDLED_WIT[0]);
+        assert_eq!(get_wit(Some(current_version)).unwrap(), BUNDLED_WIT[0]);
+        assert!(get_wit(Some("0.0.0-missing")).is_err());
+    }
+
+    #[test]
+    fn it_should_get_previous_wit_versions() {
+        assert_eq!(list_wit_versions().unwrap(), vec!["0.2.0", "0.1.0"]);
+        assert_eq!(get_wit(Some("0.1.0")).unwrap(), BUNDLED_WIT[1]);
+        assert!(!get_wit(Some("0.1.0")).unwrap().contains("cache-status"));
+        assert!(!get_wit(Some("0.1.0")).unwrap().contains("run-batch"));
+        assert!(!get_wit(Some("0.1.0")).unwrap().contains("slipway-typed"));
+    }
+}
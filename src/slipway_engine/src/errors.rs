@@ -11,6 +11,9 @@ pub enum RigError {
     #[error("Rig definition parse failed.\n{error}")]
     RigParseFailed { error: serde_json::Error },
 
+    #[error("Rig YAML definition parse failed.\n{error}")]
+    RigYamlParseFailed { error: serde_norway::Error },
+
     #[error("Invalid JSONPath expression at location \"{location}\".\n{error}")]
     InvalidJsonPathExpression {
         location: String,
@@ -93,6 +96,9 @@ pub enum ComponentLoadErrorInner {
         error: Arc<serde_json::Error>, // We're using Arc here so that ComponentError can be cloned.
     },
 
+    #[error("Component YAML definition parse failed.\n{error}")]
+    DefinitionYamlParseFailed { error: Arc<serde_norway::Error> },
+
     #[error("JSON TypeDef parse failed for {schema_name}.\n{error}")]
     JsonTypeDefParseFailed {
         schema_name: String,
@@ -120,6 +126,9 @@ pub enum ComponentLoadErrorInner {
         error: Arc<serde_json::Error>, // We're using Arc here so that ComponentError can be cloned.
     },
 
+    #[error("Component archive was rejected:\n{path}\n{error}")]
+    ArchiveRejected { path: String, error: String },
+
     #[error("Component was not found.")]
     NotFound,
 
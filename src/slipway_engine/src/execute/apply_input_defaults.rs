@@ -0,0 +1,384 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde_json::Value;
+
+use crate::Schema;
+
+use super::coerce_component_output::jtd_definitions;
+
+/// The JSON Schema keyword, or JSON Type Definition metadata key, which declares
+/// the default value of a property.
+const DEFAULT_KEY: &str = "default";
+
+/// The maximum number of references followed when resolving a schema, after which
+/// the schema is treated as having no defaults.
+const MAX_REF_DEPTH: usize = 32;
+
+/// Populates properties missing from a component input with the defaults declared
+/// by the component's input schema, so that rigs only need to specify the inputs
+/// which differ from the defaults.
+///
+/// JSON Schemas declare defaults using the `default` keyword, and JSON Type Definitions
+/// using a `default` key in the property's `metadata`.
+///
+/// Returns the JSON pointers of the properties which were populated from defaults.
+pub(super) fn apply_input_defaults(schema: &Schema, value: &mut Value) -> Vec<String> {
+    let mut defaulted = Vec::new();
+
+    match schema {
+        Schema::JsonTypeDef { schema } => {
+            let definitions = jtd_definitions(schema);
+            apply_jtd_defaults(schema, definitions, value, "", &mut defaulted);
+        }
+        Schema::JsonSchema {
+            schema: _,
+            original,
+        } => apply_json_schema_defaults(original, original, value, "", &mut defaulted),
+    }
+
+    defaulted
+}
+
+fn child_pointer(pointer: &str, key: &str) -> String {
+    format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+fn jtd_metadata(schema: &jtd::Schema) -> &BTreeMap<String, Value> {
+    match schema {
+        jtd::Schema::Empty { metadata, .. }
+        | jtd::Schema::Ref { metadata, .. }
+        | jtd::Schema::Type { metadata, .. }
+        | jtd::Schema::Enum { metadata, .. }
+        | jtd::Schema::Elements { metadata, .. }
+        | jtd::Schema::Properties { metadata, .. }
+        | jtd::Schema::Values { metadata, .. }
+        | jtd::Schema::Discriminator { metadata, .. } => metadata,
+    }
+}
+
+fn apply_jtd_defaults(
+    schema: &jtd::Schema,
+    definitions: &BTreeMap<String, jtd::Schema>,
+    value: &mut Value,
+    pointer: &str,
+    defaulted: &mut Vec<String>,
+) {
+    let Some(schema) = resolve_jtd_schema(schema, definitions) else {
+        return;
+    };
+
+    match schema {
+        jtd::Schema::Elements { elements, .. } => {
+            if let Value::Array(items) = value {
+                for (index, item) in items.iter_mut().enumerate() {
+                    let item_pointer = child_pointer(pointer, &index.to_string());
+                    apply_jtd_defaults(elements, definitions, item, &item_pointer, defaulted);
+                }
+            }
+        }
+        jtd::Schema::Properties {
+            properties,
+            optional_properties,
+            ..
+        } => {
+            let Value::Object(map) = value else {
+                return;
+            };
+
+            for (key, property_schema) in properties.iter().chain(optional_properties.iter()) {
+                let property_pointer = child_pointer(pointer, key);
+                match map.get_mut(key) {
+                    Some(property_value) => apply_jtd_defaults(
+                        property_schema,
+                        definitions,
+                        property_value,
+                        &property_pointer,
+                        defaulted,
+                    ),
+                    None => {
+                        if let Some(default) = jtd_metadata(property_schema).get(DEFAULT_KEY) {
+                            map.insert(key.clone(), default.clone());
+                            defaulted.push(property_pointer);
+                        }
+                    }
+                }
+            }
+        }
+        jtd::Schema::Values { values, .. } => {
+            if let Value::Object(map) = value {
+                for (key, property_value) in map.iter_mut() {
+                    let property_pointer = child_pointer(pointer, key);
+                    apply_jtd_defaults(
+                        values,
+                        definitions,
+                        property_value,
+                        &property_pointer,
+                        defaulted,
+                    );
+                }
+            }
+        }
+        jtd::Schema::Empty { .. }
+        | jtd::Schema::Ref { .. }
+        | jtd::Schema::Type { .. }
+        | jtd::Schema::Enum { .. }
+        | jtd::Schema::Discriminator { .. } => {}
+    }
+}
+
+/// Resolves definition references, returning the referenced schema.
+///
+/// Returns `None` if the references form a cycle or are nested too deeply.
+fn resolve_jtd_schema<'a>(
+    mut schema: &'a jtd::Schema,
+    definitions: &'a BTreeMap<String, jtd::Schema>,
+) -> Option<&'a jtd::Schema> {
+    let mut visited = HashSet::new();
+
+    while let jtd::Schema::Ref { ref_, .. } = schema {
+        if visited.len() >= MAX_REF_DEPTH || !visited.insert(ref_.as_str()) {
+            return None;
+        }
+
+        schema = definitions.get(ref_)?;
+    }
+
+    Some(schema)
+}
+
+/// Resolves local `$ref` references, returning the referenced schema.
+///
+/// Returns `None` if the references form a cycle or are nested too deeply.
+fn resolve_json_schema<'a>(mut schema: &'a Value, root: &'a Value) -> Option<&'a Value> {
+    let mut visited = HashSet::new();
+
+    while let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+        // Only local references can be resolved.
+        let Some(referenced) = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+        else {
+            break;
+        };
+
+        if visited.len() >= MAX_REF_DEPTH || !visited.insert(reference) {
+            return None;
+        }
+
+        schema = referenced;
+    }
+
+    Some(schema)
+}
+
+fn apply_json_schema_defaults(
+    schema: &Value,
+    root: &Value,
+    value: &mut Value,
+    pointer: &str,
+    defaulted: &mut Vec<String>,
+) {
+    let Some(schema) = resolve_json_schema(schema, root) else {
+        return;
+    };
+
+    match value {
+        Value::Object(map) => {
+            let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+                return;
+            };
+
+            for (key, property_schema) in properties {
+                let property_pointer = child_pointer(pointer, key);
+                match map.get_mut(key) {
+                    Some(property_value) => apply_json_schema_defaults(
+                        property_schema,
+                        root,
+                        property_value,
+                        &property_pointer,
+                        defaulted,
+                    ),
+                    None => {
+                        if let Some(default) = resolve_json_schema(property_schema, root)
+                            .and_then(|property_schema| property_schema.get(DEFAULT_KEY))
+                        {
+                            map.insert(key.clone(), default.clone());
+                            defaulted.push(property_pointer);
+                        }
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(items_schema) = schema.get("items") {
+                for (index, item) in items.iter_mut().enumerate() {
+                    let item_pointer = child_pointer(pointer, &index.to_string());
+                    apply_json_schema_defaults(items_schema, root, item, &item_pointer, defaulted);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn jtd_schema(schema: Value) -> Schema {
+        Schema::JsonTypeDef {
+            schema: jtd::Schema::from_serde_schema(serde_json::from_value(schema).unwrap())
+                .unwrap(),
+        }
+    }
+
+    fn json_schema(schema: Value) -> Schema {
+        Schema::JsonSchema {
+            schema: Box::new(jsonschema::Validator::new(&schema).unwrap()),
+            original: schema,
+        }
+    }
+
+    #[test]
+    fn it_should_apply_jtd_defaults() {
+        let schema = jtd_schema(json!({
+            "properties": {
+                "size": { "type": "uint32" },
+            },
+            "optionalProperties": {
+                "units": { "type": "string", "metadata": { "default": "metric" } },
+                "days": { "type": "uint8", "metadata": { "default": 3 } },
+                "theme": { "ref": "theme" },
+            },
+            "definitions": {
+                "theme": {
+                    "optionalProperties": {
+                        "dark": { "type": "boolean", "metadata": { "default": false } },
+                    },
+                },
+            },
+        }));
+
+        let mut value = json!({ "size": 5, "days": 7, "theme": {} });
+        let defaulted = apply_input_defaults(&schema, &mut value);
+
+        assert_eq!(
+            value,
+            json!({ "size": 5, "units": "metric", "days": 7, "theme": { "dark": false } })
+        );
+        assert_eq!(defaulted, vec!["/theme/dark", "/units"]);
+    }
+
+    #[test]
+    fn it_should_apply_json_schema_defaults() {
+        let schema = json_schema(json!({
+            "type": "object",
+            "properties": {
+                "units": { "type": "string", "default": "metric" },
+                "days": { "type": "integer", "default": 3 },
+                "location": { "$ref": "#/$defs/location" },
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "count": { "type": "integer", "default": 1 } },
+                    },
+                },
+            },
+            "$defs": {
+                "location": {
+                    "type": "object",
+                    "properties": { "city": { "type": "string", "default": "London" } },
+                },
+            },
+        }));
+
+        let mut value = json!({ "days": 7, "location": {}, "items": [{}, { "count": 2 }] });
+        let defaulted = apply_input_defaults(&schema, &mut value);
+
+        assert_eq!(
+            value,
+            json!({
+                "units": "metric",
+                "days": 7,
+                "location": { "city": "London" },
+                "items": [{ "count": 1 }, { "count": 2 }],
+            })
+        );
+
+        let mut defaulted = defaulted;
+        defaulted.sort();
+        assert_eq!(
+            defaulted,
+            vec!["/items/0/count", "/location/city", "/units"]
+        );
+    }
+
+    #[test]
+    fn it_should_ignore_cyclic_json_schema_refs() {
+        let schema = json!({
+            "$ref": "#/$defs/a",
+            "properties": {
+                "units": { "type": "string", "default": "metric" },
+            },
+            "$defs": {
+                "a": { "$ref": "#/$defs/b" },
+                "b": { "$ref": "#/$defs/a" },
+            },
+        });
+
+        for schema in [schema, json!({ "$ref": "#" })] {
+            let mut value = json!({});
+            let mut defaulted = Vec::new();
+            apply_json_schema_defaults(&schema, &schema, &mut value, "", &mut defaulted);
+
+            assert_eq!(value, json!({}));
+            assert!(defaulted.is_empty());
+        }
+    }
+
+    #[test]
+    fn it_should_ignore_cyclic_jtd_refs() {
+        let reference = |ref_: &str| jtd::Schema::Ref {
+            definitions: Default::default(),
+            metadata: Default::default(),
+            nullable: false,
+            ref_: ref_.to_string(),
+        };
+        let definitions = BTreeMap::from([
+            ("a".to_string(), reference("b")),
+            ("b".to_string(), reference("a")),
+        ]);
+
+        let mut value = json!({});
+        let mut defaulted = Vec::new();
+        apply_jtd_defaults(
+            &reference("a"),
+            &definitions,
+            &mut value,
+            "",
+            &mut defaulted,
+        );
+
+        assert_eq!(value, json!({}));
+        assert!(defaulted.is_empty());
+    }
+
+    #[test]
+    fn it_should_not_replace_supplied_values() {
+        let schema = json_schema(json!({
+            "type": "object",
+            "properties": {
+                "units": { "type": ["string", "null"], "default": "metric" },
+            },
+        }));
+
+        let mut value = json!({ "units": null });
+        let defaulted = apply_input_defaults(&schema, &mut value);
+
+        assert_eq!(value, json!({ "units": null }));
+        assert!(defaulted.is_empty());
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Number, Value};
+
+use crate::Schema;
+
+/// Leniently coerces scalar values in a component output to the types required by
+/// the output schema, for example turning `"42"` into `42` where a number is expected.
+///
+/// Coercions may not round-trip: surrounding whitespace and leading zeros are
+/// dropped when parsing numbers, and decimal strings with more precision than an
+/// `f64` are rounded. Values which cannot be parsed as the required type are left
+/// unchanged so that schema validation reports them as usual.
+pub(super) fn coerce_component_output(schema: &Schema, value: &mut Value) {
+    match schema {
+        Schema::JsonTypeDef { schema } => {
+            let definitions = jtd_definitions(schema);
+            coerce_jtd(schema, definitions, value);
+        }
+        Schema::JsonSchema {
+            schema: _,
+            original,
+        } => coerce_json_schema(original, original, value),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ScalarType {
+    Boolean,
+    Integer,
+    Number,
+    String,
+}
+
+fn coerce_scalar(target: ScalarType, value: &mut Value) {
+    let coerced = match (target, &*value) {
+        (ScalarType::Boolean, Value::String(s)) => match s.as_str() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        (ScalarType::Integer, Value::String(s)) => parse_integer(s.trim()),
+        (ScalarType::Number, Value::String(s)) => parse_integer(s.trim()).or_else(|| {
+            s.trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(Number::from_f64)
+                .map(Value::Number)
+        }),
+        (ScalarType::String, Value::Number(n)) => Some(Value::String(n.to_string())),
+        (ScalarType::String, Value::Bool(b)) => Some(Value::String(b.to_string())),
+        _ => None,
+    };
+
+    if let Some(coerced) = coerced {
+        *value = coerced;
+    }
+}
+
+fn parse_integer(s: &str) -> Option<Value> {
+    if let Ok(i) = s.parse::<i64>() {
+        return Some(Value::Number(i.into()));
+    }
+
+    if let Ok(u) = s.parse::<u64>() {
+        return Some(Value::Number(u.into()));
+    }
+
+    None
+}
+
+pub(super) fn jtd_definitions(schema: &jtd::Schema) -> &BTreeMap<String, jtd::Schema> {
+    match schema {
+        jtd::Schema::Empty { definitions, .. }
+        | jtd::Schema::Ref { definitions, .. }
+        | jtd::Schema::Type { definitions, .. }
+        | jtd::Schema::Enum { definitions, .. }
+        | jtd::Schema::Elements { definitions, .. }
+        | jtd::Schema::Properties { definitions, .. }
+        | jtd::Schema::Values { definitions, .. }
+        | jtd::Schema::Discriminator { definitions, .. } => definitions,
+    }
+}
+
+fn coerce_jtd(
+    schema: &jtd::Schema,
+    definitions: &BTreeMap<String, jtd::Schema>,
+    value: &mut Value,
+) {
+    match schema {
+        jtd::Schema::Empty { .. } | jtd::Schema::Enum { .. } => {}
+        jtd::Schema::Ref { ref_, .. } => {
+            if let Some(definition) = definitions.get(ref_) {
+                coerce_jtd(definition, definitions, value);
+            }
+        }
+        jtd::Schema::Type { type_, .. } => {
+            let target = match type_ {
+                jtd::Type::Boolean => ScalarType::Boolean,
+                jtd::Type::Int8
+                | jtd::Type::Uint8
+                | jtd::Type::Int16
+                | jtd::Type::Uint16
+                | jtd::Type::Int32
+                | jtd::Type::Uint32 => ScalarType::Integer,
+                jtd::Type::Float32 | jtd::Type::Float64 => ScalarType::Number,
+                jtd::Type::String | jtd::Type::Timestamp => ScalarType::String,
+            };
+            coerce_scalar(target, value);
+        }
+        jtd::Schema::Elements { elements, .. } => {
+            if let Value::Array(items) = value {
+                for item in items {
+                    coerce_jtd(elements, definitions, item);
+                }
+            }
+        }
+        jtd::Schema::Properties {
+            properties,
+            optional_properties,
+            ..
+        } => {
+            if let Value::Object(map) = value {
+                for (key, property_value) in map.iter_mut() {
+                    if let Some(property_schema) =
+                        properties.get(key).or_else(|| optional_properties.get(key))
+                    {
+                        coerce_jtd(property_schema, definitions, property_value);
+                    }
+                }
+            }
+        }
+        jtd::Schema::Values { values, .. } => {
+            if let Value::Object(map) = value {
+                for property_value in map.values_mut() {
+                    coerce_jtd(values, definitions, property_value);
+                }
+            }
+        }
+        jtd::Schema::Discriminator {
+            discriminator,
+            mapping,
+            ..
+        } => {
+            let variant = value
+                .get(discriminator)
+                .and_then(|d| d.as_str())
+                .and_then(|d| mapping.get(d));
+
+            if let (Some(variant), Value::Object(map)) = (variant, value) {
+                for (key, property_value) in map.iter_mut() {
+                    if key == discriminator {
+                        continue;
+                    }
+
+                    if let jtd::Schema::Properties {
+                        properties,
+                        optional_properties,
+                        ..
+                    } = variant
+                        && let Some(property_schema) =
+                            properties.get(key).or_else(|| optional_properties.get(key))
+                    {
+                        coerce_jtd(property_schema, definitions, property_value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn coerce_json_schema(schema: &Value, root: &Value, value: &mut Value) {
+    let Value::Object(schema) = schema else {
+        return;
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+        // Only local references can be resolved.
+        if let Some(pointer) = reference.strip_prefix('#')
+            && let Some(referenced) = root.pointer(pointer)
+        {
+            coerce_json_schema(referenced, root, value);
+        }
+        return;
+    }
+
+    // Only coerce if the schema specifies a single type, otherwise it would be
+    // ambiguous which type to coerce to.
+    if let Some(type_) = schema.get("type").and_then(|t| t.as_str()) {
+        let target = match type_ {
+            "boolean" => Some(ScalarType::Boolean),
+            "integer" => Some(ScalarType::Integer),
+            "number" => Some(ScalarType::Number),
+            "string" => Some(ScalarType::String),
+            _ => None,
+        };
+
+        if let Some(target) = target {
+            coerce_scalar(target, value);
+            return;
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(|p| p.as_object());
+            let additional_properties = schema.get("additionalProperties");
+
+            for (key, property_value) in map.iter_mut() {
+                let property_schema = properties
+                    .and_then(|p| p.get(key))
+                    .or(additional_properties);
+
+                if let Some(property_schema) = property_schema {
+                    coerce_json_schema(property_schema, root, property_value);
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(items_schema) = schema.get("items") {
+                for item in items {
+                    coerce_json_schema(items_schema, root, item);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn jtd_schema(schema: Value) -> Schema {
+        Schema::JsonTypeDef {
+            schema: jtd::Schema::from_serde_schema(serde_json::from_value(schema).unwrap())
+                .unwrap(),
+        }
+    }
+
+    fn json_schema(schema: Value) -> Schema {
+        Schema::JsonSchema {
+            schema: Box::new(jsonschema::Validator::new(&schema).unwrap()),
+            original: schema,
+        }
+    }
+
+    #[test]
+    fn it_should_coerce_jtd_outputs() {
+        let schema = jtd_schema(json!({
+            "properties": {
+                "int": { "type": "int32" },
+                "float": { "type": "float64" },
+                "bool": { "type": "boolean" },
+                "string": { "type": "string" },
+                "list": { "elements": { "type": "uint8" } },
+                "nested": { "ref": "nested" },
+            },
+            "definitions": {
+                "nested": { "values": { "type": "float32" } },
+            },
+        }));
+
+        let mut value = json!({
+            "int": "42",
+            "float": " 1.5 ",
+            "bool": "true",
+            "string": 7,
+            "list": ["1", 2],
+            "nested": { "a": "3.25" },
+        });
+
+        coerce_component_output(&schema, &mut value);
+
+        assert_eq!(
+            value,
+            json!({
+                "int": 42,
+                "float": 1.5,
+                "bool": true,
+                "string": "7",
+                "list": [1, 2],
+                "nested": { "a": 3.25 },
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_coerce_json_schema_outputs() {
+        let schema = json_schema(json!({
+            "type": "object",
+            "properties": {
+                "value": { "type": "integer" },
+                "items": { "type": "array", "items": { "$ref": "#/$defs/number" } },
+            },
+            "$defs": {
+                "number": { "type": "number" },
+            },
+        }));
+
+        let mut value = json!({ "value": "10", "items": ["1.5", 2] });
+
+        coerce_component_output(&schema, &mut value);
+
+        assert_eq!(value, json!({ "value": 10, "items": [1.5, 2] }));
+    }
+
+    #[test]
+    fn it_should_not_coerce_ambiguous_json_schema_types() {
+        let schema = json_schema(json!({
+            "type": "object",
+            "properties": {
+                "value": { "type": ["integer", "string"] },
+            },
+        }));
+
+        let mut value = json!({ "value": "10" });
+
+        coerce_component_output(&schema, &mut value);
+
+        assert_eq!(value, json!({ "value": "10" }));
+    }
+
+    #[test]
+    fn it_should_not_preserve_the_original_string_when_coercing_numbers() {
+        let schema = jtd_schema(json!({
+            "properties": {
+                "int": { "type": "int32" },
+                "float": { "type": "float64" },
+            },
+        }));
+
+        let mut value = json!({ "int": " 007 ", "float": "0.1000000000000000055511151231257827" });
+
+        coerce_component_output(&schema, &mut value);
+
+        assert_eq!(value, json!({ "int": 7, "float": 0.1 }));
+    }
+
+    #[test]
+    fn it_should_leave_values_which_cannot_be_safely_coerced() {
+        let schema = jtd_schema(json!({
+            "properties": {
+                "int": { "type": "int32" },
+                "bool": { "type": "boolean" },
+            },
+        }));
+
+        let mut value = json!({ "int": "foo", "bool": "yes" });
+
+        coerce_component_output(&schema, &mut value);
+
+        assert_eq!(value, json!({ "int": "foo", "bool": "yes" }));
+    }
+}
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, str::FromStr, sync::Arc};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
 
 use crate::{
     Callout, Component, ComponentCache, ComponentFiles, ComponentHandle, ComponentInput,
@@ -6,7 +10,7 @@ use crate::{
     SlipwayReference, errors::RigError,
 };
 
-use super::component_runner::ComponentRunner;
+use super::{component_runner::ComponentRunner, concurrency_limit::ComponentConcurrencyPermit};
 
 pub(crate) mod permissions;
 
@@ -16,26 +20,108 @@ pub struct ComponentExecutionData<'call, 'rig, 'runners> {
     pub context: ComponentExecutionContext<'call, 'rig, 'runners>,
 }
 
+/// Everything a [`ComponentRunner`] needs to run a component, other than its input.
+///
+/// The fields are accessed through methods so that third party runners are
+/// insulated from changes to how the context is stored.
 #[derive(Clone)]
 pub struct ComponentExecutionContext<'call, 'rig, 'runners> {
-    pub component_reference: &'rig SlipwayReference,
-    pub component_definition: Arc<Component<Schema>>,
-    pub component_cache: &'rig dyn ComponentCache,
-    pub component_runners: &'runners [Box<dyn ComponentRunner>],
-    pub call_chain: Arc<CallChain<'rig>>,
-    pub files: Arc<ComponentFiles>,
-    pub callout_context: CalloutContext<'call, 'rig>,
-    pub rig_session_options: &'rig RigSessionOptions,
+    pub(crate) component_reference: &'rig SlipwayReference,
+    pub(crate) component_definition: Arc<Component<Schema>>,
+    pub(crate) component_cache: &'rig dyn ComponentCache,
+    pub(crate) component_runners: &'runners [Box<dyn ComponentRunner>],
+    pub(crate) call_chain: Arc<CallChain<'rig>>,
+    pub(crate) files: Arc<ComponentFiles>,
+    pub(crate) callout_context: CalloutContext<'call, 'rig>,
+    pub(crate) rig_session_options: &'rig RigSessionOptions,
+    pub(crate) concurrency_permit: Arc<ComponentConcurrencyPermit>,
+    pub(crate) permission_denial: Arc<Mutex<Option<PermissionDenial>>>,
+}
+
+/// A host call made by a component which was denied by its permissions.
+#[derive(Debug, Clone)]
+pub struct PermissionDenial {
+    pub message: String,
+    pub inner: Vec<String>,
 }
 
-impl ComponentExecutionContext<'_, '_, '_> {
+impl<'call, 'rig, 'runners> ComponentExecutionContext<'call, 'rig, 'runners> {
+    /// The reference the component was loaded from.
+    pub fn component_reference(&self) -> &'rig SlipwayReference {
+        self.component_reference
+    }
+
+    /// The component's definition, including its input and output schemas.
+    pub fn component_definition(&self) -> &Arc<Component<Schema>> {
+        &self.component_definition
+    }
+
+    /// The cache of every component the rig uses, including the component's callouts.
+    pub fn component_cache(&self) -> &'rig dyn ComponentCache {
+        self.component_cache
+    }
+
+    /// The runners available for running the component's callouts.
+    pub fn component_runners(&self) -> &'runners [Box<dyn ComponentRunner>] {
+        self.component_runners
+    }
+
+    /// The chain of components which led to this component being run,
+    /// along with the permissions granted by each.
+    pub fn call_chain(&self) -> &Arc<CallChain<'rig>> {
+        &self.call_chain
+    }
+
+    /// The files the component was loaded with, such as its WASM or Javascript.
+    pub fn files(&self) -> &Arc<ComponentFiles> {
+        &self.files
+    }
+
+    /// The callouts the component can make, keyed by handle.
+    pub fn callout_context(&self) -> &CalloutContext<'call, 'rig> {
+        &self.callout_context
+    }
+
+    /// The options of the rig session the component is running in.
+    pub fn rig_session_options(&self) -> &'rig RigSessionOptions {
+        self.rig_session_options
+    }
+
+    /// The handle of the component in the rig.
     pub fn component_handle(&self) -> &ComponentHandle {
         self.call_chain.current_component_handle()
     }
 
+    /// Runs the future with this component's concurrency permit released,
+    /// so that any nested components it waits on are able to run.
+    pub async fn release_concurrency_permit_while<F: Future>(&self, future: F) -> F::Output {
+        self.concurrency_permit.release_while(future).await
+    }
+
+    /// The handles of the components in the call chain, for use in messages.
     pub fn component_handle_trail(&self) -> String {
         self.call_chain.component_handle_trail()
     }
+
+    /// Records that a host call made by the component was denied by its permissions.
+    ///
+    /// If the component then fails, it fails with `RunComponentError::PermissionDenied`
+    /// for the first denial rather than with the error the component returned.
+    pub fn record_permission_denial(&self, denial: PermissionDenial) {
+        let mut permission_denial = self
+            .permission_denial
+            .lock()
+            .expect("permission denial lock should not be poisoned");
+        permission_denial.get_or_insert(denial);
+    }
+
+    /// Returns the first permission denial recorded since this was last called.
+    pub(crate) fn take_permission_denial(&self) -> Option<PermissionDenial> {
+        self.permission_denial
+            .lock()
+            .expect("permission denial lock should not be poisoned")
+            .take()
+    }
 }
 
 #[derive(Clone)]
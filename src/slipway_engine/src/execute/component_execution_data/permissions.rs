@@ -3,6 +3,19 @@ use std::sync::Arc;
 use super::{CallChain, ChainItem, Permissions};
 
 /// Ensure that the check passes at every level of the call chain.
+///
+/// The effective permissions of a component are the intersection of the permissions
+/// at every level of its call chain, from the rig (or host) down to the component itself:
+/// - At each level, a request must be matched by at least one `allow` permission
+///   and must not be matched by any `deny` permission.
+/// - `deny` always takes precedence over `allow`, at any level.
+/// - An `Inherit` level defers to the levels above it. If the top of the chain is
+///   reached while inheriting, the request is checked against empty permissions,
+///   and so is denied.
+///
+/// Because a callout adds its own level below the calling component, the permissions
+/// granted to a callout can narrow, but never exceed, those of its caller.
+/// A `permissions_chain` on a component rigging adds one level per link.
 #[must_use]
 pub fn ensure_permissions<F>(call_chain: Arc<CallChain<'_>>, check: F) -> bool
 where
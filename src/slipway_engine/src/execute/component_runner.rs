@@ -1,4 +1,10 @@
-use std::{borrow::Cow, path::Path, sync::Arc};
+use std::{
+    borrow::Cow,
+    convert::Infallible,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 
 use crate::{
     CallChain, ComponentExecutionContext, ComponentFiles, ComponentHandle, RigExecutionState,
@@ -7,21 +13,77 @@ use crate::{
 };
 use async_trait::async_trait;
 use thiserror::Error;
-use tracing::{Instrument, info_span};
+use tracing::{Instrument, debug, info_span, trace};
 
 use super::{
+    apply_input_defaults::apply_input_defaults,
+    coerce_component_output::coerce_component_output,
     component_execution_data::ComponentExecutionData,
+    memoize::get_memoized_result,
     rig_execution_state::get_component_execution_data_for_callout,
     validate_component_io::{ValidationData, validate_component_io},
 };
 
 pub enum TryAotCompileComponentResult {
-    Compiled,
+    Compiled {
+        /// The target triple the component was compiled for.
+        target: String,
+
+        /// The path the AOT compiled artifact was written to.
+        path: PathBuf,
+
+        /// The size of the written AOT compiled artifact.
+        bytes_written: u64,
+    },
+    UpToDate {
+        /// The target triple the existing AOT compiled artifact was compiled for.
+        target: String,
+
+        /// The path of the existing AOT compiled artifact.
+        path: PathBuf,
+    },
     CannotCompile,
 }
+
+pub enum TryVerifyComponentResult {
+    Verified,
+    CannotVerify,
+}
+
 pub enum TryRunComponentResult {
-    CannotRun,
-    Ran { result: RunComponentResult },
+    /// The runner does not support the component, optionally with the reason why,
+    /// which is reported if no runner can run the component.
+    CannotRun {
+        reason: Option<String>,
+    },
+    Ran {
+        result: RunComponentResult,
+    },
+}
+
+pub enum TryRunBatchComponentResult {
+    CannotRun { reason: Option<String> },
+    Ran { results: Vec<RunComponentResult> },
+}
+
+/// Why a runner did not run a component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CannotRunReason {
+    pub component_runner: String,
+    pub reason: Option<String>,
+}
+
+fn format_cannot_run_reasons(reasons: &[CannotRunReason]) -> String {
+    reasons
+        .iter()
+        .map(|r| {
+            format!(
+                "\n  {}: {}",
+                r.component_runner,
+                r.reason.as_deref().unwrap_or("No reason given.")
+            )
+        })
+        .collect()
 }
 
 pub struct RunComponentResult {
@@ -49,12 +111,77 @@ pub enum RunComponentError {
     #[error("Deserializing output JSON failed.\n{source}")]
     DeserializeOutputFailed { source: serde_json::Error },
 
+    #[error("Component exceeded its evaluation limit.\n{message}")]
+    Timeout { message: String },
+
+    #[error("Component exceeded its {kind} limit.\n{message}")]
+    ResourceLimitExceeded { kind: String, message: String },
+
+    #[error("Component was denied permission: {message}\nInner errors:\n{inner:#?}")]
+    PermissionDenied { message: String, inner: Vec<String> },
+
     #[error("Component load failed.\n{0}")]
     ComponentLoadFailed(#[from] ComponentLoadError),
 }
 
+/// Runs components, for example by executing their WASM or JavaScript.
+///
+/// Embedders can support other kinds of component by implementing this trait and
+/// including the runner in the list passed to `run_rig`.
+///
+/// Unless a component's rigging requests a runner by its identifier, every runner is
+/// called in order. Runners return `CannotRun` for components they don't support, and
+/// the output of each runner which does run is added to the input of the next under
+/// the `run` key. The output of the last runner to run is the component's output.
+///
+/// # Example
+///
+/// A runner for components which contain a `greeting.txt` file, which outputs the
+/// file's text along with the component's handle in the rig:
+///
+/// ```
+/// use async_trait::async_trait;
+/// use slipway_engine::{
+///     ComponentExecutionContext, ComponentRunner, RunComponentError, RunComponentResult,
+///     RunMetadata, TryRunComponentResult,
+/// };
+///
+/// struct GreetingRunner;
+///
+/// #[async_trait(?Send)]
+/// impl ComponentRunner for GreetingRunner {
+///     fn identifier(&self) -> String {
+///         "greeting".to_string()
+///     }
+///
+///     async fn run<'call>(
+///         &self,
+///         _input: &serde_json::Value,
+///         context: &'call ComponentExecutionContext<'call, '_, '_>,
+///     ) -> Result<TryRunComponentResult, RunComponentError> {
+///         let Some(greeting) = context.files().try_get_text("greeting.txt").await? else {
+///             return Ok(TryRunComponentResult::CannotRun {
+///                 reason: Some("No \"greeting.txt\" file found.".to_string()),
+///             });
+///         };
+///
+///         let output = serde_json::json!({
+///             "greeting": greeting.as_str(),
+///             "handle": context.component_handle().to_string(),
+///         });
+///
+///         Ok(TryRunComponentResult::Ran {
+///             result: RunComponentResult {
+///                 output,
+///                 metadata: RunMetadata::default(),
+///             },
+///         })
+///     }
+/// }
+/// ```
 #[async_trait(?Send)]
 pub trait ComponentRunner: Send + Sync {
+    /// A unique name for the runner, which a component's rigging can use to request it.
     fn identifier(&self) -> String;
 
     async fn aot_compile(
@@ -62,16 +189,54 @@ pub trait ComponentRunner: Send + Sync {
         _component_reference: &SlipwayReference,
         _aot_path: &Path,
         _target: Option<&str>,
+        _force: bool,
         _files: Arc<ComponentFiles>,
     ) -> Result<TryAotCompileComponentResult, RunComponentError> {
         Ok(TryAotCompileComponentResult::CannotCompile)
     }
 
+    /// Checks that the component could be run by this runner, without running it.
+    ///
+    /// This is used to find corrupt or incompatible components before they are needed.
+    async fn verify(
+        &self,
+        _component_reference: &SlipwayReference,
+        _files: Arc<ComponentFiles>,
+    ) -> Result<TryVerifyComponentResult, RunComponentError> {
+        Ok(TryVerifyComponentResult::CannotVerify)
+    }
+
+    /// Runs the component with the given input, or returns `CannotRun` if this runner
+    /// does not support the component. The component's definition and files are
+    /// available from the context.
     async fn run<'call>(
         &self,
         input: &serde_json::Value,
         context: &'call ComponentExecutionContext<'call, '_, '_>,
     ) -> Result<TryRunComponentResult, RunComponentError>;
+
+    /// Runs the component once for each input, returning one result per input.
+    ///
+    /// This is only called for components which declare themselves `batchable`.
+    /// The default implementation runs each input separately, so runners only
+    /// need to override it if they can reuse work between inputs.
+    async fn run_batch<'call>(
+        &self,
+        inputs: &[serde_json::Value],
+        context: &'call ComponentExecutionContext<'call, '_, '_>,
+    ) -> Result<TryRunBatchComponentResult, RunComponentError> {
+        let mut results = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            match self.run(input, context).await? {
+                TryRunComponentResult::Ran { result } => results.push(result),
+                TryRunComponentResult::CannotRun { reason } => {
+                    return Ok(TryRunBatchComponentResult::CannotRun { reason });
+                }
+            }
+        }
+
+        Ok(TryRunBatchComponentResult::Ran { results })
+    }
 }
 
 #[derive(Error, Debug)]
@@ -82,8 +247,30 @@ pub enum RunError<THostError> {
     #[error("Component load failed during running.\n{0}")]
     ComponentLoadFailed(#[from] ComponentLoadError),
 
-    #[error("No component runner was found for component \"{component_handle}\".")]
-    ComponentRunnerNotFound { component_handle: ComponentHandle },
+    #[error(
+        "No component runner was found for component \"{component_handle}\".{}",
+        format_cannot_run_reasons(.reasons)
+    )]
+    ComponentRunnerNotFound {
+        component_handle: ComponentHandle,
+        reasons: Vec<CannotRunReason>,
+    },
+
+    #[error(
+        "The \"{component_runner}\" runner requested for component \"{component_handle}\" does not exist."
+    )]
+    RequestedComponentRunnerNotFound {
+        component_handle: ComponentHandle,
+        component_runner: String,
+    },
+
+    #[error(
+        "The \"{component_runner}\" runner requested for component \"{component_handle}\" cannot run it."
+    )]
+    RequestedComponentRunnerCannotRun {
+        component_handle: ComponentHandle,
+        component_runner: String,
+    },
 
     #[error(
         "Run component failed for component \"{component_handle}\" using \"{component_runner}\" runner.\n{error}"
@@ -98,21 +285,73 @@ pub enum RunError<THostError> {
     HostError(THostError),
 }
 
+impl<THostError> RunError<THostError> {
+    /// Returns the host error if this is one, and otherwise the error without a host
+    /// error type, so that it can be displayed even if the host error can't be.
+    pub fn split_host_error(self) -> Result<RunError<Infallible>, THostError> {
+        match self {
+            RunError::Rig(error) => Ok(RunError::Rig(error)),
+            RunError::ComponentLoadFailed(error) => Ok(RunError::ComponentLoadFailed(error)),
+            RunError::ComponentRunnerNotFound {
+                component_handle,
+                reasons,
+            } => Ok(RunError::ComponentRunnerNotFound {
+                component_handle,
+                reasons,
+            }),
+            RunError::RequestedComponentRunnerNotFound {
+                component_handle,
+                component_runner,
+            } => Ok(RunError::RequestedComponentRunnerNotFound {
+                component_handle,
+                component_runner,
+            }),
+            RunError::RequestedComponentRunnerCannotRun {
+                component_handle,
+                component_runner,
+            } => Ok(RunError::RequestedComponentRunnerCannotRun {
+                component_handle,
+                component_runner,
+            }),
+            RunError::RunComponentFailed {
+                component_handle,
+                component_runner,
+                error,
+            } => Ok(RunError::RunComponentFailed {
+                component_handle,
+                component_runner,
+                error,
+            }),
+            RunError::HostError(error) => Err(error),
+        }
+    }
+}
+
 pub async fn run_component<'rig, THostError>(
     handle: &ComponentHandle,
     state: &RigExecutionState<'rig, '_>,
     component_runners: &[Box<dyn ComponentRunner>],
     call_chain: Arc<CallChain<'rig>>,
 ) -> Result<RunComponentResult, RunError<THostError>> {
+    let component_state = state
+        .component_states
+        .get(handle)
+        .expect("component state should exist");
+
+    if component_state.execution_skipped {
+        debug!("Skipping component \"{handle}\" as its skip condition was met.");
+        return Ok(RunComponentResult {
+            output: serde_json::Value::Null,
+            metadata: RunMetadata::default(),
+        });
+    }
+
     let execution_data =
         state.get_component_execution_data(handle, Arc::clone(&call_chain), component_runners)?;
 
-    if state.session.run_record_enabled() {
-        let component_state = state
-            .component_states
-            .get(handle)
-            .expect("component state should exist");
+    let requested_runner = component_state.rigging.runner.as_deref();
 
+    if state.session.run_record_enabled() {
         let input = component_state
             .execution_input
             .as_ref()
@@ -123,27 +362,150 @@ pub async fn run_component<'rig, THostError>(
             Arc::clone(&execution_data.context.call_chain),
             Arc::clone(input),
             component_state.rigging.callouts.clone(),
+            component_state.rigging.runner.clone(),
         );
     }
 
-    run_component_inner(&execution_data).await
+    if let Some(result) = get_memoized_result(handle, state) {
+        return Ok(result);
+    }
+
+    match requested_runner {
+        Some(requested_runner) => {
+            run_component_with_runner(&execution_data, requested_runner).await
+        }
+        None => run_component_inner(&execution_data).await,
+    }
 }
 
 pub async fn run_component_callout<THostError>(
     handle: &ComponentHandle,
     input: serde_json::Value,
     execution_context: &ComponentExecutionContext<'_, '_, '_>,
+) -> Result<RunComponentResult, RunError<THostError>> {
+    // The calling component is suspended until the callout completes, so it releases
+    // its concurrency permit to avoid deadlocking when the limit is reached.
+    execution_context
+        .release_concurrency_permit_while(run_component_callout_inner(
+            handle,
+            input,
+            execution_context,
+        ))
+        .await
+}
+
+async fn run_component_callout_inner<THostError>(
+    handle: &ComponentHandle,
+    input: serde_json::Value,
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
 ) -> Result<RunComponentResult, RunError<THostError>> {
     let execution_data =
         get_component_execution_data_for_callout(handle, input, execution_context)?;
 
+    run_callout_execution_data(&execution_data, handle).await
+}
+
+async fn run_callout_execution_data<THostError>(
+    execution_data: &ComponentExecutionData<'_, '_, '_>,
+    handle: &ComponentHandle,
+) -> Result<RunComponentResult, RunError<THostError>> {
     validate_component_io(
         ValidationData::Input(&execution_data.input.value),
         Arc::clone(&execution_data.context.component_definition),
         handle,
     )?;
 
-    let result = run_component_inner(&execution_data).await?;
+    let mut result = run_component_inner(execution_data).await?;
+
+    process_callout_output(execution_data, handle, &mut result)?;
+
+    Ok(result)
+}
+
+/// Runs a callout once for each input, returning one result per input.
+///
+/// If the component declares itself `batchable` then every input is passed to the
+/// component runners together, allowing them to reuse a single instance of the component.
+/// Otherwise each input is run as a separate callout.
+pub async fn run_component_callout_batch<THostError>(
+    handle: &ComponentHandle,
+    inputs: Vec<serde_json::Value>,
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
+) -> Result<Vec<RunComponentResult>, RunError<THostError>> {
+    execution_context
+        .release_concurrency_permit_while(run_component_callout_batch_inner(
+            handle,
+            inputs,
+            execution_context,
+        ))
+        .await
+}
+
+async fn run_component_callout_batch_inner<THostError>(
+    handle: &ComponentHandle,
+    inputs: Vec<serde_json::Value>,
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
+) -> Result<Vec<RunComponentResult>, RunError<THostError>> {
+    let mut inputs = inputs.into_iter();
+    let Some(first_input) = inputs.next() else {
+        return Ok(vec![]);
+    };
+
+    let execution_data =
+        get_component_execution_data_for_callout(handle, first_input, execution_context)?;
+
+    if !execution_data
+        .context
+        .component_definition
+        .batchable
+        .unwrap_or(false)
+    {
+        let mut results = Vec::with_capacity(inputs.len() + 1);
+        results.push(run_callout_execution_data(&execution_data, handle).await?);
+        for input in inputs {
+            results.push(run_component_callout_inner(handle, input, execution_context).await?);
+        }
+        return Ok(results);
+    }
+
+    let component_definition = Arc::clone(&execution_data.context.component_definition);
+    let inputs: Vec<serde_json::Value> = std::iter::once(execution_data.input.value.clone())
+        .chain(inputs.map(|mut input| {
+            apply_input_defaults(&component_definition.input, &mut input);
+            input
+        }))
+        .collect();
+
+    for input in inputs.iter() {
+        validate_component_io(
+            ValidationData::Input(input),
+            Arc::clone(&execution_data.context.component_definition),
+            handle,
+        )?;
+    }
+
+    let mut results = run_component_batch_inner(&execution_data, &inputs).await?;
+
+    for result in results.iter_mut() {
+        process_callout_output(&execution_data, handle, result)?;
+    }
+
+    Ok(results)
+}
+
+/// Coerces and validates the output of a callout.
+#[allow(clippy::result_large_err)] // Ignoring this. Will fix once https://github.com/rust-lang/rust/issues/87121 is stable.
+fn process_callout_output<THostError>(
+    execution_data: &ComponentExecutionData<'_, '_, '_>,
+    handle: &ComponentHandle,
+    result: &mut RunComponentResult,
+) -> Result<(), RunError<THostError>> {
+    if execution_data.context.rig_session_options.coerce_outputs {
+        coerce_component_output(
+            &execution_data.context.component_definition.output,
+            &mut result.output,
+        );
+    }
 
     validate_component_io(
         ValidationData::Output(&result.output),
@@ -151,7 +513,7 @@ pub async fn run_component_callout<THostError>(
         handle,
     )?;
 
-    Ok(result)
+    Ok(())
 }
 
 async fn run_component_inner<THostError>(
@@ -160,11 +522,13 @@ async fn run_component_inner<THostError>(
     const RUN_RESULT_KEY: &str = "run";
 
     let handle = get_handle_for_instrumentation(execution_data);
+    let _permit = execution_data.context.concurrency_permit.acquire().await;
 
     // Execute each component runner in order, feeding the output of the previous one
     // into the next one alongside the original input.
     // If a runner returns `CannotRun`, we skip it and move to the next one.
     let mut results: Vec<RunComponentResult> = vec![];
+    let mut cannot_run_reasons: Vec<CannotRunReason> = vec![];
     for runner in execution_data.context.component_runners {
         let input = match results.last() {
             Some(result) => {
@@ -175,29 +539,215 @@ async fn run_component_inner<THostError>(
             None => Cow::Borrowed(&execution_data.input.value),
         };
 
+        let result = run_instrumented(
+            runner.as_ref(),
+            input.as_ref(),
+            &execution_data.context,
+            &handle,
+        )
+        .await
+        .map_err(|e| RunError::RunComponentFailed {
+            component_handle: execution_data.context.component_handle().clone(),
+            component_runner: runner.identifier(),
+            error: e,
+        })?;
+
+        match result {
+            TryRunComponentResult::Ran { result } => results.push(result),
+            TryRunComponentResult::CannotRun { reason } => {
+                cannot_run_reasons.push(CannotRunReason {
+                    component_runner: runner.identifier(),
+                    reason,
+                })
+            }
+        }
+    }
+
+    if results.is_empty() {
+        return Err(RunError::ComponentRunnerNotFound {
+            component_handle: execution_data.context.component_handle().clone(),
+            reasons: cannot_run_reasons,
+        });
+    }
+
+    Ok(get_run_component_result(results))
+}
+
+/// The batch equivalent of `run_component_inner`, where each runner is given every input
+/// at once rather than being called once per input.
+async fn run_component_batch_inner<THostError>(
+    execution_data: &ComponentExecutionData<'_, '_, '_>,
+    inputs: &[serde_json::Value],
+) -> Result<Vec<RunComponentResult>, RunError<THostError>> {
+    const RUN_RESULT_KEY: &str = "run";
+
+    let handle = get_handle_for_instrumentation(execution_data);
+    let _permit = execution_data.context.concurrency_permit.acquire().await;
+
+    let mut runner_results: Vec<Vec<RunComponentResult>> = vec![];
+    let mut cannot_run_reasons: Vec<CannotRunReason> = vec![];
+    for runner in execution_data.context.component_runners {
+        let runner_inputs = match runner_results.last() {
+            Some(results) => Cow::Owned(
+                inputs
+                    .iter()
+                    .zip(results)
+                    .map(|(input, result)| {
+                        let mut input = input.clone();
+                        input[RUN_RESULT_KEY] = result.output.clone();
+                        input
+                    })
+                    .collect(),
+            ),
+            None => Cow::Borrowed(inputs),
+        };
+
+        execution_data.context.take_permission_denial();
+
         let result = runner
-            .run(input.as_ref(), &execution_data.context)
+            .run_batch(runner_inputs.as_ref(), &execution_data.context)
             .instrument(info_span!("component", ""=%handle))
-            .await
-            .map_err(|e| RunError::RunComponentFailed {
+            .await;
+        let result = with_permission_denial(result, &execution_data.context).map_err(|e| {
+            RunError::RunComponentFailed {
                 component_handle: execution_data.context.component_handle().clone(),
                 component_runner: runner.identifier(),
                 error: e,
-            })?;
+            }
+        })?;
 
         match result {
-            TryRunComponentResult::Ran { result } => results.push(result),
-            TryRunComponentResult::CannotRun => {}
+            TryRunBatchComponentResult::Ran { results } => {
+                if results.len() != inputs.len() {
+                    return Err(RunError::RunComponentFailed {
+                        component_handle: execution_data.context.component_handle().clone(),
+                        component_runner: runner.identifier(),
+                        error: RunComponentError::Other(format!(
+                            "Expected {} batch results but the runner returned {}.",
+                            inputs.len(),
+                            results.len()
+                        )),
+                    });
+                }
+                runner_results.push(results)
+            }
+            TryRunBatchComponentResult::CannotRun { reason } => {
+                cannot_run_reasons.push(CannotRunReason {
+                    component_runner: runner.identifier(),
+                    reason,
+                })
+            }
         }
     }
 
-    if results.is_empty() {
+    if runner_results.is_empty() {
         return Err(RunError::ComponentRunnerNotFound {
             component_handle: execution_data.context.component_handle().clone(),
+            reasons: cannot_run_reasons,
         });
     }
 
-    Ok(get_run_component_result(results))
+    Ok(get_batch_run_component_results(runner_results))
+}
+
+/// Runs the component using only the requested runner, rather than every runner
+/// which is able to run it.
+async fn run_component_with_runner<THostError>(
+    execution_data: &ComponentExecutionData<'_, '_, '_>,
+    requested_runner: &str,
+) -> Result<RunComponentResult, RunError<THostError>> {
+    let handle = get_handle_for_instrumentation(execution_data);
+
+    let Some(runner) = execution_data
+        .context
+        .component_runners
+        .iter()
+        .find(|r| r.identifier() == requested_runner)
+    else {
+        return Err(RunError::RequestedComponentRunnerNotFound {
+            component_handle: execution_data.context.component_handle().clone(),
+            component_runner: requested_runner.to_string(),
+        });
+    };
+
+    let _permit = execution_data.context.concurrency_permit.acquire().await;
+    let result = run_instrumented(
+        runner.as_ref(),
+        &execution_data.input.value,
+        &execution_data.context,
+        &handle,
+    )
+    .await
+    .map_err(|e| RunError::RunComponentFailed {
+        component_handle: execution_data.context.component_handle().clone(),
+        component_runner: runner.identifier(),
+        error: e,
+    })?;
+
+    match result {
+        TryRunComponentResult::Ran { result } => Ok(result),
+        TryRunComponentResult::CannotRun { reason: _ } => {
+            Err(RunError::RequestedComponentRunnerCannotRun {
+                component_handle: execution_data.context.component_handle().clone(),
+                component_runner: requested_runner.to_string(),
+            })
+        }
+    }
+}
+
+/// Runs the component within its tracing span, logging the input, output and duration
+/// at the trace level so that a single misbehaving component can be inspected in detail.
+async fn run_instrumented(
+    runner: &dyn ComponentRunner,
+    input: &serde_json::Value,
+    context: &ComponentExecutionContext<'_, '_, '_>,
+    handle: &str,
+) -> Result<TryRunComponentResult, RunComponentError> {
+    async {
+        let runner_identifier = runner.identifier();
+        trace!("Running component with the {runner_identifier} runner. Input: {input}");
+
+        context.take_permission_denial();
+
+        let start = Instant::now();
+        let result = with_permission_denial(runner.run(input, context).await, context);
+        let elapsed = start.elapsed();
+
+        match &result {
+            Ok(TryRunComponentResult::Ran { result }) => {
+                trace!("Component ran in {elapsed:?}. Output: {}", result.output)
+            }
+            Ok(TryRunComponentResult::CannotRun { reason }) => trace!(
+                "The {runner_identifier} runner cannot run the component: {}",
+                reason.as_deref().unwrap_or("No reason given.")
+            ),
+            Err(e) => trace!("Component failed after {elapsed:?}: {e}"),
+        }
+
+        result
+    }
+    .instrument(info_span!("component", ""=%handle))
+    .await
+}
+
+/// Replaces the error returned by a component with a `PermissionDenied` error if one
+/// of its host calls was denied, as the denial is the likely cause of the failure.
+fn with_permission_denial<T>(
+    result: Result<T, RunComponentError>,
+    context: &ComponentExecutionContext<'_, '_, '_>,
+) -> Result<T, RunComponentError> {
+    match result {
+        Err(RunComponentError::RunCallReturnedError { message, inner }) => {
+            match context.take_permission_denial() {
+                Some(denial) => Err(RunComponentError::PermissionDenied {
+                    message: denial.message,
+                    inner: denial.inner,
+                }),
+                None => Err(RunComponentError::RunCallReturnedError { message, inner }),
+            }
+        }
+        result => result,
+    }
 }
 
 fn get_handle_for_instrumentation(execution_data: &ComponentExecutionData<'_, '_, '_>) -> String {
@@ -219,6 +769,9 @@ fn get_handle_for_instrumentation(execution_data: &ComponentExecutionData<'_, '_
         SlipwayReference::Http { url: _ } => {
             format!("{handle}:http")
         }
+        SlipwayReference::Git { url: _, rev: _ } => {
+            format!("{handle}:git")
+        }
     }
 }
 
@@ -236,6 +789,27 @@ fn get_run_component_result(results: Vec<RunComponentResult>) -> RunComponentRes
     }
 }
 
+/// Combines the batch results of each runner into a single result per input.
+fn get_batch_run_component_results(
+    runner_results: Vec<Vec<RunComponentResult>>,
+) -> Vec<RunComponentResult> {
+    let input_count = runner_results.first().map_or(0, Vec::len);
+    let mut per_input: Vec<Vec<RunComponentResult>> = (0..input_count)
+        .map(|_| Vec::with_capacity(runner_results.len()))
+        .collect();
+
+    for results in runner_results {
+        for (input_results, result) in per_input.iter_mut().zip(results) {
+            input_results.push(result);
+        }
+    }
+
+    per_input
+        .into_iter()
+        .map(get_run_component_result)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -251,6 +825,8 @@ mod tests {
                 prepare_component_duration: Duration::from_secs(2),
                 call_duration: Duration::from_secs(3),
                 process_output_duration: Duration::from_secs(4),
+                nested: None,
+                memoized_from: None,
             },
         };
 
@@ -261,6 +837,8 @@ mod tests {
                 prepare_component_duration: Duration::from_secs(6),
                 call_duration: Duration::from_secs(7),
                 process_output_duration: Duration::from_secs(8),
+                nested: None,
+                memoized_from: None,
             },
         };
 
@@ -274,7 +852,59 @@ mod tests {
                 prepare_component_duration: Duration::from_secs(8),
                 call_duration: Duration::from_secs(10),
                 process_output_duration: Duration::from_secs(12),
+                nested: None,
+                memoized_from: None,
             }
         );
     }
+
+    #[test]
+    fn get_batch_run_component_results_should_combine_results_per_input() {
+        fn result(value: u32, call_seconds: u64) -> RunComponentResult {
+            RunComponentResult {
+                output: serde_json::json!({ "value": value }),
+                metadata: RunMetadata {
+                    call_duration: Duration::from_secs(call_seconds),
+                    ..Default::default()
+                },
+            }
+        }
+
+        let combined = get_batch_run_component_results(vec![
+            vec![result(1, 1), result(2, 2)],
+            vec![result(10, 3), result(20, 4)],
+        ]);
+
+        assert_eq!(combined.len(), 2);
+        assert_eq!(combined[0].output, serde_json::json!({ "value": 10 }));
+        assert_eq!(combined[0].metadata.call_duration, Duration::from_secs(4));
+        assert_eq!(combined[1].output, serde_json::json!({ "value": 20 }));
+        assert_eq!(combined[1].metadata.call_duration, Duration::from_secs(6));
+    }
+
+    #[test]
+    fn it_should_list_why_each_runner_could_not_run_component() {
+        let error = RunError::<()>::ComponentRunnerNotFound {
+            component_handle: crate::utils::ch("test"),
+            reasons: vec![
+                CannotRunReason {
+                    component_runner: "wasmtime".to_string(),
+                    reason: Some("No \"run.wasm\" file found.".to_string()),
+                },
+                CannotRunReason {
+                    component_runner: "custom".to_string(),
+                    reason: None,
+                },
+            ],
+        };
+
+        assert_eq!(
+            error.to_string(),
+            concat!(
+                "No component runner was found for component \"test\".\n",
+                "  wasmtime: No \"run.wasm\" file found.\n",
+                "  custom: No reason given."
+            )
+        );
+    }
 }
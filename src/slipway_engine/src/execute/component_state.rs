@@ -1,7 +1,7 @@
 use super::primitives::Hash;
 use std::{collections::HashSet, sync::Arc};
 
-use crate::{ComponentHandle, ComponentRigging, JsonMetadata, RunMetadata};
+use crate::{ComponentHandle, ComponentRigging, InputProvenance, JsonMetadata, RunMetadata};
 
 // NOTE: The Arcs in this struct can actually be Rc, because we run in a single
 // threaded async runtime. However, because Wasmtime requires Send we are using
@@ -23,8 +23,20 @@ pub struct ComponentState<'rig> {
     /// When a component is ready to be executed, this will contain the input with all references resolved.
     pub execution_input: Option<Arc<ComponentInput>>,
 
+    /// Where each value in the `execution_input` resolved from, if the session
+    /// options enable recording input provenance.
+    pub input_provenance: Option<Arc<Vec<InputProvenance>>>,
+
+    /// Whether the resolved input meets the rigging's skip condition, in which case
+    /// the component is not run and its output is `null`.
+    pub execution_skipped: bool,
+
     /// The output of the component after it has been executed.
     pub execution_output: Option<Arc<ComponentOutput>>,
+
+    /// The reason the component failed to run, if it failed.
+    /// Components which depend on a failed component never become ready to execute.
+    pub execution_failure: Option<Arc<ComponentFailure>>,
 }
 
 impl ComponentState<'_> {
@@ -51,6 +63,10 @@ impl ComponentState<'_> {
 pub struct ComponentInput {
     pub value: serde_json::Value,
     pub json_metadata: JsonMetadata,
+
+    /// The JSON pointers of the input properties which were populated from
+    /// defaults declared by the component's input schema.
+    pub defaulted: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -66,6 +82,12 @@ pub struct ComponentOutput {
     pub run_metadata: RunMetadata,
 }
 
+#[derive(Debug)]
+pub struct ComponentFailure {
+    pub message: String,
+    pub input_hash_used: Hash,
+}
+
 #[derive(Debug)]
 pub struct ComponentOutputOverride {
     pub value: serde_json::Value,
@@ -0,0 +1,170 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Bounds the number of components which can execute concurrently across a rig,
+/// including any callouts and fragments they run.
+///
+/// Cloning the limit shares the underlying permits, so nested rig sessions created
+/// from the same options are bounded by the same limit.
+#[derive(Debug, Clone)]
+pub struct ComponentConcurrencyLimit {
+    max_concurrent_components: usize,
+    semaphore: Arc<Semaphore>,
+}
+
+impl ComponentConcurrencyLimit {
+    pub fn new(max_concurrent_components: usize) -> Self {
+        assert!(
+            max_concurrent_components > 0,
+            "max_concurrent_components must be greater than zero"
+        );
+
+        ComponentConcurrencyLimit {
+            max_concurrent_components,
+            semaphore: Arc::new(Semaphore::new(max_concurrent_components)),
+        }
+    }
+
+    pub fn max_concurrent_components(&self) -> usize {
+        self.max_concurrent_components
+    }
+
+    pub fn available_permits(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    async fn acquire(&self) -> OwnedSemaphorePermit {
+        Arc::clone(&self.semaphore)
+            .acquire_owned()
+            .await
+            .expect("component concurrency semaphore should never be closed")
+    }
+}
+
+/// The permit held by a single executing component.
+///
+/// The permit is released while the component waits on nested components, such as
+/// callouts or fragment rigs, so that a parent can never deadlock waiting for
+/// a child which is itself waiting for a permit.
+#[derive(Debug)]
+pub(crate) struct ComponentConcurrencyPermit {
+    limit: Option<ComponentConcurrencyLimit>,
+    permit: Mutex<Option<OwnedSemaphorePermit>>,
+}
+
+impl ComponentConcurrencyPermit {
+    pub(crate) fn new(limit: Option<ComponentConcurrencyLimit>) -> Self {
+        ComponentConcurrencyPermit {
+            limit,
+            permit: Mutex::new(None),
+        }
+    }
+
+    /// Waits for a permit, queueing if the limit has been reached,
+    /// and holds it until the returned guard is dropped.
+    pub(crate) async fn acquire(&self) -> ComponentConcurrencyPermitGuard<'_> {
+        if let Some(limit) = &self.limit {
+            let permit = limit.acquire().await;
+            *self.lock() = Some(permit);
+        }
+
+        ComponentConcurrencyPermitGuard { owner: self }
+    }
+
+    /// Runs the future with the permit released, re-acquiring it afterwards.
+    pub(crate) async fn release_while<F: Future>(&self, future: F) -> F::Output {
+        let released = self.lock().take();
+
+        let Some(released) = released else {
+            return future.await;
+        };
+
+        drop(released);
+        let output = future.await;
+
+        let limit = self
+            .limit
+            .as_ref()
+            .expect("limit should exist if a permit was held");
+        let permit = limit.acquire().await;
+        *self.lock() = Some(permit);
+
+        output
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<OwnedSemaphorePermit>> {
+        self.permit
+            .lock()
+            .expect("component concurrency permit lock should not be poisoned")
+    }
+}
+
+/// Releases the component's permit when dropped.
+pub(crate) struct ComponentConcurrencyPermitGuard<'permit> {
+    owner: &'permit ComponentConcurrencyPermit,
+}
+
+impl Drop for ComponentConcurrencyPermitGuard<'_> {
+    fn drop(&mut self) {
+        self.owner.lock().take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+    use futures::FutureExt;
+
+    use super::*;
+
+    #[slipway_test_async]
+    async fn it_should_queue_when_limit_is_reached() {
+        let limit = ComponentConcurrencyLimit::new(1);
+        let first = ComponentConcurrencyPermit::new(Some(limit.clone()));
+        let second = ComponentConcurrencyPermit::new(Some(limit.clone()));
+
+        let first_guard = first.acquire().await;
+        assert_eq!(limit.available_permits(), 0);
+
+        assert!(second.acquire().now_or_never().is_none());
+
+        drop(first_guard);
+        assert_eq!(limit.available_permits(), 1);
+
+        let _second_guard = second.acquire().await;
+        assert_eq!(limit.available_permits(), 0);
+    }
+
+    #[slipway_test_async]
+    async fn it_should_release_permit_while_waiting_on_nested_components() {
+        let limit = ComponentConcurrencyLimit::new(1);
+        let parent = ComponentConcurrencyPermit::new(Some(limit.clone()));
+        let child = ComponentConcurrencyPermit::new(Some(limit.clone()));
+
+        let _parent_guard = parent.acquire().await;
+
+        let child_ran = parent
+            .release_while(async {
+                let _child_guard = child.acquire().await;
+                true
+            })
+            .await;
+
+        assert!(child_ran);
+        assert_eq!(limit.available_permits(), 0);
+        assert!(parent.lock().is_some());
+    }
+
+    #[slipway_test_async]
+    async fn it_should_not_limit_when_no_limit_is_set() {
+        let permit = ComponentConcurrencyPermit::new(None);
+
+        let _guard = permit.acquire().await;
+
+        assert!(permit.lock().is_none());
+    }
+}
@@ -4,10 +4,15 @@ use serde_json::json;
 
 use jsonpath_rust::{JsonPath, JsonPathValue};
 
-use crate::{ComponentHandle, ComponentInput, errors::RigError, execute::primitives::JsonMetadata};
+use crate::{
+    ComponentHandle, ComponentInput,
+    errors::RigError,
+    execute::primitives::{HashAlgorithm, JsonMetadata},
+};
 
 use super::{
     find_json_path_strings::{FoundJsonPathString, PathType},
+    find_template_strings::{FoundTemplateString, TemplatePart},
     simple_json_path::JsonPathOperations,
 };
 
@@ -18,6 +23,7 @@ use super::{
 /// The input is the input to the current component, as defined by the rig, which still contains
 /// the JSON Path strings.
 /// The json_path_strings are the JSON Path strings that were found in the input.
+/// The template_strings are the strings in the input which interpolate JSON Path strings.
 /// The function returns the evaluated input, which is the supplied input with the JSON Path strings
 /// replaced with the values from the serialized rig state.
 pub(super) fn evaluate_input(
@@ -25,69 +31,51 @@ pub(super) fn evaluate_input(
     serialized_rig_state: &serde_json::Value,
     input: Option<&serde_json::Value>,
     json_path_strings: &Vec<FoundJsonPathString>,
+    template_strings: &[FoundTemplateString],
+    hash_algorithm: HashAlgorithm,
 ) -> Result<ComponentInput, RigError> {
     let evaluated_input = match input {
         Some(input) => {
             let mut evaluated_input = input.clone();
 
+            // Templates are rendered first, as they never remove values and so the
+            // paths to the JSON Path strings remain valid.
+            for template in template_strings {
+                let rendered =
+                    render_template_string(component_handle, serialized_rig_state, template)?;
+
+                template.path_to.replace(
+                    &mut evaluated_input,
+                    Some(serde_json::Value::String(rendered)),
+                )?;
+            }
+
             // Important: We must evaluate the JSON Path strings in reverse,
             // as we potentially remove items from arrays as we go.
             for found in json_path_strings.iter().rev() {
-                let path = JsonPath::from_str(&found.path).map_err(|e| {
-                    RigError::InvalidJsonPathExpression {
-                        location: found.path_to.to_json_path_string(),
-                        error: e,
-                    }
-                })?;
-
-                let result = path.find_slice(serialized_rig_state);
-
-                let extracted_result = match found.path_type {
-                    PathType::Array => Some(serde_json::Value::Array(
-                        result
-                            .into_iter()
-                            .filter_map(map_json_ptr_to_value)
-                            .collect(),
-                    )),
-                    PathType::OptionalValue => {
-                        result.into_iter().filter_map(map_json_ptr_to_value).next()
-                    }
-                    PathType::RequiredValue => Some(
-                        result
-                            .into_iter()
-                            .filter_map(map_json_ptr_to_value)
-                            .next()
-                            .ok_or(RigError::ResolveJsonPathFailed {
-                                message: format!(
-                                    r#"The input path "{}" required "{}" to be a value"#,
-                                    found.path_to.to_prefixed_path_string(
-                                        &(component_handle.to_string() + ".input")
-                                    ),
-                                    found.path
-                                ),
-                                state: serialized_rig_state.clone(),
-                            })?,
-                    ),
-                };
+                let extracted_result =
+                    evaluate_json_path_string(component_handle, serialized_rig_state, found)?;
 
                 found
                     .path_to
                     .replace(&mut evaluated_input, extracted_result)?;
             }
 
-            let json_metadata = JsonMetadata::from_value(&evaluated_input);
+            let json_metadata = JsonMetadata::from_value(&evaluated_input, hash_algorithm);
 
             ComponentInput {
                 value: evaluated_input,
                 json_metadata,
+                defaulted: vec![],
             }
         }
         None => {
             let input_value = json!({});
-            let json_metadata = JsonMetadata::from_value(&input_value);
+            let json_metadata = JsonMetadata::from_value(&input_value, hash_algorithm);
             ComponentInput {
                 value: input_value,
                 json_metadata,
+                defaulted: vec![],
             }
         }
     };
@@ -95,6 +83,78 @@ pub(super) fn evaluate_input(
     Ok(evaluated_input)
 }
 
+/// Evaluates a single JSON Path string against the serialized rig state, returning
+/// `None` if an optional value was not found.
+pub(super) fn evaluate_json_path_string(
+    component_handle: &ComponentHandle,
+    serialized_rig_state: &serde_json::Value,
+    found: &FoundJsonPathString,
+) -> Result<Option<serde_json::Value>, RigError> {
+    let path =
+        JsonPath::from_str(&found.path).map_err(|e| RigError::InvalidJsonPathExpression {
+            location: found.path_to.to_json_path_string(),
+            error: e,
+        })?;
+
+    let result = path.find_slice(serialized_rig_state);
+
+    let extracted_result = match found.path_type {
+        PathType::Array => Some(serde_json::Value::Array(
+            result
+                .into_iter()
+                .filter_map(map_json_ptr_to_value)
+                .collect(),
+        )),
+        PathType::OptionalValue => result.into_iter().filter_map(map_json_ptr_to_value).next(),
+        PathType::RequiredValue => Some(
+            result
+                .into_iter()
+                .filter_map(map_json_ptr_to_value)
+                .next()
+                .ok_or(RigError::ResolveJsonPathFailed {
+                    message: format!(
+                        r#"The input path "{}" required "{}" to be a value"#,
+                        found
+                            .path_to
+                            .to_prefixed_path_string(&(component_handle.to_string() + ".input")),
+                        found.path
+                    ),
+                    state: serialized_rig_state.clone(),
+                })?,
+        ),
+    };
+
+    Ok(extracted_result)
+}
+
+/// Renders a template string by interpolating each reference.
+///
+/// Strings are inserted as they are, and other values are inserted as JSON.
+/// Optional references which are not found are replaced with an empty string,
+/// and required references which are not found are an error.
+fn render_template_string(
+    component_handle: &ComponentHandle,
+    serialized_rig_state: &serde_json::Value,
+    template: &FoundTemplateString,
+) -> Result<String, RigError> {
+    let mut rendered = String::new();
+
+    for part in template.parts.iter() {
+        match part {
+            TemplatePart::Literal(literal) => rendered.push_str(literal),
+            TemplatePart::Reference { found, .. } => {
+                match evaluate_json_path_string(component_handle, serialized_rig_state, found)? {
+                    None => {}
+                    Some(serde_json::Value::String(s)) => rendered.push_str(&s),
+                    Some(value) => rendered.push_str(&value.to_string()),
+                }
+            }
+        }
+    }
+
+    Ok(rendered)
+}
+
 fn map_json_ptr_to_value(v: JsonPathValue<'_, serde_json::Value>) -> Option<serde_json::Value> {
     match v {
         JsonPathValue::NewValue(v) => Some(v),
@@ -144,6 +204,8 @@ mod tests {
             &serialized_rig_state,
             Some(&input),
             &json_path_strings,
+            &[],
+            HashAlgorithm::default(),
         )
         .unwrap();
 
@@ -190,6 +252,8 @@ mod tests {
             &serialized_rig_state,
             Some(&input),
             &json_path_strings,
+            &[],
+            HashAlgorithm::default(),
         );
 
         assert!(maybe_execution_input.is_err());
@@ -235,6 +299,8 @@ mod tests {
             &serialized_rig_state,
             Some(&input),
             &json_path_strings,
+            &[],
+            HashAlgorithm::default(),
         )
         .unwrap();
 
@@ -286,6 +352,8 @@ mod tests {
             &serialized_rig_state,
             Some(&input),
             &json_path_strings,
+            &[],
+            HashAlgorithm::default(),
         )
         .unwrap();
 
@@ -336,6 +404,8 @@ mod tests {
             &serialized_rig_state,
             Some(&input),
             &json_path_strings,
+            &[],
+            HashAlgorithm::default(),
         )
         .unwrap();
 
@@ -353,4 +423,84 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn it_should_interpolate_template_strings() {
+        let serialized_rig_state = json!({
+            "constants": {
+                "host": "example.com",
+            },
+            "rigging": {
+                "component_a": {
+                    "output": {
+                        "version": 2,
+                        "tags": ["a", "b"],
+                    }
+                }
+            }
+        });
+
+        let input = json!({
+            "url": "https://{{$.constants.host}}/v{{$$.component_a.version}}",
+            "tags": "tags={{$$.component_a.tags}}",
+            "missing": "[{{$?constants.missing}}]",
+            "array": [
+                "$?constants.missing",
+                "{{$.constants.host}}",
+            ],
+            "escaped": "\\{{$.constants.host}}",
+        });
+
+        let json_path_strings =
+            super::super::find_json_path_strings::find_json_path_strings(&input);
+        let template_strings = super::super::find_template_strings::find_template_strings(&input);
+
+        let execution_input = evaluate_input(
+            &ch("test"),
+            &serialized_rig_state,
+            Some(&input),
+            &json_path_strings,
+            &template_strings,
+            HashAlgorithm::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            execution_input.value,
+            json!({
+                "url": "https://example.com/v2",
+                "tags": "tags=[\"a\",\"b\"]",
+                "missing": "[]",
+                "array": ["example.com"],
+                "escaped": "{{$.constants.host}}",
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_fail_if_required_template_reference_is_not_found() {
+        let serialized_rig_state = json!({
+            "constants": {}
+        });
+
+        let input = json!({
+            "url": "https://{{$.constants.host}}/v1",
+        });
+
+        let template_strings = super::super::find_template_strings::find_template_strings(&input);
+
+        let result = evaluate_input(
+            &ch("test"),
+            &serialized_rig_state,
+            Some(&input),
+            &vec![],
+            &template_strings,
+            HashAlgorithm::default(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(RigError::ResolveJsonPathFailed { .. })
+        ));
+    }
 }
@@ -5,7 +5,9 @@ use std::sync::LazyLock;
 
 use crate::{errors::RigError, parse::types::primitives::ComponentHandle};
 
-use super::find_json_path_strings::FoundJsonPathString;
+use super::{
+    find_json_path_strings::FoundJsonPathString, find_template_strings::FoundTemplateString,
+};
 
 /// This regex matches any JSON path string that references either the output
 /// or input of a component.
@@ -27,9 +29,7 @@ impl ExtractDependencies for Vec<FoundJsonPathString<'_>> {
         let mut result = HashSet::new();
 
         for found in self {
-            if let Some(captures) = COMPONENT_DEPENDENCY_REGEX.captures(&found.path) {
-                let component_handle = &captures["component_handle"];
-                let component_handle = ComponentHandle::from_str(component_handle)?;
+            if let Some(component_handle) = get_dependency(found)? {
                 result.insert(component_handle);
             }
         }
@@ -37,6 +37,32 @@ impl ExtractDependencies for Vec<FoundJsonPathString<'_>> {
         Ok(result)
     }
 }
+
+impl ExtractDependencies for Vec<FoundTemplateString<'_>> {
+    fn extract_dependencies(&self) -> Result<HashSet<ComponentHandle>, RigError> {
+        let mut result = HashSet::new();
+
+        for (_, found) in self.iter().flat_map(|template| template.references()) {
+            if let Some(component_handle) = get_dependency(found)? {
+                result.insert(component_handle);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Returns the component referenced by the JSON path string, if any.
+pub(super) fn get_dependency(
+    found: &FoundJsonPathString<'_>,
+) -> Result<Option<ComponentHandle>, RigError> {
+    match COMPONENT_DEPENDENCY_REGEX.captures(&found.path) {
+        Some(captures) => Ok(Some(ComponentHandle::from_str(
+            &captures["component_handle"],
+        )?)),
+        None => Ok(None),
+    }
+}
 #[cfg(test)]
 mod tests {
     use std::borrow::Cow;
@@ -67,46 +67,11 @@ fn find_json_path_strings_inner<'a>(
             }
         }
         Value::String(s) => {
-            let maybe_path: Option<(Cow<'_, str>, PathType)> =
-                if let Some(captures) = COMPONENT_OUTPUT_SHORTCUT_REGEX.captures(s) {
-                    // The string uses the $$ shortcut, so we need to transform it into a proper
-                    // JSON path.
-                    let component_handle = &captures["component_handle"];
-                    let rest = &captures["rest"];
-
-                    let new_path =
-                        Cow::Owned("$.rigging.".to_string() + component_handle + ".output" + rest);
-
-                    let value_specifier = &captures["value_specifier"];
-
-                    let path_type = match value_specifier {
-                        REQUIRED_VALUE_CHAR => PathType::RequiredValue,
-                        OPTIONAL_VALUE_CHAR => PathType::OptionalValue,
-                        ARRAY_VALUE_CHAR => PathType::Array,
-                        _ => unreachable!(),
-                    };
-
-                    Some((new_path, path_type))
-                } else if s.starts_with(REQUIRED_VALUE_PREFIX) {
-                    // The string is already a valid JSON path.
-                    Some((Cow::Borrowed(s), PathType::RequiredValue))
-                } else if let Some(rest) = s.strip_prefix(OPTIONAL_VALUE_PREFIX) {
-                    // The string uses the $? custom prefix to indicate they want an optional single value result.
-                    let new_path = Cow::Owned(JSON_QUERY_PREFIX.to_string() + rest);
-                    Some((new_path, PathType::OptionalValue))
-                } else if let Some(rest) = s.strip_prefix(ARRAY_VALUE_PREFIX) {
-                    // The string uses the $* custom prefix to indicate they require an array result.
-                    let new_path = Cow::Owned(JSON_QUERY_PREFIX.to_string() + rest);
-                    Some((new_path, PathType::Array))
-                } else {
-                    None
-                };
-
-            if let Some(path) = maybe_path {
+            if let Some((path, path_type)) = parse_json_path_string(s) {
                 let result = FoundJsonPathString {
                     path_to: current_path.clone(),
-                    path: path.0,
-                    path_type: path.1,
+                    path,
+                    path_type,
                 };
                 results.push(result);
             }
@@ -115,6 +80,44 @@ fn find_json_path_strings_inner<'a>(
     }
 }
 
+/// Parses a string as a JSON path, returning the path to evaluate against the
+/// serialized rig state and the type of result required, or `None` if the string
+/// is not a JSON path.
+pub(super) fn parse_json_path_string(s: &str) -> Option<(Cow<'_, str>, PathType)> {
+    if let Some(captures) = COMPONENT_OUTPUT_SHORTCUT_REGEX.captures(s) {
+        // The string uses the $$ shortcut, so we need to transform it into a proper
+        // JSON path.
+        let component_handle = &captures["component_handle"];
+        let rest = &captures["rest"];
+
+        let new_path = Cow::Owned("$.rigging.".to_string() + component_handle + ".output" + rest);
+
+        let value_specifier = &captures["value_specifier"];
+
+        let path_type = match value_specifier {
+            REQUIRED_VALUE_CHAR => PathType::RequiredValue,
+            OPTIONAL_VALUE_CHAR => PathType::OptionalValue,
+            ARRAY_VALUE_CHAR => PathType::Array,
+            _ => unreachable!(),
+        };
+
+        Some((new_path, path_type))
+    } else if s.starts_with(REQUIRED_VALUE_PREFIX) {
+        // The string is already a valid JSON path.
+        Some((Cow::Borrowed(s), PathType::RequiredValue))
+    } else if let Some(rest) = s.strip_prefix(OPTIONAL_VALUE_PREFIX) {
+        // The string uses the $? custom prefix to indicate they want an optional single value result.
+        let new_path = Cow::Owned(JSON_QUERY_PREFIX.to_string() + rest);
+        Some((new_path, PathType::OptionalValue))
+    } else if let Some(rest) = s.strip_prefix(ARRAY_VALUE_PREFIX) {
+        // The string uses the $* custom prefix to indicate they require an array result.
+        let new_path = Cow::Owned(JSON_QUERY_PREFIX.to_string() + rest);
+        Some((new_path, PathType::Array))
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,332 @@
+use serde_json::Value;
+
+use crate::Rig;
+
+use super::{
+    find_json_path_strings::{FoundJsonPathString, parse_json_path_string},
+    simple_json_path::SimpleJsonPath,
+};
+
+const TEMPLATE_START: &str = "{{";
+const TEMPLATE_END: &str = "}}";
+const ESCAPE_CHAR: char = '\\';
+
+/// A string in a component input which interpolates JSON path references,
+/// for example `"https://{{$.constants.host}}/v1"`.
+///
+/// Templates are only found when the rig enables them.
+/// A reference is any JSON path string, such as `$$.component.value`, between `{{` and `}}`.
+/// Any other text between braces is left unchanged.
+///
+/// Backslashes escape a following `{{`: a run of backslashes immediately before `{{` is
+/// halved, and if the run has an odd length the `{{` is literal. So `\{{` is a literal `{{`,
+/// and `\\{{$.constants.a}}` is a literal `\` followed by a reference.
+/// Backslashes anywhere else are unchanged.
+#[derive(Eq, PartialEq, Debug)]
+pub(super) struct FoundTemplateString<'a> {
+    pub path_to: Vec<SimpleJsonPath<'a>>,
+    pub parts: Vec<TemplatePart<'a>>,
+}
+
+#[derive(Eq, PartialEq, Debug)]
+pub(super) enum TemplatePart<'a> {
+    Literal(String),
+    Reference {
+        /// The reference as written in the template, without the braces.
+        expression: &'a str,
+        found: FoundJsonPathString<'a>,
+    },
+}
+
+/// Returns whether the rig enables templates in component inputs.
+pub(super) fn templates_enabled(rig: &Rig) -> bool {
+    rig.templates.unwrap_or(false)
+}
+
+impl<'a> FoundTemplateString<'a> {
+    /// Returns the JSON path strings referenced by the template.
+    pub fn references(&self) -> impl Iterator<Item = (&'a str, &FoundJsonPathString<'a>)> {
+        self.parts.iter().filter_map(|part| match part {
+            TemplatePart::Literal(_) => None,
+            TemplatePart::Reference { expression, found } => Some((*expression, found)),
+        })
+    }
+}
+
+pub(super) fn find_template_strings(value: &Value) -> Vec<FoundTemplateString> {
+    let mut results = Vec::new();
+    let mut current_path = Vec::new();
+    find_template_strings_inner(value, &mut current_path, &mut results);
+    results
+}
+
+fn find_template_strings_inner<'a>(
+    value: &'a Value,
+    current_path: &mut Vec<SimpleJsonPath<'a>>,
+    results: &mut Vec<FoundTemplateString<'a>>,
+) {
+    match value {
+        Value::Object(map) => {
+            for (key, val) in map {
+                current_path.push(SimpleJsonPath::Field(key));
+                find_template_strings_inner(val, current_path, results);
+                current_path.pop();
+            }
+        }
+        Value::Array(arr) => {
+            for (index, val) in arr.iter().enumerate() {
+                current_path.push(SimpleJsonPath::Index(index));
+                find_template_strings_inner(val, current_path, results);
+                current_path.pop();
+            }
+        }
+        Value::String(s) => {
+            // Strings which are entirely a JSON path are replaced with the value, not interpolated.
+            if parse_json_path_string(s).is_some() {
+                return;
+            }
+
+            if let Some(parts) = parse_template(s, current_path) {
+                results.push(FoundTemplateString {
+                    path_to: current_path.clone(),
+                    parts,
+                });
+            }
+        }
+        _ => {} // For other types, do nothing.
+    }
+}
+
+/// Splits the string into literal text and references, returning `None` if the string
+/// contains neither references nor escapes, and so can be used unchanged.
+fn parse_template<'a>(
+    s: &'a str,
+    current_path: &[SimpleJsonPath<'a>],
+) -> Option<Vec<TemplatePart<'a>>> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut is_template = false;
+    let mut rest = s;
+
+    while let Some(start_index) = rest.find(TEMPLATE_START) {
+        let before = &rest[..start_index];
+        let after_start = &rest[start_index + TEMPLATE_START.len()..];
+
+        let unescaped = before.trim_end_matches(ESCAPE_CHAR);
+        let escape_count = before.len() - unescaped.len();
+
+        literal.push_str(unescaped);
+        literal.extend(std::iter::repeat_n(ESCAPE_CHAR, escape_count / 2));
+
+        if escape_count > 0 {
+            is_template = true;
+        }
+
+        if escape_count % 2 == 1 {
+            literal.push_str(TEMPLATE_START);
+            rest = after_start;
+            continue;
+        }
+
+        let reference = after_start.find(TEMPLATE_END).and_then(|end_index| {
+            let expression = after_start[..end_index].trim();
+            parse_json_path_string(expression)
+                .map(|(path, path_type)| (expression, path, path_type, end_index))
+        });
+
+        match reference {
+            Some((expression, path, path_type, end_index)) => {
+                if !literal.is_empty() {
+                    parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                }
+
+                parts.push(TemplatePart::Reference {
+                    expression,
+                    found: FoundJsonPathString {
+                        path_to: current_path.to_vec(),
+                        path,
+                        path_type,
+                    },
+                });
+
+                rest = &after_start[end_index + TEMPLATE_END.len()..];
+                is_template = true;
+            }
+            None => {
+                literal.push_str(TEMPLATE_START);
+                rest = after_start;
+            }
+        }
+    }
+
+    if !is_template {
+        return None;
+    }
+
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        parts.push(TemplatePart::Literal(literal));
+    }
+
+    Some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use serde_json::json;
+
+    use super::super::find_json_path_strings::PathType;
+
+    use super::*;
+
+    fn literal(s: &str) -> TemplatePart<'_> {
+        TemplatePart::Literal(s.to_string())
+    }
+
+    fn reference<'a>(
+        path_to: &[SimpleJsonPath<'a>],
+        expression: &'a str,
+        path: &'a str,
+        path_type: PathType,
+    ) -> TemplatePart<'a> {
+        TemplatePart::Reference {
+            expression,
+            found: FoundJsonPathString {
+                path_to: path_to.to_vec(),
+                path: Cow::Borrowed(path),
+                path_type,
+            },
+        }
+    }
+
+    #[test]
+    fn it_should_find_template_references() {
+        let value = json!({
+            "url": "https://{{$$.config.host}}/v1?page={{ $?constants.page }}",
+            "plain": "https://example.com",
+            "path": "$$.config.host",
+        });
+
+        let path_to = vec![SimpleJsonPath::Field("url")];
+
+        assert_eq!(
+            find_template_strings(&value),
+            vec![FoundTemplateString {
+                path_to: path_to.clone(),
+                parts: vec![
+                    literal("https://"),
+                    reference(
+                        &path_to,
+                        "$$.config.host",
+                        "$.rigging.config.output.host",
+                        PathType::RequiredValue
+                    ),
+                    literal("/v1?page="),
+                    reference(
+                        &path_to,
+                        "$?constants.page",
+                        "$.constants.page",
+                        PathType::OptionalValue
+                    ),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn it_should_find_template_references_in_arrays() {
+        let value = json!(["a", "{{$.constants.b}}"]);
+
+        let path_to = vec![SimpleJsonPath::Index(1)];
+
+        assert_eq!(
+            find_template_strings(&value),
+            vec![FoundTemplateString {
+                path_to: path_to.clone(),
+                parts: vec![reference(
+                    &path_to,
+                    "$.constants.b",
+                    "$.constants.b",
+                    PathType::RequiredValue
+                )],
+            }]
+        );
+    }
+
+    #[test]
+    fn it_should_ignore_braces_without_references() {
+        let value = json!({
+            "mustache": "Hello {{name}}",
+            "unclosed": "Hello {{$.constants.name",
+        });
+
+        assert!(find_template_strings(&value).is_empty());
+    }
+
+    #[test]
+    fn it_should_unescape_braces() {
+        let value = json!({
+            "escaped": "\\{{$.constants.a}} {{$.constants.b}}",
+            "escaped_only": "\\{{$.constants.a}}",
+        });
+
+        let escaped_path_to = vec![SimpleJsonPath::Field("escaped")];
+
+        assert_eq!(
+            find_template_strings(&value),
+            vec![
+                FoundTemplateString {
+                    path_to: escaped_path_to.clone(),
+                    parts: vec![
+                        literal("{{$.constants.a}} "),
+                        reference(
+                            &escaped_path_to,
+                            "$.constants.b",
+                            "$.constants.b",
+                            PathType::RequiredValue
+                        ),
+                    ],
+                },
+                FoundTemplateString {
+                    path_to: vec![SimpleJsonPath::Field("escaped_only")],
+                    parts: vec![literal("{{$.constants.a}}")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_unescape_escape_characters_before_braces() {
+        let value = json!({
+            "escaped_backslash": "a\\\\{{$.constants.a}}",
+            "escaped_both": "\\\\\\{{$.constants.a}}",
+            "unescaped_backslash": "a\\b {{name}}",
+        });
+
+        let escaped_backslash_path_to = vec![SimpleJsonPath::Field("escaped_backslash")];
+
+        assert_eq!(
+            find_template_strings(&value),
+            vec![
+                FoundTemplateString {
+                    path_to: escaped_backslash_path_to.clone(),
+                    parts: vec![
+                        literal("a\\"),
+                        reference(
+                            &escaped_backslash_path_to,
+                            "$.constants.a",
+                            "$.constants.a",
+                            PathType::RequiredValue
+                        ),
+                    ],
+                },
+                FoundTemplateString {
+                    path_to: vec![SimpleJsonPath::Field("escaped_both")],
+                    parts: vec![literal("\\{{$.constants.a}}")],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::{ComponentHandle, RigExecutionState, errors::RigError, execute::primitives::Hash};
+
+use super::{
+    INPUT_KEY, OUTPUT_KEY, RIGGING_KEY,
+    evaluate_input::evaluate_json_path_string,
+    extract_dependencies_from_json_path_strings::get_dependency,
+    find_json_path_strings::find_json_path_strings,
+    find_template_strings::{find_template_strings, templates_enabled},
+    serialize_rig_state,
+    simple_json_path::JsonPathOperations,
+};
+
+/// Describes where a value in a component's evaluated input came from.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct InputProvenance {
+    /// The location of the value within the component input, e.g. `input.a[0]`.
+    pub input_path: String,
+
+    /// The JSON Path expression as written in the rig, e.g. `$$.component.a`.
+    pub expression: String,
+
+    /// The JSON Path which was evaluated against the rig state.
+    pub resolved_path: String,
+
+    /// The component the value was taken from, if the expression referenced one.
+    pub source_component: Option<ComponentHandle>,
+
+    /// The hash of the source component's output, or of its input if the expression
+    /// referenced its input, identifying the upstream data the value depended on.
+    pub source_hash: Option<Hash>,
+
+    /// The resolved value, or `None` if an optional value was not found.
+    pub value: Option<serde_json::Value>,
+}
+
+/// Returns the provenance of every JSON Path expression in the component's input,
+/// evaluated against the current state of the rig.
+pub(crate) fn explain_component_input(
+    state: &RigExecutionState,
+    handle: &ComponentHandle,
+) -> Result<Vec<InputProvenance>, RigError> {
+    let component_state = state.get_component_state(handle)?;
+
+    if component_state.execution_input.is_none() {
+        return Err(RigError::StepFailed {
+            error: format!("Component {} has no execution input", handle),
+        });
+    }
+
+    let serialized_rig_state = serialize_evaluated_rig_state(state)?;
+    component_input_provenance(state, handle, &serialized_rig_state)
+}
+
+/// Records the provenance of each evaluated component input on the component states,
+/// for the components whose provenance isn't already recorded for their current input.
+pub(super) fn record_input_provenance(state: &mut RigExecutionState) -> Result<(), RigError> {
+    let unrecorded: Vec<_> = state
+        .component_states
+        .iter()
+        .filter(|(_, component_state)| {
+            component_state.execution_input.is_some() && component_state.input_provenance.is_none()
+        })
+        .map(|(&handle, _)| handle)
+        .collect();
+
+    if unrecorded.is_empty() {
+        return Ok(());
+    }
+
+    let serialized_rig_state = serialize_evaluated_rig_state(state)?;
+
+    let mut recorded = Vec::new();
+    for handle in unrecorded {
+        let provenance = component_input_provenance(state, handle, &serialized_rig_state)?;
+        recorded.push((handle, provenance));
+    }
+
+    for (handle, provenance) in recorded {
+        state.get_component_state_mut(handle)?.input_provenance = Some(Arc::new(provenance));
+    }
+
+    Ok(())
+}
+
+/// Serializes the rig state including the outputs and evaluated inputs of every component,
+/// so that any JSON Path expression in a component input can be evaluated against it.
+fn serialize_evaluated_rig_state(state: &RigExecutionState) -> Result<serde_json::Value, RigError> {
+    let mut serialized_rig_state = serialize_rig_state(state)?;
+    for (&other_handle, other_state) in state.component_states.iter() {
+        let serialized_component = &mut serialized_rig_state[RIGGING_KEY][&other_handle.0];
+
+        if let Some(output) = other_state.output() {
+            serialized_component[OUTPUT_KEY] = output.clone();
+        }
+
+        if let Some(execution_input) = other_state.execution_input.as_ref() {
+            serialized_component[INPUT_KEY] = execution_input.value.clone();
+        }
+    }
+
+    Ok(serialized_rig_state)
+}
+
+fn component_input_provenance(
+    state: &RigExecutionState,
+    handle: &ComponentHandle,
+    serialized_rig_state: &serde_json::Value,
+) -> Result<Vec<InputProvenance>, RigError> {
+    let component_state = state.get_component_state(handle)?;
+
+    let Some(input) = component_state.input() else {
+        return Ok(Vec::new());
+    };
+
+    let json_path_strings = find_json_path_strings(input);
+    let template_strings = if templates_enabled(state.session.rig()) {
+        find_template_strings(input)
+    } else {
+        Vec::new()
+    };
+
+    let expressions = json_path_strings
+        .iter()
+        .map(|found| {
+            let expression = found
+                .path_to
+                .get(input)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            (expression, found)
+        })
+        .chain(
+            template_strings
+                .iter()
+                .flat_map(|template| template.references()),
+        );
+
+    expressions
+        .map(|(expression, found)| {
+            let source_component = get_dependency(found)?;
+            let source_hash = source_component
+                .as_ref()
+                .and_then(|source| source_hash(state, source, &found.path));
+
+            Ok(InputProvenance {
+                input_path: found.path_to.to_prefixed_path_string(INPUT_KEY),
+                expression: expression.to_string(),
+                resolved_path: found.path.to_string(),
+                source_component,
+                source_hash,
+                value: evaluate_json_path_string(handle, serialized_rig_state, found)?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the hash of the source component's input if the resolved path references
+/// its input, and otherwise the hash of its output.
+fn source_hash(
+    state: &RigExecutionState,
+    source: &ComponentHandle,
+    resolved_path: &str,
+) -> Option<Hash> {
+    let source_state = state.get_component_state(source).ok()?;
+
+    let input_prefix = format!("$.{RIGGING_KEY}.{source}.{INPUT_KEY}");
+    let references_input = resolved_path
+        .strip_prefix(&input_prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']));
+
+    let json_metadata = if references_input {
+        source_state
+            .execution_input
+            .as_ref()
+            .map(|input| &input.json_metadata)
+    } else {
+        match source_state.output_override.as_ref() {
+            Some(output_override) => Some(&output_override.json_metadata),
+            None => source_state
+                .execution_output
+                .as_ref()
+                .map(|output| &output.json_metadata),
+        }
+    };
+
+    json_metadata.map(|metadata| metadata.hash.clone())
+}
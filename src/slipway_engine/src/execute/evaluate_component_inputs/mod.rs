@@ -6,10 +6,13 @@ use std::{
 
 use self::{
     extract_dependencies_from_json_path_strings::ExtractDependencies,
-    find_json_path_strings::FoundJsonPathString,
+    find_json_path_strings::FoundJsonPathString, find_template_strings::FoundTemplateString,
 };
 
 use super::{
+    apply_input_defaults::apply_input_defaults,
+    primitives::JsonMetadata,
+    skip::should_skip,
     topological_sort::sort_and_group,
     validate_component_io::{ValidationData, validate_component_io_from_session},
 };
@@ -17,9 +20,15 @@ use super::{
 mod evaluate_input;
 mod extract_dependencies_from_json_path_strings;
 mod find_json_path_strings;
+mod find_template_strings;
+mod input_provenance;
 mod map_dependencies_to_rig_handles;
 mod simple_json_path;
 
+pub use input_provenance::InputProvenance;
+pub(super) use input_provenance::explain_component_input;
+use input_provenance::record_input_provenance;
+
 const RIGGING_KEY: &str = "rigging";
 const RIG_CONTEXT_KEY: &str = "context";
 const INPUT_KEY: &str = "input";
@@ -32,6 +41,8 @@ pub(super) fn evaluate_component_inputs<'rig, 'cache>(
     let mut component_evaluate_input_params: HashMap<&ComponentHandle, EvaluateInputParams> =
         HashMap::new();
 
+    let templates_enabled = find_template_strings::templates_enabled(state.session.rig());
+
     for component_state in state.component_states.values() {
         // Get the input of the component, which is either the input_override or the input or None.
         let input = component_state.input();
@@ -42,8 +53,15 @@ pub(super) fn evaluate_component_inputs<'rig, 'cache>(
             None => Vec::new(),
         };
 
+        // Find all the strings which interpolate JSON path strings in the input of the component.
+        let template_strings = match input {
+            Some(input) if templates_enabled => find_template_strings::find_template_strings(input),
+            _ => Vec::new(),
+        };
+
         // Extract the component's dependencies from the JSON path strings.
-        let component_dependencies = json_path_strings.extract_dependencies()?;
+        let mut component_dependencies = json_path_strings.extract_dependencies()?;
+        component_dependencies.extend(template_strings.extract_dependencies()?);
 
         // The component can execute if all of it's dependencies have an execution_output.
         let can_execute = component_dependencies.iter().all(|d| {
@@ -61,6 +79,7 @@ pub(super) fn evaluate_component_inputs<'rig, 'cache>(
                 EvaluateInputParams {
                     input,
                     json_path_strings,
+                    template_strings,
                 },
             );
         }
@@ -76,17 +95,12 @@ pub(super) fn evaluate_component_inputs<'rig, 'cache>(
     let component_groups = sorted_and_grouped.grouped;
 
     let mut execution_inputs: HashMap<&ComponentHandle, ComponentInput> = HashMap::new();
+    let mut skipped_components: HashSet<&ComponentHandle> = HashSet::new();
 
     // We have to evaluate the inputs in topological order because they may refer to the
     // evaluated inputs of their dependencies.
     if !component_evaluate_input_params.is_empty() {
-        // Serialize the rig state to a JSON value.
-        let mut serialized_rig_state = serde_json::to_value(&state.session.rig)
-            .map_err(|error| RigError::RigParseFailed { error })?;
-
-        // Add the additional rig context to the serialized rig state.
-        serialized_rig_state[RIG_CONTEXT_KEY] =
-            state.session.options.rig_additional_context.clone();
+        let mut serialized_rig_state = serialize_rig_state(&state)?;
 
         // For each component handle, in execution order.
         for &component_handle in execution_order.iter() {
@@ -107,18 +121,42 @@ pub(super) fn evaluate_component_inputs<'rig, 'cache>(
                 component_evaluate_input_params.get(component_handle)
             {
                 // Evaluate the execution input on the latest serialized rig state.
-                let execution_input = evaluate_input::evaluate_input(
+                let mut execution_input = evaluate_input::evaluate_input(
                     component_handle,
                     &serialized_rig_state,
                     evaluate_input_params.input,
                     &evaluate_input_params.json_path_strings,
+                    &evaluate_input_params.template_strings,
+                    state.session.options.hash_algorithm,
                 )?;
 
-                validate_component_io_from_session(
-                    state.session,
-                    component_state,
-                    ValidationData::Input(&execution_input.value),
-                )?;
+                // Apply the component's input defaults before the input is validated,
+                // and re-hash it so that the hash reflects the input the component receives.
+                let component_definition = &state
+                    .session
+                    .component_cache
+                    .get(&component_state.rigging.component)
+                    .definition;
+                let defaulted =
+                    apply_input_defaults(&component_definition.input, &mut execution_input.value);
+                if !defaulted.is_empty() {
+                    execution_input.json_metadata = JsonMetadata::from_value(
+                        &execution_input.value,
+                        state.session.options.hash_algorithm,
+                    );
+                    execution_input.defaulted = defaulted;
+                }
+
+                // Skipped components are never run, so their input doesn't need to be valid.
+                if should_skip(component_state.rigging, &execution_input.value)? {
+                    skipped_components.insert(component_handle);
+                } else {
+                    validate_component_io_from_session(
+                        state.session,
+                        component_state,
+                        ValidationData::Input(&execution_input.value),
+                    )?;
+                }
 
                 // Set the execution input in the serialized rig state (in case
                 // later components reference this component's input).
@@ -142,7 +180,36 @@ pub(super) fn evaluate_component_inputs<'rig, 'cache>(
     // Update the execution input of every component.
     for key in state.session.rig.rigging.components.keys() {
         let component_state = state.get_component_state_mut(key)?;
-        component_state.execution_input = execution_inputs.remove(key).map(Arc::new);
+        let execution_input = execution_inputs.remove(key).map(Arc::new);
+
+        // The recorded input provenance is kept while the evaluated input is unchanged,
+        // so that it is only recorded again for components whose inputs changed.
+        let input_changed = match (&component_state.execution_input, &execution_input) {
+            (Some(previous), Some(current)) => {
+                previous.json_metadata.hash != current.json_metadata.hash
+            }
+            _ => true,
+        };
+        if input_changed {
+            component_state.input_provenance = None;
+        }
+
+        // A failure only applies to the input the component failed with, so the
+        // component can be run again once its input changes.
+        let failure_is_stale = component_state
+            .execution_failure
+            .as_ref()
+            .is_some_and(|failure| {
+                execution_input
+                    .as_ref()
+                    .is_none_or(|input| input.json_metadata.hash != failure.input_hash_used)
+            });
+        if failure_is_stale {
+            component_state.execution_failure = None;
+        }
+
+        component_state.execution_input = execution_input;
+        component_state.execution_skipped = skipped_components.contains(key);
         component_state.dependencies.clone_from(
             dependency_map_refs
                 .get(key)
@@ -150,10 +217,26 @@ pub(super) fn evaluate_component_inputs<'rig, 'cache>(
         );
     }
 
+    if state.session.options.record_input_provenance {
+        record_input_provenance(&mut state)?;
+    }
+
     Ok(state)
 }
 
+/// Serializes the rig to a JSON value, including the additional rig context,
+/// so that JSON Path strings can be evaluated against it.
+fn serialize_rig_state(state: &RigExecutionState) -> Result<serde_json::Value, RigError> {
+    let mut serialized_rig_state = serde_json::to_value(&state.session.rig)
+        .map_err(|error| RigError::RigParseFailed { error })?;
+
+    serialized_rig_state[RIG_CONTEXT_KEY] = state.session.options.rig_additional_context.clone();
+
+    Ok(serialized_rig_state)
+}
+
 struct EvaluateInputParams<'rig> {
     input: Option<&'rig serde_json::Value>,
     json_path_strings: Vec<FoundJsonPathString<'rig>>,
+    template_strings: Vec<FoundTemplateString<'rig>>,
 }
@@ -16,6 +16,8 @@ pub(super) trait JsonPathOperations {
     fn to_prefixed_path_string(&self, prefix: &str) -> String;
 
     fn replace(&self, target: &mut Value, new_value: Option<Value>) -> Result<(), RigError>;
+
+    fn get<'v>(&self, target: &'v Value) -> Option<&'v Value>;
 }
 
 impl JsonPathOperations for Vec<SimpleJsonPath<'_>> {
@@ -94,12 +96,21 @@ impl JsonPathOperations for Vec<SimpleJsonPath<'_>> {
                 *current = new_value;
             }
             None => {
-                unreachable!("new_value should never be None here, as we handle None in the loop");
+                // We only get here for an empty path, in which case an absent optional
+                // value replaces the entire input with null.
+                *current = Value::Null;
             }
         }
 
         Ok(())
     }
+
+    fn get<'v>(&self, target: &'v Value) -> Option<&'v Value> {
+        self.iter().try_fold(target, |current, path| match path {
+            SimpleJsonPath::Field(field) => current.get(*field),
+            SimpleJsonPath::Index(index) => current.get(*index),
+        })
+    }
 }
 
 // Note: There is more test coverage in the tests for the `execute` module.
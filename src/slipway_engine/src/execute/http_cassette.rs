@@ -0,0 +1,258 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const REDACTED_HEADER_VALUE: &str = "[REDACTED]";
+
+/// Records HTTP interactions to a cassette file, or replays them from it if the
+/// file already exists, so that rigs which fetch external data can be run reproducibly.
+#[derive(Debug)]
+pub struct HttpCassette {
+    path: PathBuf,
+    mode: HttpCassetteMode,
+    redact_headers: Vec<String>,
+    interactions: Mutex<Vec<HttpInteraction>>,
+
+    /// Held while the cassette file is written, so that writes happen in the
+    /// order interactions are recorded.
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HttpCassetteMode {
+    Record,
+    Replay,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+struct HttpCassetteFile {
+    interactions: Vec<HttpInteraction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct HttpInteraction {
+    pub request: HttpInteractionRequest,
+    pub response: HttpInteractionResponse,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct HttpInteractionRequest {
+    pub method: String,
+    pub url: String,
+
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<HttpCassetteBody>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct HttpInteractionResponse {
+    pub status_code: u16,
+
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+
+    pub body: HttpCassetteBody,
+}
+
+/// A request or response body, stored as text where possible so that cassettes are readable.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpCassetteBody {
+    Text(String),
+    Base64(String),
+}
+
+impl HttpCassetteBody {
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => HttpCassetteBody::Text(text.to_string()),
+            Err(_) => HttpCassetteBody::Base64(BASE64_STANDARD.encode(bytes)),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        match self {
+            HttpCassetteBody::Text(text) => Ok(text.as_bytes().to_vec()),
+            HttpCassetteBody::Base64(encoded) => BASE64_STANDARD.decode(encoded),
+        }
+    }
+}
+
+impl HttpCassette {
+    /// Loads the cassette for replay if the file exists, otherwise creates an
+    /// empty cassette which will record interactions to the file.
+    pub fn load(path: &Path, redact_headers: Vec<String>) -> anyhow::Result<Self> {
+        let (mode, interactions) = if path.exists() {
+            let contents = std::fs::read_to_string(path)?;
+            let file: HttpCassetteFile = serde_json::from_str(&contents)?;
+            (HttpCassetteMode::Replay, file.interactions)
+        } else {
+            (HttpCassetteMode::Record, Vec::new())
+        };
+
+        Ok(HttpCassette {
+            path: path.to_owned(),
+            mode,
+            redact_headers,
+            interactions: Mutex::new(interactions),
+            write_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    pub fn mode(&self) -> HttpCassetteMode {
+        self.mode
+    }
+
+    /// Returns the recorded response for the first interaction matching the
+    /// method, URL and body of the request.
+    pub fn find_response(
+        &self,
+        method: &str,
+        url: &str,
+        body: Option<&[u8]>,
+    ) -> Option<HttpInteractionResponse> {
+        let body = body.map(HttpCassetteBody::from_bytes);
+
+        self.interactions
+            .lock()
+            .expect("should be able to lock HTTP cassette")
+            .iter()
+            .find(|i| {
+                i.request.method.eq_ignore_ascii_case(method)
+                    && i.request.url == url
+                    && i.request.body == body
+            })
+            .map(|i| i.response.clone())
+    }
+
+    /// Adds the interaction to the cassette, redacting any configured headers,
+    /// and writes the cassette file on a blocking thread.
+    pub async fn record(&self, mut interaction: HttpInteraction) -> anyhow::Result<()> {
+        self.redact(&mut interaction.request.headers);
+        self.redact(&mut interaction.response.headers);
+
+        let _write_guard = self.write_lock.lock().await;
+
+        let contents = {
+            let mut interactions = self
+                .interactions
+                .lock()
+                .expect("should be able to lock HTTP cassette");
+
+            interactions.push(interaction);
+
+            let file = HttpCassetteFile {
+                interactions: interactions.clone(),
+            };
+
+            serde_json::to_string_pretty(&file)?
+        };
+
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || std::fs::write(path, contents)).await??;
+
+        Ok(())
+    }
+
+    fn redact(&self, headers: &mut [(String, String)]) {
+        for (name, value) in headers.iter_mut() {
+            if self
+                .redact_headers
+                .iter()
+                .any(|h| h.eq_ignore_ascii_case(name))
+            {
+                *value = REDACTED_HEADER_VALUE.to_string();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+
+    use super::*;
+
+    fn interaction(url: &str, body: Option<&[u8]>) -> HttpInteraction {
+        HttpInteraction {
+            request: HttpInteractionRequest {
+                method: "POST".to_string(),
+                url: url.to_string(),
+                headers: vec![("Authorization".to_string(), "Bearer abc".to_string())],
+                body: body.map(HttpCassetteBody::from_bytes),
+            },
+            response: HttpInteractionResponse {
+                status_code: 200,
+                headers: vec![("Content-Type".to_string(), "text/plain".to_string())],
+                body: HttpCassetteBody::from_bytes(b"hello"),
+            },
+        }
+    }
+
+    #[slipway_test_async]
+    async fn it_should_record_and_then_replay_interactions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassette.json");
+
+        let cassette = HttpCassette::load(&path, vec!["authorization".to_string()]).unwrap();
+        assert_eq!(cassette.mode(), HttpCassetteMode::Record);
+        cassette
+            .record(interaction("https://example.com/a", Some(b"{}")))
+            .await
+            .unwrap();
+
+        let cassette = HttpCassette::load(&path, vec![]).unwrap();
+        assert_eq!(cassette.mode(), HttpCassetteMode::Replay);
+
+        let response = cassette
+            .find_response("post", "https://example.com/a", Some(b"{}"))
+            .unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.to_bytes().unwrap(), b"hello");
+
+        assert!(
+            cassette
+                .find_response("POST", "https://example.com/a", None)
+                .is_none()
+        );
+        assert!(
+            cassette
+                .find_response("GET", "https://example.com/a", Some(b"{}"))
+                .is_none()
+        );
+    }
+
+    #[slipway_test_async]
+    async fn it_should_redact_headers_when_recording() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassette.json");
+
+        let cassette = HttpCassette::load(&path, vec!["authorization".to_string()]).unwrap();
+        cassette
+            .record(interaction("https://example.com/a", None))
+            .await
+            .unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("Bearer abc"));
+        assert!(contents.contains(REDACTED_HEADER_VALUE));
+        assert!(contents.contains("text/plain"));
+    }
+
+    #[test]
+    fn it_should_store_binary_bodies_as_base64() {
+        let body = HttpCassetteBody::from_bytes(&[0xff, 0x00, 0x01]);
+
+        assert!(matches!(body, HttpCassetteBody::Base64(_)));
+        assert_eq!(body.to_bytes().unwrap(), vec![0xff, 0x00, 0x01]);
+    }
+}
@@ -21,7 +21,10 @@ pub(super) fn initialize<'rig, 'cache>(
                     input_override: None,
                     output_override: None,
                     execution_input: None,
+                    input_provenance: None,
+                    execution_skipped: false,
                     execution_output: None,
+                    execution_failure: None,
                     dependencies: HashSet::new(),
                 },
             )
@@ -0,0 +1,193 @@
+use tracing::debug;
+
+use crate::{
+    ComponentHandle, ComponentRigging, RigExecutionState, RunComponentResult, RunMetadata,
+};
+
+/// Removes memoized components which are identical to an earlier component in the list,
+/// so that when the list is run as a wave each duplicate runs in a later wave and reuses
+/// the output of the first, rather than both running.
+pub fn deduplicate_memoized_components<'a>(
+    handles: Vec<&'a ComponentHandle>,
+    state: &RigExecutionState<'_, '_>,
+) -> Vec<&'a ComponentHandle> {
+    let mut deduplicated: Vec<&'a ComponentHandle> = Vec::with_capacity(handles.len());
+
+    for handle in handles {
+        let is_duplicate = deduplicated
+            .iter()
+            .any(|&other_handle| is_identical_memoized_component(handle, other_handle, state));
+
+        if is_duplicate {
+            debug!(
+                "Deferring memoized component \"{handle}\" until an identical component has run."
+            );
+        } else {
+            deduplicated.push(handle);
+        }
+    }
+
+    deduplicated
+}
+
+/// Returns the output of another memoized component in the rig which has identical rigging
+/// and has already run with an identical input, if the component is memoized.
+///
+/// The returned metadata has zero durations, as the component was not run, and
+/// records the component whose output was reused.
+pub(super) fn get_memoized_result(
+    handle: &ComponentHandle,
+    state: &RigExecutionState<'_, '_>,
+) -> Option<RunComponentResult> {
+    let component_state = state.component_states.get(handle)?;
+    if !is_memoized(component_state.rigging) {
+        return None;
+    }
+
+    let input = component_state.execution_input.as_ref()?;
+
+    state
+        .component_states
+        .iter()
+        .find_map(|(&other_handle, other_state)| {
+            if other_handle == handle
+                || !is_memoized(other_state.rigging)
+                || !is_same_rigging(component_state.rigging, other_state.rigging)
+            {
+                return None;
+            }
+
+            let output = other_state.execution_output.as_ref()?;
+            if output.input_hash_used != input.json_metadata.hash {
+                return None;
+            }
+
+            debug!("Reusing memoized output of component \"{other_handle}\" for \"{handle}\".");
+
+            Some(RunComponentResult {
+                output: output.value.clone(),
+                metadata: RunMetadata {
+                    memoized_from: Some(other_handle.clone()),
+                    ..Default::default()
+                },
+            })
+        })
+}
+
+/// Returns true if both components are memoized, with identical rigging and inputs.
+fn is_identical_memoized_component(
+    a: &ComponentHandle,
+    b: &ComponentHandle,
+    state: &RigExecutionState<'_, '_>,
+) -> bool {
+    let (Some(a_state), Some(b_state)) =
+        (state.component_states.get(a), state.component_states.get(b))
+    else {
+        return false;
+    };
+
+    let (Some(a_input), Some(b_input)) = (&a_state.execution_input, &b_state.execution_input)
+    else {
+        return false;
+    };
+
+    is_memoized(a_state.rigging)
+        && is_memoized(b_state.rigging)
+        && is_same_rigging(a_state.rigging, b_state.rigging)
+        && a_input.json_metadata.hash == b_input.json_metadata.hash
+}
+
+fn is_memoized(rigging: &ComponentRigging) -> bool {
+    rigging.memoize.unwrap_or(false)
+}
+
+/// Returns true if the riggings are identical, ignoring their inputs.
+fn is_same_rigging(a: &ComponentRigging, b: &ComponentRigging) -> bool {
+    a.component == b.component
+        && a.allow == b.allow
+        && a.deny == b.deny
+        && a.permissions_chain == b.permissions_chain
+        && a.callouts == b.callouts
+        && a.runner == b.runner
+        && a.skip == b.skip
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common_macros::slipway_test_async;
+    use serde_json::json;
+
+    use crate::{
+        BasicComponentCache, Instruction, Rig, RigSession, Rigging, SlipwayReference, utils::ch,
+    };
+
+    use super::*;
+
+    fn memoized_rigging(input: serde_json::Value, memoize: bool) -> ComponentRigging {
+        let mut rigging =
+            ComponentRigging::for_test_with_reference(SlipwayReference::for_test("a"), Some(input));
+        rigging.memoize = Some(memoize);
+        rigging
+    }
+
+    #[slipway_test_async]
+    async fn it_should_reuse_output_of_identical_memoized_component() {
+        let rig = Rig::for_test(Rigging {
+            components: [
+                (ch("a1"), memoized_rigging(json!({ "x": 1 }), true)),
+                (ch("a2"), memoized_rigging(json!({ "x": 1 }), true)),
+                (ch("a3"), memoized_rigging(json!({ "x": 2 }), true)),
+                (ch("a4"), memoized_rigging(json!({ "x": 1 }), false)),
+            ]
+            .into_iter()
+            .collect(),
+        });
+
+        let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+        let rig_session = RigSession::new_for_test(rig, &component_cache);
+
+        let state = rig_session.initialize().unwrap();
+        assert!(get_memoized_result(&ch("a2"), &state).is_none());
+
+        let state = state
+            .step(Instruction::SetOutput {
+                handle: ch("a1"),
+                value: json!({ "y": 1 }),
+                metadata: Default::default(),
+            })
+            .unwrap();
+
+        let result = get_memoized_result(&ch("a2"), &state).unwrap();
+        assert_eq!(result.output, json!({ "y": 1 }));
+        assert_eq!(result.metadata.memoized_from, Some(ch("a1")));
+        assert_eq!(result.metadata.overall_duration(), Duration::ZERO);
+
+        assert!(get_memoized_result(&ch("a3"), &state).is_none());
+        assert!(get_memoized_result(&ch("a4"), &state).is_none());
+    }
+
+    #[slipway_test_async]
+    async fn it_should_deduplicate_identical_memoized_components() {
+        let rig = Rig::for_test(Rigging {
+            components: [
+                (ch("a1"), memoized_rigging(json!({ "x": 1 }), true)),
+                (ch("a2"), memoized_rigging(json!({ "x": 1 }), true)),
+                (ch("a3"), memoized_rigging(json!({ "x": 2 }), true)),
+                (ch("a4"), memoized_rigging(json!({ "x": 1 }), false)),
+            ]
+            .into_iter()
+            .collect(),
+        });
+
+        let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+        let rig_session = RigSession::new_for_test(rig, &component_cache);
+        let state = rig_session.initialize().unwrap();
+
+        let handles = [ch("a1"), ch("a2"), ch("a3"), ch("a4")];
+        let deduplicated = deduplicate_memoized_components(handles.iter().collect(), &state);
+
+        assert_eq!(deduplicated, vec![&ch("a1"), &ch("a3"), &ch("a4")]);
+    }
+}
@@ -1,17 +1,27 @@
+mod apply_input_defaults;
+mod coerce_component_output;
 pub(crate) mod component_execution_data;
 pub(crate) mod component_runner;
 pub(crate) mod component_state;
+pub(crate) mod concurrency_limit;
 mod evaluate_component_inputs;
 pub(crate) mod fonts;
+pub(crate) mod http_cassette;
 mod initialize;
+mod memoize;
+pub(crate) mod output_limit;
 pub(crate) mod primitives;
 pub(crate) mod rig_execution_state;
 pub(crate) mod rig_session;
 mod run_record;
+mod skip;
 pub(crate) mod step;
 mod topological_sort;
 mod validate_component_io;
 
+pub use evaluate_component_inputs::InputProvenance;
+pub use memoize::deduplicate_memoized_components;
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
@@ -81,9 +91,14 @@ mod tests {
     }
 
     mod step {
+        use std::sync::Arc;
+
         use common_macros::slipway_test_async;
 
-        use crate::{BasicComponentCache, RigSession, errors::RigError};
+        use crate::{
+            BasicComponentCache, Environment, InputProvenance, RigSession, RigSessionOptions,
+            errors::RigError,
+        };
 
         use super::*;
 
@@ -179,6 +194,37 @@ mod tests {
             );
         }
 
+        #[slipway_test_async]
+        async fn it_should_only_interpolate_templates_when_enabled_for_rig() {
+            for (templates, expected) in [
+                (None, "v{{$.constants.test_constant}}"),
+                (Some(false), "v{{$.constants.test_constant}}"),
+                (Some(true), "vtest_constant_value"),
+            ] {
+                let mut rig = Rig::for_test(Rigging {
+                    components: [ComponentRigging::for_test(
+                        "a",
+                        Some(json!({"value": "v{{$.constants.test_constant}}"})),
+                    )]
+                    .into_iter()
+                    .collect(),
+                });
+                rig.templates = templates;
+
+                let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+                let rig_session = RigSession::new_for_test(rig, &component_cache);
+
+                let s = rig_session.initialize().unwrap();
+
+                let a = get_component_state(&s, "a");
+
+                assert_eq!(
+                    a.execution_input.as_ref().unwrap().value,
+                    json!({ "value": expected })
+                );
+            }
+        }
+
         #[slipway_test_async]
         async fn it_should_allow_setting_the_output_on_a_component_which_can_execute() {
             let rig = create_rig();
@@ -247,6 +293,143 @@ mod tests {
             );
         }
 
+        #[slipway_test_async]
+        async fn it_should_explain_where_component_inputs_came_from() {
+            let rig = create_rig();
+
+            let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+            let rig_session = RigSession::new_for_test(rig, &component_cache);
+
+            let mut s = rig_session.initialize().unwrap();
+
+            s = set_output_to(s, "c", json!({ "x": 1, "z": 3 }));
+
+            let provenance = s.explain_component_input(&ch("f")).unwrap();
+            let c_output_hash = get_component_state(&s, "c")
+                .execution_output
+                .as_ref()
+                .map(|output| output.json_metadata.hash.clone());
+
+            assert_eq!(
+                provenance,
+                vec![
+                    InputProvenance {
+                        input_path: "input.c_x".to_string(),
+                        expression: "$$*c.x".to_string(),
+                        resolved_path: "$.rigging.c.output.x".to_string(),
+                        source_component: Some(ch("c")),
+                        source_hash: c_output_hash.clone(),
+                        value: Some(json!([1])),
+                    },
+                    InputProvenance {
+                        input_path: "input.c_y".to_string(),
+                        expression: "$$?c.y".to_string(),
+                        resolved_path: "$.rigging.c.output.y".to_string(),
+                        source_component: Some(ch("c")),
+                        source_hash: c_output_hash.clone(),
+                        value: None,
+                    },
+                    InputProvenance {
+                        input_path: "input.c_z".to_string(),
+                        expression: "$$.c.z".to_string(),
+                        resolved_path: "$.rigging.c.output.z".to_string(),
+                        source_component: Some(ch("c")),
+                        source_hash: c_output_hash.clone(),
+                        value: Some(json!(3)),
+                    },
+                ]
+            );
+
+            let provenance = s.explain_component_input(&ch("c")).unwrap();
+            assert_eq!(provenance.len(), 3);
+            assert!(provenance.iter().all(|p| p.source_component.is_none()));
+        }
+
+        #[slipway_test_async]
+        async fn it_should_record_input_provenance_when_enabled() {
+            let rig = create_rig();
+
+            let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+            let options = RigSessionOptions::builder(Environment::for_test())
+                .record_input_provenance(true)
+                .build(&rig)
+                .await;
+            let rig_session = RigSession::new_with_options(rig, &component_cache, options);
+
+            let mut s = rig_session.initialize().unwrap();
+
+            assert!(get_component_state(&s, "f").input_provenance.is_none());
+
+            s = set_output_to(s, "c", json!({ "x": 1, "z": 3 }));
+
+            let recorded = s.input_provenance();
+            assert_eq!(
+                recorded.get(&ch("f")).copied(),
+                Some(s.explain_component_input(&ch("f")).unwrap().as_slice())
+            );
+            assert!(!recorded.contains_key(&ch("g")));
+
+            let serialized = serde_json::to_value(&recorded).unwrap();
+            assert_eq!(
+                serialized["f"][0]["source_hash"],
+                json!(
+                    get_component_state(&s, "c")
+                        .execution_output
+                        .as_ref()
+                        .unwrap()
+                        .json_metadata
+                        .hash
+                        .to_string()
+                )
+            );
+        }
+
+        #[slipway_test_async]
+        async fn it_should_only_record_input_provenance_for_changed_inputs() {
+            let rig = create_rig();
+
+            let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+            let options = RigSessionOptions::builder(Environment::for_test())
+                .record_input_provenance(true)
+                .build(&rig)
+                .await;
+            let rig_session = RigSession::new_with_options(rig, &component_cache, options);
+
+            let mut s = rig_session.initialize().unwrap();
+
+            let c_provenance = get_component_state(&s, "c")
+                .input_provenance
+                .clone()
+                .unwrap();
+
+            s = set_output_to(s, "c", json!({ "x": 1, "z": 3 }));
+
+            // The input of "c" is unchanged, so its provenance is not recorded again.
+            assert!(Arc::ptr_eq(
+                get_component_state(&s, "c")
+                    .input_provenance
+                    .as_ref()
+                    .unwrap(),
+                &c_provenance
+            ));
+            assert!(get_component_state(&s, "f").input_provenance.is_some());
+        }
+
+        #[slipway_test_async]
+        async fn it_should_not_explain_inputs_of_components_which_cannot_execute() {
+            let rig = create_rig();
+
+            let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+            let rig_session = RigSession::new_for_test(rig, &component_cache);
+
+            let s = rig_session.initialize().unwrap();
+
+            assert!(matches!(
+                s.explain_component_input(&ch("g")),
+                Err(RigError::StepFailed { .. })
+            ));
+        }
+
         #[slipway_test_async]
         async fn it_should_not_allow_required_json_path_references_missing_resolved_values() {
             let rig = create_rig();
@@ -464,6 +647,230 @@ mod tests {
             );
         }
 
+        #[slipway_test_async]
+        async fn it_should_expose_direct_dependencies_and_dependents() {
+            let rig = create_rig();
+
+            let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+            let rig_session = RigSession::new_for_test(rig, &component_cache);
+
+            let s = rig_session.initialize().unwrap();
+
+            assert_eq!(
+                s.execution_order(),
+                &[&ch("c"), &ch("d"), &ch("b"), &ch("a")]
+            );
+            assert_eq!(s.component_groups().len(), 2);
+
+            assert_eq!(s.dependencies(&ch("a")), Some(vec![&ch("b")]));
+            assert_eq!(s.dependencies(&ch("c")), Some(vec![]));
+            assert_eq!(s.dependents(&ch("c")), Some(vec![&ch("b")]));
+            assert_eq!(s.dependents(&ch("a")), Some(vec![]));
+            assert_eq!(s.dependents(&ch("d")), Some(vec![]));
+
+            assert_eq!(s.dependencies(&ch("missing")), None);
+            assert_eq!(s.dependents(&ch("missing")), None);
+        }
+
+        #[slipway_test_async]
+        async fn it_should_return_required_components() {
+            let rig = create_rig();
+
+            let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+            let rig_session = RigSession::new_for_test(rig, &component_cache);
+
+            let s = rig_session.initialize().unwrap();
+
+            assert_eq!(
+                s.required_components(&ch("a")),
+                Some([&ch("a"), &ch("b"), &ch("c")].into_iter().collect())
+            );
+            assert_eq!(
+                s.required_components(&ch("d")),
+                Some([&ch("d")].into_iter().collect())
+            );
+            assert_eq!(s.required_components(&ch("missing")), None);
+
+            let s = s
+                .step(Instruction::SetOutputOverride {
+                    handle: ch("b"),
+                    value: json!({}),
+                })
+                .unwrap();
+
+            assert_eq!(
+                s.required_components(&ch("a")),
+                Some([&ch("a"), &ch("b")].into_iter().collect())
+            );
+        }
+
+        #[slipway_test_async]
+        async fn it_should_return_unused_components() {
+            let rig = create_rig();
+
+            let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+            let rig_session = RigSession::new_for_test(rig, &component_cache);
+
+            let s = rig_session.initialize().unwrap();
+
+            assert_eq!(s.unused_components(&ch("a")), Some(vec![&ch("d")]));
+            assert_eq!(
+                s.unused_components(&ch("b")),
+                Some(vec![&ch("d"), &ch("a")])
+            );
+            assert_eq!(s.unused_components(&ch("missing")), None);
+
+            // Overridden outputs do not change how the rig is wired.
+            let s = s
+                .step(Instruction::SetOutputOverride {
+                    handle: ch("b"),
+                    value: json!({}),
+                })
+                .unwrap();
+
+            assert_eq!(s.unused_components(&ch("a")), Some(vec![&ch("d")]));
+        }
+
+        #[slipway_test_async]
+        async fn it_should_not_execute_dependents_of_failed_components() {
+            let rig = create_rig();
+
+            let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+            let rig_session = RigSession::new_for_test(rig, &component_cache);
+
+            let s = rig_session.initialize().unwrap();
+
+            let s = s
+                .step(Instruction::SetFailure {
+                    handle: ch("c"),
+                    message: "failed".to_string(),
+                })
+                .unwrap();
+
+            let failed = s.failed_components();
+            assert_eq!(failed.len(), 1);
+            assert_eq!(failed[0].0, &ch("c"));
+            assert_eq!(failed[0].1.message, "failed");
+
+            // "b" depends on "c", so it cannot execute or fail.
+            assert!(
+                s.get_component_state(&ch("b"))
+                    .unwrap()
+                    .execution_input
+                    .is_none()
+            );
+            assert!(
+                s.step(Instruction::SetFailure {
+                    handle: ch("b"),
+                    message: "failed".to_string(),
+                })
+                .is_err()
+            );
+
+            // Setting the output clears the failure.
+            let s = s
+                .step(Instruction::SetOutput {
+                    handle: ch("c"),
+                    value: json!({ "foo": "bar" }),
+                    metadata: Default::default(),
+                })
+                .unwrap();
+
+            assert!(s.failed_components().is_empty());
+        }
+
+        #[slipway_test_async]
+        async fn it_should_clear_failures_when_the_input_changes() {
+            let rig = create_rig();
+
+            let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+            let rig_session = RigSession::new_for_test(rig, &component_cache);
+
+            let s = rig_session.initialize().unwrap();
+
+            let s = s
+                .step(Instruction::SetFailure {
+                    handle: ch("c"),
+                    message: "failed".to_string(),
+                })
+                .unwrap();
+
+            // Stepping without changing the input keeps the failure.
+            let s = s
+                .step(Instruction::SetOutput {
+                    handle: ch("d"),
+                    value: json!({ "foo": "bar" }),
+                    metadata: Default::default(),
+                })
+                .unwrap();
+
+            assert_eq!(s.failed_components().len(), 1);
+
+            let s = s
+                .step(Instruction::SetInputOverride {
+                    handle: ch("c"),
+                    value: json!({ "foo": "baz" }),
+                })
+                .unwrap();
+
+            assert!(s.failed_components().is_empty());
+        }
+
+        #[slipway_test_async]
+        async fn it_should_summarize_component_io_bytes() {
+            let rig = create_rig();
+
+            let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+            let rig_session = RigSession::new_for_test(rig, &component_cache);
+
+            let s = rig_session.initialize().unwrap();
+
+            let s = s
+                .step(Instruction::SetOutput {
+                    handle: ch("c"),
+                    value: json!({ "foo": "bar" }),
+                    metadata: Default::default(),
+                })
+                .unwrap();
+
+            let summary = s.io_summary();
+            assert_eq!(summary.components.len(), s.execution_order().len());
+
+            let component_summary = |handle: &str| {
+                summary
+                    .components
+                    .iter()
+                    .find(|c| c.handle == &ch(handle))
+                    .unwrap()
+            };
+
+            let c_input = s
+                .get_component_state(&ch("c"))
+                .unwrap()
+                .execution_input
+                .as_ref()
+                .unwrap();
+            let c = component_summary("c");
+            assert_eq!(c.input_bytes, Some(c_input.json_metadata.serialized.len()));
+            assert_eq!(c.output_bytes, Some(r#"{"foo":"bar"}"#.len()));
+
+            let a = component_summary("a");
+            assert_eq!(a.input_bytes, None);
+            assert_eq!(a.output_bytes, None);
+
+            let expected_input_bytes: usize = summary
+                .components
+                .iter()
+                .filter_map(|c| c.input_bytes)
+                .sum();
+            assert_eq!(summary.total_input_bytes, expected_input_bytes);
+            assert_eq!(summary.total_output_bytes, r#"{"foo":"bar"}"#.len());
+            assert_eq!(
+                summary.total_bytes(),
+                expected_input_bytes + r#"{"foo":"bar"}"#.len()
+            );
+        }
+
         #[slipway_test_async]
         async fn setting_input_override_should_update_input_hash() {
             let rig = create_rig();
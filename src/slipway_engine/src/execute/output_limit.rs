@@ -0,0 +1,84 @@
+use std::io::Write;
+
+use crate::RunComponentError;
+
+/// Returns an error if a component's output, of the given size in bytes of
+/// serialized JSON, exceeds the session's `max_output_bytes`.
+pub fn ensure_output_within_limit(
+    output_bytes: usize,
+    max_output_bytes: Option<usize>,
+) -> Result<(), RunComponentError> {
+    match max_output_bytes {
+        Some(max_output_bytes) if output_bytes > max_output_bytes => {
+            Err(RunComponentError::ResourceLimitExceeded {
+                kind: "output".to_string(),
+                message: format!(
+                    "The component output was {output_bytes} bytes, which exceeds the maximum of {max_output_bytes} bytes."
+                ),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// As `ensure_output_within_limit`, for outputs which have not been serialized.
+/// The output is only measured if there is a limit.
+pub fn ensure_output_value_within_limit(
+    output: &serde_json::Value,
+    max_output_bytes: Option<usize>,
+) -> Result<(), RunComponentError> {
+    if max_output_bytes.is_none() {
+        return Ok(());
+    }
+
+    let mut counter = ByteCounter(0);
+    serde_json::to_writer(&mut counter, output).expect("serde_json::Value should serialize");
+
+    ensure_output_within_limit(counter.0, max_output_bytes)
+}
+
+/// Counts the bytes written to it, so that outputs can be measured without
+/// allocating their serialized form.
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn it_should_allow_output_within_limit() {
+        let output = json!({ "a": 1 });
+        let size = serde_json::to_string(&output).unwrap().len();
+
+        assert!(ensure_output_value_within_limit(&output, None).is_ok());
+        assert!(ensure_output_value_within_limit(&output, Some(size)).is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_output_exceeding_limit() {
+        let output = json!({ "a": "hello" });
+        let size = serde_json::to_string(&output).unwrap().len();
+
+        let Err(RunComponentError::ResourceLimitExceeded { kind, message }) =
+            ensure_output_value_within_limit(&output, Some(size - 1))
+        else {
+            panic!("Expected ResourceLimitExceeded error");
+        };
+
+        assert_eq!(kind, "output");
+        assert!(message.contains(&size.to_string()));
+    }
+}
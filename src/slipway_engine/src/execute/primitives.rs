@@ -1,6 +1,7 @@
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sha2::{Digest, Sha256};
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Hash {
@@ -23,6 +24,59 @@ impl Hash {
     }
 }
 
+/// Hashes are serialized as hexadecimal strings, matching their display format.
+impl Serialize for Hash {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// The algorithm used to hash component inputs and outputs during execution.
+/// The hashes are only used to detect changes within a session, so they
+/// don't need to be cryptographically secure.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+
+    /// BLAKE3, which is considerably faster than SHA-256 when hashing large inputs
+    /// and outputs.
+    Blake3,
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            _ => Err(format!(
+                "Unknown hash algorithm \"{s}\", expected \"sha256\" or \"blake3\""
+            )),
+        }
+    }
+}
+
+impl HashAlgorithm {
+    pub fn hash(&self, bytes: &[u8]) -> Hash {
+        match self {
+            HashAlgorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                hasher.update(bytes);
+
+                // Read hash digest and consume hasher
+                Hash::new(hasher.finalize().into())
+            }
+            HashAlgorithm::Blake3 => Hash::new(blake3::hash(bytes).into()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct JsonMetadata {
     pub hash: Hash,
@@ -30,16 +84,9 @@ pub struct JsonMetadata {
 }
 
 impl JsonMetadata {
-    pub fn from_value(value: &Value) -> Self {
+    pub fn from_value(value: &Value, hash_algorithm: HashAlgorithm) -> Self {
         let serialized = serde_json::to_string(&value).expect("serde_json::Value should serialize");
-        let serialized_bytes = serialized.as_bytes();
-
-        let mut hasher = Sha256::new();
-        hasher.update(serialized_bytes);
-
-        // Read hash digest and consume hasher
-        let value = hasher.finalize();
-        let hash = Hash::new(value.into());
+        let hash = hash_algorithm.hash(serialized.as_bytes());
 
         JsonMetadata { hash, serialized }
     }
@@ -67,15 +114,44 @@ mod tests {
             "c": 3,
         });
 
-        let metadata = JsonMetadata::from_value(&json);
-        let metadata_of_clone = JsonMetadata::from_value(&json_clone);
-        let metadata_of_other = JsonMetadata::from_value(&json_other);
+        for hash_algorithm in [HashAlgorithm::Sha256, HashAlgorithm::Blake3] {
+            let metadata = JsonMetadata::from_value(&json, hash_algorithm);
+            let metadata_of_clone = JsonMetadata::from_value(&json_clone, hash_algorithm);
+            let metadata_of_other = JsonMetadata::from_value(&json_other, hash_algorithm);
+
+            assert_eq!(metadata, metadata_of_clone);
+            assert_ne!(metadata, metadata_of_other);
+
+            assert_eq!(metadata.hash.value.len(), 32);
+
+            assert_eq!(metadata.serialized, "{\"a\":1,\"b\":2,\"c\":3}");
+        }
+    }
+
+    #[test]
+    fn it_should_hash_differently_for_each_algorithm() {
+        let json = json!({ "a": 1 });
 
-        assert_eq!(metadata, metadata_of_clone);
-        assert_ne!(metadata, metadata_of_other);
+        let sha256 = JsonMetadata::from_value(&json, HashAlgorithm::Sha256);
+        let blake3 = JsonMetadata::from_value(&json, HashAlgorithm::Blake3);
 
-        assert_eq!(metadata.hash.value.len(), 32);
+        assert_ne!(sha256.hash, blake3.hash);
+        assert_eq!(
+            sha256.hash.to_string(),
+            "015abd7f5cc57a2dd94b7590f04ad8084273905ee33ec5cebeae62276a97f862"
+        );
+    }
 
-        assert_eq!(metadata.serialized, "{\"a\":1,\"b\":2,\"c\":3}");
+    #[test]
+    fn it_should_parse_hash_algorithm() {
+        assert_eq!(
+            HashAlgorithm::from_str("sha256").unwrap(),
+            HashAlgorithm::Sha256
+        );
+        assert_eq!(
+            HashAlgorithm::from_str("blake3").unwrap(),
+            HashAlgorithm::Blake3
+        );
+        assert!(HashAlgorithm::from_str("md5").is_err());
     }
 }
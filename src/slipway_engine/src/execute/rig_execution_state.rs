@@ -1,20 +1,23 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     sync::Arc,
 };
 
 use crate::{
-    Callout, Callouts, ChainItem, ComponentCache, ComponentHandle, ComponentInput, Immutable,
-    Instruction, JsonMetadata, PERMISSIONS_NONE_VEC, Permissions, RigSession, RigSessionOptions,
-    SlipwayReference, errors::RigError,
+    Callout, Callouts, ChainItem, ComponentCache, ComponentFailure, ComponentHandle,
+    ComponentInput, Immutable, Instruction, JsonMetadata, OutputContentType, PERMISSIONS_NONE_VEC,
+    Permissions, RigSession, RigSessionOptions, SlipwayReference, errors::RigError,
 };
 
 use super::{
+    apply_input_defaults::apply_input_defaults,
     component_execution_data::{
         CallChain, CalloutContext, ComponentExecutionContext, ComponentExecutionData,
     },
     component_runner::ComponentRunner,
     component_state::ComponentState,
+    concurrency_limit::ComponentConcurrencyPermit,
+    evaluate_component_inputs::{InputProvenance, explain_component_input},
     step::step,
 };
 
@@ -22,11 +25,207 @@ use super::{
 pub struct RigExecutionState<'rig, 'cache> {
     pub session: &'rig RigSession<'cache>,
     pub component_states: HashMap<&'rig ComponentHandle, ComponentState<'rig>>,
-    pub valid_execution_order: Vec<&'rig ComponentHandle>,
-    pub component_groups: Vec<HashSet<&'rig ComponentHandle>>,
+    pub(crate) valid_execution_order: Vec<&'rig ComponentHandle>,
+    pub(crate) component_groups: Vec<HashSet<&'rig ComponentHandle>>,
+}
+
+/// The serialized JSON byte counts of a rig's component inputs and outputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RigIoSummary<'rig> {
+    pub total_input_bytes: usize,
+    pub total_output_bytes: usize,
+    pub components: Vec<ComponentIoSummary<'rig>>,
+}
+
+impl RigIoSummary<'_> {
+    /// Returns the total number of bytes flowing through the rig.
+    pub fn total_bytes(&self) -> usize {
+        self.total_input_bytes + self.total_output_bytes
+    }
+}
+
+/// The serialized JSON byte counts of a single component's input and output.
+/// Each is `None` if the component does not yet have an input or output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentIoSummary<'rig> {
+    pub handle: &'rig ComponentHandle,
+    pub input_bytes: Option<usize>,
+    pub output_bytes: Option<usize>,
 }
 
 impl<'rig, 'cache> RigExecutionState<'rig, 'cache> {
+    /// Returns every component handle in the rig in a valid execution order,
+    /// where each component comes after all the components it depends on.
+    ///
+    /// The order is deterministic for a given rig state, but may change as the
+    /// state changes, for example if a component's input is overridden.
+    pub fn execution_order(&self) -> &[&'rig ComponentHandle] {
+        &self.valid_execution_order
+    }
+
+    /// Returns the isolated groups of components in the rig, where no component in
+    /// one group depends, directly or indirectly, on a component in another group.
+    pub fn component_groups(&self) -> &[HashSet<&'rig ComponentHandle>] {
+        &self.component_groups
+    }
+
+    /// Returns the components which the given component directly depends on,
+    /// in execution order, or `None` if the component does not exist in the rig.
+    pub fn dependencies(&self, handle: &ComponentHandle) -> Option<Vec<&'rig ComponentHandle>> {
+        let component_state = self.component_states.get(handle)?;
+
+        Some(
+            self.valid_execution_order
+                .iter()
+                .filter(|&&h| component_state.dependencies.contains(h))
+                .copied()
+                .collect(),
+        )
+    }
+
+    /// Returns the components which directly depend on the given component,
+    /// in execution order, or `None` if the component does not exist in the rig.
+    pub fn dependents(&self, handle: &ComponentHandle) -> Option<Vec<&'rig ComponentHandle>> {
+        if !self.component_states.contains_key(handle) {
+            return None;
+        }
+
+        Some(
+            self.valid_execution_order
+                .iter()
+                .filter(|&&h| {
+                    self.component_states
+                        .get(h)
+                        .is_some_and(|s| s.dependencies.contains(handle))
+                })
+                .copied()
+                .collect(),
+        )
+    }
+
+    /// Returns the given component and every component it depends on, directly or
+    /// indirectly, or `None` if the component does not exist in the rig.
+    /// These are the only components which need to run to produce the component's output.
+    /// The dependencies of components with an overridden output are not required.
+    pub fn required_components(
+        &self,
+        handle: &ComponentHandle,
+    ) -> Option<HashSet<&'rig ComponentHandle>> {
+        let (&handle, _) = self.component_states.get_key_value(handle)?;
+
+        let mut required = HashSet::new();
+        let mut pending = vec![handle];
+        while let Some(next) = pending.pop() {
+            if !required.insert(next) {
+                continue;
+            }
+
+            if let Some(component_state) = self.component_states.get(next)
+                && component_state.output_override.is_none()
+            {
+                pending.extend(component_state.dependencies.iter().copied());
+            }
+        }
+
+        Some(required)
+    }
+
+    /// Returns the components whose output is not used, directly or indirectly, by the
+    /// given output component, in execution order, or `None` if the component does not
+    /// exist in the rig. Running these components does not contribute to the output.
+    /// Unlike `required_components`, output overrides are ignored, as this describes
+    /// how the rig is wired rather than what needs to run.
+    pub fn unused_components(
+        &self,
+        output_handle: &ComponentHandle,
+    ) -> Option<Vec<&'rig ComponentHandle>> {
+        let (&output_handle, _) = self.component_states.get_key_value(output_handle)?;
+
+        let mut used = HashSet::new();
+        let mut pending = vec![output_handle];
+        while let Some(next) = pending.pop() {
+            if !used.insert(next) {
+                continue;
+            }
+
+            if let Some(component_state) = self.component_states.get(next) {
+                pending.extend(component_state.dependencies.iter().copied());
+            }
+        }
+
+        Some(
+            self.valid_execution_order
+                .iter()
+                .filter(|&&h| !used.contains(h))
+                .copied()
+                .collect(),
+        )
+    }
+
+    /// Returns the components which failed to run, in execution order,
+    /// along with the reason each one failed.
+    pub fn failed_components(&self) -> Vec<(&'rig ComponentHandle, &ComponentFailure)> {
+        self.valid_execution_order
+            .iter()
+            .filter_map(|&h| {
+                self.component_states
+                    .get(h)
+                    .and_then(|s| s.execution_failure.as_deref())
+                    .map(|failure| (h, failure))
+            })
+            .collect()
+    }
+
+    /// Returns the number of serialized JSON bytes flowing into and out of each
+    /// component, in execution order, along with the totals for the rig.
+    /// Overridden outputs are counted in place of the component's execution output.
+    pub fn io_summary(&self) -> RigIoSummary<'rig> {
+        let components: Vec<_> = self
+            .valid_execution_order
+            .iter()
+            .filter_map(|&handle| {
+                let component_state = self.component_states.get(handle)?;
+
+                let input_bytes = component_state
+                    .execution_input
+                    .as_ref()
+                    .map(|input| input.json_metadata.serialized.len());
+
+                let output_bytes = match &component_state.output_override {
+                    Some(output_override) => Some(output_override.json_metadata.serialized.len()),
+                    None => component_state
+                        .execution_output
+                        .as_ref()
+                        .map(|output| output.json_metadata.serialized.len()),
+                };
+
+                Some(ComponentIoSummary {
+                    handle,
+                    input_bytes,
+                    output_bytes,
+                })
+            })
+            .collect();
+
+        RigIoSummary {
+            total_input_bytes: components.iter().filter_map(|c| c.input_bytes).sum(),
+            total_output_bytes: components.iter().filter_map(|c| c.output_bytes).sum(),
+            components,
+        }
+    }
+
+    /// Returns the content type the component declares for its output, or `None` if
+    /// the component does not exist in the rig or does not declare a content type.
+    pub fn output_content_type(&self, handle: &ComponentHandle) -> Option<OutputContentType> {
+        let component_state = self.component_states.get(handle)?;
+
+        self.session
+            .component_cache
+            .try_get(&component_state.rigging.component)?
+            .definition
+            .output_content_type
+    }
+
     pub fn step(
         &self,
         instruction: Instruction,
@@ -34,6 +233,30 @@ impl<'rig, 'cache> RigExecutionState<'rig, 'cache> {
         step(self, instruction)
     }
 
+    /// Returns where each JSON Path expression in the component's input
+    /// resolved from, using the current state of the rig.
+    pub fn explain_component_input(
+        &self,
+        handle: &ComponentHandle,
+    ) -> Result<Vec<InputProvenance>, RigError> {
+        explain_component_input(self, handle)
+    }
+
+    /// Returns the recorded provenance of each evaluated component input, keyed by
+    /// component handle, if the session options enable recording input provenance.
+    /// The result can be serialized to JSON for building lineage tooling.
+    pub fn input_provenance(&self) -> BTreeMap<&'rig ComponentHandle, &[InputProvenance]> {
+        self.component_states
+            .iter()
+            .filter_map(|(&handle, component_state)| {
+                component_state
+                    .input_provenance
+                    .as_deref()
+                    .map(|provenance| (handle, provenance.as_slice()))
+            })
+            .collect()
+    }
+
     pub fn get_component_execution_data<'call, 'runners>(
         &self,
         handle: &'rig ComponentHandle,
@@ -160,11 +383,21 @@ where
 
     let component_runners = execution_context.component_runners;
 
-    let json_metadata = JsonMetadata::from_value(&input);
+    // Callout inputs are supplied by the calling component rather than the rig,
+    // but they receive the same input defaults as components in the rig.
+    let mut input = input;
+    let defaulted = apply_input_defaults(
+        &component_cache.get(component_reference).definition.input,
+        &mut input,
+    );
+
+    let json_metadata =
+        JsonMetadata::from_value(&input, execution_context.rig_session_options.hash_algorithm);
 
     let input = Arc::new(ComponentInput {
         value: input,
         json_metadata,
+        defaulted,
     });
 
     get_component_execution_data(
@@ -210,6 +443,10 @@ where
             files,
             callout_context,
             rig_session_options,
+            concurrency_permit: Arc::new(ComponentConcurrencyPermit::new(
+                rig_session_options.component_concurrency_limit.clone(),
+            )),
+            permission_denial: Arc::default(),
         },
     })
 }
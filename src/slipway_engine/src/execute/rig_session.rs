@@ -1,14 +1,19 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use futures::lock::Mutex;
 
 use crate::errors::RigError;
-use crate::load::ComponentCache;
-use crate::{CallChain, Callout, ComponentHandle, ComponentInput, Immutable, SlipwayReference};
+use crate::load::{ComponentCache, ComponentHttpClient};
+use crate::{
+    CallChain, Callout, ComponentHandle, ComponentInput, HashAlgorithm, Immutable, SlipwayReference,
+};
 
+use super::concurrency_limit::ComponentConcurrencyLimit;
 use super::fonts::FontContext;
+use super::http_cassette::HttpCassette;
 use super::initialize::initialize;
 use super::rig_execution_state::RigExecutionState;
 use super::run_record::RigRunRecord;
@@ -63,6 +68,14 @@ impl<'cache> RigSession<'cache> {
         initialize(self)
     }
 
+    pub fn rig(&self) -> &Rig {
+        &self.rig
+    }
+
+    pub fn options(&self) -> &RigSessionOptions {
+        &self.options
+    }
+
     pub fn rigging_component_references(&self) -> Vec<&SlipwayReference> {
         self.rig
             .rigging
@@ -78,9 +91,10 @@ impl<'cache> RigSession<'cache> {
         call_chain: Arc<CallChain>,
         input: Arc<ComponentInput>,
         callouts: Option<HashMap<ComponentHandle, Callout>>,
+        runner: Option<String>,
     ) {
         if let Some(run_record) = &self.options.run_record {
-            run_record.push_run_record(component_reference, call_chain, input, callouts);
+            run_record.push_run_record(component_reference, call_chain, input, callouts, runner);
         }
     }
 
@@ -97,13 +111,98 @@ impl<'cache> RigSession<'cache> {
     }
 }
 
+/// Options controlling how a rig is executed.
+///
+/// Use `RigSessionOptions::builder` to create options, which defaults any
+/// options which aren't set.
 #[derive(Debug, Clone)]
 pub struct RigSessionOptions {
+    /// The path which relative paths, such as local component references, are resolved against.
     pub base_path: PathBuf,
+
+    /// An optional folder containing AOT compiled WASM components, which are used
+    /// in preference to JIT compiling the components.
     pub aot_path: Option<PathBuf>,
+
+    /// An optional URL template for fetching AOT compiled WASM components from a
+    /// registry when they aren't found in the `aot_path`. The `{hash}` and `{target}`
+    /// placeholders are replaced with the WASM hash and host target triple.
+    /// The URL must use `https`.
+    pub aot_registry_url: Option<String>,
+
+    /// The key used to verify the signatures of artifacts fetched from the AOT registry.
+    /// Artifacts are native code, so the AOT registry is only used when a key is set.
+    pub aot_registry_key: Option<String>,
+
+    /// The timezone and locale the rig is run in.
     pub environment: Environment,
+
+    /// The context made available to components alongside the rig's own context,
+    /// containing the environment and device.
     pub rig_additional_context: serde_json::Value,
+
+    /// An explicit proxy URL for outbound HTTP requests. When not set the
+    /// `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables are used.
+    pub proxy: Option<String>,
+
+    /// The client used to download AOT compiled components from the AOT registry,
+    /// which is shared with the components loader when one is given.
+    pub components_http_client: Arc<ComponentHttpClient>,
+
+    /// An optional cassette used to record or replay HTTP requests.
+    pub http_cassette: Option<Arc<HttpCassette>>,
+
+    /// Whether to leniently coerce component outputs to the types in their
+    /// output schemas before they are validated.
+    pub coerce_outputs: bool,
+
+    /// Whether to log outgoing HTTP requests and their responses at trace level,
+    /// with secrets redacted and bodies truncated.
+    pub log_http: bool,
+
+    /// Whether permission errors should explain how each level of the call chain
+    /// resolved the request, including the `allow` and `deny` permissions at each
+    /// level and which of them matched.
+    pub explain_permissions: bool,
+
+    /// Whether to write an audit log entry for each permission checked host call
+    /// a component makes, such as HTTP fetches, environment variable reads,
+    /// file loads and callouts.
+    pub audit_log: bool,
+
+    /// An optional limit on the number of components executing concurrently
+    /// across the rig, including callouts and fragments. Components queue
+    /// for a permit once the limit is reached.
+    pub component_concurrency_limit: Option<ComponentConcurrencyLimit>,
+
+    /// An optional maximum number of iterations of any single loop in a Javascript
+    /// component, after which it fails with `RunComponentError::Timeout`.
+    /// This stops synchronous loops which spin forever without calling the host.
+    pub js_step_limit: Option<u64>,
+
+    /// An optional total budget for running each Javascript component, after which it
+    /// fails with `RunComponentError::Timeout`. The Boa runner checks the budget each
+    /// time the component calls the host, so `js_step_limit` is still needed to stop
+    /// synchronous loops which never call the host.
+    pub js_time_limit: Option<Duration>,
+
+    /// An optional maximum size of each component's output, in bytes of serialized JSON,
+    /// after which the component fails with `RunComponentError::ResourceLimitExceeded`.
+    /// This protects the host from components which return excessively large outputs.
+    pub max_output_bytes: Option<usize>,
+
+    /// The algorithm used to hash component inputs and outputs. SHA-256 is the
+    /// default, but BLAKE3 is faster for components with large JSON inputs or outputs.
+    pub hash_algorithm: HashAlgorithm,
+
+    /// Whether to record where each value in every component's evaluated input
+    /// resolved from, so that lineage can be traced through the finished rig.
+    pub record_input_provenance: bool,
+
+    /// Records each component run, so that the run can be saved as a debug rig.
     run_record: Option<RigRunRecord>,
+
+    /// The fonts available to components.
     font_context: Arc<Mutex<FontContext>>,
 }
 
@@ -123,6 +222,10 @@ impl Environment {
 }
 
 impl RigSessionOptions {
+    pub fn builder(environment: Environment) -> RigSessionOptionsBuilder {
+        RigSessionOptionsBuilder::new(environment)
+    }
+
     pub async fn new_for_serve(
         rig: &Rig,
         base_path: PathBuf,
@@ -130,19 +233,16 @@ impl RigSessionOptions {
         fonts_path: PathBuf,
         environment: Environment,
         device_context: Option<serde_json::Value>,
+        proxy: Option<String>,
     ) -> Self {
-        let font_context = FontContext::new_with_path(&fonts_path).await;
-        let device_context = device_context.or_else(|| rig.context.clone().and_then(|c| c.device));
-        let rig_additional_context = get_rig_additional_context(&environment, device_context);
-
-        RigSessionOptions {
-            base_path,
-            aot_path,
-            environment,
-            rig_additional_context,
-            run_record: None,
-            font_context: Arc::new(Mutex::new(font_context)),
-        }
+        RigSessionOptions::builder(environment)
+            .base_path(base_path)
+            .aot_path(aot_path)
+            .fonts_path(Some(fonts_path))
+            .device_context(device_context)
+            .proxy(proxy)
+            .build(rig)
+            .await
     }
 
     pub async fn new_for_run(
@@ -150,29 +250,14 @@ impl RigSessionOptions {
         use_run_record: bool,
         fonts_path: Option<&Path>,
         environment: Environment,
+        http_cassette: Option<Arc<HttpCassette>>,
     ) -> Self {
-        let run_record = if use_run_record {
-            Some(RigRunRecord::new())
-        } else {
-            None
-        };
-
-        let font_context = match fonts_path {
-            None => FontContext::new(),
-            Some(fonts_path) => FontContext::new_with_path(fonts_path).await,
-        };
-
-        let device_context = rig.context.clone().and_then(|c| c.device);
-        let rig_additional_context = get_rig_additional_context(&environment, device_context);
-
-        RigSessionOptions {
-            base_path: PathBuf::from("."),
-            aot_path: None,
-            environment,
-            rig_additional_context,
-            run_record,
-            font_context: Arc::new(Mutex::new(font_context)),
-        }
+        RigSessionOptions::builder(environment)
+            .run_record(use_run_record)
+            .fonts_path(fonts_path.map(Path::to_owned))
+            .http_cassette(http_cassette)
+            .build(rig)
+            .await
     }
 
     pub fn new_for_test(
@@ -180,21 +265,227 @@ impl RigSessionOptions {
         environment: Environment,
         device_context: Option<serde_json::Value>,
     ) -> Self {
-        let device_context = device_context.or_else(|| rig.context.clone().and_then(|c| c.device));
-        let rig_additional_context = get_rig_additional_context(&environment, device_context);
+        RigSessionOptions::builder(environment)
+            .device_context(device_context)
+            .build_with_font_context(rig, FontContext::new())
+    }
 
-        RigSessionOptions {
+    pub fn font_context(&self) -> Arc<Mutex<FontContext>> {
+        Arc::clone(&self.font_context)
+    }
+}
+
+/// Builds `RigSessionOptions`, defaulting any options which aren't set.
+pub struct RigSessionOptionsBuilder {
+    base_path: PathBuf,
+    aot_path: Option<PathBuf>,
+    aot_registry_url: Option<String>,
+    aot_registry_key: Option<String>,
+    fonts_path: Option<PathBuf>,
+    environment: Environment,
+    device_context: Option<serde_json::Value>,
+    proxy: Option<String>,
+    components_http_client: Option<Arc<ComponentHttpClient>>,
+    http_cassette: Option<Arc<HttpCassette>>,
+    coerce_outputs: bool,
+    log_http: bool,
+    explain_permissions: bool,
+    audit_log: bool,
+    max_concurrent_components: Option<usize>,
+    js_step_limit: Option<u64>,
+    js_time_limit: Option<Duration>,
+    max_output_bytes: Option<usize>,
+    hash_algorithm: HashAlgorithm,
+    record_input_provenance: bool,
+    run_record: bool,
+}
+
+impl RigSessionOptionsBuilder {
+    pub fn new(environment: Environment) -> Self {
+        Self {
             base_path: PathBuf::from("."),
             aot_path: None,
+            aot_registry_url: None,
+            aot_registry_key: None,
+            fonts_path: None,
             environment,
-            rig_additional_context,
-            run_record: None,
-            font_context: Arc::new(Mutex::new(FontContext::new())),
+            device_context: None,
+            proxy: None,
+            components_http_client: None,
+            http_cassette: None,
+            coerce_outputs: false,
+            log_http: false,
+            explain_permissions: false,
+            audit_log: false,
+            max_concurrent_components: None,
+            js_step_limit: None,
+            js_time_limit: None,
+            max_output_bytes: None,
+            hash_algorithm: HashAlgorithm::default(),
+            record_input_provenance: false,
+            run_record: false,
         }
     }
 
-    pub fn font_context(&self) -> Arc<Mutex<FontContext>> {
-        Arc::clone(&self.font_context)
+    /// Sets the path which relative paths are resolved against. Defaults to the current directory.
+    pub fn base_path(mut self, base_path: PathBuf) -> Self {
+        self.base_path = base_path;
+        self
+    }
+
+    pub fn aot_path(mut self, aot_path: Option<PathBuf>) -> Self {
+        self.aot_path = aot_path;
+        self
+    }
+
+    pub fn aot_registry_url(mut self, aot_registry_url: Option<String>) -> Self {
+        self.aot_registry_url = aot_registry_url;
+        self
+    }
+
+    /// Sets the key used to verify artifacts fetched from the AOT registry.
+    pub fn aot_registry_key(mut self, aot_registry_key: Option<String>) -> Self {
+        self.aot_registry_key = aot_registry_key;
+        self
+    }
+
+    /// Sets a folder of additional fonts to make available to components,
+    /// alongside the system fonts.
+    pub fn fonts_path(mut self, fonts_path: Option<PathBuf>) -> Self {
+        self.fonts_path = fonts_path;
+        self
+    }
+
+    /// Sets the device context passed to components. When not set the
+    /// device in the rig's context is used.
+    pub fn device_context(mut self, device_context: Option<serde_json::Value>) -> Self {
+        self.device_context = device_context;
+        self
+    }
+
+    pub fn proxy(mut self, proxy: Option<String>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Sets the client used to download AOT compiled components, so that it can be
+    /// shared with the components loader. When not set a client using the proxy is created.
+    pub fn components_http_client(
+        mut self,
+        components_http_client: Option<Arc<ComponentHttpClient>>,
+    ) -> Self {
+        self.components_http_client = components_http_client;
+        self
+    }
+
+    pub fn http_cassette(mut self, http_cassette: Option<Arc<HttpCassette>>) -> Self {
+        self.http_cassette = http_cassette;
+        self
+    }
+
+    pub fn coerce_outputs(mut self, coerce_outputs: bool) -> Self {
+        self.coerce_outputs = coerce_outputs;
+        self
+    }
+
+    pub fn log_http(mut self, log_http: bool) -> Self {
+        self.log_http = log_http;
+        self
+    }
+
+    pub fn explain_permissions(mut self, explain_permissions: bool) -> Self {
+        self.explain_permissions = explain_permissions;
+        self
+    }
+
+    pub fn audit_log(mut self, audit_log: bool) -> Self {
+        self.audit_log = audit_log;
+        self
+    }
+
+    /// Sets the maximum number of components executing concurrently across the rig.
+    pub fn max_concurrent_components(mut self, max_concurrent_components: Option<usize>) -> Self {
+        self.max_concurrent_components = max_concurrent_components;
+        self
+    }
+
+    pub fn js_step_limit(mut self, js_step_limit: Option<u64>) -> Self {
+        self.js_step_limit = js_step_limit;
+        self
+    }
+
+    pub fn js_time_limit(mut self, js_time_limit: Option<Duration>) -> Self {
+        self.js_time_limit = js_time_limit;
+        self
+    }
+
+    pub fn max_output_bytes(mut self, max_output_bytes: Option<usize>) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    pub fn hash_algorithm(mut self, hash_algorithm: HashAlgorithm) -> Self {
+        self.hash_algorithm = hash_algorithm;
+        self
+    }
+
+    /// Sets whether to record the provenance of each component's evaluated input,
+    /// which is then available from `RigExecutionState::input_provenance`.
+    pub fn record_input_provenance(mut self, record_input_provenance: bool) -> Self {
+        self.record_input_provenance = record_input_provenance;
+        self
+    }
+
+    /// Sets whether to record each component run, so that the run can be saved as a debug rig.
+    pub fn run_record(mut self, run_record: bool) -> Self {
+        self.run_record = run_record;
+        self
+    }
+
+    /// Builds the options for running the rig, loading any fonts from the fonts path.
+    pub async fn build(self, rig: &Rig) -> RigSessionOptions {
+        let font_context = match &self.fonts_path {
+            None => FontContext::new(),
+            Some(fonts_path) => FontContext::new_with_path(fonts_path).await,
+        };
+
+        self.build_with_font_context(rig, font_context)
+    }
+
+    fn build_with_font_context(self, rig: &Rig, font_context: FontContext) -> RigSessionOptions {
+        let device_context = self
+            .device_context
+            .or_else(|| rig.context.clone().and_then(|c| c.device));
+        let rig_additional_context = get_rig_additional_context(&self.environment, device_context);
+        let components_http_client = self
+            .components_http_client
+            .unwrap_or_else(|| Arc::new(ComponentHttpClient::with_proxy(self.proxy.as_deref())));
+
+        RigSessionOptions {
+            base_path: self.base_path,
+            aot_path: self.aot_path,
+            aot_registry_url: self.aot_registry_url,
+            aot_registry_key: self.aot_registry_key,
+            environment: self.environment,
+            rig_additional_context,
+            proxy: self.proxy,
+            components_http_client,
+            http_cassette: self.http_cassette,
+            coerce_outputs: self.coerce_outputs,
+            log_http: self.log_http,
+            explain_permissions: self.explain_permissions,
+            audit_log: self.audit_log,
+            component_concurrency_limit: self
+                .max_concurrent_components
+                .map(ComponentConcurrencyLimit::new),
+            js_step_limit: self.js_step_limit,
+            js_time_limit: self.js_time_limit,
+            max_output_bytes: self.max_output_bytes,
+            hash_algorithm: self.hash_algorithm,
+            record_input_provenance: self.record_input_provenance,
+            run_record: self.run_record.then(RigRunRecord::new),
+            font_context: Arc::new(Mutex::new(font_context)),
+        }
     }
 }
 
@@ -208,3 +499,96 @@ fn get_rig_additional_context(
         "device": device_context,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+    use serde_json::json;
+
+    use crate::{Rigging, parse::types::DefaultRigContext};
+
+    use super::*;
+
+    fn rig_with_device(device: Option<serde_json::Value>) -> Rig {
+        let mut rig = Rig::for_test(Rigging {
+            components: Default::default(),
+        });
+        rig.context = device.map(|device| DefaultRigContext {
+            device: Some(device),
+        });
+        rig
+    }
+
+    #[slipway_test_async]
+    async fn it_should_default_unset_options() {
+        let rig = rig_with_device(None);
+
+        let options = RigSessionOptions::builder(Environment::for_test())
+            .build(&rig)
+            .await;
+
+        assert_eq!(options.base_path, PathBuf::from("."));
+        assert!(options.aot_path.is_none());
+        assert!(!options.coerce_outputs);
+        assert!(options.component_concurrency_limit.is_none());
+        assert!(options.run_record.is_none());
+        assert_eq!(options.hash_algorithm, HashAlgorithm::Sha256);
+        assert!(!options.record_input_provenance);
+        assert_eq!(
+            options.rig_additional_context,
+            json!({
+                "timezone": TEST_TIMEZONE,
+                "locale": TEST_LOCALE,
+                "device": null,
+            })
+        );
+    }
+
+    #[slipway_test_async]
+    async fn it_should_build_configured_options() {
+        let rig = rig_with_device(Some(json!({ "width": 100 })));
+
+        let options = RigSessionOptions::builder(Environment::for_test())
+            .base_path(PathBuf::from("/rigs"))
+            .coerce_outputs(true)
+            .max_concurrent_components(Some(2))
+            .max_output_bytes(Some(1024))
+            .hash_algorithm(HashAlgorithm::Blake3)
+            .run_record(true)
+            .build(&rig)
+            .await;
+
+        assert_eq!(options.base_path, PathBuf::from("/rigs"));
+        assert!(options.coerce_outputs);
+        assert_eq!(
+            options
+                .component_concurrency_limit
+                .as_ref()
+                .map(|l| l.max_concurrent_components()),
+            Some(2)
+        );
+        assert_eq!(options.max_output_bytes, Some(1024));
+        assert_eq!(options.hash_algorithm, HashAlgorithm::Blake3);
+        assert!(options.run_record.is_some());
+        assert_eq!(
+            options.rig_additional_context["device"],
+            json!({ "width": 100 })
+        );
+    }
+
+    #[test]
+    fn it_should_prefer_explicit_device_context() {
+        let rig = rig_with_device(Some(json!({ "width": 100 })));
+
+        let options = RigSessionOptions::new_for_test(
+            &rig,
+            Environment::for_test(),
+            Some(json!({ "width": 200 })),
+        );
+
+        assert_eq!(
+            options.rig_additional_context["device"],
+            json!({ "width": 200 })
+        );
+    }
+}
@@ -20,6 +20,7 @@ struct ComponentRunRecord {
     permissions: Vec<PermissionsOwned>,
     input: Arc<ComponentInput>,
     callouts: Option<HashMap<ComponentHandle, Callout>>,
+    runner: Option<String>,
 }
 
 impl RigRunRecord {
@@ -35,6 +36,7 @@ impl RigRunRecord {
         call_chain: Arc<CallChain>,
         input: Arc<ComponentInput>,
         callouts: Option<HashMap<ComponentHandle, Callout>>,
+        runner: Option<String>,
     ) {
         let component_handle = call_chain.unique_handle();
         let permissions = call_chain
@@ -55,6 +57,7 @@ impl RigRunRecord {
                 permissions,
                 input,
                 callouts,
+                runner,
             });
     }
 
@@ -85,6 +88,9 @@ impl RigRunRecord {
                             .collect(),
                     ),
                     callouts: record.callouts.clone(),
+                    runner: record.runner.clone(),
+                    memoize: None,
+                    skip: None,
                 },
             );
         }
@@ -101,6 +107,8 @@ impl RigRunRecord {
                 components: rigging,
             },
             context: None,
+            outputs: None,
+            templates: None,
         }
     }
 }
@@ -0,0 +1,123 @@
+use std::str::FromStr;
+
+use jsonpath_rust::{JsonPath, JsonPathValue};
+
+use crate::{ComponentRigging, SkipCondition, errors::RigError};
+
+/// Returns true if the component's skip condition is met by its resolved input,
+/// in which case the component is not run and its output is `null`.
+pub(super) fn should_skip(
+    rigging: &ComponentRigging,
+    input: &serde_json::Value,
+) -> Result<bool, RigError> {
+    let Some(condition) = rigging.skip.as_ref() else {
+        return Ok(false);
+    };
+
+    match condition {
+        SkipCondition::NullInput => Ok(input.is_null()),
+        SkipCondition::Unless(path) => {
+            let json_path =
+                JsonPath::from_str(path).map_err(|e| RigError::InvalidJsonPathExpression {
+                    location: "skip.unless".to_string(),
+                    error: e,
+                })?;
+
+            let is_met = json_path.find_slice(input).into_iter().any(|v| match v {
+                JsonPathValue::Slice(v, _) => is_truthy(v),
+                JsonPathValue::NewValue(v) => is_truthy(&v),
+                JsonPathValue::NoValue => false,
+            });
+
+            Ok(!is_met)
+        }
+    }
+}
+
+fn is_truthy(value: &serde_json::Value) -> bool {
+    !matches!(
+        value,
+        serde_json::Value::Null | serde_json::Value::Bool(false)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+    use serde_json::json;
+
+    use crate::{
+        BasicComponentCache, Instruction, Rig, RigSession, Rigging, RunMetadata, utils::ch,
+    };
+
+    use super::*;
+
+    fn skip_rigging(input: serde_json::Value, skip: Option<SkipCondition>) -> ComponentRigging {
+        let mut rigging = ComponentRigging::for_test("a", Some(input)).1;
+        rigging.skip = skip;
+        rigging
+    }
+
+    #[test]
+    fn it_should_skip_null_input() {
+        let rigging = skip_rigging(json!(null), Some(SkipCondition::NullInput));
+
+        assert!(should_skip(&rigging, &json!(null)).unwrap());
+        assert!(!should_skip(&rigging, &json!({})).unwrap());
+    }
+
+    #[test]
+    fn it_should_skip_unless_path_is_truthy() {
+        let rigging = skip_rigging(
+            json!({}),
+            Some(SkipCondition::Unless("$.items[0]".to_string())),
+        );
+
+        assert!(should_skip(&rigging, &json!({})).unwrap());
+        assert!(should_skip(&rigging, &json!({ "items": [] })).unwrap());
+        assert!(should_skip(&rigging, &json!({ "items": [null] })).unwrap());
+        assert!(should_skip(&rigging, &json!({ "items": [false] })).unwrap());
+        assert!(!should_skip(&rigging, &json!({ "items": [0] })).unwrap());
+        assert!(!should_skip(&rigging, &json!({ "items": [{ "x": 1 }] })).unwrap());
+    }
+
+    #[test]
+    fn it_should_not_skip_without_condition() {
+        let rigging = skip_rigging(json!(null), None);
+
+        assert!(!should_skip(&rigging, &json!(null)).unwrap());
+    }
+
+    #[slipway_test_async]
+    async fn it_should_mark_components_with_absent_optional_input_as_skipped() {
+        let rig = Rig::for_test(Rigging {
+            components: [
+                ComponentRigging::for_test("a", None),
+                (
+                    ch("b"),
+                    skip_rigging(json!("$$?a.missing"), Some(SkipCondition::NullInput)),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        });
+
+        let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+        let rig_session = RigSession::new_for_test(rig, &component_cache);
+
+        let state = rig_session.initialize().unwrap();
+        assert!(!state.component_states[&ch("a")].execution_skipped);
+
+        let state = state
+            .step(Instruction::SetOutput {
+                handle: ch("a"),
+                value: json!({ "present": 1 }),
+                metadata: RunMetadata::default(),
+            })
+            .unwrap();
+
+        let b = &state.component_states[&ch("b")];
+        assert!(b.execution_skipped);
+        assert_eq!(b.execution_input.as_ref().unwrap().value, json!(null));
+    }
+}
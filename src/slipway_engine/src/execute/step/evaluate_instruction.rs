@@ -1,9 +1,11 @@
 use std::sync::Arc;
 
 use crate::{
-    ComponentInputOverride, ComponentOutput, ComponentOutputOverride, RigExecutionState,
+    ComponentFailure, ComponentInputOverride, ComponentOutput, ComponentOutputOverride,
+    RigExecutionState,
     errors::RigError,
     execute::{
+        coerce_component_output::coerce_component_output,
         primitives::JsonMetadata,
         validate_component_io::{ValidationData, validate_component_io_from_session},
     },
@@ -30,8 +32,9 @@ pub(super) fn evaluate_instruction<'rig, 'cache>(
         }
         Instruction::SetOutputOverride { handle, value } => {
             let mut state = state;
+            let json_metadata =
+                JsonMetadata::from_value(&value, state.session.options.hash_algorithm);
             let component_state = state.get_component_state_mut(&handle)?;
-            let json_metadata = JsonMetadata::from_value(&value);
             component_state.output_override = Some(Arc::new(ComponentOutputOverride {
                 value,
                 json_metadata,
@@ -49,16 +52,31 @@ pub(super) fn evaluate_instruction<'rig, 'cache>(
             value,
             metadata,
         } => {
+            let mut value = value;
             {
                 let component_state = state.get_component_state(&handle)?;
-                validate_component_io_from_session(
-                    state.session,
-                    component_state,
-                    ValidationData::Output(&value),
-                )?;
+
+                // Skipped components output `null`, regardless of their output schema.
+                if !component_state.execution_skipped {
+                    if state.session.options.coerce_outputs {
+                        let component_definition = &state
+                            .session
+                            .component_cache
+                            .get(&component_state.rigging.component)
+                            .definition;
+                        coerce_component_output(&component_definition.output, &mut value);
+                    }
+
+                    validate_component_io_from_session(
+                        state.session,
+                        component_state,
+                        ValidationData::Output(&value),
+                    )?;
+                }
             }
 
             let mut state = state;
+            let hash_algorithm = state.session.options.hash_algorithm;
             let component_state = state.get_component_state_mut(&handle)?;
 
             let input = component_state
@@ -71,8 +89,9 @@ pub(super) fn evaluate_instruction<'rig, 'cache>(
                     ),
                 })?;
 
-            let json_metadata = JsonMetadata::from_value(&value);
+            let json_metadata = JsonMetadata::from_value(&value, hash_algorithm);
             component_state.output_override = None;
+            component_state.execution_failure = None;
             component_state.execution_output = Some(Arc::new(ComponentOutput {
                 value,
                 input_hash_used: input.json_metadata.hash.clone(),
@@ -80,6 +99,25 @@ pub(super) fn evaluate_instruction<'rig, 'cache>(
                 run_metadata: metadata,
             }));
 
+            Ok(state)
+        }
+        Instruction::SetFailure { handle, message } => {
+            let mut state = state;
+            let hash_algorithm = state.session.options.hash_algorithm;
+            let component_state = state.get_component_state_mut(&handle)?;
+
+            let input = component_state
+                .execution_input
+                .as_ref()
+                .ok_or(RigError::StepFailed {
+                    error: format!("component {} cannot currently be executed", handle),
+                })?;
+
+            component_state.execution_failure = Some(Arc::new(ComponentFailure {
+                message,
+                input_hash_used: input.json_metadata.hash.clone(),
+            }));
+
             Ok(state)
         }
     }
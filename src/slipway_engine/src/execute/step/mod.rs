@@ -43,6 +43,13 @@ pub enum Instruction {
         value: serde_json::Value,
         metadata: RunMetadata,
     },
+
+    // Marks a component as having failed to run. Components which depend on it
+    // will not become ready to execute.
+    SetFailure {
+        handle: ComponentHandle,
+        message: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
@@ -51,6 +58,22 @@ pub struct RunMetadata {
     pub prepare_component_duration: Duration,
     pub call_duration: Duration,
     pub process_output_duration: Duration,
+
+    /// For components which execute a sub-rig, such as Fragments, the metadata
+    /// of each component in the sub-rig.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nested: Option<Vec<NestedRunMetadata>>,
+
+    /// For memoized components which reused the output of an identical component
+    /// rather than running, the handle of that component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memoized_from: Option<ComponentHandle>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NestedRunMetadata {
+    pub handle: ComponentHandle,
+    pub metadata: RunMetadata,
 }
 
 impl RunMetadata {
@@ -80,6 +103,14 @@ impl RunMetadata {
                 .process_output_duration
                 .checked_add(other.process_output_duration)
                 .expect("Duration overflow"),
+            nested: match (&self.nested, &other.nested) {
+                (None, None) => None,
+                (a, b) => Some(a.iter().chain(b.iter()).flatten().cloned().collect()),
+            },
+            memoized_from: self
+                .memoized_from
+                .clone()
+                .or_else(|| other.memoized_from.clone()),
         }
     }
 }
@@ -4,14 +4,19 @@
 use std::ops::Deref;
 use std::sync::LazyLock;
 
+pub use execute::InputProvenance;
 pub use execute::component_execution_data::permissions::*;
 pub use execute::component_execution_data::*;
 pub use execute::component_runner::*;
 pub use execute::component_state::{
-    ComponentInput, ComponentInputOverride, ComponentOutput, ComponentOutputOverride,
-    ComponentState,
+    ComponentFailure, ComponentInput, ComponentInputOverride, ComponentOutput,
+    ComponentOutputOverride, ComponentState,
 };
+pub use execute::concurrency_limit::ComponentConcurrencyLimit;
+pub use execute::deduplicate_memoized_components;
 pub use execute::fonts::*;
+pub use execute::http_cassette::*;
+pub use execute::output_limit::*;
 pub use execute::primitives::*;
 pub use execute::rig_execution_state::*;
 pub use execute::rig_session::*;
@@ -11,7 +11,10 @@ use super::super::component_io_abstractions::ComponentIOAbstractions;
 use crate::{
     ComponentFiles, ComponentFilesLoader, LoadedComponent, SlipwayReference,
     errors::{ComponentLoadError, ComponentLoadErrorInner},
-    load::{SLIPWAY_COMPONENT_FILE_NAME, is_safe_path::is_safe_path},
+    load::{
+        SLIPWAY_COMPONENT_FILE_NAME, SLIPWAY_COMPONENT_YAML_FILE_NAMES, is_safe_path::is_safe_path,
+    },
+    yaml_to_json,
 };
 
 pub(super) async fn load_from_directory(
@@ -19,10 +22,8 @@ pub(super) async fn load_from_directory(
     path: &Path,
     io_abstractions: Arc<dyn ComponentIOAbstractions>,
 ) -> Result<LoadedComponent, ComponentLoadError> {
-    let definition_path = path.join(SLIPWAY_COMPONENT_FILE_NAME);
-    let definition_string = io_abstractions
-        .load_text(&definition_path, component_reference)
-        .await?;
+    let definition_string =
+        load_definition(component_reference, path, io_abstractions.as_ref()).await?;
 
     let component_files = Arc::new(ComponentFiles::new(Box::new(
         DirectoryComponentFilesLoader::new(
@@ -39,6 +40,34 @@ pub(super) async fn load_from_directory(
     ))
 }
 
+/// Loads the component definition as a JSON string, converting it from YAML
+/// if the directory only contains a YAML definition.
+async fn load_definition(
+    component_reference: &SlipwayReference,
+    path: &Path,
+    io_abstractions: &dyn ComponentIOAbstractions,
+) -> Result<String, ComponentLoadError> {
+    let definition_path = path.join(SLIPWAY_COMPONENT_FILE_NAME);
+
+    if !io_abstractions.exists(&definition_path).await {
+        for yaml_file_name in SLIPWAY_COMPONENT_YAML_FILE_NAMES {
+            let yaml_path = path.join(yaml_file_name);
+            if io_abstractions.exists(&yaml_path).await {
+                let yaml_string = io_abstractions
+                    .load_text(&yaml_path, component_reference)
+                    .await?;
+                let json = yaml_to_json(&yaml_string)
+                    .map_err(|e| ComponentLoadError::new(component_reference, e))?;
+                return Ok(json.to_string());
+            }
+        }
+    }
+
+    io_abstractions
+        .load_text(&definition_path, component_reference)
+        .await
+}
+
 struct DirectoryComponentFilesLoader {
     io_abstractions: Arc<dyn ComponentIOAbstractions>,
     component_reference: SlipwayReference,
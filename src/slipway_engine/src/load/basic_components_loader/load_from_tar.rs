@@ -1,7 +1,7 @@
 use std::{
     collections::HashMap,
     io::{Read, SeekFrom},
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
     sync::Arc,
 };
 
@@ -19,14 +19,39 @@ use super::super::component_io_abstractions::ComponentIOAbstractions;
 
 type FileEntriesResult = (Box<dyn FileHandle>, HashMap<String, FileEntry>);
 
+const DEFAULT_MAX_FILE_SIZE: u64 = 256 * 1024 * 1024;
+const DEFAULT_MAX_TOTAL_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Limits applied when reading component TAR files, so that untrusted
+/// archives cannot exhaust memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TarLimits {
+    /// The maximum size in bytes of any single file in the archive.
+    pub max_file_size: u64,
+
+    /// The maximum combined size in bytes of all files in the archive.
+    pub max_total_size: u64,
+}
+
+impl Default for TarLimits {
+    fn default() -> Self {
+        Self {
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            max_total_size: DEFAULT_MAX_TOTAL_SIZE,
+        }
+    }
+}
+
 pub(super) async fn load_from_tar(
     component_reference: &SlipwayReference,
     path: &Path,
+    limits: &TarLimits,
     io_abstractions: Arc<dyn ComponentIOAbstractions>,
 ) -> Result<LoadedComponent, ComponentLoadError> {
     let file = io_abstractions.load_file(path, component_reference).await?;
 
-    let (mut file, all_files) = get_all_file_entries(file, component_reference, path).await?;
+    let (mut file, all_files) =
+        get_all_file_entries(file, component_reference, path, *limits).await?;
 
     let Some(definition_entry) = all_files.get(SLIPWAY_COMPONENT_FILE_NAME) else {
         return Err(ComponentLoadError::new(
@@ -168,6 +193,7 @@ async fn get_all_file_entries(
     file: Box<dyn FileHandle>,
     component_reference: &SlipwayReference,
     path: &Path,
+    limits: TarLimits,
 ) -> Result<FileEntriesResult, ComponentLoadError> {
     // Convert to a blocking std::fs::File.
     let std_file = async_read_to_sync(file);
@@ -179,6 +205,7 @@ async fn get_all_file_entries(
     let (file, all_files) = tokio::task::spawn_blocking(move || {
         let mut a = Archive::new(std_file);
         let mut all_files = HashMap::new();
+        let mut total_length: u64 = 0;
         for file in map_io_error(
             a.entries(),
             &component_reference_clone,
@@ -201,6 +228,15 @@ async fn get_all_file_entries(
                 "Failed to get file entry path",
             )?;
 
+            if !is_safe_entry_path(&entry_path) {
+                return Err(archive_rejected(
+                    &component_reference_clone,
+                    &path,
+                    &entry_path.to_string_lossy(),
+                    "Entry paths must be relative and must not contain \"..\"".to_string(),
+                ));
+            }
+
             // Remove the leading "./" from the path if it exists.
             let file_path_raw = entry_path.to_string_lossy().to_string();
             let file_path = match file_path_raw.strip_prefix("./") {
@@ -216,6 +252,32 @@ async fn get_all_file_entries(
                 &file_path,
                 "Failed to get file length",
             )?;
+
+            if length > limits.max_file_size {
+                return Err(archive_rejected(
+                    &component_reference_clone,
+                    &path,
+                    &file_path,
+                    format!(
+                        "File size of {} bytes exceeds the limit of {} bytes",
+                        length, limits.max_file_size
+                    ),
+                ));
+            }
+
+            total_length = total_length.saturating_add(length);
+            if total_length > limits.max_total_size {
+                return Err(archive_rejected(
+                    &component_reference_clone,
+                    &path,
+                    &file_path,
+                    format!(
+                        "Total size of files exceeds the limit of {} bytes",
+                        limits.max_total_size
+                    ),
+                ));
+            }
+
             let offset = file.raw_file_position();
 
             let file_entry = FileEntry { offset, length };
@@ -278,6 +340,27 @@ fn map_tar_io_error<T>(
     })
 }
 
+pub(in crate::load) fn is_safe_entry_path(entry_path: &Path) -> bool {
+    entry_path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+fn archive_rejected(
+    reference: &SlipwayReference,
+    tar_path: &Path,
+    inner_path: &str,
+    error: String,
+) -> ComponentLoadError {
+    ComponentLoadError::new(
+        reference,
+        ComponentLoadErrorInner::ArchiveRejected {
+            path: format!("{}:{}", tar_path.to_string_lossy(), inner_path),
+            error,
+        },
+    )
+}
+
 struct FileEntry {
     offset: u64,
     length: u64,
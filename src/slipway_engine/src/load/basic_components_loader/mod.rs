@@ -1,13 +1,18 @@
 use std::{
     borrow::Cow,
+    collections::HashMap,
     path::{Path, PathBuf},
     sync::Arc,
 };
 
+use super::component_download_headers::ComponentDownloadHeaders;
+use super::component_http_client::ComponentHttpClient;
 use super::component_io_abstractions::{ComponentIOAbstractions, ComponentIOAbstractionsImpl};
 use async_trait::async_trait;
 use futures::future::join_all;
+use semver::Version;
 use tracing::{debug, error, trace};
+use url::Url;
 
 use crate::{
     SlipwayReference,
@@ -20,12 +25,24 @@ use super::{ComponentsLoader, LoadedComponent, special_components::load_special_
 mod load_from_directory;
 mod load_from_tar;
 
-const DEFAULT_REGISTRY_LOOKUP_URL: &str =
+pub use load_from_tar::TarLimits;
+pub(in crate::load) use load_from_tar::is_safe_entry_path;
+
+pub const DEFAULT_REGISTRY_LOOKUP_URL: &str =
     "https://registry.slipway.co/components/{publisher}.{name}.{version}.tar";
 
-fn get_default_slipway_components_cache_dir() -> PathBuf {
-    let home_dir = dirs::home_dir().expect("Home directory required for caching components");
-    home_dir.join(".slipway/components")
+/// The default maximum number of components which are downloaded at the same time.
+pub const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 8;
+
+pub fn get_default_slipway_components_cache_dir() -> PathBuf {
+    try_get_default_slipway_components_cache_dir()
+        .expect("Home directory required for caching components")
+}
+
+/// Returns the default components cache directory, or `None` if the
+/// home directory can't be determined.
+pub fn try_get_default_slipway_components_cache_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home_dir| home_dir.join(".slipway/components"))
 }
 
 pub fn clear_components_cache() {
@@ -43,6 +60,10 @@ pub fn clear_components_cache() {
 pub struct BasicComponentsLoader {
     registry_lookup_urls: Vec<String>,
     local_base_directory: PathBuf,
+    tar_limits: TarLimits,
+    component_paths: HashMap<SlipwayReference, PathBuf>,
+    allow_non_http_git_urls: bool,
+    http_client: Arc<ComponentHttpClient>,
     io_abstractions: Arc<dyn ComponentIOAbstractions>,
 }
 
@@ -51,6 +72,12 @@ pub struct BasicComponentsLoaderBuilder {
     registry_lookup_urls: Vec<String>,
     components_cache_path: Option<PathBuf>,
     local_base_directory: Option<PathBuf>,
+    tar_limits: Option<TarLimits>,
+    proxy: Option<String>,
+    headers: ComponentDownloadHeaders,
+    max_concurrent_downloads: usize,
+    component_paths: HashMap<SlipwayReference, PathBuf>,
+    allow_non_http_git_urls: bool,
     io_abstractions: Option<Arc<dyn ComponentIOAbstractions>>,
 }
 
@@ -61,6 +88,12 @@ impl BasicComponentsLoaderBuilder {
             registry_lookup_urls: vec![],
             components_cache_path: None,
             local_base_directory: None,
+            tar_limits: None,
+            proxy: None,
+            headers: ComponentDownloadHeaders::default(),
+            max_concurrent_downloads: DEFAULT_MAX_CONCURRENT_DOWNLOADS,
+            component_paths: HashMap::new(),
+            allow_non_http_git_urls: false,
             io_abstractions: None,
         }
     }
@@ -92,6 +125,62 @@ impl BasicComponentsLoaderBuilder {
         self
     }
 
+    pub fn tar_limits(mut self, tar_limits: TarLimits) -> Self {
+        self.tar_limits = Some(tar_limits);
+        self
+    }
+
+    /// Sets an explicit proxy URL for downloading components. When not set the
+    /// `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables are used.
+    pub fn proxy(mut self, proxy: Option<String>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Sets the User-Agent used when downloading components.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.headers.set_user_agent(user_agent.to_string());
+        self
+    }
+
+    /// Adds a header to all HTTP component downloads. Credential headers, such as
+    /// `Authorization`, are only sent to URLs from the configured registries.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.add_header(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Adds a header to HTTP component downloads from a single registry, overriding
+    /// any header with the same name added with `header`.
+    /// The registry is identified by its lookup URL, as passed to `registry_lookup_url`.
+    pub fn registry_header(mut self, registry_url: &str, name: &str, value: &str) -> Self {
+        self.headers
+            .add_registry_header(registry_url, name.to_string(), value.to_string());
+        self
+    }
+
+    /// Sets the maximum number of components which are downloaded at the same time,
+    /// to avoid being rate limited by registries. The minimum is one.
+    pub fn max_concurrent_downloads(mut self, max_concurrent_downloads: usize) -> Self {
+        self.max_concurrent_downloads = max_concurrent_downloads;
+        self
+    }
+
+    /// Loads the referenced component from the given local path rather than resolving
+    /// the reference. This is used to run rigs with their components bundled alongside.
+    pub fn component_path(mut self, reference: SlipwayReference, path: &Path) -> Self {
+        self.component_paths.insert(reference, path.to_owned());
+        self
+    }
+
+    /// Allows git components to be fetched from `ssh://` and `file://` URLs.
+    /// These are disabled by default because they can reach hosts and paths
+    /// using the user's own credentials and configuration.
+    pub fn allow_non_http_git_urls(mut self, allow_non_http_git_urls: bool) -> Self {
+        self.allow_non_http_git_urls = allow_non_http_git_urls;
+        self
+    }
+
     fn io_abstractions(mut self, io_abstractions: Arc<dyn ComponentIOAbstractions>) -> Self {
         self.io_abstractions = Some(io_abstractions);
         self
@@ -114,14 +203,31 @@ impl BasicComponentsLoaderBuilder {
             .local_base_directory
             .unwrap_or_else(|| PathBuf::from(""));
 
-        let io_abstractions = self
-            .io_abstractions
-            .unwrap_or_else(|| Arc::new(ComponentIOAbstractionsImpl::new(components_cache_path)));
+        let tar_limits = self.tar_limits.unwrap_or_default();
+
+        let mut headers = self.headers;
+        headers.set_registries(&registry_lookup_urls);
+
+        let http_client = Arc::new(ComponentHttpClient::new(self.proxy.as_deref(), headers));
+
+        let io_abstractions = self.io_abstractions.unwrap_or_else(|| {
+            Arc::new(ComponentIOAbstractionsImpl::new(
+                components_cache_path,
+                Arc::clone(&http_client),
+                self.max_concurrent_downloads,
+                self.allow_non_http_git_urls,
+                tar_limits,
+            ))
+        });
 
         BasicComponentsLoader {
             registry_lookup_urls,
             io_abstractions,
             local_base_directory,
+            tar_limits,
+            component_paths: self.component_paths,
+            allow_non_http_git_urls: self.allow_non_http_git_urls,
+            http_client,
         }
     }
 }
@@ -136,6 +242,41 @@ impl BasicComponentsLoader {
     pub fn builder() -> BasicComponentsLoaderBuilder {
         BasicComponentsLoaderBuilder::new()
     }
+
+    /// Returns the registry lookup URLs, in the order they are searched.
+    pub fn registry_lookup_urls(&self) -> &[String] {
+        &self.registry_lookup_urls
+    }
+
+    /// Returns the HTTP client used to download components, which applies the
+    /// configured proxy and headers, including registry credentials.
+    pub fn http_client(&self) -> &Arc<ComponentHttpClient> {
+        &self.http_client
+    }
+}
+
+fn is_http_git_url(url: &Url) -> bool {
+    matches!(url.scheme(), "https" | "http")
+}
+
+/// Interpolates the component's publisher, name and version into a registry lookup URL.
+pub fn resolve_registry_lookup_url(
+    registry_lookup_url: &str,
+    publisher: &str,
+    name: &str,
+    version: &Version,
+) -> String {
+    let (namespace, localname) = match name.split_once("__") {
+        Some((ns, l)) => (ns, l),
+        None => (name, name),
+    };
+
+    registry_lookup_url
+        .replace("{publisher}", publisher)
+        .replace("{name}", name)
+        .replace("{namespace}", namespace)
+        .replace("{localname}", localname)
+        .replace("{version}", &version.to_string())
 }
 
 impl Default for BasicComponentsLoader {
@@ -150,8 +291,34 @@ impl ComponentsLoader for BasicComponentsLoader {
         &self,
         component_references: &[SlipwayReference],
     ) -> Vec<Result<LoadedComponent, ComponentLoadError>> {
-        let futures = component_references.iter().map(|r| self.load_component(r));
-        join_all(futures).await
+        // References which only differ in how they are written are loaded once.
+        let mut canonical_indexes: HashMap<String, usize> = HashMap::new();
+        let mut distinct_references = Vec::new();
+        let reference_indexes: Vec<usize> = component_references
+            .iter()
+            .map(|r| {
+                *canonical_indexes.entry(r.canonical()).or_insert_with(|| {
+                    distinct_references.push(r);
+                    distinct_references.len() - 1
+                })
+            })
+            .collect();
+
+        let futures = distinct_references.iter().map(|r| self.load_component(r));
+        let results = join_all(futures).await;
+
+        component_references
+            .iter()
+            .zip(reference_indexes)
+            .map(|(reference, index)| match &results[index] {
+                Ok(c) => Ok(LoadedComponent::new(
+                    reference.clone(),
+                    c.definition.clone(),
+                    Arc::clone(&c.files),
+                )),
+                Err(e) => Err(ComponentLoadError::new(reference, e.error.clone())),
+            })
+            .collect()
     }
 }
 
@@ -160,7 +327,14 @@ impl BasicComponentsLoader {
         &self,
         component_reference: &SlipwayReference,
     ) -> Result<LoadedComponent, ComponentLoadError> {
-        debug!("Loading component: {}", component_reference);
+        debug!("Loading component: {}", component_reference.canonical());
+
+        if let Some(path) = self.component_paths.get(component_reference) {
+            return self
+                .load_component_from_path(component_reference, path)
+                .await;
+        }
+
         match component_reference {
             SlipwayReference::Special(inner) => Ok(load_special_component(inner)),
             SlipwayReference::Local { path: _ } => {
@@ -169,6 +343,9 @@ impl BasicComponentsLoader {
             SlipwayReference::Http { url: _ } => {
                 self.load_http_component(component_reference).await
             }
+            SlipwayReference::Git { url: _, rev: _ } => {
+                self.load_git_component(component_reference).await
+            }
             SlipwayReference::Registry {
                 publisher,
                 name,
@@ -184,18 +361,9 @@ impl BasicComponentsLoader {
                     ));
                 }
 
-                let (namespace, localname) = match name.split_once("__") {
-                    Some((ns, l)) => (ns, l),
-                    None => (name.as_str(), name.as_str()),
-                };
-
                 for registry_lookup_url in self.registry_lookup_urls.iter() {
-                    let resolved_registry_lookup_url = registry_lookup_url
-                        .replace("{publisher}", publisher)
-                        .replace("{name}", name)
-                        .replace("{namespace}", namespace)
-                        .replace("{localname}", localname)
-                        .replace("{version}", &version.to_string());
+                    let resolved_registry_lookup_url =
+                        resolve_registry_lookup_url(registry_lookup_url, publisher, name, version);
 
                     let processed_url =
                         process_url_str(&resolved_registry_lookup_url).map_err(|e| {
@@ -297,6 +465,70 @@ impl BasicComponentsLoader {
         }
     }
 
+    async fn load_git_component(
+        &self,
+        component_reference: &SlipwayReference,
+    ) -> Result<LoadedComponent, ComponentLoadError> {
+        let SlipwayReference::Git { url, rev } = component_reference else {
+            panic!(
+                "Expected SlipwayReference::Git, got: {:?}",
+                component_reference
+            );
+        };
+
+        if !self.allow_non_http_git_urls && !is_http_git_url(url) {
+            return Err(ComponentLoadError::new(
+                component_reference,
+                ComponentLoadErrorInner::FileLoadFailed {
+                    path: url.to_string(),
+                    error: format!(
+                        "Git URLs with the \"{}\" scheme are disabled. Only http and https git URLs are allowed unless non-HTTP git URLs are explicitly allowed.",
+                        url.scheme()
+                    ),
+                },
+            ));
+        }
+
+        let local_path = self
+            .io_abstractions
+            .cache_git_repository(url, rev, component_reference)
+            .await?;
+
+        let local_reference = SlipwayReference::Local { path: local_path };
+
+        let result = self.load_local_component(&local_reference).await;
+
+        match result {
+            Err(e) => Err(ComponentLoadError::new(component_reference, e.error)),
+            Ok(c) => Ok(LoadedComponent::new(
+                component_reference.clone(),
+                c.definition,
+                c.files,
+            )),
+        }
+    }
+
+    async fn load_component_from_path(
+        &self,
+        component_reference: &SlipwayReference,
+        path: &Path,
+    ) -> Result<LoadedComponent, ComponentLoadError> {
+        let local_reference = SlipwayReference::Local {
+            path: path.to_owned(),
+        };
+
+        let result = self.load_local_component(&local_reference).await;
+
+        match result {
+            Err(e) => Err(ComponentLoadError::new(component_reference, e.error)),
+            Ok(c) => Ok(LoadedComponent::new(
+                component_reference.clone(),
+                c.definition,
+                c.files,
+            )),
+        }
+    }
+
     async fn load_local_component(
         &self,
         component_reference: &SlipwayReference,
@@ -325,6 +557,7 @@ impl BasicComponentsLoader {
             load_from_tar::load_from_tar(
                 component_reference,
                 &path,
+                &self.tar_limits,
                 Arc::clone(&self.io_abstractions),
             )
             .await
@@ -439,6 +672,16 @@ mod tests {
                 Ok(PathBuf::from_str(file_path_str).unwrap())
             }
 
+            async fn cache_git_repository(
+                &self,
+                url: &Url,
+                rev: &str,
+                _component_reference: &SlipwayReference,
+            ) -> Result<PathBuf, ComponentLoadError> {
+                let file_path_str = self.url_to_file.get(&format!("{url}#{rev}")).unwrap();
+                Ok(PathBuf::from_str(file_path_str).unwrap())
+            }
+
             async fn exists(&self, path: &Path) -> bool {
                 self.map.bin.contains_key(path.to_string_lossy().as_ref())
                     || self.map.text.contains_key(path.to_string_lossy().as_ref())
@@ -501,6 +744,86 @@ mod tests {
             .await;
         }
 
+        #[slipway_test_async]
+        async fn it_should_load_component_from_git_repository() {
+            // This test does not test the actual fetching of the repository, but rather the loading
+            // of the component directory once it has been fetched.
+            const GIT_URL: &str = "https://example.com/my_component.git";
+            let component_reference = SlipwayReference::Git {
+                url: Url::parse(GIT_URL).unwrap(),
+                rev: "v1.0.0".to_string(),
+            };
+
+            let path_to_component = "/cache/git/my_component";
+            let definition_content = r#"{ "definition": "1" }"#;
+
+            let io_abstractions = MockComponentIOAbstractions {
+                component_path: PathBuf::from_str(path_to_component).unwrap(),
+                component_reference: SlipwayReference::Local {
+                    path: PathBuf::from_str(path_to_component).unwrap(),
+                },
+                url_to_file: HashMap::from([(
+                    format!("{GIT_URL}#v1.0.0"),
+                    path_to_component.to_string(),
+                )]),
+                map: MockComponentFileLoaderInner {
+                    text: HashMap::from([(
+                        format!("{}/slipway_component.json", path_to_component),
+                        definition_content.to_string(),
+                    )]),
+                    bin: HashMap::new(),
+                },
+            };
+
+            let loader = BasicComponentsLoaderBuilder::new()
+                .io_abstractions(Arc::new(io_abstractions))
+                .build();
+
+            let result = loader
+                .load_components(std::slice::from_ref(&component_reference))
+                .await;
+
+            assert_eq!(result.len(), 1);
+
+            let loaded = result.first().unwrap().as_ref().unwrap();
+
+            assert_eq!(loaded.reference, component_reference);
+            assert_eq!(loaded.definition, definition_content);
+        }
+
+        #[slipway_test_async]
+        async fn it_should_load_yaml_component_definition_as_json() {
+            let path_to_component = "path/to/my_component";
+            let component_reference = SlipwayReference::Local {
+                path: PathBuf::from_str(path_to_component).unwrap(),
+            };
+
+            let io_abstractions = MockComponentIOAbstractions {
+                component_path: PathBuf::from_str(path_to_component).unwrap(),
+                component_reference: component_reference.clone(),
+                url_to_file: HashMap::new(),
+                map: MockComponentFileLoaderInner {
+                    text: HashMap::from([(
+                        format!("{}/slipway_component.yaml", path_to_component),
+                        "definition: \"1\"\n".to_string(),
+                    )]),
+                    bin: HashMap::new(),
+                },
+            };
+
+            let loader = BasicComponentsLoaderBuilder::new()
+                .io_abstractions(Arc::new(io_abstractions))
+                .build();
+
+            let result = loader.load_components(&[component_reference]).await;
+
+            assert_eq!(result.len(), 1);
+
+            let loaded = result.first().unwrap().as_ref().unwrap();
+
+            assert_eq!(loaded.definition, r#"{"definition":"1"}"#);
+        }
+
         async fn run_load_all_component_files_tests(
             component_reference: SlipwayReference,
             path_to_component: &str,
@@ -624,6 +947,15 @@ mod tests {
                 unimplemented!()
             }
 
+            async fn cache_git_repository(
+                &self,
+                _url: &Url,
+                _rev: &str,
+                _component_reference: &SlipwayReference,
+            ) -> Result<PathBuf, ComponentLoadError> {
+                unimplemented!()
+            }
+
             async fn exists(&self, _path: &Path) -> bool {
                 true
             }
@@ -768,6 +1100,15 @@ mod tests {
                     })
             }
 
+            async fn cache_git_repository(
+                &self,
+                _url: &Url,
+                _rev: &str,
+                _component_reference: &SlipwayReference,
+            ) -> Result<PathBuf, ComponentLoadError> {
+                unimplemented!()
+            }
+
             async fn exists(&self, path: &Path) -> bool {
                 self.files.contains_key(path.to_string_lossy().as_ref())
             }
@@ -1071,6 +1412,34 @@ mod tests {
             .await;
         }
 
+        #[slipway_test_async]
+        async fn it_should_load_component_from_specified_path() {
+            let component_reference = SlipwayReference::Registry {
+                publisher: "p1".to_string(),
+                name: "n1".to_string(),
+                version: Version::parse("1.2.3").expect("Invalid version"),
+            };
+
+            let data = MockData::new();
+            let tar_data = create_tar(&data);
+
+            let io_abstractions = MockComponentIOAbstractions {
+                files: HashMap::from([("bundle/components/0.tar".to_string(), tar_data.clone())]),
+                url_to_file_map: HashMap::new(),
+            };
+
+            let loader = BasicComponentsLoaderBuilder::new()
+                .without_default_registry()
+                .component_path(
+                    component_reference.clone(),
+                    Path::new("bundle/components/0.tar"),
+                )
+                .io_abstractions(Arc::new(io_abstractions))
+                .build();
+
+            assert_result(loader, component_reference, data, "bundle/components/0.tar").await;
+        }
+
         #[slipway_test_async]
         async fn it_should_load_from_local_registry() {
             const URL: &str = "file:path/to/{publisher}.{name}.{version}.tar";
@@ -1129,5 +1498,119 @@ mod tests {
             )
             .await;
         }
+
+        fn create_tar_with_raw_entry_name(data: &MockData, entry_name: &str) -> Vec<u8> {
+            let mut buffer = Cursor::new(Vec::new());
+            {
+                let mut builder = Builder::new(&mut buffer);
+
+                add_text_to_tar(
+                    SLIPWAY_COMPONENT_FILE_NAME,
+                    data.definition_content,
+                    &mut builder,
+                );
+
+                // The builder refuses to write unsafe paths, so set the name bytes directly.
+                let mut header = Header::new_gnu();
+                let name = &mut header.as_gnu_mut().unwrap().name;
+                name[..entry_name.len()].copy_from_slice(entry_name.as_bytes());
+                header.set_size(data.file1_content.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder
+                    .append(&header, data.file1_content.as_bytes())
+                    .unwrap();
+
+                builder.finish().unwrap();
+            }
+
+            buffer.into_inner()
+        }
+
+        async fn load_rejected_tar(tar_data: Vec<u8>, limits: TarLimits) -> (String, String) {
+            let component_reference = SlipwayReference::Local {
+                path: PathBuf::from_str("path/to/my_component.tar").unwrap(),
+            };
+
+            let io_abstractions = MockComponentIOAbstractions {
+                files: HashMap::from([("path/to/my_component.tar".to_string(), tar_data)]),
+                url_to_file_map: HashMap::new(),
+            };
+
+            let loader = BasicComponentsLoaderBuilder::new()
+                .tar_limits(limits)
+                .io_abstractions(Arc::new(io_abstractions))
+                .build();
+
+            let mut result = loader.load_components(&[component_reference]).await;
+
+            match result.remove(0) {
+                Err(ComponentLoadError {
+                    error: ComponentLoadErrorInner::ArchiveRejected { path, error },
+                    ..
+                }) => (path, error),
+                Err(e) => panic!("Unexpected error: {:?}", e),
+                Ok(_) => panic!("Expected the archive to be rejected"),
+            }
+        }
+
+        #[slipway_test_async]
+        async fn it_should_reject_tar_entries_containing_parent_directories() {
+            let data = MockData::new();
+            let tar_data = create_tar_with_raw_entry_name(&data, "../file1.json");
+
+            let (path, _) = load_rejected_tar(tar_data, TarLimits::default()).await;
+
+            assert_eq!(path, "path/to/my_component.tar:../file1.json");
+        }
+
+        #[slipway_test_async]
+        async fn it_should_reject_tar_entries_with_absolute_paths() {
+            let data = MockData::new();
+            let tar_data = create_tar_with_raw_entry_name(&data, "/etc/file1.json");
+
+            let (path, _) = load_rejected_tar(tar_data, TarLimits::default()).await;
+
+            assert_eq!(path, "path/to/my_component.tar:/etc/file1.json");
+        }
+
+        #[slipway_test_async]
+        async fn it_should_reject_tar_files_larger_than_the_file_size_limit() {
+            let data = MockData::new();
+            let tar_data = create_tar(&data);
+
+            let (path, error) = load_rejected_tar(
+                tar_data,
+                TarLimits {
+                    max_file_size: 10,
+                    ..TarLimits::default()
+                },
+            )
+            .await;
+
+            assert_eq!(
+                path,
+                format!("path/to/my_component.tar:{}", SLIPWAY_COMPONENT_FILE_NAME)
+            );
+            assert!(error.contains("exceeds the limit of 10 bytes"));
+        }
+
+        #[slipway_test_async]
+        async fn it_should_reject_tars_larger_than_the_total_size_limit() {
+            let data = MockData::new();
+            let tar_data = create_tar(&data);
+
+            let (path, error) = load_rejected_tar(
+                tar_data,
+                TarLimits {
+                    max_total_size: 30,
+                    ..TarLimits::default()
+                },
+            )
+            .await;
+
+            assert_eq!(path, "path/to/my_component.tar:file1.json");
+            assert!(error.contains("Total size of files exceeds the limit of 30 bytes"));
+        }
     }
 }
@@ -0,0 +1,266 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::Deserialize;
+use tar::Archive;
+use tracing::debug;
+
+use super::{
+    SLIPWAY_COMPONENT_FILE_NAME,
+    component_io_abstractions::{EXTRACTED_CACHE_DIRECTORY, GIT_CACHE_DIRECTORY},
+};
+
+const COMPONENT_FILE_EXTENSION: &str = "tar";
+
+/// A downloaded component, or fetched git repository, in the local components cache.
+///
+/// Downloaded components are extracted to directories, but TAR files downloaded
+/// by earlier versions are also listed so that they can be removed.
+#[derive(Debug, Clone)]
+pub struct CachedComponent {
+    pub path: PathBuf,
+
+    /// The registry reference of the component, if its definition could be read.
+    pub reference: Option<String>,
+
+    /// The size of the component on disk, including every file in component directories.
+    pub size_bytes: u64,
+
+    /// When the component was last downloaded or loaded from the cache.
+    pub last_used: SystemTime,
+}
+
+/// Lists the components in the given components cache directory,
+/// returning an empty list if the directory doesn't exist.
+pub fn list_cached_components(cache_path: &Path) -> std::io::Result<Vec<CachedComponent>> {
+    let mut components = Vec::new();
+
+    for path in read_dir_paths(cache_path)? {
+        if path.is_file()
+            && path
+                .extension()
+                .is_some_and(|e| e == COMPONENT_FILE_EXTENSION)
+        {
+            components.push(CachedComponent {
+                reference: read_tar_component_reference(&path),
+                size_bytes: path.metadata()?.len(),
+                last_used: last_used(&path)?,
+                path,
+            });
+        }
+    }
+
+    let directory_paths = read_dir_paths(&cache_path.join(EXTRACTED_CACHE_DIRECTORY))?
+        .into_iter()
+        .chain(read_dir_paths(&cache_path.join(GIT_CACHE_DIRECTORY))?);
+
+    for path in directory_paths {
+        if path.is_dir() {
+            components.push(CachedComponent {
+                reference: read_directory_component_reference(&path),
+                size_bytes: directory_size(&path)?,
+                last_used: last_used(&path)?,
+                path,
+            });
+        }
+    }
+
+    components.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(components)
+}
+
+/// Removes the component from the components cache.
+pub fn remove_cached_component(component: &CachedComponent) -> std::io::Result<()> {
+    if component.path.is_dir() {
+        std::fs::remove_dir_all(&component.path)
+    } else {
+        std::fs::remove_file(&component.path)
+    }
+}
+
+/// Records that a cached component was used, so that recently used components
+/// are not pruned. Failures are ignored, as this only affects pruning.
+pub(super) fn touch_cached_component(path: &Path) {
+    let result = File::open(path).and_then(|file| file.set_modified(SystemTime::now()));
+
+    if let Err(e) = result {
+        debug!(
+            "Failed to update last used time of cached component {}: {e}",
+            path.display()
+        );
+    }
+}
+
+fn read_dir_paths(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    std::fs::read_dir(path)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect()
+}
+
+/// The modified time is used rather than the access time, as access times
+/// are often not updated. The modified time is updated whenever the component is used.
+fn last_used(path: &Path) -> std::io::Result<SystemTime> {
+    path.metadata()?.modified()
+}
+
+fn directory_size(path: &Path) -> std::io::Result<u64> {
+    let mut size = 0;
+    for entry in walkdir::WalkDir::new(path) {
+        let entry = entry.map_err(std::io::Error::other)?;
+        if entry.file_type().is_file() {
+            size += entry.metadata().map_err(std::io::Error::other)?.len();
+        }
+    }
+    Ok(size)
+}
+
+#[derive(Deserialize)]
+struct ComponentIdentity {
+    publisher: String,
+    name: String,
+    version: String,
+}
+
+impl ComponentIdentity {
+    fn parse(definition: &str) -> Option<String> {
+        let identity: ComponentIdentity = serde_json::from_str(definition).ok()?;
+        Some(format!(
+            "{}.{}.{}",
+            identity.publisher, identity.name, identity.version
+        ))
+    }
+}
+
+fn read_tar_component_reference(path: &Path) -> Option<String> {
+    let mut archive = Archive::new(File::open(path).ok()?);
+
+    for entry in archive.entries().ok()? {
+        let mut entry = entry.ok()?;
+        let entry_path = entry.path().ok()?;
+        if entry_path
+            .components()
+            .filter(|c| !matches!(c, std::path::Component::CurDir))
+            .eq(Path::new(SLIPWAY_COMPONENT_FILE_NAME).components())
+        {
+            let mut definition = String::new();
+            entry.read_to_string(&mut definition).ok()?;
+            return ComponentIdentity::parse(&definition);
+        }
+    }
+
+    None
+}
+
+fn read_directory_component_reference(path: &Path) -> Option<String> {
+    let definition = std::fs::read_to_string(path.join(SLIPWAY_COMPONENT_FILE_NAME)).ok()?;
+    ComponentIdentity::parse(&definition)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const DEFINITION: &str = r#"{ "publisher": "p", "name": "n", "version": "1.2.3" }"#;
+
+    fn write_tar(path: &Path, file_name: &str, contents: &str) {
+        let mut builder = tar::Builder::new(File::create(path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, file_name, contents.as_bytes())
+            .unwrap();
+        builder.finish().unwrap();
+    }
+
+    #[test]
+    fn it_should_list_cached_components() {
+        let cache = tempfile::tempdir().unwrap();
+        write_tar(
+            &cache.path().join("a.tar"),
+            SLIPWAY_COMPONENT_FILE_NAME,
+            DEFINITION,
+        );
+        write_tar(&cache.path().join("b.tar"), "other.json", "{}");
+        std::fs::write(cache.path().join("ignored.txt"), "").unwrap();
+
+        let git_component = cache.path().join(GIT_CACHE_DIRECTORY).join("c");
+        std::fs::create_dir_all(&git_component).unwrap();
+        std::fs::write(git_component.join(SLIPWAY_COMPONENT_FILE_NAME), DEFINITION).unwrap();
+
+        let extracted_component = cache.path().join(EXTRACTED_CACHE_DIRECTORY).join("d");
+        std::fs::create_dir_all(&extracted_component).unwrap();
+        std::fs::write(
+            extracted_component.join(SLIPWAY_COMPONENT_FILE_NAME),
+            DEFINITION,
+        )
+        .unwrap();
+
+        let components = list_cached_components(cache.path()).unwrap();
+
+        assert_eq!(components.len(), 4);
+        assert_eq!(components[0].path, cache.path().join("a.tar"));
+        assert_eq!(components[0].reference.as_deref(), Some("p.n.1.2.3"));
+        assert!(components[0].size_bytes > 0);
+        assert_eq!(components[1].reference, None);
+        assert_eq!(components[2].path, extracted_component);
+        assert_eq!(components[2].reference.as_deref(), Some("p.n.1.2.3"));
+        assert_eq!(components[3].path, git_component);
+        assert_eq!(components[3].reference.as_deref(), Some("p.n.1.2.3"));
+        assert_eq!(components[3].size_bytes, DEFINITION.len() as u64);
+    }
+
+    #[test]
+    fn it_should_list_nothing_if_cache_does_not_exist() {
+        let cache = tempfile::tempdir().unwrap();
+
+        let components = list_cached_components(&cache.path().join("missing")).unwrap();
+
+        assert!(components.is_empty());
+    }
+
+    #[test]
+    fn it_should_update_last_used_when_touched() {
+        let cache = tempfile::tempdir().unwrap();
+        let path = cache.path().join("a.tar");
+        write_tar(&path, SLIPWAY_COMPONENT_FILE_NAME, DEFINITION);
+
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        File::open(&path).unwrap().set_modified(old).unwrap();
+
+        touch_cached_component(&path);
+
+        let components = list_cached_components(cache.path()).unwrap();
+        assert!(components[0].last_used > old);
+    }
+
+    #[test]
+    fn it_should_remove_cached_components() {
+        let cache = tempfile::tempdir().unwrap();
+        write_tar(
+            &cache.path().join("a.tar"),
+            SLIPWAY_COMPONENT_FILE_NAME,
+            DEFINITION,
+        );
+        let git_component = cache.path().join(GIT_CACHE_DIRECTORY).join("c");
+        std::fs::create_dir_all(&git_component).unwrap();
+
+        for component in list_cached_components(cache.path()).unwrap() {
+            remove_cached_component(&component).unwrap();
+        }
+
+        assert!(list_cached_components(cache.path()).unwrap().is_empty());
+    }
+}
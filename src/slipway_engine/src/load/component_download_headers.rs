@@ -0,0 +1,308 @@
+use url::Url;
+
+const DEFAULT_USER_AGENT: &str = concat!("slipway/", env!("CARGO_PKG_VERSION"));
+
+/// Headers which carry credentials, and so are only sent to configured registries.
+const CREDENTIAL_HEADERS: &[&str] = &["authorization", "proxy-authorization", "cookie"];
+
+/// The headers sent when downloading components over HTTP.
+#[derive(Clone, Debug, Default)]
+pub(super) struct ComponentDownloadHeaders {
+    user_agent: Option<String>,
+    default_headers: Vec<(String, String)>,
+    registry_headers: Vec<RegistryHeaders>,
+    registries: Vec<RegistryUrlPrefix>,
+}
+
+/// Headers which only apply to downloads from a specific registry.
+#[derive(Clone, Debug)]
+struct RegistryHeaders {
+    url_prefix: Option<RegistryUrlPrefix>,
+    headers: Vec<(String, String)>,
+}
+
+impl ComponentDownloadHeaders {
+    pub fn set_user_agent(&mut self, user_agent: String) {
+        self.user_agent = Some(user_agent);
+    }
+
+    pub fn add_header(&mut self, name: String, value: String) {
+        self.default_headers.push((name, value));
+    }
+
+    /// Sets the registry lookup URLs which default credential headers, such as
+    /// `Authorization`, are sent to.
+    pub fn set_registries(&mut self, registry_lookup_urls: &[String]) {
+        self.registries = registry_lookup_urls
+            .iter()
+            .filter_map(|url| RegistryUrlPrefix::parse(url))
+            .collect();
+    }
+
+    /// Adds a header which only applies to URLs from the given registry lookup URL,
+    /// overriding any default header with the same name.
+    pub fn add_registry_header(&mut self, registry_url: &str, name: String, value: String) {
+        let url_prefix = RegistryUrlPrefix::parse(registry_url);
+
+        match self
+            .registry_headers
+            .iter_mut()
+            .find(|r| url_prefix.is_some() && r.url_prefix == url_prefix)
+        {
+            Some(registry_headers) => registry_headers.headers.push((name, value)),
+            None => self.registry_headers.push(RegistryHeaders {
+                url_prefix,
+                headers: vec![(name, value)],
+            }),
+        }
+    }
+
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT)
+    }
+
+    /// Returns the headers to send when downloading from the given URL.
+    ///
+    /// Default credential headers are only sent to URLs from a configured registry,
+    /// so that they aren't leaked to the hosts of HTTP component references.
+    pub fn headers_for_url(&self, url: &Url) -> Vec<(String, String)> {
+        let is_registry_url = self.registries.iter().any(|r| r.matches(url));
+
+        let mut headers: Vec<_> = self
+            .default_headers
+            .iter()
+            .filter(|(name, _)| is_registry_url || !is_credential_header(name))
+            .cloned()
+            .collect();
+
+        for registry_headers in self.registry_headers.iter().filter(|r| {
+            r.url_prefix
+                .as_ref()
+                .is_some_and(|url_prefix| url_prefix.matches(url))
+        }) {
+            for (name, value) in registry_headers.headers.iter() {
+                headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+                headers.push((name.clone(), value.clone()));
+            }
+        }
+
+        headers
+    }
+}
+
+fn is_credential_header(name: &str) -> bool {
+    CREDENTIAL_HEADERS
+        .iter()
+        .any(|credential_header| credential_header.eq_ignore_ascii_case(name))
+}
+
+/// The static part of a registry lookup URL, before the first placeholder.
+///
+/// URLs match if they have the same origin (scheme, host and port), and their path
+/// starts with the same whole path segments, so that a prefix can't match a
+/// lookalike host or a sibling path.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegistryUrlPrefix {
+    origin: url::Origin,
+    segments: Vec<String>,
+
+    /// The start of a path segment ending in a placeholder,
+    /// such as `slipway_` in `https://example.com/slipway_{name}`.
+    partial_segment: Option<String>,
+}
+
+impl RegistryUrlPrefix {
+    /// Parses the static part of the registry lookup URL. Returns `None` if it isn't
+    /// an absolute URL with a host, or if a placeholder appears before the path.
+    pub fn parse(registry_url: &str) -> Option<Self> {
+        let placeholder_index = registry_url.find('{');
+        let prefix = &registry_url[..placeholder_index.unwrap_or(registry_url.len())];
+
+        let url = Url::parse(prefix).ok()?;
+        url.host()?;
+
+        // A placeholder before the path would leave a partial host in the prefix.
+        let (_, after_scheme) = prefix.split_once("://")?;
+        if placeholder_index.is_some() && !after_scheme.contains('/') {
+            return None;
+        }
+
+        let mut segments: Vec<String> = url.path_segments()?.map(str::to_string).collect();
+        let last_segment = segments.pop().unwrap_or_default();
+
+        let partial_segment = if placeholder_index.is_some() {
+            Some(last_segment).filter(|s| !s.is_empty())
+        } else {
+            if !last_segment.is_empty() {
+                segments.push(last_segment);
+            }
+            None
+        };
+
+        Some(Self {
+            origin: url.origin(),
+            segments,
+            partial_segment,
+        })
+    }
+
+    /// Returns true if the URL is beneath this prefix.
+    pub fn matches(&self, url: &Url) -> bool {
+        if url.origin() != self.origin {
+            return false;
+        }
+
+        let Some(mut url_segments) = url.path_segments() else {
+            return false;
+        };
+
+        for segment in self.segments.iter() {
+            if url_segments.next() != Some(segment.as_str()) {
+                return false;
+            }
+        }
+
+        match &self.partial_segment {
+            Some(partial_segment) => url_segments
+                .next()
+                .is_some_and(|segment| segment.starts_with(partial_segment.as_str())),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_apply_default_headers_to_all_urls() {
+        let mut headers = ComponentDownloadHeaders::default();
+        headers.add_header("X-Test".to_string(), "1".to_string());
+
+        let url = Url::parse("https://example.com/a.tar").unwrap();
+
+        assert_eq!(
+            headers.headers_for_url(&url),
+            vec![("X-Test".to_string(), "1".to_string())]
+        );
+    }
+
+    #[test]
+    fn it_should_override_default_headers_for_registry() {
+        let mut headers = ComponentDownloadHeaders::default();
+        headers.add_header("Authorization".to_string(), "Bearer default".to_string());
+        headers.set_registries(&[
+            "https://private.example.com/{publisher}.{name}.{version}.tar".to_string(),
+            "https://public.example.com/{publisher}.{name}.{version}.tar".to_string(),
+        ]);
+        headers.add_registry_header(
+            "https://private.example.com/{publisher}.{name}.{version}.tar",
+            "authorization".to_string(),
+            "Bearer private".to_string(),
+        );
+
+        let private_url = Url::parse("https://private.example.com/p.n.1.0.0.tar").unwrap();
+        let public_url = Url::parse("https://public.example.com/p.n.1.0.0.tar").unwrap();
+
+        assert_eq!(
+            headers.headers_for_url(&private_url),
+            vec![("authorization".to_string(), "Bearer private".to_string())]
+        );
+        assert_eq!(
+            headers.headers_for_url(&public_url),
+            vec![("Authorization".to_string(), "Bearer default".to_string())]
+        );
+    }
+
+    #[test]
+    fn it_should_only_send_default_credential_headers_to_registries() {
+        let mut headers = ComponentDownloadHeaders::default();
+        headers.add_header("Authorization".to_string(), "Bearer token".to_string());
+        headers.add_header("X-Test".to_string(), "1".to_string());
+        headers.set_registries(&[
+            "https://registry.example.com/{publisher}.{name}.{version}.tar".to_string(),
+        ]);
+
+        let registry_url = Url::parse("https://registry.example.com/p.n.1.0.0.tar").unwrap();
+        let other_url = Url::parse("https://other.example.com/p.n.1.0.0.tar").unwrap();
+
+        assert_eq!(headers.headers_for_url(&registry_url).len(), 2);
+        assert_eq!(
+            headers.headers_for_url(&other_url),
+            vec![("X-Test".to_string(), "1".to_string())]
+        );
+    }
+
+    #[test]
+    fn it_should_match_registry_url_prefixes_by_origin_and_path_segments() {
+        let cases = [
+            (
+                "https://example.com/{name}",
+                "https://example.com/a.tar",
+                true,
+            ),
+            (
+                "https://example.com/{name}",
+                "http://example.com/a.tar",
+                false,
+            ),
+            (
+                "https://example.com/{name}",
+                "https://example.com:8443/a.tar",
+                false,
+            ),
+            (
+                "https://example.com{name}",
+                "https://example.com.evil.io/a.tar",
+                false,
+            ),
+            (
+                "https://example.com/r/{name}",
+                "https://example.com/r/a.tar",
+                true,
+            ),
+            (
+                "https://example.com/r/{name}",
+                "https://example.com/rx/a.tar",
+                false,
+            ),
+            ("https://example.com/r", "https://example.com/r/a.tar", true),
+            (
+                "https://example.com/r",
+                "https://example.com/rx/a.tar",
+                false,
+            ),
+            (
+                "https://example.com/s_{name}",
+                "https://example.com/s_a.tar",
+                true,
+            ),
+            (
+                "https://example.com/s_{name}",
+                "https://example.com/t_a.tar",
+                false,
+            ),
+            (
+                "https://{publisher}.example.com/",
+                "https://p.example.com/",
+                false,
+            ),
+        ];
+
+        for (registry_url, url, expected) in cases {
+            let matches = RegistryUrlPrefix::parse(registry_url)
+                .is_some_and(|prefix| prefix.matches(&Url::parse(url).unwrap()));
+            assert_eq!(matches, expected, "{registry_url} {url}");
+        }
+    }
+
+    #[test]
+    fn it_should_use_default_user_agent() {
+        let mut headers = ComponentDownloadHeaders::default();
+        assert!(headers.user_agent().starts_with("slipway/"));
+
+        headers.set_user_agent("custom".to_string());
+        assert_eq!(headers.user_agent(), "custom");
+    }
+}
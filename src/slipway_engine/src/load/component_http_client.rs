@@ -0,0 +1,61 @@
+use url::Url;
+
+use super::component_download_headers::ComponentDownloadHeaders;
+
+/// The HTTP client used to download components and query registries.
+///
+/// It is created once per components loader, so that connections are reused and
+/// every request uses the loader's proxy, User-Agent and per-registry headers.
+#[derive(Debug)]
+pub struct ComponentHttpClient {
+    client: Result<reqwest::Client, reqwest::Error>,
+    headers: ComponentDownloadHeaders,
+}
+
+impl ComponentHttpClient {
+    /// Creates the client. An explicit proxy is used for every request, otherwise the
+    /// `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables are used.
+    ///
+    /// Errors creating the client, such as an invalid proxy URL, are returned
+    /// when the client is used.
+    pub(super) fn new(proxy: Option<&str>, headers: ComponentDownloadHeaders) -> Self {
+        Self {
+            client: create_client(proxy, headers.user_agent()),
+            headers,
+        }
+    }
+
+    /// Creates a client which uses the proxy but sends no registry headers.
+    pub(crate) fn with_proxy(proxy: Option<&str>) -> Self {
+        Self::new(proxy, ComponentDownloadHeaders::default())
+    }
+
+    /// Returns the underlying client, which applies the proxy and User-Agent
+    /// but not the per-registry headers.
+    pub fn client(&self) -> Result<&reqwest::Client, &reqwest::Error> {
+        self.client.as_ref()
+    }
+
+    /// Creates a request to the URL with the headers configured for it, including
+    /// any credentials for the registry the URL belongs to.
+    pub fn request(
+        &self,
+        method: reqwest::Method,
+        url: &Url,
+    ) -> Result<reqwest::RequestBuilder, &reqwest::Error> {
+        let mut request = self.client()?.request(method, url.as_str());
+        for (name, value) in self.headers.headers_for_url(url) {
+            request = request.header(name, value);
+        }
+        Ok(request)
+    }
+}
+
+fn create_client(proxy: Option<&str>, user_agent: &str) -> Result<reqwest::Client, reqwest::Error> {
+    let mut client_builder = reqwest::Client::builder().user_agent(user_agent);
+    if let Some(proxy) = proxy {
+        client_builder = client_builder
+            .proxy(reqwest::Proxy::all(proxy)?.no_proxy(reqwest::NoProxy::from_env()));
+    }
+    client_builder.build()
+}
@@ -1,5 +1,4 @@
 use async_trait::async_trait;
-use tokio::io::AsyncSeekExt;
 use tokio_util::io::StreamReader;
 use tracing::debug;
 use tracing::warn;
@@ -7,13 +6,20 @@ use url::Url;
 
 use crate::errors::ComponentLoadErrorInner;
 
+use super::basic_components_loader::TarLimits;
+use super::component_http_client::ComponentHttpClient;
+use super::rate_limit::{MAX_RATE_LIMITED_RETRIES, get_rate_limited_retry_delay};
+
 use crate::errors::ComponentLoadError;
 
 use crate::SlipwayReference;
+use crate::parse::types::slipway_reference::is_valid_git_revision;
 
 use futures::TryStreamExt;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
 
 pub(super) trait FileHandle:
     tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin + Send
@@ -48,20 +54,61 @@ pub(super) trait ComponentIOAbstractions: Send + Sync {
         component_reference: &SlipwayReference,
     ) -> Result<PathBuf, ComponentLoadError>;
 
+    async fn cache_git_repository(
+        &self,
+        url: &Url,
+        rev: &str,
+        component_reference: &SlipwayReference,
+    ) -> Result<PathBuf, ComponentLoadError>;
+
     async fn exists(&self, path: &Path) -> bool;
 
     async fn is_dir(&self, path: &Path) -> bool;
 }
 
+pub(super) const GIT_CACHE_DIRECTORY: &str = "git";
+
+/// The directory in the components cache which downloaded component TAR files
+/// are extracted to, so that their files can be deduplicated.
+pub(super) const EXTRACTED_CACHE_DIRECTORY: &str = "extracted";
+
+const GIT_ALLOWED_PROTOCOLS: &str = "https:http";
+const GIT_ALLOWED_PROTOCOLS_WITH_NON_HTTP: &str = "https:http:ssh:file";
+const GIT_COMMIT_SHA_LENGTH: usize = 40;
+
 #[derive(Clone)]
 pub(super) struct ComponentIOAbstractionsImpl {
     local_component_cache_path: PathBuf,
+    http_client: Arc<ComponentHttpClient>,
+
+    /// Limits the number of components which are downloaded at the same time.
+    download_permits: Arc<Semaphore>,
+
+    /// The transports git may use, passed to git as `GIT_ALLOW_PROTOCOL`.
+    git_allowed_protocols: &'static str,
+
+    /// The limits applied when extracting downloaded component TAR files.
+    tar_limits: TarLimits,
 }
 
 impl ComponentIOAbstractionsImpl {
-    pub fn new(local_component_cache_path: PathBuf) -> Self {
+    pub fn new(
+        local_component_cache_path: PathBuf,
+        http_client: Arc<ComponentHttpClient>,
+        max_concurrent_downloads: usize,
+        allow_non_http_git_urls: bool,
+        tar_limits: TarLimits,
+    ) -> Self {
         Self {
             local_component_cache_path,
+            http_client,
+            download_permits: Arc::new(Semaphore::new(max_concurrent_downloads.max(1))),
+            git_allowed_protocols: if allow_non_http_git_urls {
+                GIT_ALLOWED_PROTOCOLS_WITH_NON_HTTP
+            } else {
+                GIT_ALLOWED_PROTOCOLS
+            },
+            tar_limits,
         }
     }
 }
@@ -104,38 +151,79 @@ impl ComponentIOAbstractions for ComponentIOAbstractionsImpl {
         component_reference: &SlipwayReference,
     ) -> Result<PathBuf, ComponentLoadError> {
         let file_name = super::filename_from_url::filename_from_url(url);
-        let file_path = self.local_component_cache_path.join(file_name);
+        let extracted_cache_path = self
+            .local_component_cache_path
+            .join(EXTRACTED_CACHE_DIRECTORY);
+        let directory_path = extracted_cache_path.join(file_name);
 
-        if file_path.exists() {
+        if directory_path.exists() {
             debug!("Found component in cache: {url}");
-            return Ok(file_path);
+            super::cached_components::touch_cached_component(&directory_path);
+            return Ok(directory_path);
         }
 
         debug!("Downloading component: {url}");
 
-        // Create the directory if it doesn't exist
-        if !file_path.parent().unwrap().exists() {
-            tokio::fs::create_dir_all(file_path.parent().unwrap())
-                .await
+        tokio::fs::create_dir_all(&extracted_cache_path)
+            .await
+            .map_err(|e| {
+                file_load_failed_error(
+                    component_reference,
+                    extracted_cache_path.to_string_lossy(),
+                    format!(
+                        "Error creating local components directory at {}.\n{e}",
+                        extracted_cache_path.to_string_lossy(),
+                    ),
+                )
+            })?;
+
+        let _download_permit = self
+            .download_permits
+            .acquire()
+            .await
+            .expect("download semaphore should never be closed");
+
+        let mut attempt = 0;
+        let response = loop {
+            let request = self
+                .http_client
+                .request(reqwest::Method::GET, url)
                 .map_err(|e| {
                     file_load_failed_error(
                         component_reference,
-                        file_path.to_string_lossy(),
-                        format!(
-                            "Error creating local components directory at {}.\n{e}",
-                            self.local_component_cache_path.to_string_lossy(),
-                        ),
+                        url,
+                        format!("Error creating HTTP client to fetch component.\n{e}"),
                     )
                 })?;
-        }
 
-        let response = reqwest::get(url.as_str()).await.map_err(|e| {
-            file_load_failed_error(
-                component_reference,
-                url,
-                format!("Error fetching component from url.\n{e}"),
-            )
-        })?;
+            let response = request.send().await.map_err(|e| {
+                file_load_failed_error(
+                    component_reference,
+                    url,
+                    format!("Error fetching component from url.\n{e}"),
+                )
+            })?;
+
+            if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS
+                || attempt >= MAX_RATE_LIMITED_RETRIES
+            {
+                break response;
+            }
+
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok());
+            let delay = get_rate_limited_retry_delay(retry_after, attempt);
+
+            warn!(
+                "Rate limited downloading component: {url}\nRetrying in {} seconds.",
+                delay.as_secs()
+            );
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        };
 
         if response.status() != 200 {
             return Err(file_load_failed_error(
@@ -167,22 +255,186 @@ impl ComponentIOAbstractions for ComponentIOAbstractionsImpl {
                 file_load_failed_error(
                     component_reference,
                     url,
+                    format!("Error downloading component file.\n{e}"),
+                )
+            })?;
+
+        // The component is extracted to a temp directory and then moved to the cache
+        // to avoid race conditions with other threads trying to download or access it.
+        let temp_dir = tempfile::tempdir_in(&extracted_cache_path).map_err(|e| {
+            file_load_failed_error(
+                component_reference,
+                url,
+                format!("Error creating temporary directory to extract component.\n{e}"),
+            )
+        })?;
+
+        let temp_file = temp_file.into_std().await;
+        let temp_path = temp_dir.path().to_owned();
+        let cache_path = self.local_component_cache_path.clone();
+        let tar_limits = self.tar_limits;
+        tokio::task::spawn_blocking(move || {
+            super::content_store::extract_tar_deduplicated(
+                &cache_path,
+                temp_file,
+                &temp_path,
+                tar_limits,
+            )
+        })
+        .await
+        .map_err(|e| ComponentLoadError {
+            reference: Box::new(component_reference.clone()),
+            error: ComponentLoadErrorInner::ThreadJoinFailed {
+                message: e.to_string(),
+            },
+        })?
+        .map_err(|e| {
+            file_load_failed_error(
+                component_reference,
+                url,
+                format!("Error extracting component TAR file.\n{e}"),
+            )
+        })?;
+
+        match std::fs::rename(temp_dir.path(), &directory_path) {
+            Ok(()) => {}
+            Err(_) if directory_path.exists() => {
+                warn!(
+                    "Cached component \"{:?}\" already exists. Existing directory will be used.",
+                    directory_path
+                );
+            }
+            Err(e) => {
+                return Err(file_load_failed_error(
+                    component_reference,
+                    directory_path.to_string_lossy(),
+                    format!("Error moving component to cache.\n{e}"),
+                ));
+            }
+        }
+
+        Ok(directory_path)
+    }
+
+    async fn cache_git_repository(
+        &self,
+        url: &Url,
+        rev: &str,
+        component_reference: &SlipwayReference,
+    ) -> Result<PathBuf, ComponentLoadError> {
+        if !is_valid_git_revision(rev) {
+            return Err(file_load_failed_error(
+                component_reference,
+                url,
+                format!("Invalid git revision \"{rev}\"."),
+            ));
+        }
+
+        // Branches and tags can move, so they are resolved to a commit on every load
+        // and the cache is keyed by the commit rather than the revision name.
+        let git = Git {
+            allowed_protocols: self.git_allowed_protocols,
+        };
+        let commit = {
+            let url = url.to_string();
+            let rev = rev.to_string();
+            tokio::task::spawn_blocking(move || git.resolve_revision(&url, &rev))
+        }
+        .await
+        .map_err(|e| ComponentLoadError {
+            reference: Box::new(component_reference.clone()),
+            error: ComponentLoadErrorInner::ThreadJoinFailed {
+                message: e.to_string(),
+            },
+        })?
+        .map_err(|e| {
+            file_load_failed_error(
+                component_reference,
+                url,
+                format!("Error resolving git revision \"{rev}\".\n{e}"),
+            )
+        })?;
+
+        let mut url_with_rev = url.clone();
+        url_with_rev.set_fragment(Some(&commit));
+        let git_cache_path = self.local_component_cache_path.join(GIT_CACHE_DIRECTORY);
+        let directory_path = git_cache_path.join(
+            super::filename_from_url::directory_name_from_url(&url_with_rev),
+        );
+
+        if directory_path.exists() {
+            debug!("Found git component in cache: {url_with_rev}");
+            super::cached_components::touch_cached_component(&directory_path);
+            return Ok(directory_path);
+        }
+
+        debug!("Fetching git component: {url_with_rev}");
+
+        tokio::fs::create_dir_all(&git_cache_path)
+            .await
+            .map_err(|e| {
+                file_load_failed_error(
+                    component_reference,
+                    git_cache_path.to_string_lossy(),
                     format!(
-                        "Error downloading component file to {}.\n{e}",
-                        file_path.to_string_lossy()
+                        "Error creating local git components directory at {}.\n{e}",
+                        git_cache_path.to_string_lossy(),
                     ),
                 )
             })?;
 
-        move_temp_file(temp_file, &file_path).await.map_err(|e| {
+        // We fetch to a temp directory and then move it to the cache to avoid
+        // race conditions with other threads trying to fetch or access the same repository.
+        let temp_dir = tempfile::tempdir_in(&git_cache_path).map_err(|e| {
+            file_load_failed_error(
+                component_reference,
+                url,
+                format!("Error creating temporary directory to fetch component.\n{e}"),
+            )
+        })?;
+
+        let temp_path = temp_dir.path().to_owned();
+        let fetch_url = url.to_string();
+        let fetch_rev = rev.to_string();
+        let cache_path = self.local_component_cache_path.clone();
+        tokio::task::spawn_blocking(move || {
+            git.fetch_revision(&temp_path, &fetch_url, &fetch_rev)?;
+            super::content_store::deduplicate_directory(&cache_path, &temp_path);
+            Ok::<(), String>(())
+        })
+        .await
+        .map_err(|e| ComponentLoadError {
+            reference: Box::new(component_reference.clone()),
+            error: ComponentLoadErrorInner::ThreadJoinFailed {
+                message: e.to_string(),
+            },
+        })?
+        .map_err(|e| {
             file_load_failed_error(
                 component_reference,
-                file_path.to_string_lossy(),
-                format!("Error moving component file to cache.\n{e}"),
+                url,
+                format!("Error fetching git revision \"{rev}\".\n{e}"),
             )
         })?;
 
-        Ok(file_path)
+        match std::fs::rename(temp_dir.path(), &directory_path) {
+            Ok(()) => {}
+            Err(_) if directory_path.exists() => {
+                warn!(
+                    "Cached git component \"{:?}\" already exists. Existing directory will be used.",
+                    directory_path
+                );
+            }
+            Err(e) => {
+                return Err(file_load_failed_error(
+                    component_reference,
+                    directory_path.to_string_lossy(),
+                    format!("Error moving git component to cache.\n{e}"),
+                ));
+            }
+        }
+
+        Ok(directory_path)
     }
 
     async fn exists(&self, path: &Path) -> bool {
@@ -194,6 +446,101 @@ impl ComponentIOAbstractions for ComponentIOAbstractionsImpl {
     }
 }
 
+/// Runs the `git` executable, so that the user's existing git credentials
+/// and configuration are respected, restricted to the allowed transports.
+#[derive(Clone, Copy)]
+struct Git {
+    allowed_protocols: &'static str,
+}
+
+impl Git {
+    fn run(&self, directory: Option<&Path>, args: &[&str]) -> Result<String, String> {
+        let mut command = std::process::Command::new("git");
+        if let Some(directory) = directory {
+            command.arg("-C").arg(directory);
+        }
+
+        let output = command
+            .args(args)
+            .env("GIT_ALLOW_PROTOCOL", self.allowed_protocols)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .output()
+            .map_err(|e| format!("Failed to run git: {e}"))?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(format!(
+                "git {} failed:\n{}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    /// Resolves a branch or tag to the commit it currently points to.
+    /// Full commit SHAs are returned unchanged.
+    fn resolve_revision(&self, url: &str, rev: &str) -> Result<String, String> {
+        if is_full_commit_sha(rev) {
+            return Ok(rev.to_string());
+        }
+
+        let output = self.run(None, &["ls-remote", "--end-of-options", url, rev])?;
+        let refs: Vec<(&str, &str)> = output
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .collect();
+
+        // Annotated tags are listed with a peeled `^{}` entry pointing at the commit.
+        let candidates = [
+            format!("refs/tags/{rev}^{{}}"),
+            format!("refs/tags/{rev}"),
+            format!("refs/heads/{rev}"),
+            rev.to_string(),
+        ];
+
+        candidates
+            .iter()
+            .find_map(|candidate| {
+                refs.iter()
+                    .find(|(_, name)| name == candidate)
+                    .map(|(commit, _)| commit.to_string())
+            })
+            .ok_or_else(|| format!("Revision \"{rev}\" was not found in {url}"))
+    }
+
+    /// Fetches a single revision of a git repository into the given empty directory.
+    fn fetch_revision(&self, directory: &Path, url: &str, rev: &str) -> Result<(), String> {
+        self.run(Some(directory), &["init", "--quiet"])?;
+        self.run(
+            Some(directory),
+            &["remote", "add", "--end-of-options", "origin", url],
+        )?;
+        self.run(
+            Some(directory),
+            &[
+                "fetch",
+                "--quiet",
+                "--depth",
+                "1",
+                "--end-of-options",
+                "origin",
+                rev,
+            ],
+        )?;
+        self.run(
+            Some(directory),
+            &["checkout", "--quiet", "--detach", "FETCH_HEAD"],
+        )?;
+
+        Ok(())
+    }
+}
+
+fn is_full_commit_sha(rev: &str) -> bool {
+    rev.len() == GIT_COMMIT_SHA_LENGTH && rev.chars().all(|c| c.is_ascii_hexdigit())
+}
+
 fn file_load_failed_error(
     component_reference: &SlipwayReference,
     path: impl AsRef<str>,
@@ -207,26 +554,3 @@ fn file_load_failed_error(
         },
     )
 }
-
-async fn move_temp_file(mut tmp_file: tokio::fs::File, file_path: &Path) -> std::io::Result<()> {
-    match tokio::fs::OpenOptions::new()
-        .write(true)
-        .create_new(true)
-        .open(file_path)
-        .await
-    {
-        Ok(mut dest_file) => {
-            tmp_file.seek(std::io::SeekFrom::Start(0)).await?;
-            tokio::io::copy(&mut tmp_file, &mut dest_file).await?;
-        }
-        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
-            warn!(
-                "Cached component file \"{:?}\" already exists. Existing file will be used.",
-                file_path
-            );
-        }
-        Err(e) => return Err(e),
-    }
-
-    Ok(())
-}
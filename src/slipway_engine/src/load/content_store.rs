@@ -0,0 +1,344 @@
+use std::{
+    fs::File,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+use tar::{Archive, EntryType};
+use tracing::debug;
+
+use super::basic_components_loader::{TarLimits, is_safe_entry_path};
+
+/// The directory in the components cache which stores file contents by hash.
+pub(super) const CONTENT_CACHE_DIRECTORY: &str = "cas";
+
+/// Directories within cached components which are never deduplicated.
+const EXCLUDED_DIRECTORY_NAMES: &[&str] = &[".git"];
+
+/// Extracts the files of a component TAR file into the directory, and deduplicates
+/// them as per `deduplicate_directory`.
+///
+/// Only regular files and directories are extracted, so that links within the
+/// archive can't refer to files outside of the component.
+pub(super) fn extract_tar_deduplicated(
+    cache_path: &Path,
+    tar_file: File,
+    destination: &Path,
+    limits: TarLimits,
+) -> std::io::Result<()> {
+    let mut archive = Archive::new(tar_file);
+    let mut total_size: u64 = 0;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.into_owned();
+
+        if !is_safe_entry_path(&entry_path) {
+            return Err(std::io::Error::other(format!(
+                "Entry path \"{}\" must be relative and must not contain \"..\"",
+                entry_path.display()
+            )));
+        }
+
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            std::fs::create_dir_all(destination.join(&entry_path))?;
+            continue;
+        }
+
+        if entry_type != EntryType::Regular && entry_type != EntryType::Continuous {
+            debug!(
+                "Skipping TAR entry \"{}\" of type {entry_type:?}",
+                entry_path.display()
+            );
+            continue;
+        }
+
+        let size = entry.header().entry_size()?;
+        total_size = total_size.saturating_add(size);
+        if size > limits.max_file_size || total_size > limits.max_total_size {
+            return Err(std::io::Error::other(format!(
+                "Entry \"{}\" exceeds the component TAR file size limits",
+                entry_path.display()
+            )));
+        }
+
+        let file_path = destination.join(&entry_path);
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::io::copy(&mut entry, &mut File::create(&file_path)?)?;
+    }
+
+    deduplicate_directory(cache_path, destination);
+
+    Ok(())
+}
+
+/// Replaces the file with a hard link to a file in the content store which has
+/// the same contents, adding it to the store if necessary.
+/// This is best effort: if the file cannot be linked it is left unchanged.
+fn deduplicate_file(cache_path: &Path, path: &Path) {
+    if let Err(e) = deduplicate_file_inner(cache_path, path) {
+        debug!(
+            "Failed to deduplicate cached component file {}: {e}",
+            path.display()
+        );
+    }
+}
+
+/// Deduplicates every file in the directory, as per `deduplicate_file`.
+pub(super) fn deduplicate_directory(cache_path: &Path, path: &Path) {
+    for file_path in component_file_paths(path) {
+        deduplicate_file(cache_path, &file_path);
+    }
+}
+
+/// Removes files from the content store which are no longer used by any
+/// component in the given components cache, returning the number of bytes removed.
+///
+/// Stored files are hard linked into each component which uses them, so a stored
+/// file is unused once the store holds its only link.
+pub fn remove_unused_cached_content(cache_path: &Path) -> std::io::Result<u64> {
+    let content_path = cache_path.join(CONTENT_CACHE_DIRECTORY);
+    if !content_path.exists() {
+        return Ok(0);
+    }
+
+    let mut removed_bytes = 0;
+    for entry in std::fs::read_dir(&content_path)? {
+        let path = entry?.path();
+        let metadata = path.metadata()?;
+
+        if metadata.nlink() <= 1 {
+            removed_bytes += metadata.len();
+            std::fs::remove_file(&path)?;
+        }
+    }
+
+    Ok(removed_bytes)
+}
+
+fn deduplicate_file_inner(cache_path: &Path, path: &Path) -> std::io::Result<()> {
+    let content_path = cache_path.join(CONTENT_CACHE_DIRECTORY);
+    std::fs::create_dir_all(&content_path)?;
+
+    let stored_path = content_path.join(hash_file(path)?);
+
+    match std::fs::hard_link(path, &stored_path) {
+        // The file is now the stored copy of its contents.
+        Ok(()) => return Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e),
+    }
+
+    // Link to the stored copy alongside the file and then replace the file, so that
+    // other threads never see a missing file.
+    let link_path = temporary_link_path(path);
+    std::fs::hard_link(&stored_path, &link_path)?;
+    std::fs::rename(&link_path, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&link_path);
+    })
+}
+
+fn temporary_link_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}.link", std::process::id()));
+    path.with_file_name(file_name)
+}
+
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn component_file_paths(path: &Path) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|e| !is_excluded_directory(e.path()))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect()
+}
+
+fn is_excluded_directory(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| EXCLUDED_DIRECTORY_NAMES.iter().any(|e| name == *e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored_files(cache_path: &Path) -> Vec<PathBuf> {
+        let mut paths = std::fs::read_dir(cache_path.join(CONTENT_CACHE_DIRECTORY))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn it_should_store_identical_files_once() {
+        let cache = tempfile::tempdir().unwrap();
+        std::fs::write(cache.path().join("a.tar"), "same").unwrap();
+        std::fs::write(cache.path().join("b.tar"), "same").unwrap();
+        std::fs::write(cache.path().join("c.tar"), "different").unwrap();
+
+        for name in ["a.tar", "b.tar", "c.tar"] {
+            deduplicate_file(cache.path(), &cache.path().join(name));
+        }
+
+        let stored = stored_files(cache.path());
+        assert_eq!(stored.len(), 2);
+        assert!(
+            stored.contains(
+                &cache
+                    .path()
+                    .join(CONTENT_CACHE_DIRECTORY)
+                    .join(hash_file(&cache.path().join("a.tar")).unwrap())
+            )
+        );
+
+        assert_eq!(
+            std::fs::read_to_string(cache.path().join("b.tar")).unwrap(),
+            "same"
+        );
+        assert_eq!(
+            std::fs::read_to_string(cache.path().join("c.tar")).unwrap(),
+            "different"
+        );
+    }
+
+    #[test]
+    fn it_should_deduplicate_directories_except_git_metadata() {
+        let cache = tempfile::tempdir().unwrap();
+        let component = cache.path().join("git").join("a");
+        std::fs::create_dir_all(component.join("fonts")).unwrap();
+        std::fs::create_dir_all(component.join(".git")).unwrap();
+        std::fs::write(component.join("fonts").join("a.ttf"), "font").unwrap();
+        std::fs::write(component.join("b.ttf"), "font").unwrap();
+        std::fs::write(component.join(".git").join("HEAD"), "head").unwrap();
+
+        deduplicate_directory(cache.path(), &component);
+
+        assert_eq!(stored_files(cache.path()).len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(component.join("b.ttf")).unwrap(),
+            "font"
+        );
+    }
+
+    fn write_tar(path: &Path, entries: &[(&str, EntryType, &str)]) {
+        let mut builder = tar::Builder::new(File::create(path).unwrap());
+        for (entry_path, entry_type, contents) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(*entry_type);
+            header.set_mode(0o644);
+
+            // The builder refuses to write unsafe paths, so set the name bytes directly.
+            let name = &mut header.as_gnu_mut().unwrap().name;
+            name[..entry_path.len()].copy_from_slice(entry_path.as_bytes());
+
+            let data = if *entry_type == EntryType::Symlink {
+                header.set_link_name(contents).unwrap();
+                ""
+            } else {
+                contents
+            };
+            header.set_size(data.len() as u64);
+            header.set_cksum();
+            builder.append(&header, data.as_bytes()).unwrap();
+        }
+        builder.finish().unwrap();
+    }
+
+    #[test]
+    fn it_should_deduplicate_files_across_extracted_tars() {
+        let cache = tempfile::tempdir().unwrap();
+        let tar_path = cache.path().join("component.tar");
+
+        for name in ["a", "b"] {
+            write_tar(
+                &tar_path,
+                &[
+                    ("./slipway_component.json", EntryType::Regular, name),
+                    ("fonts/font.ttf", EntryType::Regular, "font"),
+                    ("link", EntryType::Symlink, "/etc/passwd"),
+                ],
+            );
+
+            let destination = cache.path().join(name);
+            extract_tar_deduplicated(
+                cache.path(),
+                File::open(&tar_path).unwrap(),
+                &destination,
+                TarLimits::default(),
+            )
+            .unwrap();
+
+            assert_eq!(
+                std::fs::read_to_string(destination.join("fonts/font.ttf")).unwrap(),
+                "font"
+            );
+            assert!(!destination.join("link").exists());
+        }
+
+        // The font is stored once, alongside each definition.
+        assert_eq!(stored_files(cache.path()).len(), 3);
+    }
+
+    #[test]
+    fn it_should_reject_unsafe_or_oversized_tar_entries() {
+        let cache = tempfile::tempdir().unwrap();
+        let tar_path = cache.path().join("component.tar");
+
+        write_tar(&tar_path, &[("../escape", EntryType::Regular, "data")]);
+        let result = extract_tar_deduplicated(
+            cache.path(),
+            File::open(&tar_path).unwrap(),
+            &cache.path().join("a"),
+            TarLimits::default(),
+        );
+        assert!(result.is_err());
+        assert!(!cache.path().join("escape").exists());
+
+        write_tar(&tar_path, &[("large", EntryType::Regular, "data")]);
+        let result = extract_tar_deduplicated(
+            cache.path(),
+            File::open(&tar_path).unwrap(),
+            &cache.path().join("b"),
+            TarLimits {
+                max_file_size: 3,
+                ..TarLimits::default()
+            },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_should_remove_unused_content() {
+        let cache = tempfile::tempdir().unwrap();
+        std::fs::write(cache.path().join("a.tar"), "a").unwrap();
+        std::fs::write(cache.path().join("b.tar"), "bb").unwrap();
+        deduplicate_file(cache.path(), &cache.path().join("a.tar"));
+        deduplicate_file(cache.path(), &cache.path().join("b.tar"));
+
+        std::fs::remove_file(cache.path().join("b.tar")).unwrap();
+
+        assert_eq!(remove_unused_cached_content(cache.path()).unwrap(), 2);
+        assert_eq!(stored_files(cache.path()).len(), 1);
+        assert_eq!(remove_unused_cached_content(cache.path()).unwrap(), 0);
+    }
+
+    #[test]
+    fn it_should_remove_nothing_if_content_store_does_not_exist() {
+        let cache = tempfile::tempdir().unwrap();
+
+        assert_eq!(remove_unused_cached_content(cache.path()).unwrap(), 0);
+    }
+}
@@ -7,6 +7,16 @@ const COMPONENT_FILE_EXTENSION: &str = ".tar";
 
 // Creates a valid filename from a URL by hashing the URL and combining it with the host and path
 pub(super) fn filename_from_url(url: &Url) -> String {
+    format!("{}{}", name_from_url(url), COMPONENT_FILE_EXTENSION)
+}
+
+// Creates a valid directory name from a URL, in the same way as `filename_from_url`
+// but without a file extension.
+pub(super) fn directory_name_from_url(url: &Url) -> String {
+    name_from_url(url)
+}
+
+fn name_from_url(url: &Url) -> String {
     let url_str = url.as_str();
 
     // Compute the SHA256 hash of the URL
@@ -31,11 +41,8 @@ pub(super) fn filename_from_url(url: &Url) -> String {
         &sanitized_filename_base
     };
 
-    // Combine the sanitized filename base, hash, and extension to form the final filename
-    format!(
-        "{}-{}{}",
-        truncated_filename_base, hash_prefix, COMPONENT_FILE_EXTENSION
-    )
+    // Combine the sanitized filename base and hash to form the final name
+    format!("{}-{}", truncated_filename_base, hash_prefix)
 }
 
 fn sanitize_filename(filename: &str) -> String {
@@ -123,6 +130,18 @@ mod tests {
         assert_filename(&filename, "text_plain_base64_SGVsbG8sIFdvcmxkIQ_3D_3D");
     }
 
+    #[test]
+    fn directory_name_from_url_should_include_fragment_in_hash() {
+        let url_1 = Url::parse("https://example.com/repo.git#v1").unwrap();
+        let url_2 = Url::parse("https://example.com/repo.git#v2").unwrap();
+
+        let name_1 = directory_name_from_url(&url_1);
+        let name_2 = directory_name_from_url(&url_2);
+
+        assert!(name_1.starts_with("example_com_repo_git-"));
+        assert_ne!(name_1, name_2);
+    }
+
     #[test]
     fn sanitize_filename_should_remove_initial_period() {
         let filename = ".hidden";
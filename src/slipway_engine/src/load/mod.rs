@@ -8,18 +8,35 @@ use crate::{
 };
 
 pub(super) mod basic_components_loader;
+mod cached_components;
+mod component_download_headers;
+mod component_http_client;
 mod component_io_abstractions;
+mod content_store;
 mod filename_from_url;
 mod is_safe_path;
 mod parse_schema;
 mod prime_component_cache;
+mod rate_limit;
+mod shared_components_cache;
 pub(super) mod special_components;
 
 use async_trait::async_trait;
+pub use cached_components::{CachedComponent, list_cached_components, remove_cached_component};
+pub use component_download_headers::RegistryUrlPrefix;
+pub use component_http_client::ComponentHttpClient;
+pub use content_store::remove_unused_cached_content;
 pub use parse_schema::parse_schema;
+pub use prime_component_cache::validate_component;
+pub use shared_components_cache::{SharedComponentsCache, SharedComponentsLoader};
 
 const SLIPWAY_COMPONENT_FILE_NAME: &str = "slipway_component.json";
 
+/// Hand-written components in a local directory may use a YAML definition
+/// instead of `slipway_component.json`.
+const SLIPWAY_COMPONENT_YAML_FILE_NAMES: [&str; 2] =
+    ["slipway_component.yaml", "slipway_component.yml"];
+
 #[async_trait(?Send)]
 pub trait ComponentsLoader {
     async fn load_components(
@@ -197,14 +214,36 @@ pub struct PrimedComponent {
     pub files: Arc<ComponentFiles>,
 }
 
+/// A non-fatal issue found while loading a component.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComponentLoadWarning {
+    pub reference: SlipwayReference,
+    pub message: String,
+}
+
+impl std::fmt::Display for ComponentLoadWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Component \"{}\": {}", self.reference, self.message)
+    }
+}
+
 pub struct BasicComponentCache {
     components: HashMap<SlipwayReference, PrimedComponent>,
+
+    // Maps each canonical reference to the key it is stored under in `components`,
+    // so that references which differ only in form (such as `file:./a.tar` and
+    // `file:a.tar`) resolve to the same component.
+    canonical_references: HashMap<String, SlipwayReference>,
+
+    warnings: Vec<ComponentLoadWarning>,
 }
 
 impl BasicComponentCache {
     pub fn empty() -> Self {
         Self {
             components: HashMap::new(),
+            canonical_references: HashMap::new(),
+            warnings: Vec::new(),
         }
     }
 
@@ -215,13 +254,64 @@ impl BasicComponentCache {
         prime_component_cache::prime_component_cache(rig, loader).await
     }
 
+    /// Primes the cache with every component which can be loaded, returning the
+    /// errors of the components which couldn't be loaded rather than stopping at
+    /// the first error.
+    pub async fn primed_collecting_errors(
+        rig: &Rig,
+        loader: &impl ComponentsLoader,
+    ) -> (Self, Vec<ComponentLoadError>) {
+        prime_component_cache::prime_component_cache_collecting_errors(rig, loader).await
+    }
+
     pub fn for_primed(components: HashMap<SlipwayReference, PrimedComponent>) -> Self {
-        Self { components }
+        let canonical_references = components
+            .keys()
+            .map(|reference| (reference.canonical(), reference.clone()))
+            .collect();
+
+        Self {
+            components,
+            canonical_references,
+            warnings: Vec::new(),
+        }
     }
 
     pub fn into_inner(self) -> HashMap<SlipwayReference, PrimedComponent> {
         self.components
     }
+
+    /// Returns each component in the cache along with its reference.
+    pub fn iter(&self) -> impl Iterator<Item = (&SlipwayReference, &PrimedComponent)> {
+        self.components.iter()
+    }
+
+    /// Returns the number of components in the cache.
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Returns any non-fatal issues found while priming the cache.
+    pub fn warnings(&self) -> &[ComponentLoadWarning] {
+        &self.warnings
+    }
+
+    fn add_warning(&mut self, warning: ComponentLoadWarning) {
+        self.warnings.push(warning);
+    }
+
+    fn stored_reference<'a>(
+        &'a self,
+        component_reference: &'a SlipwayReference,
+    ) -> &'a SlipwayReference {
+        self.canonical_references
+            .get(&component_reference.canonical())
+            .unwrap_or(component_reference)
+    }
 }
 
 impl default::Default for BasicComponentCache {
@@ -233,6 +323,7 @@ impl default::Default for BasicComponentCache {
 impl ComponentCache for BasicComponentCache {
     fn clear(&mut self) {
         self.components.clear();
+        self.canonical_references.clear();
     }
 
     fn add(
@@ -241,8 +332,14 @@ impl ComponentCache for BasicComponentCache {
         definition: Component<Schema>,
         files: Arc<ComponentFiles>,
     ) {
+        let stored_reference = self
+            .canonical_references
+            .entry(component_reference.canonical())
+            .or_insert_with(|| component_reference.clone())
+            .clone();
+
         self.components.insert(
-            component_reference.clone(),
+            stored_reference,
             PrimedComponent {
                 definition: Arc::new(definition),
                 files,
@@ -251,12 +348,13 @@ impl ComponentCache for BasicComponentCache {
     }
 
     fn try_get(&self, component_reference: &SlipwayReference) -> Option<&PrimedComponent> {
-        self.components.get(component_reference)
+        self.components
+            .get(self.stored_reference(component_reference))
     }
 
     fn get(&self, component_reference: &SlipwayReference) -> &PrimedComponent {
         self.components
-            .get(component_reference)
+            .get(self.stored_reference(component_reference))
             .expect_with(|| format!("component \"{}\" not found in cache", component_reference))
     }
 }
@@ -3,37 +3,95 @@ use std::{
     sync::Arc,
 };
 
+use tracing::info;
+
 use crate::{
-    Callout, Component, ComponentHandle, Rig, Schema, SlipwayReference,
+    Callout, Component, ComponentHandle, Permission, PermissionsChainLinkRef, Rig, Rigging, Schema,
+    SlipwayReference,
     custom_iter_tools::CustomIterTools,
     errors::{ComponentLoadError, ComponentLoadErrorInner},
     load::ComponentsLoader,
     parse_component,
 };
 
-use super::{BasicComponentCache, ComponentCache, ComponentFiles, parse_schema};
+use super::{
+    BasicComponentCache, ComponentCache, ComponentFiles, ComponentLoadWarning, parse_schema,
+};
 
 pub(super) async fn prime_component_cache(
     rig: &Rig,
     components_loader: &impl ComponentsLoader,
 ) -> Result<BasicComponentCache, ComponentLoadError> {
+    let (component_cache, _) = prime_component_cache_inner(rig, components_loader, true).await?;
+    Ok(component_cache)
+}
+
+/// Primes the cache with every component which can be loaded, returning the
+/// errors of the components which couldn't be loaded rather than the first error.
+pub(super) async fn prime_component_cache_collecting_errors(
+    rig: &Rig,
+    components_loader: &impl ComponentsLoader,
+) -> (BasicComponentCache, Vec<ComponentLoadError>) {
+    prime_component_cache_inner(rig, components_loader, false)
+        .await
+        .expect("errors should be collected rather than returned")
+}
+
+async fn prime_component_cache_inner(
+    rig: &Rig,
+    components_loader: &impl ComponentsLoader,
+    fail_fast: bool,
+) -> Result<(BasicComponentCache, Vec<ComponentLoadError>), ComponentLoadError> {
     let mut component_cache = BasicComponentCache::empty();
-    let mut pending_component_references = get_rig_distinct_references(rig);
-    let mut loaded_component_references: HashSet<SlipwayReference> = HashSet::new();
+    let mut errors = Vec::new();
+
+    // References are keyed by their canonical form, so that references which differ
+    // only in form (such as `file:./a.tar` and `file:a.tar`) are only loaded once.
+    let mut pending_component_references = by_canonical(get_rig_distinct_references(rig));
+    let mut loaded_component_references: HashSet<String> = HashSet::new();
 
     while !pending_component_references.is_empty() {
-        let next = pending_component_references.drain().collect::<Vec<_>>();
+        let next = pending_component_references
+            .drain()
+            .map(|(_, reference)| reference)
+            .collect::<Vec<_>>();
         let loaded_components = components_loader.load_components(&next).await;
-        loaded_component_references.extend(next);
+        loaded_component_references.extend(next.iter().map(SlipwayReference::canonical));
 
         for maybe_loaded_component in loaded_components {
-            let loaded_component = maybe_loaded_component?;
+            let maybe_definition = match maybe_loaded_component {
+                Ok(loaded_component) => parse_loaded_component_definition(&loaded_component)
+                    .await
+                    .map(|definition| (loaded_component, definition)),
+                Err(e) => Err(e),
+            };
+
+            let (loaded_component, definition) = match maybe_definition {
+                Ok(loaded) => loaded,
+                Err(e) if fail_fast => return Err(e),
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+
+            for warning in get_component_warnings(&loaded_component.reference, &definition) {
+                component_cache.add_warning(warning);
+            }
 
-            let definition = parse_loaded_component_definition(&loaded_component).await?;
+            if let Some(permissions) = definition.permissions.as_ref() {
+                info!(
+                    "Component \"{}\" declares permissions: {}",
+                    loaded_component.reference,
+                    format_permissions(permissions)
+                );
+            }
 
             let new_references = {
-                let mut all_references = get_component_distinct_references(&definition);
-                all_references.retain(|r| !loaded_component_references.contains(r));
+                let mut all_references =
+                    by_canonical(get_component_distinct_references(&definition));
+                all_references
+                    .retain(|canonical, _| !loaded_component_references.contains(canonical));
                 all_references
             };
 
@@ -47,7 +105,33 @@ pub(super) async fn prime_component_cache(
         }
     }
 
-    Ok(component_cache)
+    let mut permission_warnings = get_declared_permission_warnings(&rig.rigging, &component_cache);
+    for primed_component in component_cache.components.values() {
+        if let Some(rigging) = primed_component.definition.rigging.as_ref() {
+            permission_warnings.extend(get_declared_permission_warnings(rigging, &component_cache));
+        }
+    }
+
+    for warning in permission_warnings {
+        component_cache.add_warning(warning);
+    }
+
+    Ok((component_cache, errors))
+}
+
+/// Parses and validates a loaded component's definition, including its input and
+/// output schemas, returning any non-fatal warnings.
+///
+/// This performs the same checks as loading the component into a rig, so the
+/// errors are the same as a consumer of the component would see.
+pub async fn validate_component(
+    loaded_component: &super::LoadedComponent,
+) -> Result<Vec<ComponentLoadWarning>, ComponentLoadError> {
+    let definition = parse_loaded_component_definition(loaded_component).await?;
+    Ok(get_component_warnings(
+        &loaded_component.reference,
+        &definition,
+    ))
 }
 
 pub(super) async fn parse_loaded_component_definition(
@@ -90,11 +174,151 @@ pub(super) async fn parse_component_with_json(
         output,
         constants: parsed_definition.constants,
         rigging: parsed_definition.rigging,
+        fragment_inputs: parsed_definition.fragment_inputs,
+        fragment_input: parsed_definition.fragment_input,
+        fragment_output: parsed_definition.fragment_output,
+        permissions: parsed_definition.permissions,
+        batchable: parsed_definition.batchable,
+        output_content_type: parsed_definition.output_content_type,
+        fonts: parsed_definition.fonts,
         callouts: parsed_definition.callouts,
     };
     Ok(definition)
 }
 
+fn get_component_warnings<T>(
+    reference: &SlipwayReference,
+    definition: &Component<T>,
+) -> Vec<ComponentLoadWarning> {
+    let mut warnings = Vec::new();
+
+    if let SlipwayReference::Registry {
+        publisher,
+        name,
+        version,
+    } = reference
+        && (publisher != &definition.publisher.0
+            || name != &definition.name.0
+            || version != &definition.version)
+    {
+        warnings.push(ComponentLoadWarning {
+            reference: reference.clone(),
+            message: format!(
+                "Component definition has the ID \"{}.{}.{}\", which does not match the reference.",
+                definition.publisher, definition.name, definition.version
+            ),
+        });
+    }
+
+    if let Some(fragment_inputs) = definition.fragment_inputs.as_ref() {
+        let mut seen = HashSet::new();
+        for handle in fragment_inputs {
+            if !seen.insert(handle) {
+                warnings.push(ComponentLoadWarning {
+                    reference: reference.clone(),
+                    message: format!("Fragment input \"{}\" is specified more than once.", handle),
+                });
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Returns warnings for components in the rigging which are granted fewer,
+/// or more, permissions than they declare they require.
+fn get_declared_permission_warnings(
+    rigging: &Rigging,
+    component_cache: &BasicComponentCache,
+) -> Vec<ComponentLoadWarning> {
+    let mut warnings = Vec::new();
+
+    let mut check = |handle: &ComponentHandle,
+                     reference: &SlipwayReference,
+                     chain: Vec<PermissionsChainLinkRef>| {
+        let Some(declared) = component_cache
+            .try_get(reference)
+            .and_then(|c| c.definition.permissions.as_ref())
+        else {
+            return;
+        };
+
+        for permission in declared {
+            if !is_granted(permission, &chain) {
+                warnings.push(ComponentLoadWarning {
+                    reference: reference.clone(),
+                    message: format!(
+                        "Component \"{handle}\" declares it requires the permission {}, but the rig does not grant it.",
+                        format_permission(permission)
+                    ),
+                });
+            }
+        }
+
+        // Only a single link can be meaningfully compared, as a longer chain
+        // is the intersection of the permissions of each link.
+        if let [link] = chain.as_slice() {
+            for permission in link.allow.iter() {
+                if !declared.iter().any(|d| d.covers(permission)) {
+                    warnings.push(ComponentLoadWarning {
+                        reference: reference.clone(),
+                        message: format!(
+                            "Component \"{handle}\" is granted the permission {}, which it does not declare it requires.",
+                            format_permission(permission)
+                        ),
+                    });
+                }
+            }
+        }
+    };
+
+    let empty = Vec::new();
+    for (handle, component_rigging) in rigging.components.iter() {
+        check(
+            handle,
+            &component_rigging.component,
+            component_rigging.permissions_as_chain(),
+        );
+
+        for (callout_handle, callout) in component_rigging.callouts.iter().flatten() {
+            check(
+                callout_handle,
+                &callout.component,
+                vec![PermissionsChainLinkRef {
+                    allow: callout.allow.as_ref().unwrap_or(&empty),
+                    deny: callout.deny.as_ref().unwrap_or(&empty),
+                }],
+            );
+        }
+    }
+
+    warnings
+}
+
+/// Returns true if the permission is allowed, and not denied, by every link in the chain.
+/// A deny which overlaps the permission in either direction is treated as denying it.
+fn is_granted(permission: &Permission, chain: &[PermissionsChainLinkRef]) -> bool {
+    chain.iter().all(|link| {
+        link.allow.iter().any(|a| a.covers(permission))
+            && !link
+                .deny
+                .iter()
+                .any(|d| d.covers(permission) || permission.covers(d))
+    })
+}
+
+fn format_permission(permission: &Permission) -> String {
+    serde_json::to_string(permission).unwrap_or_else(|_| format!("{permission:?}"))
+}
+
+fn format_permissions(permissions: &[Permission]) -> String {
+    permissions
+        .iter()
+        .map(format_permission)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn handle_component_load_error<T>(
     reference: &SlipwayReference,
     result: Result<T, ComponentLoadErrorInner>,
@@ -129,6 +353,13 @@ fn get_component_distinct_references<T>(component: &Component<T>) -> HashSet<Sli
         .collect()
 }
 
+fn by_canonical(references: HashSet<SlipwayReference>) -> HashMap<String, SlipwayReference> {
+    references
+        .into_iter()
+        .map(|reference| (reference.canonical(), reference))
+        .collect()
+}
+
 fn get_callouts_references<'a>(
     callouts: &'a Option<HashMap<ComponentHandle, Callout>>,
 ) -> Box<dyn Iterator<Item = &'a SlipwayReference> + 'a> {
@@ -137,3 +368,217 @@ fn get_callouts_references<'a>(
         None => Box::new(std::iter::empty()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use common_macros::slipway_test_async;
+
+    use crate::{
+        LoadedComponent,
+        test_utils::{MockComponentsLoader, no_component_files, schema_any},
+        utils::ch,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_should_warn_when_definition_does_not_match_registry_reference() {
+        let reference = SlipwayReference::for_test("foo");
+        let mut definition = Component::<Schema>::for_test(&reference, schema_any(), schema_any());
+
+        assert!(get_component_warnings(&reference, &definition).is_empty());
+
+        definition.name = crate::Name::from_str("bar").unwrap();
+
+        let warnings = get_component_warnings(&reference, &definition);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].reference, reference);
+    }
+
+    #[test]
+    fn it_should_warn_for_duplicate_fragment_inputs() {
+        let reference = SlipwayReference::for_test("foo");
+        let mut definition = Component::<Schema>::for_test(&reference, schema_any(), schema_any());
+        definition.fragment_inputs = Some(vec![ch("a"), ch("b"), ch("a")]);
+
+        let warnings = get_component_warnings(&reference, &definition);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].message,
+            "Fragment input \"a\" is specified more than once."
+        );
+    }
+
+    #[slipway_test_async]
+    async fn it_should_collect_errors_of_components_which_fail_to_load() {
+        let known = SlipwayReference::for_test("known");
+        let missing = SlipwayReference::for_test("missing");
+        let rig = Rig::for_test(
+            serde_json::from_value(serde_json::json!({
+                "a": { "component": known },
+                "b": { "component": missing },
+            }))
+            .unwrap(),
+        );
+        let loader = MockComponentsLoader::new(HashMap::from([(
+            "known".to_string(),
+            (schema_any(), schema_any()),
+        )]));
+
+        assert!(prime_component_cache(&rig, &loader).await.is_err());
+
+        let (cache, errors) = prime_component_cache_collecting_errors(&rig, &loader).await;
+
+        assert!(cache.try_get(&known).is_some());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(*errors[0].reference, missing);
+    }
+
+    #[slipway_test_async]
+    async fn it_should_fail_to_validate_malformed_component() {
+        let reference = SlipwayReference::for_test("foo");
+        let loaded_component = LoadedComponent::new(
+            reference.clone(),
+            r#"{ "publisher": "test" }"#.to_string(),
+            no_component_files(),
+        );
+
+        let error = validate_component(&loaded_component).await.unwrap_err();
+
+        assert_eq!(*error.reference, reference);
+        assert!(matches!(
+            error.error,
+            ComponentLoadErrorInner::DefinitionParseFailed { .. }
+        ));
+    }
+
+    fn rigging_for(reference: &SlipwayReference, allow: Vec<Permission>) -> Rigging {
+        serde_json::from_value(serde_json::json!({
+            "a": {
+                "component": reference,
+                "allow": allow,
+            }
+        }))
+        .unwrap()
+    }
+
+    fn cache_with_declared_permissions(
+        reference: &SlipwayReference,
+        permissions: Vec<Permission>,
+    ) -> BasicComponentCache {
+        let mut definition = Component::<Schema>::for_test(reference, schema_any(), schema_any());
+        definition.permissions = Some(permissions);
+
+        let mut cache = BasicComponentCache::empty();
+        cache.add(reference, definition, no_component_files());
+        cache
+    }
+
+    #[test]
+    fn it_should_warn_when_rig_grants_fewer_permissions_than_declared() {
+        let reference = SlipwayReference::for_test("foo");
+        let cache = cache_with_declared_permissions(
+            &reference,
+            vec![Permission::Env(crate::StringPermission::Any {})],
+        );
+
+        let warnings = get_declared_permission_warnings(&rigging_for(&reference, vec![]), &cache);
+
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("the rig does not grant it"));
+    }
+
+    #[test]
+    fn it_should_warn_when_rig_grants_more_permissions_than_declared() {
+        let reference = SlipwayReference::for_test("foo");
+        let cache = cache_with_declared_permissions(
+            &reference,
+            vec![Permission::Env(crate::StringPermission::Any {})],
+        );
+
+        let warnings = get_declared_permission_warnings(
+            &rigging_for(&reference, vec![Permission::All]),
+            &cache,
+        );
+
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("does not declare it requires"));
+    }
+
+    #[test]
+    fn it_should_not_warn_when_rig_grants_declared_permissions() {
+        let reference = SlipwayReference::for_test("foo");
+        let permissions = vec![Permission::Env(crate::StringPermission::Any {})];
+        let cache = cache_with_declared_permissions(&reference, permissions.clone());
+
+        let warnings =
+            get_declared_permission_warnings(&rigging_for(&reference, permissions), &cache);
+
+        assert!(warnings.is_empty());
+    }
+
+    struct RecordingComponentsLoader {
+        requested: std::sync::Mutex<Vec<SlipwayReference>>,
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl ComponentsLoader for RecordingComponentsLoader {
+        async fn load_components(
+            &self,
+            component_references: &[SlipwayReference],
+        ) -> Vec<Result<LoadedComponent, ComponentLoadError>> {
+            self.requested
+                .lock()
+                .unwrap()
+                .extend_from_slice(component_references);
+
+            component_references
+                .iter()
+                .map(|component_reference| {
+                    let definition = Component::<Schema>::for_test(
+                        &SlipwayReference::for_test("b"),
+                        schema_any(),
+                        schema_any(),
+                    );
+
+                    Ok(LoadedComponent::new(
+                        component_reference.clone(),
+                        serde_json::to_string(&definition).unwrap(),
+                        no_component_files(),
+                    ))
+                })
+                .collect()
+        }
+    }
+
+    #[slipway_test_async]
+    async fn it_should_load_equivalent_references_once() {
+        let rig = Rig::for_test(
+            serde_json::from_value(serde_json::json!({
+                "a": { "component": "file:./b.tar" },
+                "b": { "component": "file:b.tar" },
+            }))
+            .unwrap(),
+        );
+        let loader = RecordingComponentsLoader {
+            requested: std::sync::Mutex::new(Vec::new()),
+        };
+
+        let cache = prime_component_cache(&rig, &loader).await.unwrap();
+
+        assert_eq!(loader.requested.lock().unwrap().len(), 1);
+        assert_eq!(cache.len(), 1);
+        assert!(
+            cache
+                .try_get(&SlipwayReference::from_str("file:./b.tar").unwrap())
+                .is_some()
+        );
+        assert!(
+            cache
+                .try_get(&SlipwayReference::from_str("file:b.tar").unwrap())
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+/// The maximum number of times a download is retried after being rate limited.
+pub(super) const MAX_RATE_LIMITED_RETRIES: u32 = 5;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Returns how long to wait before retrying a rate limited download.
+///
+/// A `Retry-After` header containing a number of seconds is honored, otherwise
+/// the delay doubles with each attempt. The delay is capped at one minute.
+pub(super) fn get_rate_limited_retry_delay(retry_after: Option<&str>, attempt: u32) -> Duration {
+    let delay = retry_after
+        .and_then(|retry_after| retry_after.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_else(|| INITIAL_BACKOFF.saturating_mul(2u32.saturating_pow(attempt)));
+
+    delay.min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_honor_retry_after_seconds() {
+        assert_eq!(
+            get_rate_limited_retry_delay(Some("3"), 0),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            get_rate_limited_retry_delay(Some(" 3 "), 4),
+            Duration::from_secs(3)
+        );
+    }
+
+    #[test]
+    fn it_should_back_off_exponentially_without_retry_after() {
+        assert_eq!(
+            get_rate_limited_retry_delay(None, 0),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            get_rate_limited_retry_delay(None, 1),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            get_rate_limited_retry_delay(None, 3),
+            Duration::from_secs(8)
+        );
+
+        // HTTP dates are not supported, so fall back to backing off.
+        assert_eq!(
+            get_rate_limited_retry_delay(Some("Wed, 21 Oct 2015 07:28:00 GMT"), 2),
+            Duration::from_secs(4)
+        );
+    }
+
+    #[test]
+    fn it_should_cap_retry_delay() {
+        assert_eq!(get_rate_limited_retry_delay(Some("3600"), 0), MAX_BACKOFF);
+        assert_eq!(get_rate_limited_retry_delay(None, 40), MAX_BACKOFF);
+    }
+}
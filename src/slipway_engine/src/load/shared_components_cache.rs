@@ -0,0 +1,236 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::{SlipwayReference, errors::ComponentLoadError};
+
+use super::{ComponentFiles, ComponentsLoader, LoadedComponent};
+
+/// A cache of loaded components which can be shared between rigs, for example
+/// across all the requests handled by a server, so that components resolved
+/// for one rig are reused by another.
+///
+/// Components are keyed by their canonical reference. Local components are not
+/// cached, so that changes on disk are picked up. Nothing is invalidated
+/// automatically, so the cache must be cleared if components may have changed.
+#[derive(Clone, Default)]
+pub struct SharedComponentsCache {
+    components: Arc<Mutex<HashMap<String, SharedComponent>>>,
+}
+
+struct SharedComponent {
+    definition: String,
+    files: Arc<ComponentFiles>,
+}
+
+impl SharedComponentsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a components loader which loads components from this cache,
+    /// falling back to the given loader for any which are not cached.
+    pub fn loader<'a, TLoader: ComponentsLoader>(
+        &'a self,
+        inner: &'a TLoader,
+    ) -> SharedComponentsLoader<'a, TLoader> {
+        SharedComponentsLoader { cache: self, inner }
+    }
+
+    /// Removes all components from the cache.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the number of components in the cache.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn try_get(&self, reference: &SlipwayReference) -> Option<LoadedComponent> {
+        self.lock().get(&reference.canonical()).map(|c| {
+            LoadedComponent::new(
+                reference.clone(),
+                c.definition.clone(),
+                Arc::clone(&c.files),
+            )
+        })
+    }
+
+    fn add(&self, component: &LoadedComponent) {
+        if matches!(component.reference, SlipwayReference::Local { .. }) {
+            return;
+        }
+
+        self.lock().insert(
+            component.reference.canonical(),
+            SharedComponent {
+                definition: component.definition.clone(),
+                files: Arc::clone(&component.files),
+            },
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, SharedComponent>> {
+        self.components
+            .lock()
+            .expect("should be able to lock shared components cache")
+    }
+}
+
+impl std::fmt::Debug for SharedComponentsCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedComponentsCache")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// A components loader which reuses components from a [`SharedComponentsCache`].
+pub struct SharedComponentsLoader<'a, TLoader: ComponentsLoader> {
+    cache: &'a SharedComponentsCache,
+    inner: &'a TLoader,
+}
+
+#[async_trait(?Send)]
+impl<TLoader: ComponentsLoader> ComponentsLoader for SharedComponentsLoader<'_, TLoader> {
+    async fn load_components(
+        &self,
+        component_references: &[SlipwayReference],
+    ) -> Vec<Result<LoadedComponent, ComponentLoadError>> {
+        let mut results: Vec<Option<Result<LoadedComponent, ComponentLoadError>>> =
+            component_references
+                .iter()
+                .map(|r| self.cache.try_get(r).map(Ok))
+                .collect();
+
+        let uncached_references: Vec<SlipwayReference> = component_references
+            .iter()
+            .zip(results.iter())
+            .filter(|(_, result)| result.is_none())
+            .map(|(r, _)| r.clone())
+            .collect();
+
+        debug!(
+            "Reusing {} of {} components from shared cache.",
+            component_references.len() - uncached_references.len(),
+            component_references.len()
+        );
+
+        if !uncached_references.is_empty() {
+            let mut loaded = self
+                .inner
+                .load_components(&uncached_references)
+                .await
+                .into_iter();
+
+            for result in results.iter_mut().filter(|r| r.is_none()) {
+                let loaded_component = loaded
+                    .next()
+                    .expect("loader should return a result for each component reference");
+
+                if let Ok(component) = &loaded_component {
+                    self.cache.add(component);
+                }
+
+                *result = Some(loaded_component);
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|r| r.expect("every component reference should have a result"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use common_macros::slipway_test_async;
+
+    use crate::test_utils::no_component_files;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct CountingComponentsLoader {
+        loaded: AtomicUsize,
+    }
+
+    #[async_trait(?Send)]
+    impl ComponentsLoader for CountingComponentsLoader {
+        async fn load_components(
+            &self,
+            component_references: &[SlipwayReference],
+        ) -> Vec<Result<LoadedComponent, ComponentLoadError>> {
+            self.loaded
+                .fetch_add(component_references.len(), Ordering::SeqCst);
+            component_references
+                .iter()
+                .map(|r| {
+                    Ok(LoadedComponent::new(
+                        r.clone(),
+                        "{}".to_string(),
+                        no_component_files(),
+                    ))
+                })
+                .collect()
+        }
+    }
+
+    #[slipway_test_async]
+    async fn it_should_reuse_components_across_loaders() {
+        let inner = CountingComponentsLoader::default();
+
+        let cache = SharedComponentsCache::new();
+
+        let a = SlipwayReference::for_test("a");
+        let b = SlipwayReference::for_test("b");
+
+        let results = cache.loader(&inner).load_components(&[a.clone()]).await;
+        assert!(results[0].is_ok());
+        assert_eq!(inner.loaded.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.len(), 1);
+
+        let results = cache
+            .loader(&inner)
+            .load_components(&[b.clone(), a.clone()])
+            .await;
+        assert_eq!(results[0].as_ref().unwrap().reference, b);
+        assert_eq!(results[1].as_ref().unwrap().reference, a);
+        assert_eq!(inner.loaded.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.len(), 2);
+
+        cache.clear();
+        assert!(cache.is_empty());
+
+        cache.loader(&inner).load_components(&[a]).await;
+        assert_eq!(inner.loaded.load(Ordering::SeqCst), 3);
+    }
+
+    #[slipway_test_async]
+    async fn it_should_not_cache_local_components() {
+        let inner = CountingComponentsLoader::default();
+
+        let cache = SharedComponentsCache::new();
+        let local = SlipwayReference::Local {
+            path: "component".into(),
+        };
+
+        cache.loader(&inner).load_components(&[local.clone()]).await;
+        cache.loader(&inner).load_components(&[local]).await;
+
+        assert_eq!(inner.loaded.load(Ordering::SeqCst), 2);
+        assert!(cache.is_empty());
+    }
+}
@@ -1,16 +1,24 @@
 use async_trait::async_trait;
 
 use crate::{
-    ComponentFilesLoader, LoadedComponent, PrimedComponent, SlipwayReference,
+    BasicComponentCache, ComponentFilesLoader, LoadedComponent, PrimedComponent, SlipwayReference,
     SpecialComponentReference,
 };
 
-use std::{str::FromStr, sync::Arc};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+use tokio::sync::OnceCell;
 
 use super::{ComponentFiles, prime_component_cache::parse_component_with_json};
 
 const SLIPWAY_PUBLISHER: &str = "slipwayhq";
 
+const SPECIAL_COMPONENT_REFERENCES: [SpecialComponentReference; 2] = [
+    SpecialComponentReference::Passthrough,
+    SpecialComponentReference::Sink,
+];
+
+static PRIMED_SPECIAL_COMPONENTS: OnceCell<BasicComponentCache> = OnceCell::const_new();
+
 pub fn load_special_component(reference: &SpecialComponentReference) -> LoadedComponent {
     let definition = get_special_definition(reference);
 
@@ -41,6 +49,24 @@ pub async fn prime_special_component(reference: &SpecialComponentReference) -> P
     }
 }
 
+/// Returns a cache containing every special component.
+/// The special components are primed on first use and then shared for the lifetime
+/// of the process, so callers such as the fragment runner don't need to re-prime them.
+pub async fn primed_special_components_cache() -> &'static BasicComponentCache {
+    PRIMED_SPECIAL_COMPONENTS
+        .get_or_init(|| async {
+            let mut components = HashMap::new();
+            for reference in SPECIAL_COMPONENT_REFERENCES.iter() {
+                components.insert(
+                    SlipwayReference::Special(reference.clone()),
+                    prime_special_component(reference).await,
+                );
+            }
+            BasicComponentCache::for_primed(components)
+        })
+        .await
+}
+
 fn get_special_definition(
     reference: &SpecialComponentReference,
 ) -> crate::Component<serde_json::Value> {
@@ -55,6 +81,13 @@ fn get_special_definition(
         output: serde_json::json!({}),
         constants: None,
         rigging: None,
+        fragment_inputs: None,
+        fragment_input: None,
+        fragment_output: None,
+        permissions: None,
+        batchable: None,
+        output_content_type: None,
+        fonts: None,
         callouts: None,
     }
 }
@@ -91,3 +124,28 @@ impl ComponentFilesLoader for NoFiles {
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+
+    use crate::ComponentCache;
+
+    use super::*;
+
+    #[slipway_test_async]
+    async fn it_should_prime_special_components_once() {
+        let first = primed_special_components_cache().await;
+        let second = primed_special_components_cache().await;
+
+        assert!(std::ptr::eq(first, second));
+
+        for reference in SPECIAL_COMPONENT_REFERENCES.iter() {
+            let primed = first
+                .try_get(&SlipwayReference::Special(reference.clone()))
+                .expect("Special component should be primed");
+
+            assert_eq!(primed.definition.name.0, format!("{}", reference));
+        }
+    }
+}
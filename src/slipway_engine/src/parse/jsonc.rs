@@ -0,0 +1,137 @@
+/// Converts JSON with comments and trailing commas (JSONC) into strict JSON.
+///
+/// Comments and trailing commas are replaced with spaces rather than removed,
+/// and newlines are preserved, so that the line and column numbers in any
+/// parse errors still refer to the original source.
+pub fn strip_jsonc(input: &str) -> String {
+    let mut output = input.as_bytes().to_vec();
+    blank_comments(&mut output);
+    blank_trailing_commas(&mut output);
+
+    // Only ASCII bytes outside of strings are replaced with ASCII spaces,
+    // and comments are always blanked in full, so the result is valid UTF-8.
+    String::from_utf8(output).expect("stripped JSONC should be valid UTF-8")
+}
+
+fn blank_comments(bytes: &mut [u8]) {
+    let mut index = 0;
+    let mut in_string = false;
+
+    while index < bytes.len() {
+        let byte = bytes[index];
+
+        if in_string {
+            match byte {
+                b'\\' => index += 1,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            index += 1;
+            continue;
+        }
+
+        match (byte, bytes.get(index + 1)) {
+            (b'"', _) => {
+                in_string = true;
+                index += 1;
+            }
+            (b'/', Some(b'/')) => {
+                while index < bytes.len() && bytes[index] != b'\n' {
+                    blank(bytes, index);
+                    index += 1;
+                }
+            }
+            (b'/', Some(b'*')) => {
+                blank(bytes, index);
+                blank(bytes, index + 1);
+                index += 2;
+                while index < bytes.len() {
+                    if bytes[index] == b'*' && bytes.get(index + 1) == Some(&b'/') {
+                        blank(bytes, index);
+                        blank(bytes, index + 1);
+                        index += 2;
+                        break;
+                    }
+                    blank(bytes, index);
+                    index += 1;
+                }
+            }
+            _ => index += 1,
+        }
+    }
+}
+
+fn blank_trailing_commas(bytes: &mut [u8]) {
+    let mut index = 0;
+    let mut in_string = false;
+
+    while index < bytes.len() {
+        let byte = bytes[index];
+
+        if in_string {
+            match byte {
+                b'\\' => index += 1,
+                b'"' => in_string = false,
+                _ => {}
+            }
+        } else if byte == b'"' {
+            in_string = true;
+        } else if byte == b',' {
+            let next = bytes[index + 1..]
+                .iter()
+                .find(|next| !next.is_ascii_whitespace());
+            if matches!(next, Some(b'}') | Some(b']')) {
+                bytes[index] = b' ';
+            }
+        }
+
+        index += 1;
+    }
+}
+
+/// Replaces the byte with a space, unless it is a newline.
+fn blank(bytes: &mut [u8], index: usize) {
+    if bytes[index] != b'\n' && bytes[index] != b'\r' {
+        bytes[index] = b' ';
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_strip_comments_and_trailing_commas() {
+        let input = r#"{
+    // A line comment.
+    "a": "// not a comment", /* A block
+    comment */ "b": [1, 2,],
+    "c": "escaped \" /* quote */",
+}"#;
+
+        let output = strip_jsonc(input);
+
+        assert_eq!(output.len(), input.len());
+        assert_eq!(output.lines().count(), input.lines().count());
+
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "a": "// not a comment",
+                "b": [1, 2],
+                "c": "escaped \" /* quote */"
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_preserve_error_positions() {
+        let input = "{\n  // comment\n  \"a\": 1,\n  \"b\": x\n}";
+
+        let error = serde_json::from_str::<serde_json::Value>(&strip_jsonc(input)).unwrap_err();
+
+        assert_eq!(error.line(), 4);
+        assert_eq!(error.column(), 8);
+    }
+}
@@ -1,12 +1,15 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use crate::errors::{ComponentLoadErrorInner, RigError};
 
 use self::types::{Component, Rig};
 
+mod jsonc;
 pub(crate) mod types;
 pub(crate) mod url;
 
+pub use jsonc::strip_jsonc;
+
 pub fn parse_rig(input: &str) -> Result<Rig, RigError> {
     serde_json::from_str(input).map_err(|error| RigError::RigParseFailed { error })
 }
@@ -22,6 +25,41 @@ pub fn parse_component(
         .map_err(|e| ComponentLoadErrorInner::DefinitionParseFailed { error: Arc::new(e) })
 }
 
+/// Parses a rig definition which may contain comments and trailing commas.
+/// Error line and column numbers refer to the original input.
+pub fn parse_rig_jsonc(input: &str) -> Result<Rig, RigError> {
+    parse_rig(&strip_jsonc(input))
+}
+
+/// Parses a YAML rig definition. The YAML is converted to JSON before
+/// deserialization, so the result is identical to the equivalent JSON rig.
+pub fn parse_rig_yaml(input: &str) -> Result<Rig, RigError> {
+    let json = serde_norway::from_str::<serde_json::Value>(input)
+        .map_err(|error| RigError::RigYamlParseFailed { error })?;
+    parse_rig_json(json)
+}
+
+/// Converts a YAML component definition to the equivalent JSON value.
+pub fn yaml_to_json(input: &str) -> Result<serde_json::Value, ComponentLoadErrorInner> {
+    serde_norway::from_str(input)
+        .map_err(|e| ComponentLoadErrorInner::DefinitionYamlParseFailed { error: Arc::new(e) })
+}
+
+/// Returns true if the path has a `.yaml` or `.yml` extension.
+pub fn is_yaml_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "yaml" || extension == "yml")
+}
+
+/// Returns true if the path has a `.jsonc` extension.
+///
+/// JSON5 files aren't accepted, as only comments and trailing commas are
+/// supported rather than the full JSON5 syntax.
+pub fn is_jsonc_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "jsonc")
+}
+
 #[cfg(test)]
 mod tests {
     use std::{fmt::Debug, path::Path};
@@ -97,6 +135,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_should_parse_yaml_rig_identically_to_json_rig() {
+        let json = r#"
+        {
+            "constants": { "width": 800 },
+            "rigging": {
+                "render": {
+                    "component": "a.b.1.0.0",
+                    "input": { "width": "$.constants.width" }
+                }
+            }
+        }"#;
+
+        let yaml = r#"
+constants:
+  width: 800
+rigging:
+  render:
+    component: a.b.1.0.0
+    input:
+      width: $.constants.width
+"#;
+
+        let from_json = serde_json::to_value(parse_rig(json).unwrap()).unwrap();
+        let from_yaml = serde_json::to_value(parse_rig_yaml(yaml).unwrap()).unwrap();
+        assert_eq!(from_json, from_yaml);
+    }
+
+    #[test]
+    fn it_should_detect_yaml_paths() {
+        assert!(is_yaml_path(Path::new("rig.yaml")));
+        assert!(is_yaml_path(Path::new("path/to/rig.yml")));
+        assert!(!is_yaml_path(Path::new("rig.json")));
+        assert!(!is_yaml_path(Path::new("yaml")));
+    }
+
+    #[test]
+    fn it_should_detect_jsonc_paths() {
+        assert!(is_jsonc_path(Path::new("rig.jsonc")));
+        assert!(is_jsonc_path(Path::new("path/to/rig.jsonc")));
+        assert!(!is_jsonc_path(Path::new("rig.json")));
+        assert!(!is_jsonc_path(Path::new("rig.json5")));
+    }
+
     #[test]
     fn it_should_provide_a_sensible_message_when_duplicate_rigging_keys() {
         let json = r#"
@@ -127,4 +209,48 @@ mod tests {
             },
         }
     }
+
+    #[test]
+    fn it_should_reject_rig_output_names_which_are_not_valid_file_names() {
+        for name in ["../../x", "/etc/foo", "a.b", ""] {
+            let json = serde_json::json!({
+                "rigging": { "a": { "component": "a.b.1.0.0" } },
+                "outputs": { name: "a" }
+            });
+
+            let Err(RigError::RigParseFailed { error }) = parse_rig(&json.to_string()) else {
+                panic!("Expected output name \"{name}\" to be rejected");
+            };
+            assert!(
+                error.to_string().contains("RigOutputName"),
+                "Unexpected error: {error}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_reject_rig_outputs_which_are_not_in_the_rigging() {
+        let json = r#"
+        {
+            "rigging": { "a": { "component": "a.b.1.0.0" } },
+            "outputs": { "chart": "a", "data": "b" }
+        }"#;
+
+        let Err(RigError::RigParseFailed { error }) = parse_rig(json) else {
+            panic!("Expected an error");
+        };
+        assert!(
+            error
+                .to_string()
+                .contains("Rig output \"data\" refers to component \"b\""),
+            "Unexpected error: {error}"
+        );
+
+        let json = r#"
+        {
+            "rigging": { "a": { "component": "a.b.1.0.0" } },
+            "outputs": { "chart": "a" }
+        }"#;
+        assert!(parse_rig(json).is_ok());
+    }
 }
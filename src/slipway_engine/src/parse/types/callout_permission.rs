@@ -0,0 +1,52 @@
+use super::{CalloutPermission, ComponentHandle, SlipwayReference};
+
+impl CalloutPermission {
+    pub fn matches(&self, handle: &ComponentHandle, component: &SlipwayReference) -> bool {
+        if let Some(required_handle) = self.handle.as_ref()
+            && required_handle != handle
+        {
+            return false;
+        }
+
+        if let Some(required_component) = self.component.as_ref()
+            && required_component != component
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::utils::ch;
+
+    use super::*;
+
+    fn reference(s: &str) -> SlipwayReference {
+        SlipwayReference::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn it_should_match_any_callout() {
+        let permission = CalloutPermission {
+            handle: None,
+            component: None,
+        };
+        assert!(permission.matches(&ch("increment"), &reference("p1.n1.1.0.0")));
+    }
+
+    #[test]
+    fn it_should_match_handle_and_component() {
+        let permission = CalloutPermission {
+            handle: Some(ch("increment")),
+            component: Some(reference("p1.n1.1.0.0")),
+        };
+        assert!(permission.matches(&ch("increment"), &reference("p1.n1.1.0.0")));
+        assert!(!permission.matches(&ch("other"), &reference("p1.n1.1.0.0")));
+        assert!(!permission.matches(&ch("increment"), &reference("p1.n2.1.0.0")));
+    }
+}
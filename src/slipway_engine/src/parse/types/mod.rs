@@ -10,7 +10,10 @@
 //! and is what the users will expect based on other toolchains
 //! such as Node's package.json.
 
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
 
 use semver::Version;
 use serde::{Deserialize, Serialize};
@@ -20,17 +23,22 @@ use url::Url;
 use crate::errors::RigError;
 
 use self::{
-    primitives::{ComponentHandle, Description, Name, Publisher},
+    primitives::{ComponentHandle, Description, Name, Publisher, RigOutputName},
     slipway_id::SlipwayId,
     slipway_reference::SlipwayReference,
 };
 
+mod callout_permission;
 mod local_component_permission;
 mod path_permission;
+mod permission_covers;
 pub(crate) mod primitives;
 mod registry_component_permission;
+mod rig_constants;
+mod rig_overlay;
 pub(crate) mod slipway_id;
 pub(crate) mod slipway_reference;
+mod sorted_map;
 mod string_permission;
 mod url_permission;
 
@@ -45,7 +53,7 @@ fn parse_component_version(version_string: &str) -> Result<Version, RigError> {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
-#[serde(deny_unknown_fields)]
+#[serde(try_from = "UncheckedRig")]
 pub struct Rig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<Description>,
@@ -57,6 +65,55 @@ pub struct Rig {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context: Option<DefaultRigContext>,
+
+    /// Named outputs of the rig, each of which is the output of a component.
+    /// When specified, these are the rig's outputs rather than a single output component.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<BTreeMap<RigOutputName, ComponentHandle>>,
+
+    /// Whether strings in component inputs can interpolate JSON path references
+    /// between `{{` and `}}`, for example `"https://{{$.constants.host}}/v1"`.
+    /// Disabled by default, so strings which happen to contain `{{` are unchanged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub templates: Option<bool>,
+}
+
+/// A rig as it is deserialized, before checks which span multiple fields.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UncheckedRig {
+    description: Option<Description>,
+    constants: Option<serde_json::Value>,
+    rigging: Rigging,
+    context: Option<DefaultRigContext>,
+    outputs: Option<BTreeMap<RigOutputName, ComponentHandle>>,
+    templates: Option<bool>,
+}
+
+impl TryFrom<UncheckedRig> for Rig {
+    type Error = RigError;
+
+    fn try_from(rig: UncheckedRig) -> Result<Self, Self::Error> {
+        // Check the outputs exist now, rather than failing after the rig has run.
+        for (name, handle) in rig.outputs.iter().flatten() {
+            if !rig.rigging.components.contains_key(handle) {
+                return Err(RigError::RigValidationFailed {
+                    error: format!(
+                        "Rig output \"{name}\" refers to component \"{handle}\", which is not in the rigging."
+                    ),
+                });
+            }
+        }
+
+        Ok(Rig {
+            description: rig.description,
+            constants: rig.constants,
+            rigging: rig.rigging,
+            context: rig.context,
+            outputs: rig.outputs,
+            templates: rig.templates,
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -69,7 +126,10 @@ pub struct DefaultRigContext {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Rigging {
     #[serde(flatten)]
-    #[serde(with = "::serde_with::rust::maps_duplicate_key_is_error")]
+    #[serde(
+        serialize_with = "sorted_map::serialize",
+        deserialize_with = "::serde_with::rust::maps_duplicate_key_is_error::deserialize"
+    )]
     pub components: HashMap<ComponentHandle, ComponentRigging>,
 }
 
@@ -112,8 +172,41 @@ pub struct ComponentRigging {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub permissions_chain: Option<Vec<PermissionsChainLink>>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "sorted_map::serialize_option"
+    )]
     pub callouts: Option<Callouts>,
+
+    /// The identifier of the component runner to use, for example `wasmtime` or `js_boa`.
+    /// If not specified, every runner which is able to run the component is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runner: Option<String>,
+
+    /// Whether to reuse the output of another memoized component in the rig which has
+    /// identical rigging and has already run with an identical input, rather than running
+    /// the component again. Only use this for components which are pure functions of their input.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memoize: Option<bool>,
+
+    /// A condition under which the component is skipped rather than run,
+    /// in which case its output is `null`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip: Option<SkipCondition>,
+}
+
+/// A condition, evaluated against a component's resolved input, under which
+/// the component is skipped rather than run.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum SkipCondition {
+    /// Skip the component if its resolved input is `null`, for example because it
+    /// is an optional (`$$?`) reference to a value which does not exist.
+    NullInput,
+
+    /// Skip the component unless the JSON path, evaluated against its resolved input,
+    /// finds a value which is not `null` or `false`.
+    Unless(String),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -191,7 +284,13 @@ pub enum Permission {
 
     RegistryComponents(RegistryComponentPermission),
     HttpComponents(UrlPermission),
+    GitComponents(UrlPermission),
     LocalComponents(LocalComponentPermission),
+
+    /// Restricts which callouts a component may run. If a level of the call chain allows
+    /// any `callouts` permissions, only matching callouts are allowed at that level.
+    /// If it allows none, callouts are not restricted at that level.
+    Callouts(CalloutPermission),
 }
 
 impl Permission {
@@ -258,6 +357,18 @@ pub struct RegistryComponentPermission {
     pub version: Option<semver::VersionReq>,
 }
 
+/// Matches callouts by handle, component reference, or both.
+/// If neither are specified, all callouts match.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct CalloutPermission {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handle: Option<ComponentHandle>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<SlipwayReference>,
+}
+
 pub(crate) static PERMISSIONS_ALL_VEC: LazyLock<Vec<Permission>> =
     LazyLock::new(|| vec![Permission::All]);
 
@@ -276,14 +387,80 @@ pub struct Component<TSchema> {
     pub input: TSchema,
     pub output: TSchema,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "sorted_map::serialize_option"
+    )]
     pub callouts: Option<Callouts>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rigging: Option<Rigging>,
 
+    /// For Fragment Components, the handles of additional passthrough components which
+    /// are populated from the top-level properties of the input object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fragment_inputs: Option<Vec<ComponentHandle>>,
+
+    /// For Fragment Components, the handle of the passthrough component which is
+    /// populated with the input. Defaults to `input`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fragment_input: Option<ComponentHandle>,
+
+    /// For Fragment Components, the handle of the component whose output is
+    /// the output of the fragment. Defaults to `output`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fragment_output: Option<ComponentHandle>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub constants: Option<serde_json::Value>,
+
+    /// The permissions the component declares it requires, so that they can be
+    /// reviewed ahead of time and compared against what a rig grants.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<Vec<Permission>>,
+
+    /// Whether the component is a pure function of its input, which allows batched
+    /// callouts to run every input using a single instance of the component.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batchable: Option<bool>,
+
+    /// The content type the component's output should be presented as, so that
+    /// the output can be written or served without guessing from its shape.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_content_type: Option<OutputContentType>,
+
+    /// The font stacks the component requires, such as `"Roboto, sans-serif"`, so that
+    /// missing fonts can be reported before the component is run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fonts: Option<Vec<String>>,
+}
+
+/// The content type a component's output is presented as when it is written
+/// to a file or returned by the server.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputContentType {
+    /// The output is presented as JSON.
+    #[serde(rename = "application/json")]
+    Json,
+
+    /// The output is a canvas, presented as a PNG image.
+    #[serde(rename = "image/png")]
+    Png,
+
+    /// The output has an `svg` field containing SVG markup, presented as a vector image.
+    #[serde(rename = "image/svg+xml")]
+    Svg,
+}
+
+impl OutputContentType {
+    /// The file extension used when the output is saved to a file.
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            OutputContentType::Json => "json",
+            OutputContentType::Png => "png",
+            OutputContentType::Svg => "svg",
+        }
+    }
 }
 
 impl<TSchema> Component<TSchema> {
@@ -343,6 +520,8 @@ impl Clone for Schema {
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use common_macros::slipway_test;
     use semver::VersionReq;
 
@@ -356,6 +535,26 @@ mod tests {
         );
     }
 
+    #[slipway_test]
+    fn test_deserialize_output_content_type() {
+        assert_eq!(
+            serde_json::from_str::<OutputContentType>(r#""application/json""#).unwrap(),
+            OutputContentType::Json
+        );
+
+        assert_eq!(
+            serde_json::from_str::<OutputContentType>(r#""image/png""#).unwrap(),
+            OutputContentType::Png
+        );
+
+        assert_eq!(
+            serde_json::from_str::<OutputContentType>(r#""image/svg+xml""#).unwrap(),
+            OutputContentType::Svg
+        );
+
+        assert!(serde_json::from_str::<OutputContentType>(r#""text/plain""#).is_err());
+    }
+
     #[slipway_test]
     fn test_deserialize_http_permission() {
         assert_eq!(
@@ -557,6 +756,24 @@ mod tests {
         );
     }
 
+    #[slipway_test]
+    fn test_deserialize_git_component_permission() {
+        assert_eq!(
+            serde_json::from_str::<Permission>(r#"{"permission":"git_components"}"#).unwrap(),
+            Permission::GitComponents(UrlPermission::Any {})
+        );
+
+        assert_eq!(
+            serde_json::from_str::<Permission>(
+                r#"{"permission":"git_components", "prefix": "https://github.com/slipwayhq/"}"#
+            )
+            .unwrap(),
+            Permission::GitComponents(UrlPermission::Prefix {
+                prefix: Url::parse("https://github.com/slipwayhq/").unwrap()
+            })
+        );
+    }
+
     #[slipway_test]
     fn test_deserialize_local_component_permission() {
         assert_eq!(
@@ -574,4 +791,26 @@ mod tests {
             })
         );
     }
+
+    #[slipway_test]
+    fn test_deserialize_callout_permission() {
+        assert_eq!(
+            serde_json::from_str::<Permission>(r#"{"permission":"callouts"}"#).unwrap(),
+            Permission::Callouts(CalloutPermission {
+                handle: None,
+                component: None,
+            })
+        );
+
+        assert_eq!(
+            serde_json::from_str::<Permission>(
+                r#"{"permission":"callouts", "handle": "increment", "component": "slipwayhq.increment.0.0.1"}"#
+            )
+            .unwrap(),
+            Permission::Callouts(CalloutPermission {
+                handle: Some(ComponentHandle::from_str("increment").unwrap()),
+                component: Some(SlipwayReference::from_str("slipwayhq.increment.0.0.1").unwrap()),
+            })
+        );
+    }
 }
@@ -0,0 +1,172 @@
+use normalize_path::NormalizePath;
+
+use super::{
+    CalloutPermission, LocalComponentPermission, PathPermission, Permission,
+    RegistryComponentPermission, StringPermission, UrlPermission,
+};
+
+impl Permission {
+    /// Returns true if granting this permission also grants everything `other` allows,
+    /// in other words if this permission is at least as broad as `other`.
+    ///
+    /// This is conservative: if it cannot be determined that this permission covers
+    /// `other`, for example because they are different kinds of permission, it returns false.
+    pub fn covers(&self, other: &Permission) -> bool {
+        match (self, other) {
+            (Permission::All, _) => true,
+            (Permission::Http(a), Permission::Http(b)) => a.covers(b),
+            (Permission::Files(a), Permission::Files(b)) => a.covers(b),
+            (Permission::Fonts(a), Permission::Fonts(b)) => a.covers(b),
+            (Permission::Env(a), Permission::Env(b)) => a.covers(b),
+            (Permission::RegistryComponents(a), Permission::RegistryComponents(b)) => a.covers(b),
+            (Permission::HttpComponents(a), Permission::HttpComponents(b)) => a.covers(b),
+            (Permission::GitComponents(a), Permission::GitComponents(b)) => a.covers(b),
+            (Permission::LocalComponents(a), Permission::LocalComponents(b)) => a.covers(b),
+            (Permission::Callouts(a), Permission::Callouts(b)) => a.covers(b),
+            _ => false,
+        }
+    }
+}
+
+impl StringPermission {
+    fn covers(&self, other: &StringPermission) -> bool {
+        match (self, other) {
+            (StringPermission::Any {}, _) => true,
+            (_, StringPermission::Exact { exact }) => self.matches(exact),
+            (StringPermission::Prefix { prefix }, StringPermission::Prefix { prefix: other }) => {
+                other.starts_with(prefix)
+            }
+            (StringPermission::Suffix { suffix }, StringPermission::Suffix { suffix: other }) => {
+                other.ends_with(suffix)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl UrlPermission {
+    fn covers(&self, other: &UrlPermission) -> bool {
+        match (self, other) {
+            (UrlPermission::Any {}, _) => true,
+            (_, UrlPermission::Exact { exact }) => self.matches(exact),
+            (UrlPermission::Prefix { prefix }, UrlPermission::Prefix { prefix: other }) => {
+                other.as_str().starts_with(prefix.as_str())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl PathPermission {
+    fn covers(&self, other: &PathPermission) -> bool {
+        match (self, other) {
+            (PathPermission::Any {}, _) => true,
+            (_, PathPermission::Exact { exact }) => self.matches(exact),
+            (PathPermission::Within { within }, PathPermission::Within { within: other }) => {
+                other.normalize().starts_with(within.normalize())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl RegistryComponentPermission {
+    fn covers(&self, other: &RegistryComponentPermission) -> bool {
+        field_covers(&self.publisher, &other.publisher)
+            && field_covers(&self.name, &other.name)
+            && field_covers(&self.version, &other.version)
+    }
+}
+
+impl CalloutPermission {
+    fn covers(&self, other: &CalloutPermission) -> bool {
+        field_covers(&self.handle, &other.handle) && field_covers(&self.component, &other.component)
+    }
+}
+
+/// An unset field matches anything, so covers any value.
+fn field_covers<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+    match (a, b) {
+        (None, _) => true,
+        (Some(a), Some(b)) => a == b,
+        (Some(_), None) => false,
+    }
+}
+
+impl LocalComponentPermission {
+    fn covers(&self, other: &LocalComponentPermission) -> bool {
+        match (self, other) {
+            (LocalComponentPermission::Any {}, _) => true,
+            (
+                LocalComponentPermission::Exact { exact },
+                LocalComponentPermission::Exact { exact: other },
+            ) => exact == other,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test;
+    use url::Url;
+
+    use super::*;
+
+    fn http_prefix(prefix: &str) -> Permission {
+        Permission::Http(UrlPermission::Prefix {
+            prefix: Url::parse(prefix).unwrap(),
+        })
+    }
+
+    fn http_exact(exact: &str) -> Permission {
+        Permission::Http(UrlPermission::Exact {
+            exact: Url::parse(exact).unwrap(),
+        })
+    }
+
+    #[slipway_test]
+    fn it_should_cover_narrower_permissions_of_the_same_kind() {
+        assert!(Permission::All.covers(&http_exact("https://example.com/a")));
+        assert!(
+            Permission::Http(UrlPermission::Any {}).covers(&http_prefix("https://example.com/"))
+        );
+        assert!(http_prefix("https://example.com/").covers(&http_prefix("https://example.com/a/")));
+        assert!(http_prefix("https://example.com/").covers(&http_exact("https://example.com/a")));
+        assert!(
+            Permission::Env(StringPermission::Prefix {
+                prefix: "SLIPWAY_".to_string()
+            })
+            .covers(&Permission::Env(StringPermission::Exact {
+                exact: "SLIPWAY_TOKEN".to_string()
+            }))
+        );
+    }
+
+    #[slipway_test]
+    fn it_should_not_cover_broader_or_different_permissions() {
+        assert!(!http_exact("https://example.com/a").covers(&http_prefix("https://example.com/")));
+        assert!(
+            !http_prefix("https://example.com/a/").covers(&http_prefix("https://example.com/"))
+        );
+        assert!(!http_exact("https://example.com/a").covers(&Permission::All));
+        assert!(
+            !Permission::Http(UrlPermission::Any {})
+                .covers(&Permission::Env(StringPermission::Any {}))
+        );
+        assert!(
+            !Permission::RegistryComponents(RegistryComponentPermission {
+                publisher: Some("slipwayhq".to_string()),
+                name: None,
+                version: None,
+            })
+            .covers(&Permission::RegistryComponents(
+                RegistryComponentPermission {
+                    publisher: None,
+                    name: Some("render".to_string()),
+                    version: None,
+                }
+            ))
+        );
+    }
+}
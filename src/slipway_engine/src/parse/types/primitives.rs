@@ -18,6 +18,15 @@ crate::utils::create_validated_string_struct!(
     MAXIMUM_COMPONENT_HANDLE_LENGTH
 );
 
+// Rig output names are used as file names when saving outputs, so they follow
+// the same rules as component handles.
+crate::utils::create_validated_string_struct!(
+    pub RigOutputName,
+    Some(r"^\w+$"),
+    Some(1),
+    MAXIMUM_COMPONENT_HANDLE_LENGTH
+);
+
 #[cfg(test)]
 mod tests {
 
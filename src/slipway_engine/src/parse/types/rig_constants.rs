@@ -0,0 +1,82 @@
+use serde_json::{Map, Value};
+
+use super::Rig;
+
+impl Rig {
+    /// Overrides a rig constant, where the key is a `.` separated path into the constants.
+    ///
+    /// For example setting `location.city` will set the `city` property of the
+    /// `location` object, which can then be referenced as `$.constants.location.city`.
+    /// Any missing objects along the path are created, and any non-object values
+    /// along the path are replaced.
+    pub fn set_constant(&mut self, key: &str, value: Value) {
+        let mut current = self
+            .constants
+            .get_or_insert_with(|| Value::Object(Map::new()));
+
+        for segment in key.split('.') {
+            if !current.is_object() {
+                *current = Value::Object(Map::new());
+            }
+
+            current = current
+                .as_object_mut()
+                .expect("value should be an object")
+                .entry(segment)
+                .or_insert(Value::Null);
+        }
+
+        *current = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test;
+    use serde_json::json;
+
+    use crate::Rigging;
+
+    use super::*;
+
+    fn rig_with_constants(constants: Option<Value>) -> Rig {
+        Rig {
+            description: None,
+            constants,
+            rigging: Rigging {
+                components: Default::default(),
+            },
+            context: None,
+            outputs: None,
+            templates: None,
+        }
+    }
+
+    #[slipway_test]
+    fn it_should_set_constant_when_rig_has_no_constants() {
+        let mut rig = rig_with_constants(None);
+
+        rig.set_constant("city", json!("London"));
+
+        assert_eq!(rig.constants, Some(json!({ "city": "London" })));
+    }
+
+    #[slipway_test]
+    fn it_should_override_nested_constants() {
+        let mut rig = rig_with_constants(Some(json!({
+            "location": { "city": "London", "country": "UK" },
+            "units": "metric"
+        })));
+
+        rig.set_constant("location.city", json!("Leeds"));
+        rig.set_constant("units.temperature", json!("celsius"));
+
+        assert_eq!(
+            rig.constants,
+            Some(json!({
+                "location": { "city": "Leeds", "country": "UK" },
+                "units": { "temperature": "celsius" }
+            }))
+        );
+    }
+}
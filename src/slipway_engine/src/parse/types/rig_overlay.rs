@@ -0,0 +1,112 @@
+use serde_json::Value;
+
+use crate::{errors::RigError, parse::parse_rig_json};
+
+use super::Rig;
+
+impl Rig {
+    /// Deep merges the overlay into the rig, so that nearly identical rigs can share
+    /// a base rig and only specify their differences, such as component inputs or
+    /// constants which vary between environments.
+    ///
+    /// Objects are merged recursively, a `null` value removes the property, and any
+    /// other value, including arrays, replaces the existing value.
+    pub fn apply_overlay(&mut self, overlay: Value) -> Result<(), RigError> {
+        let mut merged =
+            serde_json::to_value(&*self).map_err(|error| RigError::RigParseFailed { error })?;
+
+        merge_overlay(&mut merged, overlay);
+
+        *self = parse_rig_json(merged)?;
+
+        Ok(())
+    }
+}
+
+fn merge_overlay(base: &mut Value, overlay: Value) {
+    let Value::Object(overlay) = overlay else {
+        *base = overlay;
+        return;
+    };
+
+    if !base.is_object() {
+        *base = Value::Object(Default::default());
+    }
+
+    let base = base.as_object_mut().expect("value should be an object");
+    for (key, value) in overlay {
+        if value.is_null() {
+            base.remove(&key);
+        } else {
+            merge_overlay(base.entry(key).or_insert(Value::Null), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use common_macros::slipway_test;
+    use serde_json::json;
+
+    use crate::{ComponentHandle, parse_rig};
+
+    use super::*;
+
+    #[slipway_test]
+    fn it_should_merge_overlay_into_rig() {
+        let mut rig = parse_rig(
+            &json!({
+                "constants": { "location": { "city": "London", "country": "UK" }, "debug": true },
+                "rigging": {
+                    "weather": {
+                        "component": "test.weather.1.0.0",
+                        "input": { "units": "metric", "days": [1, 2, 3] }
+                    },
+                    "render": {
+                        "component": "test.render.1.0.0",
+                        "input": { "data": "$$.weather" }
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        rig.apply_overlay(json!({
+            "constants": { "location": { "city": "Leeds" }, "debug": null },
+            "rigging": {
+                "weather": { "input": { "days": [1] } }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            rig.constants,
+            Some(json!({ "location": { "city": "Leeds", "country": "UK" } }))
+        );
+
+        let weather = serde_json::to_value(
+            &rig.rigging.components[&ComponentHandle::from_str("weather").unwrap()],
+        )
+        .unwrap();
+
+        assert_eq!(
+            weather,
+            json!({
+                "component": "test.weather.1.0.0",
+                "input": { "units": "metric", "days": [1] }
+            })
+        );
+    }
+
+    #[slipway_test]
+    fn it_should_fail_if_overlay_produces_invalid_rig() {
+        let mut rig = parse_rig(r#"{ "rigging": {} }"#).unwrap();
+
+        let result = rig.apply_overlay(json!({ "rigging": { "a": { "component": 5 } } }));
+
+        assert!(matches!(result, Err(RigError::RigParseFailed { .. })));
+    }
+}
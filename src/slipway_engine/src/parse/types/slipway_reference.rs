@@ -9,7 +9,11 @@ use regex::Regex;
 use semver::Version;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::sync::LazyLock;
-use std::{fmt::Display, path::PathBuf, str::FromStr};
+use std::{
+    fmt::Display,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
 use url::Url;
 
 use super::{REGISTRY_PUBLISHER_SEPARATOR, VERSION_SEPARATOR};
@@ -21,6 +25,8 @@ static PASSTHROUGH_STRING: &str = "passthrough";
 
 static SINK_STRING: &str = "sink";
 
+static GIT_PREFIX: &str = "git+";
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum SlipwayReference {
     // publisher.name.version
@@ -40,6 +46,12 @@ pub enum SlipwayReference {
     Http {
         url: Url,
     },
+
+    // git+https://url#rev
+    Git {
+        url: Url,
+        rev: String,
+    },
     Special(SpecialComponentReference),
 }
 
@@ -49,6 +61,52 @@ pub enum SpecialComponentReference {
     Sink,
 }
 
+impl SlipwayReference {
+    /// Returns a stable string form of the reference, where references which differ
+    /// only in how they are written, such as `file:./a/../b.tar` and `file:b.tar`,
+    /// have the same canonical form.
+    ///
+    /// Local paths are normalized lexically, without accessing the file system,
+    /// and fragments are removed from HTTP URLs.
+    pub fn canonical(&self) -> String {
+        match self {
+            SlipwayReference::Local { path } => SlipwayReference::Local {
+                path: normalize_path(path),
+            }
+            .to_string(),
+            SlipwayReference::Http { url } => {
+                let mut url = url.clone();
+                url.set_fragment(None);
+                url.to_string()
+            }
+            SlipwayReference::Registry { .. }
+            | SlipwayReference::Git { .. }
+            | SlipwayReference::Special(_) => self.to_string(),
+        }
+    }
+}
+
+/// Removes `.` components and resolves `..` components where possible.
+/// Unlike `NormalizePath`, leading `..` components of relative paths are preserved.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                // The parent of the root is the root.
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => normalized.push(component),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
 impl FromStr for SlipwayReference {
     type Err = RigError;
 
@@ -63,6 +121,10 @@ impl FromStr for SlipwayReference {
             return Ok(SlipwayReference::Special(SpecialComponentReference::Sink));
         }
 
+        if let Some(git_url) = s.strip_prefix(GIT_PREFIX) {
+            return parse_git_reference(git_url);
+        }
+
         if let Some(caps) = REGISTRY_REGEX.captures(s) {
             let version = parse_component_version(&caps["version"])?;
 
@@ -92,6 +154,50 @@ impl FromStr for SlipwayReference {
     }
 }
 
+fn parse_git_reference(s: &str) -> Result<SlipwayReference, RigError> {
+    let invalid = |message: String| RigError::InvalidSlipwayPrimitive {
+        primitive_type: stringify!(SlipwayReference).to_string(),
+        message,
+    };
+
+    let mut url = Url::parse(s)
+        .map_err(|e| invalid(format!("Failed to parse git url \"{}\". Error: {}", s, e)))?;
+
+    let rev = match url.fragment() {
+        Some(rev) if !rev.is_empty() => rev.to_string(),
+        _ => {
+            return Err(invalid(format!(
+                "git reference '{}' must specify a revision, for example: {}{}#v1.0.0",
+                s, GIT_PREFIX, s
+            )));
+        }
+    };
+
+    if !is_valid_git_revision(&rev) {
+        return Err(invalid(format!(
+            "git revision '{}' is invalid. Revisions must not start with '-' and may only contain letters, digits, '.', '_', '/' and '-'.",
+            rev
+        )));
+    }
+
+    url.set_fragment(None);
+
+    match url.scheme() {
+        "https" | "http" | "ssh" | "file" => Ok(SlipwayReference::Git { url, rev }),
+        scheme => Err(invalid(format!("Unsupported git URL scheme: {}", scheme))),
+    }
+}
+
+/// Returns true if the git revision is safe to pass to the `git` executable,
+/// so that a revision such as `--upload-pack=<command>` can't be parsed as an option.
+pub(crate) fn is_valid_git_revision(rev: &str) -> bool {
+    !rev.is_empty()
+        && !rev.starts_with('-')
+        && rev
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '/' | '-'))
+}
+
 impl Display for SlipwayReference {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -112,6 +218,9 @@ impl Display for SlipwayReference {
                 }
             }
             SlipwayReference::Http { url } => f.write_fmt(format_args!("{}", url)),
+            SlipwayReference::Git { url, rev } => {
+                f.write_fmt(format_args!("{}{}#{}", GIT_PREFIX, url, rev))
+            }
             SlipwayReference::Special(inner) => f.write_fmt(format_args!("{}", inner)),
         }
     }
@@ -340,4 +449,136 @@ mod tests {
             assert_eq!(url, Url::parse(url_str).unwrap());
         }
     }
+
+    mod canonical_tests {
+        use super::*;
+
+        fn canonical(s: &str) -> String {
+            SlipwayReference::from_str(s).unwrap().canonical()
+        }
+
+        #[test]
+        fn it_should_canonicalize_registry() {
+            assert_eq!(
+                canonical("test_publisher.test_name.1.2.3"),
+                "test_publisher.test_name.1.2.3"
+            );
+        }
+
+        #[test]
+        fn it_should_canonicalize_special() {
+            assert_eq!(canonical("passthrough"), "passthrough");
+            assert_eq!(canonical("sink"), "sink");
+        }
+
+        #[test]
+        fn it_should_canonicalize_relative_local_files() {
+            assert_eq!(canonical("file:./a/../b/c.tar"), "file:b/c.tar");
+            assert_eq!(canonical("file:b/./c.tar"), "file:b/c.tar");
+            assert_eq!(canonical("file:../a/../../c.tar"), "file:../../c.tar");
+        }
+
+        #[test]
+        fn it_should_canonicalize_absolute_local_files() {
+            assert_eq!(
+                canonical("file:///usr/local/../rigging.json"),
+                "file:///usr/rigging.json"
+            );
+            assert_eq!(
+                SlipwayReference::Local {
+                    path: PathBuf::from("/../rigging.json")
+                }
+                .canonical(),
+                "file:///rigging.json"
+            );
+        }
+
+        #[test]
+        fn it_should_canonicalize_urls() {
+            assert_eq!(
+                canonical("HTTPS://Example.COM:443/a/./b/../c.tar#fragment"),
+                "https://example.com/a/c.tar"
+            );
+            assert_eq!(
+                canonical("https://example.com/c.tar?v=1"),
+                "https://example.com/c.tar?v=1"
+            );
+        }
+
+        #[test]
+        fn it_should_canonicalize_git() {
+            assert_eq!(
+                canonical("git+HTTPS://GitHub.com/slipwayhq/slipway_example.git#v1.0.0"),
+                "git+https://github.com/slipwayhq/slipway_example.git#v1.0.0"
+            );
+        }
+    }
+
+    mod git_tests {
+        use super::*;
+
+        #[test]
+        fn it_should_serialize_and_deserialize_git() {
+            let s = r"git+https://github.com/slipwayhq/slipway_example.git#v1.0.0";
+            let json = quote(s);
+
+            let reference: SlipwayReference = serde_json::from_str(&json).unwrap();
+
+            let json_out = serde_json::to_string(&reference).unwrap();
+            assert_eq!(json, json_out);
+        }
+
+        #[test]
+        fn it_should_parse_git_from_string() {
+            let s = r"git+https://github.com/slipwayhq/slipway_example.git#main";
+
+            let reference = SlipwayReference::from_str(s).unwrap();
+
+            let SlipwayReference::Git { url, rev } = reference else {
+                panic!("Unexpected reference: {reference}");
+            };
+
+            assert_eq!(
+                url,
+                Url::parse("https://github.com/slipwayhq/slipway_example.git").unwrap()
+            );
+            assert_eq!(rev, "main");
+        }
+
+        #[test]
+        fn it_should_fail_to_parse_git_with_invalid_rev() {
+            for rev in [
+                "--upload-pack=touch%20pwned",
+                "-v",
+                "main;rm",
+                "v1.0.0%20x",
+                "a:b",
+            ] {
+                let s = format!("git+https://github.com/slipwayhq/slipway_example.git#{rev}");
+
+                assert!(
+                    SlipwayReference::from_str(&s).is_err(),
+                    "expected \"{rev}\" to be rejected"
+                );
+            }
+        }
+
+        #[test]
+        fn it_should_fail_to_parse_git_without_rev() {
+            let s = r"git+https://github.com/slipwayhq/slipway_example.git";
+
+            let reference_result = SlipwayReference::from_str(s);
+
+            assert!(reference_result.is_err());
+        }
+
+        #[test]
+        fn it_should_fail_to_parse_git_with_unsupported_scheme() {
+            let s = r"git+ftp://example.com/slipway_example.git#main";
+
+            let reference_result = SlipwayReference::from_str(s);
+
+            assert!(reference_result.is_err());
+        }
+    }
 }
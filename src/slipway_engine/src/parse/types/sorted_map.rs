@@ -0,0 +1,67 @@
+//! Serializers which write `HashMap` fields in key order, so that serializing
+//! the same rig or component always produces the same output.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Serialize, Serializer};
+
+pub(super) fn serialize<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+pub(super) fn serialize_option<K, V, S>(
+    map: &Option<HashMap<K, V>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    map.as_ref()
+        .map(|map| map.iter().collect::<BTreeMap<_, _>>())
+        .serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test;
+    use serde_json::json;
+
+    use crate::parse_rig;
+
+    #[slipway_test]
+    fn it_should_serialize_components_and_callouts_in_handle_order() {
+        let callouts = json!({
+            "z": { "component": "test.z.1.0.0" },
+            "m": { "component": "test.m.1.0.0" },
+            "a": { "component": "test.a.1.0.0" }
+        });
+
+        let rigging = (0..20)
+            .rev()
+            .map(|i| {
+                (
+                    format!("c{i:02}"),
+                    json!({ "component": "test.c.1.0.0", "callouts": callouts }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        let rig_json = json!({ "rigging": rigging }).to_string();
+
+        let first = serde_json::to_string(&parse_rig(&rig_json).unwrap()).unwrap();
+        let second = serde_json::to_string(&parse_rig(&rig_json).unwrap()).unwrap();
+
+        assert_eq!(first, second);
+        assert!(first.find("\"c00\"").unwrap() < first.find("\"c01\"").unwrap());
+        assert!(first.find("\"c18\"").unwrap() < first.find("\"c19\"").unwrap());
+        assert!(first.find("\"a\"").unwrap() < first.find("\"m\"").unwrap());
+        assert!(first.find("\"m\"").unwrap() < first.find("\"z\"").unwrap());
+    }
+}
@@ -25,7 +25,9 @@ impl ComponentRunner for SpecialComponentRunner {
         let reference = context.component_reference;
 
         let SlipwayReference::Special(inner) = reference else {
-            return Ok(TryRunComponentResult::CannotRun);
+            return Ok(TryRunComponentResult::CannotRun {
+                reason: Some("Not a special component.".to_string()),
+            });
         };
 
         match inner {
@@ -42,6 +44,8 @@ impl ComponentRunner for SpecialComponentRunner {
                             prepare_component_duration: Duration::ZERO,
                             call_duration,
                             process_output_duration: Duration::ZERO,
+                            nested: None,
+                            memoized_from: None,
                         },
                     },
                 })
@@ -54,6 +58,8 @@ impl ComponentRunner for SpecialComponentRunner {
                         prepare_component_duration: Duration::ZERO,
                         call_duration: Duration::ZERO,
                         process_output_duration: Duration::ZERO,
+                        nested: None,
+                        memoized_from: None,
                     },
                 },
             }),
@@ -40,6 +40,8 @@ impl Rig {
             context: Some(DefaultRigContext {
                 device: Some(json!({"test_device_context": "test_device_context_value"})),
             }),
+            outputs: None,
+            templates: None,
         }
     }
 }
@@ -63,6 +65,9 @@ impl ComponentRigging {
             deny: None,
             permissions_chain: None,
             callouts: None,
+            runner: None,
+            memoize: None,
+            skip: None,
         }
     }
 
@@ -78,6 +83,9 @@ impl ComponentRigging {
             deny: Some(permissions.deny.clone()),
             permissions_chain: None,
             callouts: None,
+            runner: None,
+            memoize: None,
+            skip: None,
         }
     }
 
@@ -105,6 +113,9 @@ impl ComponentRigging {
                 .into_iter()
                 .collect(),
             ),
+            runner: None,
+            memoize: None,
+            skip: None,
         }
     }
 
@@ -133,6 +144,9 @@ impl ComponentRigging {
                 .into_iter()
                 .collect(),
             ),
+            runner: None,
+            memoize: None,
+            skip: None,
         }
     }
 }
@@ -157,6 +171,13 @@ impl<TSchema> Component<TSchema> {
                 output,
                 constants: None,
                 rigging: None,
+                fragment_inputs: None,
+                fragment_input: None,
+                fragment_output: None,
+                permissions: None,
+                batchable: None,
+                output_content_type: None,
+                fonts: None,
                 callouts: None,
             },
             _ => unimplemented!("Only registry references are currently supported in this method"),
@@ -259,7 +280,7 @@ impl MockComponentsLoader {
 
 struct NoComponentFiles {}
 
-fn no_component_files() -> Arc<ComponentFiles> {
+pub(crate) fn no_component_files() -> Arc<ComponentFiles> {
     Arc::new(ComponentFiles::new(Box::new(NoComponentFiles {})))
 }
 
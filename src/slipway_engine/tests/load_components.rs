@@ -105,6 +105,9 @@ fn create_rig(component_reference: SlipwayReference) -> (Rig, ComponentHandle) {
                 deny: None,
                 permissions_chain: None,
                 callouts: None,
+                runner: None,
+                memoize: None,
+                skip: None,
             },
         )]
         .into_iter()
@@ -2,10 +2,10 @@ use std::{str::FromStr, sync::Arc, time::Instant};
 
 use async_trait::async_trait;
 use slipway_engine::{
-    BasicComponentCache, Component, ComponentExecutionContext, ComponentHandle, ComponentRigging,
-    ComponentRunner, MultiComponentCache, Rig, RigSession, Rigging, RunComponentError,
+    Component, ComponentExecutionContext, ComponentHandle, ComponentRigging, ComponentRunner,
+    MultiComponentCache, NestedRunMetadata, Rig, RigSession, Rigging, RunComponentError,
     RunComponentResult, RunMetadata, Schema, SlipwayReference, SpecialComponentReference,
-    TryRunComponentResult, prime_special_component,
+    TryRunComponentResult, primed_special_components_cache,
 };
 use slipway_host::run::{run_rig, tracing_event_handler};
 use tracing::Instrument;
@@ -27,10 +27,12 @@ impl ComponentRunner for FragmentComponentRunner {
         input: &serde_json::Value,
         context: &'call ComponentExecutionContext<'call, '_, '_>,
     ) -> Result<TryRunComponentResult, RunComponentError> {
-        let component_definition = &context.component_definition;
+        let component_definition = context.component_definition();
 
         let Some(rigging) = component_definition.rigging.as_ref() else {
-            return Ok(TryRunComponentResult::CannotRun);
+            return Ok(TryRunComponentResult::CannotRun {
+                reason: Some("The component definition has no rigging.".to_string()),
+            });
         };
 
         let run_result =
@@ -49,75 +51,108 @@ async fn run_component_fragment(
     execution_context: &ComponentExecutionContext<'_, '_, '_>,
 ) -> Result<RunComponentResult, RunComponentError> {
     let prepare_input_start = Instant::now();
-    let input_component_handle = ComponentHandle::from_str(INPUT_COMPONENT_HANDLE)
-        .expect("Default input component handle should be valid.");
+    let input_component_handle = match component_definition.fragment_input.as_ref() {
+        Some(handle) => handle.clone(),
+        None => ComponentHandle::from_str(INPUT_COMPONENT_HANDLE)
+            .expect("Default input component handle should be valid."),
+    };
 
-    let output_component_handle = ComponentHandle::from_str(OUTPUT_COMPONENT_HANDLE)
-        .expect("Default output component handle should be valid.");
+    let output_component_handle = match component_definition.fragment_output.as_ref() {
+        Some(handle) => handle.clone(),
+        None => ComponentHandle::from_str(OUTPUT_COMPONENT_HANDLE)
+            .expect("Default output component handle should be valid."),
+    };
 
     if rigging.components.contains_key(&input_component_handle) {
         return Err(RunComponentError::Other(format!(
             "Fragment should not contain a component with the handle \"{}\".",
-            INPUT_COMPONENT_HANDLE
+            input_component_handle
         )));
     }
 
     if !rigging.components.contains_key(&output_component_handle) {
         return Err(RunComponentError::Other(format!(
             "Fragment must contain a component with the handle \"{}\".",
-            OUTPUT_COMPONENT_HANDLE
+            output_component_handle
         )));
     }
 
     let mut rigging_with_input = rigging.clone();
 
-    rigging_with_input.components.insert(
-        input_component_handle,
-        ComponentRigging {
-            component: SlipwayReference::Special(SpecialComponentReference::Passthrough),
-            input: Some(input.clone()),
-            allow: None,
-            deny: None,
-            permissions_chain: None,
-            callouts: None,
-        },
-    );
+    rigging_with_input
+        .components
+        .insert(input_component_handle, passthrough_rigging(input.clone()));
+
+    if let Some(fragment_inputs) = component_definition.fragment_inputs.as_ref() {
+        let Some(input_object) = input.as_object() else {
+            return Err(RunComponentError::Other(
+                "Fragment with named inputs must be given an input object.".to_string(),
+            ));
+        };
+
+        for fragment_input_handle in fragment_inputs.iter() {
+            if rigging_with_input
+                .components
+                .contains_key(fragment_input_handle)
+            {
+                return Err(RunComponentError::Other(format!(
+                    "Fragment should not contain a component with the input handle \"{}\".",
+                    fragment_input_handle
+                )));
+            }
+
+            let fragment_input = input_object
+                .get(&fragment_input_handle.0)
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+
+            rigging_with_input.components.insert(
+                fragment_input_handle.clone(),
+                passthrough_rigging(fragment_input),
+            );
+        }
+    }
 
     let rig = Rig {
         description: None,
         constants: component_definition.constants.clone(),
         rigging: rigging_with_input,
         context: None,
+        outputs: None,
+        templates: None,
     };
 
     let prepare_input_duration = prepare_input_start.elapsed();
     let prepare_component_start = Instant::now();
 
-    let component_runners = execution_context.component_runners;
-    let call_chain = Arc::clone(&execution_context.call_chain);
+    let component_runners = execution_context.component_runners();
+    let call_chain = Arc::clone(execution_context.call_chain());
 
-    let original_component_cache = execution_context.component_cache;
-    let new_component_cache = get_component_cache_with_pass_component().await;
+    let original_component_cache = execution_context.component_cache();
+    let special_components_cache = primed_special_components_cache().await;
     let component_cache =
-        MultiComponentCache::new(vec![original_component_cache, &new_component_cache]);
+        MultiComponentCache::new(vec![original_component_cache, special_components_cache]);
 
     let rig_session = RigSession::new_with_options(
         rig,
         &component_cache,
-        execution_context.rig_session_options.clone(),
+        execution_context.rig_session_options().clone(),
     );
 
     let prepare_component_duration = prepare_component_start.elapsed();
     let call_start = Instant::now();
 
-    let run_result = run_rig::<std::io::Error>(
-        &rig_session,
-        &mut tracing_event_handler(),
-        component_runners,
-        call_chain,
-    )
-    .await
-    .map_err(|e| RunComponentError::RunCallFailed { source: e.into() })?;
+    // The fragment's own permit is released while its nested rig runs, as the
+    // nested components acquire their own permits from the same limit.
+    let run_result = execution_context
+        .release_concurrency_permit_while(run_rig::<std::io::Error>(
+            &rig_session,
+            &mut tracing_event_handler(),
+            component_runners,
+            call_chain,
+        ))
+        .await
+        .map_err(|e| RunComponentError::RunCallFailed { source: e.into() })?;
 
     let call_duration = call_start.elapsed();
     let process_output_start = Instant::now();
@@ -132,12 +167,27 @@ async fn run_component_fragment(
     let Some(output) = output_state.as_ref() else {
         return Err(RunComponentError::Other(format!(
             "Component with handle \"{}\" did not have any output set after fragment execution.",
-            INPUT_COMPONENT_HANDLE
+            output_component_handle
         )));
     };
 
     let output = output.value.clone();
 
+    let mut nested = run_result
+        .component_states
+        .iter()
+        .filter_map(|(&handle, state)| {
+            state
+                .execution_output
+                .as_ref()
+                .map(|output| NestedRunMetadata {
+                    handle: handle.clone(),
+                    metadata: output.run_metadata.clone(),
+                })
+        })
+        .collect::<Vec<_>>();
+    nested.sort_by(|a, b| a.handle.cmp(&b.handle));
+
     let process_output_duration = process_output_start.elapsed();
 
     let result = RunComponentResult {
@@ -147,21 +197,24 @@ async fn run_component_fragment(
             prepare_component_duration,
             call_duration,
             process_output_duration,
+            nested: Some(nested),
+            memoized_from: None,
         },
     };
 
     Ok(result)
 }
 
-async fn get_component_cache_with_pass_component() -> BasicComponentCache {
-    let pass_reference = SpecialComponentReference::Passthrough;
-    let pass_component = prime_special_component(&pass_reference).await;
-    BasicComponentCache::for_primed(
-        vec![(
-            SlipwayReference::Special(pass_reference.clone()),
-            pass_component,
-        )]
-        .into_iter()
-        .collect(),
-    )
+fn passthrough_rigging(input: serde_json::Value) -> ComponentRigging {
+    ComponentRigging {
+        component: SlipwayReference::Special(SpecialComponentReference::Passthrough),
+        input: Some(input),
+        allow: None,
+        deny: None,
+        permissions_chain: None,
+        callouts: None,
+        runner: None,
+        memoize: None,
+        skip: None,
+    }
 }
@@ -17,7 +17,7 @@ pub fn decode_bin(
     execution_context: &ComponentExecutionContext,
     text: String,
 ) -> Result<Vec<u8>, ComponentError> {
-    decode_bin_inner(Arc::clone(&execution_context.call_chain), text)
+    decode_bin_inner(Arc::clone(execution_context.call_chain()), text)
 }
 
 fn decode_bin_inner(
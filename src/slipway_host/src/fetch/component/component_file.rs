@@ -2,7 +2,7 @@ use std::borrow::Cow;
 
 use slipway_engine::{ComponentExecutionContext, ComponentHandle};
 
-use super::{BinResponse, RequestError};
+use super::{BinResponse, CacheStatus, RequestError};
 
 pub(super) async fn get_component_file_bin(
     execution_context: &ComponentExecutionContext<'_, '_, '_>,
@@ -10,7 +10,7 @@ pub(super) async fn get_component_file_bin(
     path: &str,
 ) -> Result<BinResponse, RequestError> {
     let handle_trail = || -> String {
-        execution_context.call_chain.component_handle_trail_for(
+        execution_context.call_chain().component_handle_trail_for(
             handle
                 .as_ref()
                 .unwrap_or_else(|| execution_context.component_handle()),
@@ -18,10 +18,10 @@ pub(super) async fn get_component_file_bin(
     };
 
     let component_reference = match handle.as_ref() {
-        None => execution_context.component_reference,
+        None => execution_context.component_reference(),
         Some(handle) => {
             let component_callout = execution_context
-                .callout_context
+                .callout_context()
                 .get_component_callout_for_handle(handle)
                 .map_err(|e| {
                     RequestError::for_error(
@@ -36,7 +36,7 @@ pub(super) async fn get_component_file_bin(
         }
     };
 
-    let component = execution_context.component_cache.get(component_reference);
+    let component = execution_context.component_cache().get(component_reference);
 
     let path = sanitize_slashes(path);
 
@@ -55,6 +55,7 @@ pub(super) async fn get_component_file_bin(
         status_code: 200,
         headers: vec![],
         body: bin.to_vec(),
+        cache_status: CacheStatus::Uncached,
     })
 }
 
@@ -7,7 +7,7 @@ use std::str::FromStr;
 use slipway_engine::{ComponentExecutionContext, ComponentHandle};
 use url::Url;
 
-use super::{BinResponse, RequestError, RequestOptions};
+use super::{BinResponse, CacheStatus, RequestError, RequestOptions};
 
 pub(super) async fn fetch_component_data(
     execution_context: &ComponentExecutionContext<'_, '_, '_>,
@@ -22,7 +22,7 @@ pub(super) async fn fetch_component_data(
                     format!(
                         "Failed to parse component handle \"{}\" from \"{}\"",
                         handle_str,
-                        execution_context.call_chain.component_handle_trail(),
+                        execution_context.call_chain().component_handle_trail(),
                     ),
                     e,
                 )
@@ -30,11 +30,6 @@ pub(super) async fn fetch_component_data(
         None => Ok(None),
     }?;
 
-    if let Some(handle) = &handle {
-        // None implies we're using the current component, and so we don't need to check permissions.
-        crate::permissions::ensure_can_use_component_handle(handle, execution_context)?;
-    }
-
     let path = url.path();
 
     match path {
@@ -45,8 +40,15 @@ pub(super) async fn fetch_component_data(
                     vec![],
                 ));
             };
+            // Running the callout checks the permissions to use the component.
             run::run_component_from_url(execution_context, handle, url, options).await
         }
-        _ => component_file::get_component_file_bin(execution_context, handle, path).await,
+        _ => {
+            if let Some(handle) = &handle {
+                // None implies we're using the current component, and so we don't need to check permissions.
+                crate::permissions::ensure_can_use_component_handle(handle, execution_context)?;
+            }
+            component_file::get_component_file_bin(execution_context, handle, path).await
+        }
     }
 }
@@ -3,7 +3,7 @@ use url::Url;
 
 use crate::run::run_component_callout;
 
-use super::{BinResponse, RequestError, RequestOptions, apply_json_change};
+use super::{BinResponse, CacheStatus, RequestError, RequestOptions, apply_json_change};
 
 pub(super) async fn run_component_from_url(
     execution_context: &ComponentExecutionContext<'_, '_, '_>,
@@ -22,7 +22,7 @@ pub(super) async fn run_component_from_url(
             RequestError::for_error(
                 format!(
                     "Failed to parse body as JSON for component {}, url: {}",
-                    execution_context.call_chain.component_handle_trail(),
+                    execution_context.call_chain().component_handle_trail(),
                     url,
                 ),
                 e,
@@ -57,7 +57,7 @@ pub(super) async fn run_component_from_url(
             format!(
                 "Failed to serialize output JSON for callout {}, url: {}",
                 execution_context
-                    .call_chain
+                    .call_chain()
                     .component_handle_trail_for(&handle),
                 url,
             ),
@@ -69,5 +69,6 @@ pub(super) async fn run_component_from_url(
         status_code: 200,
         headers: vec![("content-type".to_string(), "application/json".to_string())],
         body: result_bytes,
+        cache_status: CacheStatus::Uncached,
     })
 }
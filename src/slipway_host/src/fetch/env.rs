@@ -3,7 +3,7 @@ use std::env::VarError;
 use slipway_engine::ComponentExecutionContext;
 use url::Url;
 
-use super::{BinResponse, RequestError};
+use super::{BinResponse, CacheStatus, RequestError};
 
 pub(super) fn fetch_env_url(
     execution_context: &ComponentExecutionContext,
@@ -12,7 +12,7 @@ pub(super) fn fetch_env_url(
     let key = url.domain().ok_or_else(|| {
         RequestError::message(format!(
             "No domain (env key) found in url from component \"{}\": {}",
-            execution_context.call_chain.component_handle_trail(),
+            execution_context.call_chain().component_handle_trail(),
             url
         ))
     })?;
@@ -31,17 +31,19 @@ pub(super) fn fetch_env(
             status_code: 200,
             headers: vec![],
             body: value.into_bytes(),
+            cache_status: CacheStatus::Uncached,
         }),
         Err(VarError::NotPresent) => Ok(BinResponse {
             status_code: 404,
             headers: vec![],
             body: vec![],
+            cache_status: CacheStatus::Uncached,
         }),
         Err(e) => Err(RequestError::for_error(
             format!(
                 "Failed to fetch environment variable for key \"{}\" and component \"{}\".",
                 key,
-                execution_context.call_chain.component_handle_trail()
+                execution_context.call_chain().component_handle_trail()
             ),
             e,
         )),
@@ -1,6 +1,6 @@
 use slipway_engine::{ComponentExecutionContext, ProcessedUrl};
 
-use crate::fetch::{BinResponse, RequestError, RequestOptions};
+use crate::fetch::{BinResponse, CacheStatus, RequestError, RequestOptions};
 
 use super::TextResponse;
 
@@ -26,7 +26,7 @@ pub(super) async fn fetch_file(
         }
         slipway_engine::ProcessedUrl::RelativePath(path) => {
             crate::permissions::ensure_can_fetch_file(&path, execution_context)?;
-            let base_path = &execution_context.rig_session_options.base_path;
+            let base_path = &execution_context.rig_session_options().base_path;
             base_path.join(path)
         }
         _ => {
@@ -43,6 +43,7 @@ pub(super) async fn fetch_file(
                     status_code: 404,
                     headers: vec![],
                     body: Default::default(),
+                    cache_status: CacheStatus::Uncached,
                 },
             ));
         }
@@ -58,6 +59,7 @@ pub(super) async fn fetch_file(
         status_code: 200,
         headers: vec![],
         body: body.to_vec(),
+        cache_status: CacheStatus::Uncached,
     };
 
     Ok(bin_response)
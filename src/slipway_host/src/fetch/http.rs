@@ -1,59 +1,182 @@
-use reqwest::{Client, ClientBuilder};
-use slipway_engine::ComponentExecutionContext;
+use reqwest::{
+    Client, ClientBuilder, Method, NoProxy, Proxy, Response, StatusCode, header::LOCATION, redirect,
+};
+use slipway_engine::{
+    ComponentExecutionContext, HttpCassette, HttpCassetteBody, HttpCassetteMode, HttpInteraction,
+    HttpInteractionRequest, HttpInteractionResponse,
+};
 use std::time::Duration;
 use url::Url;
 
-use crate::fetch::{BinResponse, RequestError, RequestOptions};
+use crate::fetch::{BinResponse, CacheStatus, RequestError, RequestOptions};
+
+const DEFAULT_MAX_REDIRECTS: u32 = 5;
 
 pub(super) async fn fetch_http(
     execution_context: &ComponentExecutionContext<'_, '_, '_>,
     url: Url,
     options: Option<RequestOptions>,
 ) -> Result<BinResponse, RequestError> {
-    crate::permissions::ensure_can_fetch_url(&url, execution_context)?;
-
     let opts = options.unwrap_or_default();
+    let method = opts.method.as_deref().unwrap_or("GET").to_string();
 
-    let mut client_builder = ClientBuilder::new();
-    if let Some(ms) = opts.timeout_ms {
-        client_builder = client_builder.timeout(Duration::from_millis(ms as u64));
+    let bin_response = match execution_context
+        .rig_session_options()
+        .http_cassette
+        .as_deref()
+    {
+        Some(cassette) if cassette.mode() == HttpCassetteMode::Replay => {
+            crate::permissions::ensure_can_fetch_url(&method, &url, execution_context)?;
+            replay_http(cassette, &method, &url, opts.body.as_deref())?
+        }
+        cassette => {
+            let request = HttpInteractionRequest {
+                method: method.clone(),
+                url: url.to_string(),
+                headers: opts.headers.clone().unwrap_or_default(),
+                body: opts.body.as_deref().map(HttpCassetteBody::from_bytes),
+            };
+
+            let bin_response = send_http(execution_context, url, opts).await?;
+
+            if let Some(cassette) = cassette {
+                record_http(cassette, request, &bin_response).await?;
+            }
+
+            bin_response
+        }
+    };
+
+    if (200..300).contains(&bin_response.status_code) {
+        Ok(bin_response)
+    } else {
+        Err(RequestError::response(
+            "Response status code did not indicate success.".to_string(),
+            bin_response.into(),
+        ))
     }
-    let client: Client = client_builder
-        .build()
-        .map_err(|e| RequestError::for_error("Failed to build HTTP client.".to_string(), e))?;
+}
 
-    let mut request_builder = client.request(
-        opts.method
+fn replay_http(
+    cassette: &HttpCassette,
+    method: &str,
+    url: &Url,
+    body: Option<&[u8]>,
+) -> Result<BinResponse, RequestError> {
+    let Some(response) = cassette.find_response(method, url.as_str(), body) else {
+        return Err(RequestError::message(format!(
+            "No recorded response in the HTTP cassette for: {method} {url}"
+        )));
+    };
+
+    let body = response.body.to_bytes().map_err(|e| {
+        RequestError::for_error(
+            "Failed to decode recorded response body from the HTTP cassette.".to_string(),
+            e,
+        )
+    })?;
+
+    Ok(BinResponse {
+        status_code: response.status_code,
+        headers: response.headers,
+        body,
+        cache_status: CacheStatus::Replayed,
+    })
+}
+
+async fn record_http(
+    cassette: &HttpCassette,
+    request: HttpInteractionRequest,
+    response: &BinResponse,
+) -> Result<(), RequestError> {
+    cassette
+        .record(HttpInteraction {
+            request,
+            response: HttpInteractionResponse {
+                status_code: response.status_code,
+                headers: response.headers.clone(),
+                body: HttpCassetteBody::from_bytes(&response.body),
+            },
+        })
+        .await
+        .map_err(|e| {
+            RequestError::for_inner(
+                "Failed to record response to the HTTP cassette.".to_string(),
+                vec![format!("{e:#}")],
+            )
+        })
+}
+
+async fn send_http(
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
+    url: Url,
+    opts: RequestOptions,
+) -> Result<BinResponse, RequestError> {
+    let client = create_http_client(
+        opts.timeout_ms,
+        execution_context.rig_session_options().proxy.as_deref(),
+    )?;
+
+    let max_redirects = opts.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS);
+    let log_http = super::http_log::is_enabled(execution_context.rig_session_options().log_http);
+
+    let mut request = HttpRequest {
+        method: opts
+            .method
             .as_deref()
             .unwrap_or("GET")
             .parse()
             .map_err(|e| RequestError::for_error("Invalid HTTP method.".to_string(), e))?,
         url,
-    );
+        headers: opts.headers.unwrap_or_default(),
+        body: opts.body,
+    };
 
-    request_builder = request_builder.header(
-        "User-Agent",
-        format!(
-            "Slipway/{} ({})",
-            env!("CARGO_PKG_VERSION"),
-            env!("CARGO_PKG_REPOSITORY")
-        ),
-    );
+    let mut redirect_count = 0;
+    let response = loop {
+        crate::permissions::ensure_can_fetch_url(
+            request.method.as_str(),
+            &request.url,
+            execution_context,
+        )?;
 
-    if let Some(headers) = &opts.headers {
-        for (name, value) in headers {
-            request_builder = request_builder.header(name, value);
+        if log_http {
+            super::http_log::log_request(
+                request.method.as_str(),
+                &request.url,
+                &request.headers,
+                request.body.as_deref(),
+            );
         }
-    }
 
-    if let Some(body) = &opts.body {
-        request_builder = request_builder.body(body.clone());
-    }
+        let response = send_request(&client, &request).await?;
 
-    let response = request_builder
-        .send()
-        .await
-        .map_err(|e| RequestError::for_error("HTTP request failed.".to_string(), e))?;
+        let Some(location) = get_redirect_location(&response)? else {
+            break response;
+        };
+
+        if log_http {
+            let headers: Vec<(String, String)> = response
+                .headers()
+                .iter()
+                .map(|(key, value)| {
+                    let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                    (key.to_string(), value)
+                })
+                .collect();
+            super::http_log::log_response(&request.url, response.status().as_u16(), &headers, None);
+        }
+
+        if redirect_count >= max_redirects {
+            return Err(RequestError::message(format!(
+                "Exceeded the maximum of {max_redirects} redirects when fetching: {}",
+                request.url
+            )));
+        }
+        redirect_count += 1;
+
+        request.redirect(response.status(), &location)?;
+    };
 
     let status = response.status();
     let mut headers = vec![];
@@ -68,18 +191,272 @@ pub(super) async fn fetch_http(
         RequestError::for_error("Reading HTTP response body failed.".to_string(), e)
     })?;
 
-    let bin_response = BinResponse {
+    if log_http {
+        super::http_log::log_response(&request.url, status.as_u16(), &headers, Some(&body[..]));
+    }
+
+    Ok(BinResponse {
         status_code: status.as_u16(),
         headers,
         body: body.to_vec(),
+        cache_status: if status == StatusCode::NOT_MODIFIED {
+            CacheStatus::NotModified
+        } else {
+            CacheStatus::Uncached
+        },
+    })
+}
+
+fn create_http_client(
+    timeout_ms: Option<u32>,
+    proxy: Option<&str>,
+) -> Result<Client, RequestError> {
+    // Redirects are followed manually so that permissions can be checked for each hop.
+    let mut client_builder = ClientBuilder::new().redirect(redirect::Policy::none());
+    if let Some(ms) = timeout_ms {
+        client_builder = client_builder.timeout(Duration::from_millis(ms as u64));
+    }
+
+    // If no proxy is specified the HTTP_PROXY, HTTPS_PROXY and NO_PROXY
+    // environment variables are used.
+    if let Some(proxy) = proxy {
+        let proxy = Proxy::all(proxy)
+            .map_err(|e| RequestError::for_error(format!("Invalid proxy URL: {proxy}"), e))?;
+        client_builder = client_builder.proxy(proxy.no_proxy(NoProxy::from_env()));
+    }
+
+    client_builder
+        .build()
+        .map_err(|e| RequestError::for_error("Failed to build HTTP client.".to_string(), e))
+}
+
+struct HttpRequest {
+    method: Method,
+    url: Url,
+    headers: Vec<(String, String)>,
+    body: Option<Vec<u8>>,
+}
+
+impl HttpRequest {
+    /// Updates the request to follow a redirect, matching browser behavior for
+    /// method changes and for dropping credentials on cross-origin redirects.
+    fn redirect(&mut self, status: StatusCode, location: &str) -> Result<(), RequestError> {
+        let next_url = self.url.join(location).map_err(|e| {
+            RequestError::for_error(format!("Invalid redirect location: {location}"), e)
+        })?;
+
+        if next_url.origin() != self.url.origin() {
+            self.headers
+                .retain(|(name, _)| !name.eq_ignore_ascii_case("authorization"));
+        }
+
+        let change_to_get = (status == StatusCode::SEE_OTHER && self.method != Method::HEAD)
+            || ((status == StatusCode::MOVED_PERMANENTLY || status == StatusCode::FOUND)
+                && self.method == Method::POST);
+
+        if change_to_get {
+            self.method = Method::GET;
+            self.body = None;
+            self.headers.retain(|(name, _)| {
+                !name.eq_ignore_ascii_case("content-type")
+                    && !name.eq_ignore_ascii_case("content-length")
+            });
+        }
+
+        self.url = next_url;
+        Ok(())
+    }
+}
+
+async fn send_request(client: &Client, request: &HttpRequest) -> Result<Response, RequestError> {
+    let mut request_builder = client.request(request.method.clone(), request.url.clone());
+
+    request_builder = request_builder.header(
+        "User-Agent",
+        format!(
+            "Slipway/{} ({})",
+            env!("CARGO_PKG_VERSION"),
+            env!("CARGO_PKG_REPOSITORY")
+        ),
+    );
+
+    for (name, value) in &request.headers {
+        request_builder = request_builder.header(name, value);
+    }
+
+    if let Some(body) = &request.body {
+        request_builder = request_builder.body(body.clone());
+    }
+
+    request_builder
+        .send()
+        .await
+        .map_err(|e| RequestError::for_error("HTTP request failed.".to_string(), e))
+}
+
+fn get_redirect_location(response: &Response) -> Result<Option<String>, RequestError> {
+    let is_redirect = matches!(
+        response.status(),
+        StatusCode::MOVED_PERMANENTLY
+            | StatusCode::FOUND
+            | StatusCode::SEE_OTHER
+            | StatusCode::TEMPORARY_REDIRECT
+            | StatusCode::PERMANENT_REDIRECT
+    );
+
+    if !is_redirect {
+        return Ok(None);
+    }
+
+    let Some(location) = response.headers().get(LOCATION) else {
+        return Ok(None);
     };
 
-    if status.is_success() {
-        Ok(bin_response)
-    } else {
-        Err(RequestError::response(
-            "Response status code did not indicate success.".to_string(),
-            bin_response.into(),
-        ))
+    let location = location.to_str().map_err(|e| {
+        RequestError::for_error(
+            "Failed to convert redirect location to string.".to_string(),
+            e,
+        )
+    })?;
+
+    Ok(Some(location.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use common_macros::slipway_test_async;
+    use common_test_utils::test_server::TestServer;
+
+    use super::*;
+
+    #[slipway_test_async]
+    async fn it_should_send_requests_through_the_proxy() {
+        let test_server = TestServer::start_from_string_map(HashMap::from([(
+            "http://slipway.invalid/data".to_string(),
+            "proxied".to_string(),
+        )]));
+
+        let client = create_http_client(None, Some(&test_server.localhost_url)).unwrap();
+
+        let response = client
+            .get("http://slipway.invalid/data")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "proxied");
+    }
+
+    #[test]
+    fn it_should_reject_invalid_proxy_urls() {
+        let result = create_http_client(None, Some("not a url"));
+
+        assert!(result.is_err());
+    }
+
+    fn post_request(url: &str) -> HttpRequest {
+        HttpRequest {
+            method: Method::POST,
+            url: Url::parse(url).unwrap(),
+            headers: vec![
+                ("Authorization".to_string(), "Bearer abc".to_string()),
+                ("Content-Type".to_string(), "application/json".to_string()),
+            ],
+            body: Some(b"{}".to_vec()),
+        }
+    }
+
+    #[test]
+    fn it_should_resolve_relative_redirect_locations() {
+        let mut request = post_request("https://example.com/a/b");
+
+        request
+            .redirect(StatusCode::TEMPORARY_REDIRECT, "../c")
+            .unwrap();
+
+        assert_eq!(request.url.as_str(), "https://example.com/c");
+    }
+
+    #[test]
+    fn it_should_keep_method_and_headers_for_same_origin_temporary_redirects() {
+        let mut request = post_request("https://example.com/a");
+
+        request
+            .redirect(StatusCode::TEMPORARY_REDIRECT, "https://example.com/b")
+            .unwrap();
+
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.body, Some(b"{}".to_vec()));
+        assert_eq!(request.headers.len(), 2);
+    }
+
+    #[test]
+    fn it_should_drop_authorization_for_cross_origin_redirects() {
+        let mut request = post_request("https://example.com/a");
+
+        request
+            .redirect(StatusCode::PERMANENT_REDIRECT, "https://other.com/b")
+            .unwrap();
+
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(
+            request.headers,
+            vec![("Content-Type".to_string(), "application/json".to_string())]
+        );
+    }
+
+    #[test]
+    fn it_should_change_post_to_get_for_see_other_redirects() {
+        let mut request = post_request("https://example.com/a");
+
+        request
+            .redirect(StatusCode::SEE_OTHER, "https://example.com/b")
+            .unwrap();
+
+        assert_eq!(request.method, Method::GET);
+        assert_eq!(request.body, None);
+        assert_eq!(
+            request.headers,
+            vec![("Authorization".to_string(), "Bearer abc".to_string())]
+        );
+    }
+
+    #[slipway_test_async]
+    async fn it_should_mark_replayed_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassette.json");
+
+        let recording = HttpCassette::load(&path, vec![]).unwrap();
+        recording
+            .record(HttpInteraction {
+                request: HttpInteractionRequest {
+                    method: "GET".to_string(),
+                    url: "https://example.com/data".to_string(),
+                    headers: vec![],
+                    body: None,
+                },
+                response: HttpInteractionResponse {
+                    status_code: 200,
+                    headers: vec![],
+                    body: HttpCassetteBody::from_bytes(b"data"),
+                },
+            })
+            .await
+            .unwrap();
+
+        let replaying = HttpCassette::load(&path, vec![]).unwrap();
+        let response = replay_http(
+            &replaying,
+            "GET",
+            &Url::parse("https://example.com/data").unwrap(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(response.body, b"data");
+        assert_eq!(response.cache_status, CacheStatus::Replayed);
     }
 }
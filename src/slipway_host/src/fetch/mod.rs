@@ -2,6 +2,8 @@ mod component;
 mod env;
 mod file;
 mod http;
+mod http_log;
+mod paginate;
 
 use std::{error::Error, str::FromStr};
 
@@ -10,9 +12,11 @@ use serde::{Deserialize, Serialize};
 use slipway_engine::{ComponentExecutionContext, ComponentHandle, ProcessedUrl, process_url_str};
 use tracing::warn;
 
-use crate::run::run_component_callout;
+use crate::run::{run_component_callout, run_component_callout_batch};
 
-#[derive(Debug, Default, Deserialize)]
+pub use paginate::{PaginatedRequestOptions, Pagination};
+
+#[derive(Debug, Default, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct RequestOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -26,6 +30,10 @@ pub struct RequestOptions {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout_ms: Option<u32>,
+
+    /// The maximum number of redirects to follow for HTTP requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_redirects: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -34,6 +42,7 @@ pub struct BinResponse {
     pub status_code: u16,
     pub headers: Vec<(String, String)>,
     pub body: Vec<u8>,
+    pub cache_status: CacheStatus,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -42,6 +51,23 @@ pub struct TextResponse {
     pub status_code: u16,
     pub headers: Vec<(String, String)>,
     pub body: String,
+    pub cache_status: CacheStatus,
+}
+
+/// Whether a response was served from a cache, so that components can
+/// decide whether to revalidate data or display how fresh it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheStatus {
+    /// The response was fetched from its source.
+    Uncached,
+
+    /// The server responded with 304 Not Modified, so the requester's
+    /// cached copy is still valid.
+    NotModified,
+
+    /// The response was replayed from an HTTP cassette.
+    Replayed,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -121,6 +147,7 @@ impl From<BinResponse> for TextResponse {
             status_code: r.status_code,
             headers: r.headers,
             body: String::from_utf8_lossy(&r.body).into_owned(),
+            cache_status: r.cache_status,
         }
     }
 }
@@ -134,7 +161,7 @@ pub async fn fetch_bin(
         RequestError::for_inner(
             format!(
                 "Failed to parse URL from component {}: {url_str}",
-                execution_context.call_chain.component_handle_trail()
+                execution_context.call_chain().component_handle_trail()
             ),
             vec![e],
         )
@@ -150,7 +177,7 @@ pub async fn fetch_bin(
             "env" => env::fetch_env_url(execution_context, &url),
             _ => Err(RequestError::message(format!(
                 "Unsupported URL scheme for URL from component {}: {}",
-                execution_context.call_chain.component_handle_trail(),
+                execution_context.call_chain().component_handle_trail(),
                 url_str
             ))),
         },
@@ -167,6 +194,35 @@ pub async fn fetch_text(
         .map(Into::into)
 }
 
+/// Fetches every page of a paginated JSON API, returning the concatenated items
+/// of each page. Each page is fetched using `fetch_text`, so the permissions are
+/// checked for every page URL as for a single fetch.
+pub async fn fetch_pages(
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
+    url_str: &str,
+    options: PaginatedRequestOptions,
+) -> Result<Vec<serde_json::Value>, RequestError> {
+    paginate::fetch_pages(url_str, options, |page_url, page_options| async move {
+        fetch_text(execution_context, &page_url, page_options).await
+    })
+    .await
+}
+
+pub async fn fetch_pages_string(
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
+    url_str: &str,
+    options: PaginatedRequestOptions,
+) -> Result<String, RequestError> {
+    let items = fetch_pages(execution_context, url_str, options).await?;
+
+    serde_json::to_string(&items).map_err(|e| {
+        RequestError::for_error(
+            format!("Failed to serialize the items fetched from \"{url_str}\"."),
+            e,
+        )
+    })
+}
+
 pub async fn run_string(
     execution_context: &ComponentExecutionContext<'_, '_, '_>,
     handle: String,
@@ -180,6 +236,7 @@ pub async fn run_string(
             method: None,
             headers: None,
             timeout_ms: None,
+            max_redirects: None,
         }),
     )
     .await
@@ -197,20 +254,79 @@ pub async fn run_json(
             format!(
                 "Failed to parse component handle \"{}\" from \"{}\"",
                 handle,
-                execution_context.call_chain.component_handle_trail(),
+                execution_context.call_chain().component_handle_trail(),
             ),
             Some(format!("{e}")),
         )
     })?;
 
     // Rather than going through fetch_bin system, which would involve serializing and deserializing
-    // the input and output unnecessarily, we directly call `run_component_callout`.
-    // We must therefore perform our own permissions check here.
-    crate::permissions::ensure_can_use_component_handle(&handle, execution_context)?;
-
+    // the input and output unnecessarily, we directly call `run_component_callout`,
+    // which performs its own permissions check.
     run_component_callout(execution_context, &handle, input).await
 }
 
+pub async fn run_string_batch(
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
+    handle: String,
+    inputs: Vec<String>,
+) -> Result<Vec<String>, crate::ComponentError> {
+    let inputs = inputs
+        .iter()
+        .enumerate()
+        .map(|(index, input)| {
+            serde_json::from_str(input).map_err(|e| {
+                crate::ComponentError::for_error(
+                    format!(
+                        "Failed to parse batch input {} as JSON for component \"{}\" from \"{}\"",
+                        index,
+                        handle,
+                        execution_context.call_chain().component_handle_trail(),
+                    ),
+                    Some(format!("{e}")),
+                )
+            })
+        })
+        .collect::<Result<Vec<serde_json::Value>, _>>()?;
+
+    let outputs = run_json_batch(execution_context, handle.clone(), inputs).await?;
+
+    outputs
+        .iter()
+        .map(|output| {
+            serde_json::to_string(output).map_err(|e| {
+                crate::ComponentError::for_error(
+                    format!(
+                        "Failed to serialize batch output JSON for component \"{}\" from \"{}\"",
+                        handle,
+                        execution_context.call_chain().component_handle_trail(),
+                    ),
+                    Some(format!("{e}")),
+                )
+            })
+        })
+        .collect()
+}
+
+pub async fn run_json_batch(
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
+    handle: String,
+    inputs: Vec<serde_json::Value>,
+) -> Result<Vec<serde_json::Value>, crate::ComponentError> {
+    let handle = ComponentHandle::from_str(&handle).map_err(|e| {
+        crate::ComponentError::for_error(
+            format!(
+                "Failed to parse component handle \"{}\" from \"{}\"",
+                handle,
+                execution_context.call_chain().component_handle_trail(),
+            ),
+            Some(format!("{e}")),
+        )
+    })?;
+
+    run_component_callout_batch(execution_context, &handle, inputs).await
+}
+
 pub async fn load_bin(
     execution_context: &ComponentExecutionContext<'_, '_, '_>,
     handle: String,
@@ -254,7 +370,7 @@ pub fn env(execution_context: &ComponentExecutionContext, key: &str) -> Option<S
             warn!(
                 "Failed to fetch environment variable \"{}\" for component \"{}\":",
                 key,
-                execution_context.call_chain.component_handle_trail(),
+                execution_context.call_chain().component_handle_trail(),
             );
 
             warn!("{}", e.message);
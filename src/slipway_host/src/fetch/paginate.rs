@@ -0,0 +1,408 @@
+use std::future::Future;
+
+use serde::Deserialize;
+use tracing::warn;
+use url::Url;
+
+use super::{RequestError, RequestOptions, TextResponse};
+
+/// The number of pages fetched if the component doesn't specify a maximum,
+/// which prevents a misbehaving API from being paged through indefinitely.
+const DEFAULT_MAX_PAGES: u32 = 100;
+
+const LINK_HEADER: &str = "link";
+
+/// Options for fetching every page of a paginated JSON API.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PaginatedRequestOptions {
+    /// The options used for the request for each page.
+    #[serde(default)]
+    pub request: Option<RequestOptions>,
+
+    /// How to find the next page from each response.
+    pub pagination: Pagination,
+
+    /// A JSON pointer to the array of items in each page's body.
+    /// If not specified the body itself must be an array.
+    #[serde(default)]
+    pub items_pointer: Option<String>,
+
+    /// The maximum number of pages to fetch, defaulting to `DEFAULT_MAX_PAGES`.
+    #[serde(default)]
+    pub max_pages: Option<u32>,
+}
+
+/// How the next page is found from a response.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Pagination {
+    /// Follow the URL in the `Link` header with `rel="next"`, as used by
+    /// APIs such as GitHub's.
+    ///
+    /// Links are only followed within the origin of the first page, as each request
+    /// is sent with the same headers, which may include credentials.
+    LinkHeader,
+
+    /// Read a cursor from each response body and pass it as a query string
+    /// parameter of the next request, until the cursor is missing or empty.
+    Cursor {
+        /// A JSON pointer to the cursor in the response body, for example `/meta/next_cursor`.
+        cursor_pointer: String,
+
+        /// The query string parameter the cursor is passed to the next request in.
+        cursor_parameter: String,
+    },
+}
+
+/// Fetches each page using `fetch_page`, following the pagination strategy until there
+/// are no more pages or the maximum number of pages is reached, and returns the
+/// concatenated items of every page.
+pub(super) async fn fetch_pages<F, Fut>(
+    url: &str,
+    options: PaginatedRequestOptions,
+    mut fetch_page: F,
+) -> Result<Vec<serde_json::Value>, RequestError>
+where
+    F: FnMut(String, Option<RequestOptions>) -> Fut,
+    Fut: Future<Output = Result<TextResponse, RequestError>>,
+{
+    let max_pages = options.max_pages.unwrap_or(DEFAULT_MAX_PAGES);
+    let items_pointer = options.items_pointer.as_deref().unwrap_or("");
+
+    let mut items = Vec::new();
+    let mut next_url = Some(url.to_string());
+    let mut page_count = 0;
+
+    while let Some(page_url) = next_url.take() {
+        if page_count >= max_pages {
+            warn!(
+                "Stopped fetching pages from \"{url}\" after reaching the maximum of {max_pages} pages."
+            );
+            break;
+        }
+
+        let response = fetch_page(page_url.clone(), options.request.clone()).await?;
+        page_count += 1;
+
+        let body: serde_json::Value = serde_json::from_str(&response.body).map_err(|e| {
+            RequestError::for_error(
+                format!("Failed to parse page {page_count} from \"{page_url}\" as JSON."),
+                e,
+            )
+        })?;
+
+        match body.pointer(items_pointer) {
+            Some(serde_json::Value::Array(page_items)) => items.extend(page_items.iter().cloned()),
+            _ => {
+                return Err(RequestError::message(format!(
+                    "Expected an array at \"{items_pointer}\" in page {page_count} from \"{page_url}\"."
+                )));
+            }
+        }
+
+        next_url = match &options.pagination {
+            Pagination::LinkHeader => next_link_url(url, &page_url, &response.headers)?,
+            Pagination::Cursor {
+                cursor_pointer,
+                cursor_parameter,
+            } => next_cursor_url(&page_url, &body, cursor_pointer, cursor_parameter)?,
+        };
+
+        // Guard against APIs which link a page to itself.
+        if next_url.as_deref() == Some(page_url.as_str()) {
+            next_url = None;
+        }
+    }
+
+    Ok(items)
+}
+
+/// Returns the URL of the next page from the `Link` header, resolved against
+/// the URL of the current page.
+///
+/// Returns an error if the next page is not in the same origin as the first page.
+fn next_link_url(
+    first_page_url: &str,
+    page_url: &str,
+    headers: &[(String, String)],
+) -> Result<Option<String>, RequestError> {
+    let Some(next_link) = headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case(LINK_HEADER))
+        .find_map(|(_, value)| parse_next_link(value))
+    else {
+        return Ok(None);
+    };
+
+    let next_url = parse_url(page_url)?.join(next_link).map_err(|e| {
+        RequestError::for_error(
+            format!("Failed to resolve the next page link \"{next_link}\"."),
+            e,
+        )
+    })?;
+
+    if next_url.origin() != parse_url(first_page_url)?.origin() {
+        return Err(RequestError::message(format!(
+            "The next page link \"{next_url}\" is not in the same origin as \"{first_page_url}\"."
+        )));
+    }
+
+    Ok(Some(next_url.to_string()))
+}
+
+/// Returns the target of the link with a `rel` of `next` in a `Link` header value,
+/// for example `<https://example.com/items?page=2>; rel="next"`.
+fn parse_next_link(link_header: &str) -> Option<&str> {
+    link_header.split(',').find_map(|link| {
+        let (target, params) = link.trim().strip_prefix('<')?.split_once('>')?;
+
+        let is_next = params.split(';').any(|param| {
+            param.trim().strip_prefix("rel=").is_some_and(|rel| {
+                rel.trim_matches('"')
+                    .split_whitespace()
+                    .any(|r| r.eq_ignore_ascii_case("next"))
+            })
+        });
+
+        is_next.then_some(target)
+    })
+}
+
+/// Returns the URL of the next page by setting the cursor parameter to the
+/// cursor in the body, or `None` if the body has no cursor.
+fn next_cursor_url(
+    page_url: &str,
+    body: &serde_json::Value,
+    cursor_pointer: &str,
+    cursor_parameter: &str,
+) -> Result<Option<String>, RequestError> {
+    let cursor = match body.pointer(cursor_pointer) {
+        None | Some(serde_json::Value::Null) => return Ok(None),
+        Some(serde_json::Value::String(cursor)) if cursor.is_empty() => return Ok(None),
+        Some(serde_json::Value::String(cursor)) => cursor.clone(),
+        Some(serde_json::Value::Number(cursor)) => cursor.to_string(),
+        Some(other) => {
+            return Err(RequestError::message(format!(
+                "Expected the cursor at \"{cursor_pointer}\" to be a string or number, found: {other}"
+            )));
+        }
+    };
+
+    let mut next_url = parse_url(page_url)?;
+    let query_pairs: Vec<(String, String)> = next_url
+        .query_pairs()
+        .filter(|(name, _)| name != cursor_parameter)
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+
+    next_url
+        .query_pairs_mut()
+        .clear()
+        .extend_pairs(query_pairs)
+        .append_pair(cursor_parameter, &cursor);
+
+    Ok(Some(next_url.to_string()))
+}
+
+fn parse_url(url: &str) -> Result<Url, RequestError> {
+    Url::parse(url)
+        .map_err(|e| RequestError::for_error(format!("Failed to parse page URL \"{url}\"."), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use common_macros::slipway_test_async;
+    use serde_json::json;
+
+    use crate::fetch::CacheStatus;
+
+    use super::*;
+
+    fn response(body: serde_json::Value, headers: Vec<(&str, &str)>) -> TextResponse {
+        TextResponse {
+            status_code: 200,
+            headers: headers
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: body.to_string(),
+            cache_status: CacheStatus::Uncached,
+        }
+    }
+
+    async fn fetch_test_pages(
+        url: &str,
+        options: PaginatedRequestOptions,
+        pages: HashMap<&str, TextResponse>,
+    ) -> (Result<Vec<serde_json::Value>, RequestError>, Vec<String>) {
+        let mut requested = Vec::new();
+        let result = fetch_pages(url, options, |page_url, _| {
+            requested.push(page_url.clone());
+            let page = pages.get(page_url.as_str()).cloned();
+            async move {
+                page.ok_or_else(|| RequestError::message(format!("Page not found: {page_url}")))
+            }
+        })
+        .await;
+
+        (result, requested)
+    }
+
+    #[test]
+    fn it_should_parse_next_links() {
+        assert_eq!(
+            parse_next_link(
+                r#"<https://example.com/items?page=1>; rel="prev", <https://example.com/items?page=3>; rel="next""#
+            ),
+            Some("https://example.com/items?page=3")
+        );
+        assert_eq!(
+            parse_next_link("</items?page=2>; rel=next"),
+            Some("/items?page=2")
+        );
+        assert_eq!(
+            parse_next_link(r#"<https://example.com/items?page=9>; rel="last""#),
+            None
+        );
+    }
+
+    #[test]
+    fn it_should_replace_cursor_parameter() {
+        let next_url = next_cursor_url(
+            "https://example.com/items?limit=10&cursor=abc",
+            &json!({ "meta": { "next": "def" } }),
+            "/meta/next",
+            "cursor",
+        )
+        .unwrap();
+
+        assert_eq!(
+            next_url.as_deref(),
+            Some("https://example.com/items?limit=10&cursor=def")
+        );
+
+        let last_page = next_cursor_url(
+            "https://example.com/items",
+            &json!({ "meta": { "next": null } }),
+            "/meta/next",
+            "cursor",
+        )
+        .unwrap();
+
+        assert_eq!(last_page, None);
+    }
+
+    #[slipway_test_async]
+    async fn it_should_follow_link_headers() {
+        let options = PaginatedRequestOptions {
+            request: None,
+            pagination: Pagination::LinkHeader,
+            items_pointer: None,
+            max_pages: None,
+        };
+
+        let pages = HashMap::from([
+            (
+                "https://example.com/items",
+                response(
+                    json!([1, 2]),
+                    vec![("Link", r#"</items?page=2>; rel="next""#)],
+                ),
+            ),
+            (
+                "https://example.com/items?page=2",
+                response(json!([3]), vec![]),
+            ),
+        ]);
+
+        let (result, _) = fetch_test_pages("https://example.com/items", options, pages).await;
+
+        assert_eq!(result.unwrap(), vec![json!(1), json!(2), json!(3)]);
+    }
+
+    #[slipway_test_async]
+    async fn it_should_not_follow_link_headers_to_other_origins() {
+        let options = PaginatedRequestOptions {
+            request: None,
+            pagination: Pagination::LinkHeader,
+            items_pointer: None,
+            max_pages: None,
+        };
+
+        let pages = HashMap::from([
+            (
+                "https://example.com/items",
+                response(
+                    json!([1, 2]),
+                    vec![("Link", r#"<https://other.com/items?page=2>; rel="next""#)],
+                ),
+            ),
+            (
+                "https://other.com/items?page=2",
+                response(json!([3]), vec![]),
+            ),
+        ]);
+
+        let (result, requested) =
+            fetch_test_pages("https://example.com/items", options, pages).await;
+
+        assert!(result.is_err());
+        assert_eq!(requested, vec!["https://example.com/items"]);
+    }
+
+    #[slipway_test_async]
+    async fn it_should_follow_cursors_up_to_max_pages() {
+        let options = PaginatedRequestOptions {
+            request: None,
+            pagination: Pagination::Cursor {
+                cursor_pointer: "/next".to_string(),
+                cursor_parameter: "after".to_string(),
+            },
+            items_pointer: Some("/data".to_string()),
+            max_pages: Some(2),
+        };
+
+        let pages = HashMap::from([
+            (
+                "https://example.com/items",
+                response(json!({ "data": ["a"], "next": "1" }), vec![]),
+            ),
+            (
+                "https://example.com/items?after=1",
+                response(json!({ "data": ["b"], "next": "2" }), vec![]),
+            ),
+            (
+                "https://example.com/items?after=2",
+                response(json!({ "data": ["c"], "next": null }), vec![]),
+            ),
+        ]);
+
+        let (result, requested) =
+            fetch_test_pages("https://example.com/items", options, pages).await;
+
+        assert_eq!(result.unwrap(), vec![json!("a"), json!("b")]);
+        assert_eq!(requested.len(), 2);
+    }
+
+    #[slipway_test_async]
+    async fn it_should_fail_if_page_is_not_an_array() {
+        let options = PaginatedRequestOptions {
+            request: None,
+            pagination: Pagination::LinkHeader,
+            items_pointer: None,
+            max_pages: None,
+        };
+
+        let pages = HashMap::from([(
+            "https://example.com/items",
+            response(json!({ "items": [] }), vec![]),
+        )]);
+
+        let (result, _) = fetch_test_pages("https://example.com/items", options, pages).await;
+
+        assert!(result.is_err());
+    }
+}
@@ -1,8 +1,16 @@
+use std::collections::BTreeMap;
+
 use fontique::{FamilyId, GenericFamily, QueryFamily, QueryStatus};
 use serde::Serialize;
-use slipway_engine::{ComponentExecutionContext, FontContext};
+use slipway_engine::{
+    ComponentExecutionContext, ComponentLoadWarning, FontContext, PrimedComponent,
+    RigSessionOptions, SlipwayReference,
+};
+use subsetter::GlyphRemapper;
 use tracing::{debug, warn};
 
+use crate::ComponentError;
+
 // We can't use the Wasmtime/WIT generated ResolvedFont here, as this crate is host independent,
 // so use our own struct.
 #[derive(Debug, Serialize)]
@@ -16,9 +24,7 @@ pub async fn font(
     execution_context: &ComponentExecutionContext<'_, '_, '_>,
     font_stack: String,
 ) -> Option<ResolvedFont> {
-    let families: Vec<String> = font_stack
-        .split(",")
-        .map(|s| s.trim().trim_matches('"').to_string())
+    let families: Vec<String> = split_font_stack(&font_stack)
         .filter(|s| {
             if let Err(e) = crate::permissions::ensure_can_query_font(s, execution_context) {
                 warn!(
@@ -32,12 +38,235 @@ pub async fn font(
         })
         .collect();
 
-    let context_mutex = execution_context.rig_session_options.font_context();
+    let context_mutex = execution_context.rig_session_options().font_context();
     let mut context = context_mutex.lock().await;
 
     try_resolve_font_families(&mut context, families)
 }
 
+/// Returns a warning for each font stack declared by a component which can't be resolved
+/// from the fonts available to the rig, including the fonts folder and the bundled fonts,
+/// so that missing fonts are reported before the rig runs rather than as blank glyphs.
+pub async fn find_missing_fonts<'a>(
+    components: impl IntoIterator<Item = (&'a SlipwayReference, &'a PrimedComponent)>,
+    rig_session_options: &RigSessionOptions,
+) -> Vec<ComponentLoadWarning> {
+    let context_mutex = rig_session_options.font_context();
+    let mut context = context_mutex.lock().await;
+
+    let mut warnings = Vec::new();
+    for (reference, component) in components {
+        for font_stack in component.definition.fonts.iter().flatten() {
+            if !is_font_stack_available(&mut context, font_stack) {
+                warnings.push(ComponentLoadWarning {
+                    reference: reference.clone(),
+                    message: format!(
+                        "No font could be found for the required font stack \"{font_stack}\"."
+                    ),
+                });
+            }
+        }
+    }
+
+    warnings.sort_by_key(|w| w.reference.to_string());
+    warnings
+}
+
+fn is_font_stack_available(context: &mut FontContext, font_stack: &str) -> bool {
+    try_resolve_with_context(context, split_font_stack(font_stack).collect()).is_some()
+}
+
+fn split_font_stack(font_stack: &str) -> impl Iterator<Item = String> {
+    font_stack
+        .split(",")
+        .map(|s| s.trim().trim_matches('"').to_string())
+}
+
+/// Returns a subset of the font containing only the glyphs needed to render the text.
+///
+/// Glyphs are renumbered in the order they first appear in the text, after the
+/// `.notdef` glyph, and the subset's character map is rewritten to use the new glyph IDs
+/// so that characters can be looked up as normal. Characters which the font has no
+/// glyph for are skipped.
+pub fn subset_font(
+    execution_context: &ComponentExecutionContext,
+    font: Vec<u8>,
+    text: String,
+) -> Result<Vec<u8>, ComponentError> {
+    subset_font_inner(&font, &text).map_err(|e| {
+        ComponentError::for_error(
+            format!(
+                "Failed to subset font for component \"{}\".",
+                execution_context.call_chain().component_handle_trail()
+            ),
+            Some(e),
+        )
+    })
+}
+
+fn subset_font_inner(font: &[u8], text: &str) -> Result<Vec<u8>, String> {
+    let face =
+        ttf_parser::Face::parse(font, 0).map_err(|e| format!("Failed to parse font: {e}"))?;
+
+    let mut remapper = GlyphRemapper::new();
+    let mut char_map = BTreeMap::new();
+    let mut missing = String::new();
+    for c in text.chars() {
+        match face.glyph_index(c) {
+            Some(glyph_id) => {
+                char_map.insert(c as u32, remapper.remap(glyph_id.0));
+            }
+            None if !c.is_control() && !missing.contains(c) => missing.push(c),
+            None => {}
+        }
+    }
+
+    if !missing.is_empty() {
+        debug!("Font has no glyphs for characters, skipping: {missing}");
+    }
+
+    let subset = subsetter::subset(font, 0, &remapper).map_err(|e| format!("{e}"))?;
+
+    // The subsetter renumbers the glyphs but doesn't produce a character map for them,
+    // so we add one mapping the characters in the text to their new glyph IDs.
+    replace_font_table(&subset, *b"cmap", &build_cmap_table(&char_map))
+}
+
+/// Builds a `cmap` table with a single format 12 subtable, which maps Unicode
+/// code points to glyph IDs.
+fn build_cmap_table(char_map: &BTreeMap<u32, u16>) -> Vec<u8> {
+    // Group consecutive characters which map to consecutive glyphs.
+    let mut groups: Vec<(u32, u32, u32)> = Vec::new();
+    for (&c, &glyph_id) in char_map {
+        let glyph_id = u32::from(glyph_id);
+        match groups.last_mut() {
+            Some((start_char, end_char, start_glyph_id))
+                if c == *end_char + 1 && glyph_id == *start_glyph_id + (c - *start_char) =>
+            {
+                *end_char = c;
+            }
+            _ => groups.push((c, c, glyph_id)),
+        }
+    }
+
+    // The Unicode full repertoire and Windows Unicode full repertoire encodings.
+    const ENCODINGS: [(u16, u16); 2] = [(0, 4), (3, 10)];
+    let subtable_offset = 4 + 8 * ENCODINGS.len() as u32;
+
+    let mut table = Vec::new();
+    table.extend(0u16.to_be_bytes());
+    table.extend((ENCODINGS.len() as u16).to_be_bytes());
+    for (platform_id, encoding_id) in ENCODINGS {
+        table.extend(platform_id.to_be_bytes());
+        table.extend(encoding_id.to_be_bytes());
+        table.extend(subtable_offset.to_be_bytes());
+    }
+
+    table.extend(12u16.to_be_bytes());
+    table.extend(0u16.to_be_bytes());
+    table.extend((16 + 12 * groups.len() as u32).to_be_bytes());
+    table.extend(0u32.to_be_bytes());
+    table.extend((groups.len() as u32).to_be_bytes());
+    for (start_char, end_char, start_glyph_id) in groups {
+        table.extend(start_char.to_be_bytes());
+        table.extend(end_char.to_be_bytes());
+        table.extend(start_glyph_id.to_be_bytes());
+    }
+
+    table
+}
+
+/// Returns a copy of the font with the given table added, or replaced if it already exists.
+fn replace_font_table(font: &[u8], tag: [u8; 4], data: &[u8]) -> Result<Vec<u8>, String> {
+    const HEADER_LEN: usize = 12;
+    const TABLE_RECORD_LEN: usize = 16;
+    const HEAD_CHECKSUM_ADJUSTMENT_OFFSET: usize = 8;
+
+    let read_u32 = |offset: usize| {
+        font.get(offset..offset + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| "Font table directory is truncated.".to_string())
+    };
+
+    let sfnt_version = read_u32(0)?;
+    let num_tables = (read_u32(4)? >> 16) as usize;
+
+    let mut tables = Vec::with_capacity(num_tables + 1);
+    for index in 0..num_tables {
+        let record = HEADER_LEN + index * TABLE_RECORD_LEN;
+        let table_tag = read_u32(record)?.to_be_bytes();
+        let offset = read_u32(record + 8)? as usize;
+        let length = read_u32(record + 12)? as usize;
+
+        if table_tag == tag {
+            continue;
+        }
+
+        let table_data = offset
+            .checked_add(length)
+            .and_then(|end| font.get(offset..end))
+            .ok_or_else(|| "Font table is out of bounds.".to_string())?;
+        tables.push((table_tag, table_data.to_vec()));
+    }
+    tables.push((tag, data.to_vec()));
+    tables.sort_by_key(|(table_tag, _)| *table_tag);
+
+    let num_tables = tables.len() as u16;
+    let entry_selector = num_tables.ilog2() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+
+    let mut output = Vec::new();
+    output.extend(sfnt_version.to_be_bytes());
+    output.extend(num_tables.to_be_bytes());
+    output.extend(search_range.to_be_bytes());
+    output.extend(entry_selector.to_be_bytes());
+    output.extend((num_tables * 16 - search_range).to_be_bytes());
+
+    let mut offset = HEADER_LEN + tables.len() * TABLE_RECORD_LEN;
+    let mut head_offset = None;
+    for (table_tag, table_data) in tables.iter_mut() {
+        if table_tag == b"head" {
+            // The checksum adjustment is excluded from the checksums, and set below.
+            if let Some(adjustment) = table_data
+                .get_mut(HEAD_CHECKSUM_ADJUSTMENT_OFFSET..HEAD_CHECKSUM_ADJUSTMENT_OFFSET + 4)
+            {
+                adjustment.fill(0);
+                head_offset = Some(offset);
+            }
+        }
+
+        output.extend(*table_tag);
+        output.extend(font_checksum(table_data).to_be_bytes());
+        output.extend((offset as u32).to_be_bytes());
+        output.extend((table_data.len() as u32).to_be_bytes());
+        offset += table_data.len().next_multiple_of(4);
+    }
+
+    for (_, table_data) in tables {
+        let len = table_data.len();
+        output.extend(table_data);
+        output.resize(output.len() + len.next_multiple_of(4) - len, 0);
+    }
+
+    if let Some(head_offset) = head_offset {
+        let adjustment = 0xB1B0AFBAu32.wrapping_sub(font_checksum(&output));
+        let offset = head_offset + HEAD_CHECKSUM_ADJUSTMENT_OFFSET;
+        output[offset..offset + 4].copy_from_slice(&adjustment.to_be_bytes());
+    }
+
+    Ok(output)
+}
+
+/// Calculates an OpenType checksum, which is the sum of the data as big endian
+/// `u32` values, padded with zeros to a multiple of four bytes.
+fn font_checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut bytes = [0u8; 4];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(bytes))
+    })
+}
+
 fn try_resolve_font_families(
     context: &mut FontContext,
     families: Vec<String>,
@@ -133,6 +362,69 @@ mod tests {
         assert!(result.is_none(), "NonExistentFont should not be resolvable");
     }
 
+    const ROBOTO_FONT: &[u8] = include_bytes!("../../fonts/Roboto.ttf");
+
+    #[test]
+    fn it_should_subset_font_to_glyphs_in_text() {
+        let subset = subset_font_inner(ROBOTO_FONT, "Hello").unwrap();
+        assert!(subset.len() < ROBOTO_FONT.len());
+
+        let face = ttf_parser::Face::parse(&subset, 0).unwrap();
+
+        // The .notdef glyph, plus "H", "e", "l" and "o".
+        assert_eq!(face.number_of_glyphs(), 5);
+    }
+
+    #[test]
+    fn it_should_map_characters_to_subset_glyphs() {
+        let original = ttf_parser::Face::parse(ROBOTO_FONT, 0).unwrap();
+        let subset = subset_font_inner(ROBOTO_FONT, "Hello").unwrap();
+        let face = ttf_parser::Face::parse(&subset, 0).unwrap();
+
+        for c in ['H', 'e', 'l', 'o'] {
+            let original_glyph_id = original.glyph_index(c).unwrap();
+            let glyph_id = face
+                .glyph_index(c)
+                .unwrap_or_else(|| panic!("Subset should contain a glyph for {c:?}"));
+
+            assert_ne!(glyph_id.0, 0);
+            assert_eq!(
+                face.glyph_bounding_box(glyph_id),
+                original.glyph_bounding_box(original_glyph_id),
+                "Glyph for {c:?} should match the original font"
+            );
+            assert_eq!(
+                face.glyph_hor_advance(glyph_id),
+                original.glyph_hor_advance(original_glyph_id)
+            );
+        }
+
+        assert_eq!(face.glyph_index('x'), None);
+    }
+
+    #[test]
+    fn it_should_skip_characters_missing_from_font() {
+        let subset = subset_font_inner(ROBOTO_FONT, "A\u{10FFFD}").unwrap();
+
+        let face = ttf_parser::Face::parse(&subset, 0).unwrap();
+        assert_eq!(face.number_of_glyphs(), 2);
+    }
+
+    #[test]
+    fn it_should_fail_to_subset_invalid_font() {
+        assert!(subset_font_inner(b"not a font", "Hello").is_err());
+    }
+
+    #[test]
+    fn it_should_check_font_stack_availability() {
+        let mut context = FontContext::new();
+        assert!(is_font_stack_available(
+            &mut context,
+            r#""NonExistentFont", sans-serif"#
+        ));
+        assert!(!is_font_stack_available(&mut context, "NonExistentFont"));
+    }
+
     #[test]
     fn test_try_resolve_with_fallbacks() {
         let mut context = FontContext::new();
@@ -0,0 +1,238 @@
+use serde::Serialize;
+use slipway_engine::ComponentExecutionContext;
+
+use crate::ComponentError;
+
+// We can't use the Wasmtime/WIT generated DecodedImage here, as this crate is host independent,
+// so use our own struct.
+#[derive(Debug, Serialize)]
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+
+    /// The pixels as 8-bit RGBA, row by row.
+    pub data: Vec<u8>,
+}
+
+/// The maximum width or height of an image which can be decoded or encoded.
+pub const MAX_IMAGE_DIMENSION: u32 = 16_384;
+
+/// The maximum number of bytes which may be allocated while decoding an image.
+pub const MAX_IMAGE_ALLOC_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Decodes a PNG, JPEG or WebP image, returning its pixels as RGBA.
+///
+/// Images are untrusted input, so decoding is bounded by [`MAX_IMAGE_DIMENSION`] and
+/// [`MAX_IMAGE_ALLOC_BYTES`], and runs on the blocking thread pool so it doesn't
+/// stall other components.
+pub async fn decode_image(
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
+    bin: Vec<u8>,
+) -> Result<DecodedImage, ComponentError> {
+    run_blocking(move || decode_image_inner(&bin))
+        .await
+        .map_err(|e| {
+            ComponentError::for_error(
+                format!(
+                    "Failed to decode image for component \"{}\".",
+                    execution_context.call_chain().component_handle_trail()
+                ),
+                Some(e),
+            )
+        })
+}
+
+/// Encodes RGBA pixels as an image in the given format, either `png`, `jpeg` or `webp`.
+/// JPEG images do not support transparency, so the alpha channel is discarded.
+pub async fn encode_image(
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
+    image: DecodedImage,
+    format: String,
+) -> Result<Vec<u8>, ComponentError> {
+    let encode_format = format.clone();
+    run_blocking(move || encode_image_inner(image, &encode_format))
+        .await
+        .map_err(|e| {
+            ComponentError::for_error(
+                format!(
+                    "Failed to encode image as \"{format}\" for component \"{}\".",
+                    execution_context.call_chain().component_handle_trail()
+                ),
+                Some(e),
+            )
+        })
+}
+
+async fn run_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[cfg(not(feature = "image"))]
+const IMAGE_SUPPORT_DISABLED: &str =
+    "Image support is not enabled. Enable the \"image\" feature of slipway_host.";
+
+#[cfg(not(feature = "image"))]
+fn decode_image_inner(_bin: &[u8]) -> Result<DecodedImage, String> {
+    Err(IMAGE_SUPPORT_DISABLED.to_string())
+}
+
+#[cfg(not(feature = "image"))]
+fn encode_image_inner(_image: DecodedImage, _format: &str) -> Result<Vec<u8>, String> {
+    Err(IMAGE_SUPPORT_DISABLED.to_string())
+}
+
+#[cfg(feature = "image")]
+fn decode_image_inner(bin: &[u8]) -> Result<DecodedImage, String> {
+    let format = ::image::guess_format(bin).map_err(|e| e.to_string())?;
+    if !matches!(
+        format,
+        ::image::ImageFormat::Png | ::image::ImageFormat::Jpeg | ::image::ImageFormat::WebP
+    ) {
+        return Err(format!("Unsupported image format: {format:?}"));
+    }
+
+    let mut limits = ::image::Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+    limits.max_alloc = Some(MAX_IMAGE_ALLOC_BYTES);
+
+    let mut reader = ::image::ImageReader::with_format(std::io::Cursor::new(bin), format);
+    reader.limits(limits);
+
+    let rgba = reader.decode().map_err(|e| e.to_string())?.into_rgba8();
+
+    Ok(DecodedImage {
+        width: rgba.width(),
+        height: rgba.height(),
+        data: rgba.into_raw(),
+    })
+}
+
+#[cfg(feature = "image")]
+fn encode_image_inner(image: DecodedImage, format: &str) -> Result<Vec<u8>, String> {
+    use ::image::{DynamicImage, ImageFormat, RgbaImage};
+
+    let format = match format.to_ascii_lowercase().as_str() {
+        "png" => ImageFormat::Png,
+        "jpeg" | "jpg" => ImageFormat::Jpeg,
+        "webp" => ImageFormat::WebP,
+        other => return Err(format!("Unsupported image format: {other}")),
+    };
+
+    if image.width > MAX_IMAGE_DIMENSION || image.height > MAX_IMAGE_DIMENSION {
+        return Err(format!(
+            "Images can be at most {MAX_IMAGE_DIMENSION}x{MAX_IMAGE_DIMENSION}, found {}x{}.",
+            image.width, image.height
+        ));
+    }
+
+    let expected_len = image.width as usize * image.height as usize * 4;
+    if image.data.len() != expected_len {
+        return Err(format!(
+            "Expected {expected_len} bytes of RGBA data for a {}x{} image, found {}.",
+            image.width,
+            image.height,
+            image.data.len()
+        ));
+    }
+
+    let rgba = RgbaImage::from_raw(image.width, image.height, image.data)
+        .expect("image data length should match its dimensions");
+
+    let dynamic_image = match format {
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(rgba).into_rgb8()),
+        _ => DynamicImage::ImageRgba8(rgba),
+    };
+
+    let mut bytes = Vec::new();
+    dynamic_image
+        .write_to(&mut std::io::Cursor::new(&mut bytes), format)
+        .map_err(|e| e.to_string())?;
+
+    Ok(bytes)
+}
+
+#[cfg(all(test, feature = "image"))]
+mod tests {
+    use super::*;
+
+    fn test_image() -> DecodedImage {
+        DecodedImage {
+            width: 2,
+            height: 1,
+            data: vec![255, 0, 0, 255, 0, 0, 255, 128],
+        }
+    }
+
+    #[test]
+    fn it_should_round_trip_png() {
+        let encoded = encode_image_inner(test_image(), "png").unwrap();
+        let decoded = decode_image_inner(&encoded).unwrap();
+
+        assert_eq!(decoded.width, 2);
+        assert_eq!(decoded.height, 1);
+        assert_eq!(decoded.data, test_image().data);
+    }
+
+    #[test]
+    fn it_should_round_trip_webp() {
+        let encoded = encode_image_inner(test_image(), "webp").unwrap();
+        let decoded = decode_image_inner(&encoded).unwrap();
+
+        assert_eq!(decoded.data, test_image().data);
+    }
+
+    #[test]
+    fn it_should_encode_jpeg_without_alpha() {
+        let encoded = encode_image_inner(test_image(), "JPEG").unwrap();
+        let decoded = decode_image_inner(&encoded).unwrap();
+
+        assert_eq!((decoded.width, decoded.height), (2, 1));
+        assert!(decoded.data.chunks(4).all(|pixel| pixel[3] == 255));
+    }
+
+    #[test]
+    fn it_should_reject_mismatched_dimensions() {
+        let mut image = test_image();
+        image.height = 2;
+
+        let error = encode_image_inner(image, "png").unwrap_err();
+        assert!(error.contains("Expected 16 bytes"));
+    }
+
+    #[test]
+    fn it_should_reject_images_exceeding_limits() {
+        let image = ::image::RgbaImage::new(MAX_IMAGE_DIMENSION + 1, 1);
+        let mut encoded = Vec::new();
+        ::image::DynamicImage::ImageRgba8(image)
+            .write_to(
+                &mut std::io::Cursor::new(&mut encoded),
+                ::image::ImageFormat::Png,
+            )
+            .unwrap();
+
+        let error = decode_image_inner(&encoded).unwrap_err();
+        assert!(error.to_lowercase().contains("limit"), "{error}");
+
+        let error = encode_image_inner(
+            DecodedImage {
+                width: MAX_IMAGE_DIMENSION + 1,
+                height: 1,
+                data: vec![],
+            },
+            "png",
+        )
+        .unwrap_err();
+        assert!(error.contains("at most"), "{error}");
+    }
+
+    #[test]
+    fn it_should_reject_unsupported_formats() {
+        assert!(encode_image_inner(test_image(), "gif").is_err());
+        assert!(decode_image_inner(b"not an image").is_err());
+    }
+}
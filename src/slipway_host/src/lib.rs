@@ -4,12 +4,15 @@ use sha2::{Digest, Sha256};
 pub mod bin;
 pub mod fetch;
 pub mod fonts;
+pub mod images;
 pub mod log;
 mod permissions;
 pub mod render_state;
 pub mod run;
 pub mod tracing_writer;
 
+pub use images::{DecodedImage, decode_image, encode_image};
+
 pub const SLIPWAY_COMPONENT_WASM_FILE_NAME: &str = "run.wasm";
 
 // We can't use the Wasmtime/WIT generated ComponentError here, as this crate is host independent,
@@ -1,4 +1,4 @@
-use tracing::{debug, error, info, trace, warn};
+use tracing::{Level, debug, error, info, trace, warn};
 
 pub fn log_trace(message: String) {
     trace!(message);
@@ -19,3 +19,15 @@ pub fn log_warn(message: String) {
 pub fn log_error(message: String) {
     error!(message);
 }
+
+/// Logs a message written to the console by a Javascript component,
+/// tagged with the component's handle trail.
+pub fn log_console(level: Level, component: &str, message: String) {
+    match level {
+        Level::TRACE => trace!(component, "{message}"),
+        Level::DEBUG => debug!(component, "{message}"),
+        Level::INFO => info!(component, "{message}"),
+        Level::WARN => warn!(component, "{message}"),
+        _ => error!(component, "{message}"),
+    }
+}
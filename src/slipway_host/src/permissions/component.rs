@@ -1,144 +1,154 @@
 use std::sync::Arc;
 
 use crate::ComponentError;
-use semver::Version;
 use slipway_engine::{
-    CallChain, ComponentExecutionContext, ComponentHandle, Permission, SlipwayReference,
+    CallChain, ComponentExecutionContext, ComponentHandle, Permission, Permissions,
+    SlipwayReference,
 };
-use url::Url;
 
-use super::log_permissions_check;
+use super::{
+    LevelOutcome,
+    audit::{AuditedCall, audit_host_call},
+    log_permissions_check,
+};
 
 pub fn ensure_can_use_component_handle(
     handle: &ComponentHandle,
     execution_context: &ComponentExecutionContext,
 ) -> Result<(), ComponentError> {
     let component_callout = execution_context
-        .callout_context
+        .callout_context()
         .get_component_callout_for_handle(handle)
         .map_err(|e| ComponentError {
             message: format!(
                 "Failed to locate callout component reference for \"{}\"",
                 execution_context
-                    .call_chain
+                    .call_chain()
                     .component_handle_trail_for(handle)
             ),
             inner: vec![format!("{e}")],
         })?;
 
-    let call_chain = Arc::clone(&execution_context.call_chain);
-
-    ensure_can_use_component_reference(&component_callout.component, call_chain)
+    let call_chain = Arc::clone(execution_context.call_chain());
+    let explain_permissions = execution_context.rig_session_options().explain_permissions;
+
+    let result = ensure_can_call_out(
+        handle,
+        &component_callout.component,
+        Arc::clone(&call_chain),
+        explain_permissions,
+    )
+    .and_then(|()| {
+        ensure_can_use_component_reference(
+            &component_callout.component,
+            call_chain,
+            explain_permissions,
+        )
+    });
+
+    audit_host_call(execution_context, AuditedCall::Callout { handle }, &result);
+    super::record_permission_denial(execution_context, &result);
+    result
 }
 
-pub fn ensure_can_use_component_reference(
+/// Ensures the callout is allowed by any `callouts` permissions in the call chain.
+/// Callouts are only restricted at levels of the chain which allow at least one
+/// `callouts` permission, so that existing rigs are unaffected.
+fn ensure_can_call_out(
+    handle: &ComponentHandle,
     component_reference: &SlipwayReference,
     call_chain: Arc<CallChain<'_>>,
+    explain_permissions: bool,
 ) -> Result<(), ComponentError> {
-    log_permissions_check(&format!("use component: {component_reference}"));
-
-    let is_allowed = match component_reference {
-        SlipwayReference::Registry {
-            publisher,
-            name,
-            version,
-        } => {
-            fn matches(
-                publisher: &str,
-                name: &str,
-                version: &Version,
-                permission: &Permission,
-            ) -> bool {
-                match permission {
-                    Permission::All => true,
-                    Permission::RegistryComponents(permission) => {
-                        permission.matches(publisher, name, version)
-                    }
-                    _ => false,
-                }
-            }
-
-            slipway_engine::ensure_permissions(call_chain.clone(), |permissions| {
-                for permission in permissions.deny {
-                    if matches(publisher, name, version, permission) {
-                        super::warn_deny_permission_triggered(permission);
-                        return false;
-                    }
-                }
-
-                for permission in permissions.allow {
-                    if matches(publisher, name, version, permission) {
-                        return true;
-                    }
-                }
-
-                false
-            })
-        }
-        SlipwayReference::Http { url } => {
-            fn matches(url: &Url, permission: &Permission) -> bool {
-                match permission {
-                    Permission::All => true,
-                    Permission::HttpComponents(permission) => permission.matches(url),
-                    _ => false,
-                }
-            }
+    log_permissions_check(&format!("call out to: {handle} ({component_reference})"));
+
+    let check = |permissions: &Permissions| {
+        let outcome = super::check_level(permissions, |permission| {
+            matches!(
+                permission,
+                Permission::Callouts(callout_permission)
+                    if callout_permission.matches(handle, component_reference)
+            )
+        });
+
+        let is_restricted = permissions
+            .allow
+            .iter()
+            .any(|permission| matches!(permission, Permission::Callouts(_)));
+
+        match outcome {
+            LevelOutcome::NotAllowed if !is_restricted => LevelOutcome::Unrestricted,
+            outcome => outcome,
+        }
+    };
 
-            slipway_engine::ensure_permissions(call_chain.clone(), |permissions| {
-                for permission in permissions.deny {
-                    if matches(url, permission) {
-                        super::warn_deny_permission_triggered(permission);
-                        return false;
-                    }
-                }
-
-                for permission in permissions.allow {
-                    if matches(url, permission) {
-                        return true;
-                    }
-                }
-
-                false
-            })
-        }
-        SlipwayReference::Local { path: _ } => {
-            fn matches(path: &str, permission: &Permission) -> bool {
-                match permission {
-                    Permission::All => true,
-                    Permission::LocalComponents(permission) => permission.matches(path),
-                    _ => false,
-                }
-            }
+    if !super::is_allowed(&call_chain, &check) {
+        let message = format!(
+            "{} does not have permission to call out to \"{}\" ({})",
+            call_chain.rig_or_component_handle_trail_error_prefix(),
+            handle,
+            component_reference
+        );
+        return Err(super::create_permission_error(
+            message,
+            &call_chain,
+            explain_permissions,
+            &check,
+        ));
+    }
 
-            let local_reference_string = component_reference.to_string();
+    Ok(())
+}
 
-            slipway_engine::ensure_permissions(call_chain.clone(), |permissions| {
-                for permission in permissions.deny {
-                    if matches(&local_reference_string, permission) {
-                        super::warn_deny_permission_triggered(permission);
-                        return false;
-                    }
-                }
+pub fn ensure_can_use_component_reference(
+    component_reference: &SlipwayReference,
+    call_chain: Arc<CallChain<'_>>,
+    explain_permissions: bool,
+) -> Result<(), ComponentError> {
+    log_permissions_check(&format!("use component: {component_reference}"));
 
-                for permission in permissions.allow {
-                    if matches(&local_reference_string, permission) {
-                        return true;
-                    }
-                }
+    if matches!(component_reference, SlipwayReference::Special(_)) {
+        return Ok(());
+    }
 
-                false
-            })
+    let local_reference_string = component_reference.to_string();
+
+    let matches = |permission: &Permission| match (component_reference, permission) {
+        (_, Permission::All) => true,
+        (
+            SlipwayReference::Registry {
+                publisher,
+                name,
+                version,
+            },
+            Permission::RegistryComponents(permission),
+        ) => permission.matches(publisher, name, version),
+        (SlipwayReference::Http { url }, Permission::HttpComponents(permission)) => {
+            permission.matches(url)
+        }
+        (SlipwayReference::Git { url, rev: _ }, Permission::GitComponents(permission)) => {
+            permission.matches(url)
         }
-        SlipwayReference::Special(_) => true,
+        (SlipwayReference::Local { path: _ }, Permission::LocalComponents(permission)) => {
+            permission.matches(&local_reference_string)
+        }
+        _ => false,
     };
 
-    if !is_allowed {
+    let check = |permissions: &Permissions| super::check_level(permissions, &matches);
+
+    if !super::is_allowed(&call_chain, &check) {
         let message = format!(
             "{} does not have permission to access component \"{}\"",
             call_chain.rig_or_component_handle_trail_error_prefix(),
             component_reference
         );
-        return Err(super::create_permission_error(message, &call_chain));
+        return Err(super::create_permission_error(
+            message,
+            &call_chain,
+            explain_permissions,
+            &check,
+        ));
     }
 
     Ok(())
@@ -152,6 +162,7 @@ mod test {
     use slipway_engine::RegistryComponentPermission;
     use slipway_engine::UrlPermission;
     use slipway_engine::{ComponentHandle, Permissions, utils::ch};
+    use url::Url;
 
     use super::*;
 
@@ -162,7 +173,8 @@ mod test {
         let handle = CH.get_or_init(|| ch("test"));
         let call_chain = Arc::new(CallChain::new_for_component(handle, permissions));
         assert_eq!(
-            ensure_can_use_component_reference(&component_reference, call_chain.clone()).is_ok(),
+            ensure_can_use_component_reference(&component_reference, call_chain.clone(), false)
+                .is_ok(),
             expected
         );
     }
@@ -475,6 +487,47 @@ mod test {
         }
     }
 
+    mod git {
+        use super::*;
+
+        #[test]
+        fn it_should_allow_any_git_reference() {
+            run_test(
+                "git+https://github.com/p1/n1.git#v1.0.1",
+                Permissions::allow(&vec![Permission::GitComponents(UrlPermission::Any {})]),
+                true,
+            );
+        }
+
+        #[test]
+        fn it_should_allow_git_host_prefix() {
+            let permissions = vec![Permission::GitComponents(UrlPermission::Prefix {
+                prefix: Url::parse("https://github.com/p1/").unwrap(),
+            })];
+
+            run_test(
+                "git+https://github.com/p1/n1.git#v1.0.1",
+                Permissions::allow(&permissions),
+                true,
+            );
+
+            run_test(
+                "git+https://gitlab.com/p1/n1.git#v1.0.1",
+                Permissions::allow(&permissions),
+                false,
+            );
+        }
+
+        #[test]
+        fn it_should_not_allow_git_reference_with_http_component_permission() {
+            run_test(
+                "git+https://github.com/p1/n1.git#v1.0.1",
+                Permissions::allow(&vec![Permission::HttpComponents(UrlPermission::Any {})]),
+                false,
+            );
+        }
+    }
+
     mod local {
         use super::*;
         use slipway_engine::LocalComponentPermission;
@@ -570,4 +623,232 @@ mod test {
             }
         }
     }
+
+    mod callout_chain {
+        use slipway_engine::{ChainItem, ComponentRigging, PermissionsChainLink};
+
+        use super::*;
+
+        fn publisher_permission(publisher: &str) -> Vec<Permission> {
+            vec![Permission::RegistryComponents(
+                RegistryComponentPermission {
+                    publisher: Some(publisher.to_string()),
+                    name: None,
+                    version: None,
+                },
+            )]
+        }
+
+        fn reference(reference_str: &str) -> SlipwayReference {
+            SlipwayReference::from_str(reference_str).unwrap()
+        }
+
+        #[test]
+        fn it_should_not_allow_callout_to_exceed_caller_permissions() {
+            let caller_allow = publisher_permission("p1");
+            let callout_allow = Permission::all();
+            let caller = ch("caller");
+            let callee = ch("callee");
+
+            let call_chain = CallChain::new_child_arc(
+                &callee,
+                ChainItem::Some(Permissions::allow(&callout_allow)),
+                Arc::new(CallChain::new_for_component(
+                    &caller,
+                    Permissions::allow(&caller_allow),
+                )),
+            );
+
+            assert!(
+                ensure_can_use_component_reference(
+                    &reference("p1.n1.1.0.1"),
+                    call_chain.clone(),
+                    false
+                )
+                .is_ok()
+            );
+
+            let error = ensure_can_use_component_reference(
+                &reference("p2.n1.1.0.1"),
+                call_chain.clone(),
+                false,
+            )
+            .unwrap_err();
+            assert_eq!(
+                error.message,
+                "Component \"caller -> callee\" does not have permission to access component \"p2.n1.1.0.1\""
+            );
+        }
+
+        #[test]
+        fn it_should_not_allow_callout_to_exceed_its_own_permissions() {
+            let caller_allow = Permission::all();
+            let callout_allow = publisher_permission("p1");
+            let caller = ch("caller");
+            let callee = ch("callee");
+
+            let call_chain = CallChain::new_child_arc(
+                &callee,
+                ChainItem::Some(Permissions::allow(&callout_allow)),
+                Arc::new(CallChain::new_for_component(
+                    &caller,
+                    Permissions::allow(&caller_allow),
+                )),
+            );
+
+            assert!(
+                ensure_can_use_component_reference(
+                    &reference("p1.n1.1.0.1"),
+                    call_chain.clone(),
+                    false
+                )
+                .is_ok()
+            );
+            assert!(
+                ensure_can_use_component_reference(
+                    &reference("p2.n1.1.0.1"),
+                    call_chain.clone(),
+                    false
+                )
+                .is_err()
+            );
+        }
+
+        #[test]
+        fn it_should_deny_callout_if_any_link_denies() {
+            let caller_allow = Permission::all();
+            let caller_deny = publisher_permission("p1");
+            let callout_allow = Permission::all();
+            let caller = ch("caller");
+            let callee = ch("callee");
+
+            let call_chain = CallChain::new_child_arc(
+                &callee,
+                ChainItem::Some(Permissions::allow(&callout_allow)),
+                Arc::new(CallChain::new_for_component(
+                    &caller,
+                    Permissions::new(&caller_allow, &caller_deny),
+                )),
+            );
+
+            assert!(
+                ensure_can_use_component_reference(
+                    &reference("p1.n1.1.0.1"),
+                    call_chain.clone(),
+                    false
+                )
+                .is_err()
+            );
+            assert!(
+                ensure_can_use_component_reference(
+                    &reference("p2.n1.1.0.1"),
+                    call_chain.clone(),
+                    false
+                )
+                .is_ok()
+            );
+        }
+
+        #[test]
+        fn it_should_intersect_every_link_of_a_rigging_permissions_chain() {
+            let rigging = ComponentRigging {
+                component: reference("p1.n1.1.0.1"),
+                input: None,
+                allow: None,
+                deny: None,
+                permissions_chain: Some(vec![
+                    PermissionsChainLink {
+                        allow: Permission::all(),
+                        deny: vec![],
+                    },
+                    PermissionsChainLink {
+                        allow: publisher_permission("p1"),
+                        deny: vec![],
+                    },
+                ]),
+                callouts: None,
+                runner: None,
+                memoize: None,
+                skip: None,
+            };
+            let handle = ch("test");
+
+            let mut call_chain = Arc::new(CallChain::full_trust());
+            for link in rigging.permissions_as_chain() {
+                call_chain = CallChain::new_child_arc(
+                    &handle,
+                    ChainItem::Some(Permissions::new(link.allow, link.deny)),
+                    call_chain,
+                );
+            }
+
+            assert!(
+                ensure_can_use_component_reference(
+                    &reference("p1.n1.1.0.1"),
+                    call_chain.clone(),
+                    false
+                )
+                .is_ok()
+            );
+            assert!(
+                ensure_can_use_component_reference(
+                    &reference("p2.n1.1.0.1"),
+                    call_chain.clone(),
+                    false
+                )
+                .is_err()
+            );
+        }
+    }
+
+    mod callouts {
+        use slipway_engine::{CalloutPermission, ChainItem};
+
+        use super::*;
+
+        fn callout_permission(handle: Option<&str>) -> Vec<Permission> {
+            vec![Permission::Callouts(CalloutPermission {
+                handle: handle.map(ch),
+                component: None,
+            })]
+        }
+
+        fn check(permissions: Permissions, handle: &str) -> bool {
+            let caller = ch("caller");
+            let call_chain = CallChain::new_child_arc(
+                &caller,
+                ChainItem::Some(permissions),
+                Arc::new(CallChain::full_trust()),
+            );
+
+            ensure_can_call_out(
+                &ch(handle),
+                &SlipwayReference::from_str("p1.n1.1.0.1").unwrap(),
+                call_chain,
+                false,
+            )
+            .is_ok()
+        }
+
+        #[test]
+        fn it_should_not_restrict_callouts_without_callout_permissions() {
+            assert!(check(Permissions::allow(&Permission::all()), "increment"));
+            assert!(check(Permissions::empty(), "increment"));
+        }
+
+        #[test]
+        fn it_should_only_allow_listed_callouts() {
+            let allow = callout_permission(Some("increment"));
+            assert!(check(Permissions::allow(&allow), "increment"));
+            assert!(!check(Permissions::allow(&allow), "other"));
+        }
+
+        #[test]
+        fn it_should_deny_listed_callouts() {
+            let allow = Permission::all();
+            let deny = callout_permission(Some("increment"));
+            assert!(!check(Permissions::new(&allow, &deny), "increment"));
+            assert!(check(Permissions::new(&allow, &deny), "other"));
+        }
+    }
 }
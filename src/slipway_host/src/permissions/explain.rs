@@ -0,0 +1,131 @@
+use slipway_engine::{CallChain, CallChainLink, ChainItem, Permission, Permissions};
+
+use super::LevelOutcome;
+
+/// Describes how each level of the call chain resolved a permission check,
+/// from the rig down to the component, including the `allow` and `deny`
+/// permissions of each level and which of them matched the request.
+pub(super) fn explain_permissions(
+    call_chain: &CallChain<'_>,
+    check: &impl Fn(&Permissions) -> LevelOutcome,
+) -> Vec<String> {
+    let mut trail = call_chain.permission_trail();
+    trail.reverse();
+
+    let mut lines = vec![
+        "Permissions are checked at each level of the call chain, from the rig down to the component. \
+        The request must be allowed at every level, and a deny permission at any level takes precedence."
+            .to_string(),
+    ];
+
+    for (index, link) in trail.iter().enumerate() {
+        lines.push(explain_level(link, index == 0, check));
+    }
+
+    lines
+}
+
+fn explain_level(
+    link: &CallChainLink<'_>,
+    is_top_level: bool,
+    check: &impl Fn(&Permissions) -> LevelOutcome,
+) -> String {
+    let label = match link.handle {
+        Some(handle) => format!("Component \"{handle}\""),
+        None => "Rig".to_string(),
+    };
+
+    match &link.permissions {
+        ChainItem::Some(permissions) => format!(
+            "{label}: allow {}, deny {}: {}",
+            format_permissions(permissions.allow),
+            format_permissions(permissions.deny),
+            format_outcome(&check(permissions))
+        ),
+        ChainItem::Inherit if is_top_level => format!(
+            "{label}: inherits permissions, but there is no level above so no permissions are granted: {}",
+            format_outcome(&check(&Permissions::empty()))
+        ),
+        ChainItem::Inherit => format!("{label}: inherits the permissions of the level above"),
+    }
+}
+
+fn format_outcome(outcome: &LevelOutcome) -> String {
+    match outcome {
+        LevelOutcome::Denied(permission) => {
+            format!("denied by {}", format_permission(permission))
+        }
+        LevelOutcome::Allowed(permission) => {
+            format!("allowed by {}", format_permission(permission))
+        }
+        LevelOutcome::NotAllowed => "denied, as no allow permission matches".to_string(),
+        LevelOutcome::Unrestricted => {
+            "allowed, as this level doesn't restrict these requests".to_string()
+        }
+    }
+}
+
+fn format_permissions(permissions: &[Permission]) -> String {
+    let permissions = permissions
+        .iter()
+        .map(format_permission)
+        .collect::<Vec<_>>();
+    format!("[{}]", permissions.join(", "))
+}
+
+fn format_permission(permission: &Permission) -> String {
+    serde_json::to_string(permission).unwrap_or_else(|_| format!("{permission:?}"))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use slipway_engine::{UrlPermission, utils::ch};
+    use url::Url;
+
+    use super::super::check_level;
+    use super::*;
+
+    #[test]
+    fn it_should_explain_each_level_from_the_rig_down() {
+        let url = Url::parse("https://example.com/a").unwrap();
+        let check = |permissions: &Permissions| {
+            check_level(permissions, |permission| match permission {
+                Permission::Http(permission) => permission.matches(&url),
+                _ => false,
+            })
+        };
+
+        let rig_allow = vec![Permission::Http(UrlPermission::Any {})];
+        let component_deny = vec![Permission::Http(UrlPermission::Prefix {
+            prefix: Url::parse("https://example.com/").unwrap(),
+        })];
+        let a = ch("a");
+        let b = ch("b");
+
+        let rig = Arc::new(CallChain::new(Permissions::allow(&rig_allow)));
+        let a_chain = CallChain::new_child_arc(&a, ChainItem::Inherit, rig);
+        let b_chain = CallChain::new_child(
+            &b,
+            ChainItem::Some(Permissions::deny(&component_deny)),
+            a_chain,
+        );
+
+        let lines = explain_permissions(&b_chain, &check);
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[1],
+            r#"Rig: allow [{"permission":"http"}], deny []: allowed by {"permission":"http"}"#
+        );
+        assert_eq!(
+            lines[2],
+            r#"Component "a": inherits the permissions of the level above"#
+        );
+        assert_eq!(
+            lines[3],
+            r#"Component "b": allow [], deny [{"permission":"http","prefix":"https://example.com/"}]: denied by {"permission":"http","prefix":"https://example.com/"}"#
+        );
+    }
+}
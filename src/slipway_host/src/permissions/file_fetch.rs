@@ -1,52 +1,49 @@
 use std::{path::Path, sync::Arc};
 
+use super::audit::{AuditedCall, audit_host_call};
 use crate::{ComponentError, permissions::log_permissions_check};
-use slipway_engine::{CallChain, ComponentExecutionContext, Permission};
+use slipway_engine::{CallChain, ComponentExecutionContext, Permission, Permissions};
 
 pub fn ensure_can_fetch_file(
     path: &Path,
     execution_context: &ComponentExecutionContext,
 ) -> Result<(), ComponentError> {
     log_permissions_check(&format!("fetch file: {path:?}"));
-    ensure_can_fetch_file_inner(path, Arc::clone(&execution_context.call_chain))
+    let result = ensure_can_fetch_file_inner(
+        path,
+        Arc::clone(execution_context.call_chain()),
+        execution_context.rig_session_options().explain_permissions,
+    );
+    audit_host_call(execution_context, AuditedCall::File { path }, &result);
+    super::record_permission_denial(execution_context, &result);
+    result
 }
 
 fn ensure_can_fetch_file_inner(
     path: &Path,
     call_chain: Arc<CallChain<'_>>,
+    explain_permissions: bool,
 ) -> Result<(), ComponentError> {
-    let is_allowed = slipway_engine::ensure_permissions(Arc::clone(&call_chain), |permissions| {
-        fn matches(path: &Path, permission: &Permission) -> bool {
-            match permission {
-                Permission::All => true,
-                Permission::Files(permission) => permission.matches(path),
-                _ => false,
-            }
-        }
-
-        for permission in permissions.deny {
-            if matches(path, permission) {
-                super::warn_deny_permission_triggered(permission);
-                return false;
-            }
-        }
-
-        for permission in permissions.allow {
-            if matches(path, permission) {
-                return true;
-            }
-        }
-
-        false
-    });
-
-    if !is_allowed {
+    let check = |permissions: &Permissions| {
+        super::check_level(permissions, |permission| match permission {
+            Permission::All => true,
+            Permission::Files(permission) => permission.matches(path),
+            _ => false,
+        })
+    };
+
+    if !super::is_allowed(&call_chain, &check) {
         let message = format!(
             "{} does not have permission to fetch file \"{:?}\"",
             call_chain.rig_or_component_handle_trail_error_prefix(),
             path
         );
-        return Err(super::create_permission_error(message, &call_chain));
+        return Err(super::create_permission_error(
+            message,
+            &call_chain,
+            explain_permissions,
+            &check,
+        ));
     }
 
     Ok(())
@@ -68,7 +65,7 @@ mod test {
         let handle = CH.get_or_init(|| ch("test"));
         let call_chain = Arc::new(CallChain::new_for_component(handle, permissions));
         assert_eq!(
-            ensure_can_fetch_file_inner(path, call_chain.clone()).is_ok(),
+            ensure_can_fetch_file_inner(path, call_chain.clone(), false).is_ok(),
             expected
         );
     }
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use slipway_engine::{CallChain, ComponentExecutionContext, Permission};
+use slipway_engine::{CallChain, ComponentExecutionContext, Permission, Permissions};
 
 use crate::{ComponentError, permissions::log_permissions_check};
 
@@ -9,45 +9,38 @@ pub fn ensure_can_query_font(
     execution_context: &ComponentExecutionContext,
 ) -> Result<(), ComponentError> {
     log_permissions_check(&format!("query font: {query}"));
-    ensure_can_query_font_inner(query, Arc::clone(&execution_context.call_chain))
+    ensure_can_query_font_inner(
+        query,
+        Arc::clone(execution_context.call_chain()),
+        execution_context.rig_session_options().explain_permissions,
+    )
 }
 
 fn ensure_can_query_font_inner(
     query: &str,
     call_chain: Arc<CallChain<'_>>,
+    explain_permissions: bool,
 ) -> Result<(), ComponentError> {
-    let is_allowed = slipway_engine::ensure_permissions(Arc::clone(&call_chain), |permissions| {
-        fn matches(query: &str, permission: &Permission) -> bool {
-            match permission {
-                Permission::All => true,
-                Permission::Fonts(permission) => permission.matches(query),
-                _ => false,
-            }
-        }
-
-        for permission in permissions.deny {
-            if matches(query, permission) {
-                super::warn_deny_permission_triggered(permission);
-                return false;
-            }
-        }
-
-        for permission in permissions.allow {
-            if matches(query, permission) {
-                return true;
-            }
-        }
-
-        false
-    });
-
-    if !is_allowed {
+    let check = |permissions: &Permissions| {
+        super::check_level(permissions, |permission| match permission {
+            Permission::All => true,
+            Permission::Fonts(permission) => permission.matches(query),
+            _ => false,
+        })
+    };
+
+    if !super::is_allowed(&call_chain, &check) {
         let message = format!(
             "{} does not have permission to perform font query \"{}\"",
             call_chain.rig_or_component_handle_trail_error_prefix(),
             query
         );
-        return Err(super::create_permission_error(message, &call_chain));
+        return Err(super::create_permission_error(
+            message,
+            &call_chain,
+            explain_permissions,
+            &check,
+        ));
     }
 
     Ok(())
@@ -67,7 +60,7 @@ mod test {
         let handle = CH.get_or_init(|| ch("test"));
         let call_chain = Arc::new(CallChain::new_for_component(handle, permissions));
         assert_eq!(
-            ensure_can_query_font_inner(query, call_chain.clone()).is_ok(),
+            ensure_can_query_font_inner(query, call_chain.clone(), false).is_ok(),
             expected
         );
     }
@@ -1,53 +1,55 @@
 use std::sync::Arc;
 
+use super::audit::{AuditedCall, audit_host_call};
 use crate::{ComponentError, permissions::log_permissions_check};
-use slipway_engine::{CallChain, ComponentExecutionContext, Permission};
+use slipway_engine::{CallChain, ComponentExecutionContext, Permission, Permissions};
 use url::Url;
 
 pub fn ensure_can_fetch_url(
+    method: &str,
     url: &Url,
     execution_context: &ComponentExecutionContext,
 ) -> Result<(), ComponentError> {
     log_permissions_check(&format!("fetch URL: {url}"));
-    ensure_can_fetch_url_inner(url, Arc::clone(&execution_context.call_chain))
+    let result = ensure_can_fetch_url_inner(
+        url,
+        Arc::clone(execution_context.call_chain()),
+        execution_context.rig_session_options().explain_permissions,
+    );
+    audit_host_call(
+        execution_context,
+        AuditedCall::Fetch { method, url },
+        &result,
+    );
+    super::record_permission_denial(execution_context, &result);
+    result
 }
 
 fn ensure_can_fetch_url_inner(
     url: &Url,
     call_chain: Arc<CallChain<'_>>,
+    explain_permissions: bool,
 ) -> Result<(), ComponentError> {
-    let is_allowed = slipway_engine::ensure_permissions(Arc::clone(&call_chain), |permissions| {
-        fn matches(url: &Url, permission: &Permission) -> bool {
-            match permission {
-                Permission::All => true,
-                Permission::Http(permission) => permission.matches(url),
-                _ => false,
-            }
-        }
-
-        for permission in permissions.deny {
-            if matches(url, permission) {
-                super::warn_deny_permission_triggered(permission);
-                return false;
-            }
-        }
-
-        for permission in permissions.allow {
-            if matches(url, permission) {
-                return true;
-            }
-        }
-
-        false
-    });
-
-    if !is_allowed {
+    let check = |permissions: &Permissions| {
+        super::check_level(permissions, |permission| match permission {
+            Permission::All => true,
+            Permission::Http(permission) => permission.matches(url),
+            _ => false,
+        })
+    };
+
+    if !super::is_allowed(&call_chain, &check) {
         let message = format!(
             "{} does not have permission to fetch url \"{}\"",
             call_chain.rig_or_component_handle_trail_error_prefix(),
             url
         );
-        return Err(super::create_permission_error(message, &call_chain));
+        return Err(super::create_permission_error(
+            message,
+            &call_chain,
+            explain_permissions,
+            &check,
+        ));
     }
 
     Ok(())
@@ -67,7 +69,7 @@ mod test {
         let handle = CH.get_or_init(|| ch("test"));
         let call_chain = Arc::new(CallChain::new_for_component(handle, permissions));
         assert_eq!(
-            ensure_can_fetch_url_inner(&url, call_chain.clone()).is_ok(),
+            ensure_can_fetch_url_inner(&url, call_chain.clone(), false).is_ok(),
             expected
         );
     }
@@ -1,5 +1,7 @@
+mod audit;
 mod component;
 mod env;
+mod explain;
 mod file_fetch;
 mod font;
 mod http_fetch;
@@ -11,7 +13,11 @@ pub use file_fetch::ensure_can_fetch_file;
 pub use font::ensure_can_query_font;
 pub use http_fetch::ensure_can_fetch_url;
 use slipway_engine::CallChain;
+use slipway_engine::ComponentExecutionContext;
 use slipway_engine::Permission;
+use slipway_engine::PermissionDenial;
+use slipway_engine::Permissions;
+use std::sync::Arc;
 use tracing::Level;
 use tracing::debug;
 use tracing::span;
@@ -19,17 +25,93 @@ use tracing::warn;
 
 use crate::ComponentError;
 
+/// The result of checking a request against the permissions at one level of the call chain.
+#[derive(Debug, Clone)]
+enum LevelOutcome {
+    /// A `deny` permission matched the request.
+    Denied(Permission),
+
+    /// An `allow` permission matched the request, and no `deny` permission did.
+    Allowed(Permission),
+
+    /// Neither an `allow` nor a `deny` permission matched the request.
+    NotAllowed,
+
+    /// The level doesn't restrict this kind of request.
+    Unrestricted,
+}
+
+impl LevelOutcome {
+    fn is_allowed(&self) -> bool {
+        matches!(self, LevelOutcome::Allowed(_) | LevelOutcome::Unrestricted)
+    }
+}
+
+/// Checks the request against the permissions at one level of the call chain,
+/// where `matches` returns whether a permission applies to the request.
+/// Any matching `deny` permission takes precedence over the `allow` permissions.
+fn check_level(permissions: &Permissions, matches: impl Fn(&Permission) -> bool) -> LevelOutcome {
+    if let Some(permission) = permissions.deny.iter().find(|p| matches(p)) {
+        return LevelOutcome::Denied(permission.clone());
+    }
+
+    match permissions.allow.iter().find(|p| matches(p)) {
+        Some(permission) => LevelOutcome::Allowed(permission.clone()),
+        None => LevelOutcome::NotAllowed,
+    }
+}
+
+/// Returns whether the check allows the request at every level of the call chain.
+fn is_allowed(
+    call_chain: &Arc<CallChain<'_>>,
+    check: &impl Fn(&Permissions) -> LevelOutcome,
+) -> bool {
+    slipway_engine::ensure_permissions(Arc::clone(call_chain), |permissions| {
+        let outcome = check(permissions);
+        if let LevelOutcome::Denied(permission) = &outcome {
+            warn_deny_permission_triggered(permission);
+        }
+        outcome.is_allowed()
+    })
+}
+
 fn warn_deny_permission_triggered(permission: &Permission) {
     warn!("Deny permission triggered: {:?}", permission);
 }
 
-fn create_permission_error(message: String, call_chain: &CallChain<'_>) -> ComponentError {
+/// Creates the error returned when a permission check fails. If `explain_permissions`
+/// is set the error describes how each level of the call chain resolved the request,
+/// otherwise it contains the permissions of each level.
+fn create_permission_error(
+    message: String,
+    call_chain: &CallChain<'_>,
+    explain_permissions: bool,
+    check: &impl Fn(&Permissions) -> LevelOutcome,
+) -> ComponentError {
     let permissions = format!("{:?}", call_chain.permission_trail());
     warn!(message);
     debug!(permissions);
-    ComponentError {
-        message,
-        inner: vec![permissions],
+
+    let inner = if explain_permissions {
+        explain::explain_permissions(call_chain, check)
+    } else {
+        vec![permissions]
+    };
+
+    ComponentError { message, inner }
+}
+
+/// Records a failed permission check on the execution context, so that the
+/// component fails with a permission error if it doesn't handle the denial.
+fn record_permission_denial(
+    execution_context: &ComponentExecutionContext,
+    result: &Result<(), ComponentError>,
+) {
+    if let Err(e) = result {
+        execution_context.record_permission_denial(PermissionDenial {
+            message: e.message.clone(),
+            inner: e.inner.clone(),
+        });
     }
 }
 
@@ -1,19 +1,131 @@
+mod strip_ansi;
 pub mod to_view_model;
 mod write_rig_graph;
+pub mod write_rig_graph_json;
 
-use std::{io::Write, path::Path};
+use std::{io::Write, path::Path, str::FromStr};
 
 use slipway_engine::{Immutable, RigExecutionState};
+use tracing::warn;
 
-use crate::render_state::to_view_model::{RigExecutionStateViewModel, to_view_model};
+pub use write_rig_graph::utils::format_bytes;
+
+use crate::render_state::{
+    strip_ansi::StripAnsiWriter,
+    to_view_model::{RigExecutionStateViewModel, to_view_model},
+};
+
+/// The number of characters of each hash rendered by default.
+pub const DEFAULT_HASH_RENDER_CHAR_COUNT: usize = 8;
+
+/// The number of characters in a fully rendered hash.
+pub const FULL_HASH_CHAR_COUNT: usize = 64;
+
+/// Options controlling how the rig state is rendered to the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderStateOptions {
+    pub hash_chars: usize,
+
+    /// Whether to include color and style escape codes.
+    /// When false the graph is written as plain text.
+    pub color: bool,
+
+    /// The units used when displaying input and output sizes.
+    pub byte_units: ByteUnits,
+}
+
+impl RenderStateOptions {
+    /// Creates options rendering the given number of characters of each hash.
+    /// Values longer than the full hash are clamped to the full hash length.
+    pub fn with_hash_chars(hash_chars: usize) -> Self {
+        if hash_chars > FULL_HASH_CHAR_COUNT {
+            warn!(
+                "Hash render length of {} exceeds the full hash length, using {} instead.",
+                hash_chars, FULL_HASH_CHAR_COUNT
+            );
+        }
+
+        Self {
+            hash_chars: hash_chars.min(FULL_HASH_CHAR_COUNT),
+            ..Default::default()
+        }
+    }
+
+    /// Sets whether to include color and style escape codes.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the units used when displaying input and output sizes.
+    pub fn byte_units(mut self, byte_units: ByteUnits) -> Self {
+        self.byte_units = byte_units;
+        self
+    }
+}
+
+impl Default for RenderStateOptions {
+    fn default() -> Self {
+        Self {
+            hash_chars: DEFAULT_HASH_RENDER_CHAR_COUNT,
+            color: true,
+            byte_units: ByteUnits::default(),
+        }
+    }
+}
+
+/// The units used when displaying byte sizes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ByteUnits {
+    /// Multiples of 1024 with short lowercase labels, such as `kb` and `mb`.
+    #[default]
+    Short,
+
+    /// Multiples of 1024 with IEC labels, such as `KiB` and `MiB`.
+    Binary,
+
+    /// Multiples of 1000 with SI labels, such as `kB` and `MB`.
+    Decimal,
+}
+
+impl FromStr for ByteUnits {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "short" => Ok(ByteUnits::Short),
+            "binary" => Ok(ByteUnits::Binary),
+            "decimal" => Ok(ByteUnits::Decimal),
+            _ => Err(format!(
+                "Unknown byte units \"{s}\", expected one of: short, binary, decimal"
+            )),
+        }
+    }
+}
 
 pub fn write_state<'state, W: Write, TError: From<std::io::Error>>(
     w: &mut W,
     state: &'state RigExecutionState<'_, '_>,
+    options: RenderStateOptions,
 ) -> Result<RigExecutionStateViewModel<'state>, TError> {
     let view_model = to_view_model(state);
     writeln!(w)?;
-    write_rig_graph::write_rig_graph(w, &view_model)?;
+    if options.color {
+        write_rig_graph::write_rig_graph(w, &view_model, options)?;
+    } else {
+        write_rig_graph::write_rig_graph(&mut StripAnsiWriter::new(w), &view_model, options)?;
+    }
+    writeln!(w)?;
+    Ok(view_model)
+}
+
+/// Writes the rig graph as JSON, for consumption by other tools rather than the terminal.
+pub fn write_state_json<'state, W: Write, TError: From<std::io::Error>>(
+    w: &mut W,
+    state: &'state RigExecutionState<'_, '_>,
+) -> Result<RigExecutionStateViewModel<'state>, TError> {
+    let view_model = to_view_model(state);
+    write_rig_graph_json::write_rig_graph_json(w, &view_model)?;
     writeln!(w)?;
     Ok(view_model)
 }
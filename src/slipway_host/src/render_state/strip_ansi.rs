@@ -0,0 +1,90 @@
+use std::io::Write;
+
+const ESCAPE: u8 = 0x1b;
+
+/// A writer which removes ANSI escape sequences, such as colors and styles,
+/// before passing the remaining text on to the inner writer.
+pub(crate) struct StripAnsiWriter<'w, W: Write> {
+    inner: &'w mut W,
+    state: EscapeState,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EscapeState {
+    /// Not currently in an escape sequence.
+    Text,
+
+    /// The escape character has been seen.
+    Escape,
+
+    /// Inside a Control Sequence Introducer (`ESC [`) sequence.
+    ControlSequence,
+}
+
+impl<'w, W: Write> StripAnsiWriter<'w, W> {
+    pub fn new(inner: &'w mut W) -> Self {
+        Self {
+            inner,
+            state: EscapeState::Text,
+        }
+    }
+}
+
+impl<W: Write> Write for StripAnsiWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Escape sequences may be split across writes, so we track the state between calls.
+        let mut text = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            self.state = match (self.state, byte) {
+                (EscapeState::Text, ESCAPE) => EscapeState::Escape,
+                (EscapeState::Text, _) => {
+                    text.push(byte);
+                    EscapeState::Text
+                }
+                (EscapeState::Escape, b'[') => EscapeState::ControlSequence,
+                (EscapeState::Escape, _) => EscapeState::Text,
+                // Control sequences are terminated by a byte in the range `@` to `~`.
+                (EscapeState::ControlSequence, 0x40..=0x7e) => EscapeState::Text,
+                (EscapeState::ControlSequence, _) => EscapeState::ControlSequence,
+            };
+        }
+
+        self.inner.write_all(&text)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use termion::{color, style};
+
+    use super::*;
+
+    #[test]
+    fn it_should_strip_colors_and_styles() {
+        let mut buffer = Vec::new();
+        let mut w = StripAnsiWriter::new(&mut buffer);
+
+        write!(
+            w,
+            "├─{}◩{} {}b{}ird",
+            color::Fg(color::Yellow),
+            color::Fg(color::Reset),
+            style::Underline,
+            style::Reset,
+        )
+        .unwrap();
+
+        // Split an escape sequence across writes.
+        let red = color::Fg(color::Red).to_string();
+        let (start, end) = red.split_at(3);
+        write!(w, " {}", start).unwrap();
+        write!(w, "{}!", end).unwrap();
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), "├─◩ bird !");
+    }
+}
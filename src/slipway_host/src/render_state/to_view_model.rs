@@ -15,13 +15,13 @@ where
 
     let components = &state.component_states;
 
-    for (group_index, group) in state.component_groups.iter().enumerate() {
+    for (group_index, group) in state.component_groups().iter().enumerate() {
         let mut group_view_model = ComponentGroupViewModel {
             components: Vec::new(),
         };
 
         let mut row_index = 0;
-        for &handle in state.valid_execution_order.iter() {
+        for &handle in state.execution_order().iter() {
             if !group.contains(handle) {
                 continue;
             }
@@ -87,7 +87,7 @@ pub fn to_shortcuts<'rig>(
     state: &RigExecutionState<'rig, '_>,
 ) -> HashMap<String, &'rig ComponentHandle> {
     let mut shortcuts = HashMap::new();
-    for &handle in state.valid_execution_order.iter() {
+    for &handle in state.execution_order().iter() {
         to_shortcut(handle, &mut shortcuts);
     }
     shortcuts
@@ -1,16 +1,17 @@
 use std::io::Write;
 
+use slipway_engine::NestedRunMetadata;
 use termion::{color, style};
 
-use crate::render_state::to_view_model::{
-    ComponentGroupViewModel, ComponentViewModel, RigExecutionStateViewModel,
+use crate::render_state::{
+    ByteUnits, RenderStateOptions,
+    to_view_model::{ComponentGroupViewModel, ComponentViewModel, RigExecutionStateViewModel},
 };
 
 use utils::{format_bytes, skip_first_n_chars};
 
-mod utils;
+pub(super) mod utils;
 
-const HASH_RENDER_CHAR_COUNT: usize = 8;
 const COLUMN_PADDING: &str = "  ";
 const COLUMN_CHAR: char = '┆';
 
@@ -29,10 +30,13 @@ const COLUMN_CHAR: char = '┆';
 pub(crate) fn write_rig_graph<W: Write, TError: From<std::io::Error>>(
     w: &mut W,
     view_model: &RigExecutionStateViewModel<'_>,
+    options: RenderStateOptions,
 ) -> Result<(), TError> {
+    let hash_chars = options.hash_chars;
+    let byte_units = options.byte_units;
     let max_component_state_row_length = get_max_component_state_row_length(view_model);
-    let max_input_size_string_length = get_max_input_size_string_length(view_model);
-    let max_output_size_string_length = get_max_output_size_string_length(view_model);
+    let max_input_size_string_length = get_max_input_size_string_length(view_model, byte_units);
+    let max_output_size_string_length = get_max_output_size_string_length(view_model, byte_units);
     let max_call_duration_string_length = get_max_call_duration_string_length(view_model);
 
     for group in view_model.groups.iter() {
@@ -51,7 +55,7 @@ pub(crate) fn write_rig_graph<W: Write, TError: From<std::io::Error>>(
             write!(w, "{}", COLUMN_CHAR)?;
             write!(w, "{}", COLUMN_PADDING)?;
 
-            write_metadata(w, component, MetadataType::Hashes)?;
+            write_metadata(w, component, MetadataType::Hashes { hash_chars })?;
 
             write!(w, "{}", COLUMN_PADDING)?;
             write!(w, "{}", COLUMN_CHAR)?;
@@ -63,6 +67,7 @@ pub(crate) fn write_rig_graph<W: Write, TError: From<std::io::Error>>(
                 MetadataType::Sizes {
                     max_input_size_string_length,
                     max_output_size_string_length,
+                    byte_units,
                 },
             )?;
 
@@ -90,7 +95,10 @@ fn get_max_component_state_row_length(view_model: &RigExecutionStateViewModel<'_
 }
 
 /// Returns the length of the longest input size string.
-fn get_max_input_size_string_length(view_model: &RigExecutionStateViewModel<'_>) -> usize {
+fn get_max_input_size_string_length(
+    view_model: &RigExecutionStateViewModel<'_>,
+    byte_units: ByteUnits,
+) -> usize {
     view_model
         .groups
         .iter()
@@ -100,7 +108,7 @@ fn get_max_input_size_string_length(view_model: &RigExecutionStateViewModel<'_>)
                     .execution_input
                     .as_ref()
                     .map(|i| {
-                        format_bytes(i.json_metadata.serialized.len())
+                        format_bytes(i.json_metadata.serialized.len(), byte_units)
                             .chars()
                             .count()
                     })
@@ -112,7 +120,10 @@ fn get_max_input_size_string_length(view_model: &RigExecutionStateViewModel<'_>)
 }
 
 /// Returns the length of the longest output size string.
-fn get_max_output_size_string_length(view_model: &RigExecutionStateViewModel<'_>) -> usize {
+fn get_max_output_size_string_length(
+    view_model: &RigExecutionStateViewModel<'_>,
+    byte_units: ByteUnits,
+) -> usize {
     view_model
         .groups
         .iter()
@@ -122,7 +133,7 @@ fn get_max_output_size_string_length(view_model: &RigExecutionStateViewModel<'_>
                     .output_override
                     .as_ref()
                     .map(|i| {
-                        format_bytes(i.json_metadata.serialized.len())
+                        format_bytes(i.json_metadata.serialized.len(), byte_units)
                             .chars()
                             .count()
                     })
@@ -131,7 +142,7 @@ fn get_max_output_size_string_length(view_model: &RigExecutionStateViewModel<'_>
                             .execution_output
                             .as_ref()
                             .map(|i| {
-                                format_bytes(i.json_metadata.serialized.len())
+                                format_bytes(i.json_metadata.serialized.len(), byte_units)
                                     .chars()
                                     .count()
                             })
@@ -174,6 +185,7 @@ fn write_component_state<F: Write, TError: From<std::io::Error>>(
     const INPUT_NO_OUTPUT: char = '◩';
     const NO_INPUT_OUTPUT: char = '◪';
     const INPUT_OUTPUT: char = '■';
+    const SKIPPED: char = '⊘';
 
     for i in 0..component.row_index {
         let i_component = &group.components[i];
@@ -222,6 +234,7 @@ fn write_component_state<F: Write, TError: From<std::io::Error>>(
         }
     }
     let component_color = match component.state.output() {
+        _ if component.state.execution_skipped => ComponentColors::Skipped,
         Some(_) => match component.state.execution_input {
             Some(_) => ComponentColors::HasInputAndOutput,
             None => ComponentColors::HasOutput,
@@ -236,6 +249,7 @@ fn write_component_state<F: Write, TError: From<std::io::Error>>(
         ComponentColors::HasInput => write!(f, "{}", INPUT_NO_OUTPUT)?,
         ComponentColors::HasOutput => write!(f, "{}", NO_INPUT_OUTPUT)?,
         ComponentColors::HasInputAndOutput => write!(f, "{}", INPUT_OUTPUT)?,
+        ComponentColors::Skipped => write!(f, "{}", SKIPPED)?,
         _ => write!(f, "{}", NO_INPUT_NO_OUTPUT)?,
     }
     write!(f, "{}", color::Fg(color::Reset))?;
@@ -252,10 +266,13 @@ fn write_component_state<F: Write, TError: From<std::io::Error>>(
 }
 
 enum MetadataType {
-    Hashes,
+    Hashes {
+        hash_chars: usize,
+    },
     Sizes {
         max_input_size_string_length: usize,
         max_output_size_string_length: usize,
+        byte_units: ByteUnits,
     },
 }
 
@@ -270,21 +287,21 @@ fn write_metadata<F: Write, TError: From<std::io::Error>>(
 
     if let Some(input) = &component.state.execution_input {
         let should_underline = component.state.input_override.is_some();
+
+        // Inputs which had defaults applied from the component's input schema are italicized.
+        let should_italicize = !input.defaulted.is_empty();
         write!(f, "{}", color::Fg(color::Blue))?;
+        if should_italicize {
+            write!(f, "{}", style::Italic)?;
+        }
 
         match metadata_type {
-            MetadataType::Hashes => {
+            MetadataType::Hashes { hash_chars } => {
                 let input_hash_string =
-                    format!("{}", input.json_metadata.hash)[..HASH_RENDER_CHAR_COUNT].to_string();
+                    format!("{}", input.json_metadata.hash)[..hash_chars].to_string();
 
                 if should_underline {
-                    write!(
-                        f,
-                        "{}{}{}",
-                        style::Underline,
-                        input_hash_string,
-                        style::Reset
-                    )?;
+                    write!(f, "{}{}", style::Underline, input_hash_string)?;
                 } else {
                     write!(f, "{}", input_hash_string)?
                 }
@@ -292,31 +309,31 @@ fn write_metadata<F: Write, TError: From<std::io::Error>>(
             MetadataType::Sizes {
                 max_input_size_string_length,
                 max_output_size_string_length: _,
+                byte_units,
             } => {
-                let input_size_string = format_bytes(input.json_metadata.serialized.len());
+                let input_size_string =
+                    format_bytes(input.json_metadata.serialized.len(), byte_units);
                 let padding_required = max_input_size_string_length - input_size_string.len();
                 write!(f, "{:padding_required$}", "")?;
                 if should_underline {
-                    write!(
-                        f,
-                        "{}{}{}",
-                        style::Underline,
-                        input_size_string,
-                        style::Reset
-                    )?;
+                    write!(f, "{}{}", style::Underline, input_size_string)?;
                 } else {
                     write!(f, "{}", input_size_string)?;
                 }
             }
         }
 
+        if should_underline || should_italicize {
+            write!(f, "{}", style::Reset)?;
+        }
         write!(f, "{}", color::Fg(color::Reset))?;
     } else {
         match metadata_type {
-            MetadataType::Hashes => write!(f, "{:HASH_RENDER_CHAR_COUNT$}", "")?,
+            MetadataType::Hashes { hash_chars } => write!(f, "{:hash_chars$}", "")?,
             MetadataType::Sizes {
                 max_input_size_string_length,
                 max_output_size_string_length: _,
+                byte_units: _,
             } => {
                 write!(f, "{:max_input_size_string_length$}", "")?;
             }
@@ -361,8 +378,8 @@ fn write_metadata<F: Write, TError: From<std::io::Error>>(
         color.write_foreground(f)?;
 
         match metadata_type {
-            MetadataType::Hashes => {
-                let output_hash_string = &format!("{}", hash)[..HASH_RENDER_CHAR_COUNT];
+            MetadataType::Hashes { hash_chars } => {
+                let output_hash_string = &format!("{}", hash)[..hash_chars];
                 if should_underline {
                     write!(
                         f,
@@ -378,8 +395,9 @@ fn write_metadata<F: Write, TError: From<std::io::Error>>(
             MetadataType::Sizes {
                 max_input_size_string_length: _,
                 max_output_size_string_length,
+                byte_units,
             } => {
-                let size_string = format_bytes(size);
+                let size_string = format_bytes(size, byte_units);
                 let padding_required = max_output_size_string_length - size_string.len();
                 write!(f, "{:padding_required$}", "")?;
                 if should_underline {
@@ -395,10 +413,11 @@ fn write_metadata<F: Write, TError: From<std::io::Error>>(
         write!(f, " {} ", NO_OUTPUT)?;
 
         match metadata_type {
-            MetadataType::Hashes => write!(f, "{:HASH_RENDER_CHAR_COUNT$}", "")?,
+            MetadataType::Hashes { hash_chars } => write!(f, "{:hash_chars$}", "")?,
             MetadataType::Sizes {
                 max_input_size_string_length: _,
                 max_output_size_string_length,
+                byte_units: _,
             } => {
                 write!(f, "{:max_output_size_string_length$}", "")?;
             }
@@ -428,17 +447,53 @@ fn write_durations<F: Write, TError: From<std::io::Error>>(
             overall_duration_string
         )?;
         write!(f, "{}", color::Fg(color::Reset))?;
+
+        if let Some(nested) = output.run_metadata.nested.as_ref() {
+            write_nested_durations(f, nested)?;
+        }
     };
 
     Ok(())
 }
 
+/// Writes the durations of the components within a sub-rig (e.g. a Fragment),
+/// slowest first, so it is easy to see where the time was spent.
+fn write_nested_durations<F: Write, TError: From<std::io::Error>>(
+    f: &mut F,
+    nested: &[NestedRunMetadata],
+) -> Result<(), TError> {
+    if nested.is_empty() {
+        return Ok(());
+    }
+
+    let mut nested = nested.iter().collect::<Vec<_>>();
+    nested.sort_by(|a, b| {
+        b.metadata
+            .overall_duration()
+            .cmp(&a.metadata.overall_duration())
+    });
+
+    write!(f, "{}", COLUMN_PADDING)?;
+    write!(f, "{}", COLUMN_CHAR)?;
+    write!(f, "{}", COLUMN_PADDING)?;
+    write!(f, "{}", color::Fg(color::LightBlack))?;
+    let nested_strings = nested
+        .iter()
+        .map(|n| format!("{} {:.0?}", n.handle, n.metadata.overall_duration()))
+        .collect::<Vec<_>>();
+    write!(f, "{}", nested_strings.join(", "))?;
+    write!(f, "{}", color::Fg(color::Reset))?;
+
+    Ok(())
+}
+
 #[derive(Debug)]
 enum ComponentColors {
     Default,
     HasOutput,
     HasInput,
     HasInputAndOutput,
+    Skipped,
     HashesMatch,
     HashesDiffer,
     HashesIgnored,
@@ -451,6 +506,7 @@ impl ComponentColors {
             ComponentColors::HasOutput => write!(f, "{}", color::Fg(color::Green)),
             ComponentColors::HasInput => write!(f, "{}", color::Fg(color::Yellow)),
             ComponentColors::HasInputAndOutput => write!(f, "{}", color::Fg(color::Green)),
+            ComponentColors::Skipped => write!(f, "{}", color::Fg(color::LightBlack)),
             ComponentColors::HashesMatch => write!(f, "{}", color::Fg(color::Green)),
             ComponentColors::HashesDiffer => write!(f, "{}", color::Fg(color::Red)),
             ComponentColors::HashesIgnored => write!(f, "{}", color::Fg(color::Blue)),
@@ -517,7 +573,7 @@ mod tests {
         let view_model = to_view_model(&state);
 
         let mut buffer = Vec::new();
-        write_rig_graph::<_, std::io::Error>(&mut buffer, &view_model).unwrap();
+        write_rig_graph::<_, std::io::Error>(&mut buffer, &view_model, Default::default()).unwrap();
         let buffer_string = String::from_utf8(buffer).unwrap();
         println!("{}", buffer_string);
 
@@ -651,12 +707,16 @@ mod tests {
             prepare_component_duration: Duration::from_secs(2),
             call_duration: Duration::from_secs(3),
             process_output_duration: Duration::from_secs(4),
+            nested: None,
+            memoized_from: None,
         };
         let metadata_long = RunMetadata {
             prepare_input_duration: Duration::from_secs(10),
             prepare_component_duration: Duration::from_secs(20),
             call_duration: Duration::from_secs(30),
             process_output_duration: Duration::from_secs(40),
+            nested: None,
+            memoized_from: None,
         };
 
         state = state
@@ -722,7 +782,7 @@ mod tests {
         let view_model = to_view_model(&state);
 
         let mut buffer = Vec::new();
-        write_rig_graph::<_, std::io::Error>(&mut buffer, &view_model).unwrap();
+        write_rig_graph::<_, std::io::Error>(&mut buffer, &view_model, Default::default()).unwrap();
         let buffer_string = String::from_utf8(buffer).unwrap();
         println!("{}", buffer_string);
 
@@ -871,4 +931,110 @@ mod tests {
             ),
         );
     }
+
+    #[slipway_test_async]
+    async fn it_should_write_nested_durations_slowest_first() {
+        let rig = Rig::for_test(Rigging {
+            components: [ComponentRigging::for_test("fragment", None)]
+                .into_iter()
+                .collect(),
+        });
+
+        let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+        let rig_session = RigSession::new_for_test(rig, &component_cache);
+        let mut state = rig_session.initialize().unwrap();
+
+        fn metadata_of(secs: u64) -> RunMetadata {
+            RunMetadata {
+                call_duration: Duration::from_secs(secs),
+                ..Default::default()
+            }
+        }
+
+        state = state
+            .step(Instruction::SetOutput {
+                handle: ch("fragment"),
+                value: json!(0),
+                metadata: RunMetadata {
+                    nested: Some(vec![
+                        NestedRunMetadata {
+                            handle: ch("input"),
+                            metadata: metadata_of(1),
+                        },
+                        NestedRunMetadata {
+                            handle: ch("output"),
+                            metadata: metadata_of(5),
+                        },
+                    ]),
+                    ..metadata_of(7)
+                },
+            })
+            .unwrap();
+
+        let view_model = to_view_model(&state);
+
+        let mut buffer = Vec::new();
+        write_rig_graph::<_, std::io::Error>(&mut buffer, &view_model, Default::default()).unwrap();
+        let buffer_string = String::from_utf8(buffer).unwrap();
+        println!("{}", buffer_string);
+
+        let lines = buffer_string.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with(&format!(
+            "{}7s of 7s{}  ┆  {}output 5s, input 1s{}",
+            color::Fg(color::LightBlack),
+            color::Fg(color::Reset),
+            color::Fg(color::LightBlack),
+            color::Fg(color::Reset),
+        )));
+    }
+
+    #[slipway_test_async]
+    async fn it_should_render_configured_hash_length() {
+        let rig = Rig::for_test(Rigging {
+            components: [ComponentRigging::for_test("ant", None)]
+                .into_iter()
+                .collect(),
+        });
+
+        let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+        let rig_session = RigSession::new_for_test(rig, &component_cache);
+        let mut state = rig_session.initialize().unwrap();
+
+        state = state
+            .step(Instruction::SetOutput {
+                handle: ch("ant"),
+                value: json!(0),
+                metadata: RunMetadata {
+                    prepare_input_duration: Duration::from_secs(1),
+                    prepare_component_duration: Duration::from_secs(2),
+                    call_duration: Duration::from_secs(3),
+                    process_output_duration: Duration::from_secs(4),
+                    nested: None,
+                    memoized_from: None,
+                },
+            })
+            .unwrap();
+
+        let view_model = to_view_model(&state);
+
+        let render = |options: RenderStateOptions| {
+            let mut buffer = Vec::new();
+            write_rig_graph::<_, std::io::Error>(&mut buffer, &view_model, options).unwrap();
+            String::from_utf8(buffer).unwrap()
+        };
+
+        let short = render(RenderStateOptions::with_hash_chars(12));
+        assert!(short.contains("44136fa355b3"));
+        assert!(!short.contains("44136fa355b36"));
+        assert!(short.contains("5feceb66ffc8"));
+
+        // Longer than the full hash, so should be clamped.
+        let options = RenderStateOptions::with_hash_chars(100);
+        assert_eq!(options.hash_chars, 64);
+
+        let full = render(options);
+        assert!(full.contains("44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"));
+    }
 }
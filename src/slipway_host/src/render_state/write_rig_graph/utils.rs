@@ -1,10 +1,17 @@
-pub(crate) fn format_bytes(bytes: usize) -> String {
-    let all_units = ["bytes", "kb", "mb", "gb", "tb"];
+use crate::render_state::ByteUnits;
+
+/// Formats a number of bytes as a human readable string, such as `1.5 kb`.
+pub fn format_bytes(bytes: usize, byte_units: ByteUnits) -> String {
+    let (all_units, divisor) = match byte_units {
+        ByteUnits::Short => (["bytes", "kb", "mb", "gb", "tb"], 1024.0),
+        ByteUnits::Binary => (["bytes", "KiB", "MiB", "GiB", "TiB"], 1024.0),
+        ByteUnits::Decimal => (["bytes", "kB", "MB", "GB", "TB"], 1000.0),
+    };
     let mut size = bytes as f64;
     let mut i = 0;
 
-    while size >= 1024.0 && i < all_units.len() - 1 {
-        size /= 1024.0;
+    while size >= divisor && i < all_units.len() - 1 {
+        size /= divisor;
         i += 1;
     }
 
@@ -24,17 +31,41 @@ pub(crate) fn format_bytes(bytes: usize) -> String {
 mod format_bytes_tests {
     use super::*;
 
+    fn format_short(bytes: usize) -> String {
+        format_bytes(bytes, ByteUnits::Short)
+    }
+
     #[test]
     fn it_should_convert_bytes_to_human_readable_string() {
-        assert_eq!(format_bytes(0), "0 bytes");
-        assert_eq!(format_bytes(1), "1 byte");
-        assert_eq!(format_bytes(10), "10 bytes");
-        assert_eq!(format_bytes(256), "256 bytes");
-        assert_eq!(format_bytes(1024), "1 kb");
-        assert_eq!(format_bytes(5632), "5.50 kb");
-        assert_eq!(format_bytes(1024 * 1024), "1 mb");
-        assert_eq!(format_bytes(1024 * 1024 * 1024), "1 gb");
-        assert_eq!(format_bytes(1024 * 1024 * 1024 * 1024), "1 tb");
+        assert_eq!(format_short(0), "0 bytes");
+        assert_eq!(format_short(1), "1 byte");
+        assert_eq!(format_short(10), "10 bytes");
+        assert_eq!(format_short(256), "256 bytes");
+        assert_eq!(format_short(1024), "1 kb");
+        assert_eq!(format_short(5632), "5.50 kb");
+        assert_eq!(format_short(1024 * 1024), "1 mb");
+        assert_eq!(format_short(1024 * 1024 * 1024), "1 gb");
+        assert_eq!(format_short(1024 * 1024 * 1024 * 1024), "1 tb");
+    }
+
+    #[test]
+    fn it_should_convert_bytes_to_binary_units() {
+        assert_eq!(format_bytes(1, ByteUnits::Binary), "1 byte");
+        assert_eq!(format_bytes(1023, ByteUnits::Binary), "1023 bytes");
+        assert_eq!(format_bytes(1024, ByteUnits::Binary), "1 KiB");
+        assert_eq!(format_bytes(5000, ByteUnits::Binary), "4.88 KiB");
+        assert_eq!(format_bytes(1023 * 1024, ByteUnits::Binary), "1023 KiB");
+        assert_eq!(format_bytes(1024 * 1024, ByteUnits::Binary), "1 MiB");
+    }
+
+    #[test]
+    fn it_should_convert_bytes_to_decimal_units() {
+        assert_eq!(format_bytes(999, ByteUnits::Decimal), "999 bytes");
+        assert_eq!(format_bytes(1000, ByteUnits::Decimal), "1 kB");
+        assert_eq!(format_bytes(1023, ByteUnits::Decimal), "1.02 kB");
+        assert_eq!(format_bytes(1024, ByteUnits::Decimal), "1.02 kB");
+        assert_eq!(format_bytes(5000, ByteUnits::Decimal), "5 kB");
+        assert_eq!(format_bytes(1_000_000, ByteUnits::Decimal), "1 MB");
     }
 }
 
@@ -0,0 +1,268 @@
+use std::{io::Write, time::Duration};
+
+use serde::Serialize;
+use slipway_engine::{ComponentHandle, JsonMetadata, RunMetadata};
+
+use crate::render_state::to_view_model::{ComponentViewModel, RigExecutionStateViewModel};
+
+/// A serializable form of the rig graph, containing the same information
+/// as the terminal rendering so that other front ends can render it.
+#[derive(Serialize)]
+pub struct RigGraphJson<'rig> {
+    pub groups: Vec<ComponentGroupJson<'rig>>,
+}
+
+#[derive(Serialize)]
+pub struct ComponentGroupJson<'rig> {
+    pub components: Vec<ComponentJson<'rig>>,
+}
+
+#[derive(Serialize)]
+pub struct ComponentJson<'rig> {
+    pub handle: &'rig ComponentHandle,
+    pub shortcut: String,
+    pub group_index: usize,
+    pub row_index: usize,
+    pub input_columns_indexes: Vec<usize>,
+    pub output_row_indexes: Vec<usize>,
+
+    /// Whether the component's skip condition was met, so it is not run.
+    pub skipped: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<ComponentInputJson>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<ComponentOutputJson>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub durations: Option<ComponentDurationsJson<'rig>>,
+}
+
+#[derive(Serialize)]
+pub struct ComponentInputJson {
+    pub hash: String,
+    pub size_bytes: usize,
+    pub is_override: bool,
+
+    /// The JSON pointers of the input properties which were populated from
+    /// defaults declared by the component's input schema.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub defaulted: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ComponentOutputJson {
+    pub hash: String,
+    pub size_bytes: usize,
+    pub is_override: bool,
+
+    /// The hash of the input the output was generated from, if the output
+    /// was generated by executing the component.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_hash_used: Option<String>,
+}
+
+/// Component durations in milliseconds.
+#[derive(Serialize)]
+pub struct ComponentDurationsJson<'rig> {
+    pub prepare_input_ms: f64,
+    pub prepare_component_ms: f64,
+    pub call_ms: f64,
+    pub process_output_ms: f64,
+    pub overall_ms: f64,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nested: Vec<NestedDurationJson<'rig>>,
+}
+
+#[derive(Serialize)]
+pub struct NestedDurationJson<'rig> {
+    pub handle: &'rig ComponentHandle,
+    pub overall_ms: f64,
+}
+
+pub fn to_rig_graph_json<'rig>(
+    view_model: &RigExecutionStateViewModel<'rig>,
+) -> RigGraphJson<'rig> {
+    RigGraphJson {
+        groups: view_model
+            .groups
+            .iter()
+            .map(|group| ComponentGroupJson {
+                components: group.components.iter().map(to_component_json).collect(),
+            })
+            .collect(),
+    }
+}
+
+pub(crate) fn write_rig_graph_json<W: Write, TError: From<std::io::Error>>(
+    w: &mut W,
+    view_model: &RigExecutionStateViewModel<'_>,
+) -> Result<(), TError> {
+    serde_json::to_writer_pretty(&mut *w, &to_rig_graph_json(view_model))
+        .map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+fn to_component_json<'rig>(component: &ComponentViewModel<'rig>) -> ComponentJson<'rig> {
+    let state = component.state;
+
+    let input = state
+        .execution_input
+        .as_ref()
+        .map(|input| ComponentInputJson {
+            hash: input.json_metadata.hash.to_string(),
+            size_bytes: input.json_metadata.serialized.len(),
+            is_override: state.input_override.is_some(),
+            defaulted: input.defaulted.clone(),
+        });
+
+    let output = match (&state.output_override, &state.execution_output) {
+        (Some(output_override), _) => {
+            Some(to_output_json(&output_override.json_metadata, true, None))
+        }
+        (None, Some(execution_output)) => Some(to_output_json(
+            &execution_output.json_metadata,
+            false,
+            Some(execution_output.input_hash_used.to_string()),
+        )),
+        (None, None) => None,
+    };
+
+    let durations = state
+        .execution_output
+        .as_ref()
+        .map(|output| to_durations_json(&output.run_metadata));
+
+    ComponentJson {
+        handle: component.handle,
+        shortcut: component.shortcut.clone(),
+        group_index: component.group_index,
+        row_index: component.row_index,
+        input_columns_indexes: component.input_columns_indexes.clone(),
+        output_row_indexes: component.output_row_indexes.clone(),
+        skipped: state.execution_skipped,
+        input,
+        output,
+        durations,
+    }
+}
+
+fn to_output_json(
+    json_metadata: &JsonMetadata,
+    is_override: bool,
+    input_hash_used: Option<String>,
+) -> ComponentOutputJson {
+    ComponentOutputJson {
+        hash: json_metadata.hash.to_string(),
+        size_bytes: json_metadata.serialized.len(),
+        is_override,
+        input_hash_used,
+    }
+}
+
+fn to_durations_json(run_metadata: &RunMetadata) -> ComponentDurationsJson<'_> {
+    ComponentDurationsJson {
+        prepare_input_ms: to_ms(run_metadata.prepare_input_duration),
+        prepare_component_ms: to_ms(run_metadata.prepare_component_duration),
+        call_ms: to_ms(run_metadata.call_duration),
+        process_output_ms: to_ms(run_metadata.process_output_duration),
+        overall_ms: to_ms(run_metadata.overall_duration()),
+        nested: run_metadata
+            .nested
+            .iter()
+            .flatten()
+            .map(|n| NestedDurationJson {
+                handle: &n.handle,
+                overall_ms: to_ms(n.metadata.overall_duration()),
+            })
+            .collect(),
+    }
+}
+
+fn to_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+    use serde_json::json;
+    use slipway_engine::{
+        BasicComponentCache, ComponentRigging, Instruction, Rig, RigSession, Rigging, utils::ch,
+    };
+
+    use crate::render_state::to_view_model::to_view_model;
+
+    use super::*;
+
+    #[slipway_test_async]
+    async fn it_should_serialize_rig_graph() {
+        let rig = Rig::for_test(Rigging {
+            components: [
+                ComponentRigging::for_test("ant", None),
+                ComponentRigging::for_test("bird", Some(json!({"a": "$$.ant"}))),
+                ComponentRigging::for_test("cat", Some(json!({"b": "$$.bird"}))),
+            ]
+            .into_iter()
+            .collect(),
+        });
+
+        let component_cache = BasicComponentCache::for_test_permissive(&rig).await;
+        let rig_session = RigSession::new_for_test(rig, &component_cache);
+        let mut state = rig_session.initialize().unwrap();
+
+        state = state
+            .step(Instruction::SetOutput {
+                handle: ch("ant"),
+                value: json!(0),
+                metadata: RunMetadata {
+                    prepare_input_duration: Duration::from_millis(1),
+                    prepare_component_duration: Duration::from_millis(2),
+                    call_duration: Duration::from_millis(3),
+                    process_output_duration: Duration::from_millis(4),
+                    nested: None,
+                    memoized_from: None,
+                },
+            })
+            .unwrap();
+
+        state = state
+            .step(Instruction::SetOutputOverride {
+                handle: ch("bird"),
+                value: json!({ "value": 1 }),
+            })
+            .unwrap();
+
+        let view_model = to_view_model(&state);
+
+        let mut buffer = Vec::new();
+        write_rig_graph_json::<_, std::io::Error>(&mut buffer, &view_model).unwrap();
+        let result: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+
+        let components = result["groups"][0]["components"].as_array().unwrap();
+        assert_eq!(components.len(), 3);
+
+        let ant = &components[0];
+        assert_eq!(ant["handle"], json!("ant"));
+        assert_eq!(ant["row_index"], json!(0));
+        assert_eq!(ant["output_row_indexes"], json!([1]));
+        assert_eq!(ant["skipped"], json!(false));
+        assert_eq!(ant["output"]["size_bytes"], json!(1));
+        assert_eq!(ant["output"]["is_override"], json!(false));
+        assert_eq!(ant["output"]["input_hash_used"], ant["input"]["hash"]);
+        assert_eq!(ant["durations"]["call_ms"], json!(3.0));
+        assert_eq!(ant["durations"]["overall_ms"], json!(10.0));
+
+        let bird = &components[1];
+        assert_eq!(bird["input_columns_indexes"], json!([0]));
+        assert_eq!(bird["output"]["is_override"], json!(true));
+        assert_eq!(bird["output"]["size_bytes"], json!(11));
+        assert!(bird.get("durations").is_none());
+
+        let cat = &components[2];
+        assert_eq!(cat["row_index"], json!(2));
+        assert!(cat.get("output").is_none());
+    }
+}
@@ -2,8 +2,9 @@ use std::sync::Arc;
 
 use slipway_engine::{
     CallChain, ComponentExecutionContext, ComponentHandle, ComponentRunner, Immutable, Instruction,
-    RigExecutionState, RigSession, RunComponentError, RunError,
-    errors::{ComponentLoadError, ComponentLoadErrorInner},
+    PermissionDenial, RigExecutionState, RigSession, RunComponentError, RunError,
+    deduplicate_memoized_components,
+    errors::{ComponentLoadError, ComponentLoadErrorInner, RigError},
     run_component,
 };
 use tracing::{Instrument, info_span};
@@ -57,6 +58,43 @@ pub async fn run_rig<'rig, 'cache, 'runners, THostError>(
     component_runners: &'runners [Box<dyn ComponentRunner>],
     call_chain: Arc<CallChain<'rig>>,
 ) -> Result<Immutable<RigExecutionState<'rig, 'cache>>, RunError<THostError>>
+where
+    'cache: 'rig,
+{
+    run_rig_with_options(
+        rig_session,
+        event_handler,
+        component_runners,
+        call_chain,
+        RunRigOptions::default(),
+    )
+    .await
+}
+
+/// Options which change how `run_rig_with_options` runs a rig.
+#[derive(Default)]
+pub struct RunRigOptions<'a> {
+    /// If set, only the components required to produce the output of this component
+    /// are run, so that components which don't contribute to its output are never run.
+    pub until: Option<&'a ComponentHandle>,
+
+    /// Outputs which are set as overrides before the rig runs,
+    /// so that the overridden components are not run.
+    pub output_overrides: Vec<(ComponentHandle, serde_json::Value)>,
+
+    /// If true, a component which fails to run is marked as failed rather than
+    /// aborting the rig, and the rig continues running every component which doesn't
+    /// depend on it. The failures are available from `RigExecutionState::failed_components`.
+    pub continue_on_error: bool,
+}
+
+pub async fn run_rig_with_options<'rig, 'cache, 'runners, THostError>(
+    rig_session: &'rig RigSession<'cache>,
+    event_handler: &mut impl RunEventHandler<'rig, 'cache, THostError>,
+    component_runners: &'runners [Box<dyn ComponentRunner>],
+    call_chain: Arc<CallChain<'rig>>,
+    options: RunRigOptions<'_>,
+) -> Result<Immutable<RigExecutionState<'rig, 'cache>>, RunError<THostError>>
 where
     'cache: 'rig,
 {
@@ -64,12 +102,31 @@ where
 
     let mut state = rig_session.initialize()?;
 
+    for (handle, value) in options.output_overrides {
+        state = state.step(Instruction::SetOutputOverride { handle, value })?;
+    }
+
+    let required_components = match options.until {
+        Some(until) => Some(state.required_components(until).ok_or_else(|| {
+            RunError::Rig(RigError::ComponentNotFound {
+                handle: until.clone(),
+            })
+        })?),
+        None => None,
+    };
+
     loop {
         let ready_components: Vec<&ComponentHandle> = state
             .component_states
             .iter()
             .filter_map(|(&handle, component_state)| {
-                if component_state.execution_input.is_some() && component_state.output().is_none() {
+                if component_state.execution_input.is_some()
+                    && component_state.output().is_none()
+                    && component_state.execution_failure.is_none()
+                    && required_components
+                        .as_ref()
+                        .is_none_or(|required| required.contains(handle))
+                {
                     Some(handle)
                 } else {
                     None
@@ -77,6 +134,10 @@ where
             })
             .collect();
 
+        // Identical memoized components run once, and the duplicates reuse the output
+        // in a later wave.
+        let ready_components = deduplicate_memoized_components(ready_components, &state);
+
         let is_complete = ready_components.is_empty();
         event_handler
             .handle_state_changed(StateChangeEvent {
@@ -97,7 +158,7 @@ where
                 .map_err(|e| RunError::HostError(e))?;
 
             let result =
-                run_component(handle, &state, component_runners, Arc::clone(&call_chain)).await?;
+                run_component(handle, &state, component_runners, Arc::clone(&call_chain)).await;
 
             event_handler
                 .handle_component_run_end(ComponentRunEndEvent {
@@ -105,11 +166,27 @@ where
                 })
                 .map_err(|e| RunError::HostError(e))?;
 
-            state = state.step(Instruction::SetOutput {
-                handle: handle.clone(),
-                value: result.output,
-                metadata: result.metadata,
-            })?;
+            let next_state = result.and_then(|result| {
+                state
+                    .step(Instruction::SetOutput {
+                        handle: handle.clone(),
+                        value: result.output,
+                        metadata: result.metadata,
+                    })
+                    .map_err(RunError::from)
+            });
+
+            state = match next_state {
+                Ok(next_state) => next_state,
+                Err(error) if options.continue_on_error => match error.split_host_error() {
+                    Ok(error) => state.step(Instruction::SetFailure {
+                        handle: handle.clone(),
+                        message: error.to_string(),
+                    })?,
+                    Err(host_error) => return Err(RunError::HostError(host_error)),
+                },
+                Err(error) => return Err(error),
+            };
         }
     }
 
@@ -125,6 +202,7 @@ fn check_rig_component_permissions<THostError>(
         crate::permissions::ensure_can_use_component_reference(
             component_reference,
             Arc::clone(call_chain),
+            rig_session.options().explain_permissions,
         )
         .map_err(|e| {
             RunError::ComponentLoadFailed(ComponentLoadError {
@@ -140,14 +218,21 @@ fn check_rig_component_permissions<THostError>(
     Ok(())
 }
 
+/// Runs a callout from the current component.
+///
+/// The caller must have permission to use the callout's component, and the callout
+/// runs with the intersection of its own permissions and those of the caller's
+/// call chain (see `slipway_engine::ensure_permissions`).
 pub async fn run_component_callout(
     execution_context: &ComponentExecutionContext<'_, '_, '_>,
     handle: &ComponentHandle,
     input: serde_json::Value,
 ) -> Result<serde_json::Value, ComponentError> {
+    crate::permissions::ensure_can_use_component_handle(handle, execution_context)?;
+
     let handle_trail = || -> String {
         execution_context
-            .call_chain
+            .call_chain()
             .component_handle_trail_for(handle)
     };
 
@@ -156,31 +241,157 @@ pub async fn run_component_callout(
             .instrument(info_span!("callout"))
             .await
             .map_err(|e| {
-            let mut inner_errors = Vec::new();
-            let message = format!("Failed to run component \"{}\"", handle_trail());
-
-            if let RunError::RunComponentFailed {
-                component_handle,
-                component_runner,
-                error: RunComponentError::RunCallReturnedError { message, inner },
-            } = &e
-            {
-                inner_errors.push(format!(
-                    "Run component failed for component \"{component_handle}\" using \"{component_runner}\" runner.",
-                ));
-
-                inner_errors.push(message.clone());
-                inner_errors.extend(inner.clone());
-            }
-            else {
-                inner_errors.push(format!("{}", e));
-            }
-
-            ComponentError {
-                message,
-                inner: inner_errors,
-            }
-        })?;
+                record_callout_permission_denial(execution_context, &e);
+                ComponentError {
+                    message: format!("Failed to run component \"{}\"", handle_trail()),
+                    inner: get_callout_error_chain(&e),
+                }
+            })?;
 
     Ok(result.output)
 }
+
+/// Runs a callout from the current component once for each input.
+///
+/// The same permissions apply as for `run_component_callout`.
+pub async fn run_component_callout_batch(
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
+    handle: &ComponentHandle,
+    inputs: Vec<serde_json::Value>,
+) -> Result<Vec<serde_json::Value>, ComponentError> {
+    crate::permissions::ensure_can_use_component_handle(handle, execution_context)?;
+
+    let results = slipway_engine::run_component_callout_batch::<anyhow::Error>(
+        handle,
+        inputs,
+        execution_context,
+    )
+    .instrument(info_span!("callout_batch"))
+    .await
+    .map_err(|e| {
+        record_callout_permission_denial(execution_context, &e);
+        ComponentError {
+            message: format!(
+                "Failed to run component \"{}\"",
+                execution_context
+                    .call_chain()
+                    .component_handle_trail_for(handle)
+            ),
+            inner: get_callout_error_chain(&e),
+        }
+    })?;
+
+    Ok(results.into_iter().map(|result| result.output).collect())
+}
+
+/// Records a permission denial within a callout against the calling component,
+/// so that if the caller fails as a result it is also reported as a permission error.
+fn record_callout_permission_denial(
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
+    error: &RunError<anyhow::Error>,
+) {
+    if let RunError::RunComponentFailed {
+        error: RunComponentError::PermissionDenied { message, inner },
+        ..
+    } = error
+    {
+        execution_context.record_permission_denial(PermissionDenial {
+            message: message.clone(),
+            inner: inner.clone(),
+        });
+    }
+}
+
+/// Flattens a callout error into a list of messages, so that when the error is
+/// returned through further callouts the full path to the original error is preserved.
+fn get_callout_error_chain(error: &RunError<anyhow::Error>) -> Vec<String> {
+    let RunError::RunComponentFailed {
+        component_handle,
+        component_runner,
+        error,
+    } = error
+    else {
+        return get_source_chain(error);
+    };
+
+    let mut chain = vec![format!(
+        "Run component failed for component \"{component_handle}\" using \"{component_runner}\" runner."
+    )];
+
+    match error {
+        RunComponentError::RunCallReturnedError { message, inner }
+        | RunComponentError::PermissionDenied { message, inner } => {
+            chain.push(message.clone());
+            chain.extend(inner.iter().cloned());
+        }
+        RunComponentError::RunCallFailed { source } => {
+            chain.extend(source.chain().map(|e| e.to_string()));
+        }
+        error => chain.extend(get_source_chain(error)),
+    }
+
+    chain
+}
+
+fn get_source_chain(error: &dyn std::error::Error) -> Vec<String> {
+    std::iter::successors(Some(error), |e| e.source())
+        .map(|e| e.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+    use slipway_engine::utils::ch;
+
+    use super::*;
+
+    #[test]
+    fn it_should_include_returned_error_chain() {
+        let error = RunError::<anyhow::Error>::RunComponentFailed {
+            component_handle: ch("increment"),
+            component_runner: "wasmtime".to_string(),
+            error: RunComponentError::RunCallReturnedError {
+                message: "Failed to run component \"test -> increment\"".to_string(),
+                inner: vec!["leaf error".to_string()],
+            },
+        };
+
+        let chain = get_callout_error_chain(&error);
+
+        assert_eq!(
+            chain,
+            vec![
+                "Run component failed for component \"increment\" using \"wasmtime\" runner."
+                    .to_string(),
+                "Failed to run component \"test -> increment\"".to_string(),
+                "leaf error".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_include_every_cause_of_failed_call() {
+        let source = Err::<(), _>(anyhow::anyhow!("root cause"))
+            .context("outer context")
+            .unwrap_err();
+
+        let error = RunError::<anyhow::Error>::RunComponentFailed {
+            component_handle: ch("increment"),
+            component_runner: "wasmtime".to_string(),
+            error: RunComponentError::RunCallFailed { source },
+        };
+
+        let chain = get_callout_error_chain(&error);
+
+        assert_eq!(
+            chain,
+            vec![
+                "Run component failed for component \"increment\" using \"wasmtime\" runner."
+                    .to_string(),
+                "outer context".to_string(),
+                "root cause".to_string(),
+            ]
+        );
+    }
+}
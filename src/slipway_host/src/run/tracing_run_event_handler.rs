@@ -61,9 +61,9 @@ impl<'rig, 'cache> RunEventHandler<'rig, 'cache, std::io::Error> for TracingRunE
     ) -> Result<RigExecutionStateViewModel<'state>, std::io::Error> {
         if event.is_complete {
             writeln!(self.w, "No more components to run.")?;
-            write_state::<_, std::io::Error>(&mut self.w, event.state)
+            write_state::<_, std::io::Error>(&mut self.w, event.state, Default::default())
         } else {
-            write_state::<_, std::io::Error>(&mut self.w, event.state)
+            write_state::<_, std::io::Error>(&mut self.w, event.state, Default::default())
         }
     }
 }
@@ -30,6 +30,8 @@ async fn test_callout_error() {
     assert_run_errors_with(
         rig,
         &[
+            "\"test -> increment\"",
+            "\"test -> increment -> increment\"",
             "\"test -> increment -> increment -> increment\"",
             "slipway-increment-component-error",
         ],
@@ -0,0 +1,100 @@
+use common::{create_components_loader, get_component_runners};
+use common_test_utils::SLIPWAY_INCREMENT_COMPONENT_TAR_NAME;
+use serde_json::json;
+use slipway_engine::{
+    BasicComponentCache, CallChain, ComponentRigging, Rig, RigSession, Rigging, SlipwayReference,
+    utils::ch,
+};
+use slipway_host::run::{RunRigOptions, no_event_handler, run_rig_with_options};
+
+mod common;
+
+#[common_macros::slipway_test_async]
+async fn it_should_continue_running_independent_components_after_failure() {
+    let rig = create_rig();
+
+    let component_cache = BasicComponentCache::primed(&rig, &create_components_loader())
+        .await
+        .unwrap();
+    let component_runners = get_component_runners();
+    let session = RigSession::new_for_test(rig, &component_cache);
+
+    let state = run_rig_with_options::<()>(
+        &session,
+        &mut no_event_handler(),
+        &component_runners,
+        CallChain::full_trust_arc(),
+        RunRigOptions {
+            continue_on_error: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let failed_components = state.failed_components();
+    assert_eq!(failed_components.len(), 1);
+    assert_eq!(failed_components[0].0, &ch("fail"));
+    assert!(
+        failed_components[0]
+            .1
+            .message
+            .contains("slipway-increment-component-error")
+    );
+
+    let dependent = state.component_states.get(&ch("dependent")).unwrap();
+    assert!(dependent.execution_input.is_none());
+    assert!(dependent.output().is_none());
+
+    let independent = state.component_states.get(&ch("independent")).unwrap();
+    assert_eq!(independent.output(), Some(&json!({ "value": 2 })));
+}
+
+#[common_macros::slipway_test_async]
+async fn it_should_abort_on_first_failure_by_default() {
+    let rig = create_rig();
+
+    let component_cache = BasicComponentCache::primed(&rig, &create_components_loader())
+        .await
+        .unwrap();
+    let component_runners = get_component_runners();
+    let session = RigSession::new_for_test(rig, &component_cache);
+
+    let result = run_rig_with_options::<()>(
+        &session,
+        &mut no_event_handler(),
+        &component_runners,
+        CallChain::full_trust_arc(),
+        RunRigOptions::default(),
+    )
+    .await;
+
+    assert!(result.is_err());
+}
+
+fn create_rig() -> Rig {
+    let rigging = |input| {
+        ComponentRigging::for_test_with_reference(
+            SlipwayReference::Local {
+                path: SLIPWAY_INCREMENT_COMPONENT_TAR_NAME.into(),
+            },
+            Some(input),
+        )
+    };
+
+    Rig::for_test(Rigging {
+        components: [
+            (ch("fail"), rigging(json!({ "type": "error" }))),
+            (
+                ch("dependent"),
+                rigging(json!({ "type": "increment", "value": "$$.fail.value" })),
+            ),
+            (
+                ch("independent"),
+                rigging(json!({ "type": "increment", "value": 1 })),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+    })
+}
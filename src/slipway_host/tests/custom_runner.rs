@@ -0,0 +1,131 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use async_trait::async_trait;
+use common::{create_components_loader, get_component_runners};
+use common_test_utils::SLIPWAY_INCREMENT_COMPONENT_TAR_NAME;
+use serde_json::json;
+use slipway_engine::{
+    BasicComponentCache, CallChain, ComponentExecutionContext, ComponentRigging, ComponentRunner,
+    Rig, RigSession, Rigging, RunComponentError, RunComponentResult, RunMetadata, SlipwayReference,
+    TryRunComponentResult, utils::ch,
+};
+use slipway_host::run::{no_event_handler, run_rig};
+
+mod common;
+
+/// An example custom runner, which runs any component by returning its input value.
+struct EchoComponentRunner {}
+
+#[async_trait(?Send)]
+impl ComponentRunner for EchoComponentRunner {
+    fn identifier(&self) -> String {
+        "echo".to_string()
+    }
+
+    async fn run<'call>(
+        &self,
+        input: &serde_json::Value,
+        _context: &'call ComponentExecutionContext<'call, '_, '_>,
+    ) -> Result<TryRunComponentResult, RunComponentError> {
+        Ok(TryRunComponentResult::Ran {
+            result: RunComponentResult {
+                output: json!({ "value": input["value"] }),
+                metadata: RunMetadata::default(),
+            },
+        })
+    }
+}
+
+/// A custom runner which can't run any components, but counts how often it is asked.
+struct CountingComponentRunner {
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait(?Send)]
+impl ComponentRunner for CountingComponentRunner {
+    fn identifier(&self) -> String {
+        "counting".to_string()
+    }
+
+    async fn run<'call>(
+        &self,
+        _input: &serde_json::Value,
+        _context: &'call ComponentExecutionContext<'call, '_, '_>,
+    ) -> Result<TryRunComponentResult, RunComponentError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(TryRunComponentResult::CannotRun { reason: None })
+    }
+}
+
+#[common_macros::slipway_test_async]
+async fn it_should_run_component_with_requested_custom_runner() {
+    let mut rigging = create_rigging();
+    rigging.runner = Some("echo".to_string());
+
+    let mut component_runners = get_component_runners();
+    component_runners.push(Box::new(EchoComponentRunner {}));
+
+    let output = run_test_rig(rigging, &component_runners).await;
+
+    // The increment component would have returned 2.
+    assert_eq!(output, json!({ "value": 1 }));
+}
+
+#[common_macros::slipway_test_async]
+async fn it_should_try_custom_runner_in_order() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let mut component_runners: Vec<Box<dyn ComponentRunner>> =
+        vec![Box::new(CountingComponentRunner {
+            calls: Arc::clone(&calls),
+        })];
+    component_runners.extend(get_component_runners());
+
+    let output = run_test_rig(create_rigging(), &component_runners).await;
+
+    assert_eq!(output, json!({ "value": 2 }));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+fn create_rigging() -> ComponentRigging {
+    ComponentRigging::for_test_with_reference(
+        SlipwayReference::Local {
+            path: SLIPWAY_INCREMENT_COMPONENT_TAR_NAME.into(),
+        },
+        Some(json!({ "type": "increment", "value": 1 })),
+    )
+}
+
+async fn run_test_rig(
+    rigging: ComponentRigging,
+    component_runners: &[Box<dyn ComponentRunner>],
+) -> serde_json::Value {
+    let rig = Rig::for_test(Rigging {
+        components: [(ch("test"), rigging)].into_iter().collect(),
+    });
+
+    let component_cache = BasicComponentCache::primed(&rig, &create_components_loader())
+        .await
+        .unwrap();
+    let session = RigSession::new_for_test(rig, &component_cache);
+
+    let state = run_rig::<()>(
+        &session,
+        &mut no_event_handler(),
+        component_runners,
+        CallChain::full_trust_arc(),
+    )
+    .await
+    .unwrap();
+
+    state
+        .component_states
+        .get(&ch("test"))
+        .unwrap()
+        .output()
+        .unwrap()
+        .clone()
+}
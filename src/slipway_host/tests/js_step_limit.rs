@@ -0,0 +1,88 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use common::{create_components_loader, get_component_runners};
+use common_test_utils::SLIPWAY_SPIN_JS_COMPONENT_TAR_NAME;
+use serde_json::json;
+use slipway_engine::{
+    BasicComponentCache, CallChain, ComponentHandle, ComponentRigging, Environment, Permissions,
+    Rig, RigSession, RigSessionOptions, Rigging, RunComponentError, RunError, SlipwayReference,
+};
+use slipway_host::run::{no_event_handler, run_rig};
+
+mod common;
+
+#[common_macros::slipway_test_async]
+async fn it_should_stop_js_component_which_spins_forever() {
+    let message = run_spin_component(json!({}), |options| {
+        options.js_step_limit = Some(10_000);
+    })
+    .await;
+
+    assert!(message.contains("run.js"));
+}
+
+#[common_macros::slipway_test_async]
+async fn it_should_stop_js_component_which_exceeds_its_time_limit() {
+    let message = run_spin_component(json!({ "call_host": true }), |options| {
+        options.js_time_limit = Some(Duration::from_millis(100));
+    })
+    .await;
+
+    assert!(message.contains("run.js"));
+    assert!(message.contains("time limit"));
+}
+
+/// Runs the spinning component and returns the message of the timeout it fails with.
+async fn run_spin_component(
+    input: serde_json::Value,
+    configure: impl FnOnce(&mut RigSessionOptions),
+) -> String {
+    let rig: Rig = Rig::for_test(Rigging {
+        components: [(
+            ComponentHandle::from_str("test").unwrap(),
+            ComponentRigging::for_test_with_reference(
+                SlipwayReference::Local {
+                    path: SLIPWAY_SPIN_JS_COMPONENT_TAR_NAME.into(),
+                },
+                Some(input),
+            ),
+        )]
+        .into_iter()
+        .collect(),
+    });
+
+    let component_cache = BasicComponentCache::primed(&rig, &create_components_loader())
+        .await
+        .unwrap();
+    let component_runners = get_component_runners();
+    let call_chain = Arc::new(CallChain::new(Permissions::allow_all()));
+
+    let mut options = RigSessionOptions::new_for_test(&rig, Environment::for_test(), None);
+    configure(&mut options);
+    let session = RigSession::new_with_options(rig, &component_cache, options);
+
+    let result = run_rig(
+        &session,
+        &mut no_event_handler(),
+        &component_runners,
+        call_chain,
+    )
+    .await;
+
+    let Err(RunError::RunComponentFailed {
+        component_handle,
+        component_runner: _,
+        error,
+    }) = result
+    else {
+        panic!("Expected RunComponentFailed error");
+    };
+
+    assert_eq!(component_handle, ComponentHandle::from_str("test").unwrap());
+
+    let RunComponentError::Timeout { message } = error else {
+        panic!("Expected Timeout error, got {error}");
+    };
+
+    message
+}
@@ -90,7 +90,7 @@ async fn run(permissions: Permissions<'_>, component: &str) {
         RunError::RunComponentFailed {
             component_handle,
             component_runner: _,
-            error: RunComponentError::RunCallReturnedError { message, inner },
+            error: RunComponentError::PermissionDenied { message, inner },
         } => {
             assert_eq!(component_handle, ch("test"));
             assert_messages_contains(
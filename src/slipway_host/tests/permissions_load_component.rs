@@ -9,8 +9,9 @@ use common_test_utils::{
 };
 use serde_json::json;
 use slipway_engine::{
-    Callout, ComponentHandle, ComponentRigging, Permission, Permissions,
-    RegistryComponentPermission, Rig, Rigging, RunComponentError, RunError, SlipwayReference,
+    Callout, ComponentHandle, ComponentRigging, LocalComponentPermission, Permission, Permissions,
+    PermissionsChainLink, RegistryComponentPermission, Rig, Rigging, RunComponentError, RunError,
+    SlipwayReference,
     errors::{ComponentLoadError, ComponentLoadErrorInner},
     utils::ch,
 };
@@ -51,7 +52,7 @@ async fn permissions_load_component_from_rig(component: &str) {
         RunError::RunComponentFailed {
             component_handle,
             component_runner: _,
-            error: RunComponentError::RunCallReturnedError { message, inner },
+            error: RunComponentError::PermissionDenied { message, inner },
         } => {
             assert_eq!(component_handle, ch("test"));
             assert_messages_contains(
@@ -152,7 +153,7 @@ async fn permissions_load_component_from_callout(component: &str) {
             RunError::RunComponentFailed {
                 component_handle,
                 component_runner: _,
-                error: RunComponentError::RunCallReturnedError { message, inner },
+                error: RunComponentError::PermissionDenied { message, inner },
             } => {
                 assert_eq!(component_handle, ch("test"));
                 assert_messages_contains(
@@ -201,6 +202,143 @@ async fn permissions_load_component_from_callout(component: &str) {
     }
 }
 
+#[common_macros::slipway_test_async]
+async fn permissions_callout_cannot_exceed_caller_permissions_wasm() {
+    permissions_callout_cannot_exceed_caller_permissions(SLIPWAY_INCREMENT_COMPONENT_TAR_NAME)
+        .await;
+}
+#[common_macros::slipway_test_async]
+async fn permissions_callout_cannot_exceed_caller_permissions_js() {
+    permissions_callout_cannot_exceed_caller_permissions(SLIPWAY_INCREMENT_JS_COMPONENT_TAR_NAME)
+        .await;
+}
+async fn permissions_callout_cannot_exceed_caller_permissions(component: &str) {
+    // The call graph looks like this with a TTL of 2:
+    // test
+    //   - Only has permission from the rig to use local components.
+    //   - Makes a callout to a local `increment`, which is allowed everything.
+    // increment
+    //   - Makes a callout to the registry `slipwayhq.increment`, which its own
+    //     permissions allow but the permissions of `test` do not.
+    let create_narrowed_rig = |ttl: u32| {
+        let mut rig = create_rig(
+            Permissions::allow(&vec![Permission::LocalComponents(
+                LocalComponentPermission::Any {},
+            )]),
+            component,
+            ttl,
+        );
+        rig.rigging
+            .components
+            .get_mut(&ch("test"))
+            .unwrap()
+            .callouts = Some(
+            vec![(
+                ch("increment"),
+                Callout {
+                    component: SlipwayReference::Local {
+                        path: component.into(),
+                    },
+                    allow: Some(Permission::all()),
+                    deny: None,
+                },
+            )]
+            .into_iter()
+            .collect(),
+        );
+        rig
+    };
+
+    {
+        // The callout to the local component is allowed.
+        let rig = create_narrowed_rig(1);
+        let maybe_output = get_rig_output(rig, "test", Permissions::allow_all()).await;
+        assert!(
+            maybe_output.is_ok(),
+            "Expected success, got {:?}",
+            maybe_output
+        );
+    }
+    {
+        // The nested callout to the registry component exceeds the caller's permissions.
+        let rig = create_narrowed_rig(2);
+        let maybe_output = get_rig_output(rig, "test", Permissions::allow_all()).await;
+
+        let Err(error) = maybe_output else {
+            panic!("Expected error, got {:?}", maybe_output);
+        };
+
+        match error {
+            RunError::RunComponentFailed {
+                component_handle,
+                component_runner: _,
+                error: RunComponentError::PermissionDenied { message, inner },
+            } => {
+                assert_eq!(component_handle, ch("test"));
+                assert_messages_contains(
+                    "Component \"test -> increment\" does not have permission to access component",
+                    &message,
+                    &inner,
+                );
+            }
+            _ => panic!("Expected permission error, got {:?}", error),
+        }
+    }
+}
+
+#[common_macros::slipway_test_async]
+async fn permissions_callout_should_intersect_permissions_chain_wasm() {
+    permissions_callout_should_intersect_permissions_chain(SLIPWAY_INCREMENT_COMPONENT_TAR_NAME)
+        .await;
+}
+#[common_macros::slipway_test_async]
+async fn permissions_callout_should_intersect_permissions_chain_js() {
+    permissions_callout_should_intersect_permissions_chain(SLIPWAY_INCREMENT_JS_COMPONENT_TAR_NAME)
+        .await;
+}
+async fn permissions_callout_should_intersect_permissions_chain(component: &str) {
+    // The first link of the chain allows everything, but the second only allows
+    // local components, so the callout to the registry component is denied.
+    let mut rig = create_rig(Permissions::allow_all(), component, 1);
+    let rigging = rig.rigging.components.get_mut(&ch("test")).unwrap();
+    rigging.allow = None;
+    rigging.deny = None;
+    rigging.permissions_chain = Some(vec![
+        PermissionsChainLink {
+            allow: Permission::all(),
+            deny: vec![],
+        },
+        PermissionsChainLink {
+            allow: vec![Permission::LocalComponents(
+                LocalComponentPermission::Any {},
+            )],
+            deny: vec![],
+        },
+    ]);
+
+    let maybe_output = get_rig_output(rig, "test", Permissions::allow_all()).await;
+
+    let Err(error) = maybe_output else {
+        panic!("Expected error, got {:?}", maybe_output);
+    };
+
+    match error {
+        RunError::RunComponentFailed {
+            component_handle,
+            component_runner: _,
+            error: RunComponentError::PermissionDenied { message, inner },
+        } => {
+            assert_eq!(component_handle, ch("test"));
+            assert_messages_contains(
+                "Component \"test\" does not have permission to access component",
+                &message,
+                &inner,
+            );
+        }
+        _ => panic!("Expected permission error, got {:?}", error),
+    }
+}
+
 fn create_rig(component_permissions: Permissions, component: &str, ttl: u32) -> Rig {
     Rig::for_test(Rigging {
         components: [(
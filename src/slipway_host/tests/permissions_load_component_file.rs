@@ -34,7 +34,7 @@ async fn permissions_load_component_file(component: &str) {
         RunError::RunComponentFailed {
             component_handle,
             component_runner: _,
-            error: RunComponentError::RunCallReturnedError { message, inner },
+            error: RunComponentError::PermissionDenied { message, inner },
         } => {
             assert_eq!(component_handle, ch("test"));
             assert_messages_contains(
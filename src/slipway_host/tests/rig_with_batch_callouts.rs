@@ -0,0 +1,123 @@
+use std::str::FromStr;
+
+use common::{assert_messages_contains, get_rig_output};
+use common_test_utils::SLIPWAY_BATCH_CALLER_COMPONENT_TAR_NAME;
+use serde_json::json;
+use slipway_engine::{
+    ComponentHandle, ComponentRigging, Permission, Permissions, RegistryComponentPermission, Rig,
+    Rigging, RunComponentError, RunError, SlipwayReference, utils::ch,
+};
+
+mod common;
+
+#[common_macros::slipway_test_async]
+async fn batch_callouts_reuse_instance_wasm() {
+    // The WASM runner runs every input in the batch using a single instance
+    // of the batchable component, so its run count increases across the batch.
+    let rig = create_rig("batch", Permissions::allow_all());
+
+    let component_output = get_rig_output(rig, "test", Permissions::allow_all())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        component_output.value,
+        json!({
+            "results": [
+                { "value": 2, "run_count": 1 },
+                { "value": 3, "run_count": 2 },
+                { "value": 4, "run_count": 3 },
+            ]
+        })
+    );
+}
+
+#[common_macros::slipway_test_async]
+async fn batch_callouts_fall_back_to_separate_runs_js() {
+    // The Javascript runner doesn't support batching, so each input in the batch
+    // is run using a fresh instance of the component.
+    let rig = create_rig("batch_js", Permissions::allow_all());
+
+    let component_output = get_rig_output(rig, "test", Permissions::allow_all())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        component_output.value,
+        json!({
+            "results": [
+                { "value": 2, "run_count": 1 },
+                { "value": 3, "run_count": 1 },
+                { "value": 4, "run_count": 1 },
+            ]
+        })
+    );
+}
+
+#[common_macros::slipway_test_async]
+async fn batch_callouts_permission_denied_wasm() {
+    permissions_batch_callouts("batch").await;
+}
+
+#[common_macros::slipway_test_async]
+async fn batch_callouts_permission_denied_js() {
+    permissions_batch_callouts("batch_js").await;
+}
+
+async fn permissions_batch_callouts(handle: &str) {
+    let rig = create_rig(
+        handle,
+        Permissions::new(
+            &vec![Permission::All],
+            &vec![Permission::RegistryComponents(
+                RegistryComponentPermission {
+                    name: None,
+                    publisher: None,
+                    version: None,
+                },
+            )],
+        ),
+    );
+
+    let maybe_output = get_rig_output(rig, "test", Permissions::allow_all()).await;
+
+    let Err(error) = maybe_output else {
+        panic!("Expected error, got {:?}", maybe_output);
+    };
+
+    match error {
+        RunError::RunComponentFailed {
+            component_handle,
+            component_runner: _,
+            error: RunComponentError::PermissionDenied { message, inner },
+        } => {
+            assert_eq!(component_handle, ch("test"));
+            assert_messages_contains(
+                "Component \"test\" does not have permission to access component",
+                &message,
+                &inner,
+            );
+        }
+        _ => panic!("Expected permission error, got {:?}", error),
+    }
+}
+
+fn create_rig(handle: &str, component_permissions: Permissions) -> Rig {
+    Rig::for_test(Rigging {
+        components: [(
+            ComponentHandle::from_str("test").unwrap(),
+            ComponentRigging::for_test_with_reference_permissions(
+                SlipwayReference::Local {
+                    path: SLIPWAY_BATCH_CALLER_COMPONENT_TAR_NAME.into(),
+                },
+                Some(json!({
+                    "handle": handle,
+                    "values": [1, 2, 3]
+                })),
+                component_permissions,
+            ),
+        )]
+        .into_iter()
+        .collect(),
+    })
+}
@@ -1,7 +1,10 @@
 use std::str::FromStr;
 
 use common::get_rig_output;
-use common_test_utils::SLIPWAY_FRAGMENT_COMPONENT_TAR_NAME;
+use common_test_utils::{
+    SLIPWAY_FRAGMENT_COMPONENT_TAR_NAME, SLIPWAY_FRAGMENT_CUSTOM_HANDLES_COMPONENT_TAR_NAME,
+    SLIPWAY_FRAGMENT_NAMED_INPUTS_COMPONENT_TAR_NAME,
+};
 use serde_json::json;
 use slipway_engine::{
     ComponentHandle, ComponentRigging, Permissions, Rig, Rigging, SlipwayReference,
@@ -39,3 +42,64 @@ async fn run() {
         })
     );
 }
+
+#[common_macros::slipway_test_async]
+async fn run_with_named_inputs() {
+    let rig: Rig = Rig::for_test(Rigging {
+        components: [(
+            ComponentHandle::from_str("frag").unwrap(),
+            ComponentRigging::for_test_with_reference(
+                SlipwayReference::Local {
+                    path: SLIPWAY_FRAGMENT_NAMED_INPUTS_COMPONENT_TAR_NAME.into(),
+                },
+                Some(json!({
+                    "first": { "value": 1 },
+                    "second": { "value": 10 }
+                })),
+            ),
+        )]
+        .into_iter()
+        .collect(),
+    });
+
+    let output = get_rig_output(rig, "frag", Permissions::allow_all())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        output.value,
+        json!({
+            "value": 11
+        })
+    );
+}
+
+#[common_macros::slipway_test_async]
+async fn run_with_custom_handles() {
+    let rig: Rig = Rig::for_test(Rigging {
+        components: [(
+            ComponentHandle::from_str("frag").unwrap(),
+            ComponentRigging::for_test_with_reference(
+                SlipwayReference::Local {
+                    path: SLIPWAY_FRAGMENT_CUSTOM_HANDLES_COMPONENT_TAR_NAME.into(),
+                },
+                Some(json!({
+                    "value": 1
+                })),
+            ),
+        )]
+        .into_iter()
+        .collect(),
+    });
+
+    let output = get_rig_output(rig, "frag", Permissions::allow_all())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        output.value,
+        json!({
+            "value": 3
+        })
+    );
+}
@@ -0,0 +1,143 @@
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+};
+
+use common::{create_components_loader, get_component_runners};
+use common_test_utils::{SLIPWAY_FETCH_COMPONENT_TAR_NAME, SLIPWAY_FETCH_JS_COMPONENT_TAR_NAME};
+use serde::Deserialize;
+use serde_json::json;
+use slipway_engine::{
+    BasicComponentCache, CallChain, ComponentHandle, ComponentRigging, Environment, HttpCassette,
+    HttpCassetteMode, Permissions, Rig, RigSession, RigSessionOptions, Rigging, SlipwayReference,
+};
+use slipway_host::run::{no_event_handler, run_rig};
+
+mod common;
+
+#[common_macros::slipway_test_async]
+async fn http_cassette_record_and_replay_wasm() {
+    run(SLIPWAY_FETCH_COMPONENT_TAR_NAME).await;
+}
+
+#[common_macros::slipway_test_async]
+async fn http_cassette_record_and_replay_js() {
+    run(SLIPWAY_FETCH_JS_COMPONENT_TAR_NAME).await;
+}
+
+const BODY: &str = "cassette_body💖";
+
+/// Records a request to a server which only responds once, then replays it
+/// from the cassette after the server has gone.
+async fn run(component: &str) {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let cassette_path = temp_dir.path().join("cassette.json");
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let localhost_url = format!("http://{}/", listener.local_addr().unwrap());
+
+    let server_thread = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            let read = stream.read(&mut buffer).unwrap();
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
+
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{BODY}",
+            BODY.len()
+        )
+        .unwrap();
+    });
+
+    let url = format!("{}foo/bar", localhost_url);
+
+    let recorded =
+        run_with_cassette(component, &url, &cassette_path, HttpCassetteMode::Record).await;
+    server_thread.join().unwrap();
+
+    assert!(cassette_path.exists());
+    assert_eq!(recorded.status_code, 200);
+    assert_eq!(recorded.body_text, Some(BODY.to_string()));
+
+    let replayed =
+        run_with_cassette(component, &url, &cassette_path, HttpCassetteMode::Replay).await;
+
+    assert_eq!(replayed.status_code, 200);
+    assert_eq!(replayed.body_text, Some(BODY.to_string()));
+}
+
+async fn run_with_cassette(
+    component: &str,
+    url: &str,
+    cassette_path: &Path,
+    expected_mode: HttpCassetteMode,
+) -> Output {
+    let cassette = HttpCassette::load(cassette_path, vec![]).unwrap();
+    assert_eq!(cassette.mode(), expected_mode);
+
+    let rig: Rig = Rig::for_test(Rigging {
+        components: [(
+            ComponentHandle::from_str("test").unwrap(),
+            ComponentRigging::for_test_with_reference(
+                SlipwayReference::Local {
+                    path: component.into(),
+                },
+                Some(json!({
+                    "url": url,
+                    "method": "GET",
+                    "headers": {},
+                    "body": "",
+                    "response_type": "text"
+                })),
+            ),
+        )]
+        .into_iter()
+        .collect(),
+    });
+
+    let component_cache = BasicComponentCache::primed(&rig, &create_components_loader())
+        .await
+        .unwrap();
+    let component_runners = get_component_runners();
+    let call_chain = Arc::new(CallChain::new(Permissions::allow_all()));
+
+    let mut options = RigSessionOptions::new_for_test(&rig, Environment::for_test(), None);
+    options.http_cassette = Some(Arc::new(cassette));
+    let session = RigSession::new_with_options(rig, &component_cache, options);
+
+    let result = run_rig(
+        &session,
+        &mut no_event_handler(),
+        &component_runners,
+        call_chain,
+    )
+    .await
+    .unwrap();
+
+    let output = result
+        .component_states
+        .get(&ComponentHandle::from_str("test").unwrap())
+        .expect("Output handle should exist")
+        .execution_output
+        .as_ref()
+        .expect("Output should be populated");
+
+    serde_json::from_value::<Output>(output.value.clone()).unwrap()
+}
+
+#[derive(Deserialize)]
+struct Output {
+    status_code: u16,
+    body_text: Option<String>,
+}
@@ -1,4 +1,9 @@
-use std::str::FromStr;
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    str::FromStr,
+    time::Duration,
+};
 
 use common::get_rig_output;
 use common_test_utils::{
@@ -42,21 +47,58 @@ async fn http_text_error_status_code_js() {
     run("text", 500, SLIPWAY_FETCH_JS_COMPONENT_TAR_NAME).await;
 }
 
+#[common_macros::slipway_test_async]
+async fn http_fetch_js() {
+    run("fetch", 200, SLIPWAY_FETCH_JS_COMPONENT_TAR_NAME).await;
+}
+
+#[common_macros::slipway_test_async]
+async fn http_fetch_error_status_code_js() {
+    run("fetch", 500, SLIPWAY_FETCH_JS_COMPONENT_TAR_NAME).await;
+}
+
+#[common_macros::slipway_test_async]
+async fn http_slow_response_within_timeout_wasm() {
+    run_slow_response(SLIPWAY_FETCH_COMPONENT_TAR_NAME).await;
+}
+
+#[common_macros::slipway_test_async]
+async fn http_slow_response_within_timeout_js() {
+    run_slow_response(SLIPWAY_FETCH_JS_COMPONENT_TAR_NAME).await;
+}
+
 const BODY: &str = "test_body💖";
 
-async fn run(file_type: &str, status_code: u16, component: &str) {
-    let test_server = TestServer::start_for_call(
-        "/foo/bar".to_string(),
-        "PUT".to_string(),
-        vec![
-            ("content-type".to_string(), "application/json".to_string()),
-            ("content-length".to_string(), BODY.len().to_string()),
-        ],
-        BODY.to_string(),
-        status_code,
-    );
+const SLOW_RESPONSE_DELAY: Duration = Duration::from_millis(1500);
+
+/// Requests a server which responds more slowly than the components' default
+/// timeout, so the request only succeeds if the timeout in the input is used.
+async fn run_slow_response(component: &str) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let localhost_url = format!("http://{}/", listener.local_addr().unwrap());
+
+    let server_thread = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            let read = stream.read(&mut buffer).unwrap();
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
 
-    let localhost_url = &test_server.localhost_url;
+        std::thread::sleep(SLOW_RESPONSE_DELAY);
+
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{BODY}",
+            BODY.len()
+        )
+        .unwrap();
+    });
 
     let rig: Rig = Rig::for_test(Rigging {
         components: [(
@@ -67,13 +109,11 @@ async fn run(file_type: &str, status_code: u16, component: &str) {
                 },
                 Some(json!({
                     "url": format!("{}foo/bar", localhost_url),
-                    "method": "PUT",
-                    "headers": {
-                        "Content-Type": "application/json",
-                        "Content-Length": BODY.len().to_string()
-                    },
-                    "body": BODY,
-                    "response_type": file_type
+                    "method": "GET",
+                    "headers": {},
+                    "body": "",
+                    "response_type": "text",
+                    "timeout_ms": 10000
                 })),
             ),
         )]
@@ -84,22 +124,12 @@ async fn run(file_type: &str, status_code: u16, component: &str) {
     let component_output = get_rig_output(rig, "test", Permissions::allow_all())
         .await
         .unwrap();
-    test_server.stop();
+    server_thread.join().unwrap();
 
     let output = serde_json::from_value::<Output>(component_output.value.clone()).unwrap();
 
-    assert_eq!(output.status_code, status_code);
-
-    // Errors contain a binary body in the response, so for errors we'd get the binary size back.
-    if file_type == "text" && status_code < 400 {
-        assert_eq!(output.body_text, Some(BODY.to_string()));
-        assert!(output.body_bin.is_none());
-    } else {
-        if status_code < 400 {
-            assert!(output.body_text.is_none());
-        }
-        assert_eq!(output.body_bin, Some(BODY.as_bytes().to_vec()));
-    };
+    assert_eq!(output.status_code, 200);
+    assert_eq!(output.body_text, Some(BODY.to_string()));
 }
 
 #[derive(Deserialize)]
@@ -107,4 +137,5 @@ struct Output {
     status_code: u16,
     body_text: Option<String>,
     body_bin: Option<Vec<u8>>,
+    content_type: Option<String>,
 }
@@ -0,0 +1,35 @@
+use std::str::FromStr;
+
+use common::get_rig_output;
+use common_test_utils::SLIPWAY_STREAMING_COMPONENT_TAR_NAME;
+use serde_json::json;
+use slipway_engine::{
+    ComponentHandle, ComponentRigging, Permissions, Rig, Rigging, SlipwayReference,
+};
+
+mod common;
+
+#[common_macros::slipway_test_async]
+async fn streaming_output_wasm() {
+    let rig: Rig = Rig::for_test(Rigging {
+        components: [(
+            ComponentHandle::from_str("test").unwrap(),
+            ComponentRigging::for_test_with_reference(
+                SlipwayReference::Local {
+                    path: SLIPWAY_STREAMING_COMPONENT_TAR_NAME.into(),
+                },
+                Some(json!({
+                    "count": 3
+                })),
+            ),
+        )]
+        .into_iter()
+        .collect(),
+    });
+
+    let component_output = get_rig_output(rig, "test", Permissions::allow_all())
+        .await
+        .unwrap();
+
+    assert_eq!(component_output.value, json!({ "items": [0, 1, 2] }));
+}
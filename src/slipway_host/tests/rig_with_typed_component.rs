@@ -0,0 +1,44 @@
+use std::str::FromStr;
+
+use common::get_rig_output;
+use common_test_utils::SLIPWAY_TYPED_COMPONENT_TAR_NAME;
+use serde_json::json;
+use slipway_engine::{
+    ComponentHandle, ComponentRigging, Permissions, Rig, Rigging, SlipwayReference,
+};
+
+mod common;
+
+#[common_macros::slipway_test_async]
+async fn typed_component_wasm() {
+    let rig: Rig = Rig::for_test(Rigging {
+        components: [(
+            ComponentHandle::from_str("test").unwrap(),
+            ComponentRigging::for_test_with_reference(
+                SlipwayReference::Local {
+                    path: SLIPWAY_TYPED_COMPONENT_TAR_NAME.into(),
+                },
+                Some(json!({
+                    "value": 1,
+                    "items": [2, 3],
+                    "name": "typed"
+                })),
+            ),
+        )]
+        .into_iter()
+        .collect(),
+    });
+
+    let component_output = get_rig_output(rig, "test", Permissions::allow_all())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        component_output.value,
+        json!({
+            "value": 2,
+            "items": [3, 4],
+            "name": "typed"
+        })
+    );
+}
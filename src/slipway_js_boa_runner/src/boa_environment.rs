@@ -6,7 +6,8 @@ use boa_engine::{
     Context, JsResult, JsString, Source, context::ContextBuilder, optimizer::OptimizerOptions,
 };
 use slipway_engine::{ComponentFiles, RunComponentError};
-use tracing::{debug, error, info, trace, warn};
+use slipway_host::log::log_console;
+use tracing::Level;
 
 use crate::component_module_loader::ComponentModuleLoader;
 
@@ -14,6 +15,7 @@ const POLYFILLS: &str = include_str!("polyfills.js");
 
 pub(super) fn prepare_environment(
     files: Arc<ComponentFiles>,
+    component: String,
 ) -> Result<Context, RunComponentError> {
     let executor = Rc::new(super::async_environment::Queue::new());
     // let loader = Rc::new(SimpleModuleLoader::new(".").map_err(|e| anyhow::anyhow!(e.to_string()))?);
@@ -28,7 +30,7 @@ pub(super) fn prepare_environment(
 
     context.strict(false);
 
-    let options = RegisterOptions::new().with_console_logger(BoaConsoleLogger {});
+    let options = RegisterOptions::new().with_console_logger(BoaConsoleLogger { component });
     boa_runtime::register(&mut context, options)
         .expect("should not fail while registering the runtime");
 
@@ -47,13 +49,23 @@ pub(super) fn prepare_environment(
     Ok(context)
 }
 
+/// Routes the component's console output to the host log, tagged with the component.
 #[derive(Debug, boa_macros::Trace, boa_macros::Finalize)]
-struct BoaConsoleLogger;
+struct BoaConsoleLogger {
+    #[unsafe_ignore_trace]
+    component: String,
+}
+
+impl BoaConsoleLogger {
+    fn log_at(&self, level: Level, msg: String, state: &ConsoleState) {
+        let indent = state.indent();
+        log_console(level, &self.component, format!("{msg:>indent$}"));
+    }
+}
 
 impl Logger for BoaConsoleLogger {
     fn trace(&self, msg: String, state: &ConsoleState, context: &mut Context) -> JsResult<()> {
-        let indent = state.indent();
-        trace!("{msg:>indent$}");
+        self.log_at(Level::TRACE, msg, state);
 
         let stack_trace = context
             .stack_trace()
@@ -65,30 +77,23 @@ impl Logger for BoaConsoleLogger {
         for frame in stack_trace {
             if is_first {
                 is_first = false;
-                trace!(" at {frame:>indent$}");
+                self.log_at(Level::TRACE, format!(" at {frame}"), state);
             } else {
-                trace!("    {frame:>indent$}");
+                self.log_at(Level::TRACE, format!("    {frame}"), state);
             }
         }
 
         Ok(())
     }
 
-    fn debug(
-        &self,
-        msg: String,
-        state: &ConsoleState,
-        _context: &mut Context,
-    ) -> boa_engine::JsResult<()> {
-        let indent = state.indent();
-        debug!("{msg:>indent$}");
+    fn debug(&self, msg: String, state: &ConsoleState, _context: &mut Context) -> JsResult<()> {
+        self.log_at(Level::DEBUG, msg, state);
         Ok(())
     }
 
     #[inline]
     fn log(&self, msg: String, state: &ConsoleState, _context: &mut Context) -> JsResult<()> {
-        let indent = state.indent();
-        info!("{msg:>indent$}");
+        self.log_at(Level::INFO, msg, state);
         Ok(())
     }
 
@@ -99,15 +104,70 @@ impl Logger for BoaConsoleLogger {
 
     #[inline]
     fn warn(&self, msg: String, state: &ConsoleState, _context: &mut Context) -> JsResult<()> {
-        let indent = state.indent();
-        warn!("{msg:>indent$}");
+        self.log_at(Level::WARN, msg, state);
         Ok(())
     }
 
     #[inline]
     fn error(&self, msg: String, state: &ConsoleState, _context: &mut Context) -> JsResult<()> {
-        let indent = state.indent();
-        error!("{msg:>indent$}");
+        self.log_at(Level::ERROR, msg, state);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct CapturedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn it_should_log_console_warn_as_warn_level_host_log() {
+        let output = CapturedOutput::default();
+        let writer = output.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(Level::TRACE)
+            .with_ansi(false)
+            .without_time()
+            .with_writer(move || writer.clone())
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let mut context = Context::default();
+            let options = RegisterOptions::new().with_console_logger(BoaConsoleLogger {
+                component: "test_component".to_string(),
+            });
+            boa_runtime::register(&mut context, options).unwrap();
+
+            context
+                .eval(Source::from_bytes("console.warn('careful now');"))
+                .unwrap();
+        });
+
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let line = output
+            .lines()
+            .find(|line| line.contains("careful now"))
+            .expect("console.warn should be logged");
+
+        assert!(line.contains("WARN"), "unexpected log line: {line}");
+        assert!(
+            line.contains("test_component"),
+            "unexpected log line: {line}"
+        );
+    }
+}
@@ -1,9 +1,9 @@
-use std::{collections::HashMap, future::Future};
+use std::{collections::HashMap, future::Future, time::Instant};
 
 use boa_engine::{
-    Context, JsError, JsResult, JsValue, NativeFunction, js_string,
+    Context, JsError, JsNativeError, JsResult, JsValue, NativeFunction, js_string,
     object::{
-        ObjectInitializer,
+        JsObject, ObjectInitializer,
         builtins::{JsArrayBuffer, JsPromise, JsUint8Array},
     },
     property::{Attribute, PropertyKey},
@@ -12,10 +12,15 @@ use serde::{Deserialize, Serialize};
 use slipway_engine::{ComponentExecutionContext, RunComponentError};
 use slipway_host::{
     ComponentError,
-    fetch::{BinResponse, RequestError, RequestOptions},
+    fetch::{
+        BinResponse, CacheStatus, PaginatedRequestOptions, Pagination, RequestError, RequestOptions,
+    },
     fonts::ResolvedFont,
+    images::DecodedImage,
 };
 
+pub(super) const TIME_LIMIT_EXCEEDED_MESSAGE: &str = "Maximum run time limit exceeded";
+
 type JsFunction = dyn Fn(&JsValue, &[JsValue], &mut Context) -> JsResult<JsValue> + 'static;
 
 pub(super) fn prepare_slipway_host<'call, 'rig, 'runners, 'host, 'context>(
@@ -35,8 +40,10 @@ where
 
         macro_rules! add_function {
             ($name:ident) => {{
-                let f: Box<JsFunction> =
-                    Box::new(move |this, args, ctx| host_static.$name(this, args, ctx));
+                let f: Box<JsFunction> = Box::new(move |this, args, ctx| {
+                    host_static.ensure_within_time_limit()?;
+                    host_static.$name(this, args, ctx)
+                });
 
                 object_initializer.function(
                     NativeFunction::from_closure(f),
@@ -49,6 +56,7 @@ where
         macro_rules! add_function_async {
             ($name:ident) => {{
                 let f: Box<JsFunction> = Box::new(move |this, args, ctx| {
+                    host_static.ensure_within_time_limit()?;
                     let future = host_static.$name(this, args, ctx);
                     // We know our future only holds references to data that lives longer than the Boa runtime,
                     // but Boa needs the data to be static, so again we transmute to satisfy the requirements.
@@ -73,14 +81,18 @@ where
         add_function!(log_warn);
         add_function!(log_error);
         add_function_async!(font);
+        add_function!(subset_font);
         add_function_async!(fetch_bin);
         add_function_async!(fetch_text);
+        add_function_async!(fetch_pages);
         add_function_async!(run);
         add_function_async!(load_bin);
         add_function_async!(load_text);
         add_function!(env);
         add_function!(encode_bin);
         add_function!(decode_bin);
+        add_function_async!(decode_image);
+        add_function_async!(encode_image);
 
         object_initializer.build()
     };
@@ -102,11 +114,40 @@ where
 #[derive(Clone, Copy)]
 pub struct SlipwayHost<'call, 'rig, 'runners> {
     execution_context: &'call ComponentExecutionContext<'call, 'rig, 'runners>,
+
+    /// When the component's `js_time_limit` runs out, if it has one.
+    deadline: Option<Instant>,
 }
 
 impl<'call, 'rig, 'runners> SlipwayHost<'call, 'rig, 'runners> {
     pub fn new(execution_context: &'call ComponentExecutionContext<'call, 'rig, 'runners>) -> Self {
-        Self { execution_context }
+        let deadline = execution_context
+            .rig_session_options()
+            .js_time_limit
+            .map(|limit| Instant::now() + limit);
+
+        Self {
+            execution_context,
+            deadline,
+        }
+    }
+
+    /// Returns true once the component has run for longer than its `js_time_limit`.
+    pub fn is_past_time_limit(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() > deadline)
+    }
+
+    /// Fails with a runtime limit error, which scripts can't catch, once the
+    /// component has run for longer than its `js_time_limit`.
+    fn ensure_within_time_limit(&self) -> JsResult<()> {
+        if self.is_past_time_limit() {
+            return Err(JsNativeError::runtime_limit()
+                .with_message(TIME_LIMIT_EXCEEDED_MESSAGE)
+                .into());
+        }
+
+        Ok(())
     }
 
     pub fn log_trace(
@@ -208,6 +249,27 @@ impl<'call, 'rig, 'runners> SlipwayHost<'call, 'rig, 'runners> {
         }
     }
 
+    pub fn subset_font(
+        &self,
+        _this: &JsValue,
+        args: &[JsValue],
+        context: &mut Context,
+    ) -> JsResult<JsValue> {
+        if args.len() < 2 {
+            return Err(js_error(
+                "Expected a font u8 array and the text to subset it for.".to_string(),
+                context,
+            ));
+        }
+
+        let font = get_bin_arg(args, 0, context)?;
+        let text = get_string_arg(args, 1, context)?;
+        let subset = ::slipway_host::fonts::subset_font(self.execution_context, font, text)
+            .map_err(|e| js_error_from_component_error(e, context))?;
+
+        bin_array_to_typed_array_js_value(subset, context)
+    }
+
     pub fn fetch_bin<'a>(
         &'a self,
         _this: &JsValue,
@@ -254,6 +316,23 @@ impl<'call, 'rig, 'runners> SlipwayHost<'call, 'rig, 'runners> {
         }
     }
 
+    pub fn fetch_pages<'a>(
+        &'a self,
+        _this: &JsValue,
+        args: &[JsValue],
+        context: &'a mut Context,
+    ) -> impl Future<Output = JsResult<JsValue>> + 'a + use<'a> {
+        let url_opts = get_url_and_paginated_request_options(args, context);
+
+        async move {
+            let (url, opts) = url_opts?;
+            ::slipway_host::fetch::fetch_pages(self.execution_context, &url, opts)
+                .await
+                .map_err(|e| js_error_from_request_error(e, context))
+                .and_then(|items| value_to_js_value(items, context))
+        }
+    }
+
     pub fn run<'a>(
         &'a self,
         _this: &JsValue,
@@ -372,6 +451,66 @@ impl<'call, 'rig, 'runners> SlipwayHost<'call, 'rig, 'runners> {
 
         bin_array_to_typed_array_js_value(bin, context)
     }
+
+    pub fn decode_image<'a>(
+        &'a self,
+        _this: &JsValue,
+        args: &[JsValue],
+        context: &'a mut Context,
+    ) -> impl Future<Output = JsResult<JsValue>> + 'a + use<'a> {
+        let bin = if args.is_empty() {
+            Err(js_error(
+                "Expected a u8 array, found no arguments.".to_string(),
+                context,
+            ))
+        } else {
+            get_bin_arg(args, 0, context)
+        };
+
+        async move {
+            let image = ::slipway_host::images::decode_image(self.execution_context, bin?)
+                .await
+                .map_err(|e| js_error_from_component_error(e, context))?;
+
+            // We want to ensure we return the data as a Uint8Array,
+            // so we manually add it to the object.
+            let (js_decoded_image, data) = JsDecodedImage::from(image);
+            value_to_js_value(js_decoded_image, context).and_then(|js_result| {
+                let js_object = js_result
+                    .as_object()
+                    .expect("Decoded image should be an object");
+                let js_bin_array = bin_array_to_typed_array_js_value(data, context)?;
+                js_object.set(js_string!("data"), js_bin_array, true, context)?;
+                Ok(js_result)
+            })
+        }
+    }
+
+    pub fn encode_image<'a>(
+        &'a self,
+        _this: &JsValue,
+        args: &[JsValue],
+        context: &'a mut Context,
+    ) -> impl Future<Output = JsResult<JsValue>> + 'a + use<'a> {
+        let image_format = if args.len() < 2 {
+            Err(js_error(
+                "Expected an image and a format.".to_string(),
+                context,
+            ))
+        } else {
+            get_image_arg(args, 0, context)
+                .and_then(|image| Ok((image, get_string_arg(args, 1, context)?)))
+        };
+
+        async move {
+            let (image, format) = image_format?;
+            let bin = ::slipway_host::images::encode_image(self.execution_context, image, format)
+                .await
+                .map_err(|e| js_error_from_component_error(e, context))?;
+
+            bin_array_to_typed_array_js_value(bin, context)
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -390,6 +529,24 @@ impl JsResolvedFont {
     }
 }
 
+#[derive(Debug, Serialize)]
+pub struct JsDecodedImage {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl JsDecodedImage {
+    fn from(value: DecodedImage) -> (Self, Vec<u8>) {
+        (
+            JsDecodedImage {
+                width: value.width,
+                height: value.height,
+            },
+            value.data,
+        )
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 enum BytesOrString {
@@ -417,6 +574,9 @@ struct JsRequestOptions {
 
     #[serde(default)]
     pub timeout_ms: Option<u32>,
+
+    #[serde(default)]
+    pub max_redirects: Option<u32>,
 }
 
 impl From<JsRequestOptions> for RequestOptions {
@@ -432,6 +592,32 @@ impl From<JsRequestOptions> for RequestOptions {
                 BytesOrString::String(string) => string.into_bytes(),
             }),
             timeout_ms: value.timeout_ms,
+            max_redirects: value.max_redirects,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JsPaginatedRequestOptions {
+    #[serde(default)]
+    pub request: Option<JsRequestOptions>,
+
+    pub pagination: Pagination,
+
+    #[serde(default)]
+    pub items_pointer: Option<String>,
+
+    #[serde(default)]
+    pub max_pages: Option<u32>,
+}
+
+impl From<JsPaginatedRequestOptions> for PaginatedRequestOptions {
+    fn from(value: JsPaginatedRequestOptions) -> Self {
+        PaginatedRequestOptions {
+            request: value.request.map(Into::into),
+            pagination: value.pagination,
+            items_pointer: value.items_pointer,
+            max_pages: value.max_pages,
         }
     }
 }
@@ -440,6 +626,7 @@ impl From<JsRequestOptions> for RequestOptions {
 pub struct JsBinResponse {
     pub status_code: u16,
     pub headers: Vec<(String, String)>,
+    pub cache_status: CacheStatus,
 }
 
 impl JsBinResponse {
@@ -448,6 +635,7 @@ impl JsBinResponse {
             JsBinResponse {
                 status_code: value.status_code,
                 headers: value.headers,
+                cache_status: value.cache_status,
             },
             value.body,
         )
@@ -488,15 +676,7 @@ fn get_url_and_request_options(
             let js_arg_object = js_arg
                 .as_object()
                 .expect("Request options should be an object");
-            let body_key = js_string!("body");
-            if js_arg_object.has_property(body_key.clone(), context)? {
-                let body = js_arg_object.get(body_key, context)?;
-                if body.is_null_or_undefined() {
-                    v.body = None;
-                } else {
-                    v.body = Some(value_to_bin_array_or_string(&body, context)?);
-                }
-            }
+            read_request_body(&js_arg_object, &mut v, context)?;
             Ok(v)
         })?;
         Some(request_options)
@@ -507,6 +687,54 @@ fn get_url_and_request_options(
     Ok((url, request_options.map(Into::into)))
 }
 
+fn get_url_and_paginated_request_options(
+    args: &[JsValue],
+    context: &mut Context,
+) -> Result<(String, PaginatedRequestOptions), JsError> {
+    if args.len() < 2 {
+        return Err(js_error(
+            "Expected a URL to fetch and the pagination options.".to_string(),
+            context,
+        ));
+    }
+
+    let url = get_string_arg(args, 0, context)?;
+
+    let mut options = get_arg::<JsPaginatedRequestOptions>(args, 1, context)?;
+    if let Some(request_options) = options.request.as_mut() {
+        let js_arg = get_js_arg(args, 1, context)?;
+        let js_request = js_arg
+            .as_object()
+            .expect("Paginated request options should be an object")
+            .get(js_string!("request"), context)?;
+        if let Some(js_request_object) = js_request.as_object() {
+            read_request_body(&js_request_object, request_options, context)?;
+        }
+    }
+
+    Ok((url, options.into()))
+}
+
+/// Reads the body of the request options, which is handled manually as it can
+/// be either a string or a u8 array.
+fn read_request_body(
+    js_request_options: &JsObject,
+    request_options: &mut JsRequestOptions,
+    context: &mut Context,
+) -> Result<(), JsError> {
+    let body_key = js_string!("body");
+    if js_request_options.has_property(body_key.clone(), context)? {
+        let body = js_request_options.get(body_key, context)?;
+        if body.is_null_or_undefined() {
+            request_options.body = None;
+        } else {
+            request_options.body = Some(value_to_bin_array_or_string(&body, context)?);
+        }
+    }
+
+    Ok(())
+}
+
 fn get_js_arg<'a>(
     args: &'a [JsValue],
     index: usize,
@@ -542,6 +770,35 @@ fn get_bin_arg(args: &[JsValue], index: usize, context: &mut Context) -> Result<
     get_js_arg(args, index, context).and_then(|value| value_to_bin_array(value, context))
 }
 
+fn get_image_arg(
+    args: &[JsValue],
+    index: usize,
+    context: &mut Context,
+) -> Result<DecodedImage, JsError> {
+    let js_arg = get_js_arg(args, index, context)?;
+    let Some(js_arg_object) = js_arg.as_object() else {
+        return Err(js_error(
+            format!("Expected an image object at position {index}."),
+            context,
+        ));
+    };
+
+    let width = js_arg_object
+        .get(js_string!("width"), context)?
+        .to_u32(context)?;
+    let height = js_arg_object
+        .get(js_string!("height"), context)?
+        .to_u32(context)?;
+    let data = js_arg_object.get(js_string!("data"), context)?;
+    let data = value_to_bin_array(&data, context)?;
+
+    Ok(DecodedImage {
+        width,
+        height,
+        data,
+    })
+}
+
 fn get_json_arg(
     args: &[JsValue],
     index: usize,
@@ -1,7 +1,10 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use serde::Deserialize;
 use slipway_engine::{
-    ComponentExecutionContext, ComponentRunner, RunComponentError, TryRunComponentResult,
+    ComponentExecutionContext, ComponentFiles, ComponentRunner, RunComponentError,
+    SlipwayReference, TryRunComponentResult, TryVerifyComponentResult,
 };
 
 mod async_environment;
@@ -22,19 +25,46 @@ impl ComponentRunner for BoaComponentRunner {
         BOA_COMPONENT_RUNNER_IDENTIFIER.to_string()
     }
 
+    async fn verify(
+        &self,
+        _component_reference: &SlipwayReference,
+        files: Arc<ComponentFiles>,
+    ) -> Result<TryVerifyComponentResult, RunComponentError> {
+        let maybe_run_js = files.try_get_text(BOA_RUN_JS_FILE_NAME).await?;
+
+        let Some(run_js) = maybe_run_js else {
+            return Ok(TryVerifyComponentResult::CannotVerify);
+        };
+
+        let maybe_boa_definition = files
+            .try_get_json::<BoaComponentDefinition>(BOA_COMPONENT_DEFINITION_FILE_NAME)
+            .await?;
+
+        run_component_javascript::verify_component_javascript(
+            &run_js,
+            maybe_boa_definition,
+            &files,
+        )
+        .await?;
+
+        Ok(TryVerifyComponentResult::Verified)
+    }
+
     async fn run<'call>(
         &self,
         input: &serde_json::Value,
         context: &'call ComponentExecutionContext<'call, '_, '_>,
     ) -> Result<TryRunComponentResult, RunComponentError> {
-        let maybe_run_js = context.files.try_get_text(BOA_RUN_JS_FILE_NAME).await?;
+        let maybe_run_js = context.files().try_get_text(BOA_RUN_JS_FILE_NAME).await?;
 
         let Some(run_js) = maybe_run_js else {
-            return Ok(TryRunComponentResult::CannotRun);
+            return Ok(TryRunComponentResult::CannotRun {
+                reason: Some(format!("No \"{BOA_RUN_JS_FILE_NAME}\" file found.")),
+            });
         };
 
         let maybe_boa_definition = context
-            .files
+            .files()
             .try_get_json::<BoaComponentDefinition>(BOA_COMPONENT_DEFINITION_FILE_NAME)
             .await?;
 
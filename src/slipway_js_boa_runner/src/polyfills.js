@@ -27,11 +27,74 @@ function atob(str) {
 }
 
 // Polyfill fetch API.
+class Headers {
+  constructor(init) {
+    this._headers = new Map();
+    if (init instanceof Headers) {
+      init.forEach((value, name) => this.append(name, value));
+    } else if (Array.isArray(init)) {
+      for (const [name, value] of init) {
+        this.append(name, value);
+      }
+    } else if (init) {
+      for (const [name, value] of Object.entries(init)) {
+        this.append(name, value);
+      }
+    }
+  }
+
+  // Header names are case-insensitive, so they are stored in lowercase.
+  append(name, value) {
+    const key = name.toLowerCase();
+    const existing = this._headers.get(key);
+    this._headers.set(key, existing === undefined ? String(value) : `${existing}, ${value}`);
+  }
+
+  set(name, value) {
+    this._headers.set(name.toLowerCase(), String(value));
+  }
+
+  get(name) {
+    const value = this._headers.get(name.toLowerCase());
+    return value === undefined ? null : value;
+  }
+
+  has(name) {
+    return this._headers.has(name.toLowerCase());
+  }
+
+  delete(name) {
+    this._headers.delete(name.toLowerCase());
+  }
+
+  forEach(callback, thisArg) {
+    for (const [name, value] of this._headers) {
+      callback.call(thisArg, value, name, this);
+    }
+  }
+
+  entries() {
+    return this._headers.entries();
+  }
+
+  keys() {
+    return this._headers.keys();
+  }
+
+  values() {
+    return this._headers.values();
+  }
+
+  [Symbol.iterator]() {
+    return this.entries();
+  }
+}
+
 class Response {
   constructor(binResponse) {
     this._binResponse = binResponse;
     this.status = binResponse.status_code;
-    this.headers = new Map(binResponse.headers);
+    this.headers = new Headers(binResponse.headers);
     this.ok = this.status >= 200 && this.status < 300;
     this.statusText = this.ok ? 'OK' : 'Error';
   }
@@ -65,20 +128,26 @@ class Response {
 
   async array() {
     const bytes = await this.bytes();
-    Array.from(bytes);
+    return Array.from(bytes);
   }
 }
 
+// Requests go through `slipway_host.fetch_bin`, so they are subject to the same
+// permissions as the host fetch, and a denied request rejects the returned promise.
 async function fetch(input, init = {}) {
   // If input is Request-like, pull fields. Otherwise it's a string URL
+  const request = typeof input === 'string' ? {} : input;
   const url = typeof input === 'string' ? input : input.url;
 
+  const headers = init.headers ?? request.headers;
+
   // Convert standard fetch options to RequestOptions type
   let requestOptions = {
-    method: init.method,
-    headers: init.headers,
-    body: init.body,
-    timeout_ms: init.timeout_ms 
+    method: init.method ?? request.method,
+    headers: headers === undefined ? undefined : Array.from(new Headers(headers)),
+    body: init.body ?? request.body,
+    timeout_ms: init.timeout_ms,
+    max_redirects: init.max_redirects
   };
 
   const binResponse = await slipway_host.fetch_bin(url, requestOptions);
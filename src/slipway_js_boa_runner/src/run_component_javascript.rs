@@ -1,19 +1,20 @@
 use std::{path::Path, sync::Arc, time::Instant};
 
 use slipway_engine::{
-    ComponentExecutionContext, RunComponentError, RunComponentResult, RunMetadata,
+    ComponentExecutionContext, ComponentFiles, RunComponentError, RunComponentResult, RunMetadata,
+    ensure_output_value_within_limit,
 };
 
 use boa_engine::{
-    Context, JsError, JsValue, Module, Script, Source, builtins::promise::PromiseState, js_string,
-    property::Attribute,
+    Context, JsError, JsNativeErrorKind, JsValue, Module, Script, Source,
+    builtins::promise::PromiseState, js_string, property::Attribute,
 };
 use slipway_host::ComponentError;
 use tracing::{debug, warn};
 
 use crate::{
     BOA_RUN_JS_FILE_NAME, BoaComponentDefinition,
-    host::{SlipwayHost, prepare_slipway_host},
+    host::{SlipwayHost, TIME_LIMIT_EXCEEDED_MESSAGE, prepare_slipway_host},
 };
 
 pub(super) async fn run_component_javascript(
@@ -24,10 +25,17 @@ pub(super) async fn run_component_javascript(
 ) -> Result<RunComponentResult, RunComponentError> {
     let prepare_component_start = Instant::now();
     let host = SlipwayHost::new(execution_context);
-    let mut context =
-        super::boa_environment::prepare_environment(Arc::clone(&execution_context.files))?;
+    let mut context = super::boa_environment::prepare_environment(
+        Arc::clone(execution_context.files()),
+        execution_context.call_chain().component_handle_trail(),
+    )?;
     set_process_env(&mut context, execution_context)?;
     prepare_slipway_host(&host, &mut context)?;
+    if let Some(step_limit) = execution_context.rig_session_options().js_step_limit {
+        context
+            .runtime_limits_mut()
+            .set_loop_iteration_limit(step_limit);
+    }
     let prepare_component_duration = prepare_component_start.elapsed();
 
     let prepare_input_start = Instant::now();
@@ -45,8 +53,22 @@ pub(super) async fn run_component_javascript(
             .await?;
     let call_duration = call_start.elapsed();
 
+    // The time limit is otherwise only checked when the component calls the host,
+    // so a component which finishes after its time limit still fails.
+    if host.is_past_time_limit() {
+        return Err(RunComponentError::Timeout {
+            message: format!(
+                "Script \"{BOA_RUN_JS_FILE_NAME}\" exceeded its evaluation limit: {TIME_LIMIT_EXCEEDED_MESSAGE}"
+            ),
+        });
+    }
+
     let process_output_start = Instant::now();
     let output = convert_output(&mut context, last_result)?;
+    ensure_output_value_within_limit(
+        &output,
+        execution_context.rig_session_options().max_output_bytes,
+    )?;
     let process_output_duration = process_output_start.elapsed();
 
     Ok(RunComponentResult {
@@ -56,19 +78,52 @@ pub(super) async fn run_component_javascript(
             prepare_component_duration,
             call_duration,
             process_output_duration,
+            nested: None,
+            memoized_from: None,
         },
     })
 }
 
+/// Parses the component's scripts and module without running them,
+/// so that syntax errors are found before the component is run.
+pub(super) async fn verify_component_javascript(
+    run_js: &str,
+    boa_definition: Option<Arc<BoaComponentDefinition>>,
+    files: &ComponentFiles,
+) -> Result<(), RunComponentError> {
+    let mut context = Context::default();
+
+    let scripts = boa_definition
+        .as_ref()
+        .map(|def| def.scripts.as_slice())
+        .unwrap_or_else(|| &[]);
+
+    for script_file in scripts.iter().filter(|s| !is_run_js(s)) {
+        let content = files.get_text(script_file).await?;
+
+        Script::parse(Source::from_bytes(content.as_bytes()), None, &mut context)
+            .map_err(|e| convert_error(script_file, &mut context, e))?;
+    }
+
+    Module::parse(
+        Source::from_bytes(run_js).with_path(Path::new(BOA_RUN_JS_FILE_NAME)),
+        None,
+        &mut context,
+    )
+    .map_err(|e| convert_error(BOA_RUN_JS_FILE_NAME, &mut context, e))?;
+
+    Ok(())
+}
+
 fn set_process_env(
     context: &mut Context,
     execution_context: &ComponentExecutionContext<'_, '_, '_>,
 ) -> Result<(), RunComponentError> {
     let process = serde_json::json!({
         "env": {
-            "TZ": execution_context.rig_session_options.environment.timezone,
-            "LC": execution_context.rig_session_options.environment.locale,
-            "LC_ALL": execution_context.rig_session_options.environment.locale,
+            "TZ": execution_context.rig_session_options().environment.timezone,
+            "LC": execution_context.rig_session_options().environment.locale,
+            "LC_ALL": execution_context.rig_session_options().environment.locale,
         }
     });
 
@@ -115,14 +170,14 @@ async fn run_component_scripts_and_modules(
             continue;
         }
 
-        let content = execution_context.files.get_text(script_file).await?;
+        let content = execution_context.files().get_text(script_file).await?;
 
         run_script(script_file, &content, context).await?;
     }
 
     debug!(
         "Running Javascript component: {}",
-        execution_context.component_reference
+        execution_context.component_reference()
     );
 
     let module = Module::parse(
@@ -241,6 +296,19 @@ fn convert_output(
 }
 
 fn convert_error(script_file: &str, context: &mut Context, error: JsError) -> RunComponentError {
+    // Runtime limit errors can't be caught by the script, so they are always the top level error.
+    if let Some(native) = error.as_native()
+        && matches!(native.kind, JsNativeErrorKind::RuntimeLimit)
+    {
+        return RunComponentError::Timeout {
+            message: format!(
+                "Script \"{}\" exceeded its evaluation limit: {}",
+                script_file,
+                native.message()
+            ),
+        };
+    }
+
     let mut messages = Vec::new();
     let mut inner = Some(&error);
     while let Some(e) = inner {
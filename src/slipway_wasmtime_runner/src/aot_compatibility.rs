@@ -0,0 +1,134 @@
+use std::{
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use slipway_host::hash_bytes;
+use wasmtime::Engine;
+
+use crate::WasmOptLevel;
+
+/// Returns the path of the file recording which engine configuration an AOT compiled
+/// artifact was produced for.
+pub(super) fn get_aot_compatibility_path(aot_bytes_path: &Path) -> PathBuf {
+    let mut path = aot_bytes_path.as_os_str().to_owned();
+    path.push(".compat");
+    PathBuf::from(path)
+}
+
+/// Returns a stable hash of everything which affects whether an AOT compiled artifact
+/// can be used by the engine, including the Wasmtime version, settings and target.
+/// The optimization level is included explicitly, as Wasmtime can load artifacts
+/// compiled at any optimization level, but a changed level should trigger a recompile.
+pub(super) fn get_engine_compatibility_hash(engine: &Engine, opt_level: WasmOptLevel) -> String {
+    let mut hasher = BytesHasher::default();
+    engine.precompile_compatibility_hash().hash(&mut hasher);
+    opt_level.hash(&mut hasher);
+    hash_bytes(&hasher.bytes)
+}
+
+/// Returns true if the AOT compiled artifact exists and was produced for an engine
+/// with the given compatibility hash.
+pub(super) async fn is_aot_artifact_up_to_date(
+    aot_bytes_path: &Path,
+    compatibility_hash: &str,
+) -> bool {
+    if !tokio::fs::try_exists(aot_bytes_path).await.unwrap_or(false) {
+        return false;
+    }
+
+    match tokio::fs::read_to_string(get_aot_compatibility_path(aot_bytes_path)).await {
+        Ok(existing) => existing.trim() == compatibility_hash,
+        Err(_) => false,
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Collects the hashed bytes so they can be hashed with a stable algorithm,
+/// as the standard library hashers are not guaranteed to be stable across releases.
+#[derive(Default)]
+struct BytesHasher {
+    bytes: Vec<u8>,
+}
+
+impl Hasher for BytesHasher {
+    /// Returns the 64-bit FNV-1a hash of the collected bytes, which is also stable.
+    fn finish(&self) -> u64 {
+        self.bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        })
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+
+    use super::*;
+
+    #[test]
+    fn it_should_produce_stable_compatibility_hashes() {
+        let options = Default::default();
+        let engine = crate::create_engine(None, &options).unwrap();
+        let engine_again = crate::create_engine(None, &options).unwrap();
+
+        assert_eq!(
+            get_engine_compatibility_hash(&engine, options.opt_level),
+            get_engine_compatibility_hash(&engine_again, options.opt_level)
+        );
+    }
+
+    #[test]
+    fn it_should_produce_different_compatibility_hashes_for_different_opt_levels() {
+        let options = crate::WasmComponentRunnerOptions {
+            opt_level: WasmOptLevel::None,
+        };
+        let engine = crate::create_engine(None, &options).unwrap();
+        let default_engine = crate::create_engine(None, &Default::default()).unwrap();
+
+        assert_ne!(
+            get_engine_compatibility_hash(&engine, WasmOptLevel::None),
+            get_engine_compatibility_hash(&default_engine, WasmOptLevel::Speed)
+        );
+    }
+
+    #[test]
+    fn it_should_finish_bytes_hasher_with_fnv_1a() {
+        let mut hasher = BytesHasher::default();
+        assert_eq!(hasher.finish(), FNV_OFFSET_BASIS);
+
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+    }
+
+    #[slipway_test_async]
+    async fn it_should_only_treat_artifacts_with_matching_compatibility_as_up_to_date() {
+        let dir = tempfile::tempdir().unwrap();
+        let aot_bytes_path = dir.path().join("abc.wasm_aot");
+
+        assert!(!is_aot_artifact_up_to_date(&aot_bytes_path, "compat").await);
+
+        std::fs::write(&aot_bytes_path, b"artifact").unwrap();
+        assert!(!is_aot_artifact_up_to_date(&aot_bytes_path, "compat").await);
+
+        std::fs::write(get_aot_compatibility_path(&aot_bytes_path), "other").unwrap();
+        assert!(!is_aot_artifact_up_to_date(&aot_bytes_path, "compat").await);
+
+        std::fs::write(get_aot_compatibility_path(&aot_bytes_path), "compat\n").unwrap();
+        assert!(is_aot_artifact_up_to_date(&aot_bytes_path, "compat").await);
+    }
+
+    #[test]
+    fn it_should_get_aot_compatibility_path() {
+        assert_eq!(
+            get_aot_compatibility_path(Path::new("aot/abc.wasm_aot")),
+            PathBuf::from("aot/abc.wasm_aot.compat")
+        );
+    }
+}
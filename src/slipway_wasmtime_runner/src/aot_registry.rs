@@ -0,0 +1,329 @@
+use std::path::Path;
+
+use anyhow::Context;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use slipway_host::hash_bytes;
+use url::Url;
+use wasmtime::{Engine, Precompiled, component::Component};
+
+use crate::aot_compatibility::get_aot_compatibility_path;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const HASH_PLACEHOLDER: &str = "{hash}";
+const TARGET_PLACEHOLDER: &str = "{target}";
+
+/// The extension appended to an AOT registry file name for its signature.
+const SIGNATURE_EXTENSION: &str = ".sig";
+
+/// The extension appended to an AOT registry file name for its engine compatibility hash.
+const COMPATIBILITY_EXTENSION: &str = ".compat";
+
+/// Returns the file name an AOT compiled artifact should be published under
+/// in an AOT registry.
+fn get_aot_registry_file_name(wasm_hash: &str, target: &str) -> String {
+    format!("{wasm_hash}.{target}.wasm_aot")
+}
+
+/// Returns the files which should be published to an AOT registry for a locally
+/// AOT compiled artifact, as pairs of file names and contents.
+///
+/// Alongside the artifact these are its engine compatibility hash, and its
+/// signature using the AOT registry key.
+pub async fn get_aot_registry_files(
+    key: &str,
+    artifact_path: &Path,
+    wasm_hash: &str,
+    target: &str,
+) -> anyhow::Result<Vec<(String, Vec<u8>)>> {
+    let aot_bytes = tokio::fs::read(artifact_path)
+        .await
+        .with_context(|| format!("Failed to read {}", artifact_path.display()))?;
+
+    let compatibility_path = get_aot_compatibility_path(artifact_path);
+    let compatibility_hash = tokio::fs::read_to_string(&compatibility_path)
+        .await
+        .with_context(|| format!("Failed to read {}", compatibility_path.display()))?;
+    let compatibility_hash = compatibility_hash.trim().to_string();
+
+    let signature = sign_aot_artifact(key, wasm_hash, target, &compatibility_hash, &aot_bytes);
+
+    let file_name = get_aot_registry_file_name(wasm_hash, target);
+    Ok(vec![
+        (
+            format!("{file_name}{COMPATIBILITY_EXTENSION}"),
+            compatibility_hash.into_bytes(),
+        ),
+        (
+            format!("{file_name}{SIGNATURE_EXTENSION}"),
+            signature.into_bytes(),
+        ),
+        (file_name, aot_bytes),
+    ])
+}
+
+/// Signs an AOT compiled artifact with the AOT registry key.
+///
+/// The signature covers the WASM hash, target and engine compatibility hash as well
+/// as the artifact itself, so an artifact can't be substituted for a different component.
+fn sign_aot_artifact(
+    key: &str,
+    wasm_hash: &str,
+    target: &str,
+    compatibility_hash: &str,
+    aot_bytes: &[u8],
+) -> String {
+    let mac = create_artifact_mac(key, wasm_hash, target, compatibility_hash, aot_bytes);
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+fn verify_aot_artifact_signature(
+    key: &str,
+    wasm_hash: &str,
+    target: &str,
+    compatibility_hash: &str,
+    aot_bytes: &[u8],
+    signature: &str,
+) -> anyhow::Result<()> {
+    let signature = decode_hex(signature).context("AOT artifact signature is not valid hex")?;
+
+    create_artifact_mac(key, wasm_hash, target, compatibility_hash, aot_bytes)
+        .verify_slice(&signature)
+        .map_err(|_| anyhow::anyhow!("AOT artifact signature is invalid"))
+}
+
+fn create_artifact_mac(
+    key: &str,
+    wasm_hash: &str,
+    target: &str,
+    compatibility_hash: &str,
+    aot_bytes: &[u8],
+) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(key.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(
+        format!(
+            "{wasm_hash}\n{target}\n{compatibility_hash}\n{}",
+            hash_bytes(aot_bytes)
+        )
+        .as_bytes(),
+    );
+    mac
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.is_ascii() || hex.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Interpolates the WASM hash and target into an AOT registry URL template,
+/// for example `https://registry.example.com/aot/{hash}.{target}.wasm_aot`.
+pub(super) fn get_aot_registry_url(url_template: &str, wasm_hash: &str, target: &str) -> String {
+    url_template
+        .replace(HASH_PLACEHOLDER, wasm_hash)
+        .replace(TARGET_PLACEHOLDER, target)
+}
+
+/// Parses an AOT registry URL, which must use `https` as the artifacts are native code.
+fn parse_aot_registry_url(url: &str) -> anyhow::Result<Url> {
+    let url = Url::parse(url).with_context(|| format!("Invalid AOT registry URL: {url}"))?;
+
+    if url.scheme() != "https" {
+        anyhow::bail!(
+            "AOT registry URLs must use https, but \"{url}\" uses \"{}\"",
+            url.scheme()
+        );
+    }
+
+    Ok(url)
+}
+
+/// Fetches an AOT compiled artifact from the registry, returning `None` if the
+/// registry does not contain the artifact.
+///
+/// The artifact is only returned if it was compiled for an engine with the given
+/// compatibility hash, and its signature is valid for the given key.
+pub(super) async fn fetch_verified_aot_bytes(
+    client: &reqwest::Client,
+    url: &str,
+    key: &str,
+    wasm_hash: &str,
+    target: &str,
+    compatibility_hash: &str,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    let url = parse_aot_registry_url(url)?;
+
+    let Some(aot_bytes) = fetch_bytes(client, url.clone()).await? else {
+        return Ok(None);
+    };
+
+    let artifact_compatibility_hash = fetch_text(client, &url, COMPATIBILITY_EXTENSION)
+        .await?
+        .context("AOT artifact has no compatibility file")?;
+    if artifact_compatibility_hash.trim() != compatibility_hash {
+        anyhow::bail!("AOT artifact was compiled for an incompatible engine configuration");
+    }
+
+    let signature = fetch_text(client, &url, SIGNATURE_EXTENSION)
+        .await?
+        .context("AOT artifact has no signature")?;
+    verify_aot_artifact_signature(
+        key,
+        wasm_hash,
+        target,
+        compatibility_hash,
+        &aot_bytes,
+        signature.trim(),
+    )?;
+
+    Ok(Some(aot_bytes))
+}
+
+async fn fetch_text(
+    client: &reqwest::Client,
+    artifact_url: &Url,
+    extension: &str,
+) -> anyhow::Result<Option<String>> {
+    let url = parse_aot_registry_url(&format!("{artifact_url}{extension}"))?;
+    let Some(bytes) = fetch_bytes(client, url.clone()).await? else {
+        return Ok(None);
+    };
+
+    String::from_utf8(bytes)
+        .map(Some)
+        .with_context(|| format!("{url} is not valid UTF-8"))
+}
+
+async fn fetch_bytes(client: &reqwest::Client, url: Url) -> anyhow::Result<Option<Vec<u8>>> {
+    let response = client.get(url.clone()).send().await?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    let response = response
+        .error_for_status()
+        .with_context(|| format!("Failed to fetch {url}"))?;
+
+    Ok(Some(response.bytes().await?.to_vec()))
+}
+
+/// Deserializes an AOT compiled artifact, verifying it is a component which
+/// is compatible with the engine.
+///
+/// Wasmtime checks the artifact was produced by the same Wasmtime version with
+/// compatible engine settings and target, and errors if not.
+/// Artifacts from the AOT registry must have been verified with
+/// `fetch_verified_aot_bytes` before calling this.
+pub(super) fn load_compatible_aot_component(
+    engine: &Engine,
+    aot_bytes: &[u8],
+) -> anyhow::Result<Component> {
+    match engine.detect_precompiled(aot_bytes) {
+        Some(Precompiled::Component) => {}
+        Some(Precompiled::Module) => {
+            anyhow::bail!("AOT artifact is a precompiled module rather than a component")
+        }
+        None => anyhow::bail!("AOT artifact is not a precompiled component"),
+    }
+
+    // SAFETY: Deserializing runs the artifact's native code, so it must come from a
+    // trusted source. Artifacts from the AOT registry are only passed here once their
+    // signature has been verified against the configured key, which ties them to
+    // this WASM hash, target and engine configuration.
+    unsafe { Component::deserialize(engine, aot_bytes) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_interpolate_aot_registry_url() {
+        assert_eq!(
+            get_aot_registry_url(
+                "https://registry.example.com/aot/{hash}.{target}.wasm_aot",
+                "abc123",
+                "x86_64-unknown-linux-gnu"
+            ),
+            "https://registry.example.com/aot/abc123.x86_64-unknown-linux-gnu.wasm_aot"
+        );
+    }
+
+    #[test]
+    fn it_should_only_accept_https_aot_registry_urls() {
+        assert!(parse_aot_registry_url("https://registry.example.com/aot/a.wasm_aot").is_ok());
+
+        for url in [
+            "http://registry.example.com/aot/a.wasm_aot",
+            "file:///aot/a.wasm_aot",
+            "aot/a.wasm_aot",
+        ] {
+            assert!(parse_aot_registry_url(url).is_err(), "{url}");
+        }
+    }
+
+    #[test]
+    fn it_should_verify_aot_artifact_signatures() {
+        let signature = sign_aot_artifact("key", "abc123", "x86_64", "compat", b"artifact");
+
+        assert!(
+            verify_aot_artifact_signature(
+                "key",
+                "abc123",
+                "x86_64",
+                "compat",
+                b"artifact",
+                &signature
+            )
+            .is_ok()
+        );
+
+        for (key, wasm_hash, target, compatibility_hash, aot_bytes) in [
+            (
+                "other",
+                "abc123",
+                "x86_64",
+                "compat",
+                b"artifact".as_slice(),
+            ),
+            ("key", "def456", "x86_64", "compat", b"artifact"),
+            ("key", "abc123", "aarch64", "compat", b"artifact"),
+            ("key", "abc123", "x86_64", "other", b"artifact"),
+            ("key", "abc123", "x86_64", "compat", b"tampered"),
+        ] {
+            assert!(
+                verify_aot_artifact_signature(
+                    key,
+                    wasm_hash,
+                    target,
+                    compatibility_hash,
+                    aot_bytes,
+                    &signature
+                )
+                .is_err()
+            );
+        }
+
+        assert!(
+            verify_aot_artifact_signature("key", "abc123", "x86_64", "compat", b"artifact", "zz")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn it_should_reject_artifacts_which_are_not_precompiled_components() {
+        let engine = crate::create_engine(None, &Default::default()).unwrap();
+
+        let result = load_compatible_aot_component(&engine, b"not an artifact");
+
+        assert!(result.is_err());
+    }
+}
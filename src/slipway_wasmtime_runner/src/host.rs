@@ -4,10 +4,15 @@ use std::{
     task::{Context, Poll},
 };
 
-use self::slipway_host::{BinResponse, RequestError, RequestOptions, ResolvedFont, TextResponse};
+use crate::streamed_output::{StreamedOutput, StreamedOutputParser};
+
+use self::slipway_host::{
+    BinResponse, CacheStatus, DecodedImage, PaginatedRequestOptions, Pagination, RequestError,
+    RequestOptions, ResolvedFont, TextResponse,
+};
 use bytes::Bytes;
 use slipway_engine::ComponentExecutionContext;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use wasmtime::*;
 use wasmtime_wasi::{
     IoView, OutputStream, Pollable, ResourceTable, StdoutStream, StreamResult, WasiCtx, WasiView,
@@ -19,6 +24,7 @@ use wasmtime_wasi::{
 // https://lib.rs/crates/wasmtime-wasi
 wasmtime::component::bindgen!({
     path: "../wit/latest",
+    world: "slipway",
     async: true
 });
 
@@ -26,6 +32,10 @@ pub struct SlipwayHost<'call, 'rig, 'runners> {
     execution_context: &'call ComponentExecutionContext<'call, 'rig, 'runners>,
     wasi_ctx: WasiCtx,
     wasi_table: ResourceTable,
+
+    /// Parses the output written by a component using the `run-streaming` entry point,
+    /// or `None` if the component is not currently streaming its output.
+    streamed_output: Option<StreamedOutputParser>,
 }
 
 impl<'call, 'rig, 'runners> SlipwayHost<'call, 'rig, 'runners> {
@@ -37,8 +47,28 @@ impl<'call, 'rig, 'runners> SlipwayHost<'call, 'rig, 'runners> {
             execution_context,
             wasi_ctx,
             wasi_table: ResourceTable::default(),
+            streamed_output: None,
         }
     }
+
+    /// Starts parsing the output written by the component.
+    pub fn begin_streamed_output(&mut self) {
+        let max_output_bytes = self
+            .execution_context
+            .rig_session_options()
+            .max_output_bytes;
+        self.streamed_output = Some(StreamedOutputParser::start(max_output_bytes));
+    }
+
+    /// Returns the output written by the component since `begin_streamed_output`
+    /// was called, and stops parsing output.
+    pub async fn end_streamed_output(&mut self) -> StreamedOutput {
+        let parser = self
+            .streamed_output
+            .take()
+            .unwrap_or_else(|| StreamedOutputParser::start(None));
+        parser.finish().await
+    }
 }
 
 impl IoView for SlipwayHost<'_, '_, '_> {
@@ -89,6 +119,19 @@ impl self::slipway_host::Host for SlipwayHost<'_, '_, '_> {
         })
     }
 
+    fn subset_font(
+        &mut self,
+        font: wasmtime::component::__internal::Vec<u8>,
+        text: wasmtime::component::__internal::String,
+    ) -> impl ::core::future::Future<
+        Output = Result<wasmtime::component::__internal::Vec<u8>, ComponentError>,
+    > + ::core::marker::Send {
+        Box::pin(async {
+            ::slipway_host::fonts::subset_font(self.execution_context, font, text)
+                .map_err(Into::into)
+        })
+    }
+
     fn log_trace(
         &mut self,
         message: wasmtime::component::__internal::String,
@@ -162,6 +205,20 @@ impl self::slipway_host::Host for SlipwayHost<'_, '_, '_> {
         }))
     }
 
+    fn fetch_pages(
+        &mut self,
+        url: wasmtime::component::__internal::String,
+        options: PaginatedRequestOptions,
+    ) -> impl ::core::future::Future<
+        Output = Result<wasmtime::component::__internal::String, RequestError>,
+    > + ::core::marker::Send {
+        Box::pin(AssertSend(async move {
+            ::slipway_host::fetch::fetch_pages_string(self.execution_context, &url, options.into())
+                .await
+                .map_err(Into::into)
+        }))
+    }
+
     fn run(
         &mut self,
         handle: wasmtime::component::__internal::String,
@@ -176,6 +233,23 @@ impl self::slipway_host::Host for SlipwayHost<'_, '_, '_> {
         }))
     }
 
+    fn run_batch(
+        &mut self,
+        handle: wasmtime::component::__internal::String,
+        inputs: wasmtime::component::__internal::Vec<wasmtime::component::__internal::String>,
+    ) -> impl ::core::future::Future<
+        Output = Result<
+            wasmtime::component::__internal::Vec<wasmtime::component::__internal::String>,
+            ComponentError,
+        >,
+    > + ::core::marker::Send {
+        Box::pin(AssertSend(async {
+            ::slipway_host::fetch::run_string_batch(self.execution_context, handle, inputs)
+                .await
+                .map_err(Into::into)
+        }))
+    }
+
     fn load_bin(
         &mut self,
         handle: wasmtime::component::__internal::String,
@@ -230,6 +304,48 @@ impl self::slipway_host::Host for SlipwayHost<'_, '_, '_> {
             ::slipway_host::bin::decode_bin(self.execution_context, text).map_err(Into::into)
         })
     }
+
+    fn decode_image(
+        &mut self,
+        bin: wasmtime::component::__internal::Vec<u8>,
+    ) -> impl ::core::future::Future<Output = Result<DecodedImage, ComponentError>> + ::core::marker::Send
+    {
+        Box::pin(async {
+            ::slipway_host::images::decode_image(self.execution_context, bin)
+                .await
+                .map(Into::into)
+                .map_err(Into::into)
+        })
+    }
+
+    fn encode_image(
+        &mut self,
+        image: DecodedImage,
+        format: wasmtime::component::__internal::String,
+    ) -> impl ::core::future::Future<
+        Output = Result<wasmtime::component::__internal::Vec<u8>, ComponentError>,
+    > + ::core::marker::Send {
+        Box::pin(async {
+            ::slipway_host::images::encode_image(self.execution_context, image.into(), format)
+                .await
+                .map_err(Into::into)
+        })
+    }
+
+    fn write_output(
+        &mut self,
+        chunk: wasmtime::component::__internal::String,
+    ) -> impl ::core::future::Future<Output = ()> + ::core::marker::Send {
+        Box::pin(async move {
+            match self.streamed_output.as_mut() {
+                Some(streamed_output) => streamed_output.write(chunk).await,
+                None => warn!(
+                    "Component \"{}\" wrote output without using the \"run-streaming\" entry point. The output was ignored.",
+                    self.execution_context.call_chain().component_handle_trail()
+                ),
+            }
+        })
+    }
 }
 
 impl From<::slipway_host::fetch::RequestError> for RequestError {
@@ -237,11 +353,7 @@ impl From<::slipway_host::fetch::RequestError> for RequestError {
         RequestError {
             message: e.message,
             inner: e.inner,
-            response: e.response.map(|r| TextResponse {
-                status_code: r.status_code,
-                headers: r.headers,
-                body: r.body,
-            }),
+            response: e.response.map(TextResponse::from),
         }
     }
 }
@@ -253,6 +365,30 @@ impl From<RequestOptions> for ::slipway_host::fetch::RequestOptions {
             method: opts.method,
             body: opts.body,
             timeout_ms: opts.timeout_ms,
+            max_redirects: opts.max_redirects,
+        }
+    }
+}
+
+impl From<PaginatedRequestOptions> for ::slipway_host::fetch::PaginatedRequestOptions {
+    fn from(opts: PaginatedRequestOptions) -> Self {
+        ::slipway_host::fetch::PaginatedRequestOptions {
+            request: opts.request.map(Into::into),
+            pagination: opts.pagination.into(),
+            items_pointer: opts.items_pointer,
+            max_pages: opts.max_pages,
+        }
+    }
+}
+
+impl From<Pagination> for ::slipway_host::fetch::Pagination {
+    fn from(pagination: Pagination) -> Self {
+        match pagination {
+            Pagination::LinkHeader => ::slipway_host::fetch::Pagination::LinkHeader,
+            Pagination::Cursor(cursor) => ::slipway_host::fetch::Pagination::Cursor {
+                cursor_pointer: cursor.cursor_pointer,
+                cursor_parameter: cursor.cursor_parameter,
+            },
         }
     }
 }
@@ -263,6 +399,7 @@ impl From<::slipway_host::fetch::BinResponse> for BinResponse {
             status_code: r.status_code,
             headers: r.headers,
             body: r.body,
+            cache_status: r.cache_status.into(),
         }
     }
 }
@@ -273,6 +410,37 @@ impl From<::slipway_host::fetch::TextResponse> for TextResponse {
             status_code: r.status_code,
             headers: r.headers,
             body: r.body,
+            cache_status: r.cache_status.into(),
+        }
+    }
+}
+
+impl From<::slipway_host::fetch::CacheStatus> for CacheStatus {
+    fn from(s: ::slipway_host::fetch::CacheStatus) -> Self {
+        match s {
+            ::slipway_host::fetch::CacheStatus::Uncached => CacheStatus::Uncached,
+            ::slipway_host::fetch::CacheStatus::NotModified => CacheStatus::NotModified,
+            ::slipway_host::fetch::CacheStatus::Replayed => CacheStatus::Replayed,
+        }
+    }
+}
+
+impl From<::slipway_host::images::DecodedImage> for DecodedImage {
+    fn from(i: ::slipway_host::images::DecodedImage) -> Self {
+        DecodedImage {
+            width: i.width,
+            height: i.height,
+            data: i.data,
+        }
+    }
+}
+
+impl From<DecodedImage> for ::slipway_host::images::DecodedImage {
+    fn from(i: DecodedImage) -> Self {
+        ::slipway_host::images::DecodedImage {
+            width: i.width,
+            height: i.height,
+            data: i.data,
         }
     }
 }
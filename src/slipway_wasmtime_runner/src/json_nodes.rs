@@ -0,0 +1,245 @@
+use wasmtime::component::{ComponentType, Lift, Lower};
+
+/// The maximum nesting depth of JSON nodes returned by a component,
+/// matching the recursion limit `serde_json` applies when parsing JSON strings.
+const MAX_DEPTH: usize = 128;
+
+/// A node in a JSON value, matching the `json-node` variant in the WIT.
+///
+/// The component model does not support recursive types, so a JSON value is passed
+/// as a flat list of nodes where the first node is the root and arrays and objects
+/// refer to their children by index.
+#[derive(ComponentType, Lift, Lower, Clone, Debug, PartialEq)]
+#[component(variant)]
+pub enum JsonNode {
+    #[component(name = "null")]
+    Null,
+    #[component(name = "boolean")]
+    Boolean(bool),
+    #[component(name = "integer")]
+    Integer(i64),
+    #[component(name = "unsigned")]
+    Unsigned(u64),
+    #[component(name = "float")]
+    Float(f64),
+    #[component(name = "text")]
+    Text(String),
+    #[component(name = "array")]
+    Array(Vec<u32>),
+    #[component(name = "object")]
+    Object(Vec<(String, u32)>),
+}
+
+/// Flattens the JSON value into a list of nodes, with the root as the first node.
+pub fn json_to_nodes(value: &serde_json::Value) -> Vec<JsonNode> {
+    let mut nodes = Vec::new();
+    push_node(value, &mut nodes);
+    nodes
+}
+
+/// Pushes the node for the value, followed by the nodes of its children,
+/// and returns the index of the value's node.
+fn push_node(value: &serde_json::Value, nodes: &mut Vec<JsonNode>) -> u32 {
+    let index = nodes.len();
+
+    // Push a placeholder so that children are added after their parent.
+    nodes.push(JsonNode::Null);
+
+    let node = match value {
+        serde_json::Value::Null => JsonNode::Null,
+        serde_json::Value::Bool(b) => JsonNode::Boolean(*b),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                JsonNode::Integer(i)
+            } else if let Some(u) = n.as_u64() {
+                JsonNode::Unsigned(u)
+            } else {
+                JsonNode::Float(n.as_f64().unwrap_or_default())
+            }
+        }
+        serde_json::Value::String(s) => JsonNode::Text(s.clone()),
+        serde_json::Value::Array(items) => {
+            JsonNode::Array(items.iter().map(|item| push_node(item, nodes)).collect())
+        }
+        serde_json::Value::Object(map) => JsonNode::Object(
+            map.iter()
+                .map(|(key, item)| (key.clone(), push_node(item, nodes)))
+                .collect(),
+        ),
+    };
+
+    nodes[index] = node;
+    u32::try_from(index).expect("JSON value should have fewer than u32::MAX nodes")
+}
+
+/// Reconstructs the JSON value from a list of nodes.
+///
+/// Returns an error if the list is empty, a child index is out of range, a
+/// child does not come after its parent, a node is the child of more than one
+/// parent, or the nodes are nested too deeply.
+///
+/// Together these guarantee the nodes form a tree, so the reconstructed value
+/// is never larger than the list of nodes.
+pub fn nodes_to_json(nodes: &[JsonNode]) -> Result<serde_json::Value, String> {
+    if nodes.is_empty() {
+        return Err("The list of JSON nodes was empty.".to_string());
+    }
+
+    let mut referenced = vec![false; nodes.len()];
+    referenced[0] = true;
+
+    node_to_json(nodes, &mut referenced, 0, 0)
+}
+
+/// Returns a lower bound on the length of the JSON the nodes serialize to,
+/// without reconstructing the JSON value. This allows outputs which are too
+/// large to be rejected before they are reconstructed.
+pub fn min_serialized_len(nodes: &[JsonNode]) -> usize {
+    nodes
+        .iter()
+        .map(|node| match node {
+            JsonNode::Null => "null".len(),
+            JsonNode::Boolean(true) => "true".len(),
+            JsonNode::Boolean(false) => "false".len(),
+            JsonNode::Integer(_) | JsonNode::Unsigned(_) | JsonNode::Float(_) => 1,
+            // Quotes plus the unescaped text.
+            JsonNode::Text(s) => s.len() + 2,
+            // Brackets plus a separator between each item.
+            JsonNode::Array(items) => 2 + items.len().saturating_sub(1),
+            // Braces plus a quoted key, colon and separator for each entry.
+            JsonNode::Object(entries) => {
+                2 + entries.len().saturating_sub(1)
+                    + entries.iter().map(|(key, _)| key.len() + 3).sum::<usize>()
+            }
+        })
+        .sum()
+}
+
+fn node_to_json(
+    nodes: &[JsonNode],
+    referenced: &mut [bool],
+    index: usize,
+    depth: usize,
+) -> Result<serde_json::Value, String> {
+    if depth > MAX_DEPTH {
+        return Err(format!("JSON nodes are nested more than {MAX_DEPTH} deep."));
+    }
+
+    let mut child = |child_index: u32| -> Result<serde_json::Value, String> {
+        let child_index = child_index as usize;
+        if child_index <= index || child_index >= nodes.len() {
+            return Err(format!(
+                "JSON node {index} has an invalid child index {child_index}."
+            ));
+        }
+        if std::mem::replace(&mut referenced[child_index], true) {
+            return Err(format!(
+                "JSON node {child_index} is referenced by more than one parent."
+            ));
+        }
+        node_to_json(nodes, referenced, child_index, depth + 1)
+    };
+
+    Ok(match &nodes[index] {
+        JsonNode::Null => serde_json::Value::Null,
+        JsonNode::Boolean(b) => serde_json::Value::Bool(*b),
+        JsonNode::Integer(i) => serde_json::Value::from(*i),
+        JsonNode::Unsigned(u) => serde_json::Value::from(*u),
+        JsonNode::Float(f) => serde_json::Number::from_f64(*f)
+            .map(serde_json::Value::Number)
+            .ok_or_else(|| format!("JSON node {index} is not a finite number."))?,
+        JsonNode::Text(s) => serde_json::Value::String(s.clone()),
+        JsonNode::Array(items) => serde_json::Value::Array(
+            items
+                .iter()
+                .map(|&item| child(item))
+                .collect::<Result<_, _>>()?,
+        ),
+        JsonNode::Object(entries) => serde_json::Value::Object(
+            entries
+                .iter()
+                .map(|(key, item)| Ok((key.clone(), child(*item)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn it_should_round_trip_json_values() {
+        let value = json!({
+            "null": null,
+            "bool": true,
+            "int": -5,
+            "big": u64::MAX,
+            "float": 1.5,
+            "text": "hello",
+            "array": [1, [2, 3], { "nested": [] }],
+            "empty": {}
+        });
+
+        let nodes = json_to_nodes(&value);
+
+        assert!(matches!(nodes[0], JsonNode::Object(_)));
+        assert_eq!(nodes_to_json(&nodes).unwrap(), value);
+    }
+
+    #[test]
+    fn it_should_reject_invalid_child_indexes() {
+        assert!(nodes_to_json(&[]).is_err());
+        assert!(nodes_to_json(&[JsonNode::Array(vec![0])]).is_err());
+        assert!(nodes_to_json(&[JsonNode::Array(vec![1])]).is_err());
+        assert!(nodes_to_json(&[JsonNode::Float(f64::NAN)]).is_err());
+
+        let too_deep: Vec<_> = (1..=MAX_DEPTH as u32 + 2)
+            .map(|child| JsonNode::Array(vec![child]))
+            .chain(std::iter::once(JsonNode::Null))
+            .collect();
+        assert!(nodes_to_json(&too_deep).is_err());
+        assert_eq!(
+            nodes_to_json(&[JsonNode::Array(vec![1]), JsonNode::Null]).unwrap(),
+            json!([null])
+        );
+    }
+
+    #[test]
+    fn it_should_reject_nodes_with_more_than_one_parent() {
+        // Without this check each level would double the size of the output.
+        let shared: Vec<_> = (1..=64u32)
+            .map(|child| JsonNode::Array(vec![child, child]))
+            .chain(std::iter::once(JsonNode::Null))
+            .collect();
+        assert!(nodes_to_json(&shared).is_err());
+
+        assert!(
+            nodes_to_json(&[
+                JsonNode::Array(vec![1, 2]),
+                JsonNode::Array(vec![2]),
+                JsonNode::Null
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn it_should_not_exceed_serialized_len() {
+        let value = json!({
+            "null": null,
+            "bool": false,
+            "int": -5,
+            "float": 1.5,
+            "text": "hello",
+            "array": [1, [2, 3], { "nested": [] }],
+            "empty": {}
+        });
+
+        let nodes = json_to_nodes(&value);
+
+        assert!(min_serialized_len(&nodes) <= serde_json::to_string(&value).unwrap().len());
+    }
+}
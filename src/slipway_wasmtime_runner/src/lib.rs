@@ -1,33 +1,86 @@
+mod aot_compatibility;
+mod aot_registry;
 mod host;
+mod json_nodes;
 mod run_component_wasm;
+mod streamed_output;
 
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use anyhow::Context;
+pub use aot_registry::get_aot_registry_files;
 use async_trait::async_trait;
-use run_component_wasm::WasmData;
-pub use run_component_wasm::run_component_wasm;
+pub use json_nodes::{JsonNode, json_to_nodes, nodes_to_json};
+use run_component_wasm::{WasmData, load_component, verify_component, verify_component_wasm};
+pub use run_component_wasm::{run_component_wasm, run_component_wasm_batch};
+use serde::{Deserialize, Serialize};
 use slipway_engine::{
     ComponentExecutionContext, ComponentFiles, ComponentRunner, RunComponentError,
-    SlipwayReference, TryAotCompileComponentResult, TryRunComponentResult,
+    SlipwayReference, TryAotCompileComponentResult, TryRunBatchComponentResult,
+    TryRunComponentResult, TryVerifyComponentResult,
 };
 use slipway_host::{SLIPWAY_COMPONENT_WASM_FILE_NAME, hash_bytes};
 use tracing::{debug, info, warn};
-use wasmtime::{Config, Engine};
+use wasmtime::{Config, Engine, OptLevel, component::Component};
 
 pub const WASMTIME_COMPONENT_RUNNER_IDENTIFIER: &str = "wasmtime";
 
+/// Runs WASM components using Wasmtime.
+///
+/// Compiled components are kept in memory, keyed by the hash of their WASM bytes,
+/// so each component is only compiled once per runner. Clones share the same
+/// engine and compiled components, so a runner can be reused across rig runs.
+#[derive(Clone)]
 pub struct WasmComponentRunner {
     engine: Engine,
+    options: WasmComponentRunnerOptions,
+    compiled_components: Arc<Mutex<HashMap<String, Component>>>,
 }
 
-fn create_engine(target: Option<&str>) -> anyhow::Result<Engine> {
+/// Options which affect how WASM components are compiled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WasmComponentRunnerOptions {
+    pub opt_level: WasmOptLevel,
+}
+
+/// The Cranelift optimization level used when compiling WASM components.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmOptLevel {
+    /// No optimizations, which gives the fastest compilation.
+    None,
+
+    /// Optimize for execution speed. This is the Wasmtime default.
+    #[default]
+    Speed,
+
+    /// Optimize for execution speed and code size.
+    SpeedAndSize,
+}
+
+impl From<WasmOptLevel> for OptLevel {
+    fn from(opt_level: WasmOptLevel) -> Self {
+        match opt_level {
+            WasmOptLevel::None => OptLevel::None,
+            WasmOptLevel::Speed => OptLevel::Speed,
+            WasmOptLevel::SpeedAndSize => OptLevel::SpeedAndSize,
+        }
+    }
+}
+
+fn create_engine(
+    target: Option<&str>,
+    options: &WasmComponentRunnerOptions,
+) -> anyhow::Result<Engine> {
     let mut config = Config::new();
 
     config.async_support(true);
+    config.cranelift_opt_level(options.opt_level.into());
 
     if let Some(target) = target {
         config.target(target)?;
@@ -38,9 +91,87 @@ fn create_engine(target: Option<&str>) -> anyhow::Result<Engine> {
 
 impl WasmComponentRunner {
     pub fn new() -> Self {
-        let engine =
-            create_engine(None).expect("Should be able to create Wasmtime engine with no target");
-        Self { engine }
+        Self::try_new().expect("Should be able to create Wasmtime engine with no target")
+    }
+
+    pub fn try_new() -> anyhow::Result<Self> {
+        Self::try_new_with_options(WasmComponentRunnerOptions::default())
+    }
+
+    pub fn try_new_with_options(options: WasmComponentRunnerOptions) -> anyhow::Result<Self> {
+        let engine = create_engine(None, &options)?;
+        Ok(Self {
+            engine,
+            options,
+            compiled_components: Arc::default(),
+        })
+    }
+
+    /// Compiles the component and checks that the host can satisfy its imports,
+    /// keeping the compiled component in memory so that the first run doesn't
+    /// need to compile it. The AOT compiled file is used if it exists in `aot_path`.
+    ///
+    /// Returns `false` if the component has no WASM file.
+    pub async fn warm_up(
+        &self,
+        component_reference: &SlipwayReference,
+        files: Arc<ComponentFiles>,
+        aot_path: Option<&Path>,
+    ) -> Result<bool, RunComponentError> {
+        let maybe_wasm_bytes = files.try_get_bin(SLIPWAY_COMPONENT_WASM_FILE_NAME).await?;
+
+        let Some(wasm_bytes) = maybe_wasm_bytes else {
+            return Ok(false);
+        };
+
+        let wasm_hash = hash_bytes(&wasm_bytes);
+        if self.get_compiled_component(&wasm_hash).is_some() {
+            return Ok(true);
+        }
+
+        let aot_bytes = match aot_path {
+            Some(aot_path) => self
+                .load_local_aot_bytes(&wasm_bytes, aot_path)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to read AOT compiled file for WASM component: {component_reference}"
+                    )
+                })?,
+            None => None,
+        };
+
+        let wasm_data = match aot_bytes {
+            Some(aot_bytes) => WasmData::Aot(aot_bytes),
+            None => WasmData::Wasm(wasm_bytes),
+        };
+
+        let engine = self.engine.clone();
+        let component = tokio::task::spawn_blocking(move || {
+            let component = load_component(wasm_data, &engine)?;
+            verify_component(&component, &engine)?;
+            anyhow::Ok(component)
+        })
+        .await
+        .with_context(|| format!("Failed to warm up component \"{component_reference}\"."))??;
+
+        self.add_compiled_component(wasm_hash, component);
+
+        Ok(true)
+    }
+
+    fn get_compiled_component(&self, wasm_hash: &str) -> Option<Component> {
+        self.lock_compiled_components().get(wasm_hash).cloned()
+    }
+
+    fn add_compiled_component(&self, wasm_hash: String, component: Component) {
+        self.lock_compiled_components().insert(wasm_hash, component);
+    }
+
+    fn lock_compiled_components(&self) -> std::sync::MutexGuard<'_, HashMap<String, Component>> {
+        self.compiled_components
+            .lock()
+            .expect("should be able to lock compiled components")
     }
 }
 
@@ -61,6 +192,7 @@ impl ComponentRunner for WasmComponentRunner {
         component_reference: &SlipwayReference,
         aot_path: &Path,
         target: Option<&str>,
+        force: bool,
         files: Arc<ComponentFiles>,
     ) -> Result<TryAotCompileComponentResult, RunComponentError> {
         let maybe_wasm_bytes = files.try_get_bin(SLIPWAY_COMPONENT_WASM_FILE_NAME).await?;
@@ -71,39 +203,52 @@ impl ComponentRunner for WasmComponentRunner {
 
         let aot_bytes_path = get_aot_bytes_path(aot_path, &wasm_bytes);
 
-        let target = target.map(|t| t.to_string());
-        let aot_compiled_bytes = tokio::task::spawn_blocking(move || {
-            let current_host = format!("{}", target_lexicon::Triple::host());
-            let target = if let Some(target) = target {
-                if target == current_host {
-                    info!("Specified AOT target matches host: {target}");
-                } else {
-                    warn!(
-                        "Specified AOT target \"{target}\" does not match host \"{current_host}\"."
-                    );
-                }
-                target
+        let current_host = format!("{}", target_lexicon::Triple::host());
+        let target = if let Some(target) = target {
+            if target == current_host {
+                info!("Specified AOT target matches host: {target}");
             } else {
-                info!("No AOT target specified, using host: {current_host}");
+                warn!("Specified AOT target \"{target}\" does not match host \"{current_host}\".");
+            }
+            target.to_string()
+        } else {
+            info!("No AOT target specified, using host: {current_host}");
 
-                // We explicitly set the current host, as passing in None does not seem to always produce
-                // the expected result. In particular, on fly.io we had issues with the produced
-                // AOT file not being runnable on the final host.
-                current_host
-            };
+            // We explicitly set the current host, as passing in None does not seem to always produce
+            // the expected result. In particular, on fly.io we had issues with the produced
+            // AOT file not being runnable on the final host.
+            current_host
+        };
 
-            let engine = create_engine(Some(&target))?;
-            engine.precompile_component(&wasm_bytes)
-        })
-        .await
-        .with_context(|| {
-            format!(
-                "Failed to AOT compile component \"{}\".",
-                component_reference
-            )
-        })??;
+        let engine = create_engine(Some(&target), &self.options).with_context(|| {
+            format!("Failed to create Wasmtime engine for AOT target \"{target}\".")
+        })?;
+        let compatibility_hash =
+            aot_compatibility::get_engine_compatibility_hash(&engine, self.options.opt_level);
+
+        if !force
+            && aot_compatibility::is_aot_artifact_up_to_date(&aot_bytes_path, &compatibility_hash)
+                .await
+        {
+            info!("AOT compiled file for component \"{component_reference}\" is up to date.");
+            return Ok(TryAotCompileComponentResult::UpToDate {
+                target,
+                path: aot_bytes_path,
+            });
+        }
 
-        tokio::fs::write(aot_bytes_path, aot_compiled_bytes)
+        let aot_compiled_bytes =
+            tokio::task::spawn_blocking(move || engine.precompile_component(&wasm_bytes))
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to AOT compile component \"{}\".",
+                        component_reference
+                    )
+                })??;
+
+        let bytes_written = aot_compiled_bytes.len() as u64;
+        tokio::fs::write(&aot_bytes_path, aot_compiled_bytes)
             .await
             .with_context(|| {
                 format!(
@@ -112,7 +257,40 @@ impl ComponentRunner for WasmComponentRunner {
                 )
             })?;
 
-        Ok(TryAotCompileComponentResult::Compiled)
+        let compatibility_path = aot_compatibility::get_aot_compatibility_path(&aot_bytes_path);
+        tokio::fs::write(&compatibility_path, compatibility_hash)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to write AOT compatibility file for component \"{}\".",
+                    component_reference
+                )
+            })?;
+
+        Ok(TryAotCompileComponentResult::Compiled {
+            target,
+            path: aot_bytes_path,
+            bytes_written,
+        })
+    }
+
+    async fn verify(
+        &self,
+        component_reference: &SlipwayReference,
+        files: Arc<ComponentFiles>,
+    ) -> Result<TryVerifyComponentResult, RunComponentError> {
+        let maybe_wasm_bytes = files.try_get_bin(SLIPWAY_COMPONENT_WASM_FILE_NAME).await?;
+
+        let Some(wasm_bytes) = maybe_wasm_bytes else {
+            return Ok(TryVerifyComponentResult::CannotVerify);
+        };
+
+        let engine = self.engine.clone();
+        tokio::task::spawn_blocking(move || verify_component_wasm(&wasm_bytes, &engine))
+            .await
+            .with_context(|| format!("Failed to verify component \"{component_reference}\"."))??;
+
+        Ok(TryVerifyComponentResult::Verified)
     }
 
     async fn run<'call>(
@@ -121,60 +299,335 @@ impl ComponentRunner for WasmComponentRunner {
         context: &'call ComponentExecutionContext<'call, '_, '_>,
     ) -> Result<TryRunComponentResult, RunComponentError> {
         let maybe_wasm_bytes = context
-            .files
+            .files()
+            .try_get_bin(SLIPWAY_COMPONENT_WASM_FILE_NAME)
+            .await?;
+
+        let Some(wasm_bytes) = maybe_wasm_bytes else {
+            return Ok(TryRunComponentResult::CannotRun {
+                reason: Some(no_wasm_file_reason()),
+            });
+        };
+
+        let (component, load_duration) = self.get_component(wasm_bytes, context).await?;
+
+        let mut run_result =
+            run_component_wasm(input, WasmData::Component(component), &self.engine, context)
+                .await?;
+        run_result.metadata.prepare_component_duration += load_duration;
+
+        Ok(TryRunComponentResult::Ran { result: run_result })
+    }
+
+    async fn run_batch<'call>(
+        &self,
+        inputs: &[serde_json::Value],
+        context: &'call ComponentExecutionContext<'call, '_, '_>,
+    ) -> Result<TryRunBatchComponentResult, RunComponentError> {
+        let maybe_wasm_bytes = context
+            .files()
             .try_get_bin(SLIPWAY_COMPONENT_WASM_FILE_NAME)
             .await?;
 
         let Some(wasm_bytes) = maybe_wasm_bytes else {
-            return Ok(TryRunComponentResult::CannotRun);
+            return Ok(TryRunBatchComponentResult::CannotRun {
+                reason: Some(no_wasm_file_reason()),
+            });
         };
 
-        let wasm_data = if let Some(aot_path) = &context.rig_session_options.aot_path {
-            let aot_bytes_path = get_aot_bytes_path(aot_path, &wasm_bytes);
-            if tokio::fs::try_exists(aot_bytes_path.clone())
+        let (component, load_duration) = self.get_component(wasm_bytes, context).await?;
+
+        let mut results = run_component_wasm_batch(
+            inputs,
+            WasmData::Component(component),
+            &self.engine,
+            context,
+        )
+        .await?;
+        if let Some(first) = results.first_mut() {
+            first.metadata.prepare_component_duration += load_duration;
+        }
+
+        Ok(TryRunBatchComponentResult::Ran { results })
+    }
+}
+
+impl WasmComponentRunner {
+    /// Returns the compiled component, compiling it and keeping it in memory if
+    /// this runner hasn't compiled it before. Also returns the time taken to load
+    /// the component, so that it can be included in the run metadata.
+    async fn get_component(
+        &self,
+        wasm_bytes: Arc<Vec<u8>>,
+        context: &ComponentExecutionContext<'_, '_, '_>,
+    ) -> Result<(Component, Duration), RunComponentError> {
+        let wasm_hash = hash_bytes(&wasm_bytes);
+        if let Some(component) = self.get_compiled_component(&wasm_hash) {
+            debug!(
+                "Using in-memory compiled WASM component: {}",
+                context.component_reference()
+            );
+            return Ok((component, Duration::ZERO));
+        }
+
+        let load_start = Instant::now();
+        let wasm_data = self.get_wasm_data(wasm_bytes, context).await?;
+        let component = load_component(wasm_data, &self.engine)?;
+        self.add_compiled_component(wasm_hash, component.clone());
+
+        Ok((component, load_start.elapsed()))
+    }
+
+    /// Returns the AOT compiled component if one is available, either locally or
+    /// from the AOT registry, otherwise returns the WASM to be JIT compiled.
+    async fn get_wasm_data(
+        &self,
+        wasm_bytes: Arc<Vec<u8>>,
+        context: &ComponentExecutionContext<'_, '_, '_>,
+    ) -> Result<WasmData, RunComponentError> {
+        let options = context.rig_session_options();
+
+        if let Some(aot_path) = &options.aot_path {
+            if let Some(aot_bytes) = self
+                .load_local_aot_bytes(&wasm_bytes, aot_path)
                 .await
                 .with_context(|| {
                     format!(
-                        "Failed to check if AOT compiled file exists for WASM component: {}",
-                        context.component_reference
+                        "Failed to read AOT compiled file for WASM component: {}",
+                        context.component_reference()
                     )
                 })?
             {
-                let aot_bytes = tokio::fs::read(&aot_bytes_path).await.with_context(|| {
-                    format!(
-                        "Failed to read AOT compiled file for WASM component: {}",
-                        context.component_reference
-                    )
-                })?;
-
                 debug!(
                     "Using AOT compiled WASM component: {}",
-                    context.component_reference
+                    context.component_reference()
                 );
 
-                WasmData::Aot(aot_bytes)
-            } else {
+                return Ok(WasmData::Aot(aot_bytes));
+            }
+
+            if options.aot_registry_url.is_none() {
                 warn!(
                     "AOT compiled file not found for WASM component: {}",
-                    context.component_reference
+                    context.component_reference()
                 );
-                WasmData::Wasm(Arc::clone(&wasm_bytes))
             }
-        } else {
-            debug!(
-                "JIT compiling WASM component: {}",
-                context.component_reference
+        }
+
+        if let Some(aot_registry_url) = &options.aot_registry_url {
+            let target = format!("{}", target_lexicon::Triple::host());
+            let url = aot_registry::get_aot_registry_url(
+                aot_registry_url,
+                &hash_bytes(&wasm_bytes),
+                &target,
+            );
+
+            match self
+                .fetch_aot_component(&url, &target, &wasm_bytes, context)
+                .await
+            {
+                Ok(Some(component)) => {
+                    debug!(
+                        "Using AOT compiled WASM component from registry: {}",
+                        context.component_reference()
+                    );
+                    return Ok(WasmData::Component(component));
+                }
+                Ok(None) => {
+                    warn!(
+                        "AOT compiled file not found in registry for WASM component: {} ({url})",
+                        context.component_reference()
+                    );
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to use AOT compiled file from registry for WASM component: {} ({url})\n{e:#}",
+                        context.component_reference()
+                    );
+                }
+            }
+        }
+
+        debug!(
+            "JIT compiling WASM component: {}",
+            context.component_reference()
+        );
+        Ok(WasmData::Wasm(wasm_bytes))
+    }
+
+    /// Returns the locally AOT compiled artifact for the WASM, or `None` if there isn't one.
+    ///
+    /// An artifact compiled for a different engine configuration, such as a different
+    /// optimization level, is recompiled and replaced.
+    async fn load_local_aot_bytes(
+        &self,
+        wasm_bytes: &Arc<Vec<u8>>,
+        aot_path: &Path,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        let aot_bytes_path = get_aot_bytes_path(aot_path, wasm_bytes);
+        if !tokio::fs::try_exists(&aot_bytes_path).await? {
+            return Ok(None);
+        }
+
+        let compatibility_hash =
+            aot_compatibility::get_engine_compatibility_hash(&self.engine, self.options.opt_level);
+
+        if aot_compatibility::is_aot_artifact_up_to_date(&aot_bytes_path, &compatibility_hash).await
+        {
+            return Ok(Some(tokio::fs::read(&aot_bytes_path).await?));
+        }
+
+        warn!(
+            "AOT compiled file {} was compiled for a different engine configuration. Recompiling.",
+            aot_bytes_path.display()
+        );
+
+        let engine = self.engine.clone();
+        let wasm_bytes = Arc::clone(wasm_bytes);
+        let aot_bytes =
+            tokio::task::spawn_blocking(move || engine.precompile_component(&wasm_bytes)).await??;
+
+        // Failing to replace the artifact is only a warning, as it has been compiled.
+        let compatibility_path = aot_compatibility::get_aot_compatibility_path(&aot_bytes_path);
+        let result = match tokio::fs::write(&aot_bytes_path, &aot_bytes).await {
+            Ok(()) => tokio::fs::write(&compatibility_path, &compatibility_hash).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!(
+                "Failed to cache AOT compiled file at {}: {e}",
+                aot_bytes_path.display()
+            );
+        }
+
+        Ok(Some(aot_bytes))
+    }
+
+    async fn fetch_aot_component(
+        &self,
+        url: &str,
+        target: &str,
+        wasm_bytes: &[u8],
+        context: &ComponentExecutionContext<'_, '_, '_>,
+    ) -> anyhow::Result<Option<wasmtime::component::Component>> {
+        let options = context.rig_session_options();
+
+        let Some(key) = options.aot_registry_key.as_deref() else {
+            anyhow::bail!(
+                "An AOT registry key is required to verify artifacts from the AOT registry"
             );
-            WasmData::Wasm(Arc::clone(&wasm_bytes))
         };
 
-        let run_result = run_component_wasm(input, wasm_data, &self.engine, context).await?;
+        let compatibility_hash =
+            aot_compatibility::get_engine_compatibility_hash(&self.engine, self.options.opt_level);
 
-        Ok(TryRunComponentResult::Ran { result: run_result })
+        let client = options
+            .components_http_client
+            .client()
+            .map_err(|e| anyhow::anyhow!("Failed to create HTTP client: {e}"))?;
+
+        let Some(aot_bytes) = aot_registry::fetch_verified_aot_bytes(
+            client,
+            url,
+            key,
+            &hash_bytes(wasm_bytes),
+            target,
+            &compatibility_hash,
+        )
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let component = aot_registry::load_compatible_aot_component(&self.engine, &aot_bytes)?;
+
+        // Cache the artifact locally so subsequent runs don't need to fetch it.
+        if let Some(aot_path) = &options.aot_path {
+            let aot_bytes_path = get_aot_bytes_path(aot_path, wasm_bytes);
+            let compatibility_path = aot_compatibility::get_aot_compatibility_path(&aot_bytes_path);
+            let result = match tokio::fs::write(&aot_bytes_path, &aot_bytes).await {
+                Ok(()) => tokio::fs::write(&compatibility_path, &compatibility_hash).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!(
+                    "Failed to cache AOT compiled file at {}: {e}",
+                    aot_bytes_path.display()
+                );
+            }
+        }
+
+        Ok(Some(component))
     }
 }
 
+fn no_wasm_file_reason() -> String {
+    format!("No \"{SLIPWAY_COMPONENT_WASM_FILE_NAME}\" file found.")
+}
+
 fn get_aot_bytes_path(aot_path: &Path, wasm_bytes: &[u8]) -> PathBuf {
     let wasm_bytes_hash = hash_bytes(wasm_bytes);
     aot_path.join(format!("{wasm_bytes_hash}.wasm_aot"))
 }
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+
+    use super::*;
+
+    #[test]
+    fn it_should_share_compiled_components_between_clones() {
+        let runner = WasmComponentRunner::new();
+        let component = Component::new(&runner.engine, "(component)").unwrap();
+
+        runner
+            .clone()
+            .add_compiled_component("hash".to_string(), component);
+
+        assert!(runner.get_compiled_component("hash").is_some());
+        assert!(runner.get_compiled_component("other").is_none());
+    }
+
+    #[slipway_test_async]
+    async fn it_should_recompile_local_aot_artifacts_for_other_engine_configurations() {
+        let runner = WasmComponentRunner::new();
+        let dir = tempfile::tempdir().unwrap();
+        let wasm_bytes = Arc::new(b"(component)".to_vec());
+        let aot_bytes_path = get_aot_bytes_path(dir.path(), &wasm_bytes);
+
+        assert!(
+            runner
+                .load_local_aot_bytes(&wasm_bytes, dir.path())
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        std::fs::write(&aot_bytes_path, b"stale").unwrap();
+        std::fs::write(
+            aot_compatibility::get_aot_compatibility_path(&aot_bytes_path),
+            "other",
+        )
+        .unwrap();
+
+        let aot_bytes = runner
+            .load_local_aot_bytes(&wasm_bytes, dir.path())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_ne!(aot_bytes, b"stale");
+        assert_eq!(std::fs::read(&aot_bytes_path).unwrap(), aot_bytes);
+        assert!(
+            aot_compatibility::is_aot_artifact_up_to_date(
+                &aot_bytes_path,
+                &aot_compatibility::get_engine_compatibility_hash(
+                    &runner.engine,
+                    runner.options.opt_level
+                )
+            )
+            .await
+        );
+    }
+}
@@ -1,15 +1,51 @@
 use std::{sync::Arc, time::Instant};
 
-use crate::host::{OutputObserverStream, OutputObserverType, Slipway, SlipwayHost};
+use anyhow::Context;
+
+use crate::host::{
+    OutputObserverStream, OutputObserverType, Slipway, SlipwayHost,
+    slipway::component::types::ComponentError,
+};
+use crate::json_nodes::{JsonNode, json_to_nodes, min_serialized_len, nodes_to_json};
+use crate::streamed_output::StreamedOutput;
 use slipway_engine::{
     ComponentExecutionContext, RunComponentError, RunComponentResult, RunMetadata,
+    ensure_output_value_within_limit, ensure_output_within_limit,
 };
+use wasmtime::component::TypedFunc;
 use wasmtime::*;
 use wasmtime_wasi::WasiCtxBuilder;
 
 pub enum WasmData {
     Wasm(Arc<Vec<u8>>),
     Aot(Vec<u8>),
+    Component(wasmtime::component::Component),
+}
+
+/// The name of the default entry point which takes and returns JSON strings.
+const RUN_EXPORT_NAME: &str = "run";
+
+/// The name of the alternative entry point which takes and returns typed JSON nodes.
+const RUN_TYPED_EXPORT_NAME: &str = "run-typed";
+
+/// The name of the alternative entry point which takes a JSON string and writes
+/// its output incrementally using the `write-output` host function.
+const RUN_STREAMING_EXPORT_NAME: &str = "run-streaming";
+
+const ENTRY_POINT_EXPORT_NAMES: [&str; 3] = [
+    RUN_EXPORT_NAME,
+    RUN_TYPED_EXPORT_NAME,
+    RUN_STREAMING_EXPORT_NAME,
+];
+
+type RunTypedFunc = TypedFunc<(Vec<JsonNode>,), (Result<Vec<JsonNode>, ComponentError>,)>;
+
+type RunStreamingFunc = TypedFunc<(String,), (Result<(), ComponentError>,)>;
+
+/// The serialized input passed to whichever entry point the component exports.
+enum PreparedInput {
+    Json(String),
+    Typed(Vec<JsonNode>),
 }
 
 pub async fn run_component_wasm(
@@ -18,15 +54,70 @@ pub async fn run_component_wasm(
     engine: &Engine,
     execution_context: &ComponentExecutionContext<'_, '_, '_>,
 ) -> Result<RunComponentResult, RunComponentError> {
-    let prepare_input_start = Instant::now();
+    let mut results = run_component_wasm_batch(
+        std::slice::from_ref(input),
+        wasm_data,
+        engine,
+        execution_context,
+    )
+    .await?;
 
-    // Serialize the input JSON to a vector of bytes
-    let input_string = serde_json::to_string(input)
-        .map_err(|source| RunComponentError::SerializeInputFailed { source })?;
+    Ok(results
+        .pop()
+        .expect("batch should return one result per input"))
+}
 
-    let prepare_input_duration = prepare_input_start.elapsed();
-    let prepare_component_start = Instant::now();
+/// Compiles the component and checks that the host can satisfy all of its imports
+/// and that it exports an entry point, without running it.
+pub(super) fn verify_component_wasm(wasm_bytes: &[u8], engine: &Engine) -> anyhow::Result<()> {
+    let component = wasmtime::component::Component::new(engine, wasm_bytes)?;
+    verify_component(&component, engine)
+}
+
+/// Checks that the host can satisfy all of the compiled component's imports
+/// and that it exports an entry point.
+pub(super) fn verify_component(
+    component: &wasmtime::component::Component,
+    engine: &Engine,
+) -> anyhow::Result<()> {
+    let has_entry_point = component
+        .component_type()
+        .exports(engine)
+        .any(|(name, _)| ENTRY_POINT_EXPORT_NAMES.contains(&name));
+
+    if !has_entry_point {
+        anyhow::bail!(
+            "Component does not export a \"{RUN_EXPORT_NAME}\", \"{RUN_TYPED_EXPORT_NAME}\" or \"{RUN_STREAMING_EXPORT_NAME}\" function."
+        );
+    }
+
+    let linker = create_linker::<'static, 'static, 'static>(engine)?;
+    linker.instantiate_pre(component).context(
+        "The host cannot satisfy the component's imports. \
+        The component may have been built against an older version of the Slipway WIT, \
+        and need rebuilding against the WIT output by `slipway wit`.",
+    )?;
+
+    Ok(())
+}
+
+/// Compiles or deserializes the component, unless it is already compiled.
+pub(super) fn load_component(
+    wasm_data: WasmData,
+    engine: &Engine,
+) -> anyhow::Result<wasmtime::component::Component> {
+    Ok(match wasm_data {
+        WasmData::Wasm(wasm_bytes) => wasmtime::component::Component::new(engine, &*wasm_bytes)?,
+        WasmData::Aot(aot_bytes) => unsafe {
+            wasmtime::component::Component::deserialize(engine, &aot_bytes)?
+        },
+        WasmData::Component(component) => component,
+    })
+}
 
+fn create_linker<'call, 'rig, 'runners>(
+    engine: &Engine,
+) -> anyhow::Result<wasmtime::component::Linker<SlipwayHost<'call, 'rig, 'runners>>> {
     // Create a linker.
     let mut linker = wasmtime::component::Linker::new(engine);
 
@@ -34,11 +125,27 @@ pub async fn run_component_wasm(
     Slipway::add_to_linker(&mut linker, |state: &mut SlipwayHost| state)?;
     wasmtime_wasi::add_to_linker_async(&mut linker)?;
 
+    Ok(linker)
+}
+
+/// Runs the component once for each input, reusing a single instance of the component.
+///
+/// The time taken to prepare the component is attributed to the first result.
+pub async fn run_component_wasm_batch(
+    inputs: &[serde_json::Value],
+    wasm_data: WasmData,
+    engine: &Engine,
+    execution_context: &ComponentExecutionContext<'_, '_, '_>,
+) -> Result<Vec<RunComponentResult>, RunComponentError> {
+    let prepare_component_start = Instant::now();
+
+    let linker = create_linker(engine)?;
+
     // Create a WASI context, including stdin and stdout pipes
     let stdout = OutputObserverStream::new(OutputObserverType::Stdout);
     let stderr = OutputObserverStream::new(OutputObserverType::Stderr);
 
-    let environment = &execution_context.rig_session_options.environment;
+    let environment = &execution_context.rig_session_options().environment;
     let wasi_ctx = WasiCtxBuilder::new()
         .stdout(stdout)
         .stderr(stderr)
@@ -51,51 +158,158 @@ pub async fn run_component_wasm(
     let mut store = Store::new(engine, SlipwayHost::new(execution_context, wasi_ctx));
 
     // Create the component from raw bytes.
-    let component = match wasm_data {
-        WasmData::Wasm(wasm_bytes) => wasmtime::component::Component::new(engine, &*wasm_bytes)?,
-        WasmData::Aot(aot_bytes) => unsafe {
-            wasmtime::component::Component::deserialize(engine, &aot_bytes)?
-        },
+    let component = load_component(wasm_data, engine)?;
+
+    // Components built against the `slipway-typed` or `slipway-streaming` worlds
+    // export `run-typed` or `run-streaming` instead of `run`.
+    let export_names = component
+        .component_type()
+        .exports(engine)
+        .map(|(name, _)| name.to_string())
+        .collect::<Vec<_>>();
+    let has_export = |export_name: &str| export_names.iter().any(|name| name == export_name);
+
+    let instance = linker.instantiate_async(&mut store, &component).await?;
+
+    let entry_point = if has_export(RUN_TYPED_EXPORT_NAME) {
+        EntryPoint::Typed(instance.get_typed_func(&mut store, RUN_TYPED_EXPORT_NAME)?)
+    } else if has_export(RUN_STREAMING_EXPORT_NAME) {
+        EntryPoint::Streaming(instance.get_typed_func(&mut store, RUN_STREAMING_EXPORT_NAME)?)
+    } else {
+        // Create the SlipwayComponent instance.
+        EntryPoint::Json(Slipway::new(&mut store, &instance)?)
     };
 
-    // Create the SlipwayComponent instance.
-    let slipway_component = Slipway::instantiate_async(&mut store, &component, &linker).await?;
+    let mut prepare_component_duration = prepare_component_start.elapsed();
+
+    let max_output_bytes = execution_context.rig_session_options().max_output_bytes;
+
+    let mut results = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let prepare_input_start = Instant::now();
+
+        let prepared_input = match entry_point {
+            EntryPoint::Typed(_) => PreparedInput::Typed(json_to_nodes(input)),
+            EntryPoint::Json(_) | EntryPoint::Streaming(_) => {
+                // Serialize the input JSON to a vector of bytes
+                PreparedInput::Json(
+                    serde_json::to_string(input)
+                        .map_err(|source| RunComponentError::SerializeInputFailed { source })?,
+                )
+            }
+        };
 
-    let prepare_component_duration = prepare_component_start.elapsed();
+        let prepare_input_duration = prepare_input_start.elapsed();
 
-    // Call the function
-    let call_start = Instant::now();
-    let call_result = slipway_component.call_run(&mut store, &input_string).await;
-    let call_duration = call_start.elapsed();
+        // Call the function
+        let call_start = Instant::now();
+        let call_result = match (&entry_point, prepared_input) {
+            (EntryPoint::Json(slipway_component), PreparedInput::Json(input_string)) => {
+                slipway_component
+                    .call_run(&mut store, &input_string)
+                    .await
+                    .map(|r| r.map(CallOutput::Json))
+            }
+            (EntryPoint::Typed(run_typed), PreparedInput::Typed(input_nodes)) => {
+                match run_typed.call_async(&mut store, (input_nodes,)).await {
+                    Ok((result,)) => run_typed
+                        .post_return_async(&mut store)
+                        .await
+                        .map(|_| result.map(CallOutput::Typed)),
+                    Err(e) => Err(e),
+                }
+            }
+            (EntryPoint::Streaming(run_streaming), PreparedInput::Json(input_string)) => {
+                store.data_mut().begin_streamed_output();
+                let call_result = match run_streaming.call_async(&mut store, (input_string,)).await
+                {
+                    Ok((result,)) => run_streaming
+                        .post_return_async(&mut store)
+                        .await
+                        .map(|_| result),
+                    Err(e) => Err(e),
+                };
+                let streamed_output = store.data_mut().end_streamed_output().await;
+                call_result.map(|r| r.map(|()| CallOutput::Streamed(streamed_output)))
+            }
+            _ => unreachable!("input should be prepared for the component entry point"),
+        };
+        let call_duration = call_start.elapsed();
 
-    let process_output_start = Instant::now();
+        let process_output_start = Instant::now();
 
-    // Process the result.
-    match call_result {
-        Err(e) => Err(RunComponentError::RunCallFailed { source: e }),
-        Ok(r) => match r {
+        // Process the result.
+        let output = match call_result {
+            Err(e) => return Err(RunComponentError::RunCallFailed { source: e }),
             // The WASM component returned an error from it's `run` function.
-            Err(error) => Err(RunComponentError::RunCallReturnedError {
-                message: error.message,
-                inner: error.inner,
-            }),
-            Ok(json_string) => {
-                // Deserialize the output JSON
-                let output = serde_json::from_str(&json_string)
-                    .map_err(|source| RunComponentError::DeserializeOutputFailed { source })?;
-
-                let process_output_duration = process_output_start.elapsed();
-
-                Ok(RunComponentResult {
-                    output,
-                    metadata: RunMetadata {
-                        prepare_input_duration,
-                        prepare_component_duration,
-                        call_duration,
-                        process_output_duration,
-                    },
-                })
+            Ok(Err(error)) => {
+                return Err(RunComponentError::RunCallReturnedError {
+                    message: error.message,
+                    inner: error.inner,
+                });
             }
-        },
+            // Deserialize the output JSON
+            Ok(Ok(CallOutput::Json(json_string))) => {
+                parse_json_output(&json_string, json_string.len(), max_output_bytes)?
+            }
+            Ok(Ok(CallOutput::Streamed(streamed_output))) => {
+                ensure_output_within_limit(streamed_output.byte_count, max_output_bytes)?;
+                streamed_output
+                    .result
+                    .map_err(|source| RunComponentError::DeserializeOutputFailed { source })?
+            }
+            Ok(Ok(CallOutput::Typed(output_nodes))) => {
+                ensure_output_within_limit(min_serialized_len(&output_nodes), max_output_bytes)?;
+                let output = nodes_to_json(&output_nodes).map_err(|message| {
+                    RunComponentError::DeserializeOutputFailed {
+                        source: serde::de::Error::custom(message),
+                    }
+                })?;
+                ensure_output_value_within_limit(&output, max_output_bytes)?;
+                output
+            }
+        };
+
+        let process_output_duration = process_output_start.elapsed();
+
+        results.push(RunComponentResult {
+            output,
+            metadata: RunMetadata {
+                prepare_input_duration,
+                prepare_component_duration: std::mem::take(&mut prepare_component_duration),
+                call_duration,
+                process_output_duration,
+                nested: None,
+                memoized_from: None,
+            },
+        });
     }
+
+    Ok(results)
+}
+
+/// The entry point exported by the component.
+enum EntryPoint {
+    Json(Slipway),
+    Typed(RunTypedFunc),
+    Streaming(RunStreamingFunc),
+}
+
+/// The raw output returned or written by whichever entry point the component exports.
+enum CallOutput {
+    Json(String),
+    Typed(Vec<JsonNode>),
+    Streamed(StreamedOutput),
+}
+
+/// Parses the JSON output of the component, after checking its size so that
+/// excessively large outputs are rejected before they are parsed.
+fn parse_json_output(
+    json: &str,
+    output_bytes: usize,
+    max_output_bytes: Option<usize>,
+) -> Result<serde_json::Value, RunComponentError> {
+    ensure_output_within_limit(output_bytes, max_output_bytes)?;
+    serde_json::from_str(json)
+        .map_err(|source| RunComponentError::DeserializeOutputFailed { source })
 }
@@ -0,0 +1,146 @@
+use std::io::{Cursor, Read};
+
+use tokio::{sync::mpsc, task::JoinHandle};
+
+/// The number of chunks which can be waiting to be parsed before the component
+/// waits for the parser to catch up.
+const CHUNK_BUFFER_SIZE: usize = 16;
+
+/// Parses the output written by a component using the `run-streaming` entry point
+/// as each chunk is written, so the host never holds the complete output JSON.
+pub struct StreamedOutputParser {
+    sender: Option<mpsc::Sender<String>>,
+    parser: JoinHandle<Result<serde_json::Value, serde_json::Error>>,
+    max_output_bytes: Option<usize>,
+    byte_count: usize,
+}
+
+/// The output written by a component using the `run-streaming` entry point.
+pub struct StreamedOutput {
+    /// The parsed output. If the output exceeded the size limit this is the
+    /// result of parsing the output up to that point.
+    pub result: Result<serde_json::Value, serde_json::Error>,
+
+    /// The total size of the chunks written, including any written after the
+    /// size limit was exceeded.
+    pub byte_count: usize,
+}
+
+impl StreamedOutputParser {
+    /// Starts parsing output on the blocking thread pool.
+    pub fn start(max_output_bytes: Option<usize>) -> Self {
+        let (sender, receiver) = mpsc::channel(CHUNK_BUFFER_SIZE);
+        let parser = tokio::task::spawn_blocking(move || {
+            serde_json::from_reader(ChunkReader {
+                receiver,
+                current: Cursor::default(),
+            })
+        });
+
+        Self {
+            sender: Some(sender),
+            parser,
+            max_output_bytes,
+            byte_count: 0,
+        }
+    }
+
+    /// Passes a chunk of output to the parser.
+    ///
+    /// Once the output exceeds the size limit, or the parser has failed, further
+    /// chunks are discarded as the output will be rejected.
+    pub async fn write(&mut self, chunk: String) {
+        self.byte_count += chunk.len();
+
+        if self
+            .max_output_bytes
+            .is_some_and(|max| self.byte_count > max)
+        {
+            self.sender = None;
+        }
+
+        if let Some(sender) = &self.sender {
+            if sender.send(chunk).await.is_err() {
+                // The parser has already failed.
+                self.sender = None;
+            }
+        }
+    }
+
+    /// Waits for the parser to finish, returning the parsed output.
+    pub async fn finish(mut self) -> StreamedOutput {
+        // Closing the channel ends the output.
+        self.sender = None;
+
+        let result = self
+            .parser
+            .await
+            .unwrap_or_else(|e| Err(serde::de::Error::custom(e)));
+
+        StreamedOutput {
+            result,
+            byte_count: self.byte_count,
+        }
+    }
+}
+
+/// Reads the chunks sent to the parser as a single stream.
+struct ChunkReader {
+    receiver: mpsc::Receiver<String>,
+    current: Cursor<Vec<u8>>,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let read = self.current.read(buf)?;
+            if read > 0 || buf.is_empty() {
+                return Ok(read);
+            }
+
+            match self.receiver.blocking_recv() {
+                Some(chunk) => self.current = Cursor::new(chunk.into_bytes()),
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_macros::slipway_test_async;
+    use serde_json::json;
+
+    use super::*;
+
+    async fn parse(chunks: &[&str], max_output_bytes: Option<usize>) -> StreamedOutput {
+        let mut parser = StreamedOutputParser::start(max_output_bytes);
+        for chunk in chunks {
+            parser.write(chunk.to_string()).await;
+        }
+        parser.finish().await
+    }
+
+    #[slipway_test_async]
+    async fn it_should_parse_output_split_across_chunks() {
+        let output = parse(&["{\"ite", "ms\":[1,", "2", ",3]}"], None).await;
+
+        assert_eq!(output.result.unwrap(), json!({ "items": [1, 2, 3] }));
+        assert_eq!(output.byte_count, 16);
+    }
+
+    #[slipway_test_async]
+    async fn it_should_fail_for_invalid_output() {
+        assert!(parse(&["{\"items\":", "[1,"], None).await.result.is_err());
+        assert!(parse(&["{}", "{}"], None).await.result.is_err());
+        assert!(parse(&[], None).await.result.is_err());
+    }
+
+    #[slipway_test_async]
+    async fn it_should_stop_parsing_when_output_exceeds_limit() {
+        let output = parse(&["[1,", "2,", "3]"], Some(5)).await;
+
+        assert!(output.result.is_err());
+        assert_eq!(output.byte_count, 7);
+    }
+}
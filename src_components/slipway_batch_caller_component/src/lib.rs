@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+wit_bindgen::generate!({
+    world: "slipway",
+});
+
+struct Component;
+
+impl Guest for Component {
+    fn run(input: String) -> Result<String, ComponentError> {
+        let input: Input = serde_json::from_str(&input).map_err(|e| ComponentError {
+            message: format!("{e:#?}"),
+            inner: vec![],
+        })?;
+
+        let callout_inputs = input
+            .values
+            .iter()
+            .map(|value| {
+                serde_json::to_string(&CalloutInput { value: *value })
+                    .expect("Callout input should be serializable")
+            })
+            .collect::<Vec<_>>();
+
+        let results = slipway_host::run_batch(&input.handle, &callout_inputs)?
+            .iter()
+            .map(|result| {
+                serde_json::from_str::<serde_json::Value>(result).map_err(|e| ComponentError {
+                    message: format!("{e:#?}"),
+                    inner: vec![],
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let output = Output { results };
+        Ok(serde_json::to_string(&output).expect("Result should be serializable"))
+    }
+}
+
+export!(Component);
+
+#[derive(Deserialize)]
+struct Input {
+    handle: String,
+    values: Vec<i32>,
+}
+
+#[derive(Serialize)]
+struct CalloutInput {
+    value: i32,
+}
+
+#[derive(Serialize)]
+struct Output {
+    results: Vec<serde_json::Value>,
+}
@@ -0,0 +1,42 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+wit_bindgen::generate!({
+    world: "slipway",
+});
+
+// Counts the runs made by this instance of the component, so callers can tell
+// whether a batch of inputs was run using a single instance.
+static RUN_COUNT: AtomicU32 = AtomicU32::new(0);
+
+struct Component;
+
+impl Guest for Component {
+    fn run(input: String) -> Result<String, ComponentError> {
+        let input: Input = serde_json::from_str(&input).map_err(|e| ComponentError {
+            message: format!("{e:#?}"),
+            inner: vec![],
+        })?;
+
+        let output = Output {
+            value: input.value + 1,
+            run_count: RUN_COUNT.fetch_add(1, Ordering::Relaxed) + 1,
+        };
+
+        Ok(serde_json::to_string(&output).expect("Result should be serializable"))
+    }
+}
+
+export!(Component);
+
+#[derive(Deserialize)]
+struct Input {
+    value: i32,
+}
+
+#[derive(Serialize)]
+struct Output {
+    value: i32,
+    run_count: u32,
+}
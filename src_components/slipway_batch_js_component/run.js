@@ -0,0 +1,7 @@
+export function run(input) {
+  globalThis.run_count = (globalThis.run_count ?? 0) + 1;
+  return {
+    value: input.value + 1,
+    run_count: globalThis.run_count
+  };
+}
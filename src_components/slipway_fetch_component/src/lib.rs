@@ -9,6 +9,8 @@ wit_bindgen::generate!({
 
 struct Component;
 
+const DEFAULT_TIMEOUT_MS: u32 = 1000;
+
 impl Guest for Component {
     fn run(input: String) -> Result<String, ComponentError> {
         let input: Input = serde_json::from_str(&input).map_err(|e| ComponentError {
@@ -29,13 +31,16 @@ fn run_inner(input: Input) -> Result<String, ComponentError> {
         headers,
         body,
         response_type,
+        timeout_ms,
+        max_redirects,
     } = input;
 
     let request_options = slipway_host::RequestOptions {
         headers: Some(headers.into_iter().collect()),
         method: Some(method),
         body: Some(body.into_bytes()),
-        timeout_ms: Some(1000),
+        timeout_ms: Some(timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)),
+        max_redirects,
     };
 
     fn map_err_to_output(e: RequestError) -> Result<Output, ComponentError> {
@@ -84,6 +89,12 @@ struct Input {
     headers: HashMap<String, String>,
     body: String,
     response_type: DataResultType,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout_ms: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_redirects: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -1,10 +1,10 @@
 export async function run(input) {
-  const { url, method, headers, body, response_type } = input;
+  const { url, method, headers, body, response_type, timeout_ms = 1000 } = input;
   const requestOptions = {
     headers: Object.entries(headers),
     method,
     body,
-    timeout_ms: 1000,
+    timeout_ms,
   };
 
   function mapErrToOutput(e) {
@@ -38,6 +38,13 @@ export async function run(input) {
         status_code: res.status_code,
         body_bin: Array.from(res.body),
       };
+    } else if (response_type === "fetch") {
+      const res = await fetch(url, requestOptions);
+      return {
+        status_code: res.status,
+        body_text: await res.text(),
+        content_type: res.headers.get("Content-Type") ?? undefined,
+      };
     } else {
       throw new Error(`Unsupported response_type: ${response_type}`);
     }
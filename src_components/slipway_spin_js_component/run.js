@@ -0,0 +1,7 @@
+export function run(input) {
+  while (true) {
+    if (input.call_host) {
+      slipway_host.log_trace("spinning");
+    }
+  }
+}
\ No newline at end of file
@@ -0,0 +1,37 @@
+use serde::Deserialize;
+
+wit_bindgen::generate!({
+    world: "slipway-streaming",
+});
+
+struct Component;
+
+impl Guest for Component {
+    fn run_streaming(input: String) -> Result<(), ComponentError> {
+        let input: Input = serde_json::from_str(&input).map_err(|e| ComponentError {
+            message: format!("{e:#?}"),
+            inner: vec![],
+        })?;
+
+        // Write each item as a separate chunk, splitting the surrounding JSON
+        // across chunks so the host must join them to parse the output.
+        slipway_host::write_output("{\"ite");
+        slipway_host::write_output("ms\":[");
+        for i in 0..input.count {
+            if i > 0 {
+                slipway_host::write_output(",");
+            }
+            slipway_host::write_output(&i.to_string());
+        }
+        slipway_host::write_output("]}");
+
+        Ok(())
+    }
+}
+
+export!(Component);
+
+#[derive(Deserialize)]
+struct Input {
+    count: u32,
+}
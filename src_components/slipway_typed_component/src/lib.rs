@@ -0,0 +1,29 @@
+wit_bindgen::generate!({
+    world: "slipway-typed",
+});
+
+struct Component;
+
+impl Guest for Component {
+    fn run_typed(input: Vec<JsonNode>) -> Result<Vec<JsonNode>, ComponentError> {
+        if input.is_empty() {
+            return Err(ComponentError {
+                message: "Expected at least one JSON node.".to_string(),
+                inner: vec![],
+            });
+        }
+
+        // Arrays and objects refer to their children by index, so the structure
+        // is preserved by replacing each node in place.
+        Ok(input
+            .into_iter()
+            .map(|node| match node {
+                JsonNode::Integer(value) => JsonNode::Integer(value + 1),
+                JsonNode::Unsigned(value) => JsonNode::Unsigned(value + 1),
+                node => node,
+            })
+            .collect())
+    }
+}
+
+export!(Component);